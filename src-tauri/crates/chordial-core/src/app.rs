@@ -1,7 +1,7 @@
 //! 应用上下文 — server 层的统一入口。
 //!
 //! `AppContext` 持有整个音乐系统的所有组件实例（配置 / 存储 / 缓存 / 音乐库 /
-//! 来源管理器 / 注册器 / 本地来源），替代原 `commands.rs` 中的 `LazyLock` /
+//! 来源管理器 / 注册器 / 本地来源 / 播放设置），替代原 `commands.rs` 中的 `LazyLock` /
 //! `OnceLock` 全局单例。
 //!
 //! # 设计目标
@@ -20,18 +20,55 @@
 //! let count = ctx.library.song_count();
 //! ```
 
+use crate::module::analysis::content_hash::{self, HashStatus};
+use crate::module::analysis::{AnalysisJob, AnalysisManager, AnalysisPriority, AudioInput};
+use crate::module::artwork::{self, thumbnail};
+use crate::module::cache::location::{self, CacheLocation, MigrationReport};
 use crate::module::cache::store::CacheStore;
+use crate::module::cancel::{CancellationRegistry, CancellationToken};
 use crate::module::config::store::ConfigStore;
+use crate::module::events::{AppEvent, EventBus};
+use crate::module::lyrics::{LocalFileLyricsProvider, LyricsRegistry};
+use crate::module::metadata::{MetadataResolver, SongTagsProvider};
+use crate::module::music_library::books;
+use crate::module::music_library::diff::LibraryDiff;
+use crate::module::music_library::edits::FieldValues;
+use crate::module::music_library::grouping::GROUP_BY_CONFIG_KEY;
 use crate::module::music_library::library::MusicLibrary;
+use crate::module::music_library::localize::DISPLAY_LANGUAGE_CONFIG_KEY;
+use crate::module::music_library::stats::PlayStats;
 use crate::module::music_localSource;
+use crate::module::music_localSource::ogg_chain;
+use crate::module::music_localSource::scan_job::{self, ScanJob, ScanProgress, ScanStatus};
+use crate::module::music_localSource::scanner::ScanOptions;
 use crate::module::music_localSource::source::LocalMusicSource;
 use crate::module::music_source::manager::SourceManager;
+use crate::module::music_source::media_cache::{self, MediaCache};
+use crate::module::music_source::pcm_cache::PcmCache;
 use crate::module::music_source::registrar::{SourceCleanup, SourceRegistrar};
+use crate::module::music_source::resource::{self, WriteBackReport};
+use crate::module::music_source::types::SourceId;
 use crate::module::p2p::P2pManager;
 use crate::module::perf;
+use crate::module::platform::{self, PlatformPath};
+use crate::module::playback::{
+    live_queue, now_playing, power, preview, queue, ContentType, CrossfadeCancel, CrossfadeKeep, CrossfadeStatus, CrossfadeTracker,
+    DecodeStats, EndOfQueueBehavior, LiveQueue, LyricsInfo, NowPlayingBundle, PcmCacheSettings, PlaybackManager, PlaybackSettings,
+    PlayerReport, Preloader, QueueStep, QueueTransition, QueueView, RepeatMode, TransitionLog, TransitionRecord,
+    TransitionReport,
+};
+use crate::module::power::PowerMonitor;
+use crate::module::readiness::{Readiness, Subsystem};
+use crate::module::safe_mode;
 use crate::module::storage::persistent::PersistentStore;
-use std::path::PathBuf;
+use crate::module::storage::snapshot;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// 音乐库写回窗口：窗口内的多次保存请求合并为一次写盘。
+const LIBRARY_WRITE_BEHIND_INTERVAL: Duration = Duration::from_secs(2);
 
 /// 整个音乐系统的运行时上下文。
 ///
@@ -53,6 +90,40 @@ pub struct AppContext {
     pub local_source: Arc<LocalMusicSource>,
     /// P2P 资源共享管理器。
     pub p2p: Arc<P2pManager>,
+    /// 播放设置管理器。
+    pub playback: Arc<PlaybackManager>,
+    /// 远程歌曲的队列预加载。
+    pub preload: Arc<Preloader>,
+    /// 正在播放的队列。
+    pub queue: Arc<LiveQueue>,
+    /// 切歌过渡日志。
+    pub transitions: Arc<TransitionLog>,
+    /// 进行中的交叉淡化。
+    pub crossfade: Arc<CrossfadeTracker>,
+    /// 专辑网格的封面缩略图预热。
+    pub covers: Arc<thumbnail::Prewarmer>,
+    /// 后台重扫的状态（见 [`start_scan`](Self::start_scan)）。
+    pub scans: Arc<ScanJob>,
+    /// 音频分析管理器（技术信息等）。
+    pub analysis: Arc<AnalysisManager>,
+    /// 电源策略（电池供电时降低扫描 / 分析并行度）。
+    pub power: Arc<PowerMonitor>,
+    /// 歌词提供方注册表。
+    pub lyrics: Arc<LyricsRegistry>,
+    /// 专辑 / 艺人元数据补全。
+    pub metadata: Arc<MetadataResolver>,
+    /// 应用事件总线 — 子系统通过它互相通知，front 层订阅后转发给前端。
+    pub events: Arc<EventBus>,
+    /// 进行中长任务的取消令牌（按前端提供的 task_id 登记）。
+    pub tasks: Arc<CancellationRegistry>,
+    /// 启动预热的子系统就绪状态（见 [`warm_up`](Self::warm_up)）。
+    pub readiness: Arc<Readiness>,
+    /// 是否以安全模式启动（见 [`safe_mode`]）。
+    pub safe_mode: bool,
+    /// 数据目录（默认缓存根目录）。
+    data_dir: PathBuf,
+    /// 当前缓存根目录；迁移期间持锁，同一时间只有一次迁移。
+    cache_root: Mutex<PathBuf>,
 }
 
 impl AppContext {
@@ -62,18 +133,46 @@ impl AppContext {
     /// - `data_dir/config.json`
     /// - `data_dir/storage.json`
     /// - `data_dir/music_library.json`
+    /// - `data_dir/music_library.db`（乐库索引库）
     /// - `data_dir/source_registry.json`
     /// - `data_dir/local_source_folders.json`
-    /// - `data_dir/cache_blobs/`（Blob 缓存目录）
+    /// - `data_dir/analysis.json`
+    /// - `data_dir/metadata_provenance.json`
+    /// - `cache_root/cache_blobs/`（Blob 缓存目录）
+    /// - `cache_root/media_cache/`（远程音频缓存目录）
+    /// - `cache_root/pcm_cache/`（常播短曲目的解码 PCM 缓存目录）
+    /// - `data_dir/transitions.jsonl`（切歌过渡日志）
+    ///
+    /// `cache_root` 默认为 `data_dir`，可由配置项 [`CACHE_DIR_CONFIG_KEY`](location::CACHE_DIR_CONFIG_KEY)
+    /// 改到其他位置（见 [`relocate_cache`](Self::relocate_cache)）。
     pub fn new(data_dir: PathBuf) -> Result<Self, String> {
+        Self::open(data_dir, false)
+    }
+
+    /// 同 [`new`](Self::new)；`safe_mode` 为 true 时不启动后台线程，磁盘缓存只读。
+    pub fn open(data_dir: PathBuf, safe_mode: bool) -> Result<Self, String> {
         let _scope = perf::scope("app.new");
+        if safe_mode {
+            eprintln!("[chordial] 以安全模式启动：不启动文件监听与后台任务，缓存只读");
+        }
         // ── 配置 / 存储 / 缓存 ──
         let config = Arc::new(ConfigStore::new(data_dir.join("config.json")));
         let store = Arc::new(PersistentStore::new(data_dir.join("storage.json")));
         let cache = Arc::new(CacheStore::new());
+        cache.set_blob_read_only(safe_mode);
 
         // ── 音乐库 + 来源系统 ──
         let library = Arc::new(MusicLibrary::new(data_dir.join("music_library.json")));
+        library.set_display_language(config.get::<String>(DISPLAY_LANGUAGE_CONFIG_KEY));
+        library.set_group_by(config.get(GROUP_BY_CONFIG_KEY).unwrap_or_default());
+        // 启动扫描会改写库文件，先留下当天的快照
+        let retain = config.get::<usize>(snapshot::RETAIN_CONFIG_KEY).unwrap_or(snapshot::DEFAULT_RETAIN);
+        if let Err(e) = library
+            .set_snapshot_retain(retain)
+            .and_then(|_| library.snapshot_daily())
+        {
+            eprintln!("[chordial] 音乐库快照失败: {}", e);
+        }
         let manager = Arc::new(SourceManager::new(data_dir.join("source_registry.json")));
 
         // Arc<MusicLibrary> → Arc<dyn SourceCleanup>（级联清理回调）
@@ -81,16 +180,34 @@ impl AppContext {
         let registrar = Arc::new(SourceRegistrar::new(manager.clone(), cleanup));
 
         // ── Blob 缓存磁盘目录 ──
-        if let Err(e) = cache.enable_blob_storage(data_dir.join("cache_blobs")) {
+        let cache_root = config
+            .get::<String>(location::CACHE_DIR_CONFIG_KEY)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| data_dir.clone());
+        if let Err(e) = cache.enable_blob_storage(cache_root.join(location::BLOB_SUBDIR)) {
             eprintln!("[chordial] 启用 Blob 缓存失败: {}", e);
         }
+        match MediaCache::new(cache_root.join(location::MEDIA_SUBDIR), media_cache::DEFAULT_MAX_BYTES) {
+            Ok(media) => {
+                media.set_read_only(safe_mode);
+                registrar.set_media_cache(Arc::new(media));
+            }
+            Err(e) => eprintln!("[chordial] 启用媒体缓存失败: {}", e),
+        }
+
+        // ── 事件总线 + 电源策略（需在各子系统之前创建）──
+        let events = Arc::new(EventBus::new());
+        let power = Arc::new(PowerMonitor::new(config.clone()));
 
         // ── 本地音乐来源（must-source，自动初始化）──
+        ogg_chain::set_chain_tags(config.get(ogg_chain::CHAIN_TAGS_CONFIG_KEY).unwrap_or_default());
         let local_folder_store_path = data_dir.join("local_source_folders.json");
         let local_source = music_localSource::init_local_source(
             local_folder_store_path,
             library.clone(),
             &registrar,
+            events.clone(),
+            power.clone(),
         )
         .map_err(|e| {
             eprintln!("[chordial] 初始化本地音乐来源失败: {}", e);
@@ -98,7 +215,50 @@ impl AppContext {
         })?;
 
         // ── P2P 资源共享管理器 ──
-        let p2p = P2pManager::new(library.clone(), registrar.clone(), config.clone());
+        let p2p = P2pManager::new(
+            library.clone(),
+            registrar.clone(),
+            config.clone(),
+            events.clone(),
+        );
+
+        // ── 播放设置 ──
+        let playback = Arc::new(PlaybackManager::new(config.clone()));
+        match PcmCache::new(cache_root.join(location::PCM_SUBDIR), playback.settings().pcm_cache.max_bytes) {
+            Ok(pcm) => {
+                pcm.set_read_only(safe_mode);
+                registrar.set_pcm_cache(Arc::new(pcm));
+            }
+            Err(e) => eprintln!("[chordial] 启用 PCM 缓存失败: {}", e),
+        }
+        let preload = Preloader::new(library.clone(), registrar.clone(), playback.clone());
+        if !safe_mode {
+            preload.start();
+            power::spawn_suspend_watcher(playback.clone(), &events);
+        }
+        let transitions = Arc::new(TransitionLog::new(data_dir.join("transitions.jsonl")));
+
+        // ── 音频分析 ──
+        let analysis = Arc::new(AnalysisManager::new(data_dir.join("analysis.json"), power.clone()));
+        analysis.set_content_hash_keys(config.get::<bool>(content_hash::ENABLED_CONFIG_KEY).unwrap_or(false));
+
+        // ── 歌词提供方 ──
+        let lyrics = Arc::new(LyricsRegistry::new(config.clone()));
+        lyrics.register(Arc::new(LocalFileLyricsProvider));
+
+        // ── 元数据补全 ──
+        let metadata = Arc::new(MetadataResolver::new(
+            library.clone(),
+            data_dir.join("metadata_provenance.json"),
+        ));
+        metadata.register(Arc::new(SongTagsProvider::new(library.clone())));
+
+        // ── 音乐库写回（快照已拍好，此后包括启动扫描在内的保存请求合并写入）──
+        library.enable_write_behind(LIBRARY_WRITE_BEHIND_INTERVAL);
+
+        // ── 就绪状态：配置与播放设置已加载，来源 / 音乐库等 warm_up 完成 ──
+        let readiness = Arc::new(Readiness::new(events.clone()));
+        readiness.mark_ready(Subsystem::Settings);
 
         Ok(Self {
             config,
@@ -109,14 +269,686 @@ impl AppContext {
             registrar,
             local_source,
             p2p,
+            playback,
+            preload,
+            queue: Arc::new(LiveQueue::new()),
+            transitions,
+            crossfade: Arc::new(CrossfadeTracker::new()),
+            covers: Arc::new(thumbnail::Prewarmer::new()),
+            scans: Arc::new(ScanJob::new()),
+            analysis,
+            power,
+            lyrics,
+            metadata,
+            events,
+            tasks: Arc::new(CancellationRegistry::new()),
+            readiness,
+            safe_mode,
+            data_dir,
+            cache_root: Mutex::new(cache_root),
+        })
+    }
+
+    /// 启动预热：在后台线程增量扫描本地文件夹、启动文件监听并建立搜索索引。
+    ///
+    /// 完成后依次标记 [`Subsystem::Sources`]、[`Subsystem::Library`] 就绪；
+    /// 扫描失败时两者都标记为失败，库内已恢复的数据照常可用。
+    /// 应在事件订阅（前端桥接）建立之后调用，否则就绪事件会被丢弃。
+    ///
+    /// 安全模式下跳过扫描与文件监听，只为磁盘上已保存的音乐库建立搜索索引。
+    pub fn warm_up(self: &Arc<Self>) {
+        let ctx = self.clone();
+        let spawned = std::thread::Builder::new()
+            .name("app-warm-up".into())
+            .spawn(move || {
+                let _scope = perf::scope("app.warm_up");
+                if ctx.safe_mode {
+                    ctx.readiness.mark_ready(Subsystem::Sources);
+                    ctx.library.warm_search_index();
+                    ctx.readiness.mark_ready(Subsystem::Library);
+                    ctx.events.publish(AppEvent::LibraryChanged);
+                    return;
+                }
+                match music_localSource::startup_scan(&ctx.local_source) {
+                    Ok(new_count) => {
+                        eprintln!("[chordial] 启动扫描完成，新索引 {} 个文件", new_count);
+                        ctx.readiness.mark_ready(Subsystem::Sources);
+                        ctx.library.warm_search_index();
+                        ctx.readiness.mark_ready(Subsystem::Library);
+                        // 预热前收到 not_ready 的列表借库变更事件重新拉取
+                        ctx.events.publish(AppEvent::LibraryChanged);
+                        if ctx.config.get::<bool>(content_hash::ENABLED_CONFIG_KEY).unwrap_or(false) {
+                            if let Err(e) = ctx.spawn_content_hash_scan("content_hash_scan", None) {
+                                eprintln!("[chordial] 启动内容哈希任务失败: {}", e);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        ctx.readiness.mark_failed(Subsystem::Sources, e.clone());
+                        ctx.readiness.mark_failed(Subsystem::Library, e);
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            let error = format!("启动预热线程失败: {}", e);
+            self.readiness.mark_failed(Subsystem::Sources, error.clone());
+            self.readiness.mark_failed(Subsystem::Library, error);
+        }
+    }
+
+    /// 退出前调用：把写回窗口内尚未落盘的修改立即写入磁盘。
+    pub fn shutdown(&self) {
+        if let Err(e) = self.library.flush() {
+            eprintln!("[chordial] 退出时保存音乐库失败: {}", e);
+        }
+        if let Err(e) = self.config.flush() {
+            eprintln!("[chordial] 退出时保存配置失败: {}", e);
+        }
+    }
+
+    /// 本地元数据编辑完成后，把修改同步到这些歌曲的可写回来源（未设为只读的自建后端等）。
+    ///
+    /// `song_ids` 为编辑后的库内歌曲 ID；写回失败只记录日志，不影响本地编辑。
+    pub fn sync_song_edits(&self, song_ids: &[Option<String>], changes: &FieldValues) -> WriteBackReport {
+        let source_ids: Vec<SourceId> = song_ids
+            .iter()
+            .flatten()
+            .filter_map(|id| self.library.get_song(id))
+            .flat_map(|song| song.source_ids)
+            .collect();
+        let report = resource::sync_song_tags(&self.registrar, &source_ids, changes);
+        for (source, e) in &report.failed {
+            eprintln!("[chordial] 写回来源 '{}' 失败: {}", source, e);
+        }
+        report
+    }
+
+    /// 执行一次改变库内容的批量操作（重扫、增删文件夹），落盘后返回前后差异。
+    ///
+    /// 有变化时发布 [`AppEvent::LibraryDiff`]；`op` 失败时不落盘也不发布。
+    pub fn apply_library_change<T>(
+        &self,
+        op: impl FnOnce() -> Result<T, String>,
+    ) -> Result<(T, LibraryDiff), String> {
+        let before = self.library.snapshot();
+        let out = op()?;
+        self.library.save()?;
+        let diff = self.library.diff_since(&before);
+        if !diff.is_empty() {
+            self.events.publish(AppEvent::LibraryDiff(diff.clone()));
+        }
+        Ok((out, diff))
+    }
+
+    /// 在后台线程逐个文件夹重扫，立即返回扫描状态；已有扫描在运行时直接返回其状态。
+    ///
+    /// 每个文件夹单独落盘并发布库差异，取消或出错时已完成的文件夹保留在库中。
+    /// 进度与结束事件见 [`scan_job`]。
+    pub fn start_scan(self: &Arc<Self>, options: ScanOptions) -> Result<ScanStatus, String> {
+        if !self.scans.begin() {
+            return Ok(self.scans.status());
+        }
+        let token = self.tasks.register(scan_job::SCAN_TASK_ID);
+        let ctx = self.clone();
+        let thread_token = token.clone();
+        let options = ScanOptions {
+            cancel: token.clone(),
+            ..options
+        };
+        std::thread::Builder::new()
+            .name("library-scan".into())
+            .spawn(move || {
+                let _scope = perf::scope("app.scan");
+                let source = &ctx.local_source;
+                let folders = source.folder_manager.get_folders();
+                let mut error = None;
+                for (index, folder) in folders.iter().enumerate() {
+                    if thread_token.is_cancelled() {
+                        break;
+                    }
+                    let files = source.folder_manager.collect_audio_files(folder);
+                    let mut progress = ScanProgress {
+                        folder: platform::path_to_string(folder),
+                        folder_index: index,
+                        folder_count: folders.len(),
+                        done: 0,
+                        total: files.len(),
+                    };
+                    let publish = |progress: &ScanProgress| {
+                        ctx.scans.update(progress.clone());
+                        ctx.events.publish(AppEvent::ScanProgress(progress.clone()));
+                    };
+                    publish(&progress);
+                    let result = ctx.apply_library_change(|| {
+                        source.rescan_files_with_progress(&files, options.clone(), |done| {
+                            progress.done = done;
+                            publish(&progress);
+                        })
+                    });
+                    match result {
+                        Ok((report, _)) => ctx.scans.add_report(report),
+                        Err(e) => {
+                            error = Some(e);
+                            break;
+                        }
+                    }
+                }
+                ctx.tasks.finish(scan_job::SCAN_TASK_ID, &thread_token);
+                let status = ctx.scans.finish(thread_token.is_cancelled(), error);
+                ctx.events.publish(AppEvent::ScanFinished {
+                    report: status.report.unwrap_or_default(),
+                    cancelled: status.cancelled,
+                    error: status.error,
+                });
+            })
+            .map_err(|e| {
+                self.tasks.finish(scan_job::SCAN_TASK_ID, &token);
+                self.scans.finish(false, Some(e.to_string()));
+                format!("启动扫描失败: {}", e)
+            })?;
+        Ok(self.scans.status())
+    }
+
+    /// 取消进行中的后台扫描，返回是否有扫描在运行。
+    pub fn cancel_scan(&self) -> bool {
+        self.tasks.cancel(scan_job::SCAN_TASK_ID)
+    }
+
+    /// 在后台计算歌曲的音频内容哈希（进度事件同转码检测），立即返回任务句柄。
+    ///
+    /// `song_ids` 为 `None` 时只处理缺少哈希或文件已修改的歌曲；指定歌曲时全部重新计算，
+    /// 用于位腐校验，疑似损坏的数量记在结束事件的 `suspicious` 中。没有本地文件的歌曲跳过。
+    pub fn spawn_content_hash_scan(
+        self: &Arc<Self>,
+        task_id: &str,
+        song_ids: Option<Vec<String>>,
+    ) -> Result<AnalysisJob, String> {
+        let verify = song_ids.is_some();
+        let songs = match song_ids {
+            Some(ids) => self.library.get_songs_by_ids(&ids),
+            None => self.library.get_all_songs().into_values().collect(),
+        };
+        let songs: Vec<(String, String)> = songs
+            .into_iter()
+            .filter_map(|song| {
+                let path = resource::find_song_file_path(&self.registrar, &song.source_ids)?;
+                let stale = match &song.content_hash {
+                    Some(hash) => {
+                        platform::file_modified_secs(&PlatformPath::from(path.as_str())).unwrap_or(0) != hash.mtime
+                    }
+                    None => true,
+                };
+                (verify || stale).then_some((song.id, path))
+            })
+            .collect();
+        let ctx = self.clone();
+        self.analysis.spawn_batch(
+            task_id,
+            songs,
+            self.tasks.clone(),
+            self.events.clone(),
+            move |_, song_id, path, token| {
+                let status = ctx.update_content_hash(song_id, path, AnalysisPriority::Batch, Some(token))?;
+                Ok(status == HashStatus::Corrupted)
+            },
+        )
+    }
+
+    /// 立即重新计算单曲的内容哈希并与记录比较。
+    pub fn verify_content_hash(&self, song_id: &str) -> Result<HashStatus, String> {
+        let song = self
+            .library
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+        let path = resource::find_song_file_path(&self.registrar, &song.source_ids)
+            .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", song_id))?;
+        self.update_content_hash(song_id, &path, AnalysisPriority::Interactive, None)
+    }
+
+    fn update_content_hash(
+        &self,
+        song_id: &str,
+        path: &str,
+        priority: AnalysisPriority,
+        token: Option<&CancellationToken>,
+    ) -> Result<HashStatus, String> {
+        let (xxh3, mtime) = self.analysis.content_hash(path, priority, token)?;
+        let previous = self.library.get_song(song_id).and_then(|song| song.content_hash);
+        let (status, record) = content_hash::compare(previous.as_ref(), xxh3, mtime);
+        if status == HashStatus::Corrupted {
+            eprintln!("[chordial] 音频数据与上次记录不一致，文件可能已损坏: {}", path);
+        }
+        self.library.set_content_hash(song_id, record)?;
+        self.library.save()?;
+        Ok(status)
+    }
+
+    /// 记录一次播放并落盘；启用 PCM 缓存且歌曲满足条件时在后台解码缓存。
+    pub fn record_play(&self, song_id: &str) -> Result<PlayStats, String> {
+        let stats = self.library.record_play(song_id)?;
+        self.library.save()?;
+        let settings = self.playback.settings().pcm_cache;
+        if let (Some(cache), Some(song)) = (self.registrar.pcm_cache(), self.library.get_song(song_id)) {
+            if settings.should_cache(stats.play_count, song.duration) {
+                if let Some(path) = resource::find_song_file_path(&self.registrar, &song.source_ids) {
+                    cache.store_in_background(path);
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// 记录一次切歌过渡，附上两首当前的标题。
+    pub fn record_transition(&self, report: TransitionReport) -> Result<(), String> {
+        self.crossfade.finish(&report.to_song_id);
+        let title = |id: &str| self.library.get_song(id).map(|song| song.title);
+        let record = TransitionRecord {
+            at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            from_title: title(&report.from_song_id),
+            to_title: title(&report.to_song_id),
+            report,
+        };
+        self.transitions.record(record)
+    }
+
+    /// 进行中的交叉淡化状态，附上两首当前的标题。
+    pub fn crossfade_status(&self) -> CrossfadeStatus {
+        let title = |id: &Option<String>| id.as_deref().and_then(|id| self.library.get_song(id)).map(|song| song.title);
+        let status = self.crossfade.status();
+        CrossfadeStatus {
+            from_title: title(&status.from_song_id),
+            to_title: title(&status.to_song_id),
+            ..status
+        }
+    }
+
+    /// 取消进行中的交叉淡化，通知前端混音器保留 `keep` 指定的一首。
+    pub fn cancel_crossfade(&self, keep: CrossfadeKeep) -> Result<CrossfadeCancel, String> {
+        let cancel = self.crossfade.cancel(keep)?;
+        self.events.publish(AppEvent::CrossfadeCancelled {
+            from_song_id: cancel.from_song_id.clone(),
+            to_song_id: cancel.to_song_id.clone(),
+            keep,
+        });
+        Ok(cancel)
+    }
+
+    // ── 正在播放的队列 ──────────────────────────────
+
+    /// 队列当前内容（歌曲已本地化）；曲库中已删除的歌曲先从队列中去掉。
+    pub fn queue_view(&self) -> QueueView {
+        self.queue.retain(|id| self.library.get_song(id).is_some());
+        let snapshot = self.queue.snapshot();
+        let songs = snapshot.song_ids.iter().filter_map(|id| self.library.get_song(id)).collect();
+        QueueView {
+            songs: self.library.localize_songs(songs),
+            current_index: snapshot.current_index,
+            repeat: snapshot.repeat,
+            shuffle: snapshot.shuffle,
+            playlist_id: snapshot.playlist_id,
+            has_history: snapshot.has_history,
+        }
+    }
+
+    /// 队列变化后按新的顺序重排预加载，并返回队列内容。
+    fn queue_changed(&self) -> QueueView {
+        let view = self.queue_view();
+        self.preload.set_queue(&self.queue.upcoming(), self.queue.remaining_ms());
+        view
+    }
+
+    /// 切歌后：新歌曲按整首时长计剩余时间，重排预加载。
+    fn queue_step(&self, song_id: Option<String>) -> QueueStep {
+        let song = song_id.and_then(|id| self.library.get_song(&id));
+        self.queue
+            .set_remaining_ms(song.as_ref().and_then(|s| s.duration).unwrap_or(0) * 1000);
+        QueueStep {
+            song: song.map(|s| self.library.localize_song(s)),
+            queue: self.queue_changed(),
+        }
+    }
+
+    /// 替换整个队列并从第 `start_index` 首开始播放；`playlist_id` 为队列来自的播放列表。
+    pub fn queue_replace(&self, song_ids: Vec<String>, start_index: usize, playlist_id: Option<String>) -> QueueStep {
+        self.queue.replace(song_ids, start_index, playlist_id, |id| self.is_audiobook(id));
+        self.queue_step(self.queue.current_id())
+    }
+
+    /// 追加到队尾，`next` 为 true 时插到当前歌曲之后。
+    pub fn queue_add(&self, song_ids: &[String], next: bool) -> Result<QueueView, String> {
+        if let Some(missing) = song_ids.iter().find(|id| self.library.get_song(id).is_none()) {
+            return Err(format!("歌曲 '{}' 不存在", missing));
+        }
+        let was_empty = self.queue.current_id().is_none();
+        match next {
+            true => self.queue.insert_next(song_ids),
+            false => self.queue.add(song_ids),
+        };
+        if was_empty {
+            return Ok(self.queue_step(self.queue.current_id()).queue);
+        }
+        Ok(self.queue_changed())
+    }
+
+    /// 移除队列中的第 `index` 首（不能是正在播放的歌曲）。
+    pub fn queue_remove(&self, index: usize) -> Result<QueueView, String> {
+        self.queue.remove(index)?;
+        Ok(self.queue_changed())
+    }
+
+    /// 切到下一首（当前歌曲播完或用户点「下一首」）。
+    ///
+    /// 不循环且已在队尾时按队列播完后的设置处理：从头重播、追加相似歌曲后继续，或返回空歌曲表示停止。
+    pub fn queue_next(&self) -> QueueStep {
+        if let Some(next) = self.queue.advance() {
+            return self.queue_step(Some(next));
+        }
+        let ids = self.queue.snapshot().song_ids;
+        let plan = queue::end_of_queue(&self.library, self.playback.settings().end_of_queue, &ids);
+        let next = match plan.behavior {
+            EndOfQueueBehavior::Stop => None,
+            EndOfQueueBehavior::RepeatQueue => self.queue.jump(0).ok(),
+            EndOfQueueBehavior::Autoplay => {
+                let added: Vec<String> = plan.songs.into_iter().map(|s| s.id).collect();
+                self.queue.add(&added);
+                self.queue.advance()
+            }
+        };
+        if next.is_none() {
+            // 停止时清空预加载，避免预加载器停在一个不会再播的队列上
+            self.preload.set_queue(&[], 0);
+            return QueueStep {
+                song: None,
+                queue: self.queue_view(),
+            };
+        }
+        self.queue_step(next)
+    }
+
+    /// 回到上一首。
+    pub fn queue_previous(&self) -> QueueStep {
+        let previous = self.queue.previous();
+        self.queue_step(previous)
+    }
+
+    /// 跳到队列中的第 `index` 首。
+    pub fn queue_jump(&self, index: usize) -> Result<QueueStep, String> {
+        let song_id = self.queue.jump(index)?;
+        Ok(self.queue_step(Some(song_id)))
+    }
+
+    pub fn queue_set_repeat_mode(&self, mode: RepeatMode) -> QueueView {
+        self.queue.set_repeat(mode);
+        self.queue_changed()
+    }
+
+    /// 开关随机播放；有声书不参与随机，见 [`LiveQueue::set_shuffle`]。
+    pub fn queue_set_shuffle(&self, enabled: bool) -> QueueView {
+        self.queue.set_shuffle(enabled, |id| self.is_audiobook(id));
+        self.queue_changed()
+    }
+
+    fn is_audiobook(&self, song_id: &str) -> bool {
+        self.library
+            .get_song(song_id)
+            .is_some_and(|song| books::classify(&song) == ContentType::Audiobook)
+    }
+
+    /// 前端定期上报当前歌曲的剩余时长。
+    ///
+    /// 距离过渡开始不足 [`TRANSITION_NOTICE_MS`](live_queue::TRANSITION_NOTICE_MS)
+    /// 时返回下一首与过渡方案（每首歌只返回一次），前端据此在 `starts_in_ms` 后开始交叉淡化或无缝衔接；
+    /// 远程歌曲此前已由预加载器按剩余时长提前下载。
+    pub fn queue_report_position(&self, remaining_ms: u64) -> Option<QueueTransition> {
+        self.queue.set_remaining_ms(remaining_ms);
+        let current = self.library.get_song(&self.queue.current_id()?)?;
+        let next = self.library.get_song(&self.queue.peek_next()?)?;
+        let playlist_id = self.queue.snapshot().playlist_id;
+        let plan = self.playback.crossfade_plan(
+            playlist_id.as_deref(),
+            current.duration.map(|s| s * 1000),
+            Some(remaining_ms),
+            next.duration.map(|s| s * 1000),
+        );
+        let starts_in_ms = remaining_ms.saturating_sub(plan.duration_ms as u64);
+        if starts_in_ms > live_queue::TRANSITION_NOTICE_MS || !self.queue.announce_once() {
+            return None;
+        }
+        Some(QueueTransition {
+            next: self.library.localize_song(next),
+            plan,
+            starts_in_ms,
+        })
+    }
+
+    /// 更新 PCM 缓存设置：调整磁盘上限；关闭时清空已有副本。
+    pub fn set_pcm_cache(&self, settings: PcmCacheSettings) -> Result<PlaybackSettings, String> {
+        let updated = self.playback.set_pcm_cache(settings)?;
+        if let Some(cache) = self.registrar.pcm_cache() {
+            if settings.enabled {
+                cache.set_max_bytes(settings.max_bytes);
+            } else {
+                cache.clear()?;
+            }
+        }
+        Ok(updated)
+    }
+
+    /// 当前缓存位置。
+    pub fn cache_location(&self) -> CacheLocation {
+        let root = self.cache_root.lock();
+        CacheLocation {
+            dir: root.to_string_lossy().into_owned(),
+            is_default: *root == self.data_dir,
+        }
+    }
+
+    /// 把磁盘缓存（Blob / 媒体 / PCM）搬到 `dir` 下并切换过去；`None` 搬回数据目录。
+    ///
+    /// 先搬文件，再把各缓存换成指向新目录的实例（保留容量上限与只读状态），
+    /// 之后补搬迁移期间写入旧目录的文件，最后保存配置。任一缓存切换失败时不保存配置，
+    /// 已搬过去的文件留在新目录，下次迁移时会一并搬回。
+    pub fn relocate_cache(&self, dir: Option<&str>) -> Result<MigrationReport, String> {
+        if self.safe_mode {
+            return Err(safe_mode::READ_ONLY_ERROR.to_string());
+        }
+        let mut root = self.cache_root.lock();
+        let target = match dir {
+            Some(dir) => location::prepare_root(Path::new(dir), &root)?,
+            None => self.data_dir.clone(),
+        };
+        let mut report = location::migrate(&root, &target)?;
+
+        self.cache.enable_blob_storage(target.join(location::BLOB_SUBDIR))?;
+        let media_max = self.registrar.media_cache().map_or(media_cache::DEFAULT_MAX_BYTES, |c| c.max_bytes());
+        self.registrar
+            .set_media_cache(Arc::new(MediaCache::new(target.join(location::MEDIA_SUBDIR), media_max)?));
+        let pcm = PcmCache::new(target.join(location::PCM_SUBDIR), self.playback.settings().pcm_cache.max_bytes)?;
+        self.registrar.set_pcm_cache(Arc::new(pcm));
+
+        let late = location::migrate(&root, &target)?;
+        report.files += late.files;
+        report.bytes += late.bytes;
+        report.failed += late.failed;
+
+        match dir {
+            Some(_) => self.config.set(location::CACHE_DIR_CONFIG_KEY, &target.to_string_lossy())?,
+            None => {
+                self.config.remove(location::CACHE_DIR_CONFIG_KEY);
+            }
+        }
+        *root = target;
+        Ok(report)
+    }
+
+    /// 歌曲的完整分析输入：本地文件直接使用，远程歌曲经媒体缓存拉取（已缓存时不重复下载）。
+    ///
+    /// 远程歌曲首次分析要整文件下载，应在后台线程调用。
+    pub fn analysis_input(&self, song_id: &str) -> Result<AudioInput, String> {
+        let song = self
+            .library
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+        let (path, cache_key) = resource::fetch_song_file_path(&self.registrar, &song.source_ids)
+            .map_err(|e| format!("无法读取歌曲 '{}' 的音频: {}", song_id, e))?;
+        Ok(match cache_key {
+            None => AudioInput::Local(path),
+            Some(sid) => AudioInput::Cached {
+                path,
+                key: format!("{}:{}", sid.source_name, sid.entity_id),
+            },
+        })
+    }
+
+    /// 试听片段（FLAC 字节）：从 `start_secs` 起 `duration_secs` 秒，未指定起点时取高潮段。
+    ///
+    /// 结果按参数写入 Blob 缓存；远程歌曲首次试听要整文件下载，应在后台线程调用。
+    pub fn track_preview(
+        &self,
+        song_id: &str,
+        start_secs: Option<f64>,
+        duration_secs: Option<f64>,
+    ) -> Result<Vec<u8>, String> {
+        let duration_secs = duration_secs.unwrap_or(preview::DEFAULT_DURATION_SECS);
+        if !(duration_secs > 0.0 && duration_secs <= preview::MAX_DURATION_SECS) {
+            return Err(format!("试听时长须在 0 ~ {} 秒之间", preview::MAX_DURATION_SECS));
+        }
+        if start_secs.is_some_and(|s| !(s >= 0.0)) {
+            return Err("试听起点不能为负".to_string());
+        }
+        let duration_ms = (duration_secs * 1000.0) as u64;
+        let start_ms = start_secs.map(|secs| (secs * 1000.0) as u64);
+        let key = preview::cache_key(song_id, start_ms, duration_ms);
+        if let Some(data) = self.cache.get_blob(&key) {
+            return Ok(data);
+        }
+
+        let _scope = perf::scope("app.track_preview");
+        let song = self
+            .library
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+        let (path, _) = resource::fetch_song_file_path(&self.registrar, &song.source_ids)
+            .map_err(|e| format!("无法读取歌曲 '{}' 的音频: {}", song_id, e))?;
+        let path = PlatformPath::from(path.as_str());
+        let start_ms = match start_ms {
+            Some(ms) => ms,
+            None => preview::highlight_start_ms(&path, duration_ms)?,
+        };
+        let data = preview::render(&path, start_ms, duration_ms)?;
+        let _ = self.cache.set_blob(&key, &data, &preview::CACHE_TTL);
+        Ok(data)
+    }
+
+    /// 专辑封面缩略图（长边 `size` 像素，默认 [`thumbnail::DEFAULT_EDGE`]），优先取缓存。
+    pub fn album_thumbnail(&self, album_id: &str, size: Option<u32>) -> Result<Vec<u8>, String> {
+        let edge = size.unwrap_or(thumbnail::DEFAULT_EDGE);
+        thumbnail::album_thumbnail(&self.registrar, &self.library, &self.cache, album_id, edge)
+    }
+
+    /// 在后台为一批专辑生成缩略图（并发受限，新请求取代旧请求），返回需要生成的数量。
+    pub fn prewarm_covers(&self, album_ids: Vec<String>, size: Option<u32>) -> Result<usize, String> {
+        self.covers.spawn(
+            self.registrar.clone(),
+            self.library.clone(),
+            self.cache.clone(),
+            album_ids,
+            size.unwrap_or(thumbnail::DEFAULT_EDGE),
+        )
+    }
+
+    /// 正在播放页的聚合数据：歌词、技术信息、前奏 / 尾奏、增益、封面配色各占一个线程同时计算。
+    ///
+    /// 只使用可直接访问的文件（本地文件或媒体缓存副本），不为此下载远程歌曲；
+    /// 首次分析前奏 / 尾奏需要完整解码，应在后台线程调用。
+    pub fn now_playing_bundle(&self, song_id: &str) -> Result<NowPlayingBundle, String> {
+        let song = self
+            .library
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+        let path = resource::find_song_file_path(&self.registrar, &song.source_ids);
+        let album = song.album_id.as_deref().and_then(|id| self.library.get_album(id));
+
+        let (lyrics, technical, segments, gain, palette) = std::thread::scope(|scope| {
+            let lyrics = scope.spawn(|| {
+                let lyric = self.library.get_lyric_of_song(song_id)?;
+                let text = if lyric.text.is_empty() {
+                    resource::get_lyric_text(&self.registrar, &lyric.source_id).ok()?
+                } else {
+                    lyric.text
+                };
+                Some(LyricsInfo::from_text(text))
+            });
+            let technical = scope.spawn(|| {
+                let info = self.analysis.technical_info(path.as_deref()?).ok()?;
+                Some((*info).clone())
+            });
+            let segments = scope.spawn(|| {
+                let input = AudioInput::Local(path.clone()?);
+                self.analysis.track_segments(song_id, &input).ok()
+            });
+            let gain = scope.spawn(|| self.library.track_gain(song_id).ok());
+            let palette = scope.spawn(|| {
+                let (data, _) = artwork::resolve_album_cover(&self.registrar, &self.library, album.as_ref()?.id.as_str()).ok()?;
+                let cover = artwork::image::decode(&data).ok()?;
+                Some(artwork::palette::palette(&cover, now_playing::PALETTE_SIZE))
+            });
+            (
+                lyrics.join().ok().flatten(),
+                technical.join().ok().flatten(),
+                segments.join().ok().flatten(),
+                gain.join().ok().flatten(),
+                palette.join().ok().flatten(),
+            )
+        });
+
+        Ok(NowPlayingBundle {
+            artists: self.library.get_artists_by_ids(&song.artist_ids),
+            song: self.library.localize_song(song),
+            album,
+            lyrics: lyrics.unwrap_or_default(),
+            technical,
+            segments,
+            gain,
+            palette: palette.unwrap_or_default(),
+        })
+    }
+
+    /// 歌曲的解码与播放质量；`report` 为前端播放器此刻的缓冲状态（未播放时省略）。
+    ///
+    /// 首次查询某个文件时试解开头一段测量解码速度，应在后台线程调用。
+    pub fn decode_stats(&self, song_id: &str, report: Option<&PlayerReport>) -> Result<DecodeStats, String> {
+        let song = self
+            .library
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+        let path = resource::find_song_file_path(&self.registrar, &song.source_ids)
+            .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", song_id))?;
+        let info = self.analysis.technical_info(&path)?;
+        let speed = self
+            .playback
+            .decode_speed(&path)
+            .map_err(|e| eprintln!("[chordial] 测量解码速度失败 {}: {}", path, e))
+            .ok();
+        let gain = self.library.track_gain(song_id).ok();
+        let stats = DecodeStats::new(song_id, &info, speed)
+            .with_dsp_chain(self.playback.dsp_chain(song.content_type, gain.as_ref()));
+        Ok(match report {
+            Some(report) => stats.with_report(report),
+            None => stats,
         })
     }
 
     /// 使用系统默认配置目录（`dirs::config_dir()/chordial`）构建 AppContext。
+    ///
+    /// 设置了 `CHORDIAL_SAFE_MODE` 或带 `--safe-mode` 参数启动时进入安全模式。
     pub fn new_default_dir() -> Result<Self, String> {
         let data_dir = dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join("chordial");
-        Self::new(data_dir)
+        Self::open(data_dir, safe_mode::from_env())
     }
 }
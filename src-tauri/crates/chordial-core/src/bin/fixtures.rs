@@ -0,0 +1,38 @@
+//! 把测试用音频夹具写到目录（见 `chordial_core::module::fixtures`）。
+//!
+//! 用法：`chordial-fixtures <目录> [时长毫秒]`，每种格式写出一个 `tone.<扩展名>`。
+
+use chordial_core::module::fixtures::{self, FixtureSpec};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(dir) = args.next().map(PathBuf::from) else {
+        eprintln!("用法: chordial-fixtures <目录> [时长毫秒]");
+        return ExitCode::FAILURE;
+    };
+    let mut spec = FixtureSpec::default();
+    if let Some(ms) = args.next() {
+        match ms.parse() {
+            Ok(ms) => spec.duration_ms = ms,
+            Err(_) => {
+                eprintln!("无效的时长: {}", ms);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match fixtures::write_all(&dir, "tone", &spec) {
+        Ok(paths) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -19,7 +19,8 @@
 //! 所有参数使用 base64url 编码（`+` → `-`, `/` → `_`, 无填充），
 //! 避免文件路径中的特殊字符（如 Windows 的 `\`、`:`）破坏 URL 解析。
 //!
-//! audio 端点完整支持 HTTP Range 请求（`206 Partial Content`），
+//! audio 端点完整支持 HTTP Range 请求（`206 Partial Content`），单次 Range 响应不超过 [`MAX_RANGE_BYTES`]；
+//! 不带 Range 的请求总是以 `200` 返回整个文件（`chordial-server` 对大文件改为从磁盘流式发送）。
 
 use crate::module::music_source::registrar::SourceRegistrar;
 use crate::module::music_source::resource;
@@ -87,11 +88,55 @@ pub fn error_response(status: StatusCode, msg: &str) -> Response<Vec<u8>> {
         .unwrap()
 }
 
+/// 单个 Range 响应的最大字节数。
+///
+/// `<audio>` 首个请求总是 `bytes=0-`，按字面读到文件末尾会把整首大 FLAC 读进内存；
+/// 规范允许服务端返回比请求更短的区间，媒体元素会按 `Content-Range` 接着请求后续部分，
+/// 拖动进度时也只读取目标位置附近的一块。
+pub const MAX_RANGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// 解析单区间的 `Range` 头（`bytes=a-b` / `bytes=a-` / `bytes=-n`），返回闭区间 `(start, end)`。
+///
+/// 区间长度截断到 [`MAX_RANGE_BYTES`]；多区间请求只取第一个区间。
+/// 区间落在文件之外时返回 `Ok(None)`（应答 416），格式错误时返回 `Err`（应答 400）。
+pub fn parse_range(value: &str, file_size: u64) -> Result<Option<(u64, u64)>, &'static str> {
+    let spec = value
+        .trim()
+        .strip_prefix("bytes=")
+        .and_then(|v| v.split(',').next())
+        .ok_or("无法解析 Range 头")?;
+    let (first, last) = spec.trim().split_once('-').ok_or("无法解析 Range 头")?;
+    let last_byte = file_size.checked_sub(1);
+    let (start, end) = if first.is_empty() {
+        // "bytes=-500" → 最后 500 字节
+        let suffix: u64 = last.parse().map_err(|_| "无效的 Range 结束")?;
+        match last_byte {
+            Some(last_byte) if suffix > 0 => (file_size.saturating_sub(suffix), last_byte),
+            _ => return Ok(None),
+        }
+    } else {
+        let start: u64 = first.parse().map_err(|_| "无效的 Range 起始")?;
+        let Some(last_byte) = last_byte.filter(|b| start <= *b) else {
+            return Ok(None);
+        };
+        let end = if last.is_empty() {
+            last_byte
+        } else {
+            let end: u64 = last.parse().map_err(|_| "无效的 Range 结束")?;
+            if end < start {
+                return Ok(None);
+            }
+            end.min(last_byte)
+        };
+        (start, end)
+    };
+    Ok(Some((start, end.min(start.saturating_add(MAX_RANGE_BYTES - 1)))))
+}
+
 /// 为音频文件提供流式响应，支持 Range 请求。
 ///
 /// 使用 [`platform::open_file`] 和 [`platform::file_size`] 实现跨平台文件访问。
 pub fn serve_audio_file(path_str: &str, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
-    let _token = perf::start("media.serve_audio_file");
     let path = PlatformPath::from(path_str);
 
     // 获取文件大小（先于 open 以减少内存分配）
@@ -101,12 +146,25 @@ pub fn serve_audio_file(path_str: &str, request: &Request<Vec<u8>>) -> Response<
     };
 
     // 打开文件
-    let mut file = match platform::open_file(&path) {
+    let file = match platform::open_file(&path) {
         Ok(f) => f,
         Err(e) => return error_response(StatusCode::NOT_FOUND, &format!("文件未找到: {}", e)),
     };
 
-    let mime = platform::mime_from_path(path_str);
+    serve_audio(file, file_size, platform::mime_from_path(path_str), request)
+}
+
+/// 按请求从 `reader` 中读取音频并构建响应；Range 请求每次最多读取 [`MAX_RANGE_BYTES`]。
+///
+/// 不带 Range 的 GET 以 `200` 返回整个文件，不会只给出部分内容。媒体元素总是带 Range 请求，
+/// 不受影响；`chordial-server` 对超过上限的磁盘文件直接流式发送（见 [`audio_file_path`]），不经过这里。
+pub fn serve_audio<R: Read + Seek>(
+    mut reader: R,
+    file_size: u64,
+    mime: &str,
+    request: &Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let _token = perf::start("media.serve_audio_file");
 
     // 处理 HEAD 请求 — 只返回头信息
     if request.method() == Method::HEAD {
@@ -118,105 +176,79 @@ pub fn serve_audio_file(path_str: &str, request: &Request<Vec<u8>>) -> Response<
             .unwrap();
     }
 
-    // 解析 Range 头
-    if let Some(range_header) = request.headers().get(header::RANGE) {
-        let range_str = match range_header.to_str() {
-            Ok(s) => s,
+    // 解析 Range 头；没有 Range 时读取整个文件
+    let has_range = request.headers().contains_key(header::RANGE);
+    let range = match request.headers().get(header::RANGE) {
+        Some(range_header) => match range_header.to_str() {
+            Ok(s) => parse_range(s, file_size),
             Err(_) => return error_response(StatusCode::BAD_REQUEST, "无效的 Range 头"),
-        };
-
-        // 形如 "bytes=0-1023" 或 "bytes=1024-"
-        if let Some(range_value) = range_str.strip_prefix("bytes=") {
-            let parts: Vec<&str> = range_value.split('-').collect();
-            if parts.len() == 2 {
-                let start: u64 = match parts[0].parse() {
-                    Ok(s) => s,
-                    Err(_) => return error_response(StatusCode::BAD_REQUEST, "无效的 Range 起始"),
-                };
-
-                let end: u64 = if parts[1].is_empty() {
-                    // "bytes=1024-" → 从 start 到文件末尾
-                    file_size.saturating_sub(1)
-                } else {
-                    match parts[1].parse::<u64>() {
-                        Ok(e) => e.min(file_size.saturating_sub(1)),
-                        Err(_) => {
-                            return error_response(StatusCode::BAD_REQUEST, "无效的 Range 结束")
-                        }
-                    }
-                };
-
-                if start > end || start >= file_size {
-                    return Response::builder()
-                        .status(StatusCode::RANGE_NOT_SATISFIABLE)
-                        .header(header::CONTENT_RANGE, format!("bytes */{}", file_size))
-                        .body(Vec::new())
-                        .unwrap();
-                }
-
-                let length = end - start + 1;
-
-                if let Err(e) = file.seek(SeekFrom::Start(start)) {
-                    return error_response(
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        &format!("Seek 失败: {}", e),
-                    );
-                }
-
-                let mut buf = Vec::with_capacity(length as usize);
-                if let Err(e) = file.take(length).read_to_end(&mut buf) {
-                    return error_response(
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        &format!("读取文件失败: {}", e),
-                    );
-                }
-
-                // 仅在 perf 启用时构建 meta 字符串，避免 release 中无谓分配
-                let meta = if perf::enabled() {
-                    Some(format!("bytes={}", length))
-                } else {
-                    None
-                };
-                perf::end(&_token, meta.as_deref());
-                return Response::builder()
-                    .status(StatusCode::PARTIAL_CONTENT)
-                    .header(header::CONTENT_TYPE, mime)
-                    .header(header::CONTENT_LENGTH, length.to_string())
-                    .header(
-                        header::CONTENT_RANGE,
-                        format!("bytes {}-{}/{}", start, end, file_size),
-                    )
-                    .header(header::ACCEPT_RANGES, "bytes")
-                    .body(buf)
-                    .unwrap();
-            }
+        },
+        None => Ok(Some((0, file_size.saturating_sub(1)))),
+    };
+    let (start, end) = match range {
+        Ok(Some(range)) => range,
+        Ok(None) => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", file_size))
+                .body(Vec::new())
+                .unwrap();
         }
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+    // 空文件没有可读的字节
+    let length = (end - start + 1).min(file_size - start);
 
-        return error_response(StatusCode::BAD_REQUEST, "无法解析 Range 头");
+    if let Err(e) = reader.seek(SeekFrom::Start(start)) {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Seek 失败: {}", e),
+        );
     }
 
-    // 完整的文件响应（无 Range）
-    let mut buf = Vec::with_capacity(file_size as usize);
-    if let Err(e) = file.read_to_end(&mut buf) {
+    let mut buf = Vec::with_capacity(length as usize);
+    if let Err(e) = reader.take(length).read_to_end(&mut buf) {
         return error_response(
             StatusCode::INTERNAL_SERVER_ERROR,
             &format!("读取文件失败: {}", e),
         );
     }
 
-    // 仅在 perf 启用时构建 meta 字符串
+    // 仅在 perf 启用时构建 meta 字符串，避免 release 中无谓分配
     let meta = if perf::enabled() {
-        Some(format!("bytes={}", file_size))
+        Some(format!("bytes={}", length))
     } else {
         None
     };
     perf::end(&_token, meta.as_deref());
-    Response::builder()
+
+    let builder = Response::builder()
         .header(header::CONTENT_TYPE, mime)
-        .header(header::CONTENT_LENGTH, file_size.to_string())
-        .header(header::ACCEPT_RANGES, "bytes")
-        .body(buf)
-        .unwrap()
+        .header(header::CONTENT_LENGTH, length.to_string())
+        .header(header::ACCEPT_RANGES, "bytes");
+    // 只有 Range 请求应答 206；不带 Range 时上面已读取整个文件
+    let builder = if has_range {
+        builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, file_size))
+    } else {
+        builder
+    };
+    builder.body(buf).unwrap()
+}
+
+/// `/audio/...` 路径对应的磁盘文件（本地文件、PCM 副本或媒体缓存副本）；没有可直接读取的文件时返回 `None`。
+pub fn audio_file_path(registrar: &SourceRegistrar, path: &str) -> Option<String> {
+    let parsed = parse_url(path).ok().filter(|p| p.resource_type == "audio")?;
+    let source_id = SourceId {
+        source_name: parsed.source_name,
+        source_type: SourceType::Local,
+        entity_type: EntityType::Song,
+        entity_id: parsed.entity_id,
+    };
+    let file_path = resource::get_song_file_path(registrar, &source_id)?;
+    // 常播短曲目优先返回解码好的 PCM 副本
+    Some(registrar.pcm_cache().and_then(|cache| cache.cached_path(&file_path)).unwrap_or(file_path))
 }
 
 /// 处理一个 chordial 媒体请求，返回标准 HTTP 响应。
@@ -234,27 +266,33 @@ pub fn handle(registrar: &SourceRegistrar, path: &str, request: &Request<Vec<u8>
         // 所以先算 mime 再 move）
         Ok(ParsedUrl { resource_type, source_name, entity_id }) => match resource_type.as_str() {
             "audio" => {
-                let mime = platform::mime_from_path(&entity_id);
                 let source_id = SourceId {
                     source_name,
                     source_type: SourceType::Local,
                     entity_type: EntityType::Song,
                     entity_id,
                 };
+                // 转码流按转码格式推断 MIME，缓存也与原始文件分开存放
+                let cache_key = resource::stream_cache_key(registrar, &source_id);
+                let mime = platform::mime_from_path(&cache_key.entity_id);
 
-                match resource::get_song_file_path(registrar, &source_id) {
-                    Some(file_path) => serve_audio_file(&file_path, request),
-                    None => {
-                        // 回退：通过 trait 方法获取完整数据
-                        match resource::get_song_file(registrar, &source_id) {
-                            Ok(data) => Response::builder()
-                                .header(header::CONTENT_TYPE, mime)
-                                .header(header::CONTENT_LENGTH, data.len().to_string())
-                                .body(data)
-                                .unwrap(),
-                            Err(e) => error_response(StatusCode::NOT_FOUND, &e),
+                if let Some(file_path) = audio_file_path(registrar, path) {
+                    return serve_audio_file(&file_path, request);
+                }
+                // 回退：来源只能整文件取回；挂载了媒体缓存时先落盘，之后的请求直接按 Range 读取缓存文件。
+                // 无法落盘时（安全模式、未挂载缓存）同样按 Range 切块返回，单次响应不超过上限
+                match resource::get_song_file(registrar, &source_id) {
+                    Ok(data) => match registrar
+                        .media_cache()
+                        .and_then(|cache| cache.store(&cache_key, &data).ok())
+                    {
+                        Some(cached) => serve_audio_file(&cached, request),
+                        None => {
+                            let size = data.len() as u64;
+                            serve_audio(std::io::Cursor::new(data), size, mime, request)
                         }
-                    }
+                    },
+                    Err(e) => error_response(StatusCode::NOT_FOUND, &e),
                 }
             }
             "image" => {
@@ -305,3 +343,58 @@ pub fn handle(registrar: &SourceRegistrar, path: &str, request: &Request<Vec<u8>
         Err(e) => error_response(StatusCode::BAD_REQUEST, &e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range_caps_open_ended_requests() {
+        let size = 10 * MAX_RANGE_BYTES;
+        assert_eq!(parse_range("bytes=0-", size), Ok(Some((0, MAX_RANGE_BYTES - 1))));
+        assert_eq!(parse_range("bytes=100-199", size), Ok(Some((100, 199))));
+        assert_eq!(parse_range("bytes=-500", size), Ok(Some((size - 500, size - 1))));
+        assert_eq!(parse_range("bytes=0-99, 200-299", size), Ok(Some((0, 99))));
+        assert_eq!(parse_range("bytes=5-", 10), Ok(Some((5, 9))));
+        assert_eq!(parse_range("bytes=10-", 10), Ok(None));
+        assert_eq!(parse_range("bytes=0-", 0), Ok(None));
+        assert!(parse_range("items=0-1", size).is_err());
+        assert!(parse_range("bytes=a-1", size).is_err());
+    }
+
+    #[test]
+    fn test_serve_audio_answers_requests_without_range_in_full() {
+        let get = || Request::builder().method(Method::GET);
+        let plain = get().body(Vec::new()).unwrap();
+        let large = vec![7u8; MAX_RANGE_BYTES as usize + 10];
+        let size = large.len() as u64;
+
+        // 不带 Range：超过上限也返回 200 与完整内容，没有 Content-Range
+        let response = serve_audio(std::io::Cursor::new(large.clone()), size, "audio/flac", &plain);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], size.to_string());
+        assert!(!response.headers().contains_key(header::CONTENT_RANGE));
+        assert_eq!(response.body(), &large);
+
+        // 带 Range：截断到上限，按 206 返回
+        let open = get().header(header::RANGE, "bytes=0-").body(Vec::new()).unwrap();
+        let response = serve_audio(std::io::Cursor::new(large.clone()), size, "audio/flac", &open);
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body().len() as u64, MAX_RANGE_BYTES);
+        assert_eq!(
+            response.headers()[header::CONTENT_RANGE],
+            format!("bytes 0-{}/{}", MAX_RANGE_BYTES - 1, size)
+        );
+
+        let tail = get().header(header::RANGE, "bytes=-4").body(Vec::new()).unwrap();
+        let response = serve_audio(std::io::Cursor::new(large), size, "audio/flac", &tail);
+        assert_eq!(response.body().len(), 4);
+
+        let response = serve_audio(std::io::Cursor::new(vec![1, 2, 3]), 3, "audio/flac", &plain);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), &[1, 2, 3]);
+        let response = serve_audio(std::io::Cursor::new(Vec::new()), 0, "audio/flac", &plain);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.body().is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+//! 音频内容哈希 — 对默认音轨的已编码数据包做 xxh3-128，不含标签与封面。
+//!
+//! 只解封装、不解码，开销接近顺序读一遍文件。改标签、换封面、ID3 与 APE 标签互转都不影响结果，
+//! 因此可用于：
+//! - 精确重复检测（同一份音频数据复制到不同文件夹、标签不同）
+//! - 分析结果的缓存键（改标签后文件 mtime 变化，内容哈希不变时沿用已有结果）
+//! - 位腐检测（文件 mtime 未变但内容哈希变了，说明数据在磁盘上损坏）
+//!
+//! 重新封装（如 FLAC 重新编码、MP4 重新复用）会改变数据包，哈希随之改变。
+//! 计算需要读完整个文件，默认关闭，开启方式见 [`ENABLED_CONFIG_KEY`]。
+
+use crate::module::music_library::models::ContentHash;
+use crate::module::perf;
+use crate::module::platform::{self, PlatformPath};
+use serde::Serialize;
+use symphonia::core::errors::Error;
+use symphonia::core::formats::probe::Hint;
+use symphonia::core::formats::{FormatOptions, TrackType};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use xxhash_rust::xxh3::Xxh3;
+
+/// 是否在启动扫描后为缺少内容哈希的歌曲计算哈希（`bool`，默认关闭）。
+pub const ENABLED_CONFIG_KEY: &str = "content_hash_enabled";
+
+/// 计算文件默认音轨的内容哈希（32 位小写十六进制）。
+pub fn hash_file(path: &PlatformPath) -> Result<String, String> {
+    let _scope = perf::scope("analysis.content_hash");
+    let src = platform::open_file(path)?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = platform::path_extension(path) {
+        hint.with_extension(&ext);
+    }
+    let mut format = symphonia::default::get_probe()
+        .probe(&hint, mss, FormatOptions::default(), MetadataOptions::default())
+        .map_err(|e| format!("无法识别音频格式 '{}': {}", platform::path_to_string(path), e))?;
+    let track_id = format
+        .default_track(TrackType::Audio)
+        .ok_or_else(|| "文件中没有音频轨道".to_string())?
+        .id;
+
+    let mut hasher = Xxh3::new();
+    let mut packets = 0usize;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(Some(packet)) => packet,
+            Ok(None) => break,
+            Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("读取音频数据失败: {}", e)),
+        };
+        if packet.track_id == track_id {
+            hasher.update(&packet.data);
+            packets += 1;
+        }
+    }
+    if packets == 0 {
+        return Err("音轨没有数据".to_string());
+    }
+    Ok(format!("{:032x}", hasher.digest128()))
+}
+
+/// 与上次记录比较的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashStatus {
+    /// 第一次计算
+    New,
+    /// 与记录一致（mtime 可能因改标签而变化）
+    Unchanged,
+    /// 文件被修改过（mtime 变化）且音频数据也变了，已更新记录
+    Modified,
+    /// mtime 未变但音频数据变了 — 疑似磁盘损坏
+    Corrupted,
+}
+
+/// 把新算出的哈希与上次记录比较，返回状态和应保存的新记录。
+///
+/// 判定为 [`HashStatus::Corrupted`] 时仍返回旧记录：损坏的数据不应覆盖正确的哈希，
+/// 修复文件后再次校验即可恢复为 `Unchanged`。
+pub fn compare(previous: Option<&ContentHash>, xxh3: String, mtime: u64) -> (HashStatus, ContentHash) {
+    let current = ContentHash { xxh3, mtime };
+    match previous {
+        None => (HashStatus::New, current),
+        Some(prev) if prev.xxh3 == current.xxh3 => (HashStatus::Unchanged, current),
+        Some(prev) if prev.mtime == current.mtime => (HashStatus::Corrupted, prev.clone()),
+        Some(_) => (HashStatus::Modified, current),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 最小 16 bit 单声道 WAV；`extra` 为附加在数据块之后的 LIST 标签块。
+    fn wav(samples: &[i16], extra: &[u8]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&((4 + 24 + 8 + data.len() + extra.len()) as u32).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&[1, 0, 1, 0]);
+        out.extend_from_slice(&44_100u32.to_le_bytes());
+        out.extend_from_slice(&88_200u32.to_le_bytes());
+        out.extend_from_slice(&[2, 0, 16, 0]);
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&data);
+        out.extend_from_slice(extra);
+        out
+    }
+
+    #[test]
+    fn test_hash_ignores_tags_and_detects_corruption() {
+        let dir = std::env::temp_dir().join(format!("chordial_content_hash_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let samples: Vec<i16> = (0..4410).map(|i| ((i * 37) % 2000) as i16 - 1000).collect();
+        let mut tag = b"LIST".to_vec();
+        tag.extend_from_slice(&16u32.to_le_bytes());
+        tag.extend_from_slice(b"INFOINAM\x06\x00\x00\x00Title\x00");
+        let mut damaged = samples.clone();
+        damaged[100] ^= 0x40;
+
+        let hash = |name: &str, bytes: Vec<u8>| {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            hash_file(&PlatformPath::from(path.to_string_lossy().as_ref())).unwrap()
+        };
+        let plain = hash("plain.wav", wav(&samples, &[]));
+        let tagged = hash("tagged.wav", wav(&samples, &tag));
+        let broken = hash("broken.wav", wav(&damaged, &[]));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(plain.len(), 32);
+        assert_eq!(plain, tagged);
+        assert_ne!(plain, broken);
+
+        let (status, record) = compare(None, plain.clone(), 10);
+        assert_eq!(status, HashStatus::New);
+        assert_eq!(compare(Some(&record), plain.clone(), 20).0, HashStatus::Unchanged);
+        let (status, kept) = compare(Some(&record), broken.clone(), 10);
+        assert_eq!((status, kept.xxh3.as_str()), (HashStatus::Corrupted, plain.as_str()));
+        assert_eq!(compare(Some(&record), broken, 30).0, HashStatus::Modified);
+    }
+}
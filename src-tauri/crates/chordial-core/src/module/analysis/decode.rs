@@ -0,0 +1,226 @@
+//! 完整 PCM 解码 — 供需要逐样本分析的功能使用（静音检测、频谱分析等）。
+//!
+//! 与 [`scanner`](crate::module::music_localSource::scanner) 的「只探测不解码」不同，
+//! 这里会把整个文件解码为 `f32` 交织样本，开销与文件时长成正比，
+//! 调用方应自行缓存结果。
+//!
+//! [`decode_from`] 从指定位置开始解码。MP3 / AAC 等有损格式的帧依赖前面的帧
+//! （MP3 的位存储、MDCT 的重叠相加），直接从目标帧起解会有一小段杂音，
+//! 所以先退回 [`preroll_frames`] 帧预解码并丢弃，输出从目标采样帧精确开始。
+
+use crate::module::music_localSource::scanner;
+use crate::module::perf;
+use crate::module::platform::{self, PlatformPath};
+use symphonia::core::codecs::audio::well_known::{
+    CODEC_ID_AAC, CODEC_ID_MP1, CODEC_ID_MP2, CODEC_ID_MP3, CODEC_ID_OPUS, CODEC_ID_VORBIS,
+};
+use symphonia::core::codecs::audio::{AudioCodecId, AudioDecoderOptions};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::probe::Hint;
+use symphonia::core::formats::{FormatOptions, SeekMode, SeekTo, TrackType};
+use symphonia::core::units::TimeBase;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+
+/// 解码得到的一块交织 PCM 样本。
+pub struct PcmBlock<'a> {
+    /// 交织样本（`frames * channels` 个）
+    pub samples: &'a [f32],
+    /// 声道数
+    pub channels: usize,
+    /// 采样率（Hz）
+    pub sample_rate: u32,
+}
+
+/// 解码音频文件的默认音轨，按 packet 依次回调。
+///
+/// 单个 packet 解码失败（数据损坏）会被跳过；遇到不可恢复错误时停止并返回错误。
+pub fn decode_file<F: FnMut(PcmBlock<'_>)>(path: &PlatformPath, mut on_block: F) -> Result<(), String> {
+    decode_while(path, |block| {
+        on_block(block);
+        true
+    })
+}
+
+/// 同 [`decode_file`]，但 `on_block` 返回 `false` 时立即停止（只需要开头一段时使用）。
+pub fn decode_while<F: FnMut(PcmBlock<'_>) -> bool>(path: &PlatformPath, on_block: F) -> Result<(), String> {
+    decode_from(path, 0, on_block)
+}
+
+/// 某编码在 seek 目标之前需要预解码并丢弃的采样帧数；帧间无依赖的编码（FLAC / PCM 等）为 0。
+pub fn preroll_frames(codec: AudioCodecId, sample_rate: u32) -> u64 {
+    match codec {
+        // 位存储最多回指 511 字节，低码率下可跨约 4 帧，再加 1 帧 MDCT 重叠
+        CODEC_ID_MP3 => 5 * 1152,
+        // 子带滤波器组的状态，一帧足够
+        CODEC_ID_MP1 | CODEC_ID_MP2 => 1152,
+        // 上一帧的 MDCT 重叠 + 1 帧余量
+        CODEC_ID_AAC => 2 * 1024,
+        // 最长块（8192）的半块重叠
+        CODEC_ID_VORBIS => 4096,
+        // RFC 7845 建议至少预解码 80 ms
+        CODEC_ID_OPUS => sample_rate as u64 * 80 / 1000,
+        _ => 0,
+    }
+}
+
+/// 从 `start_ms` 处开始解码，`on_block` 返回 `false` 时立即停止。
+///
+/// 先退回 [`preroll_frames`] 帧 seek，预解码的部分丢弃，第一块从目标采样帧开始。
+/// 容器不支持 seek 时从头解码并丢弃目标之前的部分，结果相同，只是更慢。
+pub fn decode_from<F: FnMut(PcmBlock<'_>) -> bool>(
+    path: &PlatformPath,
+    start_ms: u64,
+    mut on_block: F,
+) -> Result<(), String> {
+    let _scope = perf::scope("analysis.decode_file");
+    let src = platform::open_file(path)?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = platform::path_extension(path) {
+        hint.with_extension(&ext);
+    }
+
+    let mut format = symphonia::default::get_probe()
+        .probe(
+            &hint,
+            mss,
+            FormatOptions::default(),
+            MetadataOptions::default(),
+        )
+        .map_err(|e| format!("无法识别音频格式 '{}': {}", platform::path_to_string(path), e))?;
+
+    let track = format
+        .default_track(TrackType::Audio)
+        .ok_or_else(|| "文件中没有音频轨道".to_string())?;
+    let track_id = track.id;
+    let audio_params = track
+        .codec_params
+        .as_ref()
+        .and_then(|p| p.audio())
+        .ok_or_else(|| "音频轨道缺少编解码参数".to_string())?;
+    let codec = audio_params.codec;
+    let sample_rate = audio_params.sample_rate.unwrap_or(0);
+    let time_base = track
+        .time_base
+        .unwrap_or_else(|| TimeBase::new(1, sample_rate.max(1)));
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make_audio_decoder(audio_params, &AudioDecoderOptions::default())
+        .map_err(|e| {
+            let codec = scanner::codec_name(audio_params.codec).unwrap_or_else(|| "unknown".to_string());
+            format!("创建解码器失败（{}）: {}", codec, e)
+        })?;
+
+    // 目标之前还需丢弃的采样帧数
+    let mut skip = 0u64;
+    if start_ms > 0 {
+        let target = start_ms * sample_rate as u64 / 1000;
+        let preroll_start = target.saturating_sub(preroll_frames(codec, sample_rate));
+        let seek = SeekTo::TimeStamp {
+            ts: frames_to_ts(preroll_start, sample_rate, time_base),
+            track_id,
+        };
+        skip = match format.seek(SeekMode::Accurate, seek) {
+            Ok(seeked) => {
+                decoder.reset();
+                target.saturating_sub(ts_to_frames(seeked.actual_ts, sample_rate, time_base))
+            }
+            Err(_) => target,
+        };
+    }
+
+    let mut samples: Vec<f32> = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(Some(packet)) => packet,
+            Ok(None) => break,
+            Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("读取音频数据失败: {}", e)),
+        };
+        if packet.track_id != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(buf) => {
+                let channels = buf.num_planes().max(1);
+                let sample_rate = buf.spec().rate();
+                samples.resize(buf.samples_interleaved(), 0.0);
+                buf.copy_to_slice_interleaved(&mut samples);
+                let frames = (samples.len() / channels) as u64;
+                if skip >= frames {
+                    skip -= frames;
+                    continue;
+                }
+                let offset = skip as usize * channels;
+                skip = 0;
+                let more = on_block(PcmBlock {
+                    samples: &samples[offset..],
+                    channels,
+                    sample_rate,
+                });
+                if !more {
+                    break;
+                }
+            }
+            Err(Error::DecodeError(_)) | Err(Error::IoError(_)) => continue,
+            Err(e) => return Err(format!("解码失败: {}", e)),
+        }
+    }
+    Ok(())
+}
+
+/// 采样帧数换算为音轨时间基下的时间戳。
+fn frames_to_ts(frames: u64, sample_rate: u32, time_base: TimeBase) -> u64 {
+    let (numer, denom) = (time_base.numer.max(1) as u128, time_base.denom as u128);
+    (frames as u128 * denom / (numer * sample_rate.max(1) as u128)) as u64
+}
+
+/// 音轨时间基下的时间戳换算为采样帧数。
+fn ts_to_frames(ts: u64, sample_rate: u32, time_base: TimeBase) -> u64 {
+    let (numer, denom) = (time_base.numer as u128, time_base.denom.max(1) as u128);
+    (ts as u128 * numer * sample_rate as u128 / denom) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::fixtures::{self, FixtureFormat, FixtureSpec};
+
+    #[test]
+    fn test_decode_from_lands_on_target_frame() {
+        let dir = std::env::temp_dir().join(format!("chordial-decode-from-{}", std::process::id()));
+        let spec = FixtureSpec::default();
+        let start_ms = 500;
+        let start = (start_ms * spec.sample_rate as u64 / 1000) as usize;
+
+        for format in [FixtureFormat::Flac, FixtureFormat::Ogg, FixtureFormat::Wav, FixtureFormat::M4a] {
+            let path = fixtures::write(&dir, "seek", format, &spec).unwrap();
+            let mut full = Vec::new();
+            decode_file(&path, |block| full.extend_from_slice(block.samples)).unwrap();
+            let mut tail = Vec::new();
+            decode_from(&path, start_ms, |block| {
+                tail.extend_from_slice(block.samples);
+                true
+            })
+            .unwrap();
+            // 立体声：从第 start 帧起与完整解码逐样本相同
+            assert_eq!(tail.len(), full.len() - start * 2, "{:?}", format);
+            assert_eq!(tail[..64], full[start * 2..start * 2 + 64], "{:?}", format);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_timestamp_conversion_round_trips() {
+        let per_sample = TimeBase::new(1, 44_100);
+        assert_eq!(frames_to_ts(44_100, 44_100, per_sample), 44_100);
+        // MP4 的 mdhd 时间刻度可以与采样率不同
+        let coarse = TimeBase::new(1, 1000);
+        assert_eq!(frames_to_ts(44_100, 44_100, coarse), 1000);
+        assert_eq!(ts_to_frames(1000, 44_100, coarse), 44_100);
+        assert_eq!(preroll_frames(CODEC_ID_OPUS, 48_000), 3840);
+    }
+}
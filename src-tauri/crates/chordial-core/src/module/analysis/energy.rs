@@ -0,0 +1,325 @@
+//! 能量 / 律动评分 — 给每首歌一个 0–100 的「能量」与「可舞性」分数，供按能量排序和筛选。
+//!
+//! 每 [`HOP_MS`] 计算一次整体电平和低频（< [`BASS_CUTOFF_HZ`]，底鼓 / 贝斯）电平，得到：
+//!
+//! | 特征 | 计算 | 含义 |
+//! |------|------|------|
+//! | 响度 | 非静音部分的平均功率（dBFS） | 越响能量越高 |
+//! | 动态范围 | 短时电平的 90% 与 10% 分位之差 | 压缩得越紧越「冲」 |
+//! | 起音密度 | 起音包络（电平上升量）的局部峰值每秒个数 | 鼓点、拨弦越密越热闹 |
+//! | 节拍强度 | 起音包络在 60–180 BPM 范围内的归一化自相关峰值 | 节奏越规整越适合跟着动 |
+//!
+//! 能量以响度为主，可舞性以节拍强度和速度（95–135 BPM 最高）为主，权重见 [`score`]。
+//! 分数是相对粗糙的启发式结果，用来把「适合运动」和「适合睡前」的歌分开，不追求精确。
+
+use super::decode::{decode_file, PcmBlock};
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+
+/// 电平采样间隔（毫秒）。
+const HOP_MS: u64 = 10;
+
+/// 低频电平的一阶低通截止频率（Hz）。
+const BASS_CUTOFF_HZ: f32 = 150.0;
+
+/// 低于该电平（dBFS）的采样视为静音，不计入响度和动态范围。
+const SILENCE_DB: f32 = -50.0;
+
+/// 起音包络使用的电平下限，避免静音段的对数电平放大噪声。
+const ONSET_FLOOR_DB: f32 = -80.0;
+
+/// 电平上升不足该值（dB）时不计入起音包络：平稳长音的逐点微小起伏也很规整，
+/// 不滤掉会被自相关当成极强的节拍。
+const ONSET_MIN_RISE_DB: f32 = 1.0;
+
+/// 起音包络的局部峰值超过该值（dB）才计为一次起音。
+const ONSET_THRESHOLD_DB: f32 = 6.0;
+
+/// 计算动态范围的短时电平块（采样数，400 ms）。
+const BLOCK_HOPS: usize = 40;
+
+/// 节拍搜索范围（BPM）。
+const TEMPO_RANGE: (f32, f32) = (60.0, 180.0);
+
+/// 能量等级，前端按等级着色。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum EnergyLevel {
+    /// 0–29：安静、舒缓
+    #[default]
+    Calm,
+    /// 30–49
+    Mellow,
+    /// 50–69
+    Upbeat,
+    /// 70–100：适合运动
+    Intense,
+}
+
+impl EnergyLevel {
+    pub fn from_score(energy: u8) -> Self {
+        match energy {
+            0..=29 => Self::Calm,
+            30..=49 => Self::Mellow,
+            50..=69 => Self::Upbeat,
+            _ => Self::Intense,
+        }
+    }
+}
+
+/// 单首歌曲的能量评分。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct EnergyProfile {
+    /// 能量（0–100）
+    pub energy: u8,
+    /// 可舞性（0–100）
+    pub danceability: u8,
+    pub level: EnergyLevel,
+    /// 非静音部分的平均电平（dBFS）
+    pub loudness_db: f32,
+    /// 短时电平的 90% / 10% 分位差（dB）
+    pub dynamic_range_db: f32,
+    /// 每秒起音次数
+    pub onset_rate: f32,
+    /// 节拍强度（0.0–1.0）
+    pub beat_strength: f32,
+    /// 估计速度；节拍不明显时为 `None`
+    pub tempo_bpm: Option<f32>,
+}
+
+/// 由 [`EnergyAnalyzer`] 提取、交给 [`score`] 的特征。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnergyFeatures {
+    pub loudness_db: f32,
+    pub dynamic_range_db: f32,
+    pub onset_rate: f32,
+    pub beat_strength: f32,
+    pub tempo_bpm: Option<f32>,
+}
+
+/// 流式能量分析器。
+pub struct EnergyAnalyzer {
+    hop_frames: usize,
+    bass_coef: f32,
+    bass_lp: f32,
+    acc_total: f64,
+    acc_bass: f64,
+    acc_frames: usize,
+    /// 每个采样点的 (整体功率, 低频功率)
+    hops: Vec<(f64, f64)>,
+}
+
+impl EnergyAnalyzer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            hop_frames: (sample_rate as u64 * HOP_MS / 1000).max(1) as usize,
+            bass_coef: 1.0 - (-std::f32::consts::TAU * BASS_CUTOFF_HZ / sample_rate.max(1) as f32).exp(),
+            bass_lp: 0.0,
+            acc_total: 0.0,
+            acc_bass: 0.0,
+            acc_frames: 0,
+            hops: Vec::new(),
+        }
+    }
+
+    /// 喂入交织样本，混合为单声道分析。
+    pub fn push(&mut self, samples: &[f32], channels: usize) {
+        let channels = channels.max(1);
+        for frame in samples.chunks(channels) {
+            let mono = frame.iter().sum::<f32>() / channels as f32;
+            self.bass_lp += self.bass_coef * (mono - self.bass_lp);
+            self.acc_total += (mono as f64) * (mono as f64);
+            self.acc_bass += (self.bass_lp as f64) * (self.bass_lp as f64);
+            self.acc_frames += 1;
+            if self.acc_frames == self.hop_frames {
+                let n = self.acc_frames as f64;
+                self.hops.push((self.acc_total / n, self.acc_bass / n));
+                self.acc_total = 0.0;
+                self.acc_bass = 0.0;
+                self.acc_frames = 0;
+            }
+        }
+    }
+
+    pub fn finish(self) -> EnergyProfile {
+        score(&features(&self.hops))
+    }
+}
+
+/// 解码文件并计算能量评分。
+pub fn analyze_file(path: &PlatformPath) -> Result<EnergyProfile, String> {
+    let mut analyzer: Option<EnergyAnalyzer> = None;
+    decode_file(path, |block: PcmBlock<'_>| {
+        analyzer
+            .get_or_insert_with(|| EnergyAnalyzer::new(block.sample_rate))
+            .push(block.samples, block.channels);
+    })?;
+    Ok(analyzer.map(EnergyAnalyzer::finish).unwrap_or_default())
+}
+
+fn power_db(power: f64) -> f32 {
+    if power > 0.0 {
+        (10.0 * power.log10()) as f32
+    } else {
+        f32::NEG_INFINITY
+    }
+}
+
+/// 由逐采样点功率提取特征。
+fn features(hops: &[(f64, f64)]) -> EnergyFeatures {
+    let audible: Vec<f64> = hops
+        .iter()
+        .map(|&(total, _)| total)
+        .filter(|&p| power_db(p) >= SILENCE_DB)
+        .collect();
+    if audible.is_empty() {
+        return EnergyFeatures {
+            loudness_db: ONSET_FLOOR_DB,
+            ..Default::default()
+        };
+    }
+    let loudness_db = power_db(audible.iter().sum::<f64>() / audible.len() as f64);
+
+    let mut blocks: Vec<f32> = hops
+        .chunks(BLOCK_HOPS)
+        .map(|block| power_db(block.iter().map(|h| h.0).sum::<f64>() / block.len() as f64))
+        .filter(|&db| db >= SILENCE_DB)
+        .collect();
+    blocks.sort_by(f32::total_cmp);
+    let percentile = |p: f32| blocks[((blocks.len() - 1) as f32 * p).round() as usize];
+    let dynamic_range_db = if blocks.is_empty() {
+        0.0
+    } else {
+        percentile(0.9) - percentile(0.1)
+    };
+
+    // 起音包络：低频与整体电平的上升量（低频占主导，底鼓最能代表节拍）
+    let level = |p: f64| power_db(p).max(ONSET_FLOOR_DB);
+    let rise = |from: f64, to: f64| match level(to) - level(from) {
+        d if d >= ONSET_MIN_RISE_DB => d,
+        _ => 0.0,
+    };
+    let onsets: Vec<f32> = hops
+        .windows(2)
+        .map(|w| rise(w[0].1, w[1].1) + 0.5 * rise(w[0].0, w[1].0))
+        .collect();
+    let peaks = onsets
+        .windows(3)
+        .filter(|w| w[1] >= ONSET_THRESHOLD_DB && w[1] > w[0] && w[1] >= w[2])
+        .count();
+    let seconds = (hops.len() as u64 * HOP_MS) as f32 / 1000.0;
+    let onset_rate = if seconds > 0.0 { peaks as f32 / seconds } else { 0.0 };
+
+    let (beat_strength, tempo_bpm) = beat_periodicity(&onsets);
+    EnergyFeatures {
+        loudness_db,
+        dynamic_range_db,
+        onset_rate,
+        beat_strength,
+        tempo_bpm,
+    }
+}
+
+/// 起音包络在节拍范围内的归一化自相关峰值及对应速度。
+fn beat_periodicity(onsets: &[f32]) -> (f32, Option<f32>) {
+    let lag_of = |bpm: f32| (60_000.0 / bpm / HOP_MS as f32).round() as usize;
+    let (min_lag, max_lag) = (lag_of(TEMPO_RANGE.1), lag_of(TEMPO_RANGE.0));
+    if onsets.len() <= max_lag * 2 {
+        return (0.0, None);
+    }
+    let mean = onsets.iter().sum::<f32>() / onsets.len() as f32;
+    let centered: Vec<f32> = onsets.iter().map(|o| o - mean).collect();
+    let energy: f32 = centered.iter().map(|c| c * c).sum();
+    if energy <= f32::EPSILON {
+        return (0.0, None);
+    }
+    let (lag, peak) = (min_lag..=max_lag)
+        .map(|lag| {
+            let r: f32 = centered.iter().zip(&centered[lag..]).map(|(a, b)| a * b).sum();
+            // 按重叠长度补偿，长延迟不因求和项少而吃亏
+            (lag, r / energy * centered.len() as f32 / (centered.len() - lag) as f32)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((min_lag, 0.0));
+    let strength = peak.clamp(0.0, 1.0);
+    let tempo = (strength >= 0.2).then(|| 60_000.0 / (lag as u64 * HOP_MS) as f32);
+    (strength, tempo)
+}
+
+/// 由特征计算分数。
+///
+/// - 能量 = 45% 响度（−30 → −8 dBFS）+ 25% 起音密度（0 → 4 次/秒）
+///   + 15% 节拍强度 + 15% 压缩程度（动态范围 20 → 4 dB）
+/// - 可舞性 = 55% 节拍强度 + 25% 速度贴合度 + 20% 起音密度
+pub fn score(f: &EnergyFeatures) -> EnergyProfile {
+    let unit = |x: f32| x.clamp(0.0, 1.0);
+    let loudness = unit((f.loudness_db + 30.0) / 22.0);
+    let density = unit(f.onset_rate / 4.0);
+    let compression = unit((20.0 - f.dynamic_range_db) / 16.0);
+    let tempo_fit = f.tempo_bpm.map_or(0.0, |bpm| match bpm {
+        b if (95.0..=135.0).contains(&b) => 1.0,
+        b if b < 95.0 => unit((b - TEMPO_RANGE.0) / (95.0 - TEMPO_RANGE.0)),
+        b => unit((TEMPO_RANGE.1 - b) / (TEMPO_RANGE.1 - 135.0)),
+    });
+
+    let energy = 0.45 * loudness + 0.25 * density + 0.15 * f.beat_strength + 0.15 * compression;
+    let danceability = 0.55 * f.beat_strength + 0.25 * tempo_fit + 0.2 * density;
+    let energy = (unit(energy) * 100.0).round() as u8;
+    EnergyProfile {
+        energy,
+        danceability: (unit(danceability) * 100.0).round() as u8,
+        level: EnergyLevel::from_score(energy),
+        loudness_db: f.loudness_db,
+        dynamic_range_db: f.dynamic_range_db,
+        onset_rate: f.onset_rate,
+        beat_strength: f.beat_strength,
+        tempo_bpm: f.tempo_bpm,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kick_pattern_scores_above_soft_pad() {
+        const RATE: u32 = 22_050;
+        let seconds = 20;
+        let frames = (RATE * seconds) as usize;
+        let beat = RATE as usize / 2; // 120 BPM
+
+        // 每拍一个衰减的 60 Hz 底鼓，叠加一层轻微的中频铺底
+        let kick: Vec<f32> = (0..frames)
+            .map(|i| {
+                let t = (i % beat) as f32 / RATE as f32;
+                let body = 0.9 * (-t * 18.0).exp() * (std::f32::consts::TAU * 60.0 * t).sin();
+                body + 0.05 * (std::f32::consts::TAU * 440.0 * i as f32 / RATE as f32).sin()
+            })
+            .collect();
+        // 安静、平稳的长音
+        let pad: Vec<f32> = (0..frames)
+            .map(|i| 0.03 * (std::f32::consts::TAU * 220.0 * i as f32 / RATE as f32).sin())
+            .collect();
+
+        let analyze = |samples: &[f32]| {
+            let mut analyzer = EnergyAnalyzer::new(RATE);
+            for chunk in samples.chunks(4096) {
+                analyzer.push(chunk, 1);
+            }
+            analyzer.finish()
+        };
+        let kick = analyze(&kick);
+        let pad = analyze(&pad);
+
+        assert!(kick.beat_strength > 0.5, "{:?}", kick);
+        let bpm = kick.tempo_bpm.expect("应检测到节拍");
+        assert!((bpm - 120.0).abs() < 3.0, "{}", bpm);
+        assert!(kick.energy > pad.energy + 20, "{:?} / {:?}", kick, pad);
+        assert!(kick.danceability >= 70, "{:?}", kick);
+        assert_eq!(pad.level, EnergyLevel::Calm);
+        assert_eq!(pad.tempo_bpm, None);
+        assert_eq!(EnergyLevel::from_score(kick.energy), kick.level);
+    }
+}
@@ -0,0 +1,163 @@
+//! 音频指纹 — 按 [`FRAME_MS`] 记录的响度包络，用于判断两个文件是否为同一录音。
+//!
+//! 比较的是包络的逐帧变化（起音、段落切换）而不是绝对电平，重制版整体响度不同也能对上；
+//! 比较时在 ±[`MAX_SHIFT_MS`] 内滑动对齐，容忍开头静音长度不同的单曲版 / 专辑版。
+//! 现场版、混音等重新演绎的版本节奏和段落都会变化，相似度通常很低，
+//! 因此指纹只用来区分「同一录音的不同发行」与「另一个演绎版本」，不用于找歌。
+
+use super::decode::{decode_file, PcmBlock};
+use super::segments::rms_db;
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+
+/// 指纹帧长（毫秒）。
+pub const FRAME_MS: u64 = 250;
+
+/// 对齐时最多错开的时长（毫秒）。
+pub const MAX_SHIFT_MS: u64 = 20_000;
+
+/// 至少重叠多少帧才计算相似度（10 秒）。
+const MIN_OVERLAP_FRAMES: usize = 40;
+
+/// 包络量化下限（dBFS），更低的电平都记为 0。
+const ENVELOPE_FLOOR_DB: f32 = -90.0;
+
+/// 相似度不低于该值视为同一录音。
+pub const SAME_RECORDING_THRESHOLD: f32 = 0.85;
+
+/// 一个文件的指纹。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// 逐帧电平，每单位 0.5 dB，0 对应 [`ENVELOPE_FLOOR_DB`]
+    pub envelope: Vec<u8>,
+}
+
+impl Fingerprint {
+    /// 包络的逐帧变化（dB）。
+    fn deltas(&self) -> Vec<f32> {
+        self.envelope
+            .windows(2)
+            .map(|w| (w[1] as f32 - w[0] as f32) * 0.5)
+            .collect()
+    }
+}
+
+/// 流式指纹提取：依次喂入 PCM，最后 [`finish`](Self::finish) 得到结果。
+pub struct FingerprintBuilder {
+    frame_len: usize,
+    acc: f64,
+    acc_frames: usize,
+    envelope: Vec<u8>,
+}
+
+impl FingerprintBuilder {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            frame_len: (sample_rate as u64 * FRAME_MS / 1000).max(1) as usize,
+            acc: 0.0,
+            acc_frames: 0,
+            envelope: Vec::new(),
+        }
+    }
+
+    /// 喂入交织样本。
+    pub fn push(&mut self, samples: &[f32], channels: usize) {
+        for frame in samples.chunks(channels.max(1)) {
+            let mono = frame.iter().sum::<f32>() / frame.len() as f32;
+            self.acc += (mono as f64) * (mono as f64);
+            self.acc_frames += 1;
+            if self.acc_frames == self.frame_len {
+                let db = rms_db(self.acc, self.acc_frames).clamp(ENVELOPE_FLOOR_DB, 0.0);
+                self.envelope.push(((db - ENVELOPE_FLOOR_DB) * 2.0).round() as u8);
+                self.acc = 0.0;
+                self.acc_frames = 0;
+            }
+        }
+    }
+
+    pub fn finish(self) -> Fingerprint {
+        Fingerprint {
+            envelope: self.envelope,
+        }
+    }
+}
+
+/// 解码文件并提取指纹。
+pub fn analyze_file(path: &PlatformPath) -> Result<Fingerprint, String> {
+    let mut builder: Option<FingerprintBuilder> = None;
+    decode_file(path, |block: PcmBlock<'_>| {
+        builder
+            .get_or_insert_with(|| FingerprintBuilder::new(block.sample_rate))
+            .push(block.samples, block.channels);
+    })?;
+    Ok(builder.map(FingerprintBuilder::finish).unwrap_or_default())
+}
+
+/// 两个指纹的相似度（0–1）：各对齐位置上包络变化的相关系数取最大值。
+///
+/// 任一指纹过短（不足 10 秒）时返回 0。
+pub fn similarity(a: &Fingerprint, b: &Fingerprint) -> f32 {
+    let (a, b) = (a.deltas(), b.deltas());
+    let max_shift = (MAX_SHIFT_MS / FRAME_MS) as isize;
+    let mut best = 0.0f32;
+    for shift in -max_shift..=max_shift {
+        // shift > 0：b 比 a 晚开始 shift 帧
+        let (a_start, b_start) = if shift >= 0 { (0, shift as usize) } else { ((-shift) as usize, 0) };
+        if a_start >= a.len() || b_start >= b.len() {
+            continue;
+        }
+        let len = (a.len() - a_start).min(b.len() - b_start);
+        if len < MIN_OVERLAP_FRAMES {
+            continue;
+        }
+        best = best.max(correlation(&a[a_start..a_start + len], &b[b_start..b_start + len]));
+    }
+    best
+}
+
+/// 皮尔逊相关系数；任一序列没有变化时为 0。
+fn correlation(x: &[f32], y: &[f32]) -> f32 {
+    let n = x.len() as f32;
+    let (mean_x, mean_y) = (x.iter().sum::<f32>() / n, y.iter().sum::<f32>() / n);
+    let (mut cov, mut var_x, mut var_y) = (0.0f32, 0.0f32, 0.0f32);
+    for (&a, &b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mean_x, b - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+    if var_x <= f32::EPSILON || var_y <= f32::EPSILON {
+        return 0.0;
+    }
+    cov / (var_x.sqrt() * var_y.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 简单的线性同余序列，生成可复现的「随机」包络。
+    fn envelope(seed: u32, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                60 + (state >> 24) as u8 % 100
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_similarity_aligns_shifted_copy_and_rejects_other_tracks() {
+        let original = Fingerprint { envelope: envelope(1, 800) };
+        // 单曲版：开头多 3 秒静音，整体响度低 3 dB
+        let mut single = vec![0u8; 12];
+        single.extend(original.envelope.iter().map(|v| v - 6));
+        let single = Fingerprint { envelope: single };
+        let other = Fingerprint { envelope: envelope(7, 800) };
+
+        assert!(similarity(&original, &single) > SAME_RECORDING_THRESHOLD);
+        assert!(similarity(&original, &other) < 0.5);
+        assert_eq!(similarity(&original, &Fingerprint { envelope: envelope(1, 10) }), 0.0);
+    }
+}
@@ -0,0 +1,568 @@
+//! 分析管理器 — 按需分析并缓存结果。
+
+use super::content_hash;
+use super::energy::{self, EnergyProfile};
+use super::fingerprint::{self, Fingerprint};
+use super::scheduler::{AnalysisPriority, AnalysisScheduler};
+use super::segments::{self, TrackSegments};
+use super::vocals::{self, VocalMap};
+use super::technical::{self, TechnicalInfo};
+use super::transcode::{self, TranscodeVerdict};
+use crate::module::cancel::{CancellationRegistry, CancellationToken};
+use crate::module::events::{AppEvent, EventBus};
+use crate::module::platform::{self, PlatformPath};
+use crate::module::power::PowerMonitor;
+use crate::module::storage::persistent::PersistentStore;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// 转码检测结果在持久化存储中的 key（子键为歌曲 ID）。
+const TRANSCODE_KEY: &str = "transcode";
+
+/// 前奏 / 尾奏检测结果的 key（子键为歌曲 ID）。
+const SEGMENTS_KEY: &str = "segments";
+
+/// 人声检测结果的 key（子键为歌曲 ID）。
+const VOCALS_KEY: &str = "vocals";
+
+/// 能量评分的 key（子键为歌曲 ID）。
+const ENERGY_KEY: &str = "energy";
+
+/// 音频指纹的 key（子键为歌曲 ID）。
+const FINGERPRINT_KEY: &str = "fingerprint";
+
+/// 待做完整分析的音频。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioInput {
+    /// 本地文件：路径或 mtime 变化时重新分析
+    Local(String),
+    /// 远程歌曲在媒体缓存中的副本。缓存命中会刷新文件 mtime，
+    /// 因此改用来源键（`source_name:entity_id`，含转码质量）标识，键不变即沿用上次结果
+    Cached { path: String, key: String },
+}
+
+impl AudioInput {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Local(path) | Self::Cached { path, .. } => path,
+        }
+    }
+}
+
+/// 带文件修改时间的缓存条目；文件 mtime 变化即视为失效。
+struct Cached<T> {
+    mtime: u64,
+    value: Arc<T>,
+}
+
+/// 持久化的转码检测记录。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscodeRecord {
+    pub song_id: String,
+    pub path: String,
+    /// 分析时文件的修改时间
+    pub mtime: u64,
+    pub verdict: TranscodeVerdict,
+}
+
+/// 持久化的逐曲分析结果（前奏 / 尾奏、人声区间等），文件路径或 mtime 变化即失效。
+///
+/// 记录了内容哈希时，mtime 变化但音频数据未变（只改了标签）的文件沿用原结果。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredResult<T> {
+    path: String,
+    mtime: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_hash: Option<String>,
+    result: T,
+}
+
+/// 批量分析任务（转码检测、内容哈希）的统计结果。
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct TranscodeScanSummary {
+    /// 成功分析（含命中已有结果）的歌曲数
+    pub analyzed: usize,
+    /// 其中被标记为可疑的数量（转码检测：疑似转码；内容哈希：疑似损坏）
+    pub suspicious: usize,
+    /// 分析失败的歌曲数
+    pub failed: usize,
+}
+
+/// 后台批量分析任务的句柄，调用方凭 `task_id` 取消任务。
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisJob {
+    pub task_id: String,
+    /// 待分析歌曲数
+    pub total: usize,
+    /// `true` 表示已有批量任务在运行，本次请求被合并到该任务
+    pub already_running: bool,
+}
+
+/// 分析管理器。
+///
+/// 技术信息读取很快，只缓存在内存中；转码检测、前奏 / 尾奏、人声检测、能量评分和指纹需要完整解码，
+/// 结果持久化到 `analysis.json`。
+/// 完整解码都经过 [`AnalysisScheduler`] 限流，同一时间只允许一个批量任务。
+pub struct AnalysisManager {
+    technical: Mutex<HashMap<String, Cached<TechnicalInfo>>>,
+    store: PersistentStore,
+    scheduler: AnalysisScheduler,
+    /// 正在运行的批量任务
+    batch_job: Mutex<Option<AnalysisJob>>,
+    /// 逐曲分析结果是否同时记录内容哈希，见 [`set_content_hash_keys`](Self::set_content_hash_keys)
+    content_hash_keys: AtomicBool,
+}
+
+impl AnalysisManager {
+    pub fn new(path: PathBuf, power: Arc<PowerMonitor>) -> Self {
+        let store = PersistentStore::new(path);
+        // 逐曲结果以子键写入，新建的 analysis.json 需要先有各自的空对象
+        for key in [TRANSCODE_KEY, SEGMENTS_KEY, VOCALS_KEY, ENERGY_KEY, FINGERPRINT_KEY] {
+            if !store.has(key) {
+                store.set_raw(key, serde_json::Value::Object(Default::default()));
+            }
+        }
+        Self {
+            technical: Mutex::new(HashMap::new()),
+            store,
+            scheduler: AnalysisScheduler::with_default_limit().with_power(power),
+            batch_job: Mutex::new(None),
+            content_hash_keys: AtomicBool::new(false),
+        }
+    }
+
+    /// 开启后，新的逐曲分析结果附带内容哈希，改标签后不必重新分析。
+    ///
+    /// 每次分析多读一遍文件，随 [`content_hash::ENABLED_CONFIG_KEY`] 一起开关。
+    pub fn set_content_hash_keys(&self, enabled: bool) {
+        self.content_hash_keys.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 计算文件的音频内容哈希，返回哈希和计算时的文件 mtime。
+    ///
+    /// 需要读完整个文件，与完整解码一样经过调度器排队。
+    pub fn content_hash(
+        &self,
+        path: &str,
+        priority: AnalysisPriority,
+        token: Option<&CancellationToken>,
+    ) -> Result<(String, u64), String> {
+        let platform_path = PlatformPath::from(path);
+        let mtime = platform::file_modified_secs(&platform_path).unwrap_or(0);
+        let _permit = self
+            .scheduler
+            .acquire(priority, token)
+            .ok_or("分析任务已取消")?;
+        Ok((content_hash::hash_file(&platform_path)?, mtime))
+    }
+
+    /// 获取文件的技术信息（首次读取文件，之后命中缓存直到文件被修改）。
+    pub fn technical_info(&self, path: &str) -> Result<Arc<TechnicalInfo>, String> {
+        let platform_path = PlatformPath::from(path);
+        let mtime = platform::file_modified_secs(&platform_path).unwrap_or(0);
+        if let Some(hit) = self.technical.lock().get(path) {
+            if hit.mtime == mtime {
+                return Ok(hit.value.clone());
+            }
+        }
+
+        let value = Arc::new(technical::read_technical_info(&platform_path)?);
+        self.technical.lock().insert(
+            path.to_string(),
+            Cached {
+                mtime,
+                value: value.clone(),
+            },
+        );
+        Ok(value)
+    }
+
+    /// 获取歌曲的转码检测结果；文件未变化时直接返回上次的结果。
+    ///
+    /// 有损文件不做频谱分析，直接返回置信度为 0 的结果。
+    /// 以交互优先级排队，优先于进行中的批量任务。
+    pub fn transcode_verdict(&self, song_id: &str, path: &str) -> Result<TranscodeVerdict, String> {
+        self.transcode_verdict_with(song_id, path, AnalysisPriority::Interactive, None)
+    }
+
+    /// 获取歌曲的前奏 / 尾奏边界；文件未变化时直接返回上次的结果。
+    pub fn track_segments(&self, song_id: &str, input: &AudioInput) -> Result<TrackSegments, String> {
+        self.stored_analysis(SEGMENTS_KEY, song_id, input, segments::analyze_file)
+    }
+
+    /// 获取歌曲的人声区间；文件未变化时直接返回上次的结果。
+    pub fn vocal_map(&self, song_id: &str, input: &AudioInput) -> Result<VocalMap, String> {
+        self.stored_analysis(VOCALS_KEY, song_id, input, vocals::analyze_file)
+    }
+
+    /// 获取歌曲的能量评分；文件未变化时直接返回上次的结果。
+    pub fn energy_profile(&self, song_id: &str, input: &AudioInput) -> Result<EnergyProfile, String> {
+        self.stored_analysis(ENERGY_KEY, song_id, input, energy::analyze_file)
+    }
+
+    /// 获取歌曲的音频指纹；文件未变化时直接返回上次的结果。
+    pub fn fingerprint(&self, song_id: &str, input: &AudioInput) -> Result<Fingerprint, String> {
+        self.stored_analysis(FINGERPRINT_KEY, song_id, input, fingerprint::analyze_file)
+    }
+
+    /// 以交互优先级执行需要完整解码的逐曲分析，结果按 `key/song_id` 持久化。
+    ///
+    /// 媒体缓存副本以来源键代替路径记录、mtime 记为 0，只要来源键不变就命中。
+    fn stored_analysis<T>(
+        &self,
+        key: &str,
+        song_id: &str,
+        input: &AudioInput,
+        analyze: impl FnOnce(&PlatformPath) -> Result<T, String>,
+    ) -> Result<T, String>
+    where
+        T: Serialize + DeserializeOwned + Clone,
+    {
+        self.stored_analysis_with(key, song_id, input, AnalysisPriority::Interactive, None, analyze)
+    }
+
+    fn stored_analysis_with<T>(
+        &self,
+        key: &str,
+        song_id: &str,
+        input: &AudioInput,
+        priority: AnalysisPriority,
+        token: Option<&CancellationToken>,
+        analyze: impl FnOnce(&PlatformPath) -> Result<T, String>,
+    ) -> Result<T, String>
+    where
+        T: Serialize + DeserializeOwned + Clone,
+    {
+        let platform_path = PlatformPath::from(input.path());
+        let (path, mtime) = match input {
+            AudioInput::Local(path) => (path.as_str(), platform::file_modified_secs(&platform_path).unwrap_or(0)),
+            AudioInput::Cached { key, .. } => (key.as_str(), 0),
+        };
+        let previous = self.store.get_entry::<StoredResult<T>>(key, song_id);
+        if let Some(record) = &previous {
+            if record.mtime == mtime && record.path == path {
+                return Ok(record.result.clone());
+            }
+        }
+
+        let _permit = self
+            .scheduler
+            .acquire(priority, token)
+            .ok_or("分析任务已取消")?;
+        let current_hash = match &previous {
+            Some(record) if record.content_hash.is_some() => content_hash::hash_file(&platform_path).ok(),
+            _ if self.content_hash_keys.load(Ordering::Relaxed) => content_hash::hash_file(&platform_path).ok(),
+            _ => None,
+        };
+        let record = match previous {
+            Some(record) if current_hash.is_some() && record.content_hash == current_hash => StoredResult {
+                path: path.to_string(),
+                mtime,
+                ..record
+            },
+            _ => StoredResult {
+                path: path.to_string(),
+                mtime,
+                content_hash: current_hash,
+                result: analyze(&platform_path)?,
+            },
+        };
+        let result = record.result.clone();
+        self.store.set_subkey(key, song_id, &record)?;
+        self.store.save_if_dirty()?;
+        Ok(result)
+    }
+
+    /// 当前正在运行的批量任务。
+    pub fn running_job(&self) -> Option<AnalysisJob> {
+        self.batch_job.lock().clone()
+    }
+
+    fn transcode_verdict_with(
+        &self,
+        song_id: &str,
+        path: &str,
+        priority: AnalysisPriority,
+        token: Option<&CancellationToken>,
+    ) -> Result<TranscodeVerdict, String> {
+        let platform_path = PlatformPath::from(path);
+        let mtime = platform::file_modified_secs(&platform_path).unwrap_or(0);
+        if let Some(record) = self.store.get_entry::<TranscodeRecord>(TRANSCODE_KEY, song_id) {
+            if record.mtime == mtime && record.path == path {
+                return Ok(record.verdict);
+            }
+        }
+
+        let info = self.technical_info(path)?;
+        let verdict = if info.lossless {
+            let _permit = self
+                .scheduler
+                .acquire(priority, token)
+                .ok_or("分析任务已取消")?;
+            transcode::analyze_file(&platform_path)?
+        } else {
+            TranscodeVerdict::not_applicable(info.sample_rate.unwrap_or(0) / 2)
+        };
+
+        let record = TranscodeRecord {
+            song_id: song_id.to_string(),
+            path: path.to_string(),
+            mtime,
+            verdict: verdict.clone(),
+        };
+        self.store.set_subkey(TRANSCODE_KEY, song_id, &record)?;
+        self.store.save_if_dirty()?;
+        Ok(verdict)
+    }
+
+    /// 在后台线程批量检测 `(歌曲 ID, 文件路径)`，立即返回任务句柄。
+    ///
+    /// 同一时间只运行一个批量任务：已有任务时直接返回该任务的句柄，不重复启动。
+    /// 进度与结束通过事件总线发布（[`AppEvent::AnalysisProgress`] /
+    /// [`AppEvent::AnalysisFinished`]），取消通过 `tasks` 中以 `task_id` 登记的令牌。
+    pub fn spawn_transcode_scan(
+        self: &Arc<Self>,
+        task_id: &str,
+        songs: Vec<(String, String)>,
+        tasks: Arc<CancellationRegistry>,
+        events: Arc<EventBus>,
+    ) -> Result<AnalysisJob, String> {
+        self.spawn_batch(task_id, songs, tasks, events, |manager, song_id, path, token| {
+            manager
+                .transcode_verdict_with(song_id, path, AnalysisPriority::Batch, Some(token))
+                .map(|verdict| verdict.suspicious)
+        })
+    }
+
+    /// 在后台批量计算能量评分，任务句柄、进度事件与取消方式同
+    /// [`spawn_transcode_scan`](Self::spawn_transcode_scan)。已评分且文件未变化的歌曲直接跳过。
+    pub fn spawn_energy_scan(
+        self: &Arc<Self>,
+        task_id: &str,
+        songs: Vec<(String, String)>,
+        tasks: Arc<CancellationRegistry>,
+        events: Arc<EventBus>,
+    ) -> Result<AnalysisJob, String> {
+        self.spawn_batch(task_id, songs, tasks, events, |manager, song_id, path, token| {
+            let input = AudioInput::Local(path.to_string());
+            manager
+                .stored_analysis_with(
+                    ENERGY_KEY,
+                    song_id,
+                    &input,
+                    AnalysisPriority::Batch,
+                    Some(token),
+                    energy::analyze_file,
+                )
+                .map(|_| false)
+        })
+    }
+
+    /// 在后台线程对每首歌执行 `work`，任务句柄、进度事件与取消方式同
+    /// [`spawn_transcode_scan`](Self::spawn_transcode_scan)。
+    ///
+    /// `work` 返回 `Ok(true)` 的歌曲计入 [`TranscodeScanSummary::suspicious`]。
+    pub fn spawn_batch<F>(
+        self: &Arc<Self>,
+        task_id: &str,
+        songs: Vec<(String, String)>,
+        tasks: Arc<CancellationRegistry>,
+        events: Arc<EventBus>,
+        work: F,
+    ) -> Result<AnalysisJob, String>
+    where
+        F: Fn(&Self, &str, &str, &CancellationToken) -> Result<bool, String> + Send + Sync + 'static,
+    {
+        let mut running = self.batch_job.lock();
+        if let Some(job) = running.as_ref() {
+            return Ok(AnalysisJob {
+                already_running: true,
+                ..job.clone()
+            });
+        }
+        let job = AnalysisJob {
+            task_id: task_id.to_string(),
+            total: songs.len(),
+            already_running: false,
+        };
+
+        let token = tasks.register(task_id);
+        let manager = self.clone();
+        let task_id = task_id.to_string();
+        let thread_token = token.clone();
+        let thread_tasks = tasks.clone();
+        thread::Builder::new()
+            .name("analysis-batch".into())
+            .spawn(move || {
+                let total = songs.len();
+                let summary = manager.run_batch(&songs, &thread_token, &work, |done| {
+                    events.publish(AppEvent::AnalysisProgress {
+                        task_id: task_id.clone(),
+                        done,
+                        total,
+                    });
+                });
+                *manager.batch_job.lock() = None;
+                thread_tasks.finish(&task_id, &thread_token);
+                events.publish(AppEvent::AnalysisFinished {
+                    task_id,
+                    summary,
+                    cancelled: thread_token.is_cancelled(),
+                });
+            })
+            .map_err(|e| {
+                tasks.finish(&job.task_id, &token);
+                format!("启动分析任务失败: {}", e)
+            })?;
+
+        *running = Some(job.clone());
+        Ok(job)
+    }
+
+    /// 以批量优先级检测 `(歌曲 ID, 文件路径)`；单个文件失败只计数，不中断整批。
+    ///
+    /// 工作线程数等于调度器留给批量任务的名额数。取消后不再领取新文件，
+    /// 返回已完成部分的统计。
+    pub fn scan_transcodes(
+        &self,
+        songs: &[(String, String)],
+        token: &CancellationToken,
+        on_progress: impl Fn(usize) + Sync,
+    ) -> TranscodeScanSummary {
+        let work = |manager: &Self, song_id: &str, path: &str, token: &CancellationToken| {
+            manager
+                .transcode_verdict_with(song_id, path, AnalysisPriority::Batch, Some(token))
+                .map(|verdict| verdict.suspicious)
+        };
+        self.run_batch(songs, token, &work, on_progress)
+    }
+
+    fn run_batch(
+        &self,
+        songs: &[(String, String)],
+        token: &CancellationToken,
+        work: &(impl Fn(&Self, &str, &str, &CancellationToken) -> Result<bool, String> + Sync),
+        on_progress: impl Fn(usize) + Sync,
+    ) -> TranscodeScanSummary {
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let summary = Mutex::new(TranscodeScanSummary::default());
+        let workers = self.scheduler.batch_limit().min(songs.len());
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    if token.is_cancelled() {
+                        break;
+                    }
+                    let Some((song_id, path)) = songs.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                        break;
+                    };
+                    let result = work(self, song_id, path, token);
+                    if token.is_cancelled() && result.is_err() {
+                        break;
+                    }
+                    {
+                        let mut summary = summary.lock();
+                        match result {
+                            Ok(suspicious) => {
+                                summary.analyzed += 1;
+                                if suspicious {
+                                    summary.suspicious += 1;
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("[analysis] 分析失败 {}: {}", path, e);
+                                summary.failed += 1;
+                            }
+                        }
+                    }
+                    on_progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+                });
+            }
+        });
+        summary.into_inner()
+    }
+
+    /// 已分析过且置信度不低于 `min_confidence` 的记录，按置信度降序。
+    pub fn suspected_transcodes(&self, min_confidence: f32) -> Vec<TranscodeRecord> {
+        let mut records: Vec<TranscodeRecord> = self
+            .store
+            .get_all_entries::<TranscodeRecord>(TRANSCODE_KEY)
+            .into_iter()
+            .filter(|r| r.verdict.cutoff_hz.is_some() && r.verdict.confidence >= min_confidence)
+            .collect();
+        records.sort_by(|a, b| b.verdict.confidence.total_cmp(&a.verdict.confidence));
+        records
+    }
+
+    /// 已评分且能量落在 `[min_energy, max_energy]` 内的歌曲 ID 与评分，按能量排序
+    /// （`descending` 为真时从高到低），能量相同时按可舞性。
+    pub fn songs_by_energy(&self, min_energy: u8, max_energy: u8, descending: bool) -> Vec<(String, EnergyProfile)> {
+        let mut songs: Vec<(String, EnergyProfile)> = self
+            .store
+            .get_all_map::<StoredResult<EnergyProfile>>(ENERGY_KEY)
+            .into_iter()
+            .map(|(song_id, record)| (song_id, record.result))
+            .filter(|(_, p)| (min_energy..=max_energy).contains(&p.energy))
+            .collect();
+        songs.sort_by_key(|(_, p)| (p.energy, p.danceability));
+        if descending {
+            songs.reverse();
+        }
+        songs
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::config::store::ConfigStore;
+
+    #[test]
+    fn test_cached_input_keyed_by_source() {
+        let dir = std::env::temp_dir().join(format!("chordial_analysis_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let power = Arc::new(PowerMonitor::new(Arc::new(ConfigStore::new(dir.join("config.json")))));
+        let manager = AnalysisManager::new(dir.join("analysis.json"), power);
+        let file = dir.join("0123abcd.flac");
+        std::fs::write(&file, b"").unwrap();
+
+        let input = AudioInput::Cached {
+            path: file.to_string_lossy().into_owned(),
+            key: "peer:song-1".to_string(),
+        };
+        let mut runs = 0;
+        let mut analyze = |input: &AudioInput| {
+            manager
+                .stored_analysis(SEGMENTS_KEY, "song-1", input, |_| {
+                    runs += 1;
+                    Ok(runs)
+                })
+                .unwrap()
+        };
+        assert_eq!(analyze(&input), 1);
+        // 缓存命中刷新了 mtime，来源键不变时沿用结果
+        std::fs::File::options()
+            .append(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(analyze(&input), 1);
+        // 换了转码质量（来源键不同）时重新分析
+        let other = AudioInput::Cached {
+            path: file.to_string_lossy().into_owned(),
+            key: "peer:song-1@128k.opus".to_string(),
+        };
+        assert_eq!(analyze(&other), 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
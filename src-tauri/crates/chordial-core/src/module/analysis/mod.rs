@@ -0,0 +1,40 @@
+//! 音频分析模块 — 读取 / 计算音频文件的技术属性。
+//!
+//! 与扫描阶段的 [`scanner`](crate::module::music_localSource::scanner) 不同，
+//! 这里的信息只在前端请求时才按需读取，结果按文件缓存（文件修改后自动失效）。
+//! 需要完整解码的分析（转码检测、前奏 / 尾奏、人声检测、能量评分、指纹）结果会持久化，避免重复解码。
+//!
+//! # 模块布局
+//!
+//! | 文件 | 职责 |
+//! |------|------|
+//! | [`decode`] | 完整 PCM 解码（供逐样本分析） |
+//! | [`technical`] | 编码 / 位深 / 采样率 / 实际码率 / 编码器等技术信息 |
+//! | [`transcode`] | 频谱截止检测 — 识别有损转无损的「假无损」 |
+//! | [`segments`] | 前奏 / 尾奏检测 — 供自动混音安排过渡 |
+//! | [`vocals`] | 人声活动检测 — 卡拉 OK 辅助 / 歌词演唱提示 |
+//! | [`energy`] | 能量 / 可舞性评分 — 按能量排序、筛选（运动 / 睡前歌单） |
+//! | [`fingerprint`] | 响度包络指纹 — 判断两个文件是否为同一录音 |
+//! | [`content_hash`] | 音频内容哈希 — 精确去重 / 分析缓存键 / 位腐检测 |
+//! | [`scheduler`] | 解码并发限制 + 交互 / 批量优先级 |
+//! | [`manager`] | `AnalysisManager` — 按需分析 + 结果缓存 + 后台批量任务（远程歌曲经媒体缓存分析） |
+
+pub mod content_hash;
+pub mod decode;
+pub mod energy;
+pub mod fingerprint;
+pub mod manager;
+pub mod scheduler;
+pub mod segments;
+pub mod technical;
+pub mod transcode;
+pub mod vocals;
+
+pub use energy::{EnergyLevel, EnergyProfile};
+pub use fingerprint::Fingerprint;
+pub use manager::{AnalysisJob, AnalysisManager, AudioInput, TranscodeRecord, TranscodeScanSummary};
+pub use scheduler::AnalysisPriority;
+pub use segments::TrackSegments;
+pub use technical::TechnicalInfo;
+pub use transcode::TranscodeVerdict;
+pub use vocals::{VocalMap, VocalSegment};
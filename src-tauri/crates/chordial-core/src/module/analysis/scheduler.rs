@@ -0,0 +1,161 @@
+//! 分析调度器 — 限制并发解码数，并让交互请求优先于批量任务。
+//!
+//! 每次完整解码前先向调度器申请一个执行名额（[`AnalysisPermit`]），名额随 permit 释放。
+//! 批量任务最多占用 `max_concurrent - 1` 个名额，始终给交互请求留一个空位；
+//! 有交互请求排队时，批量任务不再领取新名额，直到交互请求全部得到执行。
+//! 正在进行的解码不会被打断——抢占发生在批量任务处理下一个文件之前。
+//!
+//! 挂接 [`PowerMonitor`] 后，批量名额还受电源策略限制（电池供电时降为 1 个或暂停）。
+
+use crate::module::cancel::CancellationToken;
+use crate::module::power::PowerMonitor;
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 等待名额时检查取消状态的间隔。
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 分析请求的优先级。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisPriority {
+    /// 用户在界面上针对单首歌曲发起，需要尽快返回
+    Interactive,
+    /// 整库扫描等后台任务
+    Batch,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    running: usize,
+    running_batch: usize,
+    waiting_interactive: usize,
+}
+
+/// 分析并发调度器。
+pub struct AnalysisScheduler {
+    state: Mutex<SchedulerState>,
+    cond: Condvar,
+    max_concurrent: usize,
+    power: Option<Arc<PowerMonitor>>,
+}
+
+/// 执行名额，drop 时归还。
+pub struct AnalysisPermit<'a> {
+    scheduler: &'a AnalysisScheduler,
+    priority: AnalysisPriority,
+}
+
+impl AnalysisScheduler {
+    /// `max_concurrent` 为同时进行的解码数上限（至少为 1）。
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            state: Mutex::new(SchedulerState::default()),
+            cond: Condvar::new(),
+            max_concurrent: max_concurrent.max(1),
+            power: None,
+        }
+    }
+
+    /// 按电源策略限制批量名额。
+    pub fn with_power(mut self, power: Arc<PowerMonitor>) -> Self {
+        self.power = Some(power);
+        self
+    }
+
+    /// 按 CPU 核数的一半设置并发上限，避免批量分析占满所有核心。
+    pub fn with_default_limit() -> Self {
+        let cores = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Self::new(cores / 2)
+    }
+
+    /// 批量任务可同时占用的名额数。
+    pub fn batch_limit(&self) -> usize {
+        (self.max_concurrent - 1).max(1)
+    }
+
+    /// 申请执行名额，必要时阻塞等待。
+    ///
+    /// 等待期间 `token` 被取消则返回 `None`。
+    pub fn acquire(
+        &self,
+        priority: AnalysisPriority,
+        token: Option<&CancellationToken>,
+    ) -> Option<AnalysisPermit<'_>> {
+        let mut state = self.state.lock();
+        if priority == AnalysisPriority::Interactive {
+            state.waiting_interactive += 1;
+        }
+        loop {
+            if self.admits(&state, priority) {
+                break;
+            }
+            if token.is_some_and(|t| t.is_cancelled()) {
+                if priority == AnalysisPriority::Interactive {
+                    state.waiting_interactive -= 1;
+                }
+                return None;
+            }
+            self.cond.wait_for(&mut state, CANCEL_POLL_INTERVAL);
+        }
+        state.running += 1;
+        match priority {
+            AnalysisPriority::Interactive => state.waiting_interactive -= 1,
+            AnalysisPriority::Batch => state.running_batch += 1,
+        }
+        Some(AnalysisPermit {
+            scheduler: self,
+            priority,
+        })
+    }
+
+    fn admits(&self, state: &SchedulerState, priority: AnalysisPriority) -> bool {
+        if state.running >= self.max_concurrent {
+            return false;
+        }
+        match priority {
+            AnalysisPriority::Interactive => true,
+            AnalysisPriority::Batch => {
+                let limit = self
+                    .power
+                    .as_ref()
+                    .map_or(self.batch_limit(), |p| p.batch_analysis_limit(self.batch_limit()));
+                state.waiting_interactive == 0 && state.running_batch < limit
+            }
+        }
+    }
+}
+
+impl Drop for AnalysisPermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.scheduler.state.lock();
+        state.running -= 1;
+        if self.priority == AnalysisPriority::Batch {
+            state.running_batch -= 1;
+        }
+        drop(state);
+        self.scheduler.cond.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_leaves_slot_for_interactive() {
+        let scheduler = AnalysisScheduler::new(2);
+        let token = CancellationToken::new();
+        let _batch = scheduler.acquire(AnalysisPriority::Batch, Some(&token)).unwrap();
+
+        // 批量任务已用满自己的名额，第二个批量请求只能等待
+        token.cancel();
+        assert!(scheduler.acquire(AnalysisPriority::Batch, Some(&token)).is_none());
+
+        // 交互请求仍能立即拿到保留的名额
+        let interactive = scheduler.acquire(AnalysisPriority::Interactive, None);
+        assert!(interactive.is_some());
+    }
+}
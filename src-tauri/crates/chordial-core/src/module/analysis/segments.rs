@@ -0,0 +1,282 @@
+//! 前奏 / 尾奏检测 — 找出歌曲开头和结尾的低能量、少人声段落，供自动混音安排过渡。
+//!
+//! 分析流程：
+//! 1. 按 [`WINDOW_MS`] 窗口计算混合单声道的整体电平，以及人声频段（约 300–3400 Hz，
+//!    两个一阶低通相减得到的粗略带通）电平
+//! 2. 以非静音窗口的中位数作为「主体」参考电平
+//! 3. 整体电平明显低于主体，或人声频段占比明显低于主体的窗口记为「铺垫」窗口
+//! 4. 从第一个有声窗口起，直到连续 [`SUSTAIN_WINDOWS`] 个非铺垫窗口出现为止即为前奏；
+//!    尾奏从末尾反向同理
+//!
+//! 自动混音在上一首的尾奏内淡入下一首（电台式「压着尾奏进歌」），
+//! 尾奏越长，过渡越长。检测是启发式的，短于 [`MIN_SEGMENT_MS`] 的段落不报告。
+
+use super::decode::{decode_file, PcmBlock};
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+
+/// 分析窗口长度（毫秒）。
+const WINDOW_MS: u64 = 500;
+
+/// 低于该电平（dBFS）的窗口视为静音。
+const SILENCE_DB: f32 = -50.0;
+
+/// 整体电平比主体低多少 dB 视为铺垫。
+const LOW_ENERGY_DB: f32 = 8.0;
+
+/// 人声频段占比比主体低多少 dB 视为铺垫。
+const LOW_VOICE_DB: f32 = 6.0;
+
+/// 连续多少个非铺垫窗口才算进入主体（避免鼓点等瞬态误判）。
+const SUSTAIN_WINDOWS: usize = 4;
+
+/// 前奏 / 尾奏的最短时长（毫秒）。
+pub const MIN_SEGMENT_MS: u64 = 3000;
+
+/// 前奏 / 尾奏最多占全曲的比例；超过说明整首都很安静，不做划分。
+const MAX_SEGMENT_RATIO: f64 = 0.4;
+
+/// 人声频段边界（Hz）。
+const VOICE_BAND: (f32, f32) = (300.0, 3400.0);
+
+const FLOOR_DB: f32 = -120.0;
+
+/// 单首歌曲的段落边界（毫秒）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TrackSegments {
+    pub duration_ms: u64,
+    /// 第一个有声窗口的起点
+    pub audio_start_ms: u64,
+    /// 最后一个有声窗口的终点
+    pub audio_end_ms: u64,
+    /// 前奏结束位置；没有明显前奏时为 `None`
+    pub intro_end_ms: Option<u64>,
+    /// 尾奏开始位置；没有明显尾奏时为 `None`
+    pub outro_start_ms: Option<u64>,
+}
+
+impl TrackSegments {
+    /// 尾奏时长（到最后一个有声位置为止），没有尾奏时为 0。
+    pub fn outro_ms(&self) -> u64 {
+        self.outro_start_ms
+            .map_or(0, |start| self.audio_end_ms.saturating_sub(start))
+    }
+}
+
+/// 单个窗口的电平（dBFS）。
+#[derive(Debug, Clone, Copy)]
+pub struct WindowLevel {
+    pub total_db: f32,
+    pub voice_db: f32,
+}
+
+/// 流式段落检测器：依次喂入 PCM，最后 [`finish`](Self::finish) 得到结果。
+pub struct SegmentDetector {
+    window_frames: usize,
+    voice_band: VoiceBandFilter,
+    acc_total: f64,
+    acc_voice: f64,
+    acc_frames: usize,
+    levels: Vec<WindowLevel>,
+}
+
+impl SegmentDetector {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            window_frames: (sample_rate as u64 * WINDOW_MS / 1000).max(1) as usize,
+            voice_band: VoiceBandFilter::new(sample_rate),
+            acc_total: 0.0,
+            acc_voice: 0.0,
+            acc_frames: 0,
+            levels: Vec::new(),
+        }
+    }
+
+    /// 喂入交织样本。
+    pub fn push(&mut self, samples: &[f32], channels: usize) {
+        for frame in samples.chunks(channels.max(1)) {
+            let mono = frame.iter().sum::<f32>() / frame.len() as f32;
+            let voice = self.voice_band.process(mono);
+            self.acc_total += (mono as f64) * (mono as f64);
+            self.acc_voice += (voice as f64) * (voice as f64);
+            self.acc_frames += 1;
+            if self.acc_frames == self.window_frames {
+                self.flush_window();
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> TrackSegments {
+        // 不足半个窗口的尾巴直接丢弃，避免极短窗口干扰判断
+        if self.acc_frames * 2 >= self.window_frames {
+            self.flush_window();
+        }
+        find_segments(&self.levels)
+    }
+
+    fn flush_window(&mut self) {
+        self.levels.push(WindowLevel {
+            total_db: rms_db(self.acc_total, self.acc_frames),
+            voice_db: rms_db(self.acc_voice, self.acc_frames),
+        });
+        self.acc_total = 0.0;
+        self.acc_voice = 0.0;
+        self.acc_frames = 0;
+    }
+}
+
+/// 人声频段（[`VOICE_BAND`]）的粗略带通：两个一阶低通相减。
+///
+/// 过渡带很宽，只用于估计人声频段能量，不适合做信号处理。
+pub(crate) struct VoiceBandFilter {
+    lp_high: f32,
+    lp_low: f32,
+    coef_high: f32,
+    coef_low: f32,
+}
+
+impl VoiceBandFilter {
+    pub(crate) fn new(sample_rate: u32) -> Self {
+        let coef =
+            |cutoff: f32| 1.0 - (-std::f32::consts::TAU * cutoff / sample_rate.max(1) as f32).exp();
+        Self {
+            lp_high: 0.0,
+            lp_low: 0.0,
+            coef_high: coef(VOICE_BAND.1),
+            coef_low: coef(VOICE_BAND.0),
+        }
+    }
+
+    pub(crate) fn process(&mut self, x: f32) -> f32 {
+        self.lp_high += self.coef_high * (x - self.lp_high);
+        self.lp_low += self.coef_low * (x - self.lp_low);
+        self.lp_high - self.lp_low
+    }
+}
+
+/// 解码文件并检测前奏 / 尾奏。
+pub fn analyze_file(path: &PlatformPath) -> Result<TrackSegments, String> {
+    let mut detector: Option<SegmentDetector> = None;
+    decode_file(path, |block: PcmBlock<'_>| {
+        detector
+            .get_or_insert_with(|| SegmentDetector::new(block.sample_rate))
+            .push(block.samples, block.channels);
+    })?;
+    Ok(detector.map(SegmentDetector::finish).unwrap_or_default())
+}
+
+pub(crate) fn rms_db(sum_sq: f64, frames: usize) -> f32 {
+    let rms = (sum_sq / frames.max(1) as f64).sqrt() as f32;
+    if rms > 0.0 {
+        (20.0 * rms.log10()).max(FLOOR_DB)
+    } else {
+        FLOOR_DB
+    }
+}
+
+/// 由逐窗口电平（每个元素对应 [`WINDOW_MS`]）计算段落边界。
+pub fn find_segments(levels: &[WindowLevel]) -> TrackSegments {
+    let duration_ms = levels.len() as u64 * WINDOW_MS;
+    let audible = |l: &WindowLevel| l.total_db >= SILENCE_DB;
+    let (Some(first), Some(last)) = (
+        levels.iter().position(audible),
+        levels.iter().rposition(audible),
+    ) else {
+        return TrackSegments {
+            duration_ms,
+            ..Default::default()
+        };
+    };
+    let mut segments = TrackSegments {
+        duration_ms,
+        audio_start_ms: first as u64 * WINDOW_MS,
+        audio_end_ms: (last as u64 + 1) * WINDOW_MS,
+        intro_end_ms: None,
+        outro_start_ms: None,
+    };
+
+    let body = &levels[first..=last];
+    let mut totals: Vec<f32> = body
+        .iter()
+        .filter(|l| audible(l))
+        .map(|l| l.total_db)
+        .collect();
+    let mut ratios: Vec<f32> = body
+        .iter()
+        .filter(|l| audible(l))
+        .map(|l| l.voice_db - l.total_db)
+        .collect();
+    let (body_db, body_ratio) = (median(&mut totals), median(&mut ratios));
+    let is_lead = |l: &WindowLevel| {
+        !audible(l)
+            || l.total_db < body_db - LOW_ENERGY_DB
+            || l.voice_db - l.total_db < body_ratio - LOW_VOICE_DB
+    };
+    let flags: Vec<bool> = body.iter().map(is_lead).collect();
+    let max_windows = (body.len() as f64 * MAX_SEGMENT_RATIO) as usize;
+    let min_windows = (MIN_SEGMENT_MS / WINDOW_MS) as usize;
+
+    // 前奏：第一段持续的非铺垫窗口之前
+    let intro = lead_length(flags.iter().copied());
+    if (min_windows..=max_windows).contains(&intro) {
+        segments.intro_end_ms = Some((first + intro) as u64 * WINDOW_MS);
+    }
+    let outro = lead_length(flags.iter().rev().copied());
+    if (min_windows..=max_windows).contains(&outro) {
+        segments.outro_start_ms = Some((last + 1 - outro) as u64 * WINDOW_MS);
+    }
+    segments
+}
+
+/// 开头的铺垫窗口数：遇到连续 [`SUSTAIN_WINDOWS`] 个非铺垫窗口时停止。
+fn lead_length(flags: impl Iterator<Item = bool>) -> usize {
+    let mut run = 0;
+    for (i, lead) in flags.enumerate() {
+        if lead {
+            run = 0;
+        } else {
+            run += 1;
+            if run == SUSTAIN_WINDOWS {
+                return i + 1 - SUSTAIN_WINDOWS;
+            }
+        }
+    }
+    // 始终没有进入主体
+    usize::MAX
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    if values.is_empty() {
+        return FLOOR_DB;
+    }
+    values.sort_by(f32::total_cmp);
+    values[values.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_intro_and_outro_detected() {
+        let rate = 8000u32;
+        let tone = |secs: usize, amp: f32| -> Vec<f32> {
+            (0..rate as usize * secs)
+                .map(|n| (n as f32 * 1000.0 * std::f32::consts::TAU / rate as f32).sin() * amp)
+                .collect()
+        };
+        // 1s 静音 + 4s 轻柔前奏 + 20s 主体 + 6s 轻柔尾奏
+        let mut pcm = vec![0.0; rate as usize];
+        pcm.extend(tone(4, 0.05));
+        pcm.extend(tone(20, 0.5));
+        pcm.extend(tone(6, 0.05));
+
+        let mut detector = SegmentDetector::new(rate);
+        detector.push(&pcm, 1);
+        let segments = detector.finish();
+        assert_eq!(segments.audio_start_ms, 1000);
+        assert_eq!(segments.intro_end_ms, Some(5000));
+        assert_eq!(segments.outro_start_ms, Some(25_000));
+        assert_eq!(segments.outro_ms(), 6000);
+    }
+}
@@ -0,0 +1,243 @@
+//! 音轨技术信息 — 编码、位深、采样率、实际码率、编码器、声道布局、是否无损，以及库字段之外的其余标签。
+
+use crate::module::music_localSource::{mp4_tables, scanner};
+use crate::module::perf;
+use crate::module::platform::{self, PlatformPath};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use symphonia::core::audio::Channels;
+use symphonia::core::formats::probe::Hint;
+use symphonia::core::formats::{FormatOptions, TrackType};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, StandardTag};
+
+/// 读取文件头用于识别编码器字符串的字节数。
+const HEAD_BYTES: u64 = 256 * 1024;
+
+/// 音轨技术信息。
+#[derive(Debug, Clone, Serialize)]
+pub struct TechnicalInfo {
+    /// 容器格式（如 "flac"、"mp3"、"isomp4"）
+    pub container: String,
+    /// 编码格式（如 "flac"、"mp3"、"aac"）
+    pub codec: String,
+    /// 位深（仅 PCM / 无损格式有意义）
+    pub bit_depth: Option<u32>,
+    /// 采样率（Hz）
+    pub sample_rate: Option<u32>,
+    /// 声道数
+    pub channels: Option<u32>,
+    /// 声道布局（"mono" / "stereo" / "5.1" 等）
+    pub channel_layout: Option<String>,
+    /// 时长（毫秒）
+    pub duration_ms: Option<u64>,
+    /// 文件大小（字节）
+    pub file_size: u64,
+    /// 实际平均码率（kbps，按文件大小 / 时长计算）
+    pub bitrate_kbps: Option<u32>,
+    /// 编码器（LAME 头 / Vorbis vendor 字符串 / 编码器标签）
+    pub encoder: Option<String>,
+    /// 是否为无损编码
+    pub lossless: bool,
+    /// 未被库字段使用的其余标签（MusicBrainz ID、自定义 TXXX / Vorbis comment 等）
+    pub extra_tags: HashMap<String, Vec<String>>,
+}
+
+/// 读取文件的技术信息（只探测容器与编码参数，不解码音频）。
+pub fn read_technical_info(path: &PlatformPath) -> Result<TechnicalInfo, String> {
+    let _scope = perf::scope("analysis.technical_info");
+    let file_size = platform::file_size(path)?;
+    let src = platform::open_file(path)?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = platform::path_extension(path) {
+        hint.with_extension(&ext);
+    }
+
+    let mut format = symphonia::default::get_probe()
+        .probe(
+            &hint,
+            mss,
+            FormatOptions::default(),
+            MetadataOptions::default(),
+        )
+        .map_err(|e| format!("无法识别音频格式 '{}': {}", platform::path_to_string(path), e))?;
+
+    let container = format.format_info().short_name.to_string();
+    let track = format
+        .default_track(TrackType::Audio)
+        .ok_or_else(|| "文件中没有音频轨道".to_string())?;
+    let params = track
+        .codec_params
+        .as_ref()
+        .and_then(|p| p.audio())
+        .ok_or_else(|| "音频轨道缺少编解码参数".to_string())?;
+
+    let mut codec = scanner::codec_name(params.codec).unwrap_or_else(|| "unknown".to_string());
+    let mut lossless = scanner::is_lossless_codec(params.codec);
+    let sample_rate = params.sample_rate;
+    let channels = params.channels.as_ref().map(|c| c.count() as u32);
+    let channel_layout = params.channels.as_ref().map(channel_layout_name);
+    let mut bit_depth = if lossless {
+        params.bits_per_sample.or(params.bits_per_coded_sample)
+    } else {
+        None
+    };
+
+    let mut duration_ms = match (track.num_frames, sample_rate) {
+        (Some(frames), Some(rate)) if rate > 0 => Some(frames * 1000 / rate as u64),
+        _ => None,
+    };
+    let mut bitrate_kbps = duration_ms
+        .filter(|ms| *ms > 0)
+        .map(|ms| (file_size * 8 / ms) as u32);
+
+    // M4A：以音频轨道的采样表为准，按文件大小估算的码率会把封面算进去
+    if container == "isomp4" {
+        if let Ok(Some(info)) = mp4_tables::read_audio_info(path) {
+            codec = info.codec.name();
+            lossless = info.is_lossless();
+            bit_depth = info.bit_depth.or(bit_depth);
+            duration_ms = info.duration_ms.or(duration_ms);
+            bitrate_kbps = info.bitrate_kbps.or(bitrate_kbps);
+        }
+    }
+
+    // 编码器：优先文件头中的 LAME / vendor 字符串，其次标签
+    let mut encoder = read_head(path).and_then(|head| encoder_from_head(&head));
+    let mut extra_tags = HashMap::new();
+    if let Some(revision) = format.metadata().skip_to_latest() {
+        if encoder.is_none() {
+            encoder = revision.media.tags.iter().find_map(|tag| match &tag.std {
+                Some(StandardTag::Encoder(s)) | Some(StandardTag::EncoderSettings(s)) => {
+                    Some(s.to_string())
+                }
+                _ => None,
+            });
+        }
+        extra_tags = scanner::collect_extra_tags(&revision.media.tags);
+    }
+
+    Ok(TechnicalInfo {
+        container,
+        codec,
+        bit_depth,
+        sample_rate,
+        channels,
+        channel_layout,
+        duration_ms,
+        file_size,
+        bitrate_kbps,
+        encoder,
+        lossless,
+        extra_tags,
+    })
+}
+
+fn channel_layout_name(channels: &Channels) -> String {
+    match channels.count() {
+        1 => "mono".to_string(),
+        2 => "stereo".to_string(),
+        3 => "2.1".to_string(),
+        6 => "5.1".to_string(),
+        8 => "7.1".to_string(),
+        _ => channels.to_string(),
+    }
+}
+
+fn read_head(path: &PlatformPath) -> Option<Vec<u8>> {
+    let file = platform::open_file(path).ok()?;
+    let mut head = Vec::new();
+    file.take(HEAD_BYTES).read_to_end(&mut head).ok()?;
+    Some(head)
+}
+
+/// 从文件头字节中识别编码器字符串。
+///
+/// - MP3：Xing/Info 帧中的 LAME 标记（如 `LAME3.100`）
+/// - FLAC：VORBIS_COMMENT 块的 vendor 字符串
+/// - Ogg Vorbis / Opus：注释头的 vendor 字符串
+fn encoder_from_head(head: &[u8]) -> Option<String> {
+    if let Some(pos) = find(head, b"LAME") {
+        let tag: String = head[pos..]
+            .iter()
+            .take(9)
+            .take_while(|b| b.is_ascii_graphic())
+            .map(|&b| b as char)
+            .collect();
+        return Some(tag);
+    }
+    if head.starts_with(b"fLaC") {
+        return flac_vendor(&head[4..]);
+    }
+    for marker in [&b"\x03vorbis"[..], &b"OpusTags"[..]] {
+        if let Some(pos) = find(head, marker) {
+            return read_vendor(&head[pos + marker.len()..]);
+        }
+    }
+    None
+}
+
+/// 遍历 FLAC 元数据块，读取 VORBIS_COMMENT（类型 4）中的 vendor 字符串。
+fn flac_vendor(mut blocks: &[u8]) -> Option<String> {
+    while blocks.len() >= 4 {
+        let header = blocks[0];
+        let len = u32::from_be_bytes([0, blocks[1], blocks[2], blocks[3]]) as usize;
+        let body = blocks.get(4..4 + len)?;
+        if header & 0x7f == 4 {
+            return read_vendor(body);
+        }
+        if header & 0x80 != 0 {
+            break;
+        }
+        blocks = &blocks[4 + len..];
+    }
+    None
+}
+
+/// 读取「u32 小端长度 + UTF-8 字符串」形式的 vendor 字段。
+fn read_vendor(data: &[u8]) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let vendor = std::str::from_utf8(data.get(4..4 + len)?).ok()?.trim();
+    (!vendor.is_empty()).then(|| vendor.to_string())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoder_from_lame_header() {
+        let mut head = vec![0u8; 64];
+        head.extend_from_slice(b"Info\0\0\0\x0fLAME3.100\x55\x55");
+        assert_eq!(encoder_from_head(&head).as_deref(), Some("LAME3.100"));
+    }
+
+    #[test]
+    fn test_encoder_from_flac_vendor() {
+        let vendor = b"reference libFLAC 1.4.3 20230623";
+        let mut comment = (vendor.len() as u32).to_le_bytes().to_vec();
+        comment.extend_from_slice(vendor);
+        comment.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut head = b"fLaC".to_vec();
+        // STREAMINFO（34 字节，非最后一块）
+        head.extend_from_slice(&[0x00, 0, 0, 34]);
+        head.extend_from_slice(&[0u8; 34]);
+        // VORBIS_COMMENT（最后一块）
+        let len = comment.len() as u32;
+        head.extend_from_slice(&[0x84, (len >> 16) as u8, (len >> 8) as u8, len as u8]);
+        head.extend_from_slice(&comment);
+
+        assert_eq!(
+            encoder_from_head(&head).as_deref(),
+            Some("reference libFLAC 1.4.3 20230623")
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! 转码检测 — 识别由有损文件（MP3 / AAC 等）转换而来的「假无损」。
+//!
+//! 有损编码器会在 16–20 kHz 附近做低通，转成 FLAC 后这一截止频率依然存在，
+//! 而真正的无损母带通常一直延伸到奈奎斯特频率附近。检测流程：
+//! 1. 每隔 [`WINDOW_INTERVAL_SECS`] 取一个 Hann 窗口，用 Goertzel 算法测量
+//!    1 kHz 至奈奎斯特频率之间每 [`BAND_STEP_HZ`] 一个频点的能量
+//! 2. 所有窗口的能量取平均，以 1–4 kHz 为参考电平
+//! 3. 最后一个不低于「参考电平 − [`CUTOFF_DROP_DB`]」的频点即为截止频率
+//! 4. 截止之后 1 kHz 内出现陡峭断崖（≥ [`CLIFF_DB`]）是编码器低通的典型特征
+//!
+//! 截止频率越低、断崖越陡，置信度越高。结果仅作提示，不会自动修改库。
+
+use super::decode::{decode_file, PcmBlock};
+use crate::module::perf;
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+
+/// 单个分析窗口的帧数。
+const WINDOW_FRAMES: usize = 4096;
+
+/// 两个分析窗口起点之间的间隔（秒）。
+const WINDOW_INTERVAL_SECS: u32 = 2;
+
+/// 频点间隔（Hz）。
+const BAND_STEP_HZ: u32 = 250;
+
+/// 参考电平频段（Hz）。
+const REFERENCE_BAND: (u32, u32) = (1000, 4000);
+
+/// 低于参考电平多少 dB 视为「已无内容」。
+const CUTOFF_DROP_DB: f32 = 50.0;
+
+/// 截止处断崖判定阈值（dB）。
+const CLIFF_DB: f32 = 20.0;
+
+/// 置信度达到该值即标记为可疑。
+pub const SUSPICIOUS_CONFIDENCE: f32 = 0.5;
+
+/// 单个文件的转码检测结果。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscodeVerdict {
+    /// 检测到的高频截止（Hz）；频谱延伸到奈奎斯特频率或不适用时为 `None`
+    pub cutoff_hz: Option<u32>,
+    /// 奈奎斯特频率（采样率的一半）
+    pub nyquist_hz: u32,
+    /// 有损来源置信度（0.0–1.0）
+    pub confidence: f32,
+    /// 是否达到 [`SUSPICIOUS_CONFIDENCE`]
+    pub suspicious: bool,
+}
+
+impl TranscodeVerdict {
+    /// 不适用（有损文件 / 无声文件）时的结果。
+    pub fn not_applicable(nyquist_hz: u32) -> Self {
+        Self {
+            cutoff_hz: None,
+            nyquist_hz,
+            confidence: 0.0,
+            suspicious: false,
+        }
+    }
+}
+
+/// 解码文件并给出转码检测结果。调用方应确保文件为无损编码。
+pub fn analyze_file(path: &PlatformPath) -> Result<TranscodeVerdict, String> {
+    let _scope = perf::scope("analysis.transcode");
+    let mut sample_rate = 0u32;
+    let mut freqs: Vec<u32> = Vec::new();
+    let mut power_sum: Vec<f64> = Vec::new();
+    let mut windows = 0usize;
+
+    let hann: Vec<f32> = (0..WINDOW_FRAMES)
+        .map(|i| {
+            let x = std::f32::consts::TAU * i as f32 / (WINDOW_FRAMES - 1) as f32;
+            0.5 - 0.5 * x.cos()
+        })
+        .collect();
+    let mut window: Vec<f32> = Vec::with_capacity(WINDOW_FRAMES);
+    // 当前窗口开始前还需跳过的帧数
+    let mut skip = 0usize;
+
+    decode_file(path, |block: PcmBlock<'_>| {
+        if sample_rate == 0 {
+            sample_rate = block.sample_rate;
+            freqs = (REFERENCE_BAND.0..sample_rate / 2)
+                .step_by(BAND_STEP_HZ as usize)
+                .collect();
+            power_sum = vec![0.0; freqs.len()];
+        }
+        for frame in block.samples.chunks(block.channels) {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let mono = frame.iter().sum::<f32>() / block.channels as f32;
+            window.push(mono * hann[window.len()]);
+            if window.len() == WINDOW_FRAMES {
+                for (acc, &freq) in power_sum.iter_mut().zip(&freqs) {
+                    *acc += goertzel_power(&window, freq as f32 / sample_rate as f32);
+                }
+                windows += 1;
+                window.clear();
+                skip = ((sample_rate * WINDOW_INTERVAL_SECS) as usize).saturating_sub(WINDOW_FRAMES);
+            }
+        }
+    })?;
+
+    let nyquist_hz = sample_rate / 2;
+    if windows == 0 {
+        return Ok(TranscodeVerdict::not_applicable(nyquist_hz));
+    }
+    let spectrum: Vec<(u32, f32)> = freqs
+        .into_iter()
+        .zip(power_sum)
+        .map(|(freq, power)| (freq, power_db(power / windows as f64)))
+        .collect();
+    Ok(judge(&spectrum, nyquist_hz))
+}
+
+/// Goertzel 算法计算单个归一化频率（`freq / sample_rate`）处的能量。
+fn goertzel_power(samples: &[f32], norm_freq: f32) -> f64 {
+    let coeff = 2.0 * (std::f64::consts::TAU * norm_freq as f64).cos();
+    let (mut s1, mut s2) = (0.0f64, 0.0f64);
+    for &x in samples {
+        let s = x as f64 + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0)
+}
+
+fn power_db(power: f64) -> f32 {
+    if power > 0.0 {
+        (10.0 * power.log10()) as f32
+    } else {
+        -200.0
+    }
+}
+
+/// 由平均频谱（升序的 `(频率 Hz, 电平 dB)`）判定是否为有损转码。
+pub fn judge(spectrum: &[(u32, f32)], nyquist_hz: u32) -> TranscodeVerdict {
+    let reference: Vec<f32> = spectrum
+        .iter()
+        .filter(|(f, _)| (REFERENCE_BAND.0..=REFERENCE_BAND.1).contains(f))
+        .map(|&(_, db)| db)
+        .collect();
+    if reference.is_empty() {
+        return TranscodeVerdict::not_applicable(nyquist_hz);
+    }
+    let reference_db = reference.iter().sum::<f32>() / reference.len() as f32;
+    let floor_db = reference_db - CUTOFF_DROP_DB;
+
+    let Some(cutoff_idx) = spectrum.iter().rposition(|&(_, db)| db >= floor_db) else {
+        return TranscodeVerdict::not_applicable(nyquist_hz);
+    };
+    let (cutoff_hz, cutoff_db) = spectrum[cutoff_idx];
+    // 频谱延伸到奈奎斯特频率附近 — 没有截止
+    if cutoff_hz + 2 * BAND_STEP_HZ >= nyquist_hz {
+        return TranscodeVerdict::not_applicable(nyquist_hz);
+    }
+
+    let cliff = spectrum[cutoff_idx + 1..]
+        .iter()
+        .take_while(|&&(f, _)| f <= cutoff_hz + 1000)
+        .any(|&(_, db)| cutoff_db - db >= CLIFF_DB);
+
+    let base = match cutoff_hz {
+        0..=16_500 => 0.95,
+        16_501..=17_500 => 0.85,
+        17_501..=19_500 => 0.7,
+        19_501..=20_500 => 0.4,
+        _ => 0.2,
+    };
+    let confidence: f32 = if cliff { base } else { base * 0.5 };
+
+    TranscodeVerdict {
+        cutoff_hz: Some(cutoff_hz),
+        nyquist_hz,
+        confidence,
+        suspicious: confidence >= SUSPICIOUS_CONFIDENCE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spectrum_with_cutoff(cutoff: u32, drop_db: f32) -> Vec<(u32, f32)> {
+        (1000..22_050)
+            .step_by(BAND_STEP_HZ as usize)
+            .map(|f| {
+                let db = if f <= cutoff { -30.0 - (f as f32 / 1000.0) } else { -30.0 - drop_db - 20.0 };
+                (f, db)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mp3_lowpass_flagged() {
+        let verdict = judge(&spectrum_with_cutoff(16_000, 60.0), 22_050);
+        assert_eq!(verdict.cutoff_hz, Some(16_000));
+        assert!(verdict.suspicious);
+        assert!(verdict.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_full_band_not_flagged() {
+        let verdict = judge(&spectrum_with_cutoff(30_000, 0.0), 22_050);
+        assert_eq!(verdict.cutoff_hz, None);
+        assert!(!verdict.suspicious);
+    }
+}
@@ -0,0 +1,295 @@
+//! 人声检测 — 轻量的人声活动检测（VAD），标记歌曲中有人声演唱的区间。
+//!
+//! 不做音源分离，只依据三个廉价特征判断每个 [`WINDOW_MS`] 窗口：
+//! - **频段占比**：人声频段（300–3400 Hz）能量占整体的比例不低于全曲中位数太多
+//! - **音节起伏**：窗口内各 [`SUB_WINDOW_MS`] 子窗口的人声频段电平标准差较大，
+//!   对应演唱 / 说话时约 4 Hz 的音节节奏；持续的乐器铺底起伏很小
+//! - **居中程度**（仅立体声）：人声通常混在正中，中置（L+R）的人声频段能量明显高于两侧（L−R）
+//!
+//! 逐窗口结果经多数平滑、合并短间隔、丢弃过短片段后得到人声区间。
+//! 结果用于卡拉 OK 辅助（人声区间内压低原唱音量）和歌词视图的演唱提示。
+
+use super::decode::{decode_file, PcmBlock};
+use super::segments::{rms_db, VoiceBandFilter};
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+
+/// 判定窗口长度（毫秒）。
+const WINDOW_MS: u64 = 200;
+
+/// 计算音节起伏用的子窗口长度（毫秒）。
+const SUB_WINDOW_MS: u64 = 20;
+
+/// 低于该电平（dBFS）的窗口视为静音。
+const SILENCE_DB: f32 = -45.0;
+
+/// 人声频段占比允许低于全曲中位数的幅度（dB）。
+const BAND_SHARE_MARGIN_DB: f32 = 3.0;
+
+/// 子窗口电平标准差达到该值（dB）才视为有音节起伏。
+const MODULATION_DB: f32 = 2.5;
+
+/// 中置与两侧人声频段的电平差达到该值（dB）才视为居中。
+const CENTER_DB: f32 = 6.0;
+
+/// 多数平滑的半径（窗口数）。
+const SMOOTH_RADIUS: usize = 2;
+
+/// 短于该值（毫秒）的间隔并入前后人声区间。
+const MERGE_GAP_MS: u64 = 600;
+
+/// 短于该值（毫秒）的人声区间被丢弃。
+const MIN_VOCAL_MS: u64 = 1000;
+
+/// 一个人声区间（毫秒，左闭右开）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VocalSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// 单首歌曲的人声检测结果。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VocalMap {
+    pub duration_ms: u64,
+    /// 人声区间，按时间升序
+    pub segments: Vec<VocalSegment>,
+    /// 人声区间占全曲的比例（0.0–1.0）；接近 0 通常是纯音乐
+    pub vocal_ratio: f32,
+}
+
+impl VocalMap {
+    /// `position_ms` 是否落在人声区间内。
+    pub fn is_vocal_at(&self, position_ms: u64) -> bool {
+        let idx = self.segments.partition_point(|s| s.end_ms <= position_ms);
+        self.segments
+            .get(idx)
+            .is_some_and(|s| s.start_ms <= position_ms)
+    }
+}
+
+/// 单个判定窗口的特征。
+#[derive(Debug, Clone, Copy)]
+pub struct VocalWindow {
+    /// 中置整体电平
+    pub total_db: f32,
+    /// 中置人声频段电平
+    pub voice_db: f32,
+    /// 两侧人声频段电平；单声道时为 `None`
+    pub side_voice_db: Option<f32>,
+    /// 子窗口人声频段电平的标准差
+    pub modulation_db: f32,
+}
+
+/// 流式人声检测器。
+pub struct VocalDetector {
+    stereo: bool,
+    sub_frames: usize,
+    subs_per_window: usize,
+    mid_band: VoiceBandFilter,
+    side_band: VoiceBandFilter,
+    sub_voice: f64,
+    sub_count: usize,
+    sub_levels: Vec<f32>,
+    win_total: f64,
+    win_voice: f64,
+    win_side: f64,
+    win_frames: usize,
+    windows: Vec<VocalWindow>,
+}
+
+impl VocalDetector {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        Self {
+            stereo: channels >= 2,
+            sub_frames: (sample_rate as u64 * SUB_WINDOW_MS / 1000).max(1) as usize,
+            subs_per_window: (WINDOW_MS / SUB_WINDOW_MS) as usize,
+            mid_band: VoiceBandFilter::new(sample_rate),
+            side_band: VoiceBandFilter::new(sample_rate),
+            sub_voice: 0.0,
+            sub_count: 0,
+            sub_levels: Vec::new(),
+            win_total: 0.0,
+            win_voice: 0.0,
+            win_side: 0.0,
+            win_frames: 0,
+            windows: Vec::new(),
+        }
+    }
+
+    /// 喂入交织样本；多声道只取前两个声道。
+    pub fn push(&mut self, samples: &[f32], channels: usize) {
+        for frame in samples.chunks(channels.max(1)) {
+            let (mid, side) = match frame {
+                [left, right, ..] => ((left + right) * 0.5, (left - right) * 0.5),
+                [mono] => (*mono, 0.0),
+                [] => continue,
+            };
+            let voice = self.mid_band.process(mid) as f64;
+            let side_voice = self.side_band.process(side) as f64;
+            self.win_total += (mid as f64) * (mid as f64);
+            self.win_voice += voice * voice;
+            self.win_side += side_voice * side_voice;
+            self.win_frames += 1;
+            self.sub_voice += voice * voice;
+            self.sub_count += 1;
+            if self.sub_count == self.sub_frames {
+                self.sub_levels.push(rms_db(self.sub_voice, self.sub_count));
+                self.sub_voice = 0.0;
+                self.sub_count = 0;
+                if self.sub_levels.len() == self.subs_per_window {
+                    self.flush_window();
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> VocalMap {
+        find_vocal_segments(&self.windows)
+    }
+
+    fn flush_window(&mut self) {
+        let mean = self.sub_levels.iter().sum::<f32>() / self.sub_levels.len() as f32;
+        let variance = self
+            .sub_levels
+            .iter()
+            .map(|l| (l - mean) * (l - mean))
+            .sum::<f32>()
+            / self.sub_levels.len() as f32;
+        self.windows.push(VocalWindow {
+            total_db: rms_db(self.win_total, self.win_frames),
+            voice_db: rms_db(self.win_voice, self.win_frames),
+            side_voice_db: self.stereo.then(|| rms_db(self.win_side, self.win_frames)),
+            modulation_db: variance.sqrt(),
+        });
+        self.sub_levels.clear();
+        self.win_total = 0.0;
+        self.win_voice = 0.0;
+        self.win_side = 0.0;
+        self.win_frames = 0;
+    }
+}
+
+/// 解码文件并检测人声区间。
+pub fn analyze_file(path: &PlatformPath) -> Result<VocalMap, String> {
+    let mut detector: Option<VocalDetector> = None;
+    decode_file(path, |block: PcmBlock<'_>| {
+        detector
+            .get_or_insert_with(|| VocalDetector::new(block.sample_rate, block.channels))
+            .push(block.samples, block.channels);
+    })?;
+    Ok(detector.map(VocalDetector::finish).unwrap_or_default())
+}
+
+/// 由逐窗口特征（每个元素对应 [`WINDOW_MS`]）计算人声区间。
+pub fn find_vocal_segments(windows: &[VocalWindow]) -> VocalMap {
+    let duration_ms = windows.len() as u64 * WINDOW_MS;
+    let mut shares: Vec<f32> = windows
+        .iter()
+        .filter(|w| w.total_db >= SILENCE_DB)
+        .map(|w| w.voice_db - w.total_db)
+        .collect();
+    if shares.is_empty() {
+        return VocalMap {
+            duration_ms,
+            ..Default::default()
+        };
+    }
+    shares.sort_by(f32::total_cmp);
+    let median_share = shares[shares.len() / 2];
+
+    let raw: Vec<bool> = windows
+        .iter()
+        .map(|w| {
+            w.total_db >= SILENCE_DB
+                && w.voice_db - w.total_db >= median_share - BAND_SHARE_MARGIN_DB
+                && w.modulation_db >= MODULATION_DB
+                && w.side_voice_db
+                    .is_none_or(|side| w.voice_db - side >= CENTER_DB)
+        })
+        .collect();
+
+    // 多数平滑：邻域内过半为人声才算人声
+    let smoothed: Vec<bool> = (0..raw.len())
+        .map(|i| {
+            let lo = i.saturating_sub(SMOOTH_RADIUS);
+            let hi = (i + SMOOTH_RADIUS + 1).min(raw.len());
+            let votes = raw[lo..hi].iter().filter(|&&v| v).count();
+            votes * 2 > hi - lo
+        })
+        .collect();
+
+    let mut segments: Vec<VocalSegment> = Vec::new();
+    let mut run_start: Option<usize> = None;
+    for (i, vocal) in smoothed.iter().copied().chain([false]).enumerate() {
+        match (vocal, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                let segment = VocalSegment {
+                    start_ms: start as u64 * WINDOW_MS,
+                    end_ms: i as u64 * WINDOW_MS,
+                };
+                match segments.last_mut() {
+                    Some(prev) if segment.start_ms - prev.end_ms < MERGE_GAP_MS => {
+                        prev.end_ms = segment.end_ms
+                    }
+                    _ => segments.push(segment),
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    segments.retain(|s| s.end_ms - s.start_ms >= MIN_VOCAL_MS);
+
+    let vocal_ms: u64 = segments.iter().map(|s| s.end_ms - s.start_ms).sum();
+    VocalMap {
+        duration_ms,
+        vocal_ratio: if duration_ms > 0 {
+            vocal_ms as f32 / duration_ms as f32
+        } else {
+            0.0
+        },
+        segments,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_centered_modulated_band_marked_vocal() {
+        let instrumental = VocalWindow {
+            total_db: -20.0,
+            voice_db: -26.0,
+            side_voice_db: Some(-28.0),
+            modulation_db: 1.0,
+        };
+        let vocal = VocalWindow {
+            total_db: -18.0,
+            voice_db: -21.0,
+            side_voice_db: Some(-35.0),
+            modulation_db: 5.0,
+        };
+        // 2s 伴奏 + 3s 人声（中间夹一个误判窗口）+ 2s 伴奏 + 0.4s 人声（过短）
+        let mut windows = vec![instrumental; 10];
+        windows.extend(vec![vocal; 7]);
+        windows.push(instrumental);
+        windows.extend(vec![vocal; 7]);
+        windows.extend(vec![instrumental; 10]);
+        windows.extend(vec![vocal; 2]);
+        windows.extend(vec![instrumental; 5]);
+
+        let map = find_vocal_segments(&windows);
+        assert_eq!(
+            map.segments,
+            vec![VocalSegment {
+                start_ms: 2000,
+                end_ms: 5000
+            }]
+        );
+        assert!(map.is_vocal_at(3000));
+        assert!(!map.is_vocal_at(5000));
+    }
+}
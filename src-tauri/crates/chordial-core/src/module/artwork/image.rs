@@ -0,0 +1,145 @@
+//! 图片基础操作 — 格式识别、PNG 编解码、缩小。
+//!
+//! 统一在 8 bit RGB 上处理：透明通道合成到白底（封面导出为 JPEG 时本来也没有透明度），
+//! 16 bit / 调色板 / 灰度 PNG 解码时展开为 RGB。
+
+use super::{jpeg, ArtFormat};
+
+/// 8 bit RGB 图像，按行存储。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// 按文件头识别图片格式；不是 JPEG / PNG 时返回 `None`。
+pub fn sniff(data: &[u8]) -> Option<ArtFormat> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ArtFormat::Jpeg)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(ArtFormat::Png)
+    } else {
+        None
+    }
+}
+
+/// 读取图片宽高（只解析文件头）。
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match sniff(data)? {
+        ArtFormat::Jpeg => jpeg::dimensions(data),
+        // IHDR 固定紧跟在 8 字节签名之后
+        ArtFormat::Png => {
+            let ihdr = data.get(16..24)?;
+            Some((
+                u32::from_be_bytes([ihdr[0], ihdr[1], ihdr[2], ihdr[3]]),
+                u32::from_be_bytes([ihdr[4], ihdr[5], ihdr[6], ihdr[7]]),
+            ))
+        }
+    }
+}
+
+/// 解码 JPEG / PNG。
+pub fn decode(data: &[u8]) -> Result<RgbImage, String> {
+    match sniff(data) {
+        Some(ArtFormat::Jpeg) => jpeg::decode(data),
+        Some(ArtFormat::Png) => decode_png(data),
+        None => Err("不支持的图片格式（仅支持 JPEG / PNG）".to_string()),
+    }
+}
+
+fn decode_png(data: &[u8]) -> Result<RgbImage, String> {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| format!("解析 PNG 失败: {}", e))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| format!("解码 PNG 失败: {}", e))?;
+    let raw = &buf[..info.buffer_size()];
+
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        png::ColorType::Indexed => return Err("PNG 调色板未展开".to_string()),
+    };
+    let mut pixels = Vec::with_capacity(info.width as usize * info.height as usize * 3);
+    for px in raw.chunks_exact(channels) {
+        let (rgb, alpha) = match channels {
+            1 => ([px[0]; 3], 255),
+            2 => ([px[0]; 3], px[1]),
+            3 => ([px[0], px[1], px[2]], 255),
+            _ => ([px[0], px[1], px[2]], px[3]),
+        };
+        // 合成到白底
+        let a = alpha as u32;
+        pixels.extend(rgb.iter().map(|&c| ((c as u32 * a + 255 * (255 - a) + 127) / 255) as u8));
+    }
+    Ok(RgbImage {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+/// 编码为 PNG（RGB 8 bit）。
+pub fn encode_png(image: &RgbImage) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, image.width, image.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| format!("写入 PNG 失败: {}", e))?;
+        writer
+            .write_image_data(&image.pixels)
+            .map_err(|e| format!("写入 PNG 失败: {}", e))?;
+    }
+    Ok(out)
+}
+
+/// 等比缩放后的尺寸：长边不超过 `max_edge`，只缩小不放大。
+pub fn fit_within(width: u32, height: u32, max_edge: u32) -> (u32, u32) {
+    let long = width.max(height);
+    if long <= max_edge || max_edge == 0 {
+        return (width, height);
+    }
+    let scale = |v: u32| ((v as u64 * max_edge as u64 + long as u64 / 2) / long as u64).max(1) as u32;
+    (scale(width), scale(height))
+}
+
+/// 区域平均缩小到 `width × height`。
+pub fn resize(image: &RgbImage, width: u32, height: u32) -> RgbImage {
+    if (width, height) == (image.width, image.height) {
+        return image.clone();
+    }
+    let (sw, sh) = (image.width as usize, image.height as usize);
+    let (tw, th) = (width as usize, height as usize);
+    // 目标像素 i 覆盖的源区间 [start, end)，至少一个像素
+    let span = |i: usize, src: usize, dst: usize| {
+        let start = i * src / dst;
+        (start, ((i + 1) * src / dst).max(start + 1).min(src))
+    };
+    let mut pixels = Vec::with_capacity(tw * th * 3);
+    for ty in 0..th {
+        let (y0, y1) = span(ty, sh, th);
+        for tx in 0..tw {
+            let (x0, x1) = span(tx, sw, tw);
+            let mut sum = [0u32; 3];
+            for y in y0..y1 {
+                let row = &image.pixels[(y * sw + x0) * 3..(y * sw + x1) * 3];
+                for px in row.chunks_exact(3) {
+                    sum[0] += px[0] as u32;
+                    sum[1] += px[1] as u32;
+                    sum[2] += px[2] as u32;
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u32;
+            pixels.extend(sum.iter().map(|&s| ((s + count / 2) / count) as u8));
+        }
+    }
+    RgbImage {
+        width,
+        height,
+        pixels,
+    }
+}
@@ -0,0 +1,107 @@
+//! JPEG 编解码 — 基于 `jpeg-decoder` / `jpeg-encoder`，供封面缩放 / 转格式使用。
+//!
+//! - 解码：基线 / 扩展顺序 / 渐进式（SOF0 / SOF1 / SOF2）与无损 JPEG，灰度、YCbCr、CMYK / YCCK
+//!   统一转为 8 bit RGB（CMYK 按不带色彩配置文件的常规公式换算）。
+//! - 编码：基线 YCbCr。
+
+use super::image::RgbImage;
+use jpeg_decoder::PixelFormat;
+
+/// 编码为基线 JPEG（`quality` 1–100）。
+pub fn encode(image: &RgbImage, quality: u8) -> Result<Vec<u8>, String> {
+    let size = u16::try_from(image.width).ok().zip(u16::try_from(image.height).ok());
+    let Some((width, height)) = size.filter(|&(w, h)| w > 0 && h > 0) else {
+        return Err(format!("图片尺寸无效: {}×{}", image.width, image.height));
+    };
+    let mut out = Vec::new();
+    jpeg_encoder::Encoder::new(&mut out, quality.clamp(1, 100))
+        .encode(&image.pixels, width, height, jpeg_encoder::ColorType::Rgb)
+        .map_err(|e| format!("编码 JPEG 失败: {}", e))?;
+    Ok(out)
+}
+
+/// 读取 JPEG 的宽高（只解析到帧头，不解码）。
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    decoder.read_info().ok()?;
+    let info = decoder.info()?;
+    Some((info.width as u32, info.height as u32))
+}
+
+/// 解码 JPEG 为 RGB。
+pub fn decode(data: &[u8]) -> Result<RgbImage, String> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let raw = decoder.decode().map_err(|e| format!("解码 JPEG 失败: {}", e))?;
+    let info = decoder.info().ok_or("解码 JPEG 失败: 缺少帧头")?;
+    let pixels = match info.pixel_format {
+        PixelFormat::RGB24 => raw,
+        PixelFormat::L8 => raw.iter().flat_map(|&l| [l; 3]).collect(),
+        // 16 bit 灰度（无损 JPEG），按本机字节序存放，取高 8 位
+        PixelFormat::L16 => raw
+            .chunks_exact(2)
+            .flat_map(|c| [(u16::from_ne_bytes([c[0], c[1]]) >> 8) as u8; 3])
+            .collect(),
+        // 解码器已处理 Adobe 反相，这里是常规 CMYK（0 表示无墨）
+        PixelFormat::CMYK32 => raw
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let k = 255 - p[3] as u32;
+                [0, 1, 2].map(|i| ((255 - p[i] as u32) * k / 255) as u8)
+            })
+            .collect(),
+    };
+    Ok(RgbImage {
+        width: info.width as u32,
+        height: info.height as u32,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 24×16，左半红、右半蓝；4:2:0 渐进式编码。
+    const PROGRESSIVE: &[u8] = include_bytes!("testdata/progressive.jpg");
+    /// 同样的图案，以 CMYK 基线编码。
+    const CMYK: &[u8] = include_bytes!("testdata/cmyk.jpg");
+
+    fn pixel(image: &RgbImage, x: u32, y: u32) -> [u8; 3] {
+        let i = ((y * image.width + x) * 3) as usize;
+        [image.pixels[i], image.pixels[i + 1], image.pixels[i + 2]]
+    }
+
+    fn assert_close(actual: [u8; 3], expected: [u8; 3]) {
+        let error = actual.iter().zip(expected).map(|(a, b)| (*a as i32 - b as i32).abs()).max().unwrap();
+        assert!(error <= 12, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_jpeg_round_trip_and_progressive_cmyk_fixtures() {
+        // 非 8 整数倍的尺寸，覆盖边缘块
+        let (width, height) = (37u32, 21u32);
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend_from_slice(&[(x * 6) as u8, (y * 12) as u8, 128]);
+            }
+        }
+        let image = RgbImage { width, height, pixels };
+        let encoded = encode(&image, 95).unwrap();
+        assert_eq!(dimensions(&encoded), Some((width, height)));
+        let decoded = decode(&encoded).unwrap();
+        assert_eq!((decoded.width, decoded.height), (width, height));
+        assert!(encode(&RgbImage { width: 0, height: 1, pixels: Vec::new() }, 90).is_err());
+
+        assert_eq!(dimensions(PROGRESSIVE), Some((24, 16)));
+        let progressive = decode(PROGRESSIVE).unwrap();
+        assert_close(pixel(&progressive, 2, 8), [200, 40, 40]);
+        assert_close(pixel(&progressive, 21, 8), [40, 40, 200]);
+
+        let cmyk = decode(CMYK).unwrap();
+        assert_eq!(cmyk.pixels.len(), 24 * 16 * 3);
+        // C=0 M=Y=204 K=55 → (200, 40, 40)
+        assert_close(pixel(&cmyk, 2, 8), [200, 40, 40]);
+        assert_close(pixel(&cmyk, 21, 8), [40, 40, 200]);
+    }
+}
@@ -0,0 +1,252 @@
+//! 封面导出 — 把专辑最终采用的封面按指定尺寸 / 格式写到磁盘，用于整理各文件夹的 `folder.jpg`。
+//!
+//! # 封面来源（按顺序尝试）
+//!
+//! 1. 专辑的来源引用：本地来源依次取嵌入封面、同名图片、`cover/folder/front` 等文件夹图片
+//! 2. 专辑内各歌曲的来源引用（专辑本身没有来源引用或来源取不到封面时）
+//! 3. 元数据补全写入的 `cover_url`：本地路径、`file://`、`data:` URL
+//!
+//! 核心层没有 HTTP 客户端，`http(s)` 封面地址无法直接导出，需要前端先下载。
+//!
+//! # 子模块
+//!
+//! | 模块 | 职责 |
+//! |------|------|
+//! | [`image`] | 格式识别、PNG 编解码、区域平均缩小 |
+//! | [`jpeg`] | JPEG 编解码（含渐进式 / CMYK 解码） |
+//! | [`palette`] | 封面主要颜色提取 |
+//! | [`thumbnail`] | 专辑网格用的缩略图（Blob 缓存 + 后台预热） |
+//!
+//! 尺寸和格式都不用变时直接复制原图字节，不重新编码。
+
+pub mod image;
+pub mod jpeg;
+pub mod palette;
+pub mod thumbnail;
+
+use crate::module::music_library::library::MusicLibrary;
+use crate::module::music_source::registrar::SourceRegistrar;
+use crate::module::music_source::resource;
+use crate::module::music_source::types::SourceId;
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 导出 JPEG 的质量。
+pub const JPEG_QUALITY: u8 = 90;
+
+/// 导出尺寸上限（长边像素）。
+pub const MAX_EDGE: u32 = 4096;
+
+/// 封面图片格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtFormat {
+    Jpeg,
+    Png,
+}
+
+impl ArtFormat {
+    /// 按扩展名判断格式（不区分大小写）。
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(ArtFormat::Jpeg),
+            "png" => Some(ArtFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// 封面取自哪里。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CoverOrigin {
+    /// 来源提供的封面（嵌入 / 同名图片 / 文件夹图片）
+    Source { source_name: String, entity_id: String },
+    /// 专辑的 `cover_url`
+    Url { url: String },
+}
+
+/// 导出选项。
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ExportOptions {
+    /// 长边像素上限；只缩小不放大，`None` 保持原尺寸
+    #[serde(default)]
+    pub size: Option<u32>,
+    /// 输出格式；`None` 时按路径扩展名判断，无法判断时用 JPEG
+    #[serde(default)]
+    pub format: Option<ArtFormat>,
+}
+
+/// 导出结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtExport {
+    pub path: String,
+    pub origin: CoverOrigin,
+    pub format: ArtFormat,
+    pub width: u32,
+    pub height: u32,
+    /// 写入的字节数
+    pub bytes: u64,
+    /// 是否经过解码 / 缩放 / 转格式；为 `false` 时是原图的逐字节复制
+    pub reencoded: bool,
+}
+
+/// 找出专辑当前采用的封面原图。
+pub fn resolve_album_cover(
+    registrar: &SourceRegistrar,
+    library: &MusicLibrary,
+    album_id: &str,
+) -> Result<(Vec<u8>, CoverOrigin), String> {
+    let album = library
+        .get_album(album_id)
+        .ok_or_else(|| format!("专辑不存在: {}", album_id))?;
+
+    let song_sources = album
+        .song_ids
+        .iter()
+        .filter_map(|id| library.get_song(id))
+        .flat_map(|song| song.source_ids);
+    for source_id in album.source_ids.iter().cloned().chain(song_sources) {
+        if let Some(data) = source_cover(registrar, &source_id) {
+            return Ok((
+                data,
+                CoverOrigin::Source {
+                    source_name: source_id.source_name,
+                    entity_id: source_id.entity_id,
+                },
+            ));
+        }
+    }
+
+    match album.cover_url.as_deref().filter(|url| !url.is_empty()) {
+        Some(url) => {
+            let data = read_cover_url(url)?;
+            if image::sniff(&data).is_none() {
+                return Err(format!("封面地址不是 JPEG / PNG 图片: {}", url));
+            }
+            Ok((data, CoverOrigin::Url { url: url.to_string() }))
+        }
+        None => Err(format!("专辑《{}》没有封面", album.title)),
+    }
+}
+
+/// 来源提供的封面；取不到或不是可识别的图片时返回 `None`。
+fn source_cover(registrar: &SourceRegistrar, source_id: &SourceId) -> Option<Vec<u8>> {
+    let data = resource::get_album_picture(registrar, source_id).ok()?;
+    image::sniff(&data).map(|_| data)
+}
+
+/// 读取 `cover_url` 指向的图片：本地路径、`file://` 或 `data:` URL。
+fn read_cover_url(url: &str) -> Result<Vec<u8>, String> {
+    if let Some(rest) = url.strip_prefix("data:") {
+        let (meta, payload) = rest.split_once(',').ok_or("data URL 格式错误")?;
+        if !meta.ends_with(";base64") {
+            return Err("只支持 base64 编码的 data URL".to_string());
+        }
+        return B64
+            .decode(payload.trim())
+            .map_err(|e| format!("解析 data URL 失败: {}", e));
+    }
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return Err(format!("网络封面需要先下载到本地: {}", url));
+    }
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    std::fs::read(path).map_err(|e| format!("读取封面文件失败 {}: {}", path, e))
+}
+
+/// 把专辑封面导出到 `path`。
+///
+/// 目标文件所在目录不存在时自动创建；先写临时文件再改名，中途失败不会留下半个图片。
+pub fn export_album_art(
+    registrar: &SourceRegistrar,
+    library: &MusicLibrary,
+    album_id: &str,
+    path: &Path,
+    options: ExportOptions,
+) -> Result<ArtExport, String> {
+    if options.size == Some(0) {
+        return Err("导出尺寸必须大于 0".to_string());
+    }
+    let (data, origin) = resolve_album_cover(registrar, library, album_id)?;
+    let format = options
+        .format
+        .or_else(|| ArtFormat::from_path(path))
+        .unwrap_or(ArtFormat::Jpeg);
+    let (encoded, width, height, reencoded) = convert(&data, format, options.size)?;
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let tmp = path.with_extension("chordial-tmp");
+    std::fs::write(&tmp, &encoded).map_err(|e| format!("写入封面失败: {}", e))?;
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(format!("写入封面失败: {}", e));
+    }
+
+    Ok(ArtExport {
+        path: path.to_string_lossy().into_owned(),
+        origin,
+        format,
+        width,
+        height,
+        bytes: encoded.len() as u64,
+        reencoded,
+    })
+}
+
+/// 把原图转为目标格式 / 尺寸：`(字节, 宽, 高, 是否重新编码)`。
+fn convert(data: &[u8], format: ArtFormat, size: Option<u32>) -> Result<(Vec<u8>, u32, u32, bool), String> {
+    let source_format = image::sniff(data).ok_or("不支持的图片格式（仅支持 JPEG / PNG）")?;
+    let (width, height) = image::dimensions(data).ok_or("无法读取图片尺寸")?;
+    let target = image::fit_within(width, height, size.unwrap_or(u32::MAX).min(MAX_EDGE));
+    if source_format == format && target == (width, height) {
+        return Ok((data.to_vec(), width, height, false));
+    }
+
+    let decoded = image::decode(data)?;
+    let resized = image::resize(&decoded, target.0, target.1);
+    let encoded = match format {
+        ArtFormat::Jpeg => jpeg::encode(&resized, JPEG_QUALITY)?,
+        ArtFormat::Png => image::encode_png(&resized)?,
+    };
+    Ok((encoded, resized.width, resized.height, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: u32, height: u32) -> image::RgbImage {
+        let pixels = (0..width * height)
+            .flat_map(|i| [(i % width * 255 / width) as u8, (i / width * 255 / height) as u8, 200])
+            .collect();
+        image::RgbImage { width, height, pixels }
+    }
+
+    #[test]
+    fn test_convert_resizes_and_copies_unchanged() {
+        let png = image::encode_png(&gradient(300, 200)).unwrap();
+
+        // 格式、尺寸都不变：原样复制
+        let (bytes, w, h, reencoded) = convert(&png, ArtFormat::Png, Some(500)).unwrap();
+        assert_eq!((w, h, reencoded), (300, 200, false));
+        assert_eq!(bytes, png);
+
+        // PNG → JPEG 并缩小，保持宽高比
+        let (bytes, w, h, reencoded) = convert(&png, ArtFormat::Jpeg, Some(150)).unwrap();
+        assert_eq!((w, h, reencoded), (150, 100, true));
+        assert_eq!(image::sniff(&bytes), Some(ArtFormat::Jpeg));
+        assert_eq!(image::dimensions(&bytes), Some((150, 100)));
+
+        // JPEG → PNG
+        let (bytes, w, h, _) = convert(&bytes, ArtFormat::Png, None).unwrap();
+        assert_eq!((w, h), (150, 100));
+        let decoded = image::decode(&bytes).unwrap();
+        assert_eq!(decoded.pixels.len(), 150 * 100 * 3);
+
+        assert_eq!(ArtFormat::from_path(Path::new("a/folder.JPG")), Some(ArtFormat::Jpeg));
+        assert_eq!(ArtFormat::from_path(Path::new("cover")), None);
+    }
+}
@@ -0,0 +1,77 @@
+//! 封面配色 — 从封面中取出几种主要颜色，供正在播放页做背景渐变 / 强调色。
+//!
+//! 先缩小到 [`SAMPLE_EDGE`] 像素见方，再按每通道 4 bit 量化成直方图，
+//! 按像素数从多到少取色；与已选颜色太接近的跳过，避免整组都是同一色系的深浅变化。
+
+use super::image::{self, RgbImage};
+
+/// 取色前缩小到的边长（像素）。
+const SAMPLE_EDGE: u32 = 48;
+
+/// 两种颜色至少相差的 RGB 距离（平方）。
+const MIN_DISTANCE_SQ: u32 = 48 * 48;
+
+/// 封面的主要颜色（`#rrggbb`），按占比从高到低，最多 `count` 种。
+pub fn palette(cover: &RgbImage, count: usize) -> Vec<String> {
+    let (width, height) = image::fit_within(cover.width, cover.height, SAMPLE_EDGE);
+    let small = image::resize(cover, width, height);
+
+    // 4096 个桶：像素数与各通道累加值
+    let mut buckets = vec![(0u32, [0u32; 3]); 1 << 12];
+    for px in small.pixels.chunks_exact(3) {
+        let index = (usize::from(px[0] >> 4) << 8) | (usize::from(px[1] >> 4) << 4) | usize::from(px[2] >> 4);
+        let bucket = &mut buckets[index];
+        bucket.0 += 1;
+        for (sum, &value) in bucket.1.iter_mut().zip(px) {
+            *sum += u32::from(value);
+        }
+    }
+    buckets.retain(|(n, _)| *n > 0);
+    buckets.sort_by_key(|(n, _)| std::cmp::Reverse(*n));
+
+    let mut picked: Vec<[u8; 3]> = Vec::new();
+    for (n, sum) in buckets {
+        if picked.len() == count {
+            break;
+        }
+        let color = sum.map(|s| ((s + n / 2) / n) as u8);
+        let distinct = picked.iter().all(|p| {
+            (0..3)
+                .map(|ch| (i32::from(p[ch]) - i32::from(color[ch])).pow(2) as u32)
+                .sum::<u32>()
+                >= MIN_DISTANCE_SQ
+        });
+        if distinct {
+            picked.push(color);
+        }
+    }
+    picked
+        .iter()
+        .map(|[r, g, b]| format!("#{:02x}{:02x}{:02x}", r, g, b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_orders_by_share_and_skips_near_duplicates() {
+        // 上 3/4 深蓝（其中混一点近似色），下 1/4 橙色
+        let (width, height) = (64u32, 64u32);
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let rgb = match (y < 48, x % 2 == 0) {
+                    (true, true) => [16, 32, 128],
+                    (true, false) => [18, 34, 130],
+                    (false, _) => [240, 128, 16],
+                };
+                pixels.extend_from_slice(&rgb);
+            }
+        }
+        let colors = palette(&RgbImage { width, height, pixels }, 4);
+        assert_eq!(colors.len(), 2);
+        assert!(colors[0].starts_with("#1") && colors[1] == "#f08010");
+    }
+}
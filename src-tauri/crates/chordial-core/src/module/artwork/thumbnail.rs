@@ -0,0 +1,224 @@
+//! 封面缩略图 — 专辑网格用的小尺寸封面，生成后放进 Blob 缓存。
+//!
+//! 网格滚动时一次会出现几十上百张封面，每张都冷读原文件（嵌入封面要解析整个标签）会把 IO 堵死。
+//! [`Prewarmer`] 在后台以至多 [`PREWARM_THREADS`] 个线程提前生成即将进入视野的缩略图，
+//! 之后 [`album_thumbnail`] 直接命中缓存。新的预热请求取代尚未处理完的旧请求，快速滚动时不会堆积过时的任务。
+//!
+//! 缓存键带上 [`cover_fingerprint`]：换了封面（改嵌入封面、替换文件夹图片、改 `cover_url`）后键随之改变，
+//! 不会在有效期内一直返回旧图；旧键无人再读，到期后清除。
+
+use super::{image, jpeg, ArtFormat, JPEG_QUALITY};
+use crate::module::cache::store::CacheStore;
+use crate::module::music_library::library::MusicLibrary;
+use crate::module::music_source::registrar::SourceRegistrar;
+use crate::module::perf;
+use crate::module::platform::{self, PlatformPath};
+use crate::module::storage::entry::Ttl;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use xxhash_rust::xxh3::Xxh3;
+
+/// 缩略图默认长边像素。
+pub const DEFAULT_EDGE: u32 = 300;
+
+/// 缩略图长边上限（超过时按上限生成）。
+pub const MAX_EDGE: u32 = 1024;
+
+/// 预热的最大并发数。
+pub const PREWARM_THREADS: usize = 4;
+
+/// 缩略图在 Blob 缓存中的有效期。
+const THUMBNAIL_TTL: Ttl = Ttl::DurationSecs(30 * 24 * 3600);
+
+/// 缩略图的缓存键；`fingerprint` 见 [`cover_fingerprint`]。
+pub fn cache_key(album_id: &str, edge: u32, fingerprint: &str) -> String {
+    format!("album_thumb:{}:{}:{}", album_id, edge, fingerprint)
+}
+
+/// 专辑封面的指纹（16 位十六进制），封面可能变了时随之改变。
+///
+/// 只看元数据、不读图片，命中缓存时仍然不碰原文件内容。参与计算的有专辑的 `cover_url`（本地路径再加文件的
+/// mtime / 大小），以及按 [`resolve_album_cover`](super::resolve_album_cover) 的顺序直到第一个有本地文件的来源引用：
+/// 该文件的 mtime / 大小反映嵌入封面的改动，所在目录的 mtime 反映文件夹图片的增删与替换。
+pub fn cover_fingerprint(registrar: &SourceRegistrar, library: &MusicLibrary, album_id: &str) -> String {
+    let mut hasher = Xxh3::new();
+    let mut field = |bytes: &[u8]| {
+        hasher.update(bytes);
+        hasher.update(&[0]);
+    };
+    if let Some(album) = library.get_album(album_id) {
+        let url = album.cover_url.as_deref().unwrap_or_default();
+        field(url.as_bytes());
+        if !url.is_empty() && !url.starts_with("data:") && !url.starts_with("http") {
+            let stamp = file_stamp(url.strip_prefix("file://").unwrap_or(url));
+            field(format!("{:?}", stamp).as_bytes());
+        }
+
+        let song_sources = album
+            .song_ids
+            .iter()
+            .filter_map(|id| library.get_song(id))
+            .flat_map(|song| song.source_ids);
+        for source_id in album.source_ids.iter().cloned().chain(song_sources) {
+            field(source_id.source_name.as_bytes());
+            field(source_id.entity_id.as_bytes());
+            let path = registrar
+                .get(&source_id.source_name)
+                .and_then(|source| source.song_file_path(&source_id.entity_id));
+            if let Some(stamp) = path.as_deref().and_then(file_stamp) {
+                field(format!("{:?}", stamp).as_bytes());
+                break;
+            }
+        }
+    }
+    format!("{:016x}", hasher.digest())
+}
+
+/// 本地文件的 `[mtime, 大小, 所在目录 mtime]`；文件不存在时为 `None`。
+fn file_stamp(path: &str) -> Option<[u64; 3]> {
+    let path = PlatformPath::from(path);
+    let mtime = platform::file_modified_secs(&path).ok()?;
+    let size = platform::file_size(&path).ok()?;
+    let dir_mtime = platform::path_parent(&path)
+        .and_then(|dir| platform::file_modified_secs(&dir).ok())
+        .unwrap_or(0);
+    Some([mtime, size, dir_mtime])
+}
+
+/// 把原图缩到长边不超过 `edge` 的 JPEG；原图本来就是足够小的 JPEG 时原样返回。
+pub fn make_thumbnail(data: &[u8], edge: u32) -> Result<Vec<u8>, String> {
+    let format = image::sniff(data).ok_or("不支持的图片格式（仅支持 JPEG / PNG）")?;
+    if format == ArtFormat::Jpeg && image::dimensions(data).is_some_and(|(w, h)| w.max(h) <= edge) {
+        return Ok(data.to_vec());
+    }
+    let decoded = image::decode(data)?;
+    let (width, height) = image::fit_within(decoded.width, decoded.height, edge);
+    let resized = if (width, height) == (decoded.width, decoded.height) {
+        decoded
+    } else {
+        image::resize(&decoded, width, height)
+    };
+    jpeg::encode(&resized, JPEG_QUALITY)
+}
+
+/// 专辑封面缩略图：命中缓存直接返回，否则由原图生成并写入缓存。
+///
+/// Blob 缓存未启用或只读（安全模式）时照常返回，只是下次还要重新生成。
+pub fn album_thumbnail(
+    registrar: &SourceRegistrar,
+    library: &MusicLibrary,
+    cache: &CacheStore,
+    album_id: &str,
+    edge: u32,
+) -> Result<Vec<u8>, String> {
+    let edge = edge.clamp(1, MAX_EDGE);
+    let key = cache_key(album_id, edge, &cover_fingerprint(registrar, library, album_id));
+    if let Some(data) = cache.get_blob(&key) {
+        return Ok(data);
+    }
+    let _scope = perf::scope("artwork.thumbnail");
+    let (data, _) = super::resolve_album_cover(registrar, library, album_id)?;
+    let thumbnail = make_thumbnail(&data, edge)?;
+    let _ = cache.set_blob(&key, &thumbnail, &THUMBNAIL_TTL);
+    Ok(thumbnail)
+}
+
+/// 后台预热缩略图；同一时间只有最近一次请求在处理。
+#[derive(Default)]
+pub struct Prewarmer {
+    /// 每次请求递增；工作线程发现编号变了就停下，让位给新请求
+    generation: AtomicU64,
+}
+
+impl Prewarmer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在后台为 `album_ids` 生成缩略图，立即返回需要生成的数量（已缓存的不计入）。
+    pub fn spawn(
+        self: &Arc<Self>,
+        registrar: Arc<SourceRegistrar>,
+        library: Arc<MusicLibrary>,
+        cache: Arc<CacheStore>,
+        album_ids: Vec<String>,
+        edge: u32,
+    ) -> Result<usize, String> {
+        let edge = edge.clamp(1, MAX_EDGE);
+        let generation = self.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        let mut seen = std::collections::HashSet::new();
+        let pending: Vec<String> = album_ids
+            .into_iter()
+            .filter(|id| {
+                seen.insert(id.clone())
+                    && !cache.has_blob(&cache_key(id, edge, &cover_fingerprint(&registrar, &library, id)))
+            })
+            .collect();
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        let queued = pending.len();
+        let prewarmer = self.clone();
+        std::thread::Builder::new()
+            .name("cover-prewarm".into())
+            .spawn(move || {
+                let _scope = perf::scope("artwork.prewarm");
+                let current = || prewarmer.generation.load(Ordering::Acquire) == generation;
+                let next = AtomicUsize::new(0);
+                std::thread::scope(|s| {
+                    for _ in 0..PREWARM_THREADS.min(pending.len()) {
+                        s.spawn(|| {
+                            while current() {
+                                let Some(id) = pending.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                                    break;
+                                };
+                                // 没有封面的专辑很常见，失败不必报告；前端取图时会照常得到错误
+                                let _ = album_thumbnail(&registrar, &library, &cache, id, edge);
+                            }
+                        });
+                    }
+                });
+            })
+            .map_err(|e| format!("启动封面预热失败: {}", e))?;
+        Ok(queued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_fits_edge_and_keeps_small_jpeg() {
+        let (width, height) = (600, 400);
+        let source = image::RgbImage {
+            width,
+            height,
+            pixels: (0..width * height).flat_map(|i| [(i % 256) as u8, 80, 160]).collect(),
+        };
+        let png = image::encode_png(&source).unwrap();
+
+        let thumbnail = make_thumbnail(&png, 300).unwrap();
+        assert_eq!(image::sniff(&thumbnail), Some(ArtFormat::Jpeg));
+        assert_eq!(image::dimensions(&thumbnail), Some((300, 200)));
+
+        // 已经足够小的 JPEG 不重新编码
+        assert_eq!(make_thumbnail(&thumbnail, 300).unwrap(), thumbnail);
+
+        // 封面文件改动（内容长度 / 修改时间）后指纹随之改变
+        let dir = std::env::temp_dir().join(format!("chordial_thumb_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cover = dir.join("folder.jpg");
+        std::fs::write(&cover, &thumbnail).unwrap();
+        let path = cover.to_string_lossy().into_owned();
+        let before = file_stamp(&path).unwrap();
+        std::fs::write(&cover, &png).unwrap();
+        let resized = file_stamp(&path).unwrap();
+        assert_ne!(resized, before);
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        std::fs::File::options().write(true).open(&cover).unwrap().set_modified(later).unwrap();
+        assert_ne!(file_stamp(&path).unwrap(), resized);
+        assert_eq!(file_stamp(&dir.join("missing.jpg").to_string_lossy()), None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
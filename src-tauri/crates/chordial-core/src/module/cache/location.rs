@@ -0,0 +1,170 @@
+//! 缓存位置 — 把磁盘缓存（Blob / 远程音频 / 解码 PCM）整体放到另一个目录。
+//!
+//! 默认缓存根目录就是数据目录，缓存文件容易把系统盘占满。配置项 [`CACHE_DIR_CONFIG_KEY`]
+//! 指定其他根目录（如更大的硬盘），三个缓存子目录都放在它下面，启动时按它创建各缓存。
+//!
+//! 运行中切换由 [`AppContext::relocate_cache`](crate::AppContext::relocate_cache) 完成：
+//! 先用 [`migrate`] 把旧目录中的文件搬到新目录（同一文件系统内直接改名，跨盘时复制后删除），
+//! 再把各缓存实例换成指向新目录的实例，最后用 [`migrate`] 再搬一次迁移期间新写入旧目录的文件。
+//! 缓存文件名只取决于键，搬走后原样命中；迁移中途某个文件未命中只是一次普通的缓存未命中。
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 缓存根目录的配置键；未设置时使用数据目录。
+pub const CACHE_DIR_CONFIG_KEY: &str = "cache_dir";
+
+/// 缓存根目录下的子目录。
+pub const BLOB_SUBDIR: &str = "cache_blobs";
+pub const MEDIA_SUBDIR: &str = "media_cache";
+pub const PCM_SUBDIR: &str = "pcm_cache";
+const SUBDIRS: [&str; 3] = [BLOB_SUBDIR, MEDIA_SUBDIR, PCM_SUBDIR];
+
+/// 当前缓存位置。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheLocation {
+    /// 缓存根目录
+    pub dir: String,
+    /// 是否为默认位置（数据目录）
+    pub is_default: bool,
+}
+
+/// 一次迁移的结果。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    /// 搬过去的文件数
+    pub files: usize,
+    pub bytes: u64,
+    /// 没能搬过去的文件（已在新目录中重新缓存前它们只是缓存未命中）
+    pub failed: usize,
+}
+
+/// 校验并规范化新的缓存根目录：必须是绝对路径，不存在时创建，且不能位于旧根目录的缓存子目录之内。
+pub fn prepare_root(dir: &Path, current: &Path) -> Result<PathBuf, String> {
+    if !dir.is_absolute() {
+        return Err(format!("缓存目录必须是绝对路径: {}", dir.display()));
+    }
+    let current = current.canonicalize().unwrap_or_else(|_| current.to_path_buf());
+    let inside_cache = |dir: &Path| SUBDIRS.iter().any(|sub| dir.starts_with(current.join(sub)));
+    // 创建前先按原路径检查一次，避免在旧缓存目录里留下空目录
+    if inside_cache(dir) {
+        return Err("新缓存目录不能位于现有缓存目录之内".to_string());
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("创建缓存目录失败: {}", e))?;
+    let dir = dir.canonicalize().map_err(|e| format!("无法访问缓存目录: {}", e))?;
+    if inside_cache(&dir) {
+        return Err("新缓存目录不能位于现有缓存目录之内".to_string());
+    }
+    Ok(dir)
+}
+
+/// 把 `from` 下各缓存子目录中的文件搬到 `to` 下的同名子目录。
+///
+/// 未写完的临时文件（`.part` / `.tmp`）不搬，直接删除；新目录中已有同名文件时以新目录为准。
+/// 单个文件失败只计入 `failed`，不中断迁移。
+pub fn migrate(from: &Path, to: &Path) -> Result<MigrationReport, String> {
+    let mut report = MigrationReport {
+        from: from.to_string_lossy().into_owned(),
+        to: to.to_string_lossy().into_owned(),
+        ..Default::default()
+    };
+    if same_dir(from, to) {
+        return Ok(report);
+    }
+    for sub in SUBDIRS {
+        let (src_dir, dst_dir) = (from.join(sub), to.join(sub));
+        let Ok(entries) = fs::read_dir(&src_dir) else {
+            continue;
+        };
+        fs::create_dir_all(&dst_dir).map_err(|e| format!("创建缓存目录失败: {}", e))?;
+        for entry in entries.flatten() {
+            let src = entry.path();
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if !meta.is_file() {
+                continue;
+            }
+            if src.extension().is_some_and(|ext| ext == "part" || ext == "tmp") {
+                let _ = fs::remove_file(&src);
+                continue;
+            }
+            let dst = dst_dir.join(entry.file_name());
+            if dst.exists() {
+                let _ = fs::remove_file(&src);
+                continue;
+            }
+            match move_file(&src, &dst) {
+                Ok(()) => {
+                    report.files += 1;
+                    report.bytes += meta.len();
+                }
+                Err(e) => {
+                    eprintln!("[cache] 迁移 {} 失败: {}", src.display(), e);
+                    report.failed += 1;
+                }
+            }
+        }
+        // 搬空后删除旧子目录；仍有文件（失败或迁移期间新写入）时保留
+        let _ = fs::remove_dir(&src_dir);
+    }
+    Ok(report)
+}
+
+/// 先尝试改名；跨文件系统时复制到临时文件再改名，最后删除源文件。
+fn move_file(src: &Path, dst: &Path) -> std::io::Result<()> {
+    if fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+    let tmp = dst.with_extension("part");
+    if let Err(e) = fs::copy(src, &tmp).and_then(|_| fs::rename(&tmp, dst)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::remove_file(src)
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_moves_cache_files() {
+        let root = std::env::temp_dir().join(format!("chordial_cache_location_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (from, to) = (root.join("old"), root.join("new"));
+        fs::create_dir_all(from.join(MEDIA_SUBDIR)).unwrap();
+        fs::create_dir_all(from.join(BLOB_SUBDIR)).unwrap();
+        fs::write(from.join(MEDIA_SUBDIR).join("a.mp3"), b"abcd").unwrap();
+        fs::write(from.join(MEDIA_SUBDIR).join("b.part"), b"xx").unwrap();
+        fs::write(from.join(BLOB_SUBDIR).join("c.blob"), b"123456").unwrap();
+        // 数据目录里的其他文件不属于缓存，不搬
+        fs::write(from.join("config.json"), b"{}").unwrap();
+
+        let to = prepare_root(&to, &from).unwrap();
+        let report = migrate(&from, &to).unwrap();
+        assert_eq!((report.files, report.bytes, report.failed), (2, 10, 0));
+        assert_eq!(fs::read(to.join(MEDIA_SUBDIR).join("a.mp3")).unwrap(), b"abcd");
+        assert!(to.join(BLOB_SUBDIR).join("c.blob").is_file());
+        assert!(!to.join(MEDIA_SUBDIR).join("b.part").exists());
+        assert!(!from.join(MEDIA_SUBDIR).exists());
+        assert!(from.join("config.json").is_file());
+
+        // 再搬一次（无新文件）与搬到自身都是空操作；不能搬进旧缓存子目录
+        assert_eq!(migrate(&from, &to).unwrap().files, 0);
+        assert_eq!(migrate(&to, &to).unwrap().files, 0);
+        assert!(prepare_root(&to.join(PCM_SUBDIR).join("x"), &to).is_err());
+        assert!(prepare_root(Path::new("relative/dir"), &to).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,6 +1,7 @@
 //! 内存缓存存储模块，支持 TTL 自动过期。
 //!
 //! 数据仅存在于进程生命周期内，应用重启后全部清空。
+//! 磁盘缓存的根目录可以改到其他位置，见 [`location`]。
 //!
 //! # 使用示例
 //!
@@ -12,4 +13,5 @@
 //! cache.set("recent", &data, &Ttl::DurationSecs(600))?;
 //! ```
 
+pub mod location;
 pub mod store;
@@ -1,4 +1,5 @@
 use crate::module::perf;
+use crate::module::safe_mode;
 use crate::module::storage::entry::{StoredEntry, Ttl};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -7,6 +8,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
 /// 二进制 Blob 条目的 TTL 元数据（数据在磁盘，元数据在内存）。
@@ -69,6 +71,8 @@ pub struct CacheStore {
     blob_entries: RwLock<HashMap<String, BlobEntry>>,
     /// Blob 磁盘存储目录（`None` 表示未启用）
     blob_dir: RwLock<Option<PathBuf>>,
+    /// Blob 磁盘文件只读（安全模式）：可读取已有文件，不写入、不删除
+    blob_read_only: AtomicBool,
 }
 
 impl CacheStore {
@@ -78,6 +82,7 @@ impl CacheStore {
             entries: RwLock::new(HashMap::new()),
             blob_entries: RwLock::new(HashMap::new()),
             blob_dir: RwLock::new(None),
+            blob_read_only: AtomicBool::new(false),
         }
     }
 
@@ -94,6 +99,11 @@ impl CacheStore {
         Ok(())
     }
 
+    /// 设置 Blob 磁盘文件是否只读。只读时 [`set_blob`](Self::set_blob) 报错，删除 / 过期只清理内存元数据。
+    pub fn set_blob_read_only(&self, read_only: bool) {
+        self.blob_read_only.store(read_only, Ordering::Relaxed);
+    }
+
     /// 是否已启用 Blob 磁盘存储。
     pub fn blob_storage_enabled(&self) -> bool {
         self.blob_dir.read().is_some()
@@ -229,6 +239,9 @@ impl CacheStore {
     /// 若 key 已存在，旧的 TTL 和文件会被覆盖。
     pub fn set_blob(&self, key: &str, data: &[u8], ttl: &Ttl) -> Result<(), String> {
         let _scope = perf::scope("cache.set_blob");
+        if self.blob_read_only.load(Ordering::Relaxed) {
+            return Err(safe_mode::READ_ONLY_ERROR.to_string());
+        }
         let path = self
             .blob_path(key)
             .ok_or_else(|| "Blob 存储未启用，请先调用 enable_blob_storage".to_string())?;
@@ -280,7 +293,7 @@ impl CacheStore {
     /// 返回 `true` 表示条目存在并被删除。
     pub fn remove_blob(&self, key: &str) -> bool {
         let existed = self.blob_entries.write().remove(key).is_some();
-        if existed {
+        if existed && !self.blob_read_only.load(Ordering::Relaxed) {
             if let Some(path) = self.blob_path(key) {
                 let _ = fs::remove_file(&path);
             }
@@ -0,0 +1,77 @@
+//! 协作式取消 — 长耗时任务的取消令牌与按任务 ID 的登记表。
+//!
+//! 任务在处理每个工作单元前检查 [`CancellationToken::is_cancelled`]，
+//! 发现已取消就停止并返回已完成的部分结果；不会强行中断正在进行的 IO。
+//!
+//! 前端发起任务时附带一个自选的 `task_id`，需要取消时再以同一 ID 调用取消命令，
+//! 两者通过 [`CancellationRegistry`] 关联。
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 取消令牌。克隆得到的副本共享同一个取消状态。
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消。
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 是否已请求取消。
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// 进行中任务的取消令牌登记表。
+#[derive(Default)]
+pub struct CancellationRegistry {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl CancellationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为任务登记新令牌；同 ID 的旧任务会先被取消。
+    pub fn register(&self, task_id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(old) = self.tokens.lock().insert(task_id.to_string(), token.clone()) {
+            old.cancel();
+        }
+        token
+    }
+
+    /// 取消任务，返回任务是否存在。
+    pub fn cancel(&self, task_id: &str) -> bool {
+        match self.tokens.lock().get(task_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 任务结束后移除令牌（同 ID 已被新任务占用时保留新令牌）。
+    pub fn finish(&self, task_id: &str, token: &CancellationToken) {
+        let mut tokens = self.tokens.lock();
+        if tokens
+            .get(task_id)
+            .is_some_and(|t| Arc::ptr_eq(&t.cancelled, &token.cancelled))
+        {
+            tokens.remove(task_id);
+        }
+    }
+}
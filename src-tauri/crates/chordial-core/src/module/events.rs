@@ -0,0 +1,205 @@
+//! 应用内事件总线 — 子系统之间、以及 core 与前端之间的类型化通知。
+//!
+//! 扫描、监听、P2P、批量分析等子系统只管往 [`EventBus`] 发布 [`AppEvent`]，
+//! 不需要知道谁在订阅；订阅方（如 Tauri 层的前端桥接）各自 [`subscribe`](EventBus::subscribe)。
+//!
+//! 底层为 `tokio::sync::broadcast`：发布永不阻塞，没有订阅者时事件直接丢弃；
+//! 订阅者处理过慢时会丢失最旧的事件（收到 `Lagged`），不会拖慢发布方。
+//!
+//! 每个事件归属一个 [`EventTopic`]，供 HTTP 模式的事件流按主题订阅。
+
+use crate::module::analysis::TranscodeScanSummary;
+use crate::module::music_library::diff::LibraryDiff;
+use crate::module::music_localSource::scan_job::ScanProgress;
+use crate::module::music_localSource::source::RescanReport;
+use crate::module::p2p::P2pEvent;
+use crate::module::playback::CrossfadeKeep;
+use crate::module::readiness::Subsystem;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// 每个订阅者最多积压的事件数。
+const EVENT_BUS_CAPACITY: usize = 256;
+
+/// 应用事件。
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AppEvent {
+    /// 库内容发生变化（增删来源、扫描、CRUD、元数据补全）
+    LibraryChanged,
+    /// 批量操作（重扫、增删文件夹、文件监听同步）后的增量变化，前端只刷新受影响的条目；没有变化时不发布
+    LibraryDiff(LibraryDiff),
+    /// 批量元数据读取进度
+    MetadataReadProgress {
+        task_id: String,
+        done: usize,
+        total: usize,
+    },
+    /// 后台扫描进度：开始扫描每个文件夹时、以及读取文件期间每处理一批时发布
+    ScanProgress(ScanProgress),
+    /// 后台扫描结束；`report` 为已完成文件夹的累计结果
+    ScanFinished {
+        report: RescanReport,
+        cancelled: bool,
+        error: Option<String>,
+    },
+    /// 批量分析进度
+    AnalysisProgress {
+        task_id: String,
+        done: usize,
+        total: usize,
+    },
+    /// 批量分析结束（完成或被取消）
+    AnalysisFinished {
+        task_id: String,
+        summary: TranscodeScanSummary,
+        cancelled: bool,
+    },
+    /// 离线混音渲染进度（按已完成的歌曲数计）
+    RenderProgress {
+        task_id: String,
+        done: usize,
+        total: usize,
+    },
+    /// 用户取消了进行中的交叉淡化；前端混音器按 `keep` 停掉另一首
+    CrossfadeCancelled {
+        from_song_id: String,
+        to_song_id: String,
+        keep: CrossfadeKeep,
+    },
+    /// 文件因反复探测失败而被隔离
+    FileQuarantined { path: String },
+    /// 系统从休眠中唤醒；`pause` 为唤醒后是否保持暂停
+    SystemResumed {
+        #[cfg_attr(feature = "ts", ts(type = "number"))]
+        slept_secs: u64,
+        pause: bool,
+    },
+    /// 子系统完成启动预热；`error` 不为空表示预热失败（子系统按已有数据继续工作）
+    SubsystemReady {
+        subsystem: Subsystem,
+        error: Option<String>,
+    },
+    /// 所有子系统都已结束启动预热
+    AppReady,
+    /// P2P 子系统事件
+    P2p(P2pEvent),
+}
+
+impl AppEvent {
+    /// 事件所属的主题。
+    pub fn topic(&self) -> EventTopic {
+        match self {
+            AppEvent::LibraryChanged | AppEvent::LibraryDiff(_) => EventTopic::Library,
+            AppEvent::MetadataReadProgress { .. }
+            | AppEvent::ScanProgress(_)
+            | AppEvent::ScanFinished { .. }
+            | AppEvent::FileQuarantined { .. } => EventTopic::Scan,
+            AppEvent::AnalysisProgress { .. } | AppEvent::AnalysisFinished { .. } | AppEvent::RenderProgress { .. } => {
+                EventTopic::Analysis
+            }
+            AppEvent::CrossfadeCancelled { .. } | AppEvent::SystemResumed { .. } => EventTopic::Playback,
+            AppEvent::SubsystemReady { .. } | AppEvent::AppReady => EventTopic::System,
+            AppEvent::P2p(_) => EventTopic::P2p,
+        }
+    }
+}
+
+/// 事件主题 — 订阅方按主题筛选事件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+    /// 库内容变化
+    Library,
+    /// 扫描与元数据读取
+    Scan,
+    /// 批量分析与离线渲染
+    Analysis,
+    /// 播放控制
+    Playback,
+    /// 启动预热
+    System,
+    /// P2P
+    P2p,
+}
+
+impl EventTopic {
+    pub const ALL: [EventTopic; 6] = [
+        EventTopic::Library,
+        EventTopic::Scan,
+        EventTopic::Analysis,
+        EventTopic::Playback,
+        EventTopic::System,
+        EventTopic::P2p,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventTopic::Library => "library",
+            EventTopic::Scan => "scan",
+            EventTopic::Analysis => "analysis",
+            EventTopic::Playback => "playback",
+            EventTopic::System => "system",
+            EventTopic::P2p => "p2p",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|topic| topic.as_str() == name)
+    }
+}
+
+/// 事件总线。
+pub struct EventBus {
+    tx: broadcast::Sender<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { tx }
+    }
+
+    /// 发布事件。没有订阅者时静默丢弃。
+    pub fn publish(&self, event: AppEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    /// 订阅之后发布的所有事件。
+    pub fn subscribe(&self) -> broadcast::Receiver<AppEvent> {
+        self.tx.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_subscriber_receives_event() {
+        let bus = EventBus::new();
+        bus.publish(AppEvent::LibraryChanged); // 无订阅者，不应出错
+        let mut a = bus.subscribe();
+        let mut b = bus.subscribe();
+        bus.publish(AppEvent::FileQuarantined { path: "/x.flac".into() });
+        assert!(matches!(a.try_recv(), Ok(AppEvent::FileQuarantined { .. })));
+        assert!(matches!(b.try_recv(), Ok(AppEvent::FileQuarantined { .. })));
+        assert!(a.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_topic_names_round_trip() {
+        for topic in EventTopic::ALL {
+            assert_eq!(EventTopic::parse(topic.as_str()), Some(topic));
+        }
+        assert_eq!(EventTopic::parse("nope"), None);
+        assert_eq!(AppEvent::FileQuarantined { path: "/x.flac".into() }.topic(), EventTopic::Scan);
+    }
+}
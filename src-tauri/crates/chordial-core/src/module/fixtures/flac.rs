@@ -0,0 +1,80 @@
+//! FLAC 夹具：`fLaC` + STREAMINFO + VORBIS_COMMENT + PICTURE + VERBATIM 帧。
+
+use super::{FixtureSpec, COVER_SIZE};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use crate::module::playback::flac::{encode_frame, streaminfo_block, BLOCK_SIZE};
+
+pub(super) const BLOCK_VORBIS_COMMENT: u8 = 4;
+const BLOCK_PICTURE: u8 = 6;
+
+pub(super) fn build(spec: &FixtureSpec, pcm: &[i16], cover: Option<&[u8]>) -> Vec<u8> {
+    let mut out = b"fLaC".to_vec();
+    out.extend(streaminfo_block(spec.sample_rate, spec.frames(), false));
+    out.extend(metadata_block(BLOCK_VORBIS_COMMENT, cover.is_none(), &vorbis_comment(spec, None)));
+    if let Some(cover) = cover {
+        out.extend(metadata_block(BLOCK_PICTURE, true, &picture(cover)));
+    }
+    for frame in frames(pcm) {
+        out.extend(frame);
+    }
+    out
+}
+
+/// 按 [`BLOCK_SIZE`] 切分并编码的音频帧。
+pub(super) fn frames(pcm: &[i16]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    pcm.chunks(BLOCK_SIZE * 2)
+        .enumerate()
+        .map(|(n, block)| encode_frame(block, n as u64))
+}
+
+/// 带 4 字节块头的元数据块。
+pub(super) fn metadata_block(kind: u8, last: bool, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![kind | if last { 0x80 } else { 0 }];
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    out.extend_from_slice(body);
+    out
+}
+
+/// VORBIS_COMMENT 块体（小端长度，与 Ogg Vorbis 的注释头相同，只是没有帧标志位）。
+/// 给出 `cover` 时附带 base64 编码的 `METADATA_BLOCK_PICTURE` 字段。
+pub(super) fn vorbis_comment(spec: &FixtureSpec, cover: Option<&[u8]>) -> Vec<u8> {
+    let mut fields = vec![
+        format!("TITLE={}", spec.title),
+        format!("ARTIST={}", spec.artist),
+        format!("ALBUM={}", spec.album),
+    ];
+    if let Some(cover) = cover {
+        fields.push(format!("METADATA_BLOCK_PICTURE={}", B64.encode(picture(cover))));
+    }
+    let mut out = Vec::new();
+    put_string(&mut out, VENDOR);
+    out.extend_from_slice(&(fields.len() as u32).to_le_bytes());
+    for field in &fields {
+        put_string(&mut out, field);
+    }
+    out
+}
+
+const VENDOR: &str = "Chordial fixtures";
+
+fn put_string(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// PICTURE 块体：封面正面（类型 3）。
+fn picture(cover: &[u8]) -> Vec<u8> {
+    let mime = b"image/jpeg";
+    let mut out = Vec::with_capacity(cover.len() + 48);
+    out.extend_from_slice(&3u32.to_be_bytes());
+    out.extend_from_slice(&(mime.len() as u32).to_be_bytes());
+    out.extend_from_slice(mime);
+    out.extend_from_slice(&0u32.to_be_bytes()); // 描述为空
+    out.extend_from_slice(&COVER_SIZE.to_be_bytes());
+    out.extend_from_slice(&COVER_SIZE.to_be_bytes());
+    out.extend_from_slice(&24u32.to_be_bytes());
+    out.extend_from_slice(&0u32.to_be_bytes()); // 非索引色
+    out.extend_from_slice(&(cover.len() as u32).to_be_bytes());
+    out.extend_from_slice(cover);
+    out
+}
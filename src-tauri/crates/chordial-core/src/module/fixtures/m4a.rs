@@ -0,0 +1,136 @@
+//! M4A 夹具：`ftyp` + `mdat` + `moov`，单条音频轨道，`sowt`（16 bit 小端 PCM）采样描述。
+//!
+//! 没有 AAC 编码器，PCM 是 QuickTime / ISO 都认可的最简单的 MP4 音频编码。
+//! 全部样本放在一个 chunk 里，`mdat` 放在 `moov` 前面，chunk 偏移在写 `moov` 之前就已确定。
+
+use super::FixtureSpec;
+
+/// `ilst` 数据类型：UTF-8 文本 / JPEG
+const DATA_UTF8: u32 = 1;
+const DATA_JPEG: u32 = 13;
+
+pub(super) fn build(spec: &FixtureSpec, pcm: &[i16], cover: Option<&[u8]>) -> Vec<u8> {
+    let frames = spec.frames() as u32;
+    let rate = spec.sample_rate;
+
+    let mut ftyp = b"M4A ".to_vec();
+    ftyp.extend_from_slice(&0u32.to_be_bytes());
+    ftyp.extend_from_slice(b"M4A mp42isom");
+    let ftyp = atom(b"ftyp", &ftyp);
+
+    let data: Vec<u8> = pcm.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let mdat = atom(b"mdat", &data);
+    let chunk_offset = (ftyp.len() + 8) as u32;
+
+    let mut out = ftyp;
+    out.extend(mdat);
+    out.extend(moov(spec, frames, rate, chunk_offset, cover));
+    out
+}
+
+fn moov(spec: &FixtureSpec, frames: u32, rate: u32, chunk_offset: u32, cover: Option<&[u8]>) -> Vec<u8> {
+    let mut mvhd = be(&[0, 0, rate, frames, 0x0001_0000]);
+    mvhd.extend_from_slice(&[0x01, 0x00]); // 音量 1.0
+    mvhd.extend_from_slice(&[0; 10]);
+    mvhd.extend(unity_matrix());
+    mvhd.extend_from_slice(&[0; 24]);
+    mvhd.extend(be(&[2])); // next_track_ID
+
+    let mut tkhd = be(&[0, 0, 1, 0, frames, 0, 0]);
+    tkhd.extend_from_slice(&[0, 0, 0, 0, 0x01, 0x00, 0, 0]); // layer / 分组 / 音量 / 保留
+    tkhd.extend(unity_matrix());
+    tkhd.extend(be(&[0, 0])); // 宽高
+
+    let mut mdhd = be(&[0, 0, rate, frames]);
+    mdhd.extend_from_slice(&[0x55, 0xc4, 0, 0]); // 语言 "und"
+
+    let mut stsd_entry = vec![0; 6];
+    stsd_entry.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+    stsd_entry.extend_from_slice(&[0; 8]); // 版本 0 / 修订 / 厂商
+    stsd_entry.extend_from_slice(&2u16.to_be_bytes());
+    stsd_entry.extend_from_slice(&16u16.to_be_bytes());
+    stsd_entry.extend_from_slice(&[0; 4]);
+    stsd_entry.extend(be(&[rate << 16]));
+    let mut stsd = be(&[1]);
+    stsd.extend(atom(b"sowt", &stsd_entry));
+
+    let stbl = [
+        full_atom(b"stsd", &stsd),
+        full_atom(b"stts", &be(&[1, frames, 1])),
+        full_atom(b"stsc", &be(&[1, 1, frames, 1])),
+        full_atom(b"stsz", &be(&[4, frames])),
+        full_atom(b"stco", &be(&[1, chunk_offset])),
+    ]
+    .concat();
+
+    let dinf = atom(b"dinf", &full_atom(b"dref", &[be(&[1]), atom(b"url ", &be(&[1]))].concat()));
+    let minf = [full_atom(b"smhd", &[0; 4]), dinf, atom(b"stbl", &stbl)].concat();
+    let mdia = [
+        full_atom(b"mdhd", &mdhd),
+        full_atom(b"hdlr", &handler(b"soun", b"SoundHandler")),
+        atom(b"minf", &minf),
+    ]
+    .concat();
+    let trak = [full_atom_flags(b"tkhd", 0x7, &tkhd), atom(b"mdia", &mdia)].concat();
+
+    let mut ilst = Vec::new();
+    for (name, text) in [(b"\xa9nam", &spec.title), (b"\xa9ART", &spec.artist), (b"\xa9alb", &spec.album)] {
+        ilst.extend(tag(name, DATA_UTF8, text.as_bytes()));
+    }
+    if let Some(cover) = cover {
+        ilst.extend(tag(b"covr", DATA_JPEG, cover));
+    }
+    let meta = [full_atom(b"hdlr", &handler(b"mdir", b"")), atom(b"ilst", &ilst)].concat();
+    let udta = full_atom(b"meta", &meta);
+
+    let moov = [
+        full_atom(b"mvhd", &mvhd),
+        atom(b"trak", &trak),
+        atom(b"udta", &udta),
+    ]
+    .concat();
+    atom(b"moov", &moov)
+}
+
+/// `ilst` 中的一个标签：`<name>/data`。
+fn tag(name: &[u8; 4], data_type: u32, value: &[u8]) -> Vec<u8> {
+    let mut data = be(&[0]); // 语言 / 国家：默认
+    data.extend_from_slice(value);
+    atom(name, &full_atom_flags(b"data", data_type, &data))
+}
+
+fn handler(kind: &[u8; 4], name: &[u8]) -> Vec<u8> {
+    let mut body = be(&[0]);
+    body.extend_from_slice(kind);
+    body.extend_from_slice(&[0; 12]);
+    body.extend_from_slice(name);
+    body.push(0);
+    body
+}
+
+fn unity_matrix() -> Vec<u8> {
+    be(&[0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000])
+}
+
+fn be(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+fn atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 8);
+    out.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(body);
+    out
+}
+
+/// 版本 0、标志为 0 的 full box。
+fn full_atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    full_atom_flags(name, 0, body)
+}
+
+fn full_atom_flags(name: &[u8; 4], flags: u32, body: &[u8]) -> Vec<u8> {
+    let mut inner = be(&[flags & 0x00ff_ffff]);
+    inner.extend_from_slice(body);
+    atom(name, &inner)
+}
@@ -0,0 +1,226 @@
+//! 测试用音频夹具 — 按固定参数合成极小的合法音频文件，标签、封面、时长都是已知值。
+//!
+//! 读取器、扫描器、解码器的端到端测试需要真实格式的文件，但仓库里不能放有版权的音频，
+//! 各平台编码器输出的字节也不稳定。这里不依赖任何编码器，直接按容器规范拼出字节：
+//! 同样的 [`FixtureSpec`] 永远得到逐字节相同的文件，CI 上结果可复现。
+//!
+//! | 格式 | 音频 | 标签 | 封面 |
+//! |------|------|------|------|
+//! | FLAC | VERBATIM 帧（见 [`playback::flac`](crate::module::playback::flac)） | `VORBIS_COMMENT` | `PICTURE` 块 |
+//! | OGG | Ogg FLAC 映射，每页一帧 | `VORBIS_COMMENT` 头包 | 注释中的 `METADATA_BLOCK_PICTURE` |
+//! | MP3 | MPEG-1 Layer III 128kbps 静音帧 + `Info` 帧 | ID3v2.4 `TIT2` / `TPE1` / `TALB` | `APIC` |
+//! | M4A | `sowt`（16 bit 小端 PCM）采样描述 | `ilst` 的 `©nam` / `©ART` / `©alb` | `covr` |
+//! | WAV | 16 bit PCM | `LIST/INFO` 的 `INAM` / `IART` / `IPRD`（symphonia 0.6 读不到） | 无（WAV 没有通用的封面字段） |
+//!
+//! 除 MP3 外音频内容都是左 440Hz、右 660Hz 的正弦波（-12dBFS）；MP3 没有编码器可用，
+//! 只能写出全零的主数据，解码结果是静音，但帧数与时长是准确的。
+//!
+//! 仅在测试或启用 `fixtures` feature 时编译；`chordial-fixtures` 命令行工具用它把夹具写到目录：
+//! `cargo run -p chordial-core --features fixtures --bin chordial-fixtures -- <目录>`
+
+mod flac;
+mod m4a;
+mod mp3;
+mod ogg;
+mod wav;
+
+use crate::module::artwork::image::RgbImage;
+use crate::module::artwork::jpeg;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 夹具的容器格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureFormat {
+    Mp3,
+    Flac,
+    M4a,
+    Ogg,
+    Wav,
+}
+
+impl FixtureFormat {
+    pub const ALL: [FixtureFormat; 5] = [Self::Mp3, Self::Flac, Self::M4a, Self::Ogg, Self::Wav];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Flac => "flac",
+            Self::M4a => "m4a",
+            Self::Ogg => "ogg",
+            Self::Wav => "wav",
+        }
+    }
+
+    /// 该格式能否携带嵌入封面。
+    pub fn supports_cover(self) -> bool {
+        self != Self::Wav
+    }
+}
+
+/// 夹具参数。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureSpec {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// 时长（毫秒）
+    pub duration_ms: u32,
+    /// 采样率（Hz）；MP3 只支持 32000 / 44100 / 48000
+    pub sample_rate: u32,
+    /// 是否嵌入封面（见 [`cover_jpeg`]）
+    pub cover: bool,
+}
+
+impl Default for FixtureSpec {
+    fn default() -> Self {
+        Self {
+            title: "Fixture Tone".to_string(),
+            artist: "Chordial Fixtures".to_string(),
+            album: "Test Signals".to_string(),
+            duration_ms: 2000,
+            sample_rate: 44_100,
+            cover: true,
+        }
+    }
+}
+
+impl FixtureSpec {
+    /// 采样帧数（向下取整）。
+    pub fn frames(&self) -> u64 {
+        self.duration_ms as u64 * self.sample_rate as u64 / 1000
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(8_000..=65_535).contains(&self.sample_rate) {
+            return Err(format!("夹具采样率 {}Hz 超出范围（8000 ~ 65535Hz）", self.sample_rate));
+        }
+        if self.duration_ms == 0 || self.duration_ms > 10 * 60 * 1000 {
+            return Err(format!("夹具时长 {}ms 超出范围（1ms ~ 10 分钟）", self.duration_ms));
+        }
+        Ok(())
+    }
+}
+
+/// 嵌入的封面：32×32 的渐变图，JPEG 编码。
+pub const COVER_SIZE: u32 = 32;
+
+/// 生成封面的 JPEG 字节（每次调用结果相同）。
+pub fn cover_jpeg() -> Result<Vec<u8>, String> {
+    let mut pixels = Vec::with_capacity((COVER_SIZE * COVER_SIZE * 3) as usize);
+    for y in 0..COVER_SIZE {
+        for x in 0..COVER_SIZE {
+            pixels.extend_from_slice(&[(x * 8) as u8, (y * 8) as u8, 0x80]);
+        }
+    }
+    let image = RgbImage {
+        width: COVER_SIZE,
+        height: COVER_SIZE,
+        pixels,
+    };
+    jpeg::encode(&image, 85)
+}
+
+/// 交织的 16 bit 立体声正弦波样本。
+fn tone(spec: &FixtureSpec) -> Vec<i16> {
+    let amplitude = 0.25 * i16::MAX as f64;
+    let rate = spec.sample_rate as f64;
+    let mut pcm = Vec::with_capacity(spec.frames() as usize * 2);
+    for n in 0..spec.frames() {
+        let t = n as f64 / rate;
+        for freq in [440.0, 660.0] {
+            pcm.push((amplitude * (std::f64::consts::TAU * freq * t).sin()).round() as i16);
+        }
+    }
+    pcm
+}
+
+/// 合成一个夹具文件的完整字节。
+pub fn generate(format: FixtureFormat, spec: &FixtureSpec) -> Result<Vec<u8>, String> {
+    spec.validate()?;
+    let cover = match spec.cover && format.supports_cover() {
+        true => Some(cover_jpeg()?),
+        false => None,
+    };
+    let cover = cover.as_deref();
+    match format {
+        FixtureFormat::Mp3 => mp3::build(spec, cover),
+        FixtureFormat::Flac => Ok(flac::build(spec, &tone(spec), cover)),
+        FixtureFormat::M4a => Ok(m4a::build(spec, &tone(spec), cover)),
+        FixtureFormat::Ogg => Ok(ogg::build(spec, &tone(spec), cover)),
+        FixtureFormat::Wav => Ok(wav::build(spec, &tone(spec))),
+    }
+}
+
+/// 把夹具写到 `dir/<stem>.<扩展名>`，返回文件路径。
+pub fn write(dir: &Path, stem: &str, format: FixtureFormat, spec: &FixtureSpec) -> Result<PathBuf, String> {
+    let bytes = generate(format, spec)?;
+    fs::create_dir_all(dir).map_err(|e| format!("创建夹具目录失败: {}", e))?;
+    let path = dir.join(format!("{}.{}", stem, format.extension()));
+    fs::write(&path, bytes).map_err(|e| format!("写入夹具 '{}' 失败: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// 按同一参数写出所有格式（文件名为 `<stem>.<扩展名>`）。
+pub fn write_all(dir: &Path, stem: &str, spec: &FixtureSpec) -> Result<Vec<PathBuf>, String> {
+    FixtureFormat::ALL.iter().map(|format| write(dir, stem, *format, spec)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::analysis::decode;
+    use crate::module::music_localSource::{pictures, scanner};
+
+    #[test]
+    fn test_fixtures_probe_decode_and_cover() {
+        let dir = std::env::temp_dir().join(format!("chordial-fixtures-{}", std::process::id()));
+        let spec = FixtureSpec {
+            title: "夹具 Tone".to_string(),
+            ..FixtureSpec::default()
+        };
+        let cover = cover_jpeg().unwrap();
+
+        for format in FixtureFormat::ALL {
+            let path = write(&dir, "tone", format, &spec).unwrap();
+            // 同样的参数逐字节相同
+            assert_eq!(fs::read(&path).unwrap(), generate(format, &spec).unwrap(), "{:?}", format);
+
+            let meta = scanner::probe_file(&path).unwrap_or_else(|e| panic!("{:?}: {}", format, e));
+            // symphonia 0.6 解析了 WAV 的 LIST/INFO 但没有交给读取器，标签读不到
+            if format != FixtureFormat::Wav {
+                assert_eq!(meta.title.as_deref(), Some("夹具 Tone"), "{:?}", format);
+                assert_eq!(meta.artist.as_deref(), Some("Chordial Fixtures"), "{:?}", format);
+                assert_eq!(meta.album.as_deref(), Some("Test Signals"), "{:?}", format);
+            }
+            assert_eq!(meta.duration_secs, Some(2), "{:?}", format);
+            assert_eq!(meta.sample_rate, Some(44_100), "{:?}", format);
+            assert_eq!(meta.channels, Some(2), "{:?}", format);
+
+            let (mut frames, mut peak) = (0usize, 0f32);
+            decode::decode_file(&path, |block| {
+                frames += block.samples.len() / block.channels;
+                peak = block.samples.iter().fold(peak, |p, s| p.max(s.abs()));
+            })
+            .unwrap_or_else(|e| panic!("{:?}: {}", format, e));
+            if format == FixtureFormat::Mp3 {
+                assert!(frames as u64 >= spec.frames(), "{:?}", format);
+                assert_eq!(peak, 0.0);
+            } else {
+                assert_eq!(frames as u64, spec.frames(), "{:?}", format);
+                assert!((peak - 0.25).abs() < 0.01, "{:?}: peak {}", format, peak);
+            }
+
+            let embedded = match format {
+                FixtureFormat::Ogg => Some(scanner::extract_cover_art(&path).unwrap()),
+                _ => pictures::pick_cover(&pictures::index_file(&path))
+                    .map(|picture| pictures::read_picture(&path, picture).unwrap()),
+            };
+            match format.supports_cover() {
+                true => assert_eq!(embedded.as_ref(), Some(&cover), "{:?}", format),
+                false => assert_eq!(embedded, None),
+            }
+        }
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,77 @@
+//! MP3 夹具：ID3v2.4 标签 + `Info` 帧 + MPEG-1 Layer III 静音帧（128kbps 立体声）。
+//!
+//! 边信息全零时 `part2_3_length` 为 0，解码器不读取任何哈夫曼数据，输出静音。
+//! `Info` 帧记录音频帧数，解码器据此得到准确时长，不必按码率估算。
+
+use super::FixtureSpec;
+
+const SAMPLES_PER_FRAME: u64 = 1152;
+const BITRATE: u32 = 128_000;
+/// MPEG-1 立体声的边信息字节数
+const SIDE_INFO_LEN: usize = 32;
+
+pub(super) fn build(spec: &FixtureSpec, cover: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let rate_index = match spec.sample_rate {
+        44_100 => 0b00,
+        48_000 => 0b01,
+        32_000 => 0b10,
+        rate => return Err(format!("MP3 夹具不支持 {}Hz 采样率（仅 32000 / 44100 / 48000Hz）", rate)),
+    };
+    // 同步字 + MPEG-1 + Layer III + 无 CRC；码率索引 9（128kbps）；无填充；立体声
+    let header = [0xff, 0xfb, 0x90 | (rate_index << 2), 0x00];
+    let frame_len = (144 * BITRATE / spec.sample_rate) as usize;
+    let audio_frames = spec.frames().div_ceil(SAMPLES_PER_FRAME) as u32;
+
+    let mut out = id3_tag(spec, cover);
+
+    let mut info = header.to_vec();
+    info.resize(4 + SIDE_INFO_LEN, 0);
+    info.extend_from_slice(b"Info");
+    info.extend_from_slice(&1u32.to_be_bytes()); // 只有帧数字段
+    info.extend_from_slice(&audio_frames.to_be_bytes());
+    info.resize(frame_len, 0);
+    out.extend(info);
+
+    let mut silent = header.to_vec();
+    silent.resize(frame_len, 0);
+    for _ in 0..audio_frames {
+        out.extend_from_slice(&silent);
+    }
+    Ok(out)
+}
+
+fn id3_tag(spec: &FixtureSpec, cover: Option<&[u8]>) -> Vec<u8> {
+    let mut frames = Vec::new();
+    for (id, text) in [(b"TIT2", &spec.title), (b"TPE1", &spec.artist), (b"TALB", &spec.album)] {
+        let mut body = vec![3]; // UTF-8
+        body.extend_from_slice(text.as_bytes());
+        push_frame(&mut frames, id, &body);
+    }
+    if let Some(cover) = cover {
+        let mut body = vec![0]; // ISO-8859-1（描述为空）
+        body.extend_from_slice(b"image/jpeg\0");
+        body.push(3); // 封面正面
+        body.push(0);
+        body.extend_from_slice(cover);
+        push_frame(&mut frames, b"APIC", &body);
+    }
+
+    let mut out = b"ID3".to_vec();
+    out.extend_from_slice(&[4, 0, 0]);
+    out.extend_from_slice(&synchsafe(frames.len()));
+    out.extend(frames);
+    out
+}
+
+fn push_frame(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&synchsafe(body.len()));
+    out.extend_from_slice(&[0, 0]);
+    out.extend_from_slice(body);
+}
+
+/// ID3v2.4 的 28 bit 同步安全整数。
+fn synchsafe(n: usize) -> [u8; 4] {
+    let n = n as u32;
+    [(n >> 21) as u8 & 0x7f, (n >> 14) as u8 & 0x7f, (n >> 7) as u8 & 0x7f, n as u8 & 0x7f]
+}
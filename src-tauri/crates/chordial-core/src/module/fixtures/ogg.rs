@@ -0,0 +1,62 @@
+//! OGG 夹具：Ogg FLAC 映射（识别头包 + VORBIS_COMMENT 头包 + 每页一个音频帧）。
+
+use super::flac::{frames, metadata_block, vorbis_comment, BLOCK_VORBIS_COMMENT};
+use super::FixtureSpec;
+use crate::module::music_localSource::tag_writer::ogg::crc32;
+use crate::module::playback::flac::{streaminfo_block, BLOCK_SIZE};
+
+const SERIAL: u32 = 0x4348_5244; // "CHRD"
+const FLAG_BOS: u8 = 0x02;
+const FLAG_EOS: u8 = 0x04;
+
+pub(super) fn build(spec: &FixtureSpec, pcm: &[i16], cover: Option<&[u8]>) -> Vec<u8> {
+    // 封面按 Ogg 的惯例以 METADATA_BLOCK_PICTURE 字段放在注释里：symphonia 把每个头包
+    // 当作一次独立的元数据修订，单独的 PICTURE 头包会和标签分属两次修订
+    let headers = [metadata_block(BLOCK_VORBIS_COMMENT, true, &vorbis_comment(spec, cover))];
+
+    // 识别头包：0x7F "FLAC"、映射版本 1.0、其后的头包数、原生签名与 STREAMINFO
+    let mut ident = vec![0x7f];
+    ident.extend_from_slice(b"FLAC");
+    ident.extend_from_slice(&[1, 0]);
+    ident.extend_from_slice(&(headers.len() as u16).to_be_bytes());
+    ident.extend_from_slice(b"fLaC");
+    ident.extend(streaminfo_block(spec.sample_rate, spec.frames(), false));
+
+    let mut out = Vec::new();
+    let mut sequence = 0;
+    out.extend(page(FLAG_BOS, 0, sequence, &ident));
+    for header in &headers {
+        sequence += 1;
+        out.extend(page(0, 0, sequence, header));
+    }
+
+    let total_frames = pcm.len() / 2;
+    let mut granule = 0u64;
+    for (n, frame) in frames(pcm).enumerate() {
+        granule = (granule + BLOCK_SIZE as u64).min(total_frames as u64);
+        let is_last = (n + 1) * BLOCK_SIZE >= total_frames;
+        sequence += 1;
+        out.extend(page(if is_last { FLAG_EOS } else { 0 }, granule, sequence, &frame));
+    }
+    out
+}
+
+/// 只含一个完整包的页。
+fn page(flags: u8, granule: u64, sequence: u32, packet: &[u8]) -> Vec<u8> {
+    let mut lacing = vec![255u8; packet.len() / 255];
+    lacing.push((packet.len() % 255) as u8);
+
+    let mut out = b"OggS".to_vec();
+    out.push(0);
+    out.push(flags);
+    out.extend_from_slice(&granule.to_le_bytes());
+    out.extend_from_slice(&SERIAL.to_le_bytes());
+    out.extend_from_slice(&sequence.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // CRC，最后回填
+    out.push(lacing.len() as u8);
+    out.extend_from_slice(&lacing);
+    out.extend_from_slice(packet);
+    let crc = crc32(&out);
+    out[22..26].copy_from_slice(&crc.to_le_bytes());
+    out
+}
@@ -0,0 +1,43 @@
+//! WAV 夹具：`fmt `（16 bit PCM）+ `LIST/INFO` 标签 + `data`。
+//!
+//! symphonia 读到 `data` 块就停止解析，标签块必须放在它前面。
+
+use super::FixtureSpec;
+
+pub(super) fn build(spec: &FixtureSpec, pcm: &[i16]) -> Vec<u8> {
+    let mut fmt = Vec::with_capacity(16);
+    fmt.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    fmt.extend_from_slice(&2u16.to_le_bytes());
+    fmt.extend_from_slice(&spec.sample_rate.to_le_bytes());
+    fmt.extend_from_slice(&(spec.sample_rate * 4).to_le_bytes());
+    fmt.extend_from_slice(&4u16.to_le_bytes());
+    fmt.extend_from_slice(&16u16.to_le_bytes());
+
+    let mut info = b"INFO".to_vec();
+    for (id, text) in [(b"INAM", &spec.title), (b"IART", &spec.artist), (b"IPRD", &spec.album)] {
+        let mut value = text.as_bytes().to_vec();
+        value.push(0);
+        push_chunk(&mut info, id, &value);
+    }
+
+    let data: Vec<u8> = pcm.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+    let mut body = b"WAVE".to_vec();
+    push_chunk(&mut body, b"fmt ", &fmt);
+    push_chunk(&mut body, b"LIST", &info);
+    push_chunk(&mut body, b"data", &data);
+
+    let mut out = Vec::with_capacity(body.len() + 8);
+    push_chunk(&mut out, b"RIFF", &body);
+    out
+}
+
+/// 写入一个 RIFF 块，奇数长度补一个填充字节。
+fn push_chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
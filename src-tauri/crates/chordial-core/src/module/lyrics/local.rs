@@ -0,0 +1,57 @@
+//! 内置提供方 — 读取音频文件同目录的 `.lrc` / `.txt` 歌词。
+
+use super::traits::{LyricsCandidate, LyricsProvider, LyricsQuery};
+use crate::module::music_localSource::scanner;
+use crate::module::platform::PlatformPath;
+
+/// 同目录歌词文件提供方。候选 ID 即音频文件路径。
+pub struct LocalFileLyricsProvider;
+
+impl LocalFileLyricsProvider {
+    pub const NAME: &str = "local_file";
+}
+
+impl LyricsProvider for LocalFileLyricsProvider {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    /// 本地文件最可信，也没有网络开销，始终最先查询。
+    fn priority(&self) -> i32 {
+        100
+    }
+
+    fn search(&self, query: &LyricsQuery) -> Result<Vec<LyricsCandidate>, String> {
+        let Some(path) = query.file_path.as_deref() else {
+            return Ok(Vec::new());
+        };
+        let Some(text) = scanner::read_lyric_file(&PlatformPath::from(path)) else {
+            return Ok(Vec::new());
+        };
+        Ok(vec![LyricsCandidate {
+            provider: Self::NAME.to_string(),
+            id: path.to_string(),
+            title: query.title.clone(),
+            artist: query.artist.clone(),
+            synced: is_synced(&text),
+        }])
+    }
+
+    fn fetch(&self, id: &str) -> Result<String, String> {
+        scanner::read_lyric_file(&PlatformPath::from(id))
+            .ok_or_else(|| format!("未找到歌词文件: {}", id))
+    }
+}
+
+/// 粗略判断是否为 LRC：任意一行以 `[mm:ss` 形式的时间标签开头。
+fn is_synced(text: &str) -> bool {
+    text.lines().any(|line| {
+        let bytes = line.trim_start().as_bytes();
+        bytes.len() >= 6
+            && bytes[0] == b'['
+            && bytes[1].is_ascii_digit()
+            && bytes[2].is_ascii_digit()
+            && bytes[3] == b':'
+            && bytes[4].is_ascii_digit()
+    })
+}
@@ -0,0 +1,21 @@
+//! 歌词提供方 — 可插拔的歌词搜索 / 获取后端。
+//!
+//! 与 [`music_source`](crate::module::music_source) 的来源插件类似，
+//! 每个后端实现 [`LyricsProvider`]，注册到 [`LyricsRegistry`] 后即参与调度；
+//! 核心代码不需要知道具体有哪些提供方。
+//!
+//! # 模块布局
+//!
+//! | 文件 | 职责 |
+//! |------|------|
+//! | [`traits`] | `LyricsProvider` 接口 + 查询 / 候选 / 限流类型 |
+//! | [`registry`] | `LyricsRegistry` — 优先级调度、限流、启用开关、健康统计 |
+//! | [`local`] | 内置提供方：同目录 `.lrc` / `.txt` 文件 |
+
+pub mod local;
+pub mod registry;
+pub mod traits;
+
+pub use local::LocalFileLyricsProvider;
+pub use registry::{LyricsProviderStatus, LyricsRegistry};
+pub use traits::{LyricsCandidate, LyricsProvider, LyricsQuery, RateLimit};
@@ -0,0 +1,258 @@
+//! 歌词提供方注册表 — 按优先级调度、限流、启用开关与健康统计。
+
+use super::traits::{LyricsCandidate, LyricsProvider, LyricsQuery, RateLimit};
+use crate::module::config::store::ConfigStore;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// ConfigStore 中存放提供方启用开关的键（`{ 名称: bool }`，缺省为启用）。
+const LYRICS_PROVIDERS_CONFIG_KEY: &str = "lyrics_providers";
+
+/// 连续失败达到该次数即视为不健康。
+const UNHEALTHY_AFTER_FAILURES: u32 = 3;
+
+/// 单个提供方的运行时统计。
+#[derive(Default)]
+struct ProviderState {
+    /// 限流窗口内的请求时刻
+    recent_requests: VecDeque<Instant>,
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_success_at: Option<u64>,
+    last_latency_ms: Option<u64>,
+}
+
+impl ProviderState {
+    /// 按限流规则尝试占用一次请求额度。
+    fn try_acquire(&mut self, limit: Option<RateLimit>, now: Instant) -> bool {
+        let Some(limit) = limit else {
+            return true;
+        };
+        let window = Duration::from_secs(limit.per_secs);
+        while self
+            .recent_requests
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= window)
+        {
+            self.recent_requests.pop_front();
+        }
+        if self.recent_requests.len() as u32 >= limit.max_requests {
+            return false;
+        }
+        self.recent_requests.push_back(now);
+        true
+    }
+
+    fn is_rate_limited(&self, limit: Option<RateLimit>, now: Instant) -> bool {
+        limit.is_some_and(|limit| {
+            let window = Duration::from_secs(limit.per_secs);
+            self.recent_requests
+                .iter()
+                .filter(|t| now.duration_since(**t) < window)
+                .count() as u32
+                >= limit.max_requests
+        })
+    }
+
+    fn record<T>(&mut self, result: &Result<T, String>, started: Instant) {
+        self.last_latency_ms = Some(started.elapsed().as_millis() as u64);
+        match result {
+            Ok(_) => {
+                self.successes += 1;
+                self.consecutive_failures = 0;
+                self.last_success_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .ok()
+                    .map(|d| d.as_secs());
+            }
+            Err(e) => {
+                self.failures += 1;
+                self.consecutive_failures += 1;
+                self.last_error = Some(e.clone());
+            }
+        }
+    }
+}
+
+/// 提供方状态（供设置页展示）。
+#[derive(Debug, Clone, Serialize)]
+pub struct LyricsProviderStatus {
+    pub name: String,
+    pub priority: i32,
+    pub enabled: bool,
+    pub rate_limit: Option<RateLimit>,
+    /// 当前是否因限流暂时不可用
+    pub rate_limited: bool,
+    /// 连续失败未达阈值即视为健康
+    pub healthy: bool,
+    pub successes: u64,
+    pub failures: u64,
+    pub last_error: Option<String>,
+    /// 最近一次成功的时间（Unix 秒）
+    pub last_success_at: Option<u64>,
+    pub last_latency_ms: Option<u64>,
+}
+
+/// 歌词提供方注册表。
+///
+/// 新提供方只需实现 [`LyricsProvider`] 并调用 [`register`](Self::register)，
+/// 无需修改调度逻辑。启用开关写入 ConfigStore，重启后保留。
+pub struct LyricsRegistry {
+    config: Arc<ConfigStore>,
+    providers: RwLock<Vec<Arc<dyn LyricsProvider>>>,
+    states: Mutex<HashMap<String, ProviderState>>,
+    enabled: RwLock<HashMap<String, bool>>,
+}
+
+impl LyricsRegistry {
+    pub fn new(config: Arc<ConfigStore>) -> Self {
+        let enabled = config
+            .get::<HashMap<String, bool>>(LYRICS_PROVIDERS_CONFIG_KEY)
+            .unwrap_or_default();
+        Self {
+            config,
+            providers: RwLock::new(Vec::new()),
+            states: Mutex::new(HashMap::new()),
+            enabled: RwLock::new(enabled),
+        }
+    }
+
+    /// 注册提供方；同名提供方会被替换。
+    pub fn register(&self, provider: Arc<dyn LyricsProvider>) {
+        let mut providers = self.providers.write();
+        providers.retain(|p| p.name() != provider.name());
+        providers.push(provider);
+        providers.sort_by_key(|p| std::cmp::Reverse(p.priority()));
+    }
+
+    /// 注销提供方，返回是否存在。
+    pub fn unregister(&self, name: &str) -> bool {
+        let mut providers = self.providers.write();
+        let before = providers.len();
+        providers.retain(|p| p.name() != name);
+        self.states.lock().remove(name);
+        providers.len() != before
+    }
+
+    fn get(&self, name: &str) -> Option<Arc<dyn LyricsProvider>> {
+        self.providers.read().iter().find(|p| p.name() == name).cloned()
+    }
+
+    /// 提供方是否启用（未设置过时默认启用）。
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled.read().get(name).copied().unwrap_or(true)
+    }
+
+    /// 设置提供方启用状态并持久化。
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> Result<(), String> {
+        if self.get(name).is_none() {
+            return Err(format!("歌词提供方 '{}' 不存在", name));
+        }
+        let snapshot = {
+            let mut map = self.enabled.write();
+            map.insert(name.to_string(), enabled);
+            map.clone()
+        };
+        self.config.set(LYRICS_PROVIDERS_CONFIG_KEY, &snapshot)
+    }
+
+    /// 所有已注册提供方的状态，按优先级降序。
+    pub fn statuses(&self) -> Vec<LyricsProviderStatus> {
+        let now = Instant::now();
+        let states = self.states.lock();
+        self.providers
+            .read()
+            .iter()
+            .map(|p| {
+                let state = states.get(p.name());
+                LyricsProviderStatus {
+                    name: p.name().to_string(),
+                    priority: p.priority(),
+                    enabled: self.is_enabled(p.name()),
+                    rate_limit: p.rate_limit(),
+                    rate_limited: state.is_some_and(|s| s.is_rate_limited(p.rate_limit(), now)),
+                    healthy: state.is_none_or(|s| s.consecutive_failures < UNHEALTHY_AFTER_FAILURES),
+                    successes: state.map_or(0, |s| s.successes),
+                    failures: state.map_or(0, |s| s.failures),
+                    last_error: state.and_then(|s| s.last_error.clone()),
+                    last_success_at: state.and_then(|s| s.last_success_at),
+                    last_latency_ms: state.and_then(|s| s.last_latency_ms),
+                }
+            })
+            .collect()
+    }
+
+    /// 调用提供方一次：检查限流、计时并记录结果。
+    fn call<T>(
+        &self,
+        provider: &dyn LyricsProvider,
+        f: impl FnOnce() -> Result<T, String>,
+    ) -> Result<T, String> {
+        let started = Instant::now();
+        {
+            let mut states = self.states.lock();
+            let state = states.entry(provider.name().to_string()).or_default();
+            if !state.try_acquire(provider.rate_limit(), started) {
+                return Err(format!("歌词提供方 '{}' 请求过于频繁", provider.name()));
+            }
+        }
+        let result = f();
+        self.states
+            .lock()
+            .entry(provider.name().to_string())
+            .or_default()
+            .record(&result, started);
+        result
+    }
+
+    /// 按优先级依次查询所有启用的提供方，汇总候选。
+    ///
+    /// 单个提供方失败或被限流只记录到状态中，不影响其他提供方。
+    pub fn search(&self, query: &LyricsQuery) -> Vec<LyricsCandidate> {
+        let providers = self.providers.read().clone();
+        let mut candidates = Vec::new();
+        for provider in providers.iter().filter(|p| self.is_enabled(p.name())) {
+            match self.call(provider.as_ref(), || provider.search(query)) {
+                Ok(found) => candidates.extend(found),
+                Err(e) => eprintln!("[lyrics] {} 搜索失败: {}", provider.name(), e),
+            }
+        }
+        candidates
+    }
+
+    /// 从指定提供方获取歌词全文。
+    pub fn fetch(&self, provider_name: &str, id: &str) -> Result<String, String> {
+        let provider = self
+            .get(provider_name)
+            .ok_or_else(|| format!("歌词提供方 '{}' 不存在", provider_name))?;
+        if !self.is_enabled(provider_name) {
+            return Err(format!("歌词提供方 '{}' 已禁用", provider_name));
+        }
+        self.call(provider.as_ref(), || provider.fetch(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_window() {
+        let limit = Some(RateLimit {
+            max_requests: 2,
+            per_secs: 1,
+        });
+        let mut state = ProviderState::default();
+        let t0 = Instant::now();
+        assert!(state.try_acquire(limit, t0));
+        assert!(state.try_acquire(limit, t0));
+        assert!(!state.try_acquire(limit, t0 + Duration::from_millis(500)));
+        assert!(state.is_rate_limited(limit, t0 + Duration::from_millis(500)));
+        assert!(state.try_acquire(limit, t0 + Duration::from_secs(1)));
+    }
+}
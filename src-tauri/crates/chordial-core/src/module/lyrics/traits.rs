@@ -0,0 +1,75 @@
+//! 歌词提供方接口。
+
+use crate::module::music_library::models::Song;
+use serde::{Deserialize, Serialize};
+
+/// 歌词搜索条件。字段越全，提供方越容易命中正确的版本。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LyricsQuery {
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// 歌曲时长（秒），用于区分同名不同版本
+    pub duration_secs: Option<u64>,
+    /// 本地音频文件路径（仅本地类提供方使用）
+    pub file_path: Option<String>,
+}
+
+impl LyricsQuery {
+    /// 由库内歌曲构造查询；多位艺人以 `/` 连接。
+    pub fn for_song(song: &Song, file_path: Option<String>) -> Self {
+        Self {
+            title: song.title.clone(),
+            artist: (!song.artist_names.is_empty()).then(|| song.artist_names.join(" / ")),
+            album: song.album_title.clone(),
+            duration_secs: song.duration,
+            file_path,
+        }
+    }
+}
+
+/// 提供方返回的一条候选歌词。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LyricsCandidate {
+    /// 提供方名称
+    pub provider: String,
+    /// 提供方内部 ID，传回 [`LyricsProvider::fetch`] 获取全文
+    pub id: String,
+    pub title: String,
+    pub artist: Option<String>,
+    /// 是否为逐行时间轴（LRC）歌词
+    pub synced: bool,
+}
+
+/// 请求频率上限：`per_secs` 秒内最多 `max_requests` 次。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub max_requests: u32,
+    pub per_secs: u64,
+}
+
+/// 歌词提供方插件必须实现的接口。
+///
+/// 实现只负责与具体后端通信；限流、启用开关和健康统计由
+/// [`LyricsRegistry`](super::registry::LyricsRegistry) 统一处理。
+pub trait LyricsProvider: Send + Sync {
+    /// 提供方唯一名称，如 `"local_file"`。
+    fn name(&self) -> &str;
+
+    /// 优先级，数值大的先被查询。
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// 请求频率上限；`None` 表示不限制。
+    fn rate_limit(&self) -> Option<RateLimit> {
+        None
+    }
+
+    /// 按条件搜索候选歌词。无结果时返回空列表。
+    fn search(&self, query: &LyricsQuery) -> Result<Vec<LyricsCandidate>, String>;
+
+    /// 按候选 ID 获取歌词全文（LRC 或纯文本）。
+    fn fetch(&self, id: &str) -> Result<String, String>;
+}
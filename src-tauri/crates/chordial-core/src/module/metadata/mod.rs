@@ -0,0 +1,21 @@
+//! 元数据补全 — 可插拔的专辑 / 艺人信息提供方。
+//!
+//! 各提供方实现 [`MetadataProvider`]，注册到 [`MetadataResolver`] 后按优先级组成责任链。
+//! 每个被写入的字段都会记录来自哪个提供方（如「年份来自 MusicBrainz」）以及写入前的值，
+//! 冲突数据可以被审计，也可以逐字段撤销。
+//!
+//! # 模块布局
+//!
+//! | 文件 | 职责 |
+//! |------|------|
+//! | [`traits`] | `MetadataProvider` 接口 + 可补全字段定义 |
+//! | [`resolver`] | `MetadataResolver` — 责任链解析、来源记录、撤销 |
+//! | [`song_tags`] | 内置兜底提供方：由歌曲标签推断专辑年份、艺人流派 |
+
+pub mod resolver;
+pub mod song_tags;
+pub mod traits;
+
+pub use resolver::{FieldProvenance, MetadataProviderInfo, MetadataResolver};
+pub use song_tags::SongTagsProvider;
+pub use traits::{EnrichTarget, FieldValues, MetadataProvider};
@@ -0,0 +1,322 @@
+//! 元数据解析器 — 责任链补全 + 逐字段来源记录 + 撤销。
+
+use super::traits::{EnrichTarget, FieldValues, MetadataProvider};
+use crate::module::music_library::library::MusicLibrary;
+use crate::module::storage::persistent::PersistentStore;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 一次字段写入的来源记录。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct FieldProvenance {
+    pub field: String,
+    /// 写入的值
+    pub value: Value,
+    /// 写入前的值，撤销时恢复
+    pub previous: Value,
+    /// 给出该值的提供方
+    pub provider: String,
+    /// 写入时间（Unix 秒）
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub applied_at: u64,
+}
+
+/// 已注册提供方的概要。
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct MetadataProviderInfo {
+    pub name: String,
+    pub priority: i32,
+}
+
+/// 元数据解析器。
+///
+/// 补全时按优先级依次询问提供方（责任链）：每个字段由第一个给出非空值的
+/// 提供方决定，所有字段都有着落后不再询问后续提供方。
+/// 每次写入都记录来源与旧值，存放在 `metadata_provenance.json`，
+/// 以实体 ID 为子键、按写入顺序保存，撤销时弹出该字段最近一条记录。
+pub struct MetadataResolver {
+    library: Arc<MusicLibrary>,
+    providers: RwLock<Vec<Arc<dyn MetadataProvider>>>,
+    store: PersistentStore,
+}
+
+impl MetadataResolver {
+    pub fn new(library: Arc<MusicLibrary>, store_path: PathBuf) -> Self {
+        Self {
+            library,
+            providers: RwLock::new(Vec::new()),
+            store: PersistentStore::new(store_path),
+        }
+    }
+
+    /// 注册提供方；同名提供方会被替换。
+    pub fn register(&self, provider: Arc<dyn MetadataProvider>) {
+        let mut providers = self.providers.write();
+        providers.retain(|p| p.name() != provider.name());
+        providers.push(provider);
+        providers.sort_by_key(|p| std::cmp::Reverse(p.priority()));
+    }
+
+    pub fn providers(&self) -> Vec<MetadataProviderInfo> {
+        self.providers
+            .read()
+            .iter()
+            .map(|p| MetadataProviderInfo {
+                name: p.name().to_string(),
+                priority: p.priority(),
+            })
+            .collect()
+    }
+
+    /// 补全实体字段并写回库，返回本次写入的字段记录。
+    ///
+    /// `overwrite` 为 `false` 时只填充当前为空的字段。
+    pub fn enrich(
+        &self,
+        target: EnrichTarget,
+        id: &str,
+        overwrite: bool,
+    ) -> Result<Vec<FieldProvenance>, String> {
+        let mut current = self.load(target, id)?;
+        let wanted: Vec<&str> = target
+            .fields()
+            .iter()
+            .copied()
+            .filter(|f| overwrite || is_empty(&current[*f]))
+            .collect();
+        if wanted.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let providers = self.providers.read().clone();
+        let resolved = resolve_chain(&providers, &wanted, |provider| {
+            self.lookup(provider, target, &current)
+        });
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut applied = Vec::new();
+        for (field, (value, provider)) in resolved {
+            if current[&field] == value {
+                continue;
+            }
+            applied.push(FieldProvenance {
+                field: field.clone(),
+                previous: current[&field].clone(),
+                value: value.clone(),
+                provider,
+                applied_at: now,
+            });
+            current[&field] = value;
+        }
+        if applied.is_empty() {
+            return Ok(applied);
+        }
+
+        self.save_entity(target, current)?;
+        let mut history = self.provenance(target, id);
+        history.extend(applied.iter().cloned());
+        self.store.set_subkey(target.as_str(), id, &history)?;
+        self.store.save()?;
+        Ok(applied)
+    }
+
+    /// 对库中所有该类型的实体执行 [`enrich`](Self::enrich)，返回有字段被写入的实体数。
+    ///
+    /// 单个实体失败只记录日志，不中断其余实体。
+    pub fn enrich_all(&self, target: EnrichTarget, overwrite: bool) -> usize {
+        let ids: Vec<String> = match target {
+            EnrichTarget::Album => self.library.get_all_albums().into_keys().collect(),
+            EnrichTarget::Artist => self.library.get_all_artists().into_keys().collect(),
+        };
+        ids.iter()
+            .filter(|id| match self.enrich(target, id, overwrite) {
+                Ok(applied) => !applied.is_empty(),
+                Err(e) => {
+                    eprintln!("[metadata] 补全 {} '{}' 失败: {}", target.as_str(), id, e);
+                    false
+                }
+            })
+            .count()
+    }
+
+    /// 实体的全部字段写入记录，按写入顺序。
+    pub fn provenance(&self, target: EnrichTarget, id: &str) -> Vec<FieldProvenance> {
+        self.store
+            .get_entry::<Vec<FieldProvenance>>(target.as_str(), id)
+            .unwrap_or_default()
+    }
+
+    /// 撤销某字段最近一次写入：恢复旧值并删除该记录。
+    ///
+    /// 若用户在写入后又手动改过该字段，拒绝撤销以免覆盖手动修改。
+    pub fn revert(&self, target: EnrichTarget, id: &str, field: &str) -> Result<FieldProvenance, String> {
+        let mut history = self.provenance(target, id);
+        let pos = history
+            .iter()
+            .rposition(|r| r.field == field)
+            .ok_or_else(|| format!("字段 '{}' 没有可撤销的记录", field))?;
+        let mut current = self.load(target, id)?;
+        if current[field] != history[pos].value {
+            return Err(format!("字段 '{}' 已被手动修改，无法撤销", field));
+        }
+
+        let record = history.remove(pos);
+        current[field] = record.previous.clone();
+        self.save_entity(target, current)?;
+        if history.is_empty() {
+            self.store.remove_entry(target.as_str(), id);
+        } else {
+            self.store.set_subkey(target.as_str(), id, &history)?;
+        }
+        self.store.save()?;
+        Ok(record)
+    }
+
+    fn lookup(
+        &self,
+        provider: &dyn MetadataProvider,
+        target: EnrichTarget,
+        current: &Value,
+    ) -> Result<FieldValues, String> {
+        match target {
+            EnrichTarget::Album => {
+                let album = serde_json::from_value(current.clone()).map_err(|e| e.to_string())?;
+                let artist_name = current["artist_id"]
+                    .as_str()
+                    .and_then(|id| self.library.get_artist(id))
+                    .map(|a| a.name);
+                provider.lookup_album(&album, artist_name.as_deref())
+            }
+            EnrichTarget::Artist => {
+                let artist = serde_json::from_value(current.clone()).map_err(|e| e.to_string())?;
+                provider.lookup_artist(&artist)
+            }
+        }
+    }
+
+    /// 以 JSON 形式读取实体，便于按字段名读写。
+    fn load(&self, target: EnrichTarget, id: &str) -> Result<Value, String> {
+        let value = match target {
+            EnrichTarget::Album => self.library.get_album(id).map(serde_json::to_value),
+            EnrichTarget::Artist => self.library.get_artist(id).map(serde_json::to_value),
+        };
+        value
+            .ok_or_else(|| format!("{} '{}' 不存在", target.as_str(), id))?
+            .map_err(|e| format!("序列化失败: {}", e))
+    }
+
+    fn save_entity(&self, target: EnrichTarget, value: Value) -> Result<(), String> {
+        match target {
+            EnrichTarget::Album => {
+                let album = serde_json::from_value(value).map_err(|e| format!("字段值无效: {}", e))?;
+                self.library.update_album(&album)?;
+            }
+            EnrichTarget::Artist => {
+                let artist = serde_json::from_value(value).map_err(|e| format!("字段值无效: {}", e))?;
+                self.library.update_artist(&artist)?;
+            }
+        }
+        self.library.save()
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// 责任链：按顺序询问提供方，每个字段取第一个非空值，全部字段有值后提前结束。
+///
+/// 返回 `字段 → (值, 提供方名称)`。提供方出错只记录日志，继续询问下一个。
+fn resolve_chain<F>(
+    providers: &[Arc<dyn MetadataProvider>],
+    wanted: &[&str],
+    mut lookup: F,
+) -> FieldValuesWithSource
+where
+    F: FnMut(&dyn MetadataProvider) -> Result<FieldValues, String>,
+{
+    let mut resolved = FieldValuesWithSource::new();
+    for provider in providers {
+        if wanted.iter().all(|f| resolved.contains_key(*f)) {
+            break;
+        }
+        let fields = match lookup(provider.as_ref()) {
+            Ok(fields) => fields,
+            Err(e) => {
+                eprintln!("[metadata] {} 查询失败: {}", provider.name(), e);
+                continue;
+            }
+        };
+        for (field, value) in fields {
+            if wanted.contains(&field.as_str()) && !is_empty(&value) && !resolved.contains_key(&field) {
+                resolved.insert(field, (value, provider.name().to_string()));
+            }
+        }
+    }
+    resolved
+}
+
+type FieldValuesWithSource = std::collections::BTreeMap<String, (Value, String)>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_library::models::Album;
+    use serde_json::json;
+
+    struct Fixed(&'static str, FieldValues);
+
+    impl MetadataProvider for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn lookup_album(&self, _album: &Album, _artist_name: Option<&str>) -> Result<FieldValues, String> {
+            Ok(self.1.clone())
+        }
+    }
+
+    #[test]
+    fn test_chain_first_provider_wins_per_field() {
+        let providers: Vec<Arc<dyn MetadataProvider>> = vec![
+            Arc::new(Fixed("musicbrainz", FieldValues::from([("year".to_string(), json!(1999))]))),
+            Arc::new(Fixed(
+                "song_tags",
+                FieldValues::from([
+                    ("year".to_string(), json!(2001)),
+                    ("cover_url".to_string(), json!("https://example.com/c.jpg")),
+                ]),
+            )),
+        ];
+        let album = Album {
+            id: "a".into(),
+            title: "t".into(),
+            artist_id: String::new(),
+            cover_url: None,
+            song_ids: Vec::new(),
+            source_ids: Vec::new(),
+            year: None,
+            release_date: None,
+            original_date: None,
+            catalog_number: None,
+            barcode: None,
+        };
+        let resolved = resolve_chain(&providers, &["year", "cover_url"], |p| p.lookup_album(&album, None));
+        assert_eq!(resolved["year"], (json!(1999), "musicbrainz".to_string()));
+        assert_eq!(resolved["cover_url"].1, "song_tags");
+    }
+}
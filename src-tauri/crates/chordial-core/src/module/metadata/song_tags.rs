@@ -0,0 +1,78 @@
+//! 内置提供方 — 由歌曲的音频标签推断专辑年份与艺人流派。
+
+use super::traits::{FieldValues, MetadataProvider};
+use crate::module::music_library::library::MusicLibrary;
+use crate::module::music_library::models::{Album, Artist};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 取专辑内歌曲标签中出现次数最多的年份作为专辑年份；
+/// 取艺人歌曲中最常见的几个流派作为艺人流派。
+///
+/// 优先级最低：只在外部提供方都没有给出年份时兜底。
+pub struct SongTagsProvider {
+    library: Arc<MusicLibrary>,
+}
+
+impl SongTagsProvider {
+    pub const NAME: &str = "song_tags";
+
+    /// 艺人流派最多取几个。
+    pub const MAX_ARTIST_GENRES: usize = 3;
+
+    pub fn new(library: Arc<MusicLibrary>) -> Self {
+        Self { library }
+    }
+}
+
+impl MetadataProvider for SongTagsProvider {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn priority(&self) -> i32 {
+        -100
+    }
+
+    fn lookup_album(&self, album: &Album, _artist_name: Option<&str>) -> Result<FieldValues, String> {
+        let mut counts: HashMap<u32, usize> = HashMap::new();
+        for song in self.library.get_songs_by_ids(&album.song_ids) {
+            if let Some(year) = song.year {
+                *counts.entry(year).or_default() += 1;
+            }
+        }
+        let mut fields = FieldValues::new();
+        // 次数相同时取较早的年份（再版通常晚于首发）
+        if let Some((year, _)) = counts.into_iter().max_by_key(|&(year, n)| (n, std::cmp::Reverse(year))) {
+            fields.insert("year".to_string(), year.into());
+        }
+        Ok(fields)
+    }
+
+    fn lookup_artist(&self, artist: &Artist) -> Result<FieldValues, String> {
+        // 流派名大小写不一（"Rock" / "rock"），按小写计数，展示用首次出现的写法
+        let mut counts: HashMap<String, (usize, usize, String)> = HashMap::new();
+        for song in self.library.get_songs_by_artist(&artist.id) {
+            for genre in &song.genres {
+                let order = counts.len();
+                let entry = counts
+                    .entry(genre.to_lowercase())
+                    .or_insert_with(|| (0, order, genre.clone()));
+                entry.0 += 1;
+            }
+        }
+        let mut ranked: Vec<_> = counts.into_values().collect();
+        ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        let genres: Vec<String> = ranked
+            .into_iter()
+            .take(Self::MAX_ARTIST_GENRES)
+            .map(|(_, _, name)| name)
+            .collect();
+
+        let mut fields = FieldValues::new();
+        if !genres.is_empty() {
+            fields.insert("genres".to_string(), genres.into());
+        }
+        Ok(fields)
+    }
+}
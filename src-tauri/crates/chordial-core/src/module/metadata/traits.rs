@@ -0,0 +1,59 @@
+//! 元数据提供方接口。
+
+use crate::module::music_library::models::{Album, Artist};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// 字段名 → 字段值。字段名与库模型的 serde 字段名一致（如 `year`、`bio`）。
+pub type FieldValues = BTreeMap<String, Value>;
+
+/// 可被补全的实体类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichTarget {
+    Album,
+    Artist,
+}
+
+impl EnrichTarget {
+    /// 允许提供方写入的字段。ID、关联关系、来源引用不在其列。
+    pub fn fields(self) -> &'static [&'static str] {
+        match self {
+            Self::Album => &["title", "year", "release_date", "original_date", "catalog_number", "barcode", "cover_url"],
+            Self::Artist => &["name", "bio", "genres"],
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Album => "album",
+            Self::Artist => "artist",
+        }
+    }
+}
+
+/// 元数据提供方插件必须实现的接口。
+///
+/// 每个方法只返回自己能提供的字段，其余字段留给优先级更低的提供方；
+/// 两个方法默认都返回空结果，提供方只需实现自己支持的实体类型。
+pub trait MetadataProvider: Send + Sync {
+    /// 提供方唯一名称，如 `"musicbrainz"`。
+    fn name(&self) -> &str;
+
+    /// 优先级，数值大的先被询问；同一字段以先给出值的提供方为准。
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// 查询专辑字段。`artist_name` 为专辑艺人名（若已知）。
+    fn lookup_album(&self, _album: &Album, _artist_name: Option<&str>) -> Result<FieldValues, String> {
+        Ok(FieldValues::new())
+    }
+
+    /// 查询艺人字段。
+    fn lookup_artist(&self, _artist: &Artist) -> Result<FieldValues, String> {
+        Ok(FieldValues::new())
+    }
+}
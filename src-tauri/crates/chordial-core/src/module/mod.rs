@@ -16,14 +16,37 @@
 //! | [`music_localSource`] | 本地文件系统来源实现 |
 //! | [`music_library`] | 音乐库（Song/Artist/Album/Lyric CRUD + 关系） |
 //! | [`p2p`] | P2P 资源共享（实例间对等交换曲库） |
+//! | [`playback`] | 播放设置（变速质量等用户偏好） |
+//! | [`analysis`] | 音频分析（技术信息等，按需读取 + 缓存） |
+//! | [`artwork`] | 封面导出（按尺寸 / 格式写出专辑封面） |
+//! | [`cancel`] | 长耗时任务的协作式取消 |
+//! | [`power`] | 电源策略（电池供电时降低扫描 / 分析并行度） |
+//! | [`readiness`] | 启动预热的子系统就绪状态（就绪事件 + 未就绪错误） |
+//! | [`safe_mode`] | 安全模式启动（停用音频 / 监听 / 后台任务，缓存只读） |
+//! | [`events`] | 应用内类型化事件总线（子系统解耦 + 前端桥接） |
+//! | [`lyrics`] | 可插拔歌词提供方（搜索 / 获取 / 限流 / 健康状态） |
+//! | [`metadata`] | 可插拔专辑 / 艺人元数据补全（逐字段来源记录 + 撤销） |
+//! | `fixtures` | 测试用音频夹具合成（仅测试或 `fixtures` feature） |
 
+pub mod analysis;
+pub mod artwork;
 pub mod cache;
+pub mod cancel;
 pub mod config;
+pub mod events;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+pub mod lyrics;
+pub mod metadata;
 #[allow(non_snake_case)]
 pub mod music_localSource;
 pub mod music_library;
 pub mod music_source;
 pub mod p2p;
 pub mod perf;
+pub mod playback;
 pub mod platform;
+pub mod power;
+pub mod readiness;
+pub mod safe_mode;
 pub mod storage;
@@ -0,0 +1,205 @@
+//! 专辑 / 艺人汇总 — 曲目数、总时长与总文件大小。
+//!
+//! 汇总值随库存储，歌曲增删改时只重算受影响的专辑 / 艺人（见 [`Affected`]）。
+//! 重算从当前歌曲数据重新求和而不是在旧值上累加，同一首歌重复合并入库不会被计两次。
+//! 旧版本的库没有汇总数据，首次加载时由 [`rebuild`] 全量生成。
+
+use super::{models::Song, songs};
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+pub const ALBUM_KEY: &str = "album_totals";
+pub const ARTIST_KEY: &str = "artist_totals";
+
+/// 一个专辑或艺人名下歌曲的汇总。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Totals {
+    pub track_count: usize,
+    /// 总时长（秒）；缺少时长的歌曲不计入
+    pub duration_secs: u64,
+    /// 总文件大小（字节）；远程来源等无文件大小的歌曲不计入
+    pub size_bytes: u64,
+}
+
+impl Totals {
+    fn add(&mut self, song: &Song) {
+        self.track_count += 1;
+        self.duration_secs += song.duration.unwrap_or(0);
+        self.size_bytes += song.size_bytes.unwrap_or(0);
+    }
+}
+
+/// 一次写操作影响到的专辑 / 艺人。修改前后的歌曲都要记入，歌曲换专辑时两边都会重算。
+#[derive(Debug, Default)]
+pub struct Affected {
+    albums: HashSet<String>,
+    artists: HashSet<String>,
+}
+
+impl Affected {
+    pub fn song(&mut self, song: &Song) {
+        if let Some(album_id) = &song.album_id {
+            self.albums.insert(album_id.clone());
+        }
+        self.artists.extend(song.artist_ids.iter().cloned());
+    }
+
+    pub fn songs<'a>(&mut self, songs: impl IntoIterator<Item = &'a Song>) {
+        for song in songs {
+            self.song(song);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.albums.is_empty() && self.artists.is_empty()
+    }
+}
+
+/// 专辑的汇总；没有歌曲时为零。
+pub fn album(store: &PersistentStore, album_id: &str) -> Totals {
+    store.get_entry(ALBUM_KEY, album_id).unwrap_or_default()
+}
+
+/// 艺人的汇总；没有歌曲时为零。
+pub fn artist(store: &PersistentStore, artist_id: &str) -> Totals {
+    store.get_entry(ARTIST_KEY, artist_id).unwrap_or_default()
+}
+
+/// 库里是否已有汇总数据。
+pub fn exists(store: &PersistentStore) -> bool {
+    store.has(ALBUM_KEY) && store.has(ARTIST_KEY)
+}
+
+/// 重算受影响的专辑 / 艺人，只反序列化与它们相关的歌曲。
+pub fn refresh(store: &PersistentStore, affected: &Affected) -> Result<(), String> {
+    if affected.is_empty() {
+        return Ok(());
+    }
+    let related = store.get_entries_filtered::<Song, _>(songs::KEY, |v| {
+        let in_album = v
+            .get("album_id")
+            .and_then(Value::as_str)
+            .is_some_and(|id| affected.albums.contains(id));
+        in_album
+            || v.get("artist_ids").and_then(Value::as_array).is_some_and(|ids| {
+                ids.iter()
+                    .filter_map(Value::as_str)
+                    .any(|id| affected.artists.contains(id))
+            })
+    });
+
+    let mut albums: HashMap<&str, Totals> = affected.albums.iter().map(|id| (id.as_str(), Totals::default())).collect();
+    let mut artists: HashMap<&str, Totals> = affected.artists.iter().map(|id| (id.as_str(), Totals::default())).collect();
+    for song in &related {
+        accumulate(song, &mut albums, &mut artists);
+    }
+    write(store, ALBUM_KEY, albums)?;
+    write(store, ARTIST_KEY, artists)
+}
+
+/// 按当前全部歌曲重新生成所有汇总。
+pub fn rebuild(store: &PersistentStore) -> Result<(), String> {
+    let all_songs = songs::get_all(store);
+    let mut albums: HashMap<&str, Totals> = HashMap::new();
+    let mut artists: HashMap<&str, Totals> = HashMap::new();
+    for song in all_songs.values() {
+        if let Some(album_id) = &song.album_id {
+            albums.entry(album_id).or_default();
+        }
+        for artist_id in &song.artist_ids {
+            artists.entry(artist_id).or_default();
+        }
+        accumulate(song, &mut albums, &mut artists);
+    }
+    store.set(ALBUM_KEY, &albums)?;
+    store.set(ARTIST_KEY, &artists)
+}
+
+/// 把歌曲计入 `albums` / `artists` 中已列出的条目；同一艺人在歌曲中重复出现时只计一次。
+fn accumulate(song: &Song, albums: &mut HashMap<&str, Totals>, artists: &mut HashMap<&str, Totals>) {
+    if let Some(totals) = song.album_id.as_deref().and_then(|id| albums.get_mut(id)) {
+        totals.add(song);
+    }
+    let mut seen = HashSet::new();
+    for artist_id in &song.artist_ids {
+        if !seen.insert(artist_id.as_str()) {
+            continue;
+        }
+        if let Some(totals) = artists.get_mut(artist_id.as_str()) {
+            totals.add(song);
+        }
+    }
+}
+
+/// 写回重算结果；已没有歌曲的条目删除。
+fn write(store: &PersistentStore, key: &str, totals: HashMap<&str, Totals>) -> Result<(), String> {
+    if !store.has(key) {
+        store.set(key, &HashMap::<String, Totals>::new())?;
+    }
+    for (id, totals) in totals {
+        if totals.track_count == 0 {
+            store.remove_entry(key, id);
+        } else {
+            store.set_subkey(key, id, &totals)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(id: &str, album_id: &str, duration: u64) -> Song {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": id,
+            "artist_names": ["a"],
+            "album_title": null,
+            "duration": duration,
+            "artist_ids": ["artist", "artist"],
+            "album_id": album_id,
+            "lyric_id": null,
+            "source_ids": [],
+            "size_bytes": 1000,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_refresh_is_idempotent_and_moves_between_albums() {
+        let path = std::env::temp_dir().join(format!("chordial_aggregates_{}.json", std::process::id()));
+        let store = PersistentStore::new(path.clone());
+        let a = song("a", "x", 200);
+        let b = song("b", "x", 100);
+        let songs: HashMap<&str, &Song> = [("a", &a), ("b", &b)].into_iter().collect();
+        store.set(songs::KEY, &songs).unwrap();
+        rebuild(&store).unwrap();
+
+        let mut affected = Affected::default();
+        affected.songs([&a, &b]);
+        refresh(&store, &affected).unwrap();
+        refresh(&store, &affected).unwrap();
+        let expected = Totals { track_count: 2, duration_secs: 300, size_bytes: 2000 };
+        assert_eq!(album(&store, "x"), expected);
+        assert_eq!(artist(&store, "artist"), expected);
+
+        let moved = song("b", "y", 100);
+        store.set_subkey(songs::KEY, "b", &moved).unwrap();
+        let mut affected = Affected::default();
+        affected.songs([&b, &moved]);
+        refresh(&store, &affected).unwrap();
+        assert_eq!(album(&store, "x").duration_secs, 200);
+        assert_eq!(album(&store, "y").track_count, 1);
+
+        store.remove_entry(songs::KEY, "a");
+        let mut affected = Affected::default();
+        affected.song(&a);
+        refresh(&store, &affected).unwrap();
+        assert_eq!(album(&store, "x"), Totals::default());
+        assert!(!store.has_entry(ALBUM_KEY, "x"));
+        let _ = std::fs::remove_file(path);
+    }
+}
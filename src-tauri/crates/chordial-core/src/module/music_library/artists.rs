@@ -5,6 +5,9 @@ use std::collections::HashMap;
 
 pub const KEY: &str = "artists";
 
+/// 标签中没有艺人名时使用的占位艺人。
+pub const UNKNOWN_NAME: &str = "未知艺术家";
+
 /// 获取所有艺术家。
 pub fn get_all(store: &PersistentStore) -> HashMap<String, Artist> {
     let _scope = perf::scope("artists.get_all");
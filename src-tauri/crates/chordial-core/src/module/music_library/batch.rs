@@ -0,0 +1,62 @@
+//! 批量操作 — 列表多选后一次提交的操作（加入队列、评分、分析等）。
+//!
+//! 批量操作逐项执行、逐项记录结果：个别歌曲失败（如已被移除）不影响其余歌曲，
+//! 失败项连同原因放在 [`BatchReport::failed`] 中返回。参数本身无效（如评分越界）时整批拒绝，不做任何修改。
+//! 库的落盘由调用方在整批完成后做一次，而不是每项一次。
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// 一次批量操作的结果。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchReport {
+    /// 成功的 ID（按请求顺序）
+    pub succeeded: Vec<String>,
+    /// 失败的 ID 及原因
+    pub failed: Vec<(String, String)>,
+}
+
+impl BatchReport {
+    /// 对每个 ID 执行 `f` 并汇总结果；重复的 ID 只执行一次。
+    pub fn run<F>(ids: &[String], mut f: F) -> Self
+    where
+        F: FnMut(&str) -> Result<(), String>,
+    {
+        let mut report = Self::default();
+        let mut seen = HashSet::new();
+        for id in ids.iter().filter(|id| seen.insert(id.as_str())) {
+            match f(id) {
+                Ok(()) => report.succeeded.push(id.clone()),
+                Err(e) => report.failed.push((id.clone(), e)),
+            }
+        }
+        report
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_report_dedups_and_keeps_order() {
+        let ids: Vec<String> = ["a", "missing", "b", "a"].iter().map(|s| s.to_string()).collect();
+        let mut calls = 0;
+        let report = BatchReport::run(&ids, |id| {
+            calls += 1;
+            if id == "missing" {
+                Err("歌曲不存在".into())
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(calls, 3);
+        assert_eq!(report.succeeded, ["a", "b"]);
+        assert_eq!(report.failed, [("missing".to_string(), "歌曲不存在".to_string())]);
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,128 @@
+//! 有声书 — 识别、章节与按书记忆的收听进度。
+//!
+//! 满足以下任一条件的歌曲视为有声书，归入「书籍」分区，不参与音乐的随机播放与自动续播：
+//! - 本地文件扩展名为 `.m4b`
+//! - 流派标签为 Audiobook / Spoken Word / 有声书 等
+//! - 时长不少于 [`LONG_FILE_SECS`]
+//!
+//! 收听进度按歌曲 ID 保存在库的 `book_progress` 键下，记录位置与当时所在的章节。
+//! 快进步长与播放速度沿用 [`ContentType::Audiobook`] 的设置（默认 30 秒步长）。
+
+use super::models::Song;
+use crate::module::music_source::types::SourceType;
+use crate::module::playback::ContentType;
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+
+pub const PROGRESS_KEY: &str = "book_progress";
+
+/// 时长达到该值（秒）的文件按有声书处理。
+pub const LONG_FILE_SECS: u64 = 90 * 60;
+
+/// 视为有声书的流派（小写比较）。
+const BOOK_GENRES: [&str; 6] = ["audiobook", "audiobooks", "spoken word", "hörbuch", "有声书", "有声读物"];
+
+/// 一个章节。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub title: String,
+    /// 章节开始位置（毫秒）
+    pub start_ms: u64,
+}
+
+/// 一本书的收听进度。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookProgress {
+    /// 上次停下的位置（毫秒）
+    pub position_ms: u64,
+    /// 该位置所在章节的下标（没有章节信息时为 `None`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chapter: Option<usize>,
+    /// 保存时间（Unix 秒）
+    pub updated_at: u64,
+}
+
+/// 书籍分区中的一项。
+#[derive(Debug, Clone, Serialize)]
+pub struct Book {
+    pub song: Song,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<BookProgress>,
+}
+
+/// 判断歌曲的内容类型。已标记为播客的保持不变。
+pub fn classify(song: &Song) -> ContentType {
+    if song.content_type == ContentType::Podcast {
+        return ContentType::Podcast;
+    }
+    let m4b = song.source_ids.iter().any(|sid| {
+        sid.source_type == SourceType::Local && sid.entity_id.to_lowercase().ends_with(".m4b")
+    });
+    let book_genre = song
+        .genres
+        .iter()
+        .any(|g| BOOK_GENRES.contains(&g.trim().to_lowercase().as_str()));
+    let long = song.duration.is_some_and(|d| d >= LONG_FILE_SECS);
+    if m4b || book_genre || long {
+        ContentType::Audiobook
+    } else {
+        ContentType::Music
+    }
+}
+
+/// `position_ms` 所在章节的下标。
+pub fn chapter_at(chapters: &[Chapter], position_ms: u64) -> Option<usize> {
+    chapters.iter().rposition(|c| c.start_ms <= position_ms)
+}
+
+pub fn get_progress(store: &PersistentStore, song_id: &str) -> Option<BookProgress> {
+    store.get_entry::<BookProgress>(PROGRESS_KEY, song_id)
+}
+
+pub fn set_progress(store: &PersistentStore, song_id: &str, progress: &BookProgress) -> Result<(), String> {
+    store.set_subkey(PROGRESS_KEY, song_id, progress)
+}
+
+pub fn clear_progress(store: &PersistentStore, song_id: &str) {
+    store.remove_entry(PROGRESS_KEY, song_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_source::types::{EntityType, SourceId};
+
+    fn song(path: &str, genres: &[&str], duration: u64) -> Song {
+        let mut song: Song = serde_json::from_value(serde_json::json!({
+            "id": "s",
+            "title": "t",
+            "artist_names": [],
+            "album_title": null,
+            "duration": duration,
+            "artist_ids": [],
+            "album_id": null,
+            "lyric_id": null,
+            "source_ids": [],
+        }))
+        .unwrap();
+        song.source_ids = vec![SourceId::new("local", SourceType::Local, EntityType::Song, path)];
+        song.genres = genres.iter().map(|g| g.to_string()).collect();
+        song
+    }
+
+    #[test]
+    fn test_classify_and_chapter_lookup() {
+        assert_eq!(classify(&song("/b/Dune.M4B", &[], 60)), ContentType::Audiobook);
+        assert_eq!(classify(&song("/b/a.mp3", &["Spoken Word"], 60)), ContentType::Audiobook);
+        assert_eq!(classify(&song("/b/a.mp3", &[], LONG_FILE_SECS)), ContentType::Audiobook);
+        assert_eq!(classify(&song("/m/a.flac", &["Rock"], 240)), ContentType::Music);
+
+        let chapters = vec![
+            Chapter { title: "1".into(), start_ms: 0 },
+            Chapter { title: "2".into(), start_ms: 60_000 },
+        ];
+        assert_eq!(chapter_at(&chapters, 59_999), Some(0));
+        assert_eq!(chapter_at(&chapters, 60_000), Some(1));
+        assert_eq!(chapter_at(&[], 10), None);
+    }
+}
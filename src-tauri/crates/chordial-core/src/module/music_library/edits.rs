@@ -0,0 +1,207 @@
+//! 用户编辑与重扫冲突 — 记录用户在库内改过的元数据字段，重扫文件时优先保留用户的值。
+//!
+//! 每条编辑同时记下编辑时文件标签里的值（基线）。重扫读到新的文件值后逐字段对账：
+//!
+//! | 新文件值 | 处理 |
+//! |----------|------|
+//! | 与基线相同 | 文件没改这一项，沿用用户值 |
+//! | 与用户值相同 | 文件已与用户的修改一致，撤销这条编辑 |
+//! | 与两者都不同 | 沿用用户值，记录一条 [`MetadataConflict`] 待手动处理，基线更新为新文件值 |
+//!
+//! 编辑与冲突都以文件路径（本地来源的 `entity_id`）为键，歌曲重新索引后 ID 变化不受影响。
+
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 用户编辑的存储键：路径 → [`FileEdits`]。
+pub const EDITS_KEY: &str = "metadata_edits";
+
+/// 冲突的存储键：路径 → `Vec<MetadataConflict>`。
+pub const CONFLICTS_KEY: &str = "metadata_conflicts";
+
+/// 可编辑的元数据字段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataField {
+    Title,
+    /// 艺人（整串，按标签原文，未拆分）
+    Artist,
+    Album,
+    Year,
+}
+
+/// 单个字段的用户编辑。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldEdit {
+    /// 用户填写的值；`None` 表示清空该字段
+    pub value: Option<String>,
+    /// 编辑时（或上次对账时）文件标签中的值
+    pub file_value: Option<String>,
+}
+
+/// 一个文件的全部用户编辑。
+pub type FileEdits = HashMap<MetadataField, FieldEdit>;
+
+/// 各字段的取值（文件值或最终生效值）。
+pub type FieldValues = HashMap<MetadataField, Option<String>>;
+
+/// 重扫时发现的冲突：文件标签与用户编辑都改了同一字段。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct MetadataConflict {
+    /// 文件路径
+    pub path: String,
+    pub field: MetadataField,
+    /// 当前生效的用户值
+    pub user_value: Option<String>,
+    /// 重扫读到的文件值
+    pub file_value: Option<String>,
+    /// 用户编辑时文件中的值
+    pub previous_file_value: Option<String>,
+    /// 发现时间（Unix 秒）
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub detected_at: u64,
+}
+
+/// 冲突的处理方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// 保留用户值（基线已是新文件值，之后文件不再变化就不会再提示）
+    KeepUser,
+    /// 采用文件值，撤销该字段的用户编辑
+    UseFile,
+}
+
+/// 对账：`values` 传入本次读到的文件值，原地改写为最终生效值。
+///
+/// `edits` 同步更新（基线前移、已一致的编辑被移除）；返回新发现的冲突，
+/// 元素为 `(字段, 编辑时的文件值)`。
+pub fn reconcile(
+    edits: &mut FileEdits,
+    values: &mut FieldValues,
+) -> Vec<(MetadataField, Option<String>)> {
+    let mut fields: Vec<MetadataField> = edits.keys().copied().collect();
+    fields.sort();
+    let mut conflicts = Vec::new();
+    for field in fields {
+        let current = values.get(&field).cloned().flatten();
+        let Some(edit) = edits.get_mut(&field) else {
+            continue;
+        };
+        if current == edit.value {
+            edits.remove(&field);
+            continue;
+        }
+        if current != edit.file_value {
+            let previous = std::mem::replace(&mut edit.file_value, current);
+            conflicts.push((field, previous));
+        }
+        values.insert(field, edit.value.clone());
+    }
+    conflicts
+}
+
+/// 获取文件的用户编辑。
+pub fn get(store: &PersistentStore, path: &str) -> Option<FileEdits> {
+    store.get_entry::<FileEdits>(EDITS_KEY, path)
+}
+
+/// 写入文件的用户编辑；为空时删除条目。
+pub fn set(store: &PersistentStore, path: &str, edits: &FileEdits) -> Result<(), String> {
+    if edits.is_empty() {
+        store.remove_entry(EDITS_KEY, path);
+        Ok(())
+    } else {
+        store.set_subkey(EDITS_KEY, path, edits)
+    }
+}
+
+/// 获取文件的未处理冲突。
+pub fn get_conflicts(store: &PersistentStore, path: &str) -> Vec<MetadataConflict> {
+    store
+        .get_entry::<Vec<MetadataConflict>>(CONFLICTS_KEY, path)
+        .unwrap_or_default()
+}
+
+/// 写入文件的冲突列表；为空时删除条目。
+pub fn set_conflicts(
+    store: &PersistentStore,
+    path: &str,
+    conflicts: &[MetadataConflict],
+) -> Result<(), String> {
+    if conflicts.is_empty() {
+        store.remove_entry(CONFLICTS_KEY, path);
+        Ok(())
+    } else {
+        store.set_subkey(CONFLICTS_KEY, path, &conflicts)
+    }
+}
+
+/// 获取全部未处理冲突，按发现时间排序。
+pub fn all_conflicts(store: &PersistentStore) -> Vec<MetadataConflict> {
+    let mut all: Vec<MetadataConflict> = store
+        .get_all_entries::<Vec<MetadataConflict>>(CONFLICTS_KEY)
+        .into_iter()
+        .flatten()
+        .collect();
+    all.sort_by(|a, b| {
+        (a.detected_at, &a.path, a.field).cmp(&(b.detected_at, &b.path, b.field))
+    });
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile_keeps_user_value_and_reports_conflict() {
+        let some = |s: &str| Some(s.to_string());
+        let mut edits = FileEdits::from([
+            (
+                MetadataField::Title,
+                FieldEdit {
+                    value: some("Fixed Title"),
+                    file_value: some("fixed titel"),
+                },
+            ),
+            (
+                MetadataField::Album,
+                FieldEdit {
+                    value: some("Album"),
+                    file_value: None,
+                },
+            ),
+            (
+                MetadataField::Year,
+                FieldEdit {
+                    value: some("1999"),
+                    file_value: some("1998"),
+                },
+            ),
+        ]);
+        // 标题被外部工具改成了别的值；专辑未变；年份已与用户值一致
+        let mut values = FieldValues::from([
+            (MetadataField::Title, some("Another Title")),
+            (MetadataField::Artist, some("Someone")),
+            (MetadataField::Album, None),
+            (MetadataField::Year, some("1999")),
+        ]);
+
+        let conflicts = reconcile(&mut edits, &mut values);
+        assert_eq!(conflicts, vec![(MetadataField::Title, some("fixed titel"))]);
+        assert_eq!(values[&MetadataField::Title], some("Fixed Title"));
+        assert_eq!(values[&MetadataField::Album], some("Album"));
+        assert_eq!(values[&MetadataField::Artist], some("Someone"));
+        assert!(!edits.contains_key(&MetadataField::Year));
+        assert_eq!(edits[&MetadataField::Title].file_value, some("Another Title"));
+
+        // 再次对账不会重复报告
+        let mut values = FieldValues::from([(MetadataField::Title, some("Another Title"))]);
+        assert!(reconcile(&mut edits, &mut values).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+//! 艺人分组方式 — 艺人列表按演出者还是按作曲者（古典模式）分组。
+//!
+//! 作曲者不是库内实体，没有单独的存储：由歌曲的 `composers` 字段即时汇总成 [`Artist`] 形状的条目，
+//! ID 为 [`COMPOSER_ID_PREFIX`] 加作曲者名，前端的艺人列表与详情页不需要另写一套。
+//! 列表类查询按 [`GroupBy`] 切换；按 ID 的查询（艺人详情、艺人的歌曲 / 专辑）按 ID 前缀识别，与当前设置无关。
+
+use super::models::{Album, Artist, Song};
+use super::{albums, songs};
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// ConfigStore 中存放艺人分组方式的键。
+pub const GROUP_BY_CONFIG_KEY: &str = "library_group_by";
+
+/// 作曲者条目的 ID 前缀。
+pub const COMPOSER_ID_PREFIX: &str = "composer:";
+
+/// 艺人列表的分组方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    /// 按演出艺人（默认）
+    #[default]
+    Artist,
+    /// 按作曲者（古典模式）
+    Composer,
+}
+
+pub fn composer_id(name: &str) -> String {
+    format!("{}{}", COMPOSER_ID_PREFIX, name)
+}
+
+/// 作曲者条目 ID 对应的作曲者名；不是作曲者 ID 时返回 `None`。
+pub fn composer_name(id: &str) -> Option<&str> {
+    id.strip_prefix(COMPOSER_ID_PREFIX)
+}
+
+fn composer_entry(name: &str) -> Artist {
+    Artist {
+        id: composer_id(name),
+        name: name.to_string(),
+        bio: None,
+        genres: Vec::new(),
+        source_ids: Vec::new(),
+    }
+}
+
+/// 有作曲者标签的歌曲（没有作曲者的歌曲不存该字段，JSON 层即可排除）。
+fn songs_with_composers(store: &PersistentStore) -> Vec<Song> {
+    store.get_entries_filtered::<Song, _>(songs::KEY, |v| v.get("composers").is_some())
+}
+
+/// 库内所有作曲者，按名称排序（不区分大小写）。
+pub fn composers(store: &PersistentStore) -> Vec<Artist> {
+    let names: BTreeSet<(String, String)> = songs_with_composers(store)
+        .into_iter()
+        .flat_map(|song| song.composers)
+        .map(|name| (name.to_lowercase(), name))
+        .collect();
+    names.into_iter().map(|(_, name)| composer_entry(&name)).collect()
+}
+
+/// 按作曲者名取条目；库内没有该作曲者的歌曲时返回 `None`。
+pub fn get_composer(store: &PersistentStore, name: &str) -> Option<Artist> {
+    (!songs_by_composer(store, name).is_empty()).then(|| composer_entry(name))
+}
+
+pub fn songs_by_composer(store: &PersistentStore, name: &str) -> Vec<Song> {
+    store.get_entries_by_str_array_contains::<Song>(songs::KEY, "composers", name)
+}
+
+/// 收录了该作曲者作品的专辑，排序同 [`get_albums_by_artist`](super::relations::get_albums_by_artist)。
+pub fn albums_by_composer(store: &PersistentStore, name: &str) -> Vec<Album> {
+    let album_ids: HashSet<String> = songs_by_composer(store, name)
+        .into_iter()
+        .filter_map(|s| s.album_id)
+        .collect();
+    let mut albums: Vec<Album> = album_ids.iter().filter_map(|id| albums::get(store, id)).collect();
+    albums.sort_by_cached_key(|a| {
+        let date = a.chronology_date();
+        (date.is_none(), date)
+    });
+    albums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_composers_from_song_tags() {
+        let path = std::env::temp_dir().join(format!("chordial_grouping_{}.json", std::process::id()));
+        let store = PersistentStore::new(path.clone());
+        let song = |id: &str, composers: &[&str]| {
+            let song: Song = serde_json::from_value(serde_json::json!({
+                "id": id, "title": id, "artist_names": [], "album_title": null, "duration": 1,
+                "artist_ids": [], "album_id": null, "lyric_id": null, "source_ids": [],
+                "composers": composers,
+            }))
+            .unwrap();
+            (id.to_string(), song)
+        };
+        let all: HashMap<_, _> = [
+            song("s1", &["Mozart"]),
+            song("s2", &["bach", "Mozart"]),
+            song("s3", &[]),
+        ]
+        .into();
+        store.set(songs::KEY, &all).unwrap();
+
+        let names: Vec<String> = composers(&store).into_iter().map(|a| a.name).collect();
+        let mozart = get_composer(&store, "Mozart").unwrap();
+        let missing = get_composer(&store, "Haydn");
+        let by_mozart = songs_by_composer(&store, composer_name(&mozart.id).unwrap()).len();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(names, vec!["bach", "Mozart"]);
+        assert_eq!(mozart.id, "composer:Mozart");
+        assert!(missing.is_none());
+        assert_eq!(by_mozart, 2);
+        assert_eq!(composer_name("uuid-1234"), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! 隐藏条目 — 从库视图中排除歌曲 / 专辑，但不删除文件、不移出索引。
+//!
+//! 被隐藏的歌曲不出现在列表、搜索、按艺人 / 专辑查询和自动续播中；隐藏专辑时其曲目一并隐藏。
+//! 条目本身仍留在库里，重新扫描不会让它们重新出现，恢复后立即可见。
+//!
+//! 隐藏记录保存在 PersistentStore 的 [`KEY`] 下，记录隐藏时间供「已隐藏」列表排序。
+
+use super::albums;
+use super::models::{Album, Song};
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+pub const KEY: &str = "hidden";
+
+/// 持久化的隐藏记录：ID → 隐藏时间（Unix 秒）。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HiddenRecord {
+    #[serde(default)]
+    pub songs: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub albums: BTreeMap<String, u64>,
+}
+
+impl HiddenRecord {
+    pub fn is_empty(&self) -> bool {
+        self.songs.is_empty() && self.albums.is_empty()
+    }
+}
+
+/// 查询时使用的隐藏集合；`songs` 已包含被隐藏专辑的曲目。
+#[derive(Debug, Clone, Default)]
+pub struct HiddenSet {
+    songs: HashSet<String>,
+    albums: HashSet<String>,
+}
+
+impl HiddenSet {
+    pub fn is_empty(&self) -> bool {
+        self.songs.is_empty() && self.albums.is_empty()
+    }
+
+    pub fn hides_song(&self, song: &Song) -> bool {
+        self.songs.contains(&song.id) || song.album_id.as_ref().is_some_and(|id| self.albums.contains(id))
+    }
+
+    pub fn hides_song_id(&self, id: &str) -> bool {
+        self.songs.contains(id)
+    }
+
+    pub fn hides_album(&self, id: &str) -> bool {
+        self.albums.contains(id)
+    }
+}
+
+/// 带隐藏时间的条目。
+#[derive(Debug, Clone, Serialize)]
+pub struct HiddenEntry<T> {
+    #[serde(flatten)]
+    pub item: T,
+    pub hidden_at: u64,
+}
+
+/// 「已隐藏」列表，各自按隐藏时间从新到旧。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HiddenItems {
+    pub songs: Vec<HiddenEntry<Song>>,
+    pub albums: Vec<HiddenEntry<Album>>,
+}
+
+pub fn load(store: &PersistentStore) -> HiddenRecord {
+    store.get::<HiddenRecord>(KEY).unwrap_or_default()
+}
+
+fn save(store: &PersistentStore, record: &HiddenRecord) -> Result<(), String> {
+    if record.is_empty() {
+        store.remove(KEY);
+        Ok(())
+    } else {
+        store.set(KEY, record)
+    }
+}
+
+/// 读取隐藏记录并展开被隐藏专辑的曲目。
+pub fn resolve(store: &PersistentStore) -> HiddenSet {
+    let record = load(store);
+    if record.is_empty() {
+        return HiddenSet::default();
+    }
+    let mut songs: HashSet<String> = record.songs.into_keys().collect();
+    for id in record.albums.keys() {
+        if let Some(album) = albums::get(store, id) {
+            songs.extend(album.song_ids);
+        }
+    }
+    HiddenSet {
+        songs,
+        albums: record.albums.into_keys().collect(),
+    }
+}
+
+/// 隐藏歌曲与专辑，返回本次新隐藏的 `(歌曲, 专辑)` ID（已隐藏的不重复计入）。
+pub fn hide(
+    store: &PersistentStore,
+    song_ids: &[String],
+    album_ids: &[String],
+    at: u64,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut record = load(store);
+    let insert = |map: &mut BTreeMap<String, u64>, ids: &[String]| -> Vec<String> {
+        let mut added = Vec::new();
+        for id in ids {
+            if !map.contains_key(id) {
+                map.insert(id.clone(), at);
+                added.push(id.clone());
+            }
+        }
+        added
+    };
+    let songs = insert(&mut record.songs, song_ids);
+    let albums = insert(&mut record.albums, album_ids);
+    if !songs.is_empty() || !albums.is_empty() {
+        save(store, &record)?;
+    }
+    Ok((songs, albums))
+}
+
+/// 恢复歌曲与专辑，返回实际恢复的条目数。
+pub fn restore(store: &PersistentStore, song_ids: &[String], album_ids: &[String]) -> Result<usize, String> {
+    let mut record = load(store);
+    let restored = song_ids.iter().filter(|id| record.songs.remove(*id).is_some()).count()
+        + album_ids.iter().filter(|id| record.albums.remove(*id).is_some()).count();
+    if restored > 0 {
+        save(store, &record)?;
+    }
+    Ok(restored)
+}
+
+/// 「已隐藏」列表；已从库中移除的条目不列出。
+pub fn list(store: &PersistentStore) -> HiddenItems {
+    let record = load(store);
+    let mut songs: Vec<HiddenEntry<Song>> = record
+        .songs
+        .into_iter()
+        .filter_map(|(id, hidden_at)| Some(HiddenEntry { item: super::songs::get(store, &id)?, hidden_at }))
+        .collect();
+    let mut albums: Vec<HiddenEntry<Album>> = record
+        .albums
+        .into_iter()
+        .filter_map(|(id, hidden_at)| Some(HiddenEntry { item: albums::get(store, &id)?, hidden_at }))
+        .collect();
+    songs.sort_by(|a, b| b.hidden_at.cmp(&a.hidden_at));
+    albums.sort_by(|a, b| b.hidden_at.cmp(&a.hidden_at));
+    HiddenItems { songs, albums }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hide_album_hides_its_songs_until_restored() {
+        let path = std::env::temp_dir().join(format!("chordial_hidden_{}.json", std::process::id()));
+        let store = PersistentStore::new(path.clone());
+        let album = Album {
+            id: "al1".into(),
+            title: "Album".into(),
+            artist_id: "ar1".into(),
+            cover_url: None,
+            song_ids: vec!["s1".into(), "s2".into()],
+            source_ids: Vec::new(),
+            year: None,
+            release_date: None,
+            original_date: None,
+            catalog_number: None,
+            barcode: None,
+        };
+        store.set_raw(albums::KEY, serde_json::json!({}));
+        albums::add(&store, &album).unwrap();
+
+        let (songs, albums) = hide(&store, &["s3".into()], &["al1".into()], 10).unwrap();
+        assert_eq!((songs.len(), albums.len()), (1, 1));
+        let (again, _) = hide(&store, &["s3".into()], &[], 11).unwrap();
+        assert!(again.is_empty());
+
+        let set = resolve(&store);
+        assert!(set.hides_song_id("s1") && set.hides_song_id("s3") && set.hides_album("al1"));
+        assert!(!set.hides_song_id("s4"));
+
+        assert_eq!(restore(&store, &["s3".into()], &["al1".into(), "missing".into()]).unwrap(), 2);
+        assert!(resolve(&store).is_empty());
+        assert!(store.get_raw(KEY).is_none());
+        let _ = std::fs::remove_file(path);
+    }
+}
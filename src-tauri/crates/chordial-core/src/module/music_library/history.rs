@@ -0,0 +1,151 @@
+//! 变更历史 — 破坏性库操作的撤销日志。
+//!
+//! 每次元数据编辑、冲突处理、从库中移除或隐藏歌曲前，先把受影响的数据快照成一条 [`ChangeRecord`]，
+//! 撤销时按快照原样写回。批量操作只记一条，一次撤销即可整批恢复。
+//!
+//! 日志保存在 PersistentStore 的 [`KEY`] 下，最多保留 [`MAX_RECORDS`] 条，超出时丢弃最旧的。
+//! 撤销本身不再记入日志（不支持重做）。
+
+use super::edits::{FileEdits, MetadataConflict};
+use super::models::Song;
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+
+pub const KEY: &str = "change_history";
+
+/// 日志最多保留的记录数。
+pub const MAX_RECORDS: usize = 100;
+
+/// 单个文件在修改前的用户编辑与冲突。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSnapshot {
+    pub path: String,
+    pub edits: FileEdits,
+    pub conflicts: Vec<MetadataConflict>,
+}
+
+/// 撤销所需的数据。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// 元数据编辑 / 冲突处理：恢复这些文件的编辑与冲突，再重新索引
+    MetadataEdit { files: Vec<FileSnapshot> },
+    /// 从库中移除歌曲：重新加入这些歌曲
+    RemoveSongs { songs: Vec<Song> },
+    /// 隐藏歌曲 / 专辑：恢复这些条目
+    Hide { songs: Vec<String>, albums: Vec<String> },
+}
+
+/// 变更类型（供前端展示）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    MetadataEdit,
+    RemoveSongs,
+    Hide,
+}
+
+/// 一条变更记录。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    /// 递增编号
+    pub id: u64,
+    /// 发生时间（Unix 秒）
+    pub at: u64,
+    /// 人类可读的描述，如「编辑 12 首歌曲的元数据」
+    pub summary: String,
+    pub change: Change,
+}
+
+/// 变更记录的摘要（不含快照数据）。
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct ChangeEntry {
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub id: u64,
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub at: u64,
+    pub kind: ChangeKind,
+    pub summary: String,
+    /// 涉及的文件 / 歌曲数
+    pub items: usize,
+}
+
+impl ChangeRecord {
+    pub fn entry(&self) -> ChangeEntry {
+        let (kind, items) = match &self.change {
+            Change::MetadataEdit { files } => (ChangeKind::MetadataEdit, files.len()),
+            Change::RemoveSongs { songs } => (ChangeKind::RemoveSongs, songs.len()),
+            Change::Hide { songs, albums } => (ChangeKind::Hide, songs.len() + albums.len()),
+        };
+        ChangeEntry {
+            id: self.id,
+            at: self.at,
+            kind,
+            summary: self.summary.clone(),
+            items,
+        }
+    }
+}
+
+/// 读取整个日志（按时间先后）。
+pub fn load(store: &PersistentStore) -> Vec<ChangeRecord> {
+    store.get::<Vec<ChangeRecord>>(KEY).unwrap_or_default()
+}
+
+/// 追加一条记录，返回其编号。
+pub fn push(
+    store: &PersistentStore,
+    summary: String,
+    change: Change,
+    at: u64,
+) -> Result<u64, String> {
+    let mut records = load(store);
+    let id = records.last().map_or(1, |r| r.id + 1);
+    records.push(ChangeRecord {
+        id,
+        at,
+        summary,
+        change,
+    });
+    if records.len() > MAX_RECORDS {
+        records.drain(..records.len() - MAX_RECORDS);
+    }
+    store.set(KEY, &records)?;
+    Ok(id)
+}
+
+/// 取出最新一条记录。
+pub fn pop(store: &PersistentStore) -> Result<Option<ChangeRecord>, String> {
+    let mut records = load(store);
+    let last = records.pop();
+    if last.is_some() {
+        store.set(KEY, &records)?;
+    }
+    Ok(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_capped_and_pops_newest() {
+        let path = std::env::temp_dir().join(format!("chordial_history_{}.json", std::process::id()));
+        let store = PersistentStore::new(path.clone());
+        for i in 0..MAX_RECORDS + 5 {
+            let change = Change::RemoveSongs { songs: Vec::new() };
+            push(&store, format!("change {}", i), change, i as u64).unwrap();
+        }
+        let records = load(&store);
+        assert_eq!(records.len(), MAX_RECORDS);
+        assert_eq!(records[0].summary, "change 5");
+
+        let last = pop(&store).unwrap().unwrap();
+        assert_eq!(last.id, MAX_RECORDS as u64 + 5);
+        assert_eq!(last.entry().kind, ChangeKind::RemoveSongs);
+        assert_eq!(load(&store).len(), MAX_RECORDS - 1);
+        let _ = std::fs::remove_file(path);
+    }
+}
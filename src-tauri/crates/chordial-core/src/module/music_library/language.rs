@@ -0,0 +1,221 @@
+//! 演唱语言 — 规范化音频标签中的语言，并在没有标签时从歌词推断。
+//!
+//! 标签来源：ID3v2 `TLAN`（ISO 639-2 三字母码）、Vorbis comment `LANGUAGE`、MP4 freeform `LANGUAGE`。
+//! 各工具写法不一（`jpn`、`ja`、`Japanese`、`zh_TW`），入库前由 [`normalize`] 统一为 BCP 47 风格的
+//! 小写主标签（有 ISO 639-1 两字母码时用两字母码，如 `jpn` → `ja`），地区 / 文字子标签保留并按惯例大小写。
+//! `zxx`（无语言内容）保留，可用来筛出纯音乐；`und`（未知）视为没有标签。
+//!
+//! 推断（[`infer_from_lyrics`]）只看歌词用了哪种文字，能可靠区分的才给出结果：
+//! 含假名 → `ja`，谚文为主 → `ko`，只有汉字 → `zh`；拉丁字母只认英语（常见虚词占比足够高），
+//! 其他拉丁语言、西里尔字母等无法仅凭文字区分的不作推断。
+
+/// 有两字母码的常见语言：（ISO 639-1，ISO 639-2/T，ISO 639-2/B，英文名）。
+const LANGUAGES: &[(&str, &str, &str, &str)] = &[
+    ("ar", "ara", "ara", "arabic"),
+    ("ca", "cat", "cat", "catalan"),
+    ("cs", "ces", "cze", "czech"),
+    ("da", "dan", "dan", "danish"),
+    ("de", "deu", "ger", "german"),
+    ("el", "ell", "gre", "greek"),
+    ("en", "eng", "eng", "english"),
+    ("es", "spa", "spa", "spanish"),
+    ("fa", "fas", "per", "persian"),
+    ("fi", "fin", "fin", "finnish"),
+    ("fr", "fra", "fre", "french"),
+    ("he", "heb", "heb", "hebrew"),
+    ("hi", "hin", "hin", "hindi"),
+    ("hu", "hun", "hun", "hungarian"),
+    ("id", "ind", "ind", "indonesian"),
+    ("is", "isl", "ice", "icelandic"),
+    ("it", "ita", "ita", "italian"),
+    ("ja", "jpn", "jpn", "japanese"),
+    ("ko", "kor", "kor", "korean"),
+    ("la", "lat", "lat", "latin"),
+    ("ms", "msa", "may", "malay"),
+    ("nl", "nld", "dut", "dutch"),
+    ("no", "nor", "nor", "norwegian"),
+    ("pl", "pol", "pol", "polish"),
+    ("pt", "por", "por", "portuguese"),
+    ("ro", "ron", "rum", "romanian"),
+    ("ru", "rus", "rus", "russian"),
+    ("sv", "swe", "swe", "swedish"),
+    ("th", "tha", "tha", "thai"),
+    ("tr", "tur", "tur", "turkish"),
+    ("uk", "ukr", "ukr", "ukrainian"),
+    ("vi", "vie", "vie", "vietnamese"),
+    ("zh", "zho", "chi", "chinese"),
+];
+
+/// 常见的本地语言名。
+const NATIVE_NAMES: &[(&str, &str)] = &[
+    ("日本語", "ja"),
+    ("中文", "zh"),
+    ("普通话", "zh"),
+    ("國語", "zh"),
+    ("粤语", "yue"),
+    ("粵語", "yue"),
+    ("한국어", "ko"),
+];
+
+/// 推断所需的最少字母数，太短的歌词（如只有「纯音乐，请欣赏」）不作推断。
+const MIN_LETTERS: usize = 20;
+
+/// 英语常见虚词；拉丁字母歌词中这些词占比达到 [`ENGLISH_MIN_RATIO`] 时判定为英语。
+const ENGLISH_WORDS: &[&str] = &[
+    "the", "and", "you", "i", "me", "my", "to", "a", "of", "in", "it", "is", "that", "on", "your", "we", "be",
+    "for", "with", "love", "don't", "i'm", "all", "what", "this", "but", "so", "just", "can", "no",
+];
+const ENGLISH_MIN_RATIO: f32 = 0.2;
+
+/// 把标签中的语言值规范化为 BCP 47 风格的标记；无法识别或为 `und` 时返回 `None`。
+///
+/// 多值（`;` / `/` / `,` / `\0` 分隔）只取第一个。
+pub fn normalize(raw: &str) -> Option<String> {
+    let first = raw.split([';', '/', ',', '\0']).map(str::trim).find(|v| !v.is_empty())?;
+    if let Some((_, code)) = NATIVE_NAMES.iter().find(|(name, _)| *name == first) {
+        return Some(code.to_string());
+    }
+    let lower = first.to_lowercase().replace('_', "-");
+    let (primary, rest) = lower.split_once('-').unwrap_or((&lower, ""));
+    let primary = match LANGUAGES
+        .iter()
+        .find(|(iso1, t, b, name)| [*iso1, *t, *b, *name].contains(&primary))
+    {
+        Some((iso1, ..)) => iso1.to_string(),
+        None if (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_lowercase()) => {
+            primary.to_string()
+        }
+        None => return None,
+    };
+    if primary == "und" {
+        return None;
+    }
+    let mut tag = primary;
+    for sub in rest.split('-').filter(|s| !s.is_empty()) {
+        if !sub.chars().all(|c| c.is_ascii_alphanumeric()) {
+            break;
+        }
+        tag.push('-');
+        match sub.len() {
+            // 文字（Hant、Latn）首字母大写，地区（TW、BR）全大写
+            4 => {
+                tag.push_str(&sub[..1].to_uppercase());
+                tag.push_str(&sub[1..]);
+            }
+            2 => tag.push_str(&sub.to_uppercase()),
+            _ => tag.push_str(sub),
+        }
+    }
+    Some(tag)
+}
+
+/// 语言标记是否满足筛选条件：大小写不敏感；条件只有主标签时匹配同主标签的任意变体（`zh` 匹配 `zh-Hant`）。
+pub fn matches(tag: &str, wanted: &str) -> bool {
+    if tag.eq_ignore_ascii_case(wanted) {
+        return true;
+    }
+    !wanted.contains('-')
+        && tag
+            .split_once('-')
+            .is_some_and(|(primary, _)| primary.eq_ignore_ascii_case(wanted))
+}
+
+/// 按歌词用到的文字推断演唱语言；无法可靠判断时返回 `None`。
+///
+/// LRC 的 `[mm:ss.xx]` 时间戳与 `[ar:...]` 等标签行不参与统计。
+pub fn infer_from_lyrics(text: &str) -> Option<String> {
+    let (mut kana, mut hangul, mut han, mut latin, mut other) = (0usize, 0usize, 0usize, 0usize, 0usize);
+    let mut words: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let line = strip_lrc_tags(line);
+        for c in line.chars() {
+            match c {
+                '\u{3040}'..='\u{30ff}' | '\u{31f0}'..='\u{31ff}' => kana += 1,
+                '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' => hangul += 1,
+                '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' => han += 1,
+                c if c.is_ascii_alphabetic() || ('\u{c0}'..='\u{24f}').contains(&c) => latin += 1,
+                c if c.is_alphabetic() => other += 1,
+                _ => {}
+            }
+        }
+        words.extend(
+            line.split(|c: char| !(c.is_alphabetic() || c == '\''))
+                .filter(|w| !w.is_empty())
+                .map(str::to_lowercase),
+        );
+    }
+
+    let total = kana + hangul + han + latin + other;
+    if total < MIN_LETTERS {
+        return None;
+    }
+    let cjk = kana + hangul + han;
+    if cjk * 2 >= total {
+        // 日语歌词几乎总夹带假名，韩语歌词偶尔夹带汉字
+        if kana * 20 >= cjk {
+            return Some("ja".to_string());
+        }
+        if hangul * 2 >= cjk {
+            return Some("ko".to_string());
+        }
+        if hangul == 0 {
+            return Some("zh".to_string());
+        }
+        return None;
+    }
+    if latin * 2 >= total && !words.is_empty() {
+        let english = words.iter().filter(|w| ENGLISH_WORDS.contains(&w.as_str())).count();
+        if english as f32 / words.len() as f32 >= ENGLISH_MIN_RATIO {
+            return Some("en".to_string());
+        }
+    }
+    None
+}
+
+/// 去掉行首的 LRC 方括号标签（时间戳、`[ar:...]` 等）。
+pub(crate) fn strip_lrc_tags(mut line: &str) -> &str {
+    while let Some(rest) = line.trim_start().strip_prefix('[') {
+        match rest.split_once(']') {
+            Some((_, after)) => line = after,
+            None => break,
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_and_infer() {
+        assert_eq!(normalize("jpn").as_deref(), Some("ja"));
+        assert_eq!(normalize("ger").as_deref(), Some("de"));
+        assert_eq!(normalize("English").as_deref(), Some("en"));
+        assert_eq!(normalize("zh_tw").as_deref(), Some("zh-TW"));
+        assert_eq!(normalize("zh-hant").as_deref(), Some("zh-Hant"));
+        assert_eq!(normalize("eng/jpn").as_deref(), Some("en"));
+        assert_eq!(normalize("粤语").as_deref(), Some("yue"));
+        assert_eq!(normalize("zxx").as_deref(), Some("zxx"));
+        assert_eq!(normalize("und"), None);
+        assert_eq!(normalize("unknown language"), None);
+
+        assert!(matches("zh-Hant", "zh"));
+        assert!(matches("ZH", "zh"));
+        assert!(!matches("zh", "zh-Hant"));
+        assert!(!matches("zxx", "zh"));
+
+        let ja = "[ti:夜に駆ける]\n[00:01.00]沈むように溶けてゆくように\n[00:05.00]二人だけの空が広がる夜に";
+        assert_eq!(infer_from_lyrics(ja).as_deref(), Some("ja"));
+        let ko = "[00:01.00]너의 모든 순간 그게 나였으면 좋겠다\n나의 모든 순간";
+        assert_eq!(infer_from_lyrics(ko).as_deref(), Some("ko"));
+        let zh = "[00:01.00]天青色等烟雨 而我在等你\n炊烟袅袅升起 隔江千万里";
+        assert_eq!(infer_from_lyrics(zh).as_deref(), Some("zh"));
+        let en = "[00:01.00]Look at the stars, look how they shine for you\nAnd everything you do";
+        assert_eq!(infer_from_lyrics(en).as_deref(), Some("en"));
+        // 其他拉丁语言与过短的文本不推断
+        let es = "Despacito, quiero respirar tu cuello despacito, deja que te diga cosas al oído";
+        assert_eq!(infer_from_lyrics(es), None);
+        assert_eq!(infer_from_lyrics("[00:00.00]纯音乐，请欣赏"), None);
+    }
+}
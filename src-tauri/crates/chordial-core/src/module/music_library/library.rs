@@ -1,13 +1,29 @@
-use super::{albums, artists, lyrics, models::*, relations, search, songs};
+use super::{aggregates, albums, artists, batch, books, diff, edits, grouping, hidden, history, localize, lyrics, models::*, playlist_export, playlists, quality, relations, search, smart_playlists, songs, stats, store, versions};
+use crate::module::analysis::fingerprint::{self, Fingerprint};
 use crate::module::music_source::registrar::SourceCleanup;
 use crate::module::music_source::types::{EntityType, SourceId};
 use crate::module::perf;
+use crate::module::playback::{gain, ContentType};
 use crate::module::storage::persistent::PersistentStore;
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use crate::module::storage::snapshot::{RecoveryReport, SnapshotInfo, Snapshots};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// [`MusicLibrary::undo_last_change`] 的结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct UndoResult {
+    /// 被撤销的变更
+    pub entry: history::ChangeEntry,
+    /// 需要重新索引的文件路径
+    #[serde(skip)]
+    pub reindex_paths: Vec<String>,
+}
 
 /// 音乐库 — 所有音乐实体的统一管理入口。
 ///
@@ -24,22 +40,76 @@ use std::sync::Arc;
 /// | [`lyrics`] | 歌词 CRUD + 搜索 |
 /// | [`relations`] | 跨实体关系追溯 |
 /// | [`search`] | 统一搜索引擎（trigram 倒排索引） |
+/// | [`quality`] | 音质筛选索引（无损 / 码率 / 编码 / 采样率） |
+/// | [`playlists`] | 歌单与歌单文件夹层级 |
+/// | [`smart_playlists`] | 智能歌单的规则与求值 |
+/// | [`playlist_export`] | 歌单导出与按线索匹配的导入 |
+/// | [`hidden`] | 隐藏的歌曲 / 专辑（列表、搜索、队列中排除） |
+/// | [`store`] | SQLite 索引库（按艺人、无损筛选的查询走索引表） |
 pub struct MusicLibrary {
     store: PersistentStore,
+    /// 歌曲 / 艺人 / 专辑的 SQLite 索引库（与库文件同名的 `.db`）；打不开时为 `None`，查询退回扫描 JSON。
+    index_db: Option<store::LibraryStore>,
     /// 库版本号 — 任何写操作递增，用于 [`search::SearchIndex`] 失效检测。
     version: AtomicU64,
     /// 搜索索引缓存 — 首次查询时构建，写操作使其失效。
     /// `Arc<SearchIndex>` 允许并发查询无锁读取。
     search_index: RwLock<Option<Arc<search::SearchIndex>>>,
+    /// 音质筛选索引缓存 — 与搜索索引相同的构建 / 失效方式。
+    quality_index: RwLock<Option<Arc<quality::QualityIndex>>>,
+    /// 显示语言偏好 — 查询命令序列化歌曲前据此替换标题 / 艺人名。
+    display_language: RwLock<Option<String>>,
+    /// 艺人列表的默认分组方式（调用方未指定时使用）。
+    group_by: RwLock<grouping::GroupBy>,
+    /// 写回线程的触发器 — 启用写回后 [`save`](Self::save) 只发送请求，由后台合并落盘。
+    flush_tx: Mutex<Option<mpsc::Sender<()>>>,
+    /// 库文件的每日快照。
+    snapshots: Snapshots,
+    /// 启动时库文件损坏并已回滚的记录。
+    recovery: Option<RecoveryReport>,
 }
 
 impl MusicLibrary {
     /// 创建音乐库实例，从 `path` 指定的 JSON 文件加载已有数据。
+    ///
+    /// 文件无法解析时先回滚到最近一份快照再加载，结果见 [`recovery_report`](Self::recovery_report)。
     pub fn new(path: PathBuf) -> Self {
+        let snapshots = Snapshots::new(path.clone());
+        let recovery = snapshots.heal();
+        if let Some(report) = &recovery {
+            match &report.restored_from {
+                Some(snapshot) => eprintln!(
+                    "[library] 库文件损坏（{}），已回滚到 {} 的快照，此后的修改丢失",
+                    report.error, snapshot.date
+                ),
+                None => eprintln!("[library] 库文件损坏（{}）且没有可用快照，从空库开始", report.error),
+            }
+        }
+        let index_db = match store::LibraryStore::open(&path.with_extension("db")) {
+            Ok(db) => Some(db),
+            Err(e) => {
+                eprintln!("[library] 索引库不可用，查询退回扫描库文件: {}", e);
+                None
+            }
+        };
+        let store = PersistentStore::new(path);
+        store.track_entry_changes(&[songs::KEY, albums::KEY, artists::KEY]);
+        if !aggregates::exists(&store) {
+            if let Err(e) = aggregates::rebuild(&store) {
+                eprintln!("[library] 生成专辑 / 艺人汇总失败: {}", e);
+            }
+        }
         Self {
-            store: PersistentStore::new(path),
+            store,
+            index_db,
             version: AtomicU64::new(0),
             search_index: RwLock::new(None),
+            quality_index: RwLock::new(None),
+            display_language: RwLock::new(None),
+            group_by: RwLock::new(grouping::GroupBy::default()),
+            flush_tx: Mutex::new(None),
+            snapshots,
+            recovery,
         }
     }
 
@@ -48,12 +118,35 @@ impl MusicLibrary {
         self.version.load(Ordering::Acquire)
     }
 
+    /// 记录当前歌曲 / 专辑的内容指纹，批量写操作结束后用 [`diff_since`](Self::diff_since) 得出差异。
+    pub fn snapshot(&self) -> diff::LibrarySnapshot {
+        diff::LibrarySnapshot::capture(&self.store)
+    }
+
+    /// 自 `before` 以来增删改的歌曲与专辑。
+    pub fn diff_since(&self, before: &diff::LibrarySnapshot) -> diff::LibraryDiff {
+        before.diff(&self.snapshot())
+    }
+
     /// 标记搜索索引失效，下次查询时重建。
     /// 由所有写操作调用。
     fn bump_version(&self) {
         self.version.fetch_add(1, Ordering::Release);
         // 直接丢弃缓存的索引；正在使用旧索引的查询线程仍持有 Arc，安全继续。
         *self.search_index.write() = None;
+        *self.quality_index.write() = None;
+    }
+
+    /// 把未同步的修改写入索引库后返回它；索引库不可用或同步失败时返回 `None`，调用方退回扫描 JSON。
+    fn indexed(&self) -> Option<&store::LibraryStore> {
+        let db = self.index_db.as_ref()?;
+        match db.sync(&self.store) {
+            Ok(_) => Some(db),
+            Err(e) => {
+                eprintln!("[library] 同步索引库失败: {}", e);
+                None
+            }
+        }
     }
 
     /// 强制使搜索索引失效（外部数据变更时调用，如 reload 后）。
@@ -63,14 +156,101 @@ impl MusicLibrary {
 
     // ── 持久化 ───────────────────────────────────────
 
-    /// 立即将所有未落盘的修改写入磁盘。
+    /// 请求落盘。
+    ///
+    /// 启用写回（[`enable_write_behind`](Self::enable_write_behind)）后仅通知后台线程，
+    /// 同一时间窗内的多次请求合并为一次写盘；未启用时立即写入。
     pub fn save(&self) -> Result<(), String> {
-        self.store.save()
+        if self.request_flush() {
+            Ok(())
+        } else {
+            self.store.save()
+        }
     }
 
-    /// 仅当存在未保存修改时才写入磁盘。
+    /// 仅当存在未保存修改时才请求落盘（写回模式下同样交给后台线程）。
     pub fn save_if_dirty(&self) -> Result<(), String> {
-        self.store.save_if_dirty()
+        if self.request_flush() {
+            Ok(())
+        } else {
+            self.store.save_if_dirty()
+        }
+    }
+
+    /// 立即写入所有未落盘的修改，绕过写回窗口。
+    ///
+    /// 用于用户显式保存和应用退出。
+    pub fn flush(&self) -> Result<(), String> {
+        // 先给磁盘上的旧版本拍当天快照，再覆盖写入
+        if let Err(e) = self.snapshots.take_daily() {
+            eprintln!("[library] 每日快照失败: {}", e);
+        }
+        self.store.save_if_dirty()?;
+        self.indexed();
+        Ok(())
+    }
+
+    // ── 快照与自愈 ───────────────────────────────────
+
+    /// 当天还没有快照时为库文件拍一份（启动后与每次落盘前调用）。
+    pub fn snapshot_daily(&self) -> Result<Option<SnapshotInfo>, String> {
+        self.snapshots.take_daily()
+    }
+
+    /// 已有快照，新的在前。
+    pub fn list_snapshots(&self) -> Vec<SnapshotInfo> {
+        self.snapshots.list()
+    }
+
+    /// 设置快照保留份数，并清理多余的旧快照。
+    pub fn set_snapshot_retain(&self, retain: usize) -> Result<(), String> {
+        self.snapshots.set_retain(retain)
+    }
+
+    /// 本次启动时的损坏回滚记录；库文件完好时为 `None`。
+    pub fn recovery_report(&self) -> Option<&RecoveryReport> {
+        self.recovery.as_ref()
+    }
+
+    /// 启用写回：`save()` 请求改由后台线程合并，距首个请求 `interval` 后统一落盘。
+    ///
+    /// 采用节流而非防抖，长时间扫描期间也会按 `interval` 周期性落盘。
+    /// 后台线程只持有弱引用，音乐库释放后自动退出；退出前应调用 [`flush`](Self::flush)。
+    pub fn enable_write_behind(self: &Arc<Self>, interval: Duration) {
+        let (tx, rx) = mpsc::channel::<()>();
+        let weak = Arc::downgrade(self);
+        let spawned = thread::Builder::new()
+            .name("library-write-behind".into())
+            .spawn(move || {
+                while let Ok(()) = rx.recv() {
+                    // 窗口内的后续请求全部吸收
+                    let deadline = Instant::now() + interval;
+                    loop {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero() || rx.recv_timeout(remaining).is_err() {
+                            break;
+                        }
+                    }
+                    let Some(library) = weak.upgrade() else {
+                        break;
+                    };
+                    if let Err(e) = library.flush() {
+                        eprintln!("[library] 写回落盘失败: {}", e);
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => *self.flush_tx.lock() = Some(tx),
+            Err(e) => eprintln!("[library] 启动写回线程失败，保持同步落盘: {}", e),
+        }
+    }
+
+    /// 通知写回线程；未启用写回时返回 `false`。
+    fn request_flush(&self) -> bool {
+        match self.flush_tx.lock().as_ref() {
+            Some(tx) => tx.send(()).is_ok(),
+            None => false,
+        }
     }
 
     /// 从磁盘重新加载，丢弃所有未保存的修改。
@@ -90,6 +270,49 @@ impl MusicLibrary {
         self.bump_version();
     }
 
+    // ── 显示语言 ─────────────────────────────────────
+
+    /// 当前显示语言偏好（`None` 表示按标签原文显示）。
+    pub fn display_language(&self) -> Option<String> {
+        self.display_language.read().clone()
+    }
+
+    /// 设置显示语言偏好（仅影响展示，不修改库内数据）。
+    pub fn set_display_language(&self, lang: Option<String>) {
+        *self.display_language.write() = lang.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    }
+
+    /// 艺人列表的默认分组方式。
+    pub fn group_by(&self) -> grouping::GroupBy {
+        *self.group_by.read()
+    }
+
+    pub fn set_group_by(&self, group_by: grouping::GroupBy) {
+        *self.group_by.write() = group_by;
+    }
+
+    /// 按显示语言偏好生成歌曲的展示副本。
+    pub fn localize_song(&self, mut song: Song) -> Song {
+        if let Some(lang) = self.display_language.read().as_deref() {
+            localize::localize_song(&mut song, lang);
+        }
+        song
+    }
+
+    /// 批量版 [`localize_song`](Self::localize_song)。
+    pub fn localize_songs(&self, songs: Vec<Song>) -> Vec<Song> {
+        match self.display_language.read().as_deref() {
+            Some(lang) => songs
+                .into_iter()
+                .map(|mut song| {
+                    localize::localize_song(&mut song, lang);
+                    song
+                })
+                .collect(),
+            None => songs,
+        }
+    }
+
     // ── Song ─────────────────────────────────────────
 
     pub fn song_count(&self) -> usize {
@@ -107,6 +330,11 @@ impl MusicLibrary {
             .collect()
     }
 
+    /// 按 ID 列表分页获取歌曲，见 [`songs::get_page_by_ids`]。
+    pub fn get_songs_page_by_ids(&self, ids: &[String], offset: usize, limit: usize) -> songs::IdPage {
+        songs::get_page_by_ids(&self.store, ids, offset, limit)
+    }
+
     pub fn get_all_songs(&self) -> HashMap<String, Song> {
         self.store.get_all_map::<Song>(songs::KEY)
     }
@@ -146,6 +374,9 @@ impl MusicLibrary {
 
         if sc {
             self.store.set("songs", &all_songs)?;
+            let mut affected = aggregates::Affected::default();
+            affected.songs(all_songs.get(&stored_id));
+            aggregates::refresh(&self.store, &affected)?;
         }
         if ac {
             self.store.set("artists", &all_artists)?;
@@ -161,14 +392,22 @@ impl MusicLibrary {
     }
 
     pub fn update_song(&self, song: &Song) -> Result<(), String> {
+        let old = self.get_song(&song.id);
         songs::update(&self.store, song)?;
+        let mut affected = aggregates::Affected::default();
+        affected.songs(old.iter().chain([song]));
+        aggregates::refresh(&self.store, &affected)?;
         self.bump_version();
         Ok(())
     }
 
     pub fn remove_song(&self, id: &str) -> Result<bool, String> {
+        let old = self.get_song(id);
         let removed = songs::remove(&self.store, id)?;
         if removed {
+            let mut affected = aggregates::Affected::default();
+            affected.songs(&old);
+            aggregates::refresh(&self.store, &affected)?;
             self.bump_version();
         }
         Ok(removed)
@@ -177,7 +416,7 @@ impl MusicLibrary {
     pub fn search_songs(&self, query: &str) -> Vec<Song> {
         let _scope = perf::scope("library.search");
         let artists_map = artists::get_all(&self.store);
-        songs::search(&self.store, query, &artists_map)
+        self.without_hidden(songs::search(&self.store, query, &artists_map))
     }
 
     // ── Artist ───────────────────────────────────────
@@ -186,17 +425,57 @@ impl MusicLibrary {
         artists::count(&self.store)
     }
 
+    /// 按 ID 获取艺术家；作曲者 ID（见 [`grouping`]）返回由歌曲标签汇总的作曲者条目。
     pub fn get_artist(&self, id: &str) -> Option<Artist> {
-        artists::get(&self.store, id)
+        match grouping::composer_name(id) {
+            Some(name) => grouping::get_composer(&self.store, name),
+            None => artists::get(&self.store, id),
+        }
     }
 
     /// 批量按 ID 获取艺术家（O(1) 每条，避免 `get_all_artists` 全量反序列化）。
     pub fn get_artists_by_ids(&self, ids: &[String]) -> Vec<Artist> {
         ids.iter()
-            .filter_map(|id| artists::get(&self.store, id))
+            .filter_map(|id| self.get_artist(id))
             .collect()
     }
 
+    /// 按分组方式计数；`None` 使用 [`group_by`](Self::group_by) 设置。
+    pub fn artist_count_grouped(&self, group_by: Option<grouping::GroupBy>) -> usize {
+        match group_by.unwrap_or_else(|| self.group_by()) {
+            grouping::GroupBy::Artist => self.artist_count(),
+            grouping::GroupBy::Composer => grouping::composers(&self.store).len(),
+        }
+    }
+
+    /// 按分组方式获取全部艺人，见 [`artist_count_grouped`](Self::artist_count_grouped)。
+    pub fn get_all_artists_grouped(&self, group_by: Option<grouping::GroupBy>) -> HashMap<String, Artist> {
+        match group_by.unwrap_or_else(|| self.group_by()) {
+            grouping::GroupBy::Artist => self.get_all_artists(),
+            grouping::GroupBy::Composer => grouping::composers(&self.store)
+                .into_iter()
+                .map(|a| (a.id.clone(), a))
+                .collect(),
+        }
+    }
+
+    /// 按分组方式分页获取艺人；作曲者按名称排序。
+    pub fn get_artists_page_grouped(
+        &self,
+        group_by: Option<grouping::GroupBy>,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<Artist> {
+        match group_by.unwrap_or_else(|| self.group_by()) {
+            grouping::GroupBy::Artist => self.get_artists_page(offset, limit),
+            grouping::GroupBy::Composer => grouping::composers(&self.store)
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .collect(),
+        }
+    }
+
     pub fn get_all_artists(&self) -> HashMap<String, Artist> {
         self.store.get_all_map::<Artist>(artists::KEY)
     }
@@ -230,10 +509,36 @@ impl MusicLibrary {
         artists::search(&self.store, query)
     }
 
+    /// 手动设置艺人简介；`None` 或空白清除。立即请求落盘。
+    pub fn set_artist_bio(&self, id: &str, bio: Option<String>) -> Result<Artist, String> {
+        let mut artist = self.get_artist(id).ok_or_else(|| format!("艺术家 '{}' 不存在", id))?;
+        artist.bio = bio.map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+        self.update_artist(&artist)?;
+        self.save()?;
+        Ok(artist)
+    }
+
+    /// 手动设置艺人流派：去除首尾空白与空项，忽略大小写去重，保留给定顺序。立即请求落盘。
+    pub fn set_artist_genres(&self, id: &str, genres: Vec<String>) -> Result<Artist, String> {
+        let mut artist = self.get_artist(id).ok_or_else(|| format!("艺术家 '{}' 不存在", id))?;
+        let mut seen = HashSet::new();
+        artist.genres = genres
+            .into_iter()
+            .map(|g| g.trim().to_string())
+            .filter(|g| !g.is_empty() && seen.insert(g.to_lowercase()))
+            .collect();
+        self.update_artist(&artist)?;
+        self.save()?;
+        Ok(artist)
+    }
+
     // ── Album ────────────────────────────────────────
 
+    /// 专辑总数（不含已隐藏的专辑）。
     pub fn album_count(&self) -> usize {
-        albums::count(&self.store)
+        let hidden = hidden::load(&self.store).albums;
+        let present = hidden.keys().filter(|id| albums::get(&self.store, id).is_some()).count();
+        albums::count(&self.store) - present
     }
 
     pub fn get_album(&self, id: &str) -> Option<Album> {
@@ -253,9 +558,23 @@ impl MusicLibrary {
         self.store.get_all_map::<Album>(albums::KEY)
     }
 
-    /// 分页获取专辑。
+    /// 分页获取专辑（跳过已隐藏的专辑）。
     pub fn get_albums_page(&self, offset: usize, limit: usize) -> Vec<Album> {
-        albums::get_page(&self.store, offset, limit)
+        let hidden = self.hidden_set();
+        if hidden.is_empty() {
+            return albums::get_page(&self.store, offset, limit);
+        }
+        let Some(serde_json::Value::Object(all)) = self.store.get_raw(albums::KEY) else {
+            return Vec::new();
+        };
+        let page: Vec<String> = all
+            .keys()
+            .filter(|id| !hidden.hides_album(id))
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect();
+        self.get_albums_by_ids(&page)
     }
 
     /// 获取首页所需的数据：计数 + 少量示例条目。
@@ -305,9 +624,25 @@ impl MusicLibrary {
         Ok(removed)
     }
 
+    /// 专辑的曲目数、总时长与总大小。
+    pub fn album_totals(&self, album_id: &str) -> aggregates::Totals {
+        aggregates::album(&self.store, album_id)
+    }
+
+    /// 艺人名下歌曲的曲目数、总时长与总大小。
+    pub fn artist_totals(&self, artist_id: &str) -> aggregates::Totals {
+        aggregates::artist(&self.store, artist_id)
+    }
+
+    /// 按当前歌曲全量重新生成所有汇总。
+    pub fn rebuild_totals(&self) -> Result<(), String> {
+        aggregates::rebuild(&self.store)?;
+        self.save()
+    }
+
     pub fn search_albums(&self, query: &str) -> Vec<Album> {
         let artists_map = artists::get_all(&self.store);
-        albums::search(&self.store, query, &artists_map)
+        self.without_hidden_albums(albums::search(&self.store, query, &artists_map))
     }
 
     // ── Lyric ────────────────────────────────────────
@@ -359,12 +694,93 @@ impl MusicLibrary {
 
     /// 获取某艺术家的所有歌曲。
     pub fn get_songs_by_artist(&self, artist_id: &str) -> Vec<Song> {
-        relations::get_songs_by_artist(&self.store, artist_id)
+        match grouping::composer_name(artist_id) {
+            Some(name) => grouping::songs_by_composer(&self.store, name),
+            None => match self.indexed().map(|db| db.songs_by_artist(artist_id)) {
+                Some(Ok(songs)) => songs,
+                _ => relations::get_songs_by_artist(&self.store, artist_id),
+            },
+        }
     }
 
-    /// 获取某艺术家的所有专辑。
+    /// 获取某艺术家的所有专辑（不含已隐藏的专辑）。
     pub fn get_albums_by_artist(&self, artist_id: &str) -> Vec<Album> {
-        relations::get_albums_by_artist(&self.store, artist_id)
+        let albums = match grouping::composer_name(artist_id) {
+            Some(name) => grouping::albums_by_composer(&self.store, name),
+            None => match self.indexed().map(|db| db.albums_by_artist(artist_id)) {
+                Some(Ok(albums)) => relations::sort_by_chronology(albums),
+                _ => relations::get_albums_by_artist(&self.store, artist_id),
+            },
+        };
+        self.without_hidden_albums(albums)
+    }
+
+    /// 由合作曲目与共同专辑得出的相关艺人，见 [`relations::get_related_artists`]。
+    pub fn get_related_artists(&self, artist_id: &str, limit: usize) -> Vec<relations::RelatedArtist> {
+        relations::get_related_artists(&self.store, artist_id, limit)
+    }
+
+    /// 满足音质条件且未隐藏的歌曲 ID（按存储顺序）；既无条件也没有隐藏条目时返回 `None`，表示不限制。
+    pub fn quality_matches(&self, filter: Option<&quality::QualityFilter>) -> Option<Vec<String>> {
+        let filter = filter.filter(|f| !f.is_empty());
+        let hidden = self.hidden_set();
+        if filter.is_none() && hidden.is_empty() {
+            return None;
+        }
+        let index = self.get_or_build_quality_index();
+        let mut ids = match filter {
+            Some(filter) => index.matching_ids(filter),
+            None => index.ids().to_vec(),
+        };
+        ids.retain(|id| !hidden.hides_song_id(id));
+        Some(ids)
+    }
+
+    /// 库中出现的演唱语言及各自的歌曲数，供语言筛选列出可选项。
+    pub fn get_song_languages(&self) -> Vec<quality::LanguageCount> {
+        self.get_or_build_quality_index().languages()
+    }
+
+    /// 按音质条件筛选一组歌曲（保持原顺序），已隐藏的歌曲总是被排除。
+    pub fn filter_songs(&self, songs: Vec<Song>, filter: Option<&quality::QualityFilter>) -> Vec<Song> {
+        let Some(ids) = self.quality_matches(filter) else {
+            return songs;
+        };
+        let ids: HashSet<String> = ids.into_iter().collect();
+        songs.into_iter().filter(|s| ids.contains(&s.id)).collect()
+    }
+
+    /// 带音质条件的歌曲总数。
+    pub fn song_count_filtered(&self, filter: Option<&quality::QualityFilter>) -> usize {
+        match self.quality_matches(filter) {
+            Some(ids) => ids.len(),
+            None => self.song_count(),
+        }
+    }
+
+    /// 带音质条件的歌曲分页；顺序与不加条件时一致。
+    pub fn get_songs_page_filtered(
+        &self,
+        offset: usize,
+        limit: usize,
+        filter: Option<&quality::QualityFilter>,
+    ) -> Vec<Song> {
+        match self.quality_matches(filter) {
+            Some(ids) => {
+                let page: Vec<String> = ids.into_iter().skip(offset).take(limit).collect();
+                self.get_songs_by_ids(&page)
+            }
+            None => self.get_songs_page(offset, limit),
+        }
+    }
+
+    /// 无损编码（FLAC / ALAC / WAV 等）的歌曲。
+    pub fn get_lossless_songs(&self) -> Vec<Song> {
+        let songs = match self.indexed().map(|db| db.lossless_songs()) {
+            Some(Ok(songs)) => songs,
+            _ => songs::lossless(&self.store),
+        };
+        self.without_hidden(songs)
     }
 
     /// 获取专辑中的所有歌曲。
@@ -426,6 +842,9 @@ impl MusicLibrary {
                 for id in &to_remove {
                     self.store.remove_entry(songs::KEY, id);
                 }
+                let mut touched = aggregates::Affected::default();
+                touched.songs(&affected);
+                aggregates::refresh(&self.store, &touched)?;
             }
         }
 
@@ -581,6 +1000,9 @@ impl MusicLibrary {
             for id in &to_remove {
                 self.store.remove_entry(songs::KEY, id);
             }
+            let mut touched = aggregates::Affected::default();
+            touched.songs(&affected);
+            aggregates::refresh(&self.store, &touched)?;
             self.bump_version();
         }
 
@@ -618,19 +1040,19 @@ impl MusicLibrary {
         let _scope = perf::scope("library.cleanup_empty_entities");
 
         // Songs — JSON 层判断 source_ids 数组为空
-        let empty_songs: Vec<String> = self
+        let empty_songs: Vec<Song> = self
             .store
-            .get_entries_filtered::<serde_json::Value, _>(songs::KEY, |v| {
+            .get_entries_filtered::<Song, _>(songs::KEY, |v| {
                 v.get("source_ids")
                     .and_then(|s| s.as_array())
                     .map_or(true, |arr| arr.is_empty())
-            })
-            .into_iter()
-            .filter_map(|v| v.get("id").and_then(|i| i.as_str()).map(String::from))
-            .collect();
-        for id in &empty_songs {
-            self.store.remove_entry(songs::KEY, id);
+            });
+        for song in &empty_songs {
+            self.store.remove_entry(songs::KEY, &song.id);
         }
+        let mut touched = aggregates::Affected::default();
+        touched.songs(&empty_songs);
+        aggregates::refresh(&self.store, &touched)?;
 
         // Artists — 同上
         let empty_artists: Vec<String> = self
@@ -724,6 +1146,9 @@ impl MusicLibrary {
 
         if songs_changed {
             self.store.set("songs", &all_songs)?;
+            let mut affected = aggregates::Affected::default();
+            affected.songs(stored_ids.iter().filter_map(|id| all_songs.get(id)));
+            aggregates::refresh(&self.store, &affected)?;
         }
         if artists_changed {
             self.store.set("artists", &all_artists)?;
@@ -738,6 +1163,702 @@ impl MusicLibrary {
         Ok(stored_ids)
     }
 
+    // ── 有声书 ───────────────────────────────────────
+
+    /// 书籍分区：所有有声书及其收听进度，按标题排序。
+    ///
+    /// 旧版本入库、尚未标记内容类型的歌曲在此补标并写回库（由调用方落盘）。
+    pub fn get_books(&self) -> Result<Vec<books::Book>, String> {
+        let mut result = Vec::new();
+        for mut song in self.get_all_songs().into_values() {
+            let content_type = books::classify(&song);
+            if content_type != song.content_type {
+                song.content_type = content_type;
+                self.update_song(&song)?;
+            }
+            if content_type == ContentType::Audiobook {
+                let progress = books::get_progress(&self.store, &song.id);
+                result.push(books::Book { song, progress });
+            }
+        }
+        result.sort_by_cached_key(|b| b.song.title.to_lowercase());
+        Ok(result)
+    }
+
+    pub fn get_book_progress(&self, song_id: &str) -> Option<books::BookProgress> {
+        books::get_progress(&self.store, song_id)
+    }
+
+    /// 保存收听进度；`chapter` 为该位置所在章节的下标。
+    pub fn set_book_progress(
+        &self,
+        song_id: &str,
+        position_ms: u64,
+        chapter: Option<usize>,
+    ) -> Result<books::BookProgress, String> {
+        if !self.store.has_entry(songs::KEY, song_id) {
+            return Err(format!("歌曲不存在: {}", song_id));
+        }
+        let progress = books::BookProgress {
+            position_ms,
+            chapter,
+            updated_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+        books::set_progress(&self.store, song_id, &progress)?;
+        Ok(progress)
+    }
+
+    /// 清除收听进度（听完或从头开始）。
+    pub fn clear_book_progress(&self, song_id: &str) {
+        books::clear_progress(&self.store, song_id);
+    }
+
+    // ── 单曲增益 ─────────────────────────────────────
+
+    /// 歌曲实际应用的增益（覆盖值优先于 ReplayGain）。
+    pub fn track_gain(&self, song_id: &str) -> Result<gain::TrackGain, String> {
+        let song = self
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲不存在: {}", song_id))?;
+        Ok(gain::resolve(&song, gain::get_override(&self.store, song_id)))
+    }
+
+    /// 设置或清除（`None`）单曲增益覆盖值，返回新的生效增益。
+    pub fn set_track_gain_db(&self, song_id: &str, gain_db: Option<f32>) -> Result<gain::TrackGain, String> {
+        if !self.store.has_entry(songs::KEY, song_id) {
+            return Err(format!("歌曲不存在: {}", song_id));
+        }
+        gain::set_override(&self.store, song_id, gain_db)?;
+        self.track_gain(song_id)
+    }
+
+    /// 队列中前 `count` 首的生效增益；库中不存在的 ID 跳过。
+    pub fn preview_gains(&self, song_ids: &[String], count: usize) -> Vec<gain::TrackGain> {
+        song_ids
+            .iter()
+            .filter_map(|id| self.track_gain(id).ok())
+            .take(count)
+            .collect()
+    }
+
+    // ── 播放统计 ─────────────────────────────────────
+
+    pub fn get_play_stats(&self, song_id: &str) -> stats::PlayStats {
+        stats::get(&self.store, song_id)
+    }
+
+    /// 导入文件标签中的评分 / 播放次数（合并规则见 [`stats::merge_file_stats`]）。
+    pub fn import_file_stats(
+        &self,
+        song_id: &str,
+        rating: Option<u8>,
+        play_count: Option<u64>,
+    ) -> Result<(), String> {
+        let mut current = stats::get(&self.store, song_id);
+        if stats::merge_file_stats(&mut current, rating, play_count) {
+            stats::set(&self.store, song_id, &current)?;
+        }
+        Ok(())
+    }
+
+    /// 设置评分（0–100，`None` 清除评分）。
+    pub fn set_rating(&self, song_id: &str, rating: Option<u8>) -> Result<stats::PlayStats, String> {
+        if rating.is_some_and(|r| r > 100) {
+            return Err("评分需在 0–100 之间".to_string());
+        }
+        if !self.store.has_entry(songs::KEY, song_id) {
+            return Err(format!("歌曲不存在: {}", song_id));
+        }
+        let mut current = stats::get(&self.store, song_id);
+        current.rating = rating.filter(|r| *r > 0);
+        stats::set(&self.store, song_id, &current)?;
+        Ok(current)
+    }
+
+    /// 批量设置评分。评分越界时整批拒绝；不存在的歌曲记入失败项，其余照常设置。调用方负责落盘。
+    pub fn set_ratings(&self, song_ids: &[String], rating: Option<u8>) -> Result<batch::BatchReport, String> {
+        if rating.is_some_and(|r| r > 100) {
+            return Err("评分需在 0–100 之间".to_string());
+        }
+        Ok(batch::BatchReport::run(song_ids, |id| self.set_rating(id, rating).map(|_| ())))
+    }
+
+    /// 记录一次播放：播放次数加一并更新最近播放时间。
+    pub fn record_play(&self, song_id: &str) -> Result<stats::PlayStats, String> {
+        if !self.store.has_entry(songs::KEY, song_id) {
+            return Err(format!("歌曲不存在: {}", song_id));
+        }
+        let mut current = stats::get(&self.store, song_id);
+        current.play_count += 1;
+        current.last_played_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .ok();
+        stats::set(&self.store, song_id, &current)?;
+        Ok(current)
+    }
+
+    // ── 用户编辑 ─────────────────────────────────────
+
+    /// 获取文件的用户编辑（见 [`edits`]）。
+    pub fn user_edits(&self, path: &str) -> edits::FileEdits {
+        edits::get(&self.store, path).unwrap_or_default()
+    }
+
+    /// 记录用户对文件元数据的修改。
+    ///
+    /// `file_values` 为当前文件标签中的值，作为重扫对账的基线；已有编辑的字段保留原基线。
+    /// 修改后与文件值相同的字段视为撤销编辑。涉及字段的旧冲突一并清除。
+    /// 只记录编辑，歌曲本身需由调用方重新索引后才会更新。
+    pub fn edit_metadata(
+        &self,
+        path: &str,
+        changes: &edits::FieldValues,
+        file_values: &edits::FieldValues,
+    ) -> Result<(), String> {
+        let mut file_edits = self.user_edits(path);
+        for (field, value) in changes {
+            let file_value = file_values.get(field).cloned().flatten();
+            if *value == file_value {
+                file_edits.remove(field);
+                continue;
+            }
+            file_edits
+                .entry(*field)
+                .and_modify(|e| e.value = value.clone())
+                .or_insert_with(|| edits::FieldEdit {
+                    value: value.clone(),
+                    file_value,
+                });
+        }
+        edits::set(&self.store, path, &file_edits)?;
+
+        let mut conflicts = edits::get_conflicts(&self.store, path);
+        let before = conflicts.len();
+        conflicts.retain(|c| !changes.contains_key(&c.field));
+        if conflicts.len() != before {
+            edits::set_conflicts(&self.store, path, &conflicts)?;
+        }
+        Ok(())
+    }
+
+    /// 重扫对账：`values` 传入文件值，改写为应用用户编辑后的生效值。
+    ///
+    /// 新发现的冲突写入冲突列表（同一字段只保留最新一条），返回新冲突数。
+    pub fn apply_user_edits(
+        &self,
+        path: &str,
+        values: &mut edits::FieldValues,
+    ) -> Result<usize, String> {
+        let Some(mut file_edits) = edits::get(&self.store, path) else {
+            return Ok(0);
+        };
+        let before = file_edits.clone();
+        let found = edits::reconcile(&mut file_edits, values);
+        if file_edits != before {
+            edits::set(&self.store, path, &file_edits)?;
+        }
+        if found.is_empty() {
+            return Ok(0);
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut conflicts = edits::get_conflicts(&self.store, path);
+        for (field, previous_file_value) in &found {
+            conflicts.retain(|c| c.field != *field);
+            conflicts.push(edits::MetadataConflict {
+                path: path.to_string(),
+                field: *field,
+                user_value: values.get(field).cloned().flatten(),
+                file_value: file_edits.get(field).and_then(|e| e.file_value.clone()),
+                previous_file_value: previous_file_value.clone(),
+                detected_at: now,
+            });
+        }
+        edits::set_conflicts(&self.store, path, &conflicts)?;
+        Ok(found.len())
+    }
+
+    /// 全部未处理的元数据冲突。
+    pub fn get_metadata_conflicts(&self) -> Vec<edits::MetadataConflict> {
+        edits::all_conflicts(&self.store)
+    }
+
+    /// 处理一条冲突；[`UseFile`](edits::ConflictResolution::UseFile) 时撤销该字段的用户编辑。
+    ///
+    /// 返回冲突是否存在。采用文件值后歌曲需由调用方重新索引。
+    pub fn resolve_metadata_conflict(
+        &self,
+        path: &str,
+        field: edits::MetadataField,
+        resolution: edits::ConflictResolution,
+    ) -> Result<bool, String> {
+        let mut conflicts = edits::get_conflicts(&self.store, path);
+        let before = conflicts.len();
+        conflicts.retain(|c| c.field != field);
+        if conflicts.len() == before {
+            return Ok(false);
+        }
+        let snapshot = self.snapshot_files(&[path.to_string()]);
+        self.record_change(
+            format!("处理元数据冲突: {}", path),
+            history::Change::MetadataEdit { files: snapshot },
+        )?;
+        edits::set_conflicts(&self.store, path, &conflicts)?;
+        if resolution == edits::ConflictResolution::UseFile {
+            let mut file_edits = self.user_edits(path);
+            file_edits.remove(&field);
+            edits::set(&self.store, path, &file_edits)?;
+        }
+        Ok(true)
+    }
+
+    // ── 变更历史 ─────────────────────────────────────
+
+    /// 快照文件当前的用户编辑与冲突，供写入变更历史。
+    pub fn snapshot_files(&self, paths: &[String]) -> Vec<history::FileSnapshot> {
+        paths
+            .iter()
+            .map(|path| history::FileSnapshot {
+                path: path.clone(),
+                edits: self.user_edits(path),
+                conflicts: edits::get_conflicts(&self.store, path),
+            })
+            .collect()
+    }
+
+    /// 追加一条变更记录，返回其编号。
+    pub fn record_change(&self, summary: String, change: history::Change) -> Result<u64, String> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        history::push(&self.store, summary, change, now)
+    }
+
+    /// 变更历史摘要，最新的在前。
+    pub fn change_history(&self) -> Vec<history::ChangeEntry> {
+        history::load(&self.store)
+            .iter()
+            .rev()
+            .map(history::ChangeRecord::entry)
+            .collect()
+    }
+
+    /// 从库中移除歌曲（不删除文件），整批记为一条变更。返回实际移除的数量。
+    pub fn remove_songs(&self, ids: &[String]) -> Result<usize, String> {
+        let songs = self.get_songs_by_ids(ids);
+        if songs.is_empty() {
+            return Ok(0);
+        }
+        let summary = match songs.as_slice() {
+            [song] => format!("移除歌曲: {}", song.title),
+            _ => format!("移除 {} 首歌曲", songs.len()),
+        };
+        for song in &songs {
+            songs::remove(&self.store, &song.id)?;
+        }
+        let mut affected = aggregates::Affected::default();
+        affected.songs(&songs);
+        aggregates::refresh(&self.store, &affected)?;
+        let removed = songs.len();
+        self.record_change(summary, history::Change::RemoveSongs { songs })?;
+        self.bump_version();
+        Ok(removed)
+    }
+
+    /// 撤销最近一次变更。没有可撤销的记录时返回 `None`。
+    ///
+    /// 元数据类变更只恢复编辑记录，返回的 `reindex_paths` 需由调用方重新索引后歌曲才会更新。
+    pub fn undo_last_change(&self) -> Result<Option<UndoResult>, String> {
+        let Some(record) = history::pop(&self.store)? else {
+            return Ok(None);
+        };
+        let entry = record.entry();
+        let mut reindex_paths = Vec::new();
+        match record.change {
+            history::Change::MetadataEdit { files } => {
+                for file in files {
+                    edits::set(&self.store, &file.path, &file.edits)?;
+                    edits::set_conflicts(&self.store, &file.path, &file.conflicts)?;
+                    reindex_paths.push(file.path);
+                }
+            }
+            history::Change::RemoveSongs { songs } => {
+                self.add_songs_batch(&songs)?;
+            }
+            history::Change::Hide { songs, albums } => {
+                hidden::restore(&self.store, &songs, &albums)?;
+            }
+        }
+        Ok(Some(UndoResult {
+            entry,
+            reindex_paths,
+        }))
+    }
+
+    // ── 隐藏 ─────────────────────────────────────────
+
+    /// 当前的隐藏集合（被隐藏专辑的曲目已展开）。
+    pub fn hidden_set(&self) -> hidden::HiddenSet {
+        hidden::resolve(&self.store)
+    }
+
+    /// 去掉已隐藏的歌曲（保持原顺序）。
+    pub fn without_hidden(&self, mut songs: Vec<Song>) -> Vec<Song> {
+        let hidden = self.hidden_set();
+        if !hidden.is_empty() {
+            songs.retain(|s| !hidden.hides_song(s));
+        }
+        songs
+    }
+
+    /// 去掉已隐藏的专辑（保持原顺序）。
+    pub fn without_hidden_albums(&self, mut albums: Vec<Album>) -> Vec<Album> {
+        let hidden = self.hidden_set();
+        if !hidden.is_empty() {
+            albums.retain(|a| !hidden.hides_album(&a.id));
+        }
+        albums
+    }
+
+    /// 隐藏歌曲：从所有查询中排除，但不删除文件、不移出库。整批记为一条变更，可撤销。
+    ///
+    /// 返回新隐藏的数量（不存在或已隐藏的歌曲不计入）。
+    pub fn hide_songs(&self, ids: &[String]) -> Result<usize, String> {
+        let songs = self.get_songs_by_ids(ids);
+        let summary = match songs.as_slice() {
+            [song] => format!("隐藏歌曲: {}", song.title),
+            _ => format!("隐藏 {} 首歌曲", songs.len()),
+        };
+        let ids: Vec<String> = songs.into_iter().map(|s| s.id).collect();
+        self.hide(summary, &ids, &[])
+    }
+
+    /// 隐藏专辑及其全部曲目，行为同 [`hide_songs`](Self::hide_songs)。
+    pub fn hide_albums(&self, ids: &[String]) -> Result<usize, String> {
+        let albums = self.get_albums_by_ids(ids);
+        let summary = match albums.as_slice() {
+            [album] => format!("隐藏专辑: {}", album.title),
+            _ => format!("隐藏 {} 张专辑", albums.len()),
+        };
+        let ids: Vec<String> = albums.into_iter().map(|a| a.id).collect();
+        self.hide(summary, &[], &ids)
+    }
+
+    fn hide(&self, summary: String, song_ids: &[String], album_ids: &[String]) -> Result<usize, String> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let (songs, albums) = hidden::hide(&self.store, song_ids, album_ids, now)?;
+        let hidden = songs.len() + albums.len();
+        if hidden > 0 {
+            self.record_change(summary, history::Change::Hide { songs, albums })?;
+        }
+        Ok(hidden)
+    }
+
+    /// 恢复已隐藏的歌曲 / 专辑，返回实际恢复的数量。
+    pub fn restore_hidden(&self, song_ids: &[String], album_ids: &[String]) -> Result<usize, String> {
+        hidden::restore(&self.store, song_ids, album_ids)
+    }
+
+    /// 「已隐藏」列表，歌曲按显示语言本地化。
+    pub fn hidden_items(&self) -> hidden::HiddenItems {
+        let mut items = hidden::list(&self.store);
+        if let Some(lang) = self.display_language() {
+            for entry in &mut items.songs {
+                localize::localize_song(&mut entry.item, &lang);
+            }
+        }
+        items
+    }
+
+    // ── 歌单 ─────────────────────────────────────────
+
+    /// 歌单树（文件夹嵌套、按手动顺序）；智能歌单的曲目数按当前库求值。
+    pub fn playlist_tree(&self) -> Vec<playlists::PlaylistNode> {
+        let tree = playlists::load(&self.store);
+        if !tree.playlists.values().any(|p| p.is_smart()) {
+            return tree.nodes(&|_| 0);
+        }
+        let (songs, play_stats) = self.smart_input();
+        tree.nodes(&|p| p.rules.as_ref().map_or(0, |rules| rules.evaluate(&songs, &play_stats).len()))
+    }
+
+    /// 读出歌单树、修改并写回；修改失败时不写回。
+    fn update_playlists<T>(
+        &self,
+        f: impl FnOnce(&mut playlists::PlaylistTree) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut tree = playlists::load(&self.store);
+        let result = f(&mut tree)?;
+        playlists::save(&self.store, &tree)?;
+        Ok(result)
+    }
+
+    /// 新建歌单，放在 `folder`（`None` 为根层级）的末尾。
+    pub fn create_playlist(&self, name: &str, folder: Option<&str>) -> Result<playlists::Playlist, String> {
+        self.update_playlists(|tree| tree.create_playlist(name, folder))
+    }
+
+    /// 新建歌单文件夹，放在 `parent`（`None` 为根层级）的末尾。
+    pub fn create_playlist_folder(
+        &self,
+        name: &str,
+        parent: Option<&str>,
+    ) -> Result<playlists::PlaylistFolder, String> {
+        self.update_playlists(|tree| tree.create_folder(name, parent))
+    }
+
+    /// 重命名歌单或文件夹。
+    pub fn rename_playlist(&self, id: &str, name: &str) -> Result<(), String> {
+        self.update_playlists(|tree| tree.rename(id, name))
+    }
+
+    /// 删除歌单或文件夹（文件夹内容上移，见 [`playlists::PlaylistTree::remove`]）。
+    pub fn remove_playlist(&self, id: &str) -> Result<(), String> {
+        self.update_playlists(|tree| tree.remove(id))
+    }
+
+    /// 移动歌单或文件夹，见 [`playlists::PlaylistTree::move_node`]。
+    pub fn move_playlist(&self, id: &str, folder: Option<&str>, index: Option<usize>) -> Result<(), String> {
+        self.update_playlists(|tree| tree.move_node(id, folder, index))
+    }
+
+    /// 重排文件夹内的歌单与子文件夹。
+    pub fn reorder_playlists(&self, folder: Option<&str>, ids: &[String]) -> Result<(), String> {
+        self.update_playlists(|tree| tree.reorder(folder, ids))
+    }
+
+    /// 歌单中的歌曲，按歌单顺序；已从库中移除的歌曲跳过。
+    pub fn get_playlist_songs(&self, id: &str) -> Result<Vec<Song>, String> {
+        let tree = playlists::load(&self.store);
+        let playlist = tree
+            .playlists
+            .get(id)
+            .ok_or_else(|| format!("歌单不存在: {}", id))?;
+        Ok(self.get_songs_by_ids(&self.playlist_song_ids(playlist)))
+    }
+
+    /// 歌单的曲目 ID：普通歌单为保存的列表，智能歌单按规则对当前库求值。
+    fn playlist_song_ids(&self, playlist: &playlists::Playlist) -> Vec<String> {
+        match &playlist.rules {
+            Some(rules) => self.evaluate_smart(rules),
+            None => playlist.song_ids.clone(),
+        }
+    }
+
+    /// 批量把歌曲加入歌单（列表多选）：库中不存在的歌曲记入 `failed`，其余按请求顺序一次性追加。
+    ///
+    /// 歌单不存在或是智能歌单时整批拒绝，不做任何修改。
+    pub fn add_tracks_to_playlist(
+        &self,
+        id: &str,
+        song_ids: &[String],
+    ) -> Result<(playlists::Playlist, batch::BatchReport), String> {
+        self.update_playlists(|tree| {
+            let playlist = tree.manual_playlist_mut(id)?;
+            let report = batch::BatchReport::run(song_ids, |sid| {
+                if self.store.has_entry(songs::KEY, sid) {
+                    Ok(())
+                } else {
+                    Err(format!("歌曲不存在: {}", sid))
+                }
+            });
+            playlist.song_ids.extend(report.succeeded.iter().cloned());
+            Ok((playlist.clone(), report))
+        })
+    }
+
+    /// 整体替换歌单曲目（删除、调整顺序都通过它完成）。
+    pub fn set_playlist_songs(&self, id: &str, song_ids: Vec<String>) -> Result<playlists::Playlist, String> {
+        self.update_playlists(|tree| {
+            let playlist = tree.manual_playlist_mut(id)?;
+            playlist.song_ids = song_ids;
+            Ok(playlist.clone())
+        })
+    }
+
+    /// 导出歌单，每首曲目附带匹配线索；已从库中移除的曲目略去。
+    pub fn export_playlist(&self, id: &str) -> Result<playlist_export::ExportedPlaylist, String> {
+        let tree = playlists::load(&self.store);
+        let playlist = tree
+            .playlists
+            .get(id)
+            .ok_or_else(|| format!("歌单不存在: {}", id))?;
+        Ok(playlist_export::export(playlist, &self.get_songs_by_ids(&self.playlist_song_ids(playlist))))
+    }
+
+    /// 导入歌单：按导出文件中的线索在本机库中逐首匹配，用命中的曲目新建歌单（放在 `folder` 末尾）。
+    pub fn import_playlist_with_matching(
+        &self,
+        exported: &playlist_export::ExportedPlaylist,
+        folder: Option<&str>,
+    ) -> Result<playlist_export::PlaylistImport, String> {
+        let songs: Vec<Song> = self.get_all_songs().into_values().collect();
+        let (matched, unmatched) = playlist_export::Matcher::new(&songs).resolve(exported);
+        let playlist = self.update_playlists(|tree| {
+            let created = tree.create_playlist(&exported.name, folder)?;
+            let playlist = tree.playlist_mut(&created.id)?;
+            playlist.song_ids = matched.iter().map(|m| m.song_id.clone()).collect();
+            Ok(playlist.clone())
+        })?;
+        Ok(playlist_export::PlaylistImport {
+            playlist,
+            matched,
+            unmatched,
+        })
+    }
+
+    // ── 智能歌单 ─────────────────────────────────────
+
+    /// 智能歌单求值的输入：未隐藏的歌曲（有声书除外）与全部播放统计。
+    fn smart_input(&self) -> (Vec<Song>, HashMap<String, stats::PlayStats>) {
+        let songs = self
+            .get_all_songs()
+            .into_values()
+            .filter(|song| books::classify(song) != ContentType::Audiobook)
+            .collect();
+        let songs = self.without_hidden(songs);
+        (songs, self.store.get_all_map::<stats::PlayStats>(stats::KEY))
+    }
+
+    fn evaluate_smart(&self, rules: &smart_playlists::SmartRules) -> Vec<String> {
+        let _scope = perf::scope("library.evaluate_smart");
+        let (songs, play_stats) = self.smart_input();
+        rules.evaluate(&songs, &play_stats)
+    }
+
+    /// 新建智能歌单，放在 `folder`（`None` 为根层级）的末尾。
+    pub fn create_smart_playlist(
+        &self,
+        name: &str,
+        folder: Option<&str>,
+        rules: smart_playlists::SmartRules,
+    ) -> Result<playlists::Playlist, String> {
+        self.update_playlists(|tree| tree.create_smart_playlist(name, folder, rules))
+    }
+
+    /// 修改智能歌单的规则。
+    pub fn set_smart_playlist_rules(
+        &self,
+        id: &str,
+        rules: smart_playlists::SmartRules,
+    ) -> Result<playlists::Playlist, String> {
+        self.update_playlists(|tree| tree.set_rules(id, rules))
+    }
+
+    /// 按尚未保存的规则试算，编辑规则时实时显示结果。
+    pub fn preview_smart_playlist(
+        &self,
+        rules: &smart_playlists::SmartRules,
+    ) -> Result<smart_playlists::SmartPreview, String> {
+        rules.validate()?;
+        let ids = self.evaluate_smart(rules);
+        let shown: Vec<String> = ids.iter().take(smart_playlists::PREVIEW_LIMIT).cloned().collect();
+        Ok(smart_playlists::SmartPreview {
+            total: ids.len(),
+            songs: self.get_songs_by_ids(&shown),
+        })
+    }
+
+    /// 把智能歌单的当前曲目固化为普通歌单，见 [`playlists::PlaylistTree::materialize`]。
+    pub fn materialize_smart_playlist(&self, id: &str, name: Option<&str>) -> Result<playlists::Playlist, String> {
+        let rules = playlists::load(&self.store)
+            .playlists
+            .get(id)
+            .and_then(|p| p.rules.clone())
+            .ok_or_else(|| format!("不是智能歌单: {}", id))?;
+        let song_ids = self.evaluate_smart(&rules);
+        self.update_playlists(|tree| tree.materialize(id, name, song_ids))
+    }
+
+    // ── 同曲多版本 ───────────────────────────────────
+
+    /// 歌曲的其他版本。`fingerprint` 返回歌曲的音频指纹（无法获取时为 `None`），
+    /// 用于为前 [`versions::MAX_COMPARED`] 个版本计算与当前歌曲的相似度。
+    pub fn track_versions(
+        &self,
+        song_id: &str,
+        fingerprint: impl Fn(&Song) -> Option<Fingerprint>,
+    ) -> Result<versions::TrackVersions, String> {
+        let song = self
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+        let mut found = versions::find(&self.store, &song);
+        if let Some(own) = fingerprint(&song) {
+            for version in found.iter_mut().take(versions::MAX_COMPARED) {
+                if let Some(other) = fingerprint(&version.song) {
+                    let similarity = fingerprint::similarity(&own, &other);
+                    version.similarity = Some(similarity);
+                    version.same_recording = similarity >= fingerprint::SAME_RECORDING_THRESHOLD;
+                }
+            }
+        }
+        Ok(versions::TrackVersions {
+            track_id: song.id,
+            kind: versions::parse_title(&song.title).1,
+            versions: found,
+        })
+    }
+
+    /// 手动关联（`linked = true`）或取消关联两首歌为同曲的不同版本。
+    pub fn set_track_version_link(&self, song_id: &str, other_id: &str, linked: bool) -> Result<(), String> {
+        for id in [song_id, other_id] {
+            if !self.store.has_entry(songs::KEY, id) {
+                return Err(format!("歌曲 '{}' 不存在", id));
+            }
+        }
+        versions::set_link(&self.store, song_id, other_id, linked)
+    }
+
+    // ── 内容哈希 ─────────────────────────────────────
+
+    /// 写入歌曲的音频内容哈希。只改这一个字段，不影响聚合统计和搜索索引。
+    pub fn set_content_hash(&self, song_id: &str, hash: ContentHash) -> Result<(), String> {
+        let mut song = self
+            .get_song(song_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+        if song.content_hash.as_ref() == Some(&hash) {
+            return Ok(());
+        }
+        song.content_hash = Some(hash);
+        songs::update(&self.store, &song)
+    }
+
+    /// 精确重复：音频内容哈希相同的歌曲分组（每组至少两首），组内按 ID 排序。
+    ///
+    /// 没有计算过哈希的歌曲不参与比较。
+    pub fn exact_duplicates(&self) -> Vec<Vec<Song>> {
+        let hashed = self
+            .store
+            .get_entries_filtered::<Song, _>(songs::KEY, |v| v.get("content_hash").is_some_and(|h| !h.is_null()));
+        let mut groups: HashMap<String, Vec<Song>> = HashMap::new();
+        for song in hashed {
+            if let Some(hash) = &song.content_hash {
+                groups.entry(hash.xxh3.clone()).or_default().push(song);
+            }
+        }
+        let mut result: Vec<Vec<Song>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .map(|mut group| {
+                group.sort_by(|a, b| a.id.cmp(&b.id));
+                group
+            })
+            .collect();
+        result.sort_by(|a, b| a[0].id.cmp(&b[0].id));
+        result
+    }
+
     // ── 统一搜索 ─────────────────────────────────────
 
     /// 统一搜索引擎 — 跨 Song / Artist / Album 的子串搜索，
@@ -772,6 +1893,7 @@ impl MusicLibrary {
             limit: limit_per_type,
         };
         let id_sets = search::search_ids(&index, &filter);
+        let hidden = self.hidden_set();
 
         // 按 ID 反序列化具体实体；若指定了 source_name，再做来源过滤
         let songs = if !id_sets.songs.is_empty() {
@@ -779,9 +1901,10 @@ impl MusicLibrary {
                 .get_songs_by_ids(&id_sets.songs)
                 .into_iter()
                 .filter(|s| {
-                    source_name.map_or(true, |name| {
-                        s.source_ids.iter().any(|sid| sid.source_name == name)
-                    })
+                    !hidden.hides_song(s)
+                        && source_name.map_or(true, |name| {
+                            s.source_ids.iter().any(|sid| sid.source_name == name)
+                        })
                 })
                 .collect();
             v.truncate(limit_per_type.unwrap_or(usize::MAX));
@@ -811,9 +1934,10 @@ impl MusicLibrary {
                 .get_albums_by_ids(&id_sets.albums)
                 .into_iter()
                 .filter(|a| {
-                    source_name.map_or(true, |name| {
-                        a.source_ids.iter().any(|sid| sid.source_name == name)
-                    })
+                    !hidden.hides_album(&a.id)
+                        && source_name.map_or(true, |name| {
+                            a.source_ids.iter().any(|sid| sid.source_name == name)
+                        })
                 })
                 .collect();
             v.truncate(limit_per_type.unwrap_or(usize::MAX));
@@ -829,6 +1953,94 @@ impl MusicLibrary {
         }
     }
 
+    /// 带打分与模糊匹配的搜索，供即时搜索框使用，见 [`search::search_ranked`]。
+    ///
+    /// 每类最多返回 `limit` 条；`filter` 只作用于歌曲与歌词命中。歌曲结果按显示语言本地化。
+    pub fn search_library(
+        &self,
+        query: &str,
+        options: &search::SearchOptions,
+        filter: Option<&quality::QualityFilter>,
+        limit: usize,
+    ) -> search::RankedResults {
+        let _scope = perf::scope("library.search_ranked");
+        let index = self.get_or_build_search_index();
+        let [song_ids, artist_ids, album_ids] = search::search_ranked(&index, query, options);
+
+        let allowed: Option<HashSet<String>> = self.quality_matches(filter).map(|ids| ids.into_iter().collect());
+        let hidden = self.hidden_set();
+        let from_source = |source_ids: &[SourceId]| {
+            options
+                .source_name
+                .as_deref()
+                .map_or(true, |name| source_ids.iter().any(|sid| sid.source_name == name))
+        };
+        let song_allowed = |song: &Song| {
+            allowed.as_ref().map_or(true, |ids| ids.contains(&song.id)) && from_source(&song.source_ids)
+        };
+        let lang = self.display_language();
+        let localized = |mut song: Song| {
+            if let Some(lang) = lang.as_deref() {
+                localize::localize_song(&mut song, lang);
+            }
+            song
+        };
+
+        let songs = song_ids
+            .into_iter()
+            .filter_map(|hit| Some((songs::get(&self.store, &hit.id)?, hit)))
+            .filter(|(song, _)| song_allowed(song))
+            .take(limit)
+            .map(|(song, hit)| search::SearchHit { item: localized(song), score: hit.score, matched: hit.kind })
+            .collect();
+        let artists = artist_ids
+            .into_iter()
+            .filter_map(|hit| Some((self.get_artist(&hit.id)?, hit)))
+            .filter(|(artist, _)| from_source(&artist.source_ids))
+            .take(limit)
+            .map(|(artist, hit)| search::SearchHit { item: artist, score: hit.score, matched: hit.kind })
+            .collect();
+        let albums = album_ids
+            .into_iter()
+            .filter_map(|hit| Some((albums::get(&self.store, &hit.id)?, hit)))
+            .filter(|(album, _)| !hidden.hides_album(&album.id) && from_source(&album.source_ids))
+            .take(limit)
+            .map(|(album, hit)| search::SearchHit { item: album, score: hit.score, matched: hit.kind })
+            .collect();
+
+        let query_lower = query.trim().to_lowercase();
+        let mut lyric_hits: Vec<search::SearchHit<search::LyricHit>> = Vec::new();
+        if options.includes(EntityType::Lyric) && !query_lower.is_empty() {
+            for lyric in lyrics::search(&self.store, &query_lower) {
+                let Some((line, score)) = search::lyric_hit_line(&lyric.text, &query_lower) else {
+                    continue;
+                };
+                let Some(song) = songs::get(&self.store, &lyric.song_id).filter(|s| song_allowed(s)) else {
+                    continue;
+                };
+                lyric_hits.push(search::SearchHit {
+                    item: search::LyricHit { song: localized(song), line, score },
+                    score,
+                    matched: search::MatchKind::Lyrics,
+                });
+            }
+            lyric_hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.item.song.id.cmp(&b.item.song.id)));
+            lyric_hits.truncate(limit);
+        }
+
+        search::RankedResults {
+            songs,
+            artists,
+            albums,
+            lyrics: lyric_hits,
+        }
+    }
+
+    /// 预先建立搜索索引，使第一次搜索不必等待全库建索引。
+    pub fn warm_search_index(&self) {
+        self.get_or_build_search_index();
+    }
+
     /// 获取或构建缓存的搜索索引（按版本号校验）。
     ///
     /// 临界区仅做版本比较与 `Arc::clone`，构建在临界区外完成
@@ -859,6 +2071,24 @@ impl MusicLibrary {
         new_index
     }
 
+    /// 取得当前版本的音质索引，过期时重建（同 [`get_or_build_search_index`](Self::get_or_build_search_index)）。
+    fn get_or_build_quality_index(&self) -> Arc<quality::QualityIndex> {
+        if let Some(index) = self.quality_index.read().as_ref() {
+            if index.version() == self.version() {
+                return Arc::clone(index);
+            }
+        }
+        let new_index = Arc::new(quality::QualityIndex::build(&self.store, self.version()));
+        let mut guard = self.quality_index.write();
+        if let Some(existing) = guard.as_ref() {
+            if existing.version() == self.version() {
+                return Arc::clone(existing);
+            }
+        }
+        *guard = Some(Arc::clone(&new_index));
+        new_index
+    }
+
     // ── 私有辅助 ─────────────────────────────────────
 
 }
@@ -903,11 +2133,89 @@ fn merge_or_init_song_in_memory(
                 existing.album_title = song.album_title.clone();
                 songs_changed = true;
             }
-            // 反向填充 year：已有歌曲缺失 year 而新扫描歌曲有 year 时补齐
+            // 反向填充 year / 日期：已有歌曲缺失而新扫描歌曲有时补齐
             if existing.year.is_none() && song.year.is_some() {
                 existing.year = song.year;
                 songs_changed = true;
             }
+            if existing.release_date.is_none() && song.release_date.is_some() {
+                existing.release_date = song.release_date.clone();
+                songs_changed = true;
+            }
+            if existing.original_date.is_none() && song.original_date.is_some() {
+                existing.original_date = song.original_date.clone();
+                songs_changed = true;
+            }
+            // 多语言字段同理：已有歌曲没有时才补齐，不覆盖
+            if existing.alt_titles.is_empty() && !song.alt_titles.is_empty() {
+                existing.alt_titles = song.alt_titles.clone();
+                songs_changed = true;
+            }
+            if existing.alt_artist_names.is_empty() && !song.alt_artist_names.is_empty() {
+                existing.alt_artist_names = song.alt_artist_names.clone();
+                songs_changed = true;
+            }
+            if existing.genres.is_empty() && !song.genres.is_empty() {
+                existing.genres = song.genres.clone();
+                songs_changed = true;
+            }
+            if existing.composers.is_empty() && !song.composers.is_empty() {
+                existing.composers = song.composers.clone();
+                songs_changed = true;
+            }
+            if existing.track_number.is_none() && song.track_number.is_some() {
+                existing.track_number = song.track_number;
+                existing.disc_number = song.disc_number;
+                songs_changed = true;
+            }
+            // ReplayGain 只来自文件，重新计算过增益的文件以新值为准
+            if song.replay_gain_db.is_some() && existing.replay_gain_db != song.replay_gain_db {
+                existing.replay_gain_db = song.replay_gain_db;
+                songs_changed = true;
+            }
+            if song.isrc.is_some() && existing.isrc != song.isrc {
+                existing.isrc = song.isrc.clone();
+                songs_changed = true;
+            }
+            if song.catalog_number.is_some() && existing.catalog_number != song.catalog_number {
+                existing.catalog_number = song.catalog_number.clone();
+                songs_changed = true;
+            }
+            if song.barcode.is_some() && existing.barcode != song.barcode {
+                existing.barcode = song.barcode.clone();
+                songs_changed = true;
+            }
+            // 推断的语言不覆盖标签中的语言
+            if song.language.is_some()
+                && existing.language != song.language
+                && (!song.language_inferred || existing.language.is_none() || existing.language_inferred)
+            {
+                existing.language = song.language.clone();
+                existing.language_inferred = song.language_inferred;
+                songs_changed = true;
+            }
+            // 文件大小同理，以最近一次扫描为准
+            if song.size_bytes.is_some() && existing.size_bytes != song.size_bytes {
+                existing.size_bytes = song.size_bytes;
+                songs_changed = true;
+            }
+            // 编码与音质参数同理（文件可能被换成另一种编码的版本）
+            if song.codec.is_some()
+                && (existing.codec != song.codec
+                    || existing.lossless != song.lossless
+                    || existing.bitrate_kbps != song.bitrate_kbps
+                    || existing.sample_rate != song.sample_rate)
+            {
+                existing.codec = song.codec.clone();
+                existing.lossless = song.lossless;
+                existing.bitrate_kbps = song.bitrate_kbps;
+                existing.sample_rate = song.sample_rate;
+                songs_changed = true;
+            }
+            if existing.content_type.is_music() && !song.content_type.is_music() {
+                existing.content_type = song.content_type;
+                songs_changed = true;
+            }
         }
 
         let artists_changed = merge_artists_in_memory(
@@ -926,7 +2234,7 @@ fn merge_or_init_song_in_memory(
                 &song.artist_ids,
                 &song.source_ids,
                 &existing_id,
-                song.year,
+                song,
                 all_albums,
                 album_index,
             );
@@ -954,7 +2262,7 @@ fn merge_or_init_song_in_memory(
                 &song.artist_ids,
                 &song.source_ids,
                 &song.id,
-                song.year,
+                song,
                 all_albums,
                 album_index,
             );
@@ -1002,6 +2310,7 @@ fn merge_artists_in_memory(
                     id: artist_id.clone(),
                     name: artist_name.to_string(),
                     bio: None,
+                    genres: Vec::new(),
                     source_ids: artist_sids.clone(),
                 },
             );
@@ -1015,7 +2324,7 @@ fn merge_artists_in_memory(
 
 /// 在内存中合并或创建专辑，使用 (title, artist_id) 索引 O(1) 查找。返回是否有变化。
 ///
-/// `song_year` 来自扫描得到的 Song.year，会反向写入 album.year（若 album.year 为 None）。
+/// `song` 为扫描得到的歌曲，其 year / 发行日期会反向写入专辑（专辑对应字段为 None 时）。
 /// 这实现了"专辑年份从同名歌曲年份聚合"的需求。
 fn merge_album_in_memory(
     album_id: &str,
@@ -1023,7 +2332,7 @@ fn merge_album_in_memory(
     artist_ids: &[String],
     song_source_ids: &[SourceId],
     song_id: &str,
-    song_year: Option<u32>,
+    song: &Song,
     all_albums: &mut HashMap<String, Album>,
     album_index: &mut HashMap<(String, String), String>,
 ) -> bool {
@@ -1045,9 +2354,8 @@ fn merge_album_in_memory(
             album.song_ids.push(song_id.to_string());
             changed = true;
         }
-        // 反向填充 year：album 缺失 year 而扫描到的 song.year 存在时补齐
-        if album.year.is_none() && song_year.is_some() {
-            album.year = song_year;
+        // 反向填充 year / 日期 / 唱片编号：album 缺失而扫描到的歌曲有时补齐
+        if fill_album_release(album, song) {
             changed = true;
         }
     } else if let Some(aid) = album_index.get(&lookup_key).cloned() {
@@ -1061,8 +2369,7 @@ fn merge_album_in_memory(
                 album.song_ids.push(song_id.to_string());
                 changed = true;
             }
-            if album.year.is_none() && song_year.is_some() {
-                album.year = song_year;
+            if fill_album_release(album, song) {
                 changed = true;
             }
         }
@@ -1076,7 +2383,11 @@ fn merge_album_in_memory(
                 cover_url: None,
                 song_ids: vec![song_id.to_string()],
                 source_ids: album_sids,
-                year: song_year,
+                year: song.year,
+                release_date: song.release_date.clone(),
+                original_date: song.original_date.clone(),
+                catalog_number: song.catalog_number.clone(),
+                barcode: song.barcode.clone(),
             },
         );
         album_index.insert(lookup_key, album_id.to_string());
@@ -1086,6 +2397,32 @@ fn merge_album_in_memory(
     changed
 }
 
+/// 用歌曲的年份 / 日期 / 唱片编号 / 条形码补齐专辑缺失的对应字段，不覆盖已有值。返回是否有变化。
+fn fill_album_release(album: &mut Album, song: &Song) -> bool {
+    let mut changed = false;
+    if album.year.is_none() && song.year.is_some() {
+        album.year = song.year;
+        changed = true;
+    }
+    if album.release_date.is_none() && song.release_date.is_some() {
+        album.release_date = song.release_date.clone();
+        changed = true;
+    }
+    if album.original_date.is_none() && song.original_date.is_some() {
+        album.original_date = song.original_date.clone();
+        changed = true;
+    }
+    if album.catalog_number.is_none() && song.catalog_number.is_some() {
+        album.catalog_number = song.catalog_number.clone();
+        changed = true;
+    }
+    if album.barcode.is_none() && song.barcode.is_some() {
+        album.barcode = song.barcode.clone();
+        changed = true;
+    }
+    changed
+}
+
 /// 构建歌曲去重索引：(title_lower, sorted_artist_names_lower) → song_id。
 fn build_song_index(all_songs: &HashMap<String, Song>) -> HashMap<(String, Vec<String>), String> {
     let mut index = HashMap::with_capacity(all_songs.len());
@@ -0,0 +1,98 @@
+//! 多语言显示 — 按用户的显示语言偏好替换歌曲标题 / 艺人名。
+//!
+//! 库内始终保存标签中的原始标题；仅在查询命令序列化前调用
+//! [`localize_song`] 生成展示副本，避免本地化后的名称被写回库中。
+
+use super::models::{LocalizedText, Song};
+
+/// ConfigStore 中存放显示语言偏好的键。
+pub const DISPLAY_LANGUAGE_CONFIG_KEY: &str = "display_language";
+
+/// 替换后原始文本使用的语言标记。
+const ORIGINAL_LANG: &str = "original";
+
+/// 语言标记是否满足偏好。
+///
+/// - 完全相同（大小写不敏感）
+/// - 偏好只有主标签时匹配同主标签的任意变体（`ja` 匹配 `ja-Latn`）
+/// - 未知语言的罗马音（`und-Latn`）匹配任意 `*-Latn` 偏好
+fn lang_matches(tag: &str, pref: &str, loose: bool) -> bool {
+    if tag.eq_ignore_ascii_case(pref) {
+        return true;
+    }
+    if !loose {
+        return false;
+    }
+    let (tag_primary, tag_rest) = tag.split_once('-').unwrap_or((tag, ""));
+    let (pref_primary, pref_rest) = pref.split_once('-').unwrap_or((pref, ""));
+    if pref_rest.is_empty() && tag_primary.eq_ignore_ascii_case(pref_primary) {
+        return true;
+    }
+    tag_primary == "und" && !tag_rest.is_empty() && tag_rest.eq_ignore_ascii_case(pref_rest)
+}
+
+/// 在备用文本中挑选最符合偏好的一条（精确匹配优先）。
+pub fn pick<'a>(alts: &'a [LocalizedText], pref: &str) -> Option<&'a LocalizedText> {
+    alts.iter()
+        .find(|t| lang_matches(&t.lang, pref, false))
+        .or_else(|| alts.iter().find(|t| lang_matches(&t.lang, pref, true)))
+}
+
+/// 按偏好语言替换歌曲的标题与艺人名，原文以 `original` 语言保留在备用列表中。
+pub fn localize_song(song: &mut Song, pref: &str) {
+    if let Some(alt) = pick(&song.alt_titles, pref).cloned() {
+        let original = std::mem::replace(&mut song.title, alt.text);
+        song.alt_titles.retain(|t| t.lang != alt.lang);
+        song.alt_titles.push(LocalizedText {
+            lang: ORIGINAL_LANG.to_string(),
+            text: original,
+        });
+    }
+
+    let Some(lang) = pick(&song.alt_artist_names, pref).map(|t| t.lang.clone()) else {
+        return;
+    };
+    let names: Vec<String> = song
+        .alt_artist_names
+        .iter()
+        .filter(|t| t.lang == lang)
+        .map(|t| t.text.clone())
+        .collect();
+    if names.len() != song.artist_names.len() {
+        return;
+    }
+    let originals = std::mem::replace(&mut song.artist_names, names);
+    song.alt_artist_names.retain(|t| t.lang != lang);
+    song.alt_artist_names
+        .extend(originals.into_iter().map(|text| LocalizedText {
+            lang: ORIGINAL_LANG.to_string(),
+            text,
+        }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lang: &str, text: &str) -> LocalizedText {
+        LocalizedText {
+            lang: lang.into(),
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn test_pick_prefers_exact_match() {
+        let alts = vec![text("ja-Latn", "Yoru ni Kakeru"), text("en", "Racing into the Night")];
+        assert_eq!(pick(&alts, "en").unwrap().text, "Racing into the Night");
+        assert_eq!(pick(&alts, "ja").unwrap().lang, "ja-Latn");
+        assert!(pick(&alts, "zh").is_none());
+    }
+
+    #[test]
+    fn test_romanized_matches_latn_preference() {
+        let alts = vec![text("und-Latn", "Yoru ni Kakeru")];
+        assert!(pick(&alts, "ja-Latn").is_some());
+        assert!(pick(&alts, "ja").is_none());
+    }
+}
@@ -0,0 +1,215 @@
+//! M3U / M3U8 歌单 — 与其他播放器互通的纯文本格式。
+//!
+//! 导出为扩展 M3U：`#EXTM3U` 文件头、`#PLAYLIST:` 歌单名，每首一行 `#EXTINF:时长,艺人 - 标题`
+//! 加一行绝对路径。没有本地文件的曲目（P2P 等远程来源）其他播放器无法打开，导出时略去。
+//! 两种扩展名都按 UTF-8 写出。
+//!
+//! 导入时每个条目转成一条 [`TrackHint`]：路径（相对路径按歌单文件所在目录解析，`file://` URL 解码为路径）
+//! 加上 `#EXTINF` 中的标题 / 艺人 / 时长，之后交给 [`Matcher`](super::playlist_export::Matcher)
+//! 按路径、元数据依次匹配，换了机器或移动过音乐文件夹也能按元数据找回。
+//! `.m3u` 常见系统代码页编码，不是合法 UTF-8 时按 Latin-1 读取。
+
+use super::playlist_export::{ExportedPlaylist, TrackHint, FORMAT, FORMAT_VERSION};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// 路径的扩展名是否为 `.m3u` / `.m3u8`（不区分大小写）。
+pub fn is_m3u_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("m3u") || ext.eq_ignore_ascii_case("m3u8"))
+}
+
+/// 写出扩展 M3U 文本。
+pub fn write(exported: &ExportedPlaylist) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    let _ = writeln!(out, "#PLAYLIST:{}", single_line(&exported.name));
+    for track in &exported.tracks {
+        let Some(path) = &track.path else {
+            continue;
+        };
+        let duration = track.duration_secs.map_or(-1, |secs| secs as i64);
+        let display = match track.artists.is_empty() {
+            true => single_line(&track.title),
+            false => format!("{} - {}", single_line(&track.artists.join(", ")), single_line(&track.title)),
+        };
+        let _ = writeln!(out, "#EXTINF:{},{}", duration, display);
+        let _ = writeln!(out, "{}", path);
+    }
+    out
+}
+
+/// 解析 M3U / M3U8 内容。`name` 为文件中没有 `#PLAYLIST:` 时使用的歌单名，
+/// `base_dir` 为歌单文件所在目录，用于解析相对路径。
+pub fn parse(bytes: &[u8], name: &str, base_dir: Option<&Path>) -> ExportedPlaylist {
+    let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    };
+    let mut exported = ExportedPlaylist {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        name: name.to_string(),
+        tracks: Vec::new(),
+    };
+    // 上一行 #EXTINF 的时长与显示名，由紧随其后的条目使用
+    let mut pending: Option<(Option<u64>, String)> = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(playlist) = line.strip_prefix("#PLAYLIST:") {
+            if !playlist.trim().is_empty() {
+                exported.name = playlist.trim().to_string();
+            }
+        } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, display) = info.split_once(',').unwrap_or((info, ""));
+            // 时长后可能跟 `key="value"` 属性
+            let duration = duration.split_whitespace().next().and_then(|d| d.parse::<f64>().ok());
+            pending = Some((duration.filter(|d| *d >= 0.0).map(|d| d.round() as u64), display.trim().to_string()));
+        } else if !line.starts_with('#') {
+            exported.tracks.push(entry_hint(line, pending.take(), base_dir));
+        }
+    }
+    exported
+}
+
+/// 由一个条目和它的 `#EXTINF` 生成匹配线索。
+fn entry_hint(entry: &str, info: Option<(Option<u64>, String)>, base_dir: Option<&Path>) -> TrackHint {
+    let path = entry_path(entry, base_dir);
+    let (duration_secs, display) = info.unwrap_or_default();
+    let (artists, title) = match display.split_once(" - ") {
+        Some((artist, title)) => (vec![artist.trim().to_string()], title.trim().to_string()),
+        None => (Vec::new(), display),
+    };
+    let title = match title.is_empty() {
+        // 没有 #EXTINF 时用文件名作标题
+        true => Path::new(path.as_deref().unwrap_or(entry))
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| entry.to_string()),
+        false => title,
+    };
+    TrackHint {
+        title,
+        artists,
+        album: None,
+        duration_secs,
+        isrc: None,
+        content_hash: None,
+        path,
+    }
+}
+
+/// 条目对应的本地路径；网络 URL 返回 `None`。
+fn entry_path(entry: &str, base_dir: Option<&Path>) -> Option<String> {
+    if let Some(url) = entry.strip_prefix("file://") {
+        // file:///C:/x 与 file:///home/x 都去掉主机部分（本机为空）
+        let path = percent_decode(url.strip_prefix("localhost").unwrap_or(url));
+        let path = match path.as_bytes() {
+            [b'/', _, b':', ..] => path[1..].to_string(),
+            _ => path,
+        };
+        return Some(path);
+    }
+    if entry.contains("://") {
+        return None;
+    }
+    let path = Path::new(entry);
+    let is_absolute = path.is_absolute() || entry.starts_with('/') || entry.as_bytes().get(1) == Some(&b':');
+    match (is_absolute, base_dir) {
+        (false, Some(dir)) => Some(dir.join(entry).to_string_lossy().into_owned()),
+        _ => Some(entry.to_string()),
+    }
+}
+
+/// 解码 URL 中的 `%XX`；非法的转义原样保留。
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// M3U 是按行的格式，名称中的换行替换为空格。
+fn single_line(s: &str) -> String {
+    s.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_foreign_files() {
+        let exported = ExportedPlaylist {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            name: "Live\nSet".to_string(),
+            tracks: vec![
+                TrackHint {
+                    title: "Yellow".to_string(),
+                    artists: vec!["Coldplay".to_string()],
+                    album: Some("Parachutes".to_string()),
+                    duration_secs: Some(269),
+                    isrc: None,
+                    content_hash: None,
+                    path: Some("/music/Yellow.flac".to_string()),
+                },
+                // 远程曲目没有路径，不写出
+                TrackHint {
+                    title: "Remote".to_string(),
+                    artists: Vec::new(),
+                    album: None,
+                    duration_secs: None,
+                    isrc: None,
+                    content_hash: None,
+                    path: None,
+                },
+            ],
+        };
+        let text = write(&exported);
+        assert!(text.starts_with("#EXTM3U\n#PLAYLIST:Live Set\n"));
+        let parsed = parse(text.as_bytes(), "fallback", None);
+        assert_eq!(parsed.name, "Live Set");
+        assert_eq!(parsed.tracks.len(), 1);
+        assert_eq!(parsed.tracks[0].title, "Yellow");
+        assert_eq!(parsed.tracks[0].artists, vec!["Coldplay".to_string()]);
+        assert_eq!(parsed.tracks[0].duration_secs, Some(269));
+        assert_eq!(parsed.tracks[0].path.as_deref(), Some("/music/Yellow.flac"));
+
+        // 其他播放器写出的 Latin-1 .m3u（带多余的 BOM）：相对路径、file URL、网络流、没有 #EXTINF 的条目
+        let mut foreign = b"\xef\xbb\xbf#EXTM3U\r\n#EXTINF:180 tvg-id=\"x\",Caf\xe9 Tacvba - Eres\r\nsub/Eres.mp3\r\n".to_vec();
+        foreign.extend_from_slice(b"file:///home/a/My%20Song.flac\r\nhttp://radio.example/stream\r\n");
+        let parsed = parse(&foreign, "Road Trip", Some(Path::new("/lists")));
+        assert_eq!(parsed.name, "Road Trip");
+        let titles: Vec<&str> = parsed.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Eres", "My Song", "stream"]);
+        assert_eq!(parsed.tracks[0].artists, vec!["Café Tacvba".to_string()]);
+        assert_eq!(parsed.tracks[0].duration_secs, Some(180));
+        assert_eq!(
+            parsed.tracks[0].path.as_deref().map(Path::new),
+            Some(Path::new("/lists").join("sub/Eres.mp3").as_path())
+        );
+        assert_eq!(parsed.tracks[1].path.as_deref(), Some("/home/a/My Song.flac"));
+        assert_eq!(parsed.tracks[2].path, None);
+
+        assert!(is_m3u_path(Path::new("a/b.M3U8")));
+        assert!(!is_m3u_path(Path::new("a/b.json")));
+    }
+}
@@ -3,12 +3,30 @@
 //! # 模块架构
 //!
 //! ```text
-//! models.rs            ← Song, Artist, Album, Lyric 结构体定义
+//! models.rs            ← Song, Artist, Album, Lyric, LocalizedText 结构体定义
 //! songs.rs             ← 歌曲 CRUD + 搜索
 //! artists.rs           ← 艺术家 CRUD + 搜索
 //! albums.rs            ← 专辑 CRUD + 搜索
 //! lyrics.rs            ← 歌词 CRUD + 搜索
 //! relations.rs         ← 跨实体关系追溯（song→artist, artist→songs 等）
+//! localize.rs          ← 多语言显示（按显示语言偏好替换标题 / 艺人名）
+//! edits.rs             ← 用户编辑的元数据 + 重扫时与文件标签的冲突记录
+//! grouping.rs          ← 艺人列表分组方式（演出者 / 作曲者），作曲者条目由歌曲标签即时汇总
+//! stats.rs             ← 播放统计（评分、播放次数，可从文件标签导入）
+//! batch.rs             ← 多选批量操作的逐项结果汇总
+//! books.rs             ← 有声书识别、章节与收听进度
+//! history.rs           ← 变更历史（编辑 / 移除等破坏性操作的撤销日志）
+//! hidden.rs            ← 隐藏的歌曲 / 专辑（从所有查询中排除，但保留文件与索引）
+//! versions.rs          ← 同曲多版本（现场 / 混音 / 伴奏 / MV 等）的自动匹配与手动关联
+//! aggregates.rs        ← 专辑 / 艺人的曲目数、总时长、总大小（随歌曲增删改增量重算）
+//! diff.rs              ← 批量写操作前后的歌曲 / 专辑差异（增量刷新前端视图）
+//! quality.rs           ← 歌曲筛选（无损 / 码率 / 编码 / 采样率 / 演唱语言）及其索引
+//! language.rs          ← 演唱语言标签的规范化与按歌词推断
+//! playlists.rs         ← 歌单与可嵌套的歌单文件夹（手动排序）
+//! smart_playlists.rs   ← 智能歌单（按流派 / 年份 / 时长等规则对库求值）
+//! playlist_export.rs   ← 歌单导出格式（附匹配线索）与导入时的曲目匹配
+//! m3u.rs               ← M3U / M3U8 歌单读写（与其他播放器互通）
+//! store.rs             ← SQLite 索引库（歌曲 / 艺人 / 专辑的索引表，随库增量同步）
 //! library.rs           ← MusicLibrary 统一入口（持有 PersistentStore，委托各子模块）
 //! ```
 //!
@@ -24,11 +42,29 @@
 //! lib.save()?;
 //! ```
 
+pub mod aggregates;
 pub mod albums;
 pub mod artists;
+pub mod batch;
+pub mod books;
+pub mod diff;
+pub mod edits;
+pub mod grouping;
+pub mod hidden;
+pub mod history;
+pub mod language;
 pub mod library;
+pub mod localize;
 pub mod lyrics;
+pub mod m3u;
 pub mod models;
+pub mod playlist_export;
+pub mod playlists;
+pub mod quality;
 pub mod relations;
 pub mod search;
+pub mod smart_playlists;
 pub mod songs;
+pub mod stats;
+pub mod store;
+pub mod versions;
@@ -1,8 +1,10 @@
 use crate::module::music_source::types::SourceId;
+use crate::module::playback::ContentType;
 use serde::{Deserialize, Serialize};
 
 /// 歌曲。
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct Song {
     /// 库内统一 ID（UUID）
     pub id: String,
@@ -13,6 +15,7 @@ pub struct Song {
     /// 专辑名称，与 `album_id` 对应
     pub album_title: Option<String>,
     /// 时长（秒）
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
     pub duration: Option<u64>,
     /// 关联的艺术家 ID 列表
     pub artist_ids: Vec<String>,
@@ -25,10 +28,97 @@ pub struct Song {
     /// 发行年份（来自音频标签 Year/Date）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub year: Option<u32>,
+    /// 发行日期（`YYYY` / `YYYY-MM` / `YYYY-MM-DD`，精度取决于标签；来自 TDRC、Vorbis DATE 等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+    /// 原始发行日期（再版 / 重制版的首发日期；来自 TDOR、Vorbis ORIGINALDATE 等），格式同上
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_date: Option<String>,
+    /// 其他语言的标题（如罗马音、译名）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alt_titles: Vec<LocalizedText>,
+    /// 其他语言的艺人名；同一语言的条目按顺序与 `artist_names` 一一对应
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alt_artist_names: Vec<LocalizedText>,
+    /// 流派（来自音频标签 Genre，多值时依次排列）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub genres: Vec<String>,
+    /// 作曲者（来自音频标签 Composer，多值时依次排列）；古典模式下按此分组，见 [`grouping`](super::grouping)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub composers: Vec<String>,
+    /// 音轨号（来自 TRCK / TRACKNUMBER / trkn，`3/12` 只取 3）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_number: Option<u32>,
+    /// 碟号（来自 TPOS / DISCNUMBER / disk）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disc_number: Option<u32>,
+    /// ReplayGain 音轨增益（dB，来自音频标签）；用户覆盖值见 [`gain`](crate::module::playback::gain)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay_gain_db: Option<f32>,
+    /// 国际标准录音代码（来自音频标签，已去掉连字符并转为大写）；导入歌单时用于跨设备匹配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+    /// 唱片编号（来自音频标签 CATALOGNUMBER），同一发行版的歌曲相同，入库时汇总到专辑
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catalog_number: Option<String>,
+    /// 发行版条形码（UPC / EAN，纯数字）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub barcode: Option<String>,
+    /// 演唱语言（BCP 47 风格，如 `ja`、`zh-Hant`；`zxx` 表示纯音乐），来自音频标签，没有标签时从歌词推断，
+    /// 见 [`language`](super::language)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// `language` 是否由歌词推断而来（而非标签）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub language_inferred: bool,
+    /// 文件大小（字节，本地文件入库时记录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(type = "number | null"))]
+    pub size_bytes: Option<u64>,
+    /// 编码名称（如 `flac`、`aac`、`alac`，扫描时记录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codec: Option<String>,
+    /// 是否为无损编码（FLAC / ALAC / WAV 等），供「只看无损」筛选
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossless: bool,
+    /// 平均码率（kbps）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate_kbps: Option<u32>,
+    /// 采样率（Hz）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+    /// 内容类型；有声书的识别规则见 [`books::classify`](super::books::classify)
+    #[serde(default, skip_serializing_if = "ContentType::is_music")]
+    pub content_type: ContentType,
+    /// 音频内容哈希（不含标签），开启后由哈希任务写入，见 [`content_hash`](crate::module::analysis::content_hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<ContentHash>,
+}
+
+/// 音频内容哈希及计算时的文件修改时间。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct ContentHash {
+    /// 已编码音频数据包的 xxh3-128（32 位十六进制）
+    pub xxh3: String,
+    /// 计算时文件的修改时间（Unix 秒）
+    #[cfg_attr(feature = "ts", ts(type = "number"))]
+    pub mtime: u64,
+}
+
+/// 带语言标记的文本（用于多语言标题 / 艺人名）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct LocalizedText {
+    /// 语言标记（BCP 47 风格，如 `ja`、`ja-Latn`、`en`；`und` 表示未知语言）
+    pub lang: String,
+    /// 文本内容
+    pub text: String,
 }
 
 /// 艺术家。
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct Artist {
     /// 库内统一 ID（UUID）
     pub id: String,
@@ -36,12 +126,16 @@ pub struct Artist {
     pub name: String,
     /// 简介
     pub bio: Option<String>,
+    /// 流派
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub genres: Vec<String>,
     /// 来源引用
     pub source_ids: Vec<SourceId>,
 }
 
 /// 专辑。
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct Album {
     /// 库内统一 ID（UUID）
     pub id: String,
@@ -58,10 +152,35 @@ pub struct Album {
     /// 专辑发行年份（从同名歌曲标签聚合得到）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub year: Option<u32>,
+    /// 发行日期，格式同 [`Song::release_date`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+    /// 原始发行日期，格式同 [`Song::original_date`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_date: Option<String>,
+    /// 唱片编号（从歌曲标签汇总，或由元数据提供方补全）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catalog_number: Option<String>,
+    /// 条形码（UPC / EAN），与外部服务对账时可精确定位发行版
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub barcode: Option<String>,
+}
+
+impl Album {
+    /// 「按年份」排序用的日期：优先原始发行日期，使重制版排在首发年代而非再版年代。
+    ///
+    /// 部分日期按字典序比较即为时间先后（`"1999"` < `"1999-03"` < `"2001"`）；无任何日期时返回 `None`。
+    pub fn chronology_date(&self) -> Option<String> {
+        self.original_date
+            .clone()
+            .or_else(|| self.release_date.clone())
+            .or_else(|| self.year.map(|y| format!("{:04}", y)))
+    }
 }
 
 /// 歌词。
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct Lyric {
     /// 库内统一 ID（UUID）
     pub id: String,
@@ -0,0 +1,335 @@
+//! 歌单导出 / 导入 — 可分享的 JSON 格式，导入时按匹配线索在本机库中找回曲目。
+//!
+//! 导出文件不依赖库内 ID，每首曲目附带一组线索；导入时按可信度依次尝试：
+//!
+//! | 顺序 | 线索 | 说明 |
+//! |------|------|------|
+//! | 1 | `content_hash` | 音频内容哈希（不含标签），同一文件改名、改标签后仍能命中 |
+//! | 2 | `isrc` | 标签中的 ISRC，同一录音的不同文件也能命中 |
+//! | 3 | `path` | 导出时的本地路径，只在同一台机器上有用 |
+//! | 4 | 标题 / 艺人 / 时长 | 标题主体与版本类型相同（见 [`versions::parse_title`]）、艺人有交集、时长相差不超过 [`DURATION_TOLERANCE_SECS`] |
+//!
+//! 都没有命中的曲目原样列在结果的 `unmatched` 中，供前端提示。
+//!
+//! [`read_file`] / [`write_file`] 按扩展名在本格式（JSON）与 [`m3u`](super::m3u)（`.m3u` / `.m3u8`）之间选择；
+//! M3U 只带路径和 `#EXTINF` 中的标题 / 艺人 / 时长，导入时按后两条线索匹配。
+
+use super::m3u;
+use super::models::Song;
+use super::playlists::Playlist;
+use super::versions::{self, VersionKind};
+use crate::module::music_localSource::scanner::normalize_isrc;
+use crate::module::music_source::types::SourceType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// 导出文件的格式标识。
+pub const FORMAT: &str = "chordial-playlist";
+pub const FORMAT_VERSION: u32 = 1;
+
+/// 按元数据匹配时允许的时长误差（秒）；不同编码 / 不同来源的同一首歌时长常差一两秒。
+pub const DURATION_TOLERANCE_SECS: u64 = 3;
+
+/// 导出的歌单。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedPlaylist {
+    pub format: String,
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub tracks: Vec<TrackHint>,
+}
+
+/// 一首曲目的匹配线索。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackHint {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artists: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+    /// 音频内容哈希（xxh3）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// 导出时的本地文件路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// 命中所用的线索，按可信度从高到低。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Hash,
+    Isrc,
+    Path,
+    Metadata,
+}
+
+/// 一首命中的曲目。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrackMatch {
+    /// 在导出文件 `tracks` 中的下标
+    pub index: usize,
+    pub song_id: String,
+    pub matched_by: MatchKind,
+}
+
+/// [`MusicLibrary::import_playlist_with_matching`](super::library::MusicLibrary::import_playlist_with_matching) 的结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaylistImport {
+    /// 新建的歌单（只含命中的曲目，顺序与导出文件一致）
+    pub playlist: Playlist,
+    pub matched: Vec<TrackMatch>,
+    pub unmatched: Vec<TrackHint>,
+}
+
+/// 解析导出文件；格式标识不符或版本高于当前支持的版本时报错。
+pub fn parse(json: &str) -> Result<ExportedPlaylist, String> {
+    let exported: ExportedPlaylist =
+        serde_json::from_str(json).map_err(|e| format!("解析歌单文件失败: {}", e))?;
+    if exported.format != FORMAT {
+        return Err("不是 Chordial 歌单文件".to_string());
+    }
+    if exported.version > FORMAT_VERSION {
+        return Err(format!("歌单文件版本 {} 过新，请升级应用", exported.version));
+    }
+    Ok(exported)
+}
+
+/// 读取歌单文件：`.m3u` / `.m3u8` 按 M3U 解析（歌单名缺省为文件名），其余按 JSON 导出格式解析。
+pub fn read_file(path: &Path) -> Result<ExportedPlaylist, String> {
+    let bytes = fs::read(path).map_err(|e| format!("读取歌单文件失败: {}", e))?;
+    if m3u::is_m3u_path(path) {
+        let name = path.file_stem().map_or_else(String::new, |s| s.to_string_lossy().into_owned());
+        return Ok(m3u::parse(&bytes, &name, path.parent()));
+    }
+    parse(&String::from_utf8_lossy(&bytes))
+}
+
+/// 写出歌单文件，格式按扩展名选择（同 [`read_file`]）。先写临时文件再改名，中途失败不会留下半个文件。
+pub fn write_file(exported: &ExportedPlaylist, path: &Path) -> Result<(), String> {
+    let text = if m3u::is_m3u_path(path) {
+        m3u::write(exported)
+    } else {
+        serde_json::to_string_pretty(exported).map_err(|e| format!("序列化失败: {}", e))?
+    };
+    let tmp = path.with_extension("chordial-tmp");
+    fs::write(&tmp, text).map_err(|e| format!("写入歌单文件失败: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("写入歌单文件失败: {}", e)
+    })
+}
+
+/// 歌曲的本地文件路径（本地来源的 `entity_id`）。
+fn local_path(song: &Song) -> Option<&str> {
+    song.source_ids
+        .iter()
+        .find(|sid| sid.source_type == SourceType::Local)
+        .map(|sid| sid.entity_id.as_str())
+}
+
+/// 由歌曲生成匹配线索。
+pub fn hint(song: &Song) -> TrackHint {
+    TrackHint {
+        title: song.title.clone(),
+        artists: song.artist_names.clone(),
+        album: song.album_title.clone(),
+        duration_secs: song.duration,
+        isrc: song.isrc.clone(),
+        content_hash: song.content_hash.as_ref().map(|h| h.xxh3.clone()),
+        path: local_path(song).map(str::to_string),
+    }
+}
+
+/// 导出歌单；`songs` 按歌单顺序，已不在库中的曲目由调用方略去。
+pub fn export(playlist: &Playlist, songs: &[Song]) -> ExportedPlaylist {
+    ExportedPlaylist {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        name: playlist.name.clone(),
+        tracks: songs.iter().map(hint).collect(),
+    }
+}
+
+/// 本机库的匹配索引，导入一个歌单时构建一次。
+pub struct Matcher<'a> {
+    by_hash: HashMap<&'a str, &'a Song>,
+    by_isrc: HashMap<&'a str, &'a Song>,
+    by_path: HashMap<&'a str, &'a Song>,
+    by_title: HashMap<(String, VersionKind), Vec<&'a Song>>,
+}
+
+impl<'a> Matcher<'a> {
+    pub fn new(songs: &'a [Song]) -> Self {
+        let mut matcher = Self {
+            by_hash: HashMap::new(),
+            by_isrc: HashMap::new(),
+            by_path: HashMap::new(),
+            by_title: HashMap::new(),
+        };
+        for song in songs {
+            if let Some(hash) = &song.content_hash {
+                matcher.by_hash.entry(hash.xxh3.as_str()).or_insert(song);
+            }
+            if let Some(isrc) = &song.isrc {
+                matcher.by_isrc.entry(isrc.as_str()).or_insert(song);
+            }
+            if let Some(path) = local_path(song) {
+                matcher.by_path.entry(path).or_insert(song);
+            }
+            matcher
+                .by_title
+                .entry(versions::parse_title(&song.title))
+                .or_default()
+                .push(song);
+        }
+        matcher
+    }
+
+    /// 按线索查找本机歌曲。
+    pub fn find(&self, hint: &TrackHint) -> Option<(&'a Song, MatchKind)> {
+        if let Some(song) = hint.content_hash.as_deref().and_then(|h| self.by_hash.get(h)) {
+            return Some((song, MatchKind::Hash));
+        }
+        let isrc = hint.isrc.as_deref().and_then(normalize_isrc);
+        if let Some(song) = isrc.as_deref().and_then(|code| self.by_isrc.get(code)) {
+            return Some((song, MatchKind::Isrc));
+        }
+        if let Some(song) = hint.path.as_deref().and_then(|p| self.by_path.get(p)) {
+            return Some((song, MatchKind::Path));
+        }
+        self.find_by_metadata(hint).map(|song| (song, MatchKind::Metadata))
+    }
+
+    /// 标题主体与版本类型相同、艺人有交集（任一方没有艺人时不比较）、时长在误差内（任一方未知时不比较）。
+    /// 多个候选时取时长最接近的，其次专辑名相同的。
+    fn find_by_metadata(&self, hint: &TrackHint) -> Option<&'a Song> {
+        let candidates = self.by_title.get(&versions::parse_title(&hint.title))?;
+        let artists: Vec<String> = hint.artists.iter().map(|a| a.to_lowercase()).collect();
+        candidates
+            .iter()
+            .copied()
+            .filter(|song| {
+                artists.is_empty()
+                    || song.artist_names.is_empty()
+                    || song
+                        .artist_names
+                        .iter()
+                        .any(|name| artists.contains(&name.to_lowercase()))
+            })
+            .filter_map(|song| {
+                let diff = match (hint.duration_secs, song.duration) {
+                    (Some(a), Some(b)) => a.abs_diff(b),
+                    _ => DURATION_TOLERANCE_SECS,
+                };
+                (diff <= DURATION_TOLERANCE_SECS).then_some((song, diff))
+            })
+            .min_by_key(|(song, diff)| {
+                let same_album = hint.album.is_some()
+                    && hint.album.as_deref().map(str::to_lowercase)
+                        == song.album_title.as_deref().map(str::to_lowercase);
+                (*diff, !same_album)
+            })
+            .map(|(song, _)| song)
+    }
+
+    /// 逐首匹配导出的曲目，返回命中与未命中的曲目。
+    pub fn resolve(&self, exported: &ExportedPlaylist) -> (Vec<TrackMatch>, Vec<TrackHint>) {
+        let mut matched = Vec::new();
+        let mut unmatched = Vec::new();
+        for (index, hint) in exported.tracks.iter().enumerate() {
+            match self.find(hint) {
+                Some((song, matched_by)) => matched.push(TrackMatch {
+                    index,
+                    song_id: song.id.clone(),
+                    matched_by,
+                }),
+                None => unmatched.push(hint.clone()),
+            }
+        }
+        (matched, unmatched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn song(id: &str, title: &str, artist: &str, duration: u64, extra: serde_json::Value) -> Song {
+        let mut value = json!({
+            "id": id,
+            "title": title,
+            "artist_names": [artist],
+            "duration": duration,
+            "artist_ids": [],
+            "source_ids": [{
+                "source_name": "local",
+                "source_type": "Local",
+                "entity_type": "Song",
+                "entity_id": format!("/home/b/Music/{}.flac", id),
+            }],
+        });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_tracks_on_another_machine() {
+        let library = vec![
+            song("a", "Yellow", "Coldplay", 269, json!({ "content_hash": { "xxh3": "h1", "mtime": 0 } })),
+            song("b", "Clocks", "Coldplay", 307, json!({ "isrc": "GBAYE0200771" })),
+            song("c", "Fix You", "Coldplay", 295, json!({})),
+            song("d", "Fix You (Live)", "Coldplay", 330, json!({})),
+        ];
+        let matcher = Matcher::new(&library);
+
+        let hint = |title: &str, duration: u64| TrackHint {
+            title: title.to_string(),
+            artists: vec!["coldplay".to_string()],
+            album: None,
+            duration_secs: Some(duration),
+            isrc: None,
+            content_hash: None,
+            path: Some(format!("C:\\Users\\a\\{}.mp3", title)),
+        };
+        let exported = ExportedPlaylist {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            name: "Mix".to_string(),
+            tracks: vec![
+                TrackHint { content_hash: Some("h1".into()), ..hint("Yellow (Remastered)", 0) },
+                TrackHint { isrc: Some("gb-aye-02-00771".into()), ..hint("Clocks", 0) },
+                hint("Fix You", 296),
+                hint("Fix You (Live)", 331),
+                hint("Fix You", 400),
+                hint("Viva la Vida", 242),
+            ],
+        };
+        let json = serde_json::to_string(&exported).unwrap();
+        let (matched, unmatched) = matcher.resolve(&parse(&json).unwrap());
+
+        let found: Vec<(usize, &str, MatchKind)> =
+            matched.iter().map(|m| (m.index, m.song_id.as_str(), m.matched_by)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, "a", MatchKind::Hash),
+                (1, "b", MatchKind::Isrc),
+                (2, "c", MatchKind::Metadata),
+                (3, "d", MatchKind::Metadata),
+            ]
+        );
+        // 时长差太多、库中没有的曲目不强行匹配
+        assert_eq!(unmatched.len(), 2);
+        assert!(parse(r#"{"format":"m3u","version":1,"name":"x"}"#).is_err());
+    }
+}
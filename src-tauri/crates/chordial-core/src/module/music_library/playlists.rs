@@ -0,0 +1,366 @@
+//! 歌单与歌单文件夹 — 可嵌套的文件夹层级，文件夹内的顺序由用户手动排列。
+//!
+//! 整棵树作为一个文档保存在库的 `playlists` 键下：
+//!
+//! ```text
+//! PlaylistTree
+//! ├── root: [id, …]             ← 根层级的顺序
+//! ├── folders: id → { name, children: [id, …] }
+//! └── playlists: id → { name, song_ids }
+//! ```
+//!
+//! 文件夹与歌单共用一个 ID 空间，`children` 中的 ID 可以指向任一种；每个 ID 只出现在一个位置。
+//! 删除文件夹时其内容上移到原位置，不会连带删除歌单。
+//!
+//! 带 `rules` 的歌单为智能歌单（见 [`smart_playlists`](super::smart_playlists)），其 `song_ids` 始终为空，
+//! 曲目由规则对库求值得出。
+
+use super::smart_playlists::SmartRules;
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub const KEY: &str = "playlists";
+
+/// 一个歌单。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Playlist {
+    pub id: String,
+    pub name: String,
+    /// 曲目顺序即播放顺序；允许重复
+    #[serde(default)]
+    pub song_ids: Vec<String>,
+    /// 创建时间（Unix 秒）
+    pub created_at: u64,
+    /// 智能歌单的规则；普通歌单为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<SmartRules>,
+}
+
+impl Playlist {
+    pub fn is_smart(&self) -> bool {
+        self.rules.is_some()
+    }
+}
+
+/// 一个歌单文件夹。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistFolder {
+    pub id: String,
+    pub name: String,
+    /// 子文件夹与歌单的 ID，按显示顺序
+    #[serde(default)]
+    pub children: Vec<String>,
+    pub created_at: u64,
+}
+
+/// 持久化的歌单树。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaylistTree {
+    pub root: Vec<String>,
+    pub folders: HashMap<String, PlaylistFolder>,
+    pub playlists: HashMap<String, Playlist>,
+}
+
+/// 返回给前端的树节点。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlaylistNode {
+    Folder {
+        id: String,
+        name: String,
+        children: Vec<PlaylistNode>,
+    },
+    Playlist {
+        id: String,
+        name: String,
+        song_count: usize,
+        smart: bool,
+    },
+}
+
+pub fn load(store: &PersistentStore) -> PlaylistTree {
+    store.get(KEY).unwrap_or_default()
+}
+
+pub fn save(store: &PersistentStore, tree: &PlaylistTree) -> Result<(), String> {
+    store.set(KEY, tree)
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn clean_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("名称不能为空".to_string());
+    }
+    Ok(name.to_string())
+}
+
+impl PlaylistTree {
+    /// 前端显示用的嵌套树；智能歌单的曲目数由 `smart_count` 求出。
+    pub fn nodes(&self, smart_count: &dyn Fn(&Playlist) -> usize) -> Vec<PlaylistNode> {
+        self.nodes_of(&self.root, smart_count)
+    }
+
+    fn nodes_of(&self, ids: &[String], smart_count: &dyn Fn(&Playlist) -> usize) -> Vec<PlaylistNode> {
+        ids.iter()
+            .filter_map(|id| {
+                if let Some(folder) = self.folders.get(id) {
+                    Some(PlaylistNode::Folder {
+                        id: folder.id.clone(),
+                        name: folder.name.clone(),
+                        children: self.nodes_of(&folder.children, smart_count),
+                    })
+                } else {
+                    self.playlists.get(id).map(|p| PlaylistNode::Playlist {
+                        id: p.id.clone(),
+                        name: p.name.clone(),
+                        song_count: if p.is_smart() { smart_count(p) } else { p.song_ids.len() },
+                        smart: p.is_smart(),
+                    })
+                }
+            })
+            .collect()
+    }
+
+    fn contains(&self, id: &str) -> bool {
+        self.folders.contains_key(id) || self.playlists.contains_key(id)
+    }
+
+    /// 文件夹（`None` 为根层级）的子项列表。
+    fn children_mut(&mut self, folder: Option<&str>) -> Result<&mut Vec<String>, String> {
+        match folder {
+            None => Ok(&mut self.root),
+            Some(id) => self
+                .folders
+                .get_mut(id)
+                .map(|f| &mut f.children)
+                .ok_or_else(|| format!("歌单文件夹不存在: {}", id)),
+        }
+    }
+
+    /// 节点所在的文件夹（`None` 表示根层级）与下标；节点不在树中时返回 `None`。
+    fn position_of(&self, id: &str) -> Option<(Option<String>, usize)> {
+        if let Some(index) = self.root.iter().position(|x| x == id) {
+            return Some((None, index));
+        }
+        self.folders.values().find_map(|f| {
+            f.children
+                .iter()
+                .position(|x| x == id)
+                .map(|index| (Some(f.id.clone()), index))
+        })
+    }
+
+    /// `ancestor` 是否为 `id` 本身或其上级文件夹。
+    fn is_within(&self, id: &str, ancestor: &str) -> bool {
+        let mut current = Some(id.to_string());
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+            current = self.position_of(&node).and_then(|(parent, _)| parent);
+        }
+        false
+    }
+
+    fn insert(&mut self, id: String, folder: Option<&str>, index: Option<usize>) -> Result<(), String> {
+        let children = self.children_mut(folder)?;
+        let index = index.unwrap_or(children.len()).min(children.len());
+        children.insert(index, id);
+        Ok(())
+    }
+
+    pub fn create_folder(&mut self, name: &str, parent: Option<&str>) -> Result<PlaylistFolder, String> {
+        let folder = PlaylistFolder {
+            id: Uuid::new_v4().to_string(),
+            name: clean_name(name)?,
+            children: Vec::new(),
+            created_at: now(),
+        };
+        self.insert(folder.id.clone(), parent, None)?;
+        self.folders.insert(folder.id.clone(), folder.clone());
+        Ok(folder)
+    }
+
+    pub fn create_playlist(&mut self, name: &str, folder: Option<&str>) -> Result<Playlist, String> {
+        self.create(name, folder, None)
+    }
+
+    /// 新建智能歌单；规则不适用于字段时拒绝。
+    pub fn create_smart_playlist(
+        &mut self,
+        name: &str,
+        folder: Option<&str>,
+        rules: SmartRules,
+    ) -> Result<Playlist, String> {
+        rules.validate()?;
+        self.create(name, folder, Some(rules))
+    }
+
+    fn create(&mut self, name: &str, folder: Option<&str>, rules: Option<SmartRules>) -> Result<Playlist, String> {
+        let playlist = Playlist {
+            id: Uuid::new_v4().to_string(),
+            name: clean_name(name)?,
+            song_ids: Vec::new(),
+            created_at: now(),
+            rules,
+        };
+        self.insert(playlist.id.clone(), folder, None)?;
+        self.playlists.insert(playlist.id.clone(), playlist.clone());
+        Ok(playlist)
+    }
+
+    /// 重命名歌单或文件夹。
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<(), String> {
+        let name = clean_name(name)?;
+        if let Some(folder) = self.folders.get_mut(id) {
+            folder.name = name;
+        } else if let Some(playlist) = self.playlists.get_mut(id) {
+            playlist.name = name;
+        } else {
+            return Err(format!("歌单不存在: {}", id));
+        }
+        Ok(())
+    }
+
+    /// 删除歌单，或删除文件夹并把其内容按原顺序上移到文件夹所在位置。
+    pub fn remove(&mut self, id: &str) -> Result<(), String> {
+        let (parent, index) = self.position_of(id).ok_or_else(|| format!("歌单不存在: {}", id))?;
+        let moved_up = self.folders.remove(id).map(|f| f.children).unwrap_or_default();
+        self.playlists.remove(id);
+        let siblings = self.children_mut(parent.as_deref())?;
+        siblings.splice(index..=index, moved_up);
+        Ok(())
+    }
+
+    /// 把歌单或文件夹移到 `folder`（`None` 为根层级）的第 `index` 位，`index` 缺省时放到末尾。
+    ///
+    /// 同一文件夹内移动即为调整顺序；`index` 按移出后的列表计算。文件夹不能移入自身或其子文件夹。
+    pub fn move_node(&mut self, id: &str, folder: Option<&str>, index: Option<usize>) -> Result<(), String> {
+        if !self.contains(id) {
+            return Err(format!("歌单不存在: {}", id));
+        }
+        if let Some(target) = folder {
+            if !self.folders.contains_key(target) {
+                return Err(format!("歌单文件夹不存在: {}", target));
+            }
+            if self.is_within(target, id) {
+                return Err("不能把文件夹移入其自身或子文件夹".to_string());
+            }
+        }
+        if let Some((parent, from)) = self.position_of(id) {
+            self.children_mut(parent.as_deref())?.remove(from);
+        }
+        self.insert(id.to_string(), folder, index)
+    }
+
+    /// 按给定顺序重排文件夹（`None` 为根层级）的子项；`ids` 须恰好是该文件夹现有子项的一个排列。
+    pub fn reorder(&mut self, folder: Option<&str>, ids: &[String]) -> Result<(), String> {
+        let children = self.children_mut(folder)?;
+        let mut current = children.clone();
+        let mut requested = ids.to_vec();
+        current.sort();
+        requested.sort();
+        if current != requested {
+            return Err("排序列表与文件夹内容不一致".to_string());
+        }
+        *children = ids.to_vec();
+        Ok(())
+    }
+
+    pub fn playlist_mut(&mut self, id: &str) -> Result<&mut Playlist, String> {
+        self.playlists
+            .get_mut(id)
+            .ok_or_else(|| format!("歌单不存在: {}", id))
+    }
+
+    /// 可手动编辑曲目的歌单；智能歌单的曲目由规则决定，不能直接修改。
+    pub fn manual_playlist_mut(&mut self, id: &str) -> Result<&mut Playlist, String> {
+        let playlist = self.playlist_mut(id)?;
+        if playlist.is_smart() {
+            return Err("智能歌单的曲目由规则决定，不能手动修改".to_string());
+        }
+        Ok(playlist)
+    }
+
+    /// 把智能歌单当前的曲目复制为一个普通歌单，放在智能歌单之后；`name` 缺省时沿用智能歌单的名称。
+    pub fn materialize(&mut self, id: &str, name: Option<&str>, song_ids: Vec<String>) -> Result<Playlist, String> {
+        let source = self
+            .playlists
+            .get(id)
+            .filter(|p| p.is_smart())
+            .ok_or_else(|| format!("不是智能歌单: {}", id))?;
+        let playlist = Playlist {
+            id: Uuid::new_v4().to_string(),
+            name: clean_name(name.unwrap_or(&source.name))?,
+            song_ids,
+            created_at: now(),
+            rules: None,
+        };
+        let (parent, index) = self.position_of(id).ok_or_else(|| format!("歌单不存在: {}", id))?;
+        self.insert(playlist.id.clone(), parent.as_deref(), Some(index + 1))?;
+        self.playlists.insert(playlist.id.clone(), playlist.clone());
+        Ok(playlist)
+    }
+
+    /// 替换智能歌单的规则。
+    pub fn set_rules(&mut self, id: &str, rules: SmartRules) -> Result<Playlist, String> {
+        rules.validate()?;
+        let playlist = self.playlist_mut(id)?;
+        if !playlist.is_smart() {
+            return Err(format!("不是智能歌单: {}", id));
+        }
+        playlist.rules = Some(rules);
+        Ok(playlist.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(nodes: &[PlaylistNode]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|n| match n {
+                PlaylistNode::Folder { name, children, .. } => format!("{}/[{}]", name, names(children).join(",")),
+                PlaylistNode::Playlist { name, .. } => name.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_folders_move_and_reorder() {
+        let mut tree = PlaylistTree::default();
+        let moods = tree.create_folder("Moods", None).unwrap();
+        let calm = tree.create_folder("Calm", Some(&moods.id)).unwrap();
+        let rain = tree.create_playlist("Rain", Some(&calm.id)).unwrap();
+        let gym = tree.create_playlist("Gym", None).unwrap();
+        let road = tree.create_playlist("Road", None).unwrap();
+        assert_eq!(names(&tree.nodes(&|_| 0)), vec!["Moods/[Calm/[Rain]]", "Gym", "Road"]);
+
+        tree.move_node(&road.id, Some(&moods.id), Some(0)).unwrap();
+        tree.move_node(&gym.id, None, Some(0)).unwrap();
+        assert_eq!(names(&tree.nodes(&|_| 0)), vec!["Gym", "Moods/[Road,Calm/[Rain]]"]);
+
+        // 文件夹不能移入自己的子文件夹
+        assert!(tree.move_node(&moods.id, Some(&calm.id), None).is_err());
+
+        tree.reorder(Some(&moods.id), &[calm.id.clone(), road.id.clone()]).unwrap();
+        assert!(tree.reorder(None, std::slice::from_ref(&gym.id)).is_err());
+
+        // 删除文件夹时内容上移到原位置
+        tree.remove(&moods.id).unwrap();
+        assert_eq!(names(&tree.nodes(&|_| 0)), vec!["Gym", "Calm/[Rain]", "Road"]);
+        assert!(tree.playlists.contains_key(&rain.id));
+    }
+}
@@ -0,0 +1,225 @@
+//! 歌曲筛选 — 按无损、码率、编码、采样率与演唱语言过滤歌曲查询。
+//!
+//! 条件之间为「且」。[`QualityIndex`] 与搜索索引一样由 `MusicLibrary` 缓存、随库版本失效，
+//! 构建时直接读存储中的 JSON：
+//!
+//! | 条件 | 索引 |
+//! |------|------|
+//! | `lossless_only` | 无损歌曲的位置列表 |
+//! | `formats` | 编码名（小写）→ 位置列表 |
+//! | `min_bitrate` / `min_sample_rate` | 按数值排序的（值，位置）列表，二分找下界 |
+//! | `languages` | 语言标记 → 位置列表（主标签匹配其变体，见 [`language::matches`]） |
+//!
+//! 「位置」是歌曲在存储中的顺序，命中结果按位置输出即与不加筛选的分页顺序一致。
+//! 没有码率 / 采样率记录的歌曲不满足对应的下限条件。
+
+use super::{language, songs};
+use crate::module::perf;
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 歌曲查询的音质与语言条件；各字段缺省表示不限制。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(default)]
+pub struct QualityFilter {
+    /// 只要无损编码
+    pub lossless_only: bool,
+    /// 最低平均码率（kbps）
+    pub min_bitrate: Option<u32>,
+    /// 限定编码（如 `["flac", "alac"]`，大小写不敏感）；空表示不限制
+    pub formats: Vec<String>,
+    /// 最低采样率（Hz）
+    pub min_sample_rate: Option<u32>,
+    /// 限定演唱语言（如 `["ja", "zh"]`，`zh` 同时匹配 `zh-Hant` 等变体；`zxx` 为纯音乐）；空表示不限制
+    pub languages: Vec<String>,
+}
+
+impl QualityFilter {
+    pub fn is_empty(&self) -> bool {
+        !self.lossless_only
+            && self.min_bitrate.is_none()
+            && self.formats.is_empty()
+            && self.min_sample_rate.is_none()
+            && self.languages.is_empty()
+    }
+}
+
+/// 库中出现的一种演唱语言及其歌曲数。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub count: usize,
+}
+
+/// 歌曲音质属性的索引。
+pub struct QualityIndex {
+    version: u64,
+    ids: Vec<String>,
+    lossless: Vec<u32>,
+    by_format: HashMap<String, Vec<u32>>,
+    by_bitrate: Vec<(u32, u32)>,
+    by_sample_rate: Vec<(u32, u32)>,
+    by_language: HashMap<String, Vec<u32>>,
+}
+
+impl QualityIndex {
+    pub fn build(store: &PersistentStore, version: u64) -> Self {
+        let _scope = perf::scope("quality.build_index");
+        let mut index = Self {
+            version,
+            ids: Vec::new(),
+            lossless: Vec::new(),
+            by_format: HashMap::new(),
+            by_bitrate: Vec::new(),
+            by_sample_rate: Vec::new(),
+            by_language: HashMap::new(),
+        };
+        let Some(value) = store.get_raw(songs::KEY) else {
+            return index;
+        };
+        let Some(obj) = value.as_object() else {
+            return index;
+        };
+        for (id, song) in obj {
+            let pos = index.ids.len() as u32;
+            index.ids.push(id.clone());
+            if song.get("lossless").and_then(|v| v.as_bool()) == Some(true) {
+                index.lossless.push(pos);
+            }
+            if let Some(codec) = song.get("codec").and_then(|v| v.as_str()) {
+                index.by_format.entry(codec.to_lowercase()).or_default().push(pos);
+            }
+            if let Some(kbps) = song.get("bitrate_kbps").and_then(|v| v.as_u64()) {
+                index.by_bitrate.push((kbps as u32, pos));
+            }
+            if let Some(rate) = song.get("sample_rate").and_then(|v| v.as_u64()) {
+                index.by_sample_rate.push((rate as u32, pos));
+            }
+            if let Some(lang) = song.get("language").and_then(|v| v.as_str()) {
+                index.by_language.entry(lang.to_string()).or_default().push(pos);
+            }
+        }
+        index.by_bitrate.sort_unstable();
+        index.by_sample_rate.sort_unstable();
+        index
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// 全部歌曲 ID，按存储顺序。
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    /// 满足条件的歌曲 ID，按存储顺序。
+    pub fn matching_ids(&self, filter: &QualityFilter) -> Vec<String> {
+        let mut hits = vec![true; self.ids.len()];
+        let mut keep = |positions: &mut dyn Iterator<Item = u32>| {
+            let mut mask = vec![false; hits.len()];
+            for pos in positions {
+                mask[pos as usize] = true;
+            }
+            for (hit, m) in hits.iter_mut().zip(mask) {
+                *hit &= m;
+            }
+        };
+
+        if filter.lossless_only {
+            keep(&mut self.lossless.iter().copied());
+        }
+        if !filter.formats.is_empty() {
+            let formats: HashSet<String> = filter.formats.iter().map(|f| f.to_lowercase()).collect();
+            keep(&mut formats.iter().filter_map(|f| self.by_format.get(f)).flatten().copied());
+        }
+        if let Some(min) = filter.min_bitrate {
+            keep(&mut at_least(&self.by_bitrate, min));
+        }
+        if let Some(min) = filter.min_sample_rate {
+            keep(&mut at_least(&self.by_sample_rate, min));
+        }
+        if !filter.languages.is_empty() {
+            // 条件与标签同样规范化，`jpn` / `Japanese` 都能匹配 `ja`
+            let langs: Vec<String> =
+                filter.languages.iter().map(|l| language::normalize(l).unwrap_or_else(|| l.clone())).collect();
+            let wanted = |tag: &String| langs.iter().any(|lang| language::matches(tag, lang));
+            keep(&mut self.by_language.iter().filter(|(tag, _)| wanted(tag)).flat_map(|(_, pos)| pos.iter().copied()));
+        }
+
+        hits.iter()
+            .zip(&self.ids)
+            .filter(|(hit, _)| **hit)
+            .map(|(_, id)| id.clone())
+            .collect()
+    }
+
+    /// 库中出现的演唱语言，按歌曲数降序（相同时按语言标记排序）。
+    pub fn languages(&self) -> Vec<LanguageCount> {
+        let mut counts: Vec<LanguageCount> = self
+            .by_language
+            .iter()
+            .map(|(language, positions)| LanguageCount {
+                language: language.clone(),
+                count: positions.len(),
+            })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.language.cmp(&b.language)));
+        counts
+    }
+}
+
+/// 有序（值，位置）列表中值不小于 `min` 的位置。
+fn at_least(sorted: &[(u32, u32)], min: u32) -> impl Iterator<Item = u32> + '_ {
+    let start = sorted.partition_point(|(value, _)| *value < min);
+    sorted[start..].iter().map(|(_, pos)| *pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_filter_by_quality() {
+        let path = std::env::temp_dir().join(format!("chordial_quality_{}.json", std::process::id()));
+        let store = PersistentStore::new(path.clone());
+        store.set_raw(
+            songs::KEY,
+            json!({
+                "flac": { "codec": "flac", "lossless": true, "bitrate_kbps": 900, "sample_rate": 44100, "language": "ja" },
+                "hires": { "codec": "FLAC", "lossless": true, "bitrate_kbps": 2800, "sample_rate": 96000, "language": "zh-Hant" },
+                "mp3": { "codec": "mp3", "bitrate_kbps": 320, "sample_rate": 44100, "language": "zh" },
+                "aac": { "codec": "aac", "bitrate_kbps": 256, "sample_rate": 48000 },
+                "unknown": {},
+            }),
+        );
+        let index = QualityIndex::build(&store, 1);
+        let _ = std::fs::remove_file(&path);
+        let query = |filter: QualityFilter| index.matching_ids(&filter);
+
+        assert_eq!(query(QualityFilter::default()).len(), 5);
+        assert_eq!(query(QualityFilter { lossless_only: true, ..Default::default() }), vec!["flac", "hires"]);
+        assert_eq!(
+            query(QualityFilter { min_bitrate: Some(300), ..Default::default() }),
+            vec!["flac", "hires", "mp3"]
+        );
+        assert_eq!(
+            query(QualityFilter { formats: vec!["AAC".into(), "mp3".into()], min_sample_rate: Some(48000), ..Default::default() }),
+            vec!["aac"]
+        );
+        assert_eq!(
+            query(QualityFilter { lossless_only: true, min_sample_rate: Some(88200), ..Default::default() }),
+            vec!["hires"]
+        );
+        assert_eq!(query(QualityFilter { languages: vec!["zh".into()], ..Default::default() }), vec!["hires", "mp3"]);
+        assert_eq!(
+            query(QualityFilter { languages: vec!["ja".into()], lossless_only: true, ..Default::default() }),
+            vec!["flac"]
+        );
+        let languages: Vec<(String, usize)> = index.languages().into_iter().map(|l| (l.language, l.count)).collect();
+        assert_eq!(languages, vec![("ja".into(), 1), ("zh".into(), 1), ("zh-Hant".into(), 1)]);
+    }
+}
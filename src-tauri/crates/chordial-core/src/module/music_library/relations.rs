@@ -1,5 +1,25 @@
 use super::{albums, artists, lyrics, models::*, songs};
 use crate::module::storage::persistent::PersistentStore;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// 相关艺人默认返回的数量上限。
+pub const RELATED_ARTISTS_LIMIT: usize = 20;
+
+/// 一张共同专辑相对一首合作曲目的权重。
+const SHARED_ALBUM_WEIGHT: f32 = 0.5;
+
+/// 与某艺人有合作关系的艺人。
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedArtist {
+    pub artist: Artist,
+    /// 关系强度，按此降序排列
+    pub weight: f32,
+    /// 同一首歌中共同署名的曲目数
+    pub shared_tracks: usize,
+    /// 共同出现的专辑数（任一方为专辑艺人或有曲目收录其中）
+    pub shared_albums: usize,
+}
 
 /// 获取歌曲的艺术家列表。
 pub fn get_artists_of_song(store: &PersistentStore, song_id: &str) -> Vec<Artist> {
@@ -39,8 +59,19 @@ pub fn get_songs_by_artist(store: &PersistentStore, artist_id: &str) -> Vec<Song
 /// 优化：JSON 层过滤，仅反序列化匹配条目。
 /// 旧 `albums::get_all` 反序列化全部专辑（3853 条）= 24ms，
 /// 本方法仅反序列化匹配的 ~10 条 = ~0.1ms。
+///
+/// 按 [`Album::chronology_date`] 排序（重制版按首发时间），无日期的排在最后。
 pub fn get_albums_by_artist(store: &PersistentStore, artist_id: &str) -> Vec<Album> {
-    store.get_entries_by_str_field::<Album>(albums::KEY, "artist_id", artist_id)
+    sort_by_chronology(store.get_entries_by_str_field::<Album>(albums::KEY, "artist_id", artist_id))
+}
+
+/// 按 [`Album::chronology_date`] 排序，无日期的排在最后。
+pub fn sort_by_chronology(mut albums: Vec<Album>) -> Vec<Album> {
+    albums.sort_by_cached_key(|a| {
+        let date = a.chronology_date();
+        (date.is_none(), date)
+    });
+    albums
 }
 
 /// 获取专辑中的所有歌曲。
@@ -76,3 +107,121 @@ pub fn get_source_ids_of_album(store: &PersistentStore, album_id: &str) -> Vec<c
         .map(|a| a.source_ids)
         .unwrap_or_default()
 }
+
+/// 根据库内的合作关系计算相关艺人：同一首歌共同署名，或出现在同一张专辑中。
+///
+/// 每首合作曲目计 1 分；每张共同专辑计 [`SHARED_ALBUM_WEIGHT`]，并按专辑中其他艺人的数量均摊，
+/// 避免几十位艺人的合辑让所有人互相「相关」。占位的 [`artists::UNKNOWN_NAME`] 不参与计算。
+pub fn get_related_artists(store: &PersistentStore, artist_id: &str, limit: usize) -> Vec<RelatedArtist> {
+    let own_songs = get_songs_by_artist(store, artist_id);
+    let mut shared_tracks: HashMap<String, usize> = HashMap::new();
+    for song in &own_songs {
+        for other in song.artist_ids.iter().filter(|id| *id != artist_id) {
+            *shared_tracks.entry(other.clone()).or_default() += 1;
+        }
+    }
+
+    let album_ids: HashSet<String> = own_songs
+        .iter()
+        .filter_map(|s| s.album_id.clone())
+        .chain(get_albums_by_artist(store, artist_id).into_iter().map(|a| a.id))
+        .collect();
+    let mut shared_albums: HashMap<String, (usize, f32)> = HashMap::new();
+    for album_id in &album_ids {
+        let Some(album) = albums::get(store, album_id) else { continue };
+        let others: HashSet<String> = std::iter::once(album.artist_id.clone())
+            .chain(get_songs_in_album(store, album_id).into_iter().flat_map(|s| s.artist_ids))
+            .filter(|id| id != artist_id)
+            .collect();
+        let share = SHARED_ALBUM_WEIGHT / others.len().max(1) as f32;
+        for other in others {
+            let entry = shared_albums.entry(other).or_default();
+            entry.0 += 1;
+            entry.1 += share;
+        }
+    }
+
+    let ids: HashSet<&String> = shared_tracks.keys().chain(shared_albums.keys()).collect();
+    let mut related: Vec<RelatedArtist> = ids
+        .into_iter()
+        .filter_map(|id| artists::get(store, id))
+        .filter(|artist| artist.name != artists::UNKNOWN_NAME)
+        .map(|artist| {
+            let tracks = shared_tracks.get(&artist.id).copied().unwrap_or(0);
+            let (albums, album_weight) = shared_albums.get(&artist.id).copied().unwrap_or_default();
+            RelatedArtist {
+                weight: tracks as f32 + album_weight,
+                shared_tracks: tracks,
+                shared_albums: albums,
+                artist,
+            }
+        })
+        .collect();
+    related.sort_by(|a, b| {
+        b.weight
+            .total_cmp(&a.weight)
+            .then_with(|| a.artist.name.cmp(&b.artist.name))
+    });
+    related.truncate(limit);
+    related
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_related_artists_weighs_co_credits_above_albums() {
+        let path = std::env::temp_dir().join(format!("chordial_related_artists_{}.json", std::process::id()));
+        let store = PersistentStore::new(path.clone());
+        let artist = |id: &str, name: &str| {
+            (id.to_string(), entity::<Artist>(serde_json::json!({ "id": id, "name": name, "bio": null, "source_ids": [] })))
+        };
+        let song = |id: &str, artist_ids: &[&str], album: &str| {
+            let song: Song = entity(serde_json::json!({
+                "id": id, "title": id, "artist_names": [], "album_title": null, "duration": 1,
+                "artist_ids": artist_ids, "album_id": album, "lyric_id": null, "source_ids": [],
+            }));
+            (id.to_string(), song)
+        };
+        let album = |id: &str, artist_id: &str, song_ids: &[&str]| {
+            let album: Album = entity(serde_json::json!({
+                "id": id, "title": id, "artist_id": artist_id, "cover_url": null,
+                "song_ids": song_ids, "source_ids": [],
+            }));
+            (id.to_string(), album)
+        };
+        let all_artists: HashMap<_, _> = [
+            artist("a", "A"),
+            artist("b", "B"),
+            artist("c", "C"),
+            artist("u", artists::UNKNOWN_NAME),
+        ]
+        .into();
+        // A 与 B 合作两首；C 只与 A 同在一张专辑；U 是占位艺人
+        let all_songs: HashMap<_, _> = [
+            song("s1", &["a", "b"], "x"),
+            song("s2", &["a", "b", "u"], "x"),
+            song("s3", &["c"], "x"),
+        ]
+        .into();
+        let all_albums: HashMap<_, _> = [album("x", "a", &["s1", "s2", "s3"])].into();
+        store.set(artists::KEY, &all_artists).unwrap();
+        store.set(songs::KEY, &all_songs).unwrap();
+        store.set(albums::KEY, &all_albums).unwrap();
+
+        let related = get_related_artists(&store, "a", RELATED_ARTISTS_LIMIT);
+        let _ = std::fs::remove_file(&path);
+        let summary: Vec<(&str, usize, usize)> = related
+            .iter()
+            .map(|r| (r.artist.id.as_str(), r.shared_tracks, r.shared_albums))
+            .collect();
+        assert_eq!(summary, vec![("b", 2, 1), ("c", 0, 1)]);
+        assert!(related[0].weight > 2.0 && related[1].weight < 0.5);
+        assert_eq!(get_related_artists(&store, "a", 1).len(), 1);
+    }
+}
@@ -33,6 +33,13 @@
 //! | Song | `title` + `artist_names` + `album_title` |
 //! | Artist | `name` |
 //! | Album | `title`（艺术家名通过 `artist_id` 不直接索引，避免跨表 join） |
+//!
+//! # 排序与模糊匹配
+//!
+//! [`search_ranked`] 在子串匹配之外给每条结果打分（见 [`MatchKind`]）：主字段（歌曲 / 专辑标题、艺人名）
+//! 完全相等 > 任一字段以关键词开头 > 关键词位于词首 > 任意位置。`fuzzy` 开启时，与关键词共享至少
+//! [`FUZZY_MIN_SIMILARITY`] 比例 trigram 的条目也作为模糊结果返回（容忍拼写错误，如 `beatels`），
+//! 分数按共享比例递减，总排在子串匹配之后。歌词全文不进索引，由调用方按需扫描。
 
 use super::models::{Album, Artist, Song};
 use crate::module::music_source::types::EntityType;
@@ -40,7 +47,10 @@ use crate::module::perf;
 use crate::module::storage::persistent::PersistentStore;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// 模糊匹配所需的最低 trigram 相似度（共享 trigram 数 / 关键词 trigram 数）。
+pub const FUZZY_MIN_SIMILARITY: f32 = 0.5;
 
 /// 字符 trigram 哈希类型（FNV-style 变种，足够分散且无需 String 分配）。
 type TrigramHash = u64;
@@ -199,6 +209,103 @@ impl SearchIndex {
     }
 }
 
+impl SearchIndex {
+    /// 带打分的查询：子串匹配按 [`match_score`] 打分，`fuzzy` 时补充 trigram 相似的模糊结果。
+    fn search_type_ranked(&self, ty: &TypeIndex, query_lower: &str, fuzzy: bool) -> Vec<RankedId> {
+        let mut hits: Vec<RankedId> = self
+            .search_type(ty, query_lower)
+            .into_iter()
+            .filter_map(|id| {
+                let (score, kind) = match_score(ty.texts.get(&id)?, query_lower);
+                Some(RankedId { id, score, kind })
+            })
+            .collect();
+
+        let query_trigrams: HashSet<TrigramHash> = char_trigram_hashes(query_lower).collect();
+        if fuzzy && !query_trigrams.is_empty() {
+            let exact: HashSet<&str> = hits.iter().map(|h| h.id.as_str()).collect();
+            let mut shared: HashMap<&str, usize> = HashMap::new();
+            for h in &query_trigrams {
+                // 同一文本中重复出现的 trigram 在倒排链里有多条，只计一次
+                let ids: HashSet<&str> = ty.trigrams.get(h).into_iter().flatten().map(String::as_str).collect();
+                for id in ids {
+                    *shared.entry(id).or_default() += 1;
+                }
+            }
+            let total = query_trigrams.len() as f32;
+            let fuzzy_hits: Vec<RankedId> = shared
+                .into_iter()
+                .filter(|(id, _)| !exact.contains(id))
+                .map(|(id, n)| (id, n as f32 / total))
+                .filter(|(_, similarity)| *similarity >= FUZZY_MIN_SIMILARITY)
+                .map(|(id, similarity)| RankedId {
+                    id: id.to_string(),
+                    score: 0.5 * similarity,
+                    kind: MatchKind::Fuzzy,
+                })
+                .collect();
+            hits.extend(fuzzy_hits);
+        }
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+        hits.dedup_by(|a, b| a.id == b.id);
+        hits
+    }
+}
+
+/// 关键词命中一条索引文本的方式，从高到低。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// 某字段与关键词完全相同
+    Exact,
+    /// 某字段以关键词开头
+    Prefix,
+    /// 关键词位于某个词的开头
+    WordPrefix,
+    /// 关键词出现在字段中间
+    Substring,
+    /// 与关键词的 trigram 足够相似（拼写错误）
+    Fuzzy,
+    /// 关键词出现在歌词中
+    Lyrics,
+}
+
+/// 打分后的命中 ID。
+#[derive(Debug, Clone)]
+pub struct RankedId {
+    pub id: String,
+    /// 0 ~ 1，越大越靠前
+    pub score: f32,
+    pub kind: MatchKind,
+}
+
+/// 子串命中的得分：主字段（第一个字段）命中比其他字段高 0.05，短字段略微优先。
+fn match_score(text: &str, query_lower: &str) -> (f32, MatchKind) {
+    let mut best = (0.0f32, MatchKind::Substring);
+    for (i, field) in text.split('\x00').enumerate() {
+        let Some(pos) = field.find(query_lower) else {
+            continue;
+        };
+        let (base, kind) = if field == query_lower {
+            (0.95, MatchKind::Exact)
+        } else if pos == 0 {
+            (0.85, MatchKind::Prefix)
+        } else if !field[..pos].chars().next_back().is_some_and(char::is_alphanumeric) {
+            (0.75, MatchKind::WordPrefix)
+        } else {
+            (0.6, MatchKind::Substring)
+        };
+        let primary = if i == 0 { 0.05 } else { 0.0 };
+        let coverage = 0.05 * query_lower.len() as f32 / field.len().max(1) as f32;
+        let score = base - 0.05 + primary + coverage;
+        if score > best.0 {
+            best = (score, kind);
+        }
+    }
+    best
+}
+
 /// 提取字符串的字符 trigram 哈希序列。
 ///
 /// 对 `s` 中每个连续 3 字符窗口计算 FNV-style 哈希，
@@ -352,6 +459,97 @@ impl SearchIdSets {
     }
 }
 
+/// [`search_ranked`] 未指定条数时每类返回的结果数。
+pub const DEFAULT_RANKED_LIMIT: usize = 20;
+
+/// [`search_ranked`] 的查询选项。
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// 限定实体类型；为空时搜索歌曲、艺人与专辑（歌词由 `lyrics` 控制）
+    pub types: Vec<EntityType>,
+    /// 限定来源名称
+    pub source_name: Option<String>,
+    /// 同时搜索歌词全文（逐条扫描，比索引查询慢）
+    pub lyrics: bool,
+    /// 补充拼写相近的模糊结果
+    pub fuzzy: bool,
+}
+
+impl SearchOptions {
+    /// 是否搜索某类实体。
+    pub fn includes(&self, entity_type: EntityType) -> bool {
+        match entity_type {
+            EntityType::Lyric => self.lyrics || self.types.contains(&EntityType::Lyric),
+            _ => self.types.is_empty() || self.types.contains(&entity_type),
+        }
+    }
+}
+
+/// 打分后的一条结果；实体字段平铺，另附得分与命中方式。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit<T> {
+    #[serde(flatten)]
+    pub item: T,
+    pub score: f32,
+    pub matched: MatchKind,
+}
+
+/// 歌词命中：所属歌曲与第一处命中的歌词行。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LyricHit {
+    pub song: Song,
+    /// 去掉时间戳后的命中行
+    pub line: String,
+    pub score: f32,
+}
+
+/// 带打分的搜索结果，各类按得分从高到低。
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RankedResults {
+    pub songs: Vec<SearchHit<Song>>,
+    pub artists: Vec<SearchHit<Artist>>,
+    pub albums: Vec<SearchHit<Album>>,
+    pub lyrics: Vec<SearchHit<LyricHit>>,
+}
+
+/// 按 [`SearchOptions`] 查询三类实体，返回各自打分排序后的 ID（未截断，来源过滤由调用方完成）。
+pub fn search_ranked(index: &SearchIndex, query: &str, options: &SearchOptions) -> [Vec<RankedId>; 3] {
+    let _scope = perf::scope("search.ranked");
+    let query_lower = query.trim().to_lowercase();
+    if query_lower.is_empty() {
+        return Default::default();
+    }
+    let ranked = |ty: &TypeIndex, entity_type: EntityType| {
+        if options.includes(entity_type) {
+            index.search_type_ranked(ty, &query_lower, options.fuzzy)
+        } else {
+            Vec::new()
+        }
+    };
+    [
+        ranked(&index.songs, EntityType::Song),
+        ranked(&index.artists, EntityType::Artist),
+        ranked(&index.albums, EntityType::Album),
+    ]
+}
+
+/// 歌词文本中第一处包含关键词的行（去掉 LRC 标签）及其得分；没有命中时返回 `None`。
+pub fn lyric_hit_line(text: &str, query_lower: &str) -> Option<(String, f32)> {
+    text.lines().map(super::language::strip_lrc_tags).find_map(|line| {
+        let lower = line.to_lowercase();
+        let pos = lower.find(query_lower)?;
+        // 整句 / 词首命中优先于句中片段
+        let score = if lower.trim() == query_lower {
+            0.45
+        } else if !lower[..pos].chars().next_back().is_some_and(char::is_alphanumeric) {
+            0.4
+        } else {
+            0.35
+        };
+        Some((line.trim().to_string(), score))
+    })
+}
+
 /// 按来源名过滤 JSON 条目（黑盒谓词，作用于 `Value`）。
 ///
 /// 用于在搜索结果回填实体时，剔除不匹配来源的条目。
@@ -392,6 +590,31 @@ mod tests {
         assert_eq!(v.len(), 0);
     }
 
+    #[test]
+    fn ranked_search_orders_by_match_kind() {
+        let mut songs = TypeIndex::default();
+        songs.index("a", "yesterday\x00the beatles\x00".to_string());
+        songs.index("b", "yesterday once more\x00carpenters\x00".to_string());
+        songs.index("c", "all my yesterdays\x00\x00".to_string());
+        songs.index("d", "hello\x00adele\x00".to_string());
+        let index = SearchIndex { songs, artists: TypeIndex::default(), albums: TypeIndex::default(), version: 0 };
+
+        let hits = index.search_type_ranked(&index.songs, "yesterday", false);
+        let order: Vec<(&str, MatchKind)> = hits.iter().map(|h| (h.id.as_str(), h.kind)).collect();
+        assert_eq!(order, vec![("a", MatchKind::Exact), ("b", MatchKind::Prefix), ("c", MatchKind::WordPrefix)]);
+
+        // 拼写错误只在 fuzzy 时命中，且排在子串命中之后
+        assert!(index.search_type_ranked(&index.songs, "beatels", false).is_empty());
+        let fuzzy = index.search_type_ranked(&index.songs, "the beatels", true);
+        assert_eq!(fuzzy[0].id, "a");
+        assert_eq!(fuzzy[0].kind, MatchKind::Fuzzy);
+        assert!(index.search_type_ranked(&index.songs, "helo", true).iter().any(|h| h.id == "d"));
+
+        let lyric = "[00:01.00]All my troubles seemed so far away\n[00:05.00]Now it looks as though they're here to stay";
+        assert_eq!(lyric_hit_line(lyric, "troubles").unwrap().0, "All my troubles seemed so far away");
+        assert!(lyric_hit_line(lyric, "yesterday").is_none());
+    }
+
     #[test]
     fn build_song_text_concatenates_fields() {
         let v: Value = serde_json::json!({
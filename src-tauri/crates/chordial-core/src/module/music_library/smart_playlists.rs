@@ -0,0 +1,363 @@
+//! 智能歌单 — 由规则而不是手动挑选的曲目构成，如「流派为 Jazz 且年份 ≥ 2000 且时长 < 10 分钟」。
+//!
+//! 规则保存在歌单的 [`rules`](super::playlists::Playlist::rules) 中，智能歌单与普通歌单共用文件夹层级、
+//! 重命名、移动等操作。曲目不落盘：每次读取歌单（或歌单树的曲目数）时对未隐藏的歌曲与播放统计求值，
+//! 因此增删歌曲、改标签、评分、隐藏后，前端收到库变化事件重新读取即得到新结果。
+//! 「固化」把当前结果复制为一个普通歌单，此后不再随库变化。
+//!
+//! | 字段类型 | 字段 | 可用比较 |
+//! |----------|------|----------|
+//! | 文本 | `title` `artist` `album` `genre` `composer` `language` `codec` | `is` `is_not` `contains` `not_contains` `starts_with` |
+//! | 数值 | `year` `duration`（秒）`bitrate`（kbps）`sample_rate` `rating`（0–100）`play_count` | `is` `is_not` `lt` `le` `gt` `ge` |
+//! | 布尔 | `lossless` | `is` `is_not` |
+//!
+//! 文本比较不区分大小写；多值字段（艺人、流派、作曲者）任一值满足即满足，`is_not` / `not_contains`
+//! 则要求所有值都不满足。缺少数值的歌曲不满足任何数值条件（与 [`quality`](super::quality) 的下限条件一致）。
+
+use super::models::Song;
+use super::stats::PlayStats;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// 预览时最多返回的歌曲数（总数另行给出）。
+pub const PREVIEW_LIMIT: usize = 200;
+
+/// 规则可引用的歌曲字段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Title,
+    Artist,
+    Album,
+    Genre,
+    Composer,
+    Language,
+    Codec,
+    Year,
+    /// 时长（秒）
+    Duration,
+    /// 平均码率（kbps）
+    Bitrate,
+    SampleRate,
+    /// 评分（0–100）
+    Rating,
+    PlayCount,
+    Lossless,
+}
+
+/// 比较方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Is,
+    IsNot,
+    Contains,
+    NotContains,
+    StartsWith,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// 规则的比较值；类型须与字段匹配，见 [`Rule::validate`]。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+}
+
+/// 一条规则，如 `{ "field": "year", "op": "ge", "value": 2000 }`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub field: Field,
+    pub op: Op,
+    pub value: RuleValue,
+}
+
+/// 规则之间的组合方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// 全部满足（且）
+    #[default]
+    All,
+    /// 任一满足（或）
+    Any,
+}
+
+/// 智能歌单的完整定义。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartRules {
+    #[serde(rename = "match")]
+    pub match_mode: MatchMode,
+    /// 为空时匹配全库
+    pub rules: Vec<Rule>,
+    /// 排序字段；缺省时按艺人 → 专辑 → 碟号 → 音轨号 → 标题
+    pub sort_by: Option<Field>,
+    pub descending: bool,
+    /// 排序后最多保留的歌曲数
+    pub limit: Option<usize>,
+}
+
+/// [`preview`](super::library::MusicLibrary::preview_smart_playlist) 的结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct SmartPreview {
+    /// 满足规则的歌曲总数（已应用 `limit`）
+    pub total: usize,
+    /// 前 [`PREVIEW_LIMIT`] 首
+    pub songs: Vec<Song>,
+}
+
+/// 字段取值：文本字段可能有多个值，数值 / 布尔字段可能缺失。
+enum FieldValue<'a> {
+    Texts(Vec<&'a str>),
+    Number(Option<f64>),
+    Bool(bool),
+}
+
+impl Field {
+    fn value<'a>(self, song: &'a Song, stats: Option<&PlayStats>) -> FieldValue<'a> {
+        let one = |s: &'a Option<String>| FieldValue::Texts(s.as_deref().into_iter().collect());
+        let many = |v: &'a [String]| FieldValue::Texts(v.iter().map(String::as_str).collect());
+        let number = |n: Option<u64>| FieldValue::Number(n.map(|n| n as f64));
+        match self {
+            Field::Title => FieldValue::Texts(vec![song.title.as_str()]),
+            Field::Artist => many(&song.artist_names),
+            Field::Album => one(&song.album_title),
+            Field::Genre => many(&song.genres),
+            Field::Composer => many(&song.composers),
+            Field::Language => one(&song.language),
+            Field::Codec => one(&song.codec),
+            Field::Year => number(song.year.map(u64::from)),
+            Field::Duration => number(song.duration),
+            Field::Bitrate => number(song.bitrate_kbps.map(u64::from)),
+            Field::SampleRate => number(song.sample_rate.map(u64::from)),
+            Field::Rating => number(stats.and_then(|s| s.rating).map(u64::from)),
+            Field::PlayCount => number(Some(stats.map_or(0, |s| s.play_count))),
+            Field::Lossless => FieldValue::Bool(song.lossless),
+        }
+    }
+
+    fn is_text(self) -> bool {
+        matches!(
+            self,
+            Field::Title | Field::Artist | Field::Album | Field::Genre | Field::Composer | Field::Language | Field::Codec
+        )
+    }
+}
+
+impl Rule {
+    /// 检查比较方式与比较值是否适用于字段。
+    pub fn validate(&self) -> Result<(), String> {
+        let ok = match (&self.value, self.field) {
+            (RuleValue::Bool(_), Field::Lossless) => matches!(self.op, Op::Is | Op::IsNot),
+            (RuleValue::Text(_), field) if field.is_text() => {
+                matches!(self.op, Op::Is | Op::IsNot | Op::Contains | Op::NotContains | Op::StartsWith)
+            }
+            (RuleValue::Number(_), field) if !field.is_text() && field != Field::Lossless => {
+                matches!(self.op, Op::Is | Op::IsNot | Op::Lt | Op::Le | Op::Gt | Op::Ge)
+            }
+            _ => false,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("规则不适用: {:?} {:?} {:?}", self.field, self.op, self.value))
+        }
+    }
+
+    fn matches(&self, song: &Song, stats: Option<&PlayStats>) -> bool {
+        match (self.field.value(song, stats), &self.value) {
+            (FieldValue::Texts(values), RuleValue::Text(wanted)) => {
+                let wanted = wanted.to_lowercase();
+                let hit = |v: &&str| {
+                    let v = v.to_lowercase();
+                    match self.op {
+                        Op::Is | Op::IsNot => v == wanted,
+                        Op::Contains | Op::NotContains => v.contains(&wanted),
+                        _ => v.starts_with(&wanted),
+                    }
+                };
+                let any = values.iter().any(hit);
+                if matches!(self.op, Op::IsNot | Op::NotContains) {
+                    !any
+                } else {
+                    any
+                }
+            }
+            (FieldValue::Number(Some(v)), RuleValue::Number(n)) => match self.op {
+                Op::Is => v == *n,
+                Op::IsNot => v != *n,
+                Op::Lt => v < *n,
+                Op::Le => v <= *n,
+                Op::Gt => v > *n,
+                _ => v >= *n,
+            },
+            (FieldValue::Bool(v), RuleValue::Bool(b)) => (v == *b) == (self.op == Op::Is),
+            _ => false,
+        }
+    }
+}
+
+impl SmartRules {
+    pub fn validate(&self) -> Result<(), String> {
+        self.rules.iter().try_for_each(Rule::validate)
+    }
+
+    pub fn matches(&self, song: &Song, stats: Option<&PlayStats>) -> bool {
+        match self.match_mode {
+            MatchMode::All => self.rules.iter().all(|r| r.matches(song, stats)),
+            MatchMode::Any => self.rules.is_empty() || self.rules.iter().any(|r| r.matches(song, stats)),
+        }
+    }
+
+    /// 从 `songs` 中选出满足规则的歌曲，排序并截断后返回其 ID。
+    pub fn evaluate<'a>(
+        &self,
+        songs: impl IntoIterator<Item = &'a Song>,
+        stats: &HashMap<String, PlayStats>,
+    ) -> Vec<String> {
+        let mut hits: Vec<&Song> = songs
+            .into_iter()
+            .filter(|s| self.matches(s, stats.get(&s.id)))
+            .collect();
+        hits.sort_by(|a, b| {
+            let order = match self.sort_by {
+                Some(field) => compare_field(field, a, b, stats, self.descending),
+                None if self.descending => default_order(a, b).reverse(),
+                None => default_order(a, b),
+            };
+            // 同值时按 ID，保证结果稳定
+            order.then_with(|| a.id.cmp(&b.id))
+        });
+        if let Some(limit) = self.limit {
+            hits.truncate(limit);
+        }
+        hits.into_iter().map(|s| s.id.clone()).collect()
+    }
+}
+
+/// 按字段比较；缺少值的歌曲无论升降序都排在最后。
+fn compare_field(field: Field, a: &Song, b: &Song, stats: &HashMap<String, PlayStats>, descending: bool) -> Ordering {
+    let order = match (field.value(a, stats.get(&a.id)), field.value(b, stats.get(&b.id))) {
+        (FieldValue::Texts(x), FieldValue::Texts(y)) => match (x.first(), y.first()) {
+            (Some(x), Some(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+            (x, y) => return x.is_none().cmp(&y.is_none()),
+        },
+        (FieldValue::Number(x), FieldValue::Number(y)) => match (x, y) {
+            (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+            (x, y) => return x.is_none().cmp(&y.is_none()),
+        },
+        (FieldValue::Bool(x), FieldValue::Bool(y)) => y.cmp(&x),
+        _ => Ordering::Equal,
+    };
+    if descending {
+        order.reverse()
+    } else {
+        order
+    }
+}
+
+fn default_order(a: &Song, b: &Song) -> Ordering {
+    let lower = |s: Option<&String>| s.map(|s| s.to_lowercase());
+    lower(a.artist_names.first())
+        .cmp(&lower(b.artist_names.first()))
+        .then_with(|| lower(a.album_title.as_ref()).cmp(&lower(b.album_title.as_ref())))
+        .then_with(|| a.disc_number.unwrap_or(1).cmp(&b.disc_number.unwrap_or(1)))
+        .then_with(|| a.track_number.cmp(&b.track_number))
+        .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn song(id: &str, genre: &str, year: u32, duration: u64) -> Song {
+        serde_json::from_value(json!({
+            "id": id,
+            "title": id,
+            "artist_names": ["Artist"],
+            "album_title": null,
+            "duration": duration,
+            "artist_ids": [],
+            "album_id": null,
+            "lyric_id": null,
+            "source_ids": [],
+            "year": year,
+            "genres": [genre],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rules_filter_sort_and_limit() {
+        let songs = vec![
+            song("a", "Jazz", 1999, 300),
+            song("b", "jazz", 2005, 420),
+            song("c", "Jazz", 2012, 900),
+            song("d", "Rock", 2010, 200),
+        ];
+        let rules: SmartRules = serde_json::from_value(json!({
+            "rules": [
+                { "field": "genre", "op": "is", "value": "Jazz" },
+                { "field": "year", "op": "ge", "value": 2000 },
+                { "field": "duration", "op": "lt", "value": 600 },
+            ],
+        }))
+        .unwrap();
+        rules.validate().unwrap();
+        let stats = HashMap::new();
+        assert_eq!(rules.evaluate(&songs, &stats), vec!["b"]);
+
+        let any = SmartRules {
+            match_mode: MatchMode::Any,
+            sort_by: Some(Field::Year),
+            descending: true,
+            limit: Some(2),
+            ..rules.clone()
+        };
+        assert_eq!(any.evaluate(&songs, &stats), vec!["c", "d"]);
+
+        // 未评分的歌曲不满足评分条件，播放次数缺省为 0
+        let stats = HashMap::from([("a".to_string(), PlayStats { rating: Some(80), play_count: 3, ..Default::default() })]);
+        let rated: SmartRules =
+            serde_json::from_value(json!({ "rules": [{ "field": "rating", "op": "ge", "value": 60 }] })).unwrap();
+        assert_eq!(rated.evaluate(&songs, &stats), vec!["a"]);
+        let unplayed: SmartRules =
+            serde_json::from_value(json!({ "rules": [{ "field": "play_count", "op": "is", "value": 0 }] })).unwrap();
+        assert_eq!(unplayed.evaluate(&songs, &stats).len(), 3);
+
+        let invalid = Rule { field: Field::Year, op: Op::Contains, value: RuleValue::Number(2000.0) };
+        assert!(invalid.validate().is_err());
+        assert!(Rule { field: Field::Genre, op: Op::Is, value: RuleValue::Number(1.0) }.validate().is_err());
+    }
+
+    #[test]
+    fn test_library_evaluation_skips_audiobooks() {
+        use super::super::{books, library::MusicLibrary};
+        let dir = std::env::temp_dir().join(format!("chordial_smart_books_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let lib = MusicLibrary::new(dir.join("library.json"));
+        // 流派与年份都满足规则，但时长达到有声书阈值
+        let ids = lib
+            .add_songs_batch(&[song("track", "Jazz", 2005, 300), song("book", "Jazz", 2005, books::LONG_FILE_SECS)])
+            .unwrap();
+        let rules: SmartRules = serde_json::from_value(json!({
+            "rules": [
+                { "field": "genre", "op": "is", "value": "Jazz" },
+                { "field": "year", "op": "ge", "value": 2000 },
+            ],
+        }))
+        .unwrap();
+        let preview = lib.preview_smart_playlist(&rules).unwrap();
+        assert_eq!(preview.total, 1);
+        assert_eq!(preview.songs[0].id, ids[0]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
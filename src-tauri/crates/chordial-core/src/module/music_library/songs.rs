@@ -1,10 +1,27 @@
 use super::models::Song;
 use crate::module::perf;
 use crate::module::storage::persistent::PersistentStore;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
 pub const KEY: &str = "songs";
 
+/// 按 ID 列表批量获取时每页的上限；更长的列表由调用方按 `next_offset` 翻页。
+pub const MAX_IDS_PER_PAGE: usize = 500;
+
+/// [`get_page_by_ids`] 的一页结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct IdPage {
+    /// 本页找到的歌曲，顺序与请求中的 ID 一致
+    pub songs: Vec<Song>,
+    /// 本页中库里不存在的 ID
+    pub missing: Vec<String>,
+    /// 下一页的起点；已是最后一页时为 `None`
+    pub next_offset: Option<usize>,
+    /// 去重后的 ID 总数
+    pub total: usize,
+}
+
 /// 获取所有歌曲。
 pub fn get_all(store: &PersistentStore) -> HashMap<String, Song> {
     let _scope = perf::scope("songs.get_all");
@@ -117,9 +134,87 @@ pub fn get_page(store: &PersistentStore, offset: usize, limit: usize) -> Vec<Son
     store.get_page_entries::<Song>(KEY, offset, limit)
 }
 
+/// 按 ID 列表分页获取歌曲：重复的 ID 只保留第一次出现的位置，
+/// `offset` / `limit` 作用于去重后的列表，`limit` 最大为 [`MAX_IDS_PER_PAGE`]。
+///
+/// 每个 ID 单独按键查找，不反序列化整张歌曲表。
+pub fn get_page_by_ids(store: &PersistentStore, ids: &[String], offset: usize, limit: usize) -> IdPage {
+    let _scope = perf::scope("songs.get_page_by_ids");
+    let mut seen = HashSet::with_capacity(ids.len());
+    let unique: Vec<&String> = ids.iter().filter(|id| seen.insert(id.as_str())).collect();
+    let limit = limit.clamp(1, MAX_IDS_PER_PAGE);
+    let start = offset.min(unique.len());
+    let end = start.saturating_add(limit).min(unique.len());
+
+    let mut songs = Vec::with_capacity(end - start);
+    let mut missing = Vec::new();
+    for id in &unique[start..end] {
+        match get(store, id) {
+            Some(song) => songs.push(song),
+            None => missing.push((*id).clone()),
+        }
+    }
+    IdPage {
+        songs,
+        missing,
+        next_offset: (end < unique.len()).then_some(end),
+        total: unique.len(),
+    }
+}
+
+/// 无损编码的歌曲，按标题排序（不区分大小写）。
+///
+/// 有损歌曲不存 `lossless` 字段，JSON 层即可排除。
+pub fn lossless(store: &PersistentStore) -> Vec<Song> {
+    let mut songs = store.get_entries_filtered::<Song, _>(KEY, |v| v.get("lossless").and_then(|l| l.as_bool()) == Some(true));
+    songs.sort_by_cached_key(|s| s.title.to_lowercase());
+    songs
+}
+
 /// 获取歌曲总数。
 ///
 /// 优化：O(1) 检查 JSON Object 键数量，不反序列化。
 pub fn count(store: &PersistentStore) -> usize {
     store.count_entries(KEY)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_by_ids_keeps_order_and_dedups() {
+        let path = std::env::temp_dir().join(format!("chordial_songs_by_ids_{}.json", std::process::id()));
+        let store = PersistentStore::new(path.clone());
+        let songs: HashMap<String, Song> = ["a", "b", "c"]
+            .iter()
+            .map(|id| {
+                let song: Song = serde_json::from_value(serde_json::json!({
+                    "id": id,
+                    "title": id,
+                    "artist_names": [],
+                    "album_title": null,
+                    "duration": 1,
+                    "artist_ids": [],
+                    "album_id": null,
+                    "lyric_id": null,
+                    "source_ids": [],
+                }))
+                .unwrap();
+                (id.to_string(), song)
+            })
+            .collect();
+        store.set(KEY, &songs).unwrap();
+
+        let ids: Vec<String> = ["c", "x", "a", "c", "b"].iter().map(|s| s.to_string()).collect();
+        let first = get_page_by_ids(&store, &ids, 0, 2);
+        let order: Vec<&str> = first.songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!((order, first.missing.as_slice(), first.next_offset, first.total), (vec!["c"], &["x".to_string()][..], Some(2), 4));
+
+        let rest = get_page_by_ids(&store, &ids, 2, usize::MAX);
+        let order: Vec<&str> = rest.songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!((order, rest.next_offset), (vec!["a", "b"], None));
+        assert!(get_page_by_ids(&store, &ids, 10, 5).songs.is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,52 @@
+//! 播放统计 — 每首歌的评分、播放次数与最近播放时间。
+//!
+//! 扫描时导入文件标签中的评分 / 播放次数（见 `music_localSource::file_stats`）：
+//! 评分以文件为准，便于同步其他播放器里改过的评分；播放次数取两者较大值，避免回退。
+
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+
+pub const KEY: &str = "play_stats";
+
+/// 是否把评分写回音频文件的配置键（默认关闭）。
+pub const WRITE_BACK_CONFIG_KEY: &str = "stats_write_back";
+
+/// 单首歌曲的播放统计。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayStats {
+    /// 评分（0–100，五星制下每星 20）
+    pub rating: Option<u8>,
+    pub play_count: u64,
+    /// 最近一次播放时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_played_at: Option<u64>,
+}
+
+/// 获取歌曲的播放统计（没有记录时为默认值）。
+pub fn get(store: &PersistentStore, song_id: &str) -> PlayStats {
+    store
+        .get_entry::<PlayStats>(KEY, song_id)
+        .unwrap_or_default()
+}
+
+/// 写入歌曲的播放统计；全为空时删除条目。
+pub fn set(store: &PersistentStore, song_id: &str, stats: &PlayStats) -> Result<(), String> {
+    if *stats == PlayStats::default() {
+        store.remove_entry(KEY, song_id);
+        Ok(())
+    } else {
+        store.set_subkey(KEY, song_id, stats)
+    }
+}
+
+/// 合并文件中读到的评分 / 播放次数，返回是否有变化。
+pub fn merge_file_stats(stats: &mut PlayStats, rating: Option<u8>, play_count: Option<u64>) -> bool {
+    let before = stats.clone();
+    if let Some(rating) = rating {
+        stats.rating = Some(rating.min(100));
+    }
+    if let Some(count) = play_count {
+        stats.play_count = stats.play_count.max(count);
+    }
+    *stats != before
+}
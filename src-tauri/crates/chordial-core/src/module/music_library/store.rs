@@ -0,0 +1,345 @@
+//! SQLite 索引库 — 歌曲 / 艺人 / 专辑的带索引表，按 ID、按艺人和无损筛选的查询不再扫描整个 JSON 库。
+//!
+//! 库数据仍以 [`PersistentStore`]（`music_library.json`）为准，本模块在同目录维护 `music_library.db`：
+//!
+//! ```text
+//! songs(id PK, title_key, lossless, fingerprint, data)   INDEX (lossless, title_key)
+//! song_artists(song_id, artist_id) PK                   INDEX artist_id
+//! albums(id PK, artist_id, fingerprint, data)           INDEX artist_id
+//! artists(id PK, fingerprint, data)
+//! ```
+//!
+//! `data` 列保存条目的完整 JSON，查询直接反序列化命中的行。
+//!
+//! [`LibraryStore::sync`] 读取 [`PersistentStore::take_entry_changes`]：单条写入只重写对应的行；
+//! 整段替换（批量入库、重新加载）与打开后的第一次同步按条目指纹与表比对，只写差异。
+//! 每次同步在一个事务里完成，失败时下一次同步退回整表比对。
+
+use super::{albums, artists, models::*, songs};
+use crate::module::perf;
+use crate::module::storage::persistent::{entry_fingerprint, EntryChanges, PersistentStore};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 表结构版本；与库文件中记录的不一致时删表重建（数据从 JSON 重新同步）。
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS songs (
+    id TEXT PRIMARY KEY,
+    title_key TEXT NOT NULL,
+    lossless INTEGER NOT NULL,
+    fingerprint INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_songs_lossless ON songs(lossless, title_key);
+CREATE TABLE IF NOT EXISTS song_artists (
+    song_id TEXT NOT NULL,
+    artist_id TEXT NOT NULL,
+    PRIMARY KEY (song_id, artist_id)
+);
+CREATE INDEX IF NOT EXISTS idx_song_artists_artist ON song_artists(artist_id);
+CREATE TABLE IF NOT EXISTS albums (
+    id TEXT PRIMARY KEY,
+    artist_id TEXT NOT NULL,
+    fingerprint INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_albums_artist ON albums(artist_id);
+CREATE TABLE IF NOT EXISTS artists (
+    id TEXT PRIMARY KEY,
+    fingerprint INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+";
+
+/// 参与同步的分段：（库中的键，表名）。
+const TABLES: [(&str, &str); 3] = [(songs::KEY, "songs"), (albums::KEY, "albums"), (artists::KEY, "artists")];
+
+/// 一次同步写入的条目数。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    pub upserted: usize,
+    pub removed: usize,
+}
+
+/// 歌曲 / 艺人 / 专辑的 SQLite 索引库。
+pub struct LibraryStore {
+    conn: Mutex<Connection>,
+    /// 下一次同步是否整表比对（打开后第一次、上次同步失败后）
+    reconcile: Mutex<bool>,
+}
+
+impl LibraryStore {
+    /// 打开（不存在时创建）索引库；表结构版本不一致时清空重建。
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let conn = Connection::open(path).map_err(|e| format!("打开索引库失败: {}", e))?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+            .map_err(|e| format!("设置索引库失败: {}", e))?;
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("读取索引库版本失败: {}", e))?;
+        if version != SCHEMA_VERSION {
+            conn.execute_batch(
+                "DROP TABLE IF EXISTS songs; DROP TABLE IF EXISTS song_artists;
+                 DROP TABLE IF EXISTS albums; DROP TABLE IF EXISTS artists;",
+            )
+            .map_err(|e| format!("重建索引库失败: {}", e))?;
+        }
+        conn.execute_batch(SCHEMA).map_err(|e| format!("创建索引表失败: {}", e))?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(|e| format!("写入索引库版本失败: {}", e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            reconcile: Mutex::new(true),
+        })
+    }
+
+    /// 把 `store` 中自上次同步以来增删改的歌曲 / 艺人 / 专辑写入索引库。
+    ///
+    /// `store` 需先对三个分段调用 [`PersistentStore::track_entry_changes`]，否则每次都整表比对。
+    pub fn sync(&self, store: &PersistentStore) -> Result<SyncReport, String> {
+        let _scope = perf::scope("library_store.sync");
+        let mut reconcile = self.reconcile.lock();
+        let pending: Vec<(&str, &str, EntryChanges)> = TABLES
+            .iter()
+            .map(|&(key, table)| {
+                let changes = store.take_entry_changes(key).filter(|_| !*reconcile);
+                (key, table, changes.unwrap_or(EntryChanges::All))
+            })
+            .collect();
+        if pending.iter().all(|(_, _, c)| matches!(c, EntryChanges::Ids(ids) if ids.is_empty())) {
+            return Ok(SyncReport::default());
+        }
+        let result = self.apply(store, &pending);
+        *reconcile = result.is_err();
+        result
+    }
+
+    fn apply(&self, store: &PersistentStore, pending: &[(&str, &str, EntryChanges)]) -> Result<SyncReport, String> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction().map_err(|e| format!("开始事务失败: {}", e))?;
+        let mut report = SyncReport::default();
+        for (key, table, changes) in pending {
+            match changes {
+                EntryChanges::All => {
+                    let current = store.entry_fingerprints(key);
+                    let indexed = indexed_fingerprints(&tx, table)?;
+                    for id in indexed.keys().filter(|id| !current.contains_key(*id)) {
+                        delete_row(&tx, table, id)?;
+                        report.removed += 1;
+                    }
+                    for (id, fingerprint) in &current {
+                        if indexed.get(id) == Some(fingerprint) {
+                            continue;
+                        }
+                        let Some(value) = store.get_entry::<Value>(key, id) else {
+                            continue;
+                        };
+                        upsert_row(&tx, table, id, *fingerprint, &value)?;
+                        report.upserted += 1;
+                    }
+                }
+                EntryChanges::Ids(ids) => {
+                    for id in ids {
+                        match store.get_entry::<Value>(key, id) {
+                            Some(value) => {
+                                upsert_row(&tx, table, id, entry_fingerprint(&value), &value)?;
+                                report.upserted += 1;
+                            }
+                            None => {
+                                if delete_row(&tx, table, id)? {
+                                    report.removed += 1;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        tx.commit().map_err(|e| format!("提交事务失败: {}", e))?;
+        Ok(report)
+    }
+
+    // ── 查询 ─────────────────────────────────────────
+
+    /// 按 ID 读取条目（`key` 为 `songs` / `albums` / `artists`），走主键索引。
+    pub fn get<T: DeserializeOwned>(&self, key: &str, id: &str) -> Result<Option<T>, String> {
+        let (_, table) = TABLES
+            .iter()
+            .find(|(k, _)| *k == key)
+            .ok_or_else(|| format!("索引库中没有 '{}'", key))?;
+        let data: Option<String> = self
+            .conn
+            .lock()
+            .query_row(&format!("SELECT data FROM {} WHERE id = ?1", table), [id], |row| row.get(0))
+            .optional()
+            .map_err(|e| format!("查询索引库失败: {}", e))?;
+        data.map(|d| serde_json::from_str(&d).map_err(|e| format!("解析条目失败: {}", e)))
+            .transpose()
+    }
+
+    /// 某艺人参与的歌曲，按标题排序（走 `song_artists.artist_id` 索引）。
+    pub fn songs_by_artist(&self, artist_id: &str) -> Result<Vec<Song>, String> {
+        self.query(
+            "SELECT s.data FROM song_artists a JOIN songs s ON s.id = a.song_id
+             WHERE a.artist_id = ?1 ORDER BY s.title_key",
+            [artist_id],
+        )
+    }
+
+    /// 某艺人名下的专辑（走 `albums.artist_id` 索引），顺序由调用方决定。
+    pub fn albums_by_artist(&self, artist_id: &str) -> Result<Vec<Album>, String> {
+        self.query("SELECT data FROM albums WHERE artist_id = ?1", [artist_id])
+    }
+
+    /// 无损歌曲，按标题排序（不区分大小写）。
+    pub fn lossless_songs(&self) -> Result<Vec<Song>, String> {
+        self.query("SELECT data FROM songs WHERE lossless = 1 ORDER BY title_key", [])
+    }
+
+    /// 执行只返回 `data` 列的查询；无法解析的行跳过。
+    fn query<T: DeserializeOwned>(&self, sql: &str, args: impl rusqlite::Params) -> Result<Vec<T>, String> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare_cached(sql).map_err(|e| format!("查询索引库失败: {}", e))?;
+        let rows = stmt
+            .query_map(args, |row| row.get::<_, String>(0))
+            .map_err(|e| format!("查询索引库失败: {}", e))?;
+        let mut items = Vec::new();
+        for data in rows {
+            let data = data.map_err(|e| format!("查询索引库失败: {}", e))?;
+            if let Ok(item) = serde_json::from_str(&data) {
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+}
+
+fn indexed_fingerprints(tx: &Transaction, table: &str) -> Result<HashMap<String, u64>, String> {
+    let mut stmt = tx
+        .prepare(&format!("SELECT id, fingerprint FROM {}", table))
+        .map_err(|e| format!("读取索引失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)))
+        .map_err(|e| format!("读取索引失败: {}", e))?;
+    rows.collect::<Result<_, _>>().map_err(|e| format!("读取索引失败: {}", e))
+}
+
+fn str_field<'a>(value: &'a Value, field: &str) -> &'a str {
+    value.get(field).and_then(Value::as_str).unwrap_or("")
+}
+
+fn upsert_row(tx: &Transaction, table: &str, id: &str, fingerprint: u64, value: &Value) -> Result<(), String> {
+    let data = serde_json::to_string(value).map_err(|e| format!("序列化失败: {}", e))?;
+    let fingerprint = fingerprint as i64;
+    let result = match table {
+        "songs" => {
+            let lossless = value.get("lossless").and_then(Value::as_bool) == Some(true);
+            tx.execute(
+                "INSERT OR REPLACE INTO songs (id, title_key, lossless, fingerprint, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, str_field(value, "title").to_lowercase(), lossless, fingerprint, data],
+            )
+            .and_then(|_| tx.execute("DELETE FROM song_artists WHERE song_id = ?1", [id]))
+            .and_then(|_| {
+                let artist_ids = value.get("artist_ids").and_then(Value::as_array);
+                for artist_id in artist_ids.into_iter().flatten().filter_map(Value::as_str) {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO song_artists (song_id, artist_id) VALUES (?1, ?2)",
+                        [id, artist_id],
+                    )?;
+                }
+                Ok(0)
+            })
+        }
+        "albums" => tx.execute(
+            "INSERT OR REPLACE INTO albums (id, artist_id, fingerprint, data) VALUES (?1, ?2, ?3, ?4)",
+            params![id, str_field(value, "artist_id"), fingerprint, data],
+        ),
+        _ => tx.execute(
+            "INSERT OR REPLACE INTO artists (id, fingerprint, data) VALUES (?1, ?2, ?3)",
+            params![id, fingerprint, data],
+        ),
+    };
+    result.map(|_| ()).map_err(|e| format!("写入索引库失败: {}", e))
+}
+
+/// 删除一行，返回是否命中。
+fn delete_row(tx: &Transaction, table: &str, id: &str) -> Result<bool, String> {
+    let result = tx.execute(&format!("DELETE FROM {} WHERE id = ?1", table), [id]);
+    let result = match table {
+        "songs" => result.and_then(|n| tx.execute("DELETE FROM song_artists WHERE song_id = ?1", [id]).map(|_| n)),
+        _ => result,
+    };
+    result.map(|n| n > 0).map_err(|e| format!("写入索引库失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn song(id: &str, title: &str, artist_ids: &[&str], lossless: bool) -> Value {
+        let mut song = json!({ "id": id, "title": title, "artist_names": [], "artist_ids": artist_ids, "source_ids": [] });
+        if lossless {
+            song["lossless"] = json!(true);
+        }
+        song
+    }
+
+    #[test]
+    fn test_sync_lookup_and_filtered_queries() {
+        let dir = std::env::temp_dir().join(format!("chordial_library_store_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = PersistentStore::new(dir.join("music_library.json"));
+        store.set_raw(
+            songs::KEY,
+            json!({
+                "s1": song("s1", "beta", &["a1"], true),
+                "s2": song("s2", "Alpha", &["a1", "a2"], false),
+                "s3": song("s3", "alpha two", &["a2"], true),
+            }),
+        );
+        store.set_raw(albums::KEY, json!({ "al1": { "id": "al1", "title": "Album", "artist_id": "a1", "song_ids": [], "source_ids": [] } }));
+        store.set_raw(artists::KEY, json!({ "a1": { "id": "a1", "name": "Alice" }, "a2": { "id": "a2", "name": "Bob" } }));
+        store.track_entry_changes(&[songs::KEY, albums::KEY, artists::KEY]);
+
+        let db = LibraryStore::open(&dir.join("music_library.db")).unwrap();
+        assert_eq!(db.sync(&store).unwrap(), SyncReport { upserted: 6, removed: 0 });
+
+        // 按 ID
+        let s2: Song = db.get(songs::KEY, "s2").unwrap().unwrap();
+        assert_eq!(s2.title, "Alpha");
+        assert_eq!(db.get::<Album>(albums::KEY, "al1").unwrap().unwrap().title, "Album");
+        assert!(db.get::<Song>(songs::KEY, "missing").unwrap().is_none());
+
+        // 按艺人 / 无损筛选
+        let titles = |songs: Vec<Song>| songs.into_iter().map(|s| s.id).collect::<Vec<_>>();
+        assert_eq!(titles(db.songs_by_artist("a1").unwrap()), vec!["s2", "s1"]);
+        assert_eq!(titles(db.lossless_songs().unwrap()), vec!["s3", "s1"]);
+        assert_eq!(db.albums_by_artist("a1").unwrap().len(), 1);
+
+        // 单条写入只同步对应条目；删除的歌曲连同艺人关联一起移除
+        store.remove_entry(songs::KEY, "s2");
+        store.set_subkey(songs::KEY, "s3", &song("s3", "alpha two", &["a2"], false)).unwrap();
+        assert_eq!(db.sync(&store).unwrap(), SyncReport { upserted: 1, removed: 1 });
+        assert_eq!(titles(db.songs_by_artist("a2").unwrap()), vec!["s3"]);
+        assert_eq!(titles(db.lossless_songs().unwrap()), vec!["s1"]);
+        assert_eq!(db.sync(&store).unwrap(), SyncReport::default());
+
+        // 重新打开后整表比对，表与库一致时不写入
+        drop(db);
+        let db = LibraryStore::open(&dir.join("music_library.db")).unwrap();
+        assert_eq!(db.sync(&store).unwrap(), SyncReport::default());
+        assert_eq!(titles(db.songs_by_artist("a1").unwrap()), vec!["s1"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
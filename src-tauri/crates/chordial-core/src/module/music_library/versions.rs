@@ -0,0 +1,289 @@
+//! 同曲多版本 — 关联专辑版、现场版、混音、伴奏、MV 等版本，供「其他版本」面板使用。
+//!
+//! 自动关联：去掉标题中的版本标记（括号内或 ` - ` 之后的 Live、Remix、伴奏等）后标题相同，
+//! 且至少有一位相同艺人。调用方可再提供音频指纹，标出与当前歌曲属于同一录音的条目
+//! （见 [`fingerprint`](crate::module::analysis::fingerprint)）。
+//!
+//! 手动关联 / 取消关联按歌曲对双向保存；取消关联同时屏蔽自动匹配的结果。
+
+use super::{models::Song, songs};
+use crate::module::storage::persistent::PersistentStore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+pub const KEY: &str = "track_links";
+
+/// 每次查询最多为多少个版本计算指纹相似度（需要完整解码，首次查询较慢）。
+pub const MAX_COMPARED: usize = 8;
+
+/// 版本类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionKind {
+    /// 专辑 / 原版
+    Original,
+    Remaster,
+    Edit,
+    Live,
+    Acoustic,
+    Remix,
+    Instrumental,
+    Demo,
+    MusicVideo,
+}
+
+/// 版本标记：在括号或 ` - ` 之后出现时从标题中去掉。`Original` 表示只去掉、不改变版本类型。
+///
+/// ASCII 标记按整词匹配（`live` 不会命中 `alive`），中文标记按子串匹配。
+const MARKERS: &[(&str, VersionKind)] = &[
+    ("feat", VersionKind::Original),
+    ("ft", VersionKind::Original),
+    ("featuring", VersionKind::Original),
+    ("album version", VersionKind::Original),
+    ("original mix", VersionKind::Original),
+    ("music video", VersionKind::MusicVideo),
+    ("official video", VersionKind::MusicVideo),
+    ("mv", VersionKind::MusicVideo),
+    ("instrumental", VersionKind::Instrumental),
+    ("inst", VersionKind::Instrumental),
+    ("off vocal", VersionKind::Instrumental),
+    ("karaoke", VersionKind::Instrumental),
+    ("伴奏", VersionKind::Instrumental),
+    ("纯音乐", VersionKind::Instrumental),
+    ("live", VersionKind::Live),
+    ("现场", VersionKind::Live),
+    ("演唱会", VersionKind::Live),
+    ("acoustic", VersionKind::Acoustic),
+    ("unplugged", VersionKind::Acoustic),
+    ("不插电", VersionKind::Acoustic),
+    ("remix", VersionKind::Remix),
+    ("mix", VersionKind::Remix),
+    ("rmx", VersionKind::Remix),
+    ("混音", VersionKind::Remix),
+    ("remaster", VersionKind::Remaster),
+    ("remastered", VersionKind::Remaster),
+    ("重制", VersionKind::Remaster),
+    ("edit", VersionKind::Edit),
+    ("single version", VersionKind::Edit),
+    ("radio version", VersionKind::Edit),
+    ("demo", VersionKind::Demo),
+];
+
+/// 关联方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkSource {
+    /// 用户手动关联
+    Manual,
+    /// 去掉版本标记后标题相同
+    Title,
+}
+
+/// 某首歌的手动关联记录。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackLinks {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked: Vec<String>,
+    /// 手动取消关联的歌曲，不再自动匹配
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unlinked: Vec<String>,
+}
+
+/// 一个其他版本。
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackVersion {
+    pub song: Song,
+    pub kind: VersionKind,
+    pub link: LinkSource,
+    /// 与当前歌曲的指纹相似度（0–1）；未比较（非本地文件、超出比较数量等）时为 `None`
+    pub similarity: Option<f32>,
+    /// 指纹判断为同一录音（重制版、单曲版等）
+    pub same_recording: bool,
+}
+
+/// [`MusicLibrary::track_versions`](crate::module::music_library::library::MusicLibrary::track_versions) 的结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackVersions {
+    pub track_id: String,
+    /// 当前歌曲的版本类型
+    pub kind: VersionKind,
+    pub versions: Vec<TrackVersion>,
+}
+
+/// 拆出标题的主体与版本类型：`"Song (Live at Budokan)"` → `("song", Live)`。
+///
+/// 主体转小写，去掉标点并合并空白；不是版本标记的括号内容（如 `Part 2`）保留在主体中。
+pub fn parse_title(title: &str) -> (String, VersionKind) {
+    let lower = title.to_lowercase();
+    let mut kind = VersionKind::Original;
+    let mut note = |found: VersionKind| {
+        if kind == VersionKind::Original {
+            kind = found;
+        }
+    };
+
+    let mut base = String::new();
+    let mut rest = lower.as_str();
+    while let Some(open) = rest.find(['(', '[', '（', '【']) {
+        let open_char = rest[open..].chars().next().unwrap_or('(');
+        let close_char = match open_char {
+            '(' => ')',
+            '[' => ']',
+            '（' => '）',
+            _ => '】',
+        };
+        let inner = &rest[open + open_char.len_utf8()..];
+        let Some(close) = inner.find(close_char) else {
+            break;
+        };
+        base.push_str(&rest[..open]);
+        match classify(&inner[..close]) {
+            Some(found) => note(found),
+            None => {
+                base.push(' ');
+                base.push_str(&inner[..close]);
+            }
+        }
+        rest = &inner[close + close_char.len_utf8()..];
+    }
+    base.push_str(rest);
+
+    for separator in [" - ", " – ", " — "] {
+        if let Some(pos) = base.rfind(separator) {
+            if let Some(found) = classify(&base[pos + separator.len()..]) {
+                note(found);
+                base.truncate(pos);
+            }
+        }
+    }
+
+    let base = squash(&base);
+    if base.is_empty() {
+        (squash(&lower), kind)
+    } else {
+        (base, kind)
+    }
+}
+
+/// 判断一段文字是否为版本标记，返回其中第一个标记对应的类型。
+fn classify(segment: &str) -> Option<VersionKind> {
+    let words = format!(" {} ", squash(segment));
+    MARKERS.iter().find_map(|&(marker, kind)| {
+        let hit = if marker.is_ascii() {
+            words.contains(&format!(" {} ", marker))
+        } else {
+            segment.contains(marker)
+        };
+        hit.then_some(kind)
+    })
+}
+
+/// 只保留字母数字（含中日韩文字），其余字符视为分隔，合并为单个空格。
+fn squash(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 歌曲的手动关联记录。
+pub fn links(store: &PersistentStore, song_id: &str) -> TrackLinks {
+    store.get_entry(KEY, song_id).unwrap_or_default()
+}
+
+/// 手动关联（`linked = true`）或取消关联两首歌，双向生效。
+pub fn set_link(store: &PersistentStore, a: &str, b: &str, linked: bool) -> Result<(), String> {
+    if a == b {
+        return Err("不能与自身关联".to_string());
+    }
+    if !store.has(KEY) {
+        store.set(KEY, &HashMap::<String, TrackLinks>::new())?;
+    }
+    for (id, other) in [(a, b), (b, a)] {
+        let mut entry = links(store, id);
+        entry.linked.retain(|x| x != other);
+        entry.unlinked.retain(|x| x != other);
+        if linked {
+            entry.linked.push(other.to_string());
+        } else {
+            entry.unlinked.push(other.to_string());
+        }
+        store.set_subkey(KEY, id, &entry)?;
+    }
+    Ok(())
+}
+
+/// 查找歌曲的其他版本（不含指纹比较）：手动关联的在前，其余按版本类型、标题排序。
+pub fn find(store: &PersistentStore, song: &Song) -> Vec<TrackVersion> {
+    let own = links(store, &song.id);
+    let (base, _) = parse_title(&song.title);
+    let artists: HashSet<String> = song.artist_names.iter().map(|n| n.to_lowercase()).collect();
+
+    let mut seen: HashSet<String> = HashSet::new();
+    seen.insert(song.id.clone());
+    let mut versions = Vec::new();
+    for id in &own.linked {
+        if let Some(other) = songs::get(store, id) {
+            if seen.insert(other.id.clone()) {
+                versions.push(version(other, LinkSource::Manual));
+            }
+        }
+    }
+
+    let mut matched: Vec<TrackVersion> = store
+        .get_entries_filtered::<Song, _>(songs::KEY, |v| {
+            let id = v.get("id").and_then(Value::as_str).unwrap_or_default();
+            if seen.contains(id) || own.unlinked.iter().any(|x| x == id) {
+                return false;
+            }
+            let title = v.get("title").and_then(Value::as_str).unwrap_or_default();
+            if parse_title(title).0 != base {
+                return false;
+            }
+            let names: Vec<&str> = v
+                .get("artist_names")
+                .and_then(Value::as_array)
+                .map(|a| a.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            if names.is_empty() && artists.is_empty() {
+                return true;
+            }
+            names.iter().any(|n| artists.contains(&n.to_lowercase()))
+        })
+        .into_iter()
+        .map(|other| version(other, LinkSource::Title))
+        .collect();
+    matched.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.song.title.cmp(&b.song.title)));
+    versions.extend(matched);
+    versions
+}
+
+fn version(song: Song, link: LinkSource) -> TrackVersion {
+    let (_, kind) = parse_title(&song.title);
+    TrackVersion {
+        song,
+        kind,
+        link,
+        similarity: None,
+        same_recording: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_title_strips_version_markers() {
+        assert_eq!(parse_title("Yellow"), ("yellow".to_string(), VersionKind::Original));
+        assert_eq!(parse_title("Yellow (Live at Glastonbury)"), ("yellow".to_string(), VersionKind::Live));
+        assert_eq!(parse_title("Yellow - 2021 Remaster"), ("yellow".to_string(), VersionKind::Remaster));
+        assert_eq!(parse_title("晴天（伴奏）"), ("晴天".to_string(), VersionKind::Instrumental));
+        assert_eq!(parse_title("Song [Official Video] (feat. X)").1, VersionKind::MusicVideo);
+        assert_eq!(parse_title("Alive (Extended Mix)"), ("alive".to_string(), VersionKind::Remix));
+        // 不是版本标记的括号内容保留
+        assert_eq!(parse_title("Suite (Part 2)").0, "suite part 2");
+        assert_ne!(parse_title("Suite (Part 2)").0, parse_title("Suite (Part 1)").0);
+    }
+}
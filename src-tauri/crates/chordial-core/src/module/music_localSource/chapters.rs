@@ -0,0 +1,107 @@
+//! 章节读取 — 解析 M4B / M4A 有声书中的 Nero 章节表（`moov/udta/chpl`）。
+//!
+//! `chpl` 结构（与 ffmpeg 的解析一致）：
+//!
+//! | 字段 | 长度 |
+//! |------|------|
+//! | 版本 + 标志 | 1 + 3 字节；版本 ≥ 1 时另有 4 字节保留 |
+//! | 章节数 | 1 字节 |
+//! | 每章：开始时间（100 纳秒单位）、标题长度、标题（UTF-8） | 8 + 1 + n 字节 |
+//!
+//! 仅使用 QuickTime 文本轨道记录章节的文件、以及其他格式，返回空列表。
+
+use super::pictures::find_atom;
+use crate::module::music_library::books::Chapter;
+use crate::module::platform::{self, PlatformPath};
+use std::io::{Read, Seek, SeekFrom};
+
+/// `chpl` 的最大读取长度（255 章 × 最长标题也远小于此）。
+const MAX_CHPL_BYTES: u64 = 128 * 1024;
+
+/// 读取文件的章节列表。
+pub fn read_chapters(path: &PlatformPath) -> Result<Vec<Chapter>, String> {
+    let mut file = platform::open_file(path)?;
+    read_chapters_from(&mut file).map_err(|e| format!("读取章节失败: {}", e))
+}
+
+pub fn read_chapters_from<R: Read + Seek>(r: &mut R) -> std::io::Result<Vec<Chapter>> {
+    let file_end = r.seek(SeekFrom::End(0))?;
+    let (mut start, mut end) = (0u64, file_end);
+    for name in [b"moov", b"udta", b"chpl"] {
+        let Some((body_start, body_end)) = find_atom(r, start, end, name)? else {
+            return Ok(Vec::new());
+        };
+        (start, end) = (body_start, body_end);
+    }
+
+    let mut body = vec![0u8; (end - start).min(MAX_CHPL_BYTES) as usize];
+    r.seek(SeekFrom::Start(start))?;
+    r.read_exact(&mut body)?;
+    Ok(parse_chpl(&body))
+}
+
+/// 解析 `chpl` 数据区；截断的条目直接丢弃。
+fn parse_chpl(body: &[u8]) -> Vec<Chapter> {
+    let Some(&version) = body.first() else {
+        return Vec::new();
+    };
+    let mut pos = if version >= 1 { 8 } else { 4 };
+    let Some(&count) = body.get(pos) else {
+        return Vec::new();
+    };
+    pos += 1;
+
+    let mut chapters = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let Some(start) = body.get(pos..pos + 8) else {
+            break;
+        };
+        let start = u64::from_be_bytes(start.try_into().unwrap_or_default());
+        let Some(&len) = body.get(pos + 8) else {
+            break;
+        };
+        let Some(title) = body.get(pos + 9..pos + 9 + len as usize) else {
+            break;
+        };
+        chapters.push(Chapter {
+            title: String::from_utf8_lossy(title).into_owned(),
+            start_ms: start / 10_000,
+        });
+        pos += 9 + len as usize;
+    }
+    chapters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(name);
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn test_read_nero_chapters() {
+        let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+        for (start_ms, title) in [(0u64, "Prologue"), (95_500, "第一章")] {
+            chpl.extend_from_slice(&(start_ms * 10_000).to_be_bytes());
+            chpl.push(title.len() as u8);
+            chpl.extend_from_slice(title.as_bytes());
+        }
+        let moov = atom(b"moov", &atom(b"udta", &atom(b"chpl", &chpl)));
+        let mut file = atom(b"ftyp", b"M4B ");
+        file.extend(moov);
+
+        let chapters = read_chapters_from(&mut Cursor::new(file)).unwrap();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[1].title, "第一章");
+        assert_eq!(chapters[1].start_ms, 95_500);
+
+        let plain = atom(b"ftyp", b"M4A ");
+        assert!(read_chapters_from(&mut Cursor::new(plain)).unwrap().is_empty());
+    }
+}
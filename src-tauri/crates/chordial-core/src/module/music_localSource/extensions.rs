@@ -0,0 +1,177 @@
+//! 扩展名设置 — 决定哪些文件被当作音频扫描。
+//!
+//! 内置支持的扩展名见 [`scanner::is_supported_extension`](super::scanner::is_supported_extension)。
+//! 在此之上：
+//! - **全局别名**：把额外的扩展名当作某个内置格式，例如 `.m4b` 有声书按 M4A 处理、`.oga` 按 OGG 处理
+//! - **按文件夹覆盖**：某个监听文件夹追加自己的别名，或排除某些扩展名（如只放分轨素材的文件夹排除 `.wav`）
+//!
+//! 文件格式最终由 symphonia 按内容探测，别名只决定文件能否进入扫描。
+
+use super::scanner;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 单个监听文件夹的扩展名覆盖。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderExtensions {
+    /// 文件夹的规范路径
+    pub folder: String,
+    /// 仅在此文件夹内生效的额外别名（扩展名 → 内置扩展名）
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// 在此文件夹内不扫描的扩展名
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl FolderExtensions {
+    fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.exclude.is_empty()
+    }
+}
+
+/// 扩展名设置（持久化）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtensionSettings {
+    /// 全局别名：扩展名 → 内置扩展名
+    pub aliases: BTreeMap<String, String>,
+    /// 按文件夹覆盖
+    #[serde(default)]
+    pub folders: Vec<FolderExtensions>,
+}
+
+impl Default for ExtensionSettings {
+    fn default() -> Self {
+        let aliases = [("m4b", "m4a"), ("oga", "ogg"), ("aif", "aiff")]
+            .into_iter()
+            .map(|(ext, target)| (ext.to_string(), target.to_string()))
+            .collect();
+        Self {
+            aliases,
+            folders: Vec::new(),
+        }
+    }
+}
+
+impl ExtensionSettings {
+    /// 判断扩展名（不含点，大小写不敏感）是否受支持。`folder` 为文件所在监听文件夹的覆盖设置。
+    pub fn is_supported_extension(&self, ext: &str, folder: Option<&FolderExtensions>) -> bool {
+        let ext = ext.to_lowercase();
+        if let Some(folder) = folder {
+            if folder.exclude.contains(&ext) {
+                return false;
+            }
+            if folder.aliases.contains_key(&ext) {
+                return true;
+            }
+        }
+        scanner::is_supported_extension(&ext) || self.aliases.contains_key(&ext)
+    }
+
+    /// 找出覆盖 `path`（规范路径字符串）的文件夹设置；嵌套时取最深的一层。
+    pub fn folder_for(&self, path: &str) -> Option<&FolderExtensions> {
+        self.folders
+            .iter()
+            .filter(|f| {
+                path.strip_prefix(f.folder.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+            })
+            .max_by_key(|f| f.folder.len())
+    }
+
+    /// 设置或移除（`target` 为 `None`）一个全局别名。
+    pub fn set_alias(&mut self, ext: &str, target: Option<&str>) -> Result<(), String> {
+        let ext = normalize(ext)?;
+        match target {
+            Some(target) => {
+                let target = check_alias(&ext, target)?;
+                self.aliases.insert(ext, target);
+            }
+            None => {
+                self.aliases.remove(&ext);
+            }
+        }
+        Ok(())
+    }
+
+    /// 替换某个文件夹的覆盖设置；别名与排除列表都为空时移除该文件夹的覆盖。
+    pub fn set_folder(&mut self, mut entry: FolderExtensions) -> Result<(), String> {
+        entry.aliases = entry
+            .aliases
+            .iter()
+            .map(|(ext, target)| {
+                let ext = normalize(ext)?;
+                let target = check_alias(&ext, target)?;
+                Ok((ext, target))
+            })
+            .collect::<Result<_, String>>()?;
+        entry.exclude = entry
+            .exclude
+            .iter()
+            .map(|ext| normalize(ext))
+            .collect::<Result<_, String>>()?;
+        entry.exclude.sort();
+        entry.exclude.dedup();
+
+        self.folders.retain(|f| f.folder != entry.folder);
+        if !entry.is_empty() {
+            self.folders.push(entry);
+        }
+        Ok(())
+    }
+}
+
+/// 去掉前导点并转小写；空扩展名或含路径分隔符时报错。
+fn normalize(ext: &str) -> Result<String, String> {
+    let ext = ext.trim().trim_start_matches('.').to_lowercase();
+    if ext.is_empty() || ext.contains(['/', '\\', '.']) {
+        return Err(format!("无效的扩展名: '{}'", ext));
+    }
+    Ok(ext)
+}
+
+/// 别名必须指向内置格式，且不能重新映射内置扩展名。
+fn check_alias(ext: &str, target: &str) -> Result<String, String> {
+    let target = normalize(target)?;
+    if scanner::is_supported_extension(ext) {
+        return Err(format!("'.{}' 已是内置支持的格式，无需别名", ext));
+    }
+    if !scanner::is_supported_extension(&target) {
+        return Err(format!("'.{}' 不是受支持的音频格式", target));
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_and_folder_overrides() {
+        let mut settings = ExtensionSettings::default();
+        assert!(settings.is_supported_extension("M4B", None));
+        assert!(!settings.is_supported_extension("mka", None));
+        assert!(settings.set_alias("flac", Some("ogg")).is_err());
+        assert!(settings.set_alias("mka", Some("txt")).is_err());
+
+        let mut stems = FolderExtensions {
+            folder: "/music/stems".into(),
+            exclude: vec![".WAV".into()],
+            ..Default::default()
+        };
+        stems.aliases.insert("mka".into(), "flac".into());
+        settings.set_folder(stems).unwrap();
+
+        let folder = settings.folder_for("/music/stems/a/b.wav");
+        assert!(folder.is_some());
+        assert!(settings.folder_for("/music/stems2/b.wav").is_none());
+        assert!(!settings.is_supported_extension("wav", folder));
+        assert!(settings.is_supported_extension("mka", folder));
+        assert!(settings.is_supported_extension("wav", None));
+
+        settings
+            .set_folder(FolderExtensions { folder: "/music/stems".into(), ..Default::default() })
+            .unwrap();
+        assert!(settings.folders.is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! 文件内评分 / 播放次数 — 读取其他播放器写入的评分标签，并可把 Chordial 的评分写回文件。
+//!
+//! | 格式 | 评分 | 播放次数 |
+//! |------|------|----------|
+//! | ID3v2 | `POPM`（0–255） | `POPM` 计数 / `PCNT` |
+//! | Vorbis comment | `FMPS_RATING`（0.0–1.0）/ `RATING`（0–100） | `FMPS_PLAYCOUNT` |
+//!
+//! 评分统一换算为 0–100。`POPM` 按 foobar2000 / MusicBee / Windows Media Player
+//! 通用的五星刻度（1 / 64 / 128 / 196 / 255）换算，写回时同样取这五个值。
+//!
+//! 写回只支持 FLAC 与带（或可新建）ID3v2.3 / v2.4 标签的文件，重写逻辑与
+//! [`tag_writer`] 共用：新标签能放进原有空间（含填充）时原地覆盖，否则重写到临时文件后替换原文件。
+
+use super::tag_writer;
+use crate::module::platform::{self, PlatformPath};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use symphonia::core::meta::{RawValue, StandardTag, Tag};
+
+/// 写入 `POPM` 时使用的用户标识；其他播放器普遍识别这一条。
+const POPM_EMAIL: &str = "Windows Media Player 9 Series";
+
+/// 五星刻度对应的 `POPM` 值（下标为星数）。
+const POPM_STARS: [u8; 6] = [0, 1, 64, 128, 196, 255];
+
+/// 文件中记录的评分与播放次数。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FileStats {
+    /// 评分（0–100）；0 或缺失视为未评分
+    pub rating: Option<u8>,
+    pub play_count: Option<u64>,
+}
+
+/// 从标签中读取评分与播放次数；同一项出现多次时评分取第一条，播放次数取最大值。
+pub fn from_tags(tags: &[Tag]) -> FileStats {
+    let mut stats = FileStats::default();
+    for tag in tags {
+        if stats.rating.is_none() {
+            stats.rating = tag_rating(tag);
+        }
+        if let Some(count) = tag_play_count(tag) {
+            stats.play_count = Some(stats.play_count.unwrap_or(0).max(count));
+        }
+    }
+    stats
+}
+
+/// 是否为评分 / 播放次数标签（这些标签不再计入 `extra_tags`）。
+pub fn is_stats_tag(tag: &Tag) -> bool {
+    matches!(
+        tag.std,
+        Some(StandardTag::Rating(_) | StandardTag::PlayCounter(_))
+    ) || matches!(
+        tag.raw.key.to_ascii_uppercase().as_str(),
+        "POPM" | "PCNT" | "FMPS_RATING" | "FMPS_PLAYCOUNT"
+    )
+}
+
+/// `POPM` 评分（0–255）→ 0–100，按五星区间取整。
+pub fn popm_to_rating(popm: u8) -> Option<u8> {
+    let stars = match popm {
+        0 => return None,
+        1..=31 => 1,
+        32..=95 => 2,
+        96..=159 => 3,
+        160..=223 => 4,
+        _ => 5,
+    };
+    Some(stars * 20)
+}
+
+/// 0–100 → `POPM` 评分，四舍五入到最近的星数。
+pub fn rating_to_popm(rating: u8) -> u8 {
+    POPM_STARS[((rating.min(100) as usize) + 10) / 20]
+}
+
+fn tag_rating(tag: &Tag) -> Option<u8> {
+    match tag.raw.key.to_ascii_uppercase().as_str() {
+        "POPM" => match tag.raw.value {
+            RawValue::UnsignedInt(v) => popm_to_rating(v.min(255) as u8),
+            _ => None,
+        },
+        "FMPS_RATING" => raw_number(&tag.raw.value)
+            .filter(|v| (0.0..=1.0).contains(v) && *v > 0.0)
+            .map(|v| (v * 100.0).round() as u8),
+        _ => match tag.std {
+            Some(StandardTag::Rating(ppm)) if ppm > 0 => Some((ppm / 10_000).min(100) as u8),
+            _ => None,
+        },
+    }
+}
+
+fn tag_play_count(tag: &Tag) -> Option<u64> {
+    if let Some(StandardTag::PlayCounter(count)) = tag.std {
+        return Some(count);
+    }
+    match tag.raw.key.to_ascii_uppercase().as_str() {
+        "POPM" => tag.raw.sub_fields.as_deref()?.iter().find_map(|f| {
+            match (f.field.as_str(), &f.value) {
+                ("PLAY_COUNTER", RawValue::UnsignedInt(count)) => Some(*count),
+                _ => None,
+            }
+        }),
+        "FMPS_PLAYCOUNT" => raw_number(&tag.raw.value)
+            .filter(|v| *v >= 0.0)
+            .map(|v| v.round() as u64),
+        _ => None,
+    }
+}
+
+fn raw_number(value: &RawValue) -> Option<f64> {
+    match value {
+        RawValue::String(s) => s.trim().parse().ok(),
+        RawValue::Float(v) => Some(*v),
+        RawValue::UnsignedInt(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+// ── 写回 ──────────────────────────────────────────
+
+/// 把评分与播放次数写回文件。
+///
+/// `rating` 为 `None` 时移除文件中的评分；播放次数为 `None` 时不写。
+pub fn write_file_stats(path: &PlatformPath, stats: &FileStats) -> Result<(), String> {
+    let path = PathBuf::from(platform::path_to_string(path));
+    let mut file = File::open(&path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let mut magic = [0u8; 4];
+    file.read_exact(&mut magic)
+        .map_err(|e| format!("读取文件头失败: {}", e))?;
+    file.seek(SeekFrom::Start(0)).map_err(tag_writer::io_err)?;
+
+    if &magic == b"fLaC" {
+        write_flac(&path, file, stats)
+    } else if &magic[..3] == b"ID3" || is_mpeg_extension(&path) {
+        write_id3(&path, file, stats)
+    } else {
+        Err("暂不支持向该格式写回评分".to_string())
+    }
+}
+
+fn is_mpeg_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("mp3"))
+}
+
+// ── FLAC ──────────────────────────────────────────
+
+fn write_flac(path: &Path, file: File, stats: &FileStats) -> Result<(), String> {
+    tag_writer::flac::rewrite_comment(path, file, |comment| {
+        comment.remove_where(|key| {
+            key.eq_ignore_ascii_case(b"FMPS_RATING")
+                || (stats.play_count.is_some() && key.eq_ignore_ascii_case(b"FMPS_PLAYCOUNT"))
+        });
+        if let Some(rating) = stats.rating.filter(|r| *r > 0) {
+            comment.push("FMPS_RATING", &fmps_value(rating));
+        }
+        if let Some(count) = stats.play_count {
+            comment.push("FMPS_PLAYCOUNT", &count.to_string());
+        }
+        Ok(())
+    })
+}
+
+/// 0–100 → FMPS 的 0.0–1.0 文本。
+fn fmps_value(rating: u8) -> String {
+    let text = format!("{:.2}", rating.min(100) as f64 / 100.0);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// ── ID3v2 ─────────────────────────────────────────
+
+/// 没有标签时新建 v2.3：Windows 资源管理器等只认 v2.3 的 `POPM`。
+fn write_id3(path: &Path, file: File, stats: &FileStats) -> Result<(), String> {
+    tag_writer::id3::rewrite_frames(path, file, 3, |_, frames| {
+        frames.retain(|(id, _, _)| id != b"POPM" && (stats.play_count.is_none() || id != b"PCNT"));
+        let popm_rating = stats.rating.map_or(0, rating_to_popm);
+        if popm_rating > 0 || stats.play_count.is_some() {
+            let mut data = POPM_EMAIL.as_bytes().to_vec();
+            data.push(0);
+            data.push(popm_rating);
+            if let Some(count) = stats.play_count {
+                data.extend_from_slice(&(count.min(u32::MAX as u64) as u32).to_be_bytes());
+            }
+            frames.push((*b"POPM", [0, 0], data));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_localSource::scanner;
+    use crate::module::playback::flac::FlacWriter;
+
+    #[test]
+    fn test_flac_rating_round_trip() {
+        let dir = std::env::temp_dir().join(format!("chordial_stats_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rated.flac");
+        let mut writer = FlacWriter::create(&path, 8000).unwrap();
+        writer.write(&vec![0.25; 8000 * 2]).unwrap();
+        writer.finish().unwrap();
+
+        let stats = FileStats {
+            rating: Some(80),
+            play_count: Some(12),
+        };
+        write_file_stats(&path, &stats).unwrap();
+        let meta = scanner::probe_file(&path).unwrap();
+        assert_eq!(meta.rating, Some(80));
+        assert_eq!(meta.play_count, Some(12));
+
+        // 第二次写入可放进填充区，原地覆盖
+        let len_before = std::fs::metadata(&path).unwrap().len();
+        write_file_stats(
+            &path,
+            &FileStats {
+                rating: Some(40),
+                play_count: Some(13),
+            },
+        )
+        .unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), len_before);
+        let meta = scanner::probe_file(&path).unwrap();
+        assert_eq!((meta.rating, meta.play_count), (Some(40), Some(13)));
+        assert_eq!(rating_to_popm(80), 196);
+        assert_eq!(popm_to_rating(196), Some(80));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,129 @@
+//! 文件指纹 — 重扫时判断文件是否需要重新读取元数据。
+//!
+//! 指纹由 (mtime, 文件大小, 文件头部哈希) 组成，随文件对应的歌曲 ID 一起保存在
+//! `local_source_file_mtimes.json`：
+//!
+//! - mtime 与大小都没变：未改动，不读文件
+//! - 大小变了：已改动，重新探测
+//! - 只有 mtime 变了：比较头部哈希（前 [`HEAD_BYTES`] 字节 + 末尾 [`TAIL_BYTES`] 字节），
+//!   一致时视为「被碰过」（同步工具 / 备份还原改写了 mtime），只更新记录的 mtime
+//!
+//! 标签几乎都在这两段里（ID3v2 / FLAC / Vorbis 在头部，ID3v1 / APE 在尾部），
+//! 因此标签编辑总会改变哈希。旧版本只记录 (mtime, 大小)，没有哈希的条目按 mtime 判断。
+
+use crate::module::platform::{self, PlatformPath};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, SeekFrom};
+
+/// 参与哈希的文件头部字节数。
+pub const HEAD_BYTES: u64 = 64 * 1024;
+
+/// 参与哈希的文件尾部字节数（ID3v1 标签的长度）。
+pub const TAIL_BYTES: u64 = 128;
+
+/// 一个已索引文件的指纹。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    pub mtime: u64,
+    pub size: u64,
+    /// 头尾字节的 FNV-1a 哈希；旧版本迁移来的条目为 `None`
+    #[serde(default)]
+    pub head_hash: Option<u64>,
+    /// 文件对应的库内歌曲 ID
+    pub song_id: String,
+}
+
+/// 与当前文件比较的结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerprintMatch {
+    Unchanged,
+    /// 只有 mtime 变了，内容一致
+    Touched { mtime: u64 },
+    Changed,
+}
+
+impl FileFingerprint {
+    /// 读取文件当前的指纹；文件不可访问时返回 `None`。
+    pub fn read(path: &PlatformPath, song_id: &str) -> Option<Self> {
+        Some(Self {
+            mtime: platform::file_modified_secs(path).ok()?,
+            size: platform::file_size(path).ok()?,
+            head_hash: head_hash(path),
+            song_id: song_id.to_string(),
+        })
+    }
+
+    /// 与文件当前状态比较；文件不可访问时视为已改动。
+    pub fn compare(&self, path: &PlatformPath) -> FingerprintMatch {
+        let (Ok(mtime), Ok(size)) = (platform::file_modified_secs(path), platform::file_size(path)) else {
+            return FingerprintMatch::Changed;
+        };
+        if size != self.size {
+            return FingerprintMatch::Changed;
+        }
+        if mtime == self.mtime {
+            return FingerprintMatch::Unchanged;
+        }
+        match self.head_hash {
+            Some(hash) if head_hash(path) == Some(hash) => FingerprintMatch::Touched { mtime },
+            _ => FingerprintMatch::Changed,
+        }
+    }
+}
+
+/// 文件前 [`HEAD_BYTES`] 字节与末尾 [`TAIL_BYTES`] 字节的 FNV-1a 哈希。
+pub fn head_hash(path: &PlatformPath) -> Option<u64> {
+    let mut file = platform::open_file(path).ok()?;
+    let mut head = Vec::with_capacity(HEAD_BYTES as usize);
+    (&mut file).take(HEAD_BYTES).read_to_end(&mut head).ok()?;
+    let mut hash = fnv1a(0xcbf2_9ce4_8422_2325, &head);
+    if head.len() as u64 == HEAD_BYTES {
+        let mut tail = Vec::with_capacity(TAIL_BYTES as usize);
+        file.seek(SeekFrom::End(-(TAIL_BYTES as i64))).ok()?;
+        file.read_to_end(&mut tail).ok()?;
+        hash = fnv1a(hash, &tail);
+    }
+    Some(hash)
+}
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_touched_vs_changed() {
+        let path = std::env::temp_dir().join(format!("chordial_fingerprint_{}.mp3", std::process::id()));
+        let mut data = vec![0u8; HEAD_BYTES as usize + 4096];
+        fs::write(&path, &data).unwrap();
+        let platform_path = PlatformPath::from(path.clone());
+        let fingerprint = FileFingerprint::read(&platform_path, "song").unwrap();
+        assert_eq!(fingerprint.compare(&platform_path), FingerprintMatch::Unchanged);
+
+        // 只改 mtime：内容一致
+        let later = SystemTime::now() + Duration::from_secs(120);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(matches!(fingerprint.compare(&platform_path), FingerprintMatch::Touched { .. }));
+
+        // 大小不变、改写末尾（如 ID3v1 标签）：视为改动
+        let len = data.len();
+        data[len - 10] = 1;
+        fs::write(&path, &data).unwrap();
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(fingerprint.compare(&platform_path), FingerprintMatch::Changed);
+
+        // 没有哈希的旧条目只能按 mtime 判断
+        let legacy = FileFingerprint { head_hash: None, ..fingerprint };
+        assert_eq!(legacy.compare(&platform_path), FingerprintMatch::Changed);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -5,6 +5,8 @@
 //! - 提供增删查接口
 //! - 在添加新文件夹时自动扫描已有文件
 //! - 在移除文件夹时级联清理音乐库
+//! - 保存扫描用的扩展名设置（见 [`super::extensions`]）
+//! - 保存各文件夹的访问设置（只读 / 仅元数据，见 [`FolderAccess`]）
 //!
 //! ## 跨平台
 //!
@@ -12,17 +14,41 @@
 //! - 桌面端：`PathBuf` → `std::fs`
 //! - Android：`String`（content URI）→ JNI 桥接
 
+use super::extensions::{ExtensionSettings, FolderExtensions};
 use crate::module::platform::{self, PlatformPath};
 use crate::module::perf;
 use crate::module::storage::persistent::PersistentStore;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 持久化的文件夹条目。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FolderEntry {
     /// 用户添加的原始路径
     pub path: String,
+    /// 访问设置；旧版本的条目没有这些字段，按默认（可写、可播放）处理
+    #[serde(flatten)]
+    pub access: FolderAccess,
+}
+
+/// 文件夹的访问设置，作用于其中所有文件（含子目录）。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FolderAccess {
+    /// 只读：不向其中的文件写入标签 / 评分（如别人共享的文件夹）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// 仅元数据：照常索引，但不提供音频（如只作归档的网络共享），播放、预加载与音频分析都跳过
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub metadata_only: bool,
+}
+
+/// 一个文件夹及其访问设置，供前端列出。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FolderAccessEntry {
+    pub folder: String,
+    #[serde(flatten)]
+    pub access: FolderAccess,
 }
 
 /// 文件夹管理器。
@@ -33,10 +59,15 @@ pub struct FolderManager {
     store: PersistentStore,
     /// 运行时文件夹集合（规范路径）
     folders: RwLock<Vec<PlatformPath>>,
+    /// 扩展名设置
+    extensions: RwLock<ExtensionSettings>,
+    /// 各文件夹（规范路径）的访问设置；不在表中的为默认
+    access: RwLock<HashMap<PlatformPath, FolderAccess>>,
 }
 
 impl FolderManager {
     const KEY: &str = "local_source_folders";
+    const EXTENSIONS_KEY: &str = "local_source_extensions";
 
     /// 创建文件夹管理器，从持久化存储加载已有文件夹列表。
     ///
@@ -47,16 +78,29 @@ impl FolderManager {
             .get::<Vec<FolderEntry>>(Self::KEY)
             .unwrap_or_default();
 
-        let folders: Vec<PlatformPath> = entries
-            .iter()
-            .map(|e| PlatformPath::from(e.path.as_str()))
-            .filter(|p| platform::exists(p))
-            .map(|p| platform::canonicalize(&p).unwrap_or_else(|_| p))
-            .collect();
+        let mut folders: Vec<PlatformPath> = Vec::with_capacity(entries.len());
+        let mut access = HashMap::new();
+        for entry in &entries {
+            let path = PlatformPath::from(entry.path.as_str());
+            if !platform::exists(&path) {
+                continue;
+            }
+            let canonical = platform::canonicalize(&path).unwrap_or(path);
+            if entry.access != FolderAccess::default() {
+                access.insert(canonical.clone(), entry.access);
+            }
+            folders.push(canonical);
+        }
+
+        let extensions = store
+            .get::<ExtensionSettings>(Self::EXTENSIONS_KEY)
+            .unwrap_or_default();
 
         Self {
             store,
             folders: RwLock::new(folders),
+            extensions: RwLock::new(extensions),
+            access: RwLock::new(access),
         }
     }
 
@@ -77,6 +121,112 @@ impl FolderManager {
         self.folders.read().iter().any(|f| *f == canonical)
     }
 
+    /// 检查路径是否位于某个监听文件夹之内（含子目录）。
+    ///
+    /// 输入先规范化，`..` 与符号链接都按解析后的真实位置判断；无法规范化（不存在）的路径一律视为不在其中。
+    pub fn contains_path(&self, path: &PlatformPath) -> bool {
+        let Ok(canonical) = platform::canonicalize(path) else {
+            return false;
+        };
+        self.folders
+            .read()
+            .iter()
+            .any(|f| platform::path_starts_with(&canonical, f))
+    }
+
+    /// 按扩展名设置判断文件是否应作为音频扫描：先看所在监听文件夹的覆盖，再看全局别名与内置格式。
+    pub fn is_supported_file(&self, path: &PlatformPath) -> bool {
+        let Some(ext) = platform::path_extension(path) else {
+            return false;
+        };
+        let extensions = self.extensions.read();
+        let folder = extensions.folder_for(&platform::path_to_string(path));
+        extensions.is_supported_extension(&ext, folder)
+    }
+
+    /// 递归收集 `root` 下所有应扫描的音频文件（按 [`is_supported_file`](Self::is_supported_file) 过滤）。
+    pub fn collect_audio_files(&self, root: &PlatformPath) -> Vec<PlatformPath> {
+        collect_audio_files(root, |path| self.is_supported_file(path))
+    }
+
+    /// 当前的扩展名设置。
+    pub fn extension_settings(&self) -> ExtensionSettings {
+        self.extensions.read().clone()
+    }
+
+    /// 设置或移除（`target` 为 `None`）一个全局扩展名别名，并持久化。
+    pub fn set_extension_alias(&self, ext: &str, target: Option<&str>) -> Result<ExtensionSettings, String> {
+        self.update_extensions(|e| e.set_alias(ext, target))
+    }
+
+    /// 替换某个监听文件夹的扩展名覆盖，并持久化。
+    pub fn set_folder_extensions(&self, mut entry: FolderExtensions) -> Result<ExtensionSettings, String> {
+        let path = PlatformPath::from(entry.folder.as_str());
+        let canonical = platform::canonicalize(&path).unwrap_or(path);
+        if !self.folders.read().contains(&canonical) {
+            return Err(format!("'{}' 不是已添加的音乐文件夹", entry.folder));
+        }
+        entry.folder = platform::path_to_string(&canonical);
+        self.update_extensions(|e| e.set_folder(entry))
+    }
+
+    fn update_extensions<F>(&self, f: F) -> Result<ExtensionSettings, String>
+    where
+        F: FnOnce(&mut ExtensionSettings) -> Result<(), String>,
+    {
+        let mut extensions = self.extensions.write();
+        let mut updated = extensions.clone();
+        f(&mut updated)?;
+        self.store.set(Self::EXTENSIONS_KEY, &updated)?;
+        self.store.save()?;
+        *extensions = updated.clone();
+        Ok(updated)
+    }
+
+    /// 文件所在监听文件夹的访问设置；有嵌套时取最内层的文件夹，不在任何文件夹内时为默认。
+    pub fn access_for(&self, path: &PlatformPath) -> FolderAccess {
+        let access = self.access.read();
+        if access.is_empty() {
+            return FolderAccess::default();
+        }
+        access
+            .iter()
+            .filter(|(folder, _)| platform::path_starts_with(path, folder))
+            .max_by_key(|(folder, _)| platform::path_to_string(folder).len())
+            .map(|(_, a)| *a)
+            .unwrap_or_default()
+    }
+
+    /// 所有监听文件夹及各自的访问设置。
+    pub fn folder_access(&self) -> Vec<FolderAccessEntry> {
+        let access = self.access.read();
+        self.folders
+            .read()
+            .iter()
+            .map(|folder| FolderAccessEntry {
+                folder: platform::path_to_string(folder),
+                access: access.get(folder).copied().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// 设置某个监听文件夹的访问设置，并持久化。
+    pub fn set_folder_access(&self, folder: &PlatformPath, access: FolderAccess) -> Result<(), String> {
+        let canonical = platform::canonicalize(folder).unwrap_or_else(|_| folder.clone());
+        if !self.folders.read().contains(&canonical) {
+            return Err(format!("'{}' 不是已添加的音乐文件夹", platform::path_to_string(folder)));
+        }
+        {
+            let mut map = self.access.write();
+            if access == FolderAccess::default() {
+                map.remove(&canonical);
+            } else {
+                map.insert(canonical, access);
+            }
+        }
+        self.save()
+    }
+
     /// 获取文件夹数量。
     pub fn count(&self) -> usize {
         self.folders.read().len()
@@ -125,9 +275,17 @@ impl FolderManager {
         folders.retain(|f| *f != canonical);
         let removed = folders.len() < len_before;
         drop(folders);
+        self.access.write().remove(&canonical);
 
         if removed {
             let _ = self.save();
+            let folder = platform::path_to_string(&canonical);
+            if self.extensions.read().folders.iter().any(|f| f.folder == folder) {
+                let _ = self.update_extensions(|e| {
+                    e.folders.retain(|f| f.folder != folder);
+                    Ok(())
+                });
+            }
         }
         removed
     }
@@ -136,14 +294,17 @@ impl FolderManager {
 
     /// 保存当前文件夹列表到磁盘。
     fn save(&self) -> Result<(), String> {
+        let access = self.access.read();
         let entries: Vec<FolderEntry> = self
             .folders
             .read()
             .iter()
             .map(|p| FolderEntry {
                 path: platform::path_to_string(p),
+                access: access.get(p).copied().unwrap_or_default(),
             })
             .collect();
+        drop(access);
         self.store.set(Self::KEY, &entries)?;
         self.store.save()
     }
@@ -156,23 +317,62 @@ impl FolderManager {
 
 /// 递归收集文件夹下所有受支持的音频文件。
 ///
-/// 遍历 `root` 目录及其所有子目录，返回所有扩展名匹配的音频文件路径。
-pub fn collect_audio_files(root: &PlatformPath) -> Vec<PlatformPath> {
+/// 遍历 `root` 目录及其所有子目录，返回所有通过 `is_audio` 过滤的文件路径。
+/// 扫描监听文件夹时用 [`FolderManager::collect_audio_files`]，它按扩展名设置过滤。
+pub fn collect_audio_files<F>(root: &PlatformPath, is_audio: F) -> Vec<PlatformPath>
+where
+    F: Fn(&PlatformPath) -> bool,
+{
     // 预分配：按典型音乐库规模给个初值，减少扩容次数
     let mut files = Vec::with_capacity(256);
-    collect_audio_files_recursive(root, &mut files);
+    collect_audio_files_recursive(root, &is_audio, &mut files);
     files
 }
 
-fn collect_audio_files_recursive(dir: &PlatformPath, files: &mut Vec<PlatformPath>) {
+fn collect_audio_files_recursive<F>(dir: &PlatformPath, is_audio: &F, files: &mut Vec<PlatformPath>)
+where
+    F: Fn(&PlatformPath) -> bool,
+{
     let _scope = perf::scope("folder.collect_audio_files");
     if let Ok(entries) = platform::read_dir_entries_with_file_type(dir) {
         for (entry, is_dir) in entries {
             if is_dir {
-                collect_audio_files_recursive(&entry, files);
-            } else if super::scanner::is_supported_audio(&entry) {
+                collect_audio_files_recursive(&entry, is_audio, files);
+            } else if is_audio(&entry) {
                 files.push(entry);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_folder_access_persists_and_applies_to_subfolders() {
+        let root = std::env::temp_dir().join(format!("chordial_folder_access_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let (music, archive) = (root.join("music"), root.join("archive"));
+        fs::create_dir_all(archive.join("disc1")).unwrap();
+        fs::create_dir_all(&music).unwrap();
+        let store_path = root.join("folders.json");
+
+        let manager = FolderManager::new(PersistentStore::new(store_path.clone()));
+        manager.add_folder(&PlatformPath::from(music.clone())).unwrap();
+        manager.add_folder(&PlatformPath::from(archive.clone())).unwrap();
+        let archived = FolderAccess { read_only: true, metadata_only: true };
+        manager.set_folder_access(&PlatformPath::from(archive.clone()), archived).unwrap();
+        assert!(manager.set_folder_access(&PlatformPath::from(root.clone()), archived).is_err());
+
+        // 重新加载后仍生效，且作用于子目录中的文件
+        let manager = FolderManager::new(PersistentStore::new(store_path));
+        let track = platform::canonicalize(&PlatformPath::from(archive.join("disc1"))).unwrap().join("01.flac");
+        assert_eq!(manager.access_for(&track), archived);
+        let other = platform::canonicalize(&PlatformPath::from(music.clone())).unwrap().join("a.mp3");
+        assert_eq!(manager.access_for(&other), FolderAccess::default());
+        assert_eq!(manager.folder_access().iter().filter(|e| e.access == archived).count(), 1);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,149 @@
+//! 文件末尾的 ID3v2 标签 — ID3v2.4 允许把标签追加在音频数据之后，并以 10 字节的 `3DI` 页脚结尾。
+//!
+//! symphonia 只在流的开头探测 ID3v2，这样写标签的文件（部分流式录音工具、只追加不重写文件的标签器）
+//! 读出来没有任何元数据。这里从文件末尾找页脚、反推标签起点，整段交给 symphonia 的 ID3v2 解析器。
+//!
+//! 页脚之后允许再跟 ID3v1（128 字节 `TAG`）和 / 或 APEv2 标签，定位时依次跳过。
+
+use crate::module::platform::{self, PlatformPath};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataReader, MetadataRevision};
+use symphonia::default::meta::Id3v2Reader;
+
+const FOOTER_LEN: u64 = 10;
+const ID3V1_LEN: u64 = 128;
+const APE_FOOTER_LEN: u64 = 32;
+
+/// 在文件末尾定位追加的 ID3v2 标签，返回标签（含头与页脚）在文件中的字节范围。
+pub fn locate<R: Read + Seek>(r: &mut R) -> std::io::Result<Option<Range<u64>>> {
+    let len = r.seek(SeekFrom::End(0))?;
+    let mut end = len;
+
+    if end >= ID3V1_LEN {
+        let mut magic = [0u8; 3];
+        r.seek(SeekFrom::Start(end - ID3V1_LEN))?;
+        r.read_exact(&mut magic)?;
+        if &magic == b"TAG" {
+            end -= ID3V1_LEN;
+        }
+    }
+    if end >= APE_FOOTER_LEN {
+        let mut footer = [0u8; APE_FOOTER_LEN as usize];
+        r.seek(SeekFrom::Start(end - APE_FOOTER_LEN))?;
+        r.read_exact(&mut footer)?;
+        if &footer[..8] == b"APETAGEX" {
+            // 大小含页脚不含头；flags 第 31 位表示有头
+            let size = u32::from_le_bytes([footer[12], footer[13], footer[14], footer[15]]) as u64;
+            let flags = u32::from_le_bytes([footer[20], footer[21], footer[22], footer[23]]);
+            let total = size + if flags & 0x8000_0000 != 0 { APE_FOOTER_LEN } else { 0 };
+            end = end.saturating_sub(total);
+        }
+    }
+
+    if end < FOOTER_LEN * 2 {
+        return Ok(None);
+    }
+    let mut footer = [0u8; FOOTER_LEN as usize];
+    r.seek(SeekFrom::Start(end - FOOTER_LEN))?;
+    r.read_exact(&mut footer)?;
+    let Some(size) = parse_footer(&footer) else {
+        return Ok(None);
+    };
+    let Some(start) = end.checked_sub(size + FOOTER_LEN * 2) else {
+        return Ok(None);
+    };
+
+    // 页脚与标签头应互为镜像（仅首 3 字节不同）
+    let mut header = [0u8; FOOTER_LEN as usize];
+    r.seek(SeekFrom::Start(start))?;
+    r.read_exact(&mut header)?;
+    if &header[..3] != b"ID3" || header[3..] != footer[3..] {
+        return Ok(None);
+    }
+    Ok(Some(start..end))
+}
+
+/// 解析 `3DI` 页脚，返回标签体大小（不含头与页脚）。
+fn parse_footer(footer: &[u8; FOOTER_LEN as usize]) -> Option<u64> {
+    let valid = &footer[..3] == b"3DI"
+        && footer[3] == 4
+        && footer[5] & 0x10 != 0
+        && footer[6..].iter().all(|b| b & 0x80 == 0);
+    valid.then(|| footer[6..].iter().fold(0u64, |acc, &b| (acc << 7) | b as u64))
+}
+
+/// 读取并解析文件末尾的 ID3v2 标签；没有追加标签时返回 `Ok(None)`。
+pub fn read_revision(path: &PlatformPath) -> Result<Option<MetadataRevision>, String> {
+    let mut file = platform::open_file(path)?;
+    let Some(range) = locate(&mut file).map_err(|e| format!("读取文件末尾标签失败: {}", e))? else {
+        return Ok(None);
+    };
+    let mut bytes = vec![0u8; (range.end - range.start) as usize];
+    file.seek(SeekFrom::Start(range.start))
+        .and_then(|_| file.read_exact(&mut bytes))
+        .map_err(|e| format!("读取文件末尾标签失败: {}", e))?;
+
+    let mss = MediaSourceStream::new(Box::new(Cursor::new(bytes)), Default::default());
+    let mut reader = Id3v2Reader::try_new(mss, MetadataOptions::default())
+        .map_err(|e| format!("解析文件末尾标签失败: {}", e))?;
+    let buffer = reader
+        .read_all()
+        .map_err(|e| format!("解析文件末尾标签失败: {}", e))?;
+    Ok(Some(buffer.revision))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 仅含一个 TIT2 帧的 ID3v2.4 标签；`footer` 为真时带 `3DI` 页脚。
+    fn id3v24_tag(title: &str, footer: bool) -> Vec<u8> {
+        let mut frame = b"TIT2".to_vec();
+        let body_len = 1 + title.len();
+        frame.extend_from_slice(&(body_len as u32).to_be_bytes()); // 小于 128 时与 syncsafe 相同
+        frame.extend_from_slice(&[0, 0, 3]); // flags + UTF-8
+        frame.extend_from_slice(title.as_bytes());
+        let size = (frame.len() as u32).to_be_bytes();
+        let flags = if footer { 0x10 } else { 0 };
+        let mut tag = vec![b'I', b'D', b'3', 4, 0, flags];
+        tag.extend_from_slice(&size);
+        tag.extend_from_slice(&frame);
+        if footer {
+            tag.extend_from_slice(&[b'3', b'D', b'I', 4, 0, flags]);
+            tag.extend_from_slice(&size);
+        }
+        tag
+    }
+
+    #[test]
+    fn test_locate_appended_tag_before_id3v1() {
+        let audio = vec![0xFFu8; 400];
+        let tag = id3v24_tag("Appended", true);
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(ID3V1_LEN as usize, 0);
+
+        let mut bytes = audio.clone();
+        bytes.extend_from_slice(&tag);
+        bytes.extend_from_slice(&id3v1);
+        let start = audio.len() as u64;
+        let range = locate(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(range, Some(start..start + tag.len() as u64));
+
+        // 没有页脚（v2.3 / v2.4 头部标签）时不误判
+        let mut plain = audio.clone();
+        plain.extend_from_slice(&id3v24_tag("Appended", false));
+        assert_eq!(locate(&mut Cursor::new(&plain)).unwrap(), None);
+
+        let path = std::env::temp_dir().join(format!("chordial_id3_appended_{}.mp3", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        let revision = read_revision(&PlatformPath::from(path.to_string_lossy().as_ref())).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let title = revision.unwrap().media.tags.iter().find_map(|t| match &t.std {
+            Some(symphonia::core::meta::StandardTag::TrackTitle(title)) => Some(title.to_string()),
+            _ => None,
+        });
+        assert_eq!(title.as_deref(), Some("Appended"));
+    }
+}
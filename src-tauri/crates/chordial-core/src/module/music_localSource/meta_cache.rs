@@ -0,0 +1,171 @@
+//! 元数据读取缓存 — 同一文件的标签被反复读取时直接返回内存中的结果。
+//!
+//! 单曲详情、「用 Chordial 打开」、批量读取元数据、监听同步都会对同一批文件调用
+//! [`scanner::probe_file`]，每次都要让 symphonia 重新解析文件头。这里按规范路径缓存
+//! 探测结果，并记录当时的 (mtime, 文件大小)：两者任一变化即视为文件已改动，重新探测。
+//!
+//! 只缓存成功的结果（失败需要每次重试并计入隔离列表）。容量满时淘汰最久未用的一条；
+//! 条目数很少（默认 [`DEFAULT_CAPACITY`]），淘汰时线性扫描即可，不必维护链表。
+
+use super::scanner::{self, AudioMeta};
+use crate::module::platform::{self, PlatformPath};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// 默认缓存条目数。
+pub const DEFAULT_CAPACITY: usize = 512;
+
+struct Entry {
+    /// 探测时的 (mtime 秒, 文件大小)
+    stamp: (u64, u64),
+    meta: AudioMeta,
+    /// 最近一次使用的序号，越大越新
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    clock: u64,
+}
+
+/// 按 (路径, mtime, 大小) 失效的元数据 LRU 缓存。
+pub struct MetaCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+impl MetaCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// 读取文件元数据：文件未变化时命中缓存，否则调用 [`scanner::probe_file`] 并记住结果。
+    pub fn read(&self, path: &PlatformPath) -> Result<AudioMeta, String> {
+        self.read_with(path, scanner::probe_file)
+    }
+
+    fn read_with<F>(&self, path: &PlatformPath, probe: F) -> Result<AudioMeta, String>
+    where
+        F: FnOnce(&PlatformPath) -> Result<AudioMeta, String>,
+    {
+        // 取不到 mtime / 大小时无法判断文件是否变化，直接探测、不缓存
+        let Some(stamp) = file_stamp(path) else {
+            return probe(path);
+        };
+        let key = platform::path_to_string(path);
+        {
+            let mut inner = self.inner.lock();
+            inner.clock += 1;
+            let now = inner.clock;
+            if let Some(entry) = inner.entries.get_mut(&key).filter(|e| e.stamp == stamp) {
+                entry.last_used = now;
+                return Ok(entry.meta.clone());
+            }
+        }
+
+        // 探测期间不持锁，其他文件的读取不受影响
+        let meta = probe(path)?;
+        let mut inner = self.inner.lock();
+        if !inner.entries.contains_key(&key) && inner.entries.len() >= self.capacity {
+            let oldest = inner.entries.iter().min_by_key(|(_, e)| e.last_used).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                inner.entries.remove(&oldest);
+            }
+        }
+        let last_used = inner.clock;
+        inner.entries.insert(
+            key,
+            Entry {
+                stamp,
+                meta: meta.clone(),
+                last_used,
+            },
+        );
+        Ok(meta)
+    }
+
+    /// 丢弃某个文件的缓存（本进程改写了文件，mtime 可能仍在同一秒内）。
+    pub fn invalidate(&self, path: &PlatformPath) {
+        self.inner.lock().entries.remove(&platform::path_to_string(path));
+    }
+
+    pub fn clear(&self) {
+        self.inner.lock().entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MetaCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+fn file_stamp(path: &PlatformPath) -> Option<(u64, u64)> {
+    Some((platform::file_modified_secs(path).ok()?, platform::file_size(path).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::fs;
+
+    #[test]
+    fn test_cache_hits_until_file_changes_and_evicts_lru() {
+        let dir = std::env::temp_dir().join(format!("chordial-meta-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<PlatformPath> = (0..3).map(|n| dir.join(format!("{}.flac", n))).collect();
+        for path in &paths {
+            fs::write(path, b"abc").unwrap();
+        }
+
+        let probes = Cell::new(0);
+        let probe = |path: &PlatformPath| {
+            probes.set(probes.get() + 1);
+            Ok(AudioMeta {
+                title: platform::path_file_stem(path),
+                ..AudioMeta::default()
+            })
+        };
+        let cache = MetaCache::new(2);
+
+        assert_eq!(cache.read_with(&paths[0], probe).unwrap().title.as_deref(), Some("0"));
+        cache.read_with(&paths[0], probe).unwrap();
+        assert_eq!(probes.get(), 1);
+
+        // 大小变化 → 重新探测
+        fs::write(&paths[0], b"abcd").unwrap();
+        cache.read_with(&paths[0], probe).unwrap();
+        assert_eq!(probes.get(), 2);
+
+        // 容量 2：读 1 后再用 0，读 2 时淘汰最久未用的 1
+        cache.read_with(&paths[1], probe).unwrap();
+        cache.read_with(&paths[0], probe).unwrap();
+        cache.read_with(&paths[2], probe).unwrap();
+        assert_eq!(probes.get(), 4);
+        cache.read_with(&paths[0], probe).unwrap();
+        assert_eq!(probes.get(), 4);
+        cache.read_with(&paths[1], probe).unwrap();
+        assert_eq!(probes.get(), 5);
+
+        // 失败不缓存
+        let failing = |_: &PlatformPath| Err("坏文件".to_string());
+        cache.invalidate(&paths[2]);
+        assert!(cache.read_with(&paths[2], failing).is_err());
+        assert_eq!(cache.len(), 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -5,29 +5,59 @@
 //! ```text
 //! LocalMusicSource (source.rs)        ← MusicSource 实现
 //!   ├── Scanner (scanner.rs)          ← symphonia 音频文件元数据提取
+//!   │     └── meta_cache.rs           ← 按 (路径, mtime, 大小) 失效的元数据 LRU 缓存
+//!   │     ├── id3_appended.rs         ← 文件末尾带 `3DI` 页脚的 ID3v2.4 标签
+//!   │     └── ogg_chain.rs            ← OGG 链式流 / 复用流的逐页遍历
+//!   ├── Pictures (pictures.rs)        ← 嵌入封面索引（只记偏移，按需读取）
+//!   ├── Chapters (chapters.rs)        ← M4B 有声书的 Nero 章节表
+//!   ├── Mp4Tables (mp4_tables.rs)     ← M4A 音频轨道采样表：精确时长 / 码率、AAC 与 ALAC
+//!   ├── FileStats (file_stats.rs)     ← 文件内评分 / 播放次数的读取与写回（POPM、FMPS）
+//!   ├── TagWriter (tag_writer/)       ← 标题 / 艺人 / 专辑 / 年份写回文件标签（ID3v2、Vorbis comment、MP4）
+//!   ├── Fingerprint (fingerprint.rs)  ← 文件指纹（mtime + 大小 + 头部哈希），重扫时跳过未改动的文件
 //!   ├── FolderManager (folder.rs)     ← 文件夹持久化 + 增删管理
+//!   │     └── extensions.rs           ← 扫描的扩展名：全局别名 + 按文件夹覆盖
+//!   ├── Quarantine (quarantine.rs)    ← 反复探测失败的损坏文件隔离
+//!   ├── ScanJob (scan_job.rs)         ← 后台重扫的状态与进度（`start_scan` / `cancel_scan`）
+//!   ├── Session (session.rs)          ← 不入库的临时播放（「用 Chordial 打开」）
 //!   └── Watcher (watcher.rs)          ← notify 文件系统监听 + 增量同步
 //! ```
 //!
 //! # 工作流程
 //!
 //! 1. **初始化**：`init_local_source()` 创建 `LocalMusicSource`，自动添加系统音乐目录，
-//!    扫描已有文件并导入 `MusicLibrary`，注册为 must-source。
+//!    从库恢复文件索引并注册为 must-source；随后 `startup_scan()` 在后台增量扫描文件夹、
+//!    导入新文件并启动监听。
 //! 2. **运行时**：用户通过 Tauri 命令 `local_add_folder` / `local_remove_folder` 管理文件夹；
-//!    watcher 在后台监听文件变化，增量同步到音乐库。
+//!    watcher 在后台监听文件变化（自动跟随增删的文件夹），增量同步到音乐库并发布库差异。
 //! 3. **资源获取**：前端通过 `get_song_file` / `get_album_picture` / `get_lyric_text`
-//!    请求资源时，`LocalMusicSource` 直接从文件系统读取并返回。
+//!    请求资源时，`LocalMusicSource` 直接从文件系统读取并返回；只服务监听文件夹内
+//!    （或临时播放授权过）的文件，其余路径一律拒绝。
 
+pub mod chapters;
+pub mod extensions;
+pub mod file_stats;
+pub mod fingerprint;
 pub mod folder;
+pub mod id3_appended;
+pub mod meta_cache;
+pub mod mp4_tables;
+pub mod ogg_chain;
+pub mod pictures;
+pub mod quarantine;
+pub mod scan_job;
 pub mod scanner;
+pub mod session;
 pub mod source;
+pub mod tag_writer;
 #[cfg(not(target_os = "android"))]
 pub mod watcher;
 
+use crate::module::events::EventBus;
 use crate::module::music_library::library::MusicLibrary;
 use crate::module::music_library::models::Song;
 use crate::module::music_source::registrar::SourceRegistrar;
 use crate::module::platform::PlatformPath;
+use crate::module::power::PowerMonitor;
 use source::LocalMusicSource;
 use std::sync::Arc;
 
@@ -38,14 +68,16 @@ use std::sync::Arc;
 /// 2. 若文件夹列表为空（首次启动），自动添加系统音乐目录
 /// 3. 创建 `LocalMusicSource`
 /// 4. 从 MusicLibrary 恢复内存索引（跳过已索引文件的重扫描）
-/// 5. 增量扫描文件夹，仅对不在索引中的新文件执行 symphonia 探测
-/// 6. 将本地来源注册到注册器（must-source，不允许注销）
-/// 7. 启动文件系统监听器（后台线程，桌面端）
+/// 5. 将本地来源注册到注册器（must-source，不允许注销）
+///
+/// 耗时的增量扫描与文件监听不在这里进行，由 [`startup_scan`] 在启动预热时完成。
 ///
 /// # 参数
 /// - `folder_store_path`: 文件夹管理器的持久化存储路径
 /// - `library`: 音乐库共享引用
 /// - `registrar`: 来源注册器共享引用
+/// - `events`: 事件总线（监听同步、文件隔离等通知）
+/// - `power`: 电源策略（电池供电时减少启动扫描的探测线程）
 ///
 /// # 返回
 /// 成功时返回 `Arc<LocalMusicSource>`，失败时返回错误信息。
@@ -53,6 +85,8 @@ pub fn init_local_source(
     folder_store_path: std::path::PathBuf,
     library: Arc<MusicLibrary>,
     registrar: &SourceRegistrar,
+    events: Arc<EventBus>,
+    power: Arc<PowerMonitor>,
 ) -> Result<Arc<LocalMusicSource>, String> {
     use crate::module::storage::persistent::PersistentStore;
     use folder::FolderManager;
@@ -85,12 +119,22 @@ pub fn init_local_source(
         .map(|p| p.join("local_source_file_mtimes.json"))
         .unwrap_or_else(|| std::path::PathBuf::from("local_source_file_mtimes.json"));
     let mtime_store = PersistentStore::new(mtime_store_path);
+    let quarantine_path = library
+        .store_path()
+        .parent()
+        .map(|p| p.join("local_source_quarantine.json"))
+        .unwrap_or_else(|| std::path::PathBuf::from("local_source_quarantine.json"));
+    let quarantine = quarantine::Quarantine::new(PersistentStore::new(quarantine_path));
     // 迁移：清理 library 中遗留的旧 mtime 数据（现使用独立文件存储）
     library.remove_store_key("local_source_file_mtimes");
     let local_source = Arc::new(LocalMusicSource::new(
         folder_manager.clone(),
         library.clone(),
         mtime_store,
+        quarantine,
+        events,
+        power,
+        registrar.manager().clone(),
     ));
     let t3 = Instant::now();
     eprintln!("[local_source] ⏱ 3. LocalMusicSource 创建: {:?}", t3 - t2);
@@ -105,16 +149,48 @@ pub fn init_local_source(
     );
     // (remove_specific_song_source_ids 已在 restore_index_from_library 内部调用 save)
 
+    // 注册为 must-source（已恢复索引的文件此时即可访问）
+    registrar
+        .register(local_source.clone())
+        .map_err(|e| format!("注册本地来源失败: {}", e))?;
+    eprintln!(
+        "[local_source] ⏱ 注册来源: {:?}, 总计 {:?} — {} 文件夹, {} 已恢复",
+        t4.elapsed(),
+        t0.elapsed(),
+        folder_manager.count(),
+        restored
+    );
+
+    Ok(local_source)
+}
+
+/// 启动扫描：增量扫描全部文件夹并启动文件监听器，返回新索引的文件数。
+///
+/// 1. 遍历文件夹，mtime 缓存命中的文件直接重建索引，隔离文件跳过
+/// 2. 并行探测新文件（线程数受电源策略限制），批量导入 `MusicLibrary`
+/// 3. 保存音乐库与 mtime 缓存
+/// 4. 启动文件系统监听器（后台线程，桌面端）
+///
+/// 在后台线程调用；扫描期间库内已恢复的歌曲照常可用。
+pub fn startup_scan(local_source: &Arc<LocalMusicSource>) -> Result<usize, String> {
+    use std::time::Instant;
+
+    let folder_manager = &local_source.folder_manager;
+    let library = &local_source.library;
+    let power = &local_source.power;
+    let t4 = Instant::now();
+
     // 5. 增量扫描：mtime 缓存跳过未变化文件 + 并行探测新文件
     let folders = folder_manager.get_folders();
     let mut skipped = 0usize;
+    let mut quarantined = 0usize;
     let mut needs_probe: Vec<PlatformPath> = Vec::new();
 
     // 5a. 收集所有音频文件 → 并行 canonicalize → 分类
     let mut all_files: Vec<PlatformPath> = Vec::new();
     for folder in &folders {
         let _t_dir = Instant::now();
-        let files = folder::collect_audio_files(folder);
+        let files = folder_manager.collect_audio_files(folder);
         let t_dir = _t_dir.elapsed();
         eprintln!(
             "[local_source] ⏱ 5a-dir '{}': {} 个音频文件, {:?}",
@@ -185,17 +261,24 @@ pub fn init_local_source(
             continue;
         }
 
+        // 已隔离的损坏文件 → 不再探测
+        if local_source.quarantine.is_quarantined(&canonical) {
+            quarantined += 1;
+            continue;
+        }
+
         // 需要探测
         needs_probe.push(canonical);
     }
 
     let t5a = Instant::now();
     eprintln!(
-        "[local_source] ⏱ 5a. 目录遍历+并行canonicalize+分类: {:?} ({} 个文件, {} 个线程, 已跳过 {}, 待探测 {})",
+        "[local_source] ⏱ 5a. 目录遍历+并行canonicalize+分类: {:?} ({} 个文件, {} 个线程, 已跳过 {}, 已隔离 {}, 待探测 {})",
         t5a - t4,
         canonicalize_count,
         1, // already computed above but simplified for log
         skipped,
+        quarantined,
         needs_probe.len()
     );
 
@@ -203,10 +286,8 @@ pub fn init_local_source(
     let new_count = if needs_probe.is_empty() {
         0usize
     } else {
-        let num_threads = std::thread::available_parallelism()
-            .map(|n| n.get())
-            .unwrap_or(4)
-            .min(needs_probe.len());
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let num_threads = power.scan_threads(cores).min(needs_probe.len());
         let chunk_size = (needs_probe.len() + num_threads - 1) / num_threads;
 
         let mut results: Vec<(PlatformPath, Result<scanner::AudioMeta, String>)> =
@@ -248,22 +329,32 @@ pub fn init_local_source(
         // 5c. 批量添加到音乐库（一次性加载，内存合并，一次性写回）
         let mut new_count = 0usize;
         {
-            let songs_and_paths: Vec<(&PlatformPath, Song)> = results
+            let songs_and_paths: Vec<(&PlatformPath, &scanner::AudioMeta, Song)> = results
                 .iter()
                 .filter_map(|(path, meta_result)| {
-                    meta_result.as_ref().ok().map(|meta| (path, local_source.build_song(path, meta)))
+                    meta_result
+                        .as_ref()
+                        .ok()
+                        .map(|meta| {
+                            let mut song = local_source.build_indexed_song(path, meta);
+                            if song.language.is_none() {
+                                source::infer_language(&mut song, scanner::read_lyric_file(path).as_deref());
+                            }
+                            (path, meta, song)
+                        })
                 })
                 .collect();
 
             if !songs_and_paths.is_empty() {
                 let songs: Vec<Song> = songs_and_paths
                     .iter()
-                    .map(|(_, song)| song.clone())
+                    .map(|(_, _, song)| song.clone())
                     .collect();
                 match local_source.library.add_songs_batch(&songs) {
                     Ok(stored_ids) => {
-                        for (i, (path, _)) in songs_and_paths.iter().enumerate() {
+                        for (i, (path, meta, _)) in songs_and_paths.iter().enumerate() {
                             let stored_id = &stored_ids[i];
+                            local_source.import_file_stats(stored_id, meta);
                             local_source
                                 .file_index
                                 .write()
@@ -283,16 +374,23 @@ pub fn init_local_source(
             }
         }
 
-        // 打印探测失败的文件
+        // 打印探测失败的文件，并计入隔离列表
         for (path, meta_result) in &results {
-            if let Err(e) = meta_result {
-                eprintln!(
-                    "[local_source] 探测文件失败 '{}': {}",
-                    crate::module::platform::path_to_string(path),
-                    e
-                );
+            match meta_result {
+                Ok(_) => local_source.quarantine.record_success(path),
+                Err(e) => {
+                    eprintln!(
+                        "[local_source] 探测文件失败 '{}': {}",
+                        crate::module::platform::path_to_string(path),
+                        e
+                    );
+                    local_source.note_failure(path, e);
+                }
             }
         }
+        if let Err(e) = local_source.quarantine.save() {
+            eprintln!("[local_source] 保存隔离列表失败: {}", e);
+        }
 
         new_count
     };
@@ -317,39 +415,26 @@ pub fn init_local_source(
         );
     }
 
-    // 注册为 must-source
-    let _t_reg = Instant::now();
-    registrar
-        .register(local_source.clone())
-        .map_err(|e| format!("注册本地来源失败: {}", e))?;
-    let t_reg = _t_reg.elapsed();
-    eprintln!("[local_source] ⏱ 7. 注册来源: {:?}", t_reg);
-
     // 启动后台文件监听器（仅桌面端）
     #[cfg(not(target_os = "android"))]
     {
         let watcher_source = local_source.clone();
-        let watcher_folders = folders;
         std::thread::Builder::new()
             .name("local-source-watcher".into())
             .spawn(move || {
-                if let Err(e) = watcher::start_watcher(watcher_source, watcher_folders) {
+                if let Err(e) = watcher::start_watcher(watcher_source) {
                     eprintln!("[local_source] 文件监听器退出: {}", e);
                 }
             })
             .map_err(|e| format!("启动文件监听线程失败: {}", e))?;
     }
-    let t8 = Instant::now();
-    eprintln!("[local_source] ⏱ 8. 启动文件监听器: {:?}", t8 - t_reg);
-
     eprintln!(
-        "[local_source] ⏱ 总计: {:?} — {} 文件夹, {} 已恢复, {} 跳过, {} 新索引",
-        t8 - t0,
+        "[local_source] ⏱ 启动扫描总计: {:?} — {} 文件夹, {} 跳过, {} 新索引",
+        t4.elapsed(),
         folder_manager.count(),
-        restored,
         skipped,
         new_count
     );
 
-    Ok(local_source)
+    Ok(new_count)
 }
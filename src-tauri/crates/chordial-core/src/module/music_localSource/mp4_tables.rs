@@ -0,0 +1,341 @@
+//! M4A 采样表 — 从音频轨道的 `stbl` 计算精确时长、平均码率，并区分 AAC / ALAC。
+//!
+//! `mvhd` 的时长用的是电影时间刻度，部分编码器写得不准（或把封面、章节文本轨算进去）；
+//! 按文件大小估算的码率也会把大封面算进去。这里只看 `hdlr` 为 `soun` 的轨道：
+//!
+//! | atom | 用途 |
+//! |------|------|
+//! | `mdhd` | 轨道时间刻度；`stts` 缺失时用它的时长 |
+//! | `stts` | 各采样的时长之和 = 轨道时长 |
+//! | `stsz` | 各采样的字节数之和 = 音频数据量，除以时长得平均码率 |
+//! | `stsd` | 首个采样描述：`mp4a`（AAC，`esds` 中有标称码率）或 `alac`（无损，带位深） |
+
+use super::pictures::find_atom;
+use crate::module::platform::{self, PlatformPath};
+use std::io::{Read, Seek, SeekFrom};
+
+/// `stsz` 采样表的最大读取长度（约 1600 万个采样，远超任何单曲）。
+const MAX_STSZ_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 其余小 atom（`mdhd`、`hdlr`、`stsd`、`stts`）的最大读取长度。
+const MAX_SMALL_ATOM_BYTES: u64 = 1024 * 1024;
+
+/// 音频轨道的采样描述类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp4AudioCodec {
+    Aac,
+    Alac,
+    /// 其他采样描述（如 `ac-3`、`Opus`），保留 fourcc
+    Other([u8; 4]),
+}
+
+impl Mp4AudioCodec {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Aac => "aac".to_string(),
+            Self::Alac => "alac".to_string(),
+            Self::Other(fourcc) => String::from_utf8_lossy(fourcc).trim().to_lowercase(),
+        }
+    }
+}
+
+/// 从采样表得出的音频轨道信息。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mp4AudioInfo {
+    pub codec: Mp4AudioCodec,
+    pub duration_ms: Option<u64>,
+    /// 平均码率（kbps）：音频数据量 / 时长；采样表不可用时取 `esds` 的标称值
+    pub bitrate_kbps: Option<u32>,
+    /// ALAC 的位深
+    pub bit_depth: Option<u32>,
+}
+
+impl Mp4AudioInfo {
+    pub fn is_lossless(&self) -> bool {
+        self.codec == Mp4AudioCodec::Alac
+    }
+}
+
+pub fn read_audio_info(path: &PlatformPath) -> Result<Option<Mp4AudioInfo>, String> {
+    let mut file = platform::open_file(path)?;
+    read_audio_info_from(&mut file).map_err(|e| format!("读取 M4A 采样表失败: {}", e))
+}
+
+/// 读取第一条音频轨道的采样表；不是 MP4 或没有音频轨道时返回 `Ok(None)`。
+pub fn read_audio_info_from<R: Read + Seek>(r: &mut R) -> std::io::Result<Option<Mp4AudioInfo>> {
+    let file_end = r.seek(SeekFrom::End(0))?;
+    let Some((moov_start, moov_end)) = find_atom(r, 0, file_end, b"moov")? else {
+        return Ok(None);
+    };
+
+    let mut pos = moov_start;
+    while let Some((trak_start, trak_end)) = find_atom(r, pos, moov_end, b"trak")? {
+        pos = trak_end;
+        let Some((mdia_start, mdia_end)) = find_atom(r, trak_start, trak_end, b"mdia")? else {
+            continue;
+        };
+        let hdlr = read_child(r, mdia_start, mdia_end, b"hdlr", MAX_SMALL_ATOM_BYTES)?;
+        if hdlr.get(8..12) != Some(b"soun") {
+            continue;
+        }
+        return read_track(r, mdia_start, mdia_end).map(Some);
+    }
+    Ok(None)
+}
+
+fn read_track<R: Read + Seek>(r: &mut R, mdia_start: u64, mdia_end: u64) -> std::io::Result<Mp4AudioInfo> {
+    let (timescale, mdhd_duration) = parse_mdhd(&read_child(r, mdia_start, mdia_end, b"mdhd", MAX_SMALL_ATOM_BYTES)?);
+
+    let (mut start, mut end) = (mdia_start, mdia_end);
+    for name in [b"minf", b"stbl"] {
+        match find_atom(r, start, end, name)? {
+            Some(range) => (start, end) = range,
+            None => (start, end) = (0, 0),
+        }
+    }
+    let stsd = read_child(r, start, end, b"stsd", MAX_SMALL_ATOM_BYTES)?;
+    let stts = read_child(r, start, end, b"stts", MAX_SMALL_ATOM_BYTES)?;
+    let stsz = read_child(r, start, end, b"stsz", MAX_STSZ_BYTES)?;
+
+    let (codec, nominal_bitrate, bit_depth) = parse_stsd(&stsd);
+    let units = parse_stts(&stts).filter(|u| *u > 0).or(mdhd_duration);
+    let duration_ms = match (units, timescale) {
+        // 损坏的表可能给出极大的单位数，溢出时视为时长未知
+        (Some(units), Some(scale)) => units.checked_mul(1000).map(|u| u / scale as u64),
+        _ => None,
+    };
+    let bitrate_kbps = match (parse_stsz(&stsz), duration_ms) {
+        (Some(bytes), Some(ms)) if ms > 0 => Some((bytes * 8 / ms) as u32),
+        _ => nominal_bitrate.map(|bps| bps / 1000),
+    };
+    Ok(Mp4AudioInfo {
+        codec,
+        duration_ms,
+        bitrate_kbps,
+        bit_depth,
+    })
+}
+
+/// 读取直接子 atom 的数据区；不存在时返回空。
+fn read_child<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    end: u64,
+    name: &[u8; 4],
+    limit: u64,
+) -> std::io::Result<Vec<u8>> {
+    let Some((body_start, body_end)) = find_atom(r, start, end, name)? else {
+        return Ok(Vec::new());
+    };
+    let mut body = vec![0u8; (body_end - body_start).min(limit) as usize];
+    r.seek(SeekFrom::Start(body_start))?;
+    r.read_exact(&mut body)?;
+    Ok(body)
+}
+
+fn be_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn be_u64(data: &[u8], pos: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(pos..pos + 8)?.try_into().ok()?))
+}
+
+/// 返回（时间刻度，时长）；版本 1 的字段为 64 位。
+fn parse_mdhd(body: &[u8]) -> (Option<u32>, Option<u64>) {
+    let (timescale, duration) = match body.first() {
+        Some(1) => (be_u32(body, 20), be_u64(body, 24)),
+        Some(_) => (be_u32(body, 12), be_u32(body, 16).map(u64::from)),
+        None => (None, None),
+    };
+    // 全 1 表示时长未知
+    let duration = duration.filter(|d| *d != u64::MAX && *d != u32::MAX as u64);
+    (timescale.filter(|s| *s > 0), duration)
+}
+
+/// `stts`：各段（采样数 × 采样时长）之和；溢出时返回 `None`。
+fn parse_stts(body: &[u8]) -> Option<u64> {
+    let count = be_u32(body, 4)? as usize;
+    (0..count).try_fold(0u64, |total, i| {
+        let pos = 8 + i * 8;
+        let units = (be_u32(body, pos)? as u64).checked_mul(be_u32(body, pos + 4)? as u64)?;
+        total.checked_add(units)
+    })
+}
+
+/// `stsz`：所有采样的字节数之和。
+fn parse_stsz(body: &[u8]) -> Option<u64> {
+    let uniform = be_u32(body, 4)? as u64;
+    let count = be_u32(body, 8)? as usize;
+    if uniform != 0 {
+        return Some(uniform * count as u64);
+    }
+    (0..count).try_fold(0u64, |total, i| Some(total + be_u32(body, 12 + i * 4)? as u64))
+}
+
+/// `stsd` 的首个采样描述：返回（编码，`esds` 标称码率 bps，ALAC 位深）。
+fn parse_stsd(body: &[u8]) -> (Mp4AudioCodec, Option<u32>, Option<u32>) {
+    // 版本 + 标志 4、条目数 4，之后是首个条目（大小 4 + fourcc 4）
+    let entry = be_u32(body, 8).and_then(|size| body.get(8..8 + size as usize));
+    let Some(entry) = entry.filter(|e| e.len() >= 8) else {
+        return (Mp4AudioCodec::Other(*b"????"), None, None);
+    };
+    let fourcc: [u8; 4] = entry[4..8].try_into().unwrap_or(*b"????");
+    // 声音采样描述（版本 0）的固定字段共 28 字节，之后是子 atom
+    let children = entry.get(36..).unwrap_or_default();
+    match &fourcc {
+        b"mp4a" => (Mp4AudioCodec::Aac, find_box(children, b"esds").and_then(esds_avg_bitrate), None),
+        // ALACSpecificConfig：版本 + 标志 4、frameLength 4、compatibleVersion 1，之后是位深
+        b"alac" => {
+            let depth = find_box(children, b"alac").and_then(|cfg| cfg.get(9)).map(|d| *d as u32);
+            (Mp4AudioCodec::Alac, None, depth)
+        }
+        _ => (Mp4AudioCodec::Other(fourcc), None, None),
+    }
+}
+
+/// 在内存中的一串 atom 里找直接子 atom 的数据区。
+fn find_box<'a>(mut data: &'a [u8], name: &[u8; 4]) -> Option<&'a [u8]> {
+    while data.len() >= 8 {
+        let size = (be_u32(data, 0)? as usize).min(data.len());
+        if size < 8 {
+            return None;
+        }
+        if &data[4..8] == name {
+            return Some(&data[8..size]);
+        }
+        data = &data[size..];
+    }
+    None
+}
+
+/// `esds`：ES_Descriptor（0x03）内 DecoderConfigDescriptor（0x04）的 avgBitrate。
+fn esds_avg_bitrate(body: &[u8]) -> Option<u32> {
+    let mut pos = 4; // 版本 + 标志
+    loop {
+        let tag = *body.get(pos)?;
+        pos += 1;
+        // 长度为变长编码，每字节低 7 位，最高位表示后面还有
+        let mut len = 0usize;
+        loop {
+            let b = *body.get(pos)?;
+            pos += 1;
+            len = (len << 7) | (b & 0x7f) as usize;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        match tag {
+            0x03 => {
+                // ES_ID 2 字节 + 标志 1 字节；标志位决定是否跟有依赖流 ID / URL / OCR ID
+                let flags = *body.get(pos + 2)?;
+                pos += 3;
+                if flags & 0x80 != 0 {
+                    pos += 2;
+                }
+                if flags & 0x40 != 0 {
+                    pos += 1 + *body.get(pos)? as usize;
+                }
+                if flags & 0x20 != 0 {
+                    pos += 2;
+                }
+            }
+            // objectTypeIndication 1、streamType 1、bufferSize 3、maxBitrate 4，之后是 avgBitrate
+            0x04 => return be_u32(body, pos + 9).filter(|b| *b > 0),
+            _ => pos += len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(name);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn track(handler: &[u8; 4], entry: &[u8]) -> Vec<u8> {
+        let mut mdhd = vec![0u8; 12];
+        mdhd.extend_from_slice(&44_100u32.to_be_bytes());
+        mdhd.extend_from_slice(&(44_100u32 * 99).to_be_bytes()); // 故意写错，应以 stts 为准
+        mdhd.extend_from_slice(&[0; 4]);
+        let mut hdlr = vec![0u8; 8];
+        hdlr.extend_from_slice(handler);
+        hdlr.extend_from_slice(&[0; 12]);
+
+        let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        stsd.extend_from_slice(entry);
+        // 10 个采样 × 4410 单位 = 1 秒
+        let mut stts = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        stts.extend_from_slice(&10u32.to_be_bytes());
+        stts.extend_from_slice(&4410u32.to_be_bytes());
+        let mut stsz = vec![0u8; 8];
+        stsz.extend_from_slice(&10u32.to_be_bytes());
+        for _ in 0..10 {
+            stsz.extend_from_slice(&4000u32.to_be_bytes());
+        }
+        let stbl = atom(b"stbl", &[atom(b"stsd", &stsd), atom(b"stts", &stts), atom(b"stsz", &stsz)].concat());
+        let mdia = [atom(b"mdhd", &mdhd), atom(b"hdlr", &hdlr), atom(b"minf", &stbl)].concat();
+        atom(b"trak", &atom(b"mdia", &mdia))
+    }
+
+    #[test]
+    fn test_alac_duration_and_bitrate_from_tables() {
+        let mut alac_cfg = vec![0u8; 9];
+        alac_cfg.push(24); // 位深
+        alac_cfg.extend_from_slice(&[0; 14]);
+        let entry = atom(b"alac", &[vec![0u8; 28], atom(b"alac", &alac_cfg)].concat());
+        // 前面先放一条文本轨（章节），应被跳过
+        let moov = atom(b"moov", &[track(b"text", &entry), track(b"soun", &entry)].concat());
+        let file = [atom(b"ftyp", b"M4A \0\0\0\0"), moov].concat();
+
+        let info = read_audio_info_from(&mut Cursor::new(file)).unwrap().unwrap();
+        assert_eq!(info.codec, Mp4AudioCodec::Alac);
+        assert!(info.is_lossless());
+        assert_eq!(info.duration_ms, Some(1000));
+        assert_eq!(info.bitrate_kbps, Some(320)); // 40000 字节 / 1 秒
+        assert_eq!(info.bit_depth, Some(24));
+
+        // AAC：esds 的 DecoderConfigDescriptor 中 avgBitrate = 256000
+        let mut esds = vec![0, 0, 0, 0, 0x03, 0x19, 0, 1, 0, 0x04, 0x11, 0x40, 0x15, 0, 0, 0];
+        esds.extend_from_slice(&320_000u32.to_be_bytes());
+        esds.extend_from_slice(&256_000u32.to_be_bytes());
+        let stsd = [vec![0u8; 4], 1u32.to_be_bytes().to_vec(), atom(b"mp4a", &[vec![0u8; 28], atom(b"esds", &esds)].concat())].concat();
+        assert_eq!(parse_stsd(&stsd), (Mp4AudioCodec::Aac, Some(256_000), None));
+    }
+
+    #[test]
+    fn test_overflowing_tables_give_unknown_duration() {
+        // 三段 u32::MAX × u32::MAX，累加溢出 u64
+        let mut stts = vec![0, 0, 0, 0, 0, 0, 0, 3];
+        for _ in 0..3 {
+            stts.extend_from_slice(&u32::MAX.to_be_bytes());
+            stts.extend_from_slice(&u32::MAX.to_be_bytes());
+        }
+        assert_eq!(parse_stts(&stts), None);
+
+        // 回退到 mdhd（版本 1）的时长，同样大到乘 1000 溢出
+        let mut mdhd = vec![1u8, 0, 0, 0];
+        mdhd.extend_from_slice(&[0; 16]);
+        mdhd.extend_from_slice(&44_100u32.to_be_bytes());
+        mdhd.extend_from_slice(&(u64::MAX - 1).to_be_bytes());
+        let mut hdlr = vec![0u8; 8];
+        hdlr.extend_from_slice(b"soun");
+        hdlr.extend_from_slice(&[0; 12]);
+        let entry = atom(b"mp4a", &[0u8; 28]);
+        let stsd = [vec![0, 0, 0, 0, 0, 0, 0, 1], entry].concat();
+        let stbl = atom(b"stbl", &[atom(b"stsd", &stsd), atom(b"stts", &stts)].concat());
+        let mdia = [atom(b"mdhd", &mdhd), atom(b"hdlr", &hdlr), atom(b"minf", &stbl)].concat();
+        let file = atom(b"moov", &atom(b"trak", &atom(b"mdia", &mdia)));
+
+        let info = read_audio_info_from(&mut Cursor::new(file)).unwrap().unwrap();
+        assert_eq!(info.codec, Mp4AudioCodec::Aac);
+        assert_eq!(info.duration_ms, None);
+        assert_eq!(info.bitrate_kbps, None);
+    }
+}
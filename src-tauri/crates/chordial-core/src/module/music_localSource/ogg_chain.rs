@@ -0,0 +1,410 @@
+//! OGG 链式流 — 逐页遍历整个文件，得出每一段（chain）的编码、时长与注释头。
+//!
+//! 网络电台录音和部分编码器会把多段 OGG 首尾相接（链式流），每段有自己的 BOS 页与注释头；
+//! symphonia 探测时只读到第一段，时长和标签都只反映开头那首。另一种情况是一段里复用了多个
+//! 逻辑流（Theora 视频、Skeleton 索引等），这些流的 granule 与音频无关，统计时长要跳过。
+//!
+//! 遍历只读页头，音频流的头两个包（识别头 + 注释头）才读取页体，其余页体直接跳过。
+//! 多段时标签取第一段还是最后一段由 [`ChainTags`] 决定（全局设置，见 [`set_chain_tags`]）。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, Ordering};
+use symphonia::core::io::BufReader;
+use symphonia::core::meta::{MetadataBuilder, MetadataRevision};
+use symphonia::default::meta::embedded::vorbis::{read_vorbis_comment, VORBIS_COMMENT_METADATA_INFO};
+
+/// ConfigStore 中存放链式流标签取法的键。
+pub const CHAIN_TAGS_CONFIG_KEY: &str = "ogg_chain_tags";
+
+/// 注释头超过该大小（多为内嵌的 base64 封面）时不再收集，按没有注释处理。
+const MAX_COMMENT_BYTES: usize = 16 * 1024 * 1024;
+
+/// FLAC 映射的头包数不定，最多看这么多个包找 VORBIS_COMMENT 块。
+const MAX_HEADER_PACKETS: usize = 8;
+
+const PAGE_HEADER_LEN: usize = 27;
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_BOS: u8 = 0x02;
+
+static LAST_CHAIN_TAGS: AtomicBool = AtomicBool::new(false);
+
+/// 链式流的标签取自哪一段。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum ChainTags {
+    /// 第一段（与 symphonia 读到的一致，默认）
+    #[default]
+    First,
+    /// 最后一段（电台录音通常以最后一首为准）
+    Last,
+}
+
+pub fn set_chain_tags(choice: ChainTags) {
+    LAST_CHAIN_TAGS.store(choice == ChainTags::Last, Ordering::Relaxed);
+}
+
+pub fn chain_tags() -> ChainTags {
+    if LAST_CHAIN_TAGS.load(Ordering::Relaxed) {
+        ChainTags::Last
+    } else {
+        ChainTags::First
+    }
+}
+
+/// OGG 中能解码的音频编码。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OggCodec {
+    Vorbis,
+    Opus,
+    Flac,
+}
+
+/// 一段链式流中的音频流。
+#[derive(Debug, Clone)]
+pub struct OggChain {
+    pub codec: OggCodec,
+    /// granule 的时钟频率（Opus 固定 48000）
+    pub sample_rate: u32,
+    pub channels: u8,
+    /// 该段时长（秒）
+    pub duration_secs: f64,
+    /// 注释头的原始包（含编码特有的前缀）
+    comment: Option<Vec<u8>>,
+}
+
+impl OggChain {
+    /// 把注释头解析成 symphonia 的元数据。
+    pub fn tags(&self) -> Option<MetadataRevision> {
+        let packet = self.comment.as_deref()?;
+        let body = match self.codec {
+            OggCodec::Vorbis => packet.strip_prefix(b"\x03vorbis")?,
+            OggCodec::Opus => packet.strip_prefix(b"OpusTags")?,
+            OggCodec::Flac => packet.get(4..)?,
+        };
+        let mut builder = MetadataBuilder::new(VORBIS_COMMENT_METADATA_INFO);
+        read_vorbis_comment(&mut BufReader::new(body), &mut builder, &mut Vec::new()).ok()?;
+        Some(builder.build())
+    }
+}
+
+/// 整个文件的链式结构。
+#[derive(Debug, Clone, Default)]
+pub struct OggLayout {
+    /// 含音频流的各段，按文件顺序
+    pub chains: Vec<OggChain>,
+    /// 跳过的非音频逻辑流数（视频、Skeleton、无法识别的编码）
+    pub skipped_streams: usize,
+}
+
+impl OggLayout {
+    pub fn duration_secs(&self) -> f64 {
+        self.chains.iter().map(|c| c.duration_secs).sum()
+    }
+
+    /// 按 `choice`（通常是全局的 [`chain_tags`]）应采用的注释；
+    /// 只有一段，或取第一段时返回 `None`（沿用 symphonia 读到的）。
+    pub fn chosen_tags(&self, choice: ChainTags) -> Option<MetadataRevision> {
+        match (choice, self.chains.as_slice()) {
+            (ChainTags::Last, [_, .., last]) => last.tags(),
+            _ => None,
+        }
+    }
+}
+
+/// 逻辑流的遍历状态。
+#[derive(Default)]
+struct StreamState {
+    /// 由识别头确定；`None` 表示非音频流
+    codec: Option<OggCodec>,
+    sample_rate: u32,
+    channels: u8,
+    pre_skip: u64,
+    packets: usize,
+    partial: Vec<u8>,
+    /// 当前包超过 [`MAX_COMMENT_BYTES`]，剩余部分丢弃直到包结束
+    oversized: bool,
+    comment: Option<Vec<u8>>,
+    last_granule: Option<u64>,
+}
+
+impl StreamState {
+    fn wants_packets(&self) -> bool {
+        self.packets == 0 || (self.codec.is_some() && self.comment.is_none() && self.packets < MAX_HEADER_PACKETS)
+    }
+
+    fn on_packet(&mut self, packet: Vec<u8>) {
+        if self.packets == 0 {
+            self.identify(&packet);
+        } else if self.codec.is_some_and(|codec| is_comment_packet(codec, &packet)) {
+            self.comment = Some(packet);
+        }
+        self.packets += 1;
+    }
+
+    /// 跳过的包只计数，不当作识别头 / 注释头。
+    fn skip_packet(&mut self) {
+        self.packets += 1;
+    }
+
+    fn identify(&mut self, p: &[u8]) {
+        if p.len() >= 16 && p.starts_with(b"\x01vorbis") {
+            self.codec = Some(OggCodec::Vorbis);
+            self.channels = p[11];
+            self.sample_rate = u32::from_le_bytes([p[12], p[13], p[14], p[15]]);
+        } else if p.len() >= 12 && p.starts_with(b"OpusHead") {
+            self.codec = Some(OggCodec::Opus);
+            self.channels = p[9];
+            self.pre_skip = u16::from_le_bytes([p[10], p[11]]) as u64;
+            self.sample_rate = 48_000;
+        } else if p.len() >= 30 && p.starts_with(b"\x7fFLAC") && &p[9..13] == b"fLaC" {
+            // STREAMINFO 从第 17 字节开始，采样率占其第 10 字节起的 20 位
+            self.codec = Some(OggCodec::Flac);
+            self.sample_rate = (p[27] as u32) << 12 | (p[28] as u32) << 4 | (p[29] as u32) >> 4;
+            self.channels = ((p[29] >> 1) & 0x07) + 1;
+        }
+    }
+
+    fn into_chain(self) -> Option<OggChain> {
+        let codec = self.codec?;
+        let duration_secs = match (self.last_granule, self.sample_rate) {
+            (Some(granule), rate) if rate > 0 => granule.saturating_sub(self.pre_skip) as f64 / rate as f64,
+            _ => 0.0,
+        };
+        Some(OggChain {
+            codec,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            duration_secs,
+            comment: self.comment,
+        })
+    }
+}
+
+fn is_comment_packet(codec: OggCodec, packet: &[u8]) -> bool {
+    match codec {
+        OggCodec::Vorbis => packet.starts_with(b"\x03vorbis"),
+        OggCodec::Opus => packet.starts_with(b"OpusTags"),
+        // FLAC 元数据块类型 4 = VORBIS_COMMENT（最高位是「最后一块」标志）
+        OggCodec::Flac => packet.first().is_some_and(|b| b & 0x7f == 4),
+    }
+}
+
+/// 正在遍历的一段：逻辑流按 BOS 顺序排列。
+#[derive(Default)]
+struct ChainBuilder {
+    order: Vec<u32>,
+    streams: HashMap<u32, StreamState>,
+}
+
+impl ChainBuilder {
+    /// 结束本段：取第一个音频流，其余流计入跳过数。
+    fn finish(mut self, layout: &mut OggLayout) {
+        let mut audio = None;
+        for serial in &self.order {
+            let Some(stream) = self.streams.remove(serial) else { continue };
+            match stream.codec {
+                Some(_) if audio.is_none() => audio = stream.into_chain(),
+                _ => layout.skipped_streams += 1,
+            }
+        }
+        layout.chains.extend(audio);
+    }
+}
+
+/// 遍历 OGG 文件的全部页；不是 OGG 时返回 `Ok(None)`。
+pub fn scan<R: Read + Seek>(r: &mut R) -> std::io::Result<Option<OggLayout>> {
+    r.seek(SeekFrom::Start(0))?;
+    let mut layout = OggLayout::default();
+    let mut chain = ChainBuilder::default();
+    let mut in_bos_run = false;
+    let mut header = [0u8; PAGE_HEADER_LEN];
+    let mut lacing = [0u8; 255];
+
+    loop {
+        match r.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        if &header[..4] != b"OggS" {
+            if chain.order.is_empty() && layout.chains.is_empty() {
+                return Ok(None);
+            }
+            break; // 末尾的垃圾数据或截断，按已读部分处理
+        }
+        let flags = header[5];
+        let granule = u64::from_le_bytes(header[6..14].try_into().unwrap());
+        let serial = u32::from_le_bytes(header[14..18].try_into().unwrap());
+        let segments = &mut lacing[..header[26] as usize];
+        r.read_exact(segments)?;
+        let body_len: u64 = segments.iter().map(|&b| b as u64).sum();
+
+        if flags & FLAG_BOS != 0 {
+            // BOS 页出现在普通页之后，说明上一段已结束
+            if !in_bos_run && !chain.order.is_empty() {
+                std::mem::take(&mut chain).finish(&mut layout);
+            }
+            in_bos_run = true;
+            chain.order.push(serial);
+            chain.streams.entry(serial).or_default();
+        } else {
+            in_bos_run = false;
+        }
+
+        let Some(stream) = chain.streams.get_mut(&serial) else {
+            // 没有 BOS 的流（截断文件的残页），忽略
+            r.seek(SeekFrom::Current(body_len as i64))?;
+            continue;
+        };
+        // granule 为 -1 表示本页没有包结束
+        if granule != u64::MAX {
+            stream.last_granule = Some(granule);
+        }
+        if !stream.wants_packets() {
+            r.seek(SeekFrom::Current(body_len as i64))?;
+            continue;
+        }
+
+        let mut body = vec![0u8; body_len as usize];
+        r.read_exact(&mut body)?;
+        if flags & FLAG_CONTINUED == 0 {
+            stream.partial.clear();
+            stream.oversized = false;
+        }
+        let mut pos = 0;
+        for &len in segments.iter() {
+            let len = len as usize;
+            if stream.oversized || stream.partial.len() + len > MAX_COMMENT_BYTES {
+                // 整包丢弃：截断后的注释头无法解析，也不能挡住后面的包
+                stream.oversized = true;
+                stream.partial = Vec::new();
+            } else {
+                stream.partial.extend_from_slice(&body[pos..pos + len]);
+            }
+            pos += len;
+            if len < 255 {
+                let packet = std::mem::take(&mut stream.partial);
+                if std::mem::take(&mut stream.oversized) {
+                    stream.skip_packet();
+                } else {
+                    stream.on_packet(packet);
+                }
+            }
+        }
+    }
+
+    if !chain.order.is_empty() {
+        chain.finish(&mut layout);
+    }
+    Ok(Some(layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn raw_page(flags: u8, granule: u64, serial: u32, lacing: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut page = b"OggS\x00".to_vec();
+        page.push(flags);
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&serial.to_le_bytes());
+        page.extend_from_slice(&[0; 8]); // 序号 + CRC，遍历时不校验
+        page.push(lacing.len() as u8);
+        page.extend_from_slice(lacing);
+        page.extend_from_slice(body);
+        page
+    }
+
+    fn page(flags: u8, granule: u64, serial: u32, packet: &[u8]) -> Vec<u8> {
+        let mut lacing = vec![255u8; packet.len() / 255];
+        lacing.push((packet.len() % 255) as u8);
+        raw_page(flags, granule, serial, &lacing, packet)
+    }
+
+    /// 跨多页的包：除最后一页外每页 254 个满段，后续页带「续接」标志。
+    fn packet_pages(serial: u32, packet: &[u8]) -> Vec<u8> {
+        const CHUNK: usize = 255 * 254;
+        let (mut bytes, mut rest, mut flags) = (Vec::new(), packet, 0);
+        while rest.len() >= CHUNK {
+            bytes.extend(raw_page(flags, u64::MAX, serial, &[255; 254], &rest[..CHUNK]));
+            rest = &rest[CHUNK..];
+            flags = FLAG_CONTINUED;
+        }
+        bytes.extend(page(flags, 0, serial, rest));
+        bytes
+    }
+
+    fn opus_head(serial: u32) -> Vec<u8> {
+        let mut head = b"OpusHead\x01\x02".to_vec();
+        head.extend_from_slice(&312u16.to_le_bytes());
+        head.extend_from_slice(&[0x80, 0xbb, 0, 0, 0, 0, 0]);
+        page(FLAG_BOS, 0, serial, &head)
+    }
+
+    fn opus_tags(title: &str) -> Vec<u8> {
+        let comment = format!("TITLE={}", title);
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        tags.extend_from_slice(&1u32.to_le_bytes());
+        tags.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        tags.extend_from_slice(comment.as_bytes());
+        tags
+    }
+
+    fn opus_chain(serial: u32, title: &str, granule: u64) -> Vec<u8> {
+        let mut bytes = opus_head(serial);
+        bytes.extend(page(0, 0, serial, &opus_tags(title)));
+        bytes.extend(page(0, granule, serial, &[0xfc; 300]));
+        bytes.extend(page(0x04, u64::MAX, serial, &[]));
+        bytes
+    }
+
+    fn title(revision: MetadataRevision) -> Option<String> {
+        revision.media.tags.iter().find_map(|t| match &t.std {
+            Some(symphonia::core::meta::StandardTag::TrackTitle(s)) => Some(s.to_string()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_scan_chained_and_multiplexed() {
+        // 第一段复用了一个 Theora 流，第二段是单独的 Opus
+        let mut bytes = page(FLAG_BOS, 0, 7, b"\x80theora");
+        bytes.extend(opus_chain(1, "First", 48_000 * 2 + 312));
+        bytes.extend(page(0, 99_999_999, 7, &[0; 10]));
+        bytes.extend(opus_chain(2, "Last", 48_000 * 3 + 312));
+
+        let layout = scan(&mut Cursor::new(&bytes)).unwrap().unwrap();
+        assert_eq!(layout.chains.len(), 2);
+        assert_eq!(layout.skipped_streams, 1);
+        assert_eq!(layout.chains[0].codec, OggCodec::Opus);
+        assert_eq!(layout.chains[0].channels, 2);
+        assert!((layout.duration_secs() - 5.0).abs() < 1e-9);
+        assert_eq!(title(layout.chains[1].tags().unwrap()).as_deref(), Some("Last"));
+
+        assert!(layout.chosen_tags(ChainTags::First).is_none());
+        assert_eq!(title(layout.chosen_tags(ChainTags::Last).unwrap()).as_deref(), Some("Last"));
+
+        assert!(scan(&mut Cursor::new(b"fLaC\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_oversized_comment_packet_is_skipped_not_truncated() {
+        let mut huge = opus_tags("Huge");
+        huge.resize(MAX_COMMENT_BYTES + 1, 0);
+        let mut bytes = opus_head(1);
+        bytes.extend(packet_pages(1, &huge));
+        // 紧随其后的正常注释包不受影响
+        bytes.extend(page(0, 0, 1, &opus_tags("After")));
+        bytes.extend(page(0, 48_000 + 312, 1, &[0xfc; 300]));
+
+        let layout = scan(&mut Cursor::new(&bytes)).unwrap().unwrap();
+        assert_eq!(layout.chains.len(), 1);
+        assert!((layout.duration_secs() - 1.0).abs() < 1e-9);
+        assert_eq!(title(layout.chains[0].tags().unwrap()).as_deref(), Some("After"));
+    }
+}
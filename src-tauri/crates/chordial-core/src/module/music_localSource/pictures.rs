@@ -0,0 +1,499 @@
+//! 嵌入封面索引 — 扫描时只记录图片的位置与大小，需要时再按偏移读取。
+//!
+//! symphonia 探测元数据时会把嵌入图片整块读进内存；封面服务只需要其中一张，
+//! 这里直接解析标签结构，只读取帧头 / 块头，跳过图片数据本身：
+//!
+//! | 格式 | 位置 |
+//! |------|------|
+//! | FLAC | `PICTURE` 元数据块（类型 6） |
+//! | MP3 等 | 文件头部的 ID3v2.3 / v2.4 `APIC` 帧；没有时找文件末尾带 `3DI` 页脚的 v2.4 标签 |
+//! | MP4 / M4A | `moov/udta/meta/ilst/covr/data` |
+//!
+//! 经过非同步化 / 压缩 / 加密的 ID3 帧、Ogg 的 base64 图片等无法按偏移读取，
+//! 返回空列表，由调用方回退到 [`extract_cover_art`](super::scanner::extract_cover_art)。
+
+use super::id3_appended;
+use crate::module::platform::{self, PlatformPath};
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom};
+
+/// 超过该大小（字节）的嵌入图片不作为封面使用。
+pub const MAX_PICTURE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// 解析图片头时最多读取的字节数（MIME 与描述字段都很短）。
+const HEADER_PROBE_BYTES: u64 = 4096;
+
+/// MP4 中逐层查找封面的 atom 路径。
+const MP4_COVER_PATH: [&[u8; 4]; 5] = [b"moov", b"udta", b"meta", b"ilst", b"covr"];
+
+/// 一张嵌入图片的索引信息。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmbeddedPicture {
+    /// 图片用途（ID3 / FLAC 图片类型：3 为封面正面）；MP4 无此信息，记为 3
+    pub picture_type: u8,
+    /// MIME 类型（如 `image/jpeg`）
+    pub media_type: Option<String>,
+    /// 图片数据在文件中的起始偏移
+    pub offset: u64,
+    /// 图片数据字节数
+    pub size: u64,
+}
+
+impl EmbeddedPicture {
+    /// 是否为封面正面。
+    pub fn is_front_cover(&self) -> bool {
+        self.picture_type == 3
+    }
+}
+
+/// 索引文件中的嵌入图片（只读取标签结构，不读取图片数据）。
+pub fn index_file(path: &PlatformPath) -> Vec<EmbeddedPicture> {
+    match platform::open_file(path) {
+        Ok(mut file) => index_reader(&mut file).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// 挑选作为封面的图片：优先封面正面，其次第一张；超过 [`MAX_PICTURE_BYTES`] 的忽略。
+pub fn pick_cover(pictures: &[EmbeddedPicture]) -> Option<&EmbeddedPicture> {
+    let usable = || {
+        pictures
+            .iter()
+            .filter(|p| p.size > 0 && p.size <= MAX_PICTURE_BYTES)
+    };
+    usable()
+        .find(|p| p.is_front_cover())
+        .or_else(|| usable().next())
+}
+
+/// 按索引读取图片字节。
+pub fn read_picture(path: &PlatformPath, picture: &EmbeddedPicture) -> Result<Vec<u8>, String> {
+    let mut file = platform::open_file(path)?;
+    file.seek(SeekFrom::Start(picture.offset))
+        .map_err(|e| format!("定位封面数据失败: {}", e))?;
+    let mut data = Vec::with_capacity(picture.size as usize);
+    file.take(picture.size)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("读取封面数据失败: {}", e))?;
+    if (data.len() as u64) < picture.size {
+        return Err("封面数据不完整".to_string());
+    }
+    Ok(data)
+}
+
+/// 从任意可定位的数据源索引嵌入图片。
+pub fn index_reader<R: Read + Seek>(r: &mut R) -> std::io::Result<Vec<EmbeddedPicture>> {
+    let mut magic = [0u8; 12];
+    let n = read_up_to(r, &mut magic)?;
+    let magic = &magic[..n];
+    r.seek(SeekFrom::Start(0))?;
+
+    if magic.starts_with(b"ID3") {
+        let (pictures, tag_end) = index_id3v2(r, 0)?;
+        if !pictures.is_empty() {
+            return Ok(pictures);
+        }
+        // 带 ID3 头的 FLAC
+        r.seek(SeekFrom::Start(tag_end))?;
+        let mut marker = [0u8; 4];
+        if read_up_to(r, &mut marker)? == 4 && &marker == b"fLaC" {
+            return index_flac(r);
+        }
+        return Ok(Vec::new());
+    }
+    if magic.starts_with(b"fLaC") {
+        r.seek(SeekFrom::Start(4))?;
+        return index_flac(r);
+    }
+    if magic.len() >= 8 && &magic[4..8] == b"ftyp" {
+        return index_mp4(r);
+    }
+    if let Some(range) = id3_appended::locate(r)? {
+        return Ok(index_id3v2(r, range.start)?.0);
+    }
+    Ok(Vec::new())
+}
+
+// ── FLAC ──────────────────────────────────────────
+
+/// 遍历 FLAC 元数据块（调用时位于 `fLaC` 之后）。
+fn index_flac<R: Read + Seek>(r: &mut R) -> std::io::Result<Vec<EmbeddedPicture>> {
+    let mut pictures = Vec::new();
+    loop {
+        let mut header = [0u8; 4];
+        if read_up_to(r, &mut header)? < 4 {
+            break;
+        }
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as u64;
+        let body_start = r.stream_position()?;
+        if header[0] & 0x7f == 6 {
+            if let Some(picture) = parse_flac_picture(r, body_start, len)? {
+                pictures.push(picture);
+            }
+        }
+        if header[0] & 0x80 != 0 {
+            break;
+        }
+        r.seek(SeekFrom::Start(body_start + len))?;
+    }
+    Ok(pictures)
+}
+
+fn parse_flac_picture<R: Read + Seek>(
+    r: &mut R,
+    body_start: u64,
+    len: u64,
+) -> std::io::Result<Option<EmbeddedPicture>> {
+    let mut head = vec![0u8; len.min(HEADER_PROBE_BYTES) as usize];
+    let n = read_up_to(r, &mut head)?;
+    let head = &head[..n];
+    let mut cur = ByteCursor::new(head);
+    let Some(picture_type) = cur.u32_be() else {
+        return Ok(None);
+    };
+    let Some(mime) = cur.u32_be().and_then(|l| cur.take(l as usize)) else {
+        return Ok(None);
+    };
+    let media_type = String::from_utf8_lossy(mime).into_owned();
+    // 描述 + 宽 / 高 / 色深 / 索引色数
+    let Some(size) = cur
+        .u32_be()
+        .and_then(|l| cur.take(l as usize))
+        .and_then(|_| cur.take(16))
+        .and_then(|_| cur.u32_be())
+    else {
+        return Ok(None);
+    };
+    let offset = body_start + cur.pos as u64;
+    if offset + size as u64 > body_start + len {
+        return Ok(None);
+    }
+    Ok(Some(EmbeddedPicture {
+        picture_type: picture_type.min(u8::MAX as u32) as u8,
+        media_type: (!media_type.is_empty()).then_some(media_type),
+        offset,
+        size: size as u64,
+    }))
+}
+
+// ── ID3v2 ─────────────────────────────────────────
+
+/// 索引从 `start` 开始的 ID3v2 标签中的 APIC 帧，同时返回标签结束位置。
+fn index_id3v2<R: Read + Seek>(r: &mut R, start: u64) -> std::io::Result<(Vec<EmbeddedPicture>, u64)> {
+    let mut header = [0u8; 10];
+    r.seek(SeekFrom::Start(start))?;
+    if read_up_to(r, &mut header)? < 10 {
+        return Ok((Vec::new(), start));
+    }
+    let major = header[3];
+    let flags = header[5];
+    let tag_size = syncsafe(&header[6..10]);
+    let tag_end = start + 10 + tag_size + if flags & 0x10 != 0 { 10 } else { 0 };
+    // 整个标签经过非同步化时，帧内偏移与文件偏移不再一一对应
+    if !(3..=4).contains(&major) || flags & 0x80 != 0 {
+        return Ok((Vec::new(), tag_end));
+    }
+
+    let mut pos = start + 10;
+    if flags & 0x40 != 0 {
+        let mut ext = [0u8; 4];
+        r.read_exact(&mut ext)?;
+        pos += if major == 4 {
+            syncsafe(&ext)
+        } else {
+            u32::from_be_bytes(ext) as u64 + 4
+        };
+    }
+
+    let mut pictures = Vec::new();
+    while pos + 10 <= start + 10 + tag_size {
+        r.seek(SeekFrom::Start(pos))?;
+        let mut frame = [0u8; 10];
+        r.read_exact(&mut frame)?;
+        if frame[0] == 0 {
+            break; // 填充区
+        }
+        let size = if major == 4 {
+            syncsafe(&frame[4..8])
+        } else {
+            u32::from_be_bytes([frame[4], frame[5], frame[6], frame[7]]) as u64
+        };
+        let data_start = pos + 10;
+        if &frame[..4] == b"APIC" {
+            if let Some(picture) = parse_apic(r, major, frame[9], data_start, size)? {
+                pictures.push(picture);
+            }
+        }
+        pos = data_start + size;
+    }
+    Ok((pictures, tag_end))
+}
+
+fn parse_apic<R: Read + Seek>(
+    r: &mut R,
+    major: u8,
+    format_flags: u8,
+    data_start: u64,
+    size: u64,
+) -> std::io::Result<Option<EmbeddedPicture>> {
+    let mut skip = 0u64;
+    if major == 4 {
+        // 压缩 / 加密 / 非同步化的帧无法直接读取
+        if format_flags & 0x0e != 0 {
+            return Ok(None);
+        }
+        if format_flags & 0x40 != 0 {
+            skip += 1; // 分组标识
+        }
+        if format_flags & 0x01 != 0 {
+            skip += 4; // 数据长度指示
+        }
+    } else if format_flags & 0xc0 != 0 {
+        return Ok(None);
+    } else if format_flags & 0x20 != 0 {
+        skip += 1;
+    }
+
+    let body_start = data_start + skip;
+    let body_len = size.saturating_sub(skip);
+    r.seek(SeekFrom::Start(body_start))?;
+    let mut head = vec![0u8; body_len.min(HEADER_PROBE_BYTES) as usize];
+    let n = read_up_to(r, &mut head)?;
+    let head = &head[..n];
+
+    let mut cur = ByteCursor::new(head);
+    let Some(encoding) = cur.u8() else {
+        return Ok(None);
+    };
+    let Some(mime) = cur.until_nul(1) else {
+        return Ok(None);
+    };
+    let media_type = String::from_utf8_lossy(mime).into_owned();
+    let Some(picture_type) = cur.u8() else {
+        return Ok(None);
+    };
+    // UTF-16 描述以两字节 0 结束
+    let nul_width = if matches!(encoding, 1 | 2) { 2 } else { 1 };
+    if cur.until_nul(nul_width).is_none() {
+        return Ok(None);
+    }
+    let header_len = cur.pos as u64;
+    Ok(Some(EmbeddedPicture {
+        picture_type,
+        media_type: normalize_id3_mime(&media_type),
+        offset: body_start + header_len,
+        size: body_len.saturating_sub(header_len),
+    }))
+}
+
+/// ID3 中 MIME 字段偶尔只写 `JPG` / `PNG`。
+fn normalize_id3_mime(mime: &str) -> Option<String> {
+    match mime.to_ascii_lowercase().as_str() {
+        "" => None,
+        "jpg" | "jpeg" => Some("image/jpeg".to_string()),
+        "png" => Some("image/png".to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn syncsafe(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0u64, |acc, &b| (acc << 7) | (b & 0x7f) as u64)
+}
+
+// ── MP4 ───────────────────────────────────────────
+
+fn index_mp4<R: Read + Seek>(r: &mut R) -> std::io::Result<Vec<EmbeddedPicture>> {
+    let file_end = r.seek(SeekFrom::End(0))?;
+    let (mut start, mut end) = (0u64, file_end);
+    for name in MP4_COVER_PATH {
+        let Some((body_start, body_end)) = find_atom(r, start, end, name)? else {
+            return Ok(Vec::new());
+        };
+        // `meta` 是 full box，子 atom 之前有 4 字节版本 / 标志
+        start = if name == b"meta" {
+            body_start + 4
+        } else {
+            body_start
+        };
+        end = body_end;
+    }
+
+    let mut pictures = Vec::new();
+    let mut pos = start;
+    while let Some(atom) = read_atom_header(r, pos, end)? {
+        let (body_start, body_end) = (atom.body_start, atom.body_end);
+        if &atom.name == b"data" && body_end >= body_start + 8 {
+            let mut kind = [0u8; 4];
+            r.seek(SeekFrom::Start(body_start))?;
+            r.read_exact(&mut kind)?;
+            let media_type = match u32::from_be_bytes(kind) & 0x00ff_ffff {
+                13 => Some("image/jpeg".to_string()),
+                14 => Some("image/png".to_string()),
+                27 => Some("image/bmp".to_string()),
+                _ => None,
+            };
+            pictures.push(EmbeddedPicture {
+                picture_type: 3,
+                media_type,
+                offset: body_start + 8,
+                size: body_end - body_start - 8,
+            });
+        }
+        if body_end <= atom.start {
+            break;
+        }
+        pos = body_end;
+    }
+    Ok(pictures)
+}
+
+/// 在 `[start, end)` 范围内查找名为 `name` 的 atom，返回其数据区范围。
+pub(super) fn find_atom<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    end: u64,
+    name: &[u8; 4],
+) -> std::io::Result<Option<(u64, u64)>> {
+    let mut pos = start;
+    while let Some(atom) = read_atom_header(r, pos, end)? {
+        if &atom.name == name {
+            return Ok(Some((atom.body_start, atom.body_end)));
+        }
+        if atom.body_end <= atom.start {
+            break;
+        }
+        pos = atom.body_end;
+    }
+    Ok(None)
+}
+
+/// MP4 atom 头信息。
+struct Atom {
+    start: u64,
+    body_start: u64,
+    body_end: u64,
+    name: [u8; 4],
+}
+
+/// 读取位于 `pos` 的 atom 头。
+fn read_atom_header<R: Read + Seek>(
+    r: &mut R,
+    pos: u64,
+    end: u64,
+) -> std::io::Result<Option<Atom>> {
+    if pos + 8 > end {
+        return Ok(None);
+    }
+    r.seek(SeekFrom::Start(pos))?;
+    let mut header = [0u8; 8];
+    r.read_exact(&mut header)?;
+    let name = [header[4], header[5], header[6], header[7]];
+    let (body_start, size) = match u32::from_be_bytes([header[0], header[1], header[2], header[3]])
+    {
+        0 => (pos + 8, end - pos),
+        1 => {
+            let mut large = [0u8; 8];
+            r.read_exact(&mut large)?;
+            (pos + 16, u64::from_be_bytes(large))
+        }
+        n => (pos + 8, n as u64),
+    };
+    let body_end = pos.saturating_add(size).min(end);
+    if body_end < body_start {
+        return Ok(None);
+    }
+    Ok(Some(Atom {
+        start: pos,
+        body_start,
+        body_end,
+        name,
+    }))
+}
+
+// ── 工具 ──────────────────────────────────────────
+
+/// 尽量读满 `buf`，返回实际读取的字节数（到达末尾时可能不足）。
+fn read_up_to<R: Read>(r: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// 在内存切片上顺序读取字段。
+struct ByteCursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteCursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let slice = self.data.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32_be(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// 读取到 `width` 字节宽的 0 终止符为止（不含终止符），UTF-16 时按两字节对齐查找。
+    fn until_nul(&mut self, width: usize) -> Option<&'a [u8]> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .chunks_exact(width)
+            .position(|c| c.iter().all(|&b| b == 0))?
+            * width;
+        let value = &rest[..len];
+        self.pos += len + width;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_index_id3_apic_and_read_back() {
+        let image = b"\xff\xd8\xff\xe0fake-jpeg";
+        let mut apic = vec![0u8]; // Latin-1
+        apic.extend_from_slice(b"image/jpeg\0");
+        apic.push(3); // 封面正面
+        apic.extend_from_slice(b"cover\0");
+        apic.extend_from_slice(image);
+
+        let mut frame = b"APIC".to_vec();
+        frame.extend_from_slice(&(apic.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(&apic);
+
+        let mut file = b"ID3\x03\x00\x00".to_vec();
+        let size = frame.len() as u32;
+        file.extend((0..4).rev().map(|i| ((size >> (7 * i)) & 0x7f) as u8));
+        file.extend_from_slice(&frame);
+        file.extend_from_slice(b"\xff\xfbaudio");
+
+        let pictures = index_reader(&mut Cursor::new(&file)).unwrap();
+        let cover = pick_cover(&pictures).unwrap();
+        assert_eq!(cover.media_type.as_deref(), Some("image/jpeg"));
+        let start = cover.offset as usize;
+        assert_eq!(&file[start..start + cover.size as usize], image);
+    }
+}
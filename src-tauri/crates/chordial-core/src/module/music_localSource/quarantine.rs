@@ -0,0 +1,174 @@
+//! 损坏文件隔离 — 记录反复探测失败的音频文件。
+//!
+//! 每次扫描都对同一个损坏文件重复探测、重复打印错误没有意义。
+//! [`Quarantine`] 按文件路径累计失败次数，达到 [`QUARANTINE_THRESHOLD`] 后
+//! 该文件被隔离：后续扫描直接跳过，不进入音乐库。
+//!
+//! 隔离记录带有文件的 mtime + size；文件被替换或修复（两者任一变化）后
+//! 记录自动失效，下次扫描会重新探测。用户也可以通过「重试」手动解除隔离。
+
+use crate::module::platform::{self, PlatformPath};
+use crate::module::storage::persistent::PersistentStore;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 连续失败多少次后隔离。
+pub const QUARANTINE_THRESHOLD: u32 = 3;
+
+/// 单个文件的失败记录。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuarantineEntry {
+    /// 规范化后的文件路径
+    pub path: String,
+    /// 累计失败次数
+    pub failures: u32,
+    /// 最近一次失败的错误信息
+    pub last_error: String,
+    /// 最近一次失败的时间（Unix 秒）
+    pub last_failed_at: u64,
+    /// 失败时文件的修改时间 / 大小，用于判断文件是否已被替换
+    pub mtime: u64,
+    pub size: u64,
+}
+
+impl QuarantineEntry {
+    /// 是否已达到隔离阈值。
+    pub fn is_quarantined(&self) -> bool {
+        self.failures >= QUARANTINE_THRESHOLD
+    }
+}
+
+/// 损坏文件隔离列表（持久化）。
+pub struct Quarantine {
+    store: PersistentStore,
+    entries: RwLock<HashMap<String, QuarantineEntry>>,
+}
+
+impl Quarantine {
+    const KEY: &str = "quarantine";
+
+    /// 从持久化存储加载已有的失败记录。
+    pub fn new(store: PersistentStore) -> Self {
+        let entries = store
+            .get::<HashMap<String, QuarantineEntry>>(Self::KEY)
+            .unwrap_or_default();
+        Self {
+            store,
+            entries: RwLock::new(entries),
+        }
+    }
+
+    /// 文件是否处于隔离状态（文件已变化的记录视为失效）。
+    pub fn is_quarantined(&self, path: &PlatformPath) -> bool {
+        let key = platform::path_to_string(path);
+        let entries = self.entries.read();
+        match entries.get(&key) {
+            Some(entry) if entry.is_quarantined() => file_stamp(path) == (entry.mtime, entry.size),
+            _ => false,
+        }
+    }
+
+    /// 记录一次探测失败，返回该文件是否因此进入隔离。
+    ///
+    /// 文件自上次失败后发生变化时，失败计数从头开始。
+    pub fn record_failure(&self, path: &PlatformPath, error: &str) -> bool {
+        let key = platform::path_to_string(path);
+        let (mtime, size) = file_stamp(path);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut entries = self.entries.write();
+        let entry = entries.entry(key.clone()).or_insert_with(|| QuarantineEntry {
+            path: key,
+            failures: 0,
+            last_error: String::new(),
+            last_failed_at: 0,
+            mtime,
+            size,
+        });
+        if (entry.mtime, entry.size) != (mtime, size) {
+            entry.failures = 0;
+            entry.mtime = mtime;
+            entry.size = size;
+        }
+        let was_quarantined = entry.is_quarantined();
+        entry.failures += 1;
+        entry.last_error = error.to_string();
+        entry.last_failed_at = now;
+        !was_quarantined && entry.is_quarantined()
+    }
+
+    /// 文件探测成功：清除其失败记录。
+    pub fn record_success(&self, path: &PlatformPath) {
+        let key = platform::path_to_string(path);
+        if self.entries.read().contains_key(&key) {
+            self.entries.write().remove(&key);
+        }
+    }
+
+    /// 解除隔离（删除记录），返回记录是否存在。
+    pub fn release(&self, path: &PlatformPath) -> bool {
+        self.entries
+            .write()
+            .remove(&platform::path_to_string(path))
+            .is_some()
+    }
+
+    /// 当前处于隔离状态的文件，按最近失败时间降序。
+    pub fn list(&self) -> Vec<QuarantineEntry> {
+        let mut list: Vec<QuarantineEntry> = self
+            .entries
+            .read()
+            .values()
+            .filter(|e| e.is_quarantined())
+            .cloned()
+            .collect();
+        list.sort_by_key(|e| std::cmp::Reverse(e.last_failed_at));
+        list
+    }
+
+    /// 持久化失败记录。
+    pub fn save(&self) -> Result<(), String> {
+        let entries = self.entries.read().clone();
+        self.store.set(Self::KEY, &entries)?;
+        self.store.save()
+    }
+}
+
+/// 读取文件的 (mtime, size)；文件不可访问时返回 (0, 0)。
+fn file_stamp(path: &PlatformPath) -> (u64, u64) {
+    (
+        platform::file_modified_secs(path).unwrap_or(0),
+        platform::file_size(path).unwrap_or(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantined_after_threshold_and_released_on_retry() {
+        let dir = std::env::temp_dir().join(format!("chordial_quarantine_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("broken.flac");
+        std::fs::write(&file, b"not audio").unwrap();
+        let path = PlatformPath::from(file.to_string_lossy().as_ref());
+
+        let quarantine = Quarantine::new(PersistentStore::new(dir.join("quarantine.json")));
+        for _ in 1..QUARANTINE_THRESHOLD {
+            assert!(!quarantine.record_failure(&path, "bad header"));
+        }
+        assert!(!quarantine.is_quarantined(&path));
+        assert!(quarantine.record_failure(&path, "bad header"));
+        assert!(quarantine.is_quarantined(&path));
+        assert_eq!(quarantine.list().len(), 1);
+
+        assert!(quarantine.release(&path));
+        assert!(!quarantine.is_quarantined(&path));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,121 @@
+//! 后台扫描 — 在工作线程中逐个文件夹重扫，命令立即返回。
+//!
+//! 进度以 [`AppEvent::ScanProgress`](crate::module::events::AppEvent::ScanProgress) 发布：
+//! 每开始一个文件夹发布一次，读取文件期间每处理一批再发布一次；结束（完成、取消或出错）时发布
+//! [`AppEvent::ScanFinished`](crate::module::events::AppEvent::ScanFinished)。
+//! 同一时间只运行一次扫描，取消通过 `tasks` 中以 [`SCAN_TASK_ID`] 登记的令牌：
+//! 令牌随 [`ScanOptions::cancel`](super::scanner::ScanOptions::cancel) 传入重扫，正在读取的文件读完即停。
+
+use super::source::RescanReport;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// 后台扫描在取消登记表中的任务 ID。
+pub const SCAN_TASK_ID: &str = "library-scan";
+
+/// 扫描进度。
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct ScanProgress {
+    /// 正在扫描的文件夹
+    pub folder: String,
+    /// 当前文件夹的序号（从 0 开始）
+    pub folder_index: usize,
+    pub folder_count: usize,
+    /// 当前文件夹已处理的文件数（含未改动的文件）
+    pub done: usize,
+    /// 当前文件夹的音频文件数
+    pub total: usize,
+}
+
+/// 扫描状态；`report` 为最近一次扫描（进行中时为已完成的文件夹）的累计结果。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanStatus {
+    pub running: bool,
+    pub progress: Option<ScanProgress>,
+    pub report: Option<RescanReport>,
+    /// 最近一次扫描是否被取消
+    pub cancelled: bool,
+    /// 最近一次扫描的错误（已完成文件夹的结果保留在库中）
+    pub error: Option<String>,
+}
+
+/// 后台扫描的状态记录。
+#[derive(Default)]
+pub struct ScanJob {
+    status: Mutex<ScanStatus>,
+}
+
+impl ScanJob {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> ScanStatus {
+        self.status.lock().clone()
+    }
+
+    /// 标记扫描开始；已有扫描在运行时返回 `false`。
+    pub fn begin(&self) -> bool {
+        let mut status = self.status.lock();
+        if status.running {
+            return false;
+        }
+        *status = ScanStatus {
+            running: true,
+            report: Some(RescanReport::default()),
+            ..Default::default()
+        };
+        true
+    }
+
+    pub fn update(&self, progress: ScanProgress) {
+        self.status.lock().progress = Some(progress);
+    }
+
+    /// 把一个文件夹的结果累加进本次扫描的报告。
+    pub fn add_report(&self, folder: RescanReport) {
+        let mut status = self.status.lock();
+        let report = status.report.get_or_insert_with(RescanReport::default);
+        report.files_found += folder.files_found;
+        report.unchanged += folder.unchanged;
+        report.changed += folder.changed;
+        report.indexed += folder.indexed;
+        report.errors.extend(folder.errors);
+        report.cancelled |= folder.cancelled;
+    }
+
+    /// 标记扫描结束，返回最终状态。
+    pub fn finish(&self, cancelled: bool, error: Option<String>) -> ScanStatus {
+        let mut status = self.status.lock();
+        status.running = false;
+        status.progress = None;
+        status.cancelled = cancelled;
+        status.error = error;
+        status.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_one_scan_runs_and_reports_accumulate() {
+        let job = ScanJob::new();
+        assert!(job.begin());
+        assert!(!job.begin());
+        for indexed in [2, 3] {
+            job.add_report(RescanReport {
+                files_found: 5,
+                indexed,
+                ..Default::default()
+            });
+        }
+        let status = job.finish(true, None);
+        assert!(!status.running && status.cancelled);
+        let report = status.report.unwrap();
+        assert_eq!((report.files_found, report.indexed), (10, 5));
+        assert!(job.begin());
+    }
+}
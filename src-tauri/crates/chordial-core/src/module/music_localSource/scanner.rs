@@ -9,15 +9,29 @@
 //! - 桌面端：`std::fs::File` → symphonia
 //! - Android：`Cursor<Vec<u8>>`（预读全部字节）→ symphonia
 
+use super::file_stats;
+use super::id3_appended;
+use super::meta_cache::MetaCache;
+use super::mp4_tables;
+use super::ogg_chain;
+use super::pictures::{self, EmbeddedPicture};
+use crate::module::cancel::CancellationToken;
+use crate::module::music_library::language;
+use crate::module::music_library::models::LocalizedText;
 use crate::module::platform::{self, PlatformPath};
 use crate::module::perf;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use symphonia::core::codecs::audio::well_known::*;
+use symphonia::core::codecs::audio::AudioCodecId;
 use symphonia::core::formats::probe::Hint;
 use symphonia::core::formats::{FormatOptions, TrackType};
 use symphonia::core::io::MediaSourceStream;
-use symphonia::core::meta::{MetadataOptions, StandardTag};
+use symphonia::core::meta::{MetadataOptions, RawTag, RawValue, StandardTag, Tag};
 
 /// 从音频文件中提取的元数据。
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct AudioMeta {
     /// 歌曲标题（来自 ID3 / Vorbis comment / MP4 等标签）
     pub title: Option<String>,
@@ -33,8 +47,56 @@ pub struct AudioMeta {
     pub channels: Option<u8>,
     /// 容器格式名称（如 "FLAC", "MP3", "MP4"）
     pub format_name: Option<String>,
-    /// 发行年份（来自 ID3 TYER/TDRC、Vorbis DATE/YEAR、MP4 ©day 等标签）
+    /// 编码名称（如 "flac"、"aac"、"alac"），见 [`codec_name`]
+    pub codec: Option<String>,
+    /// 是否为无损编码（FLAC / ALAC / WAV 等 PCM）
+    pub lossless: bool,
+    /// 平均码率（kbps）：M4A 按采样表，其余按文件大小 / 时长
+    pub bitrate_kbps: Option<u32>,
+    /// 发行年份（取自 `release_date`，没有时取自 `original_date`）
     pub year: Option<u32>,
+    /// 发行日期（来自 ID3 TYER/TDRC/TDRL、Vorbis DATE/YEAR、MP4 ©day 等），见 [`parse_date_from_value`]
+    pub release_date: Option<String>,
+    /// 原始发行日期（来自 ID3 TDOR/TORY、Vorbis ORIGINALDATE/ORIGINALYEAR）
+    pub original_date: Option<String>,
+    /// 其他语言的标题（来自 `TITLE:ja-Latn`、TXXX `Title (Romanized)` 或重复的标题标签）
+    pub alt_titles: Vec<LocalizedText>,
+    /// 其他语言的艺人名（整串，未拆分）
+    pub alt_artists: Vec<LocalizedText>,
+    /// 流派（来自 TCON / GENRE / ©gen；`;` 分隔的多值已拆开）
+    pub genres: Vec<String>,
+    /// 作曲者（来自 TCOM / COMPOSER / ©wrt；`;` 分隔的多值已拆开）
+    pub composers: Vec<String>,
+    /// 音轨号
+    pub track_number: Option<u32>,
+    /// 碟号
+    pub disc_number: Option<u32>,
+    /// ReplayGain 音轨增益（dB，来自 REPLAYGAIN_TRACK_GAIN / TXXX 同名帧 / MP4 freeform）
+    pub replay_gain_db: Option<f32>,
+    /// 国际标准录音代码（来自 TSRC / ISRC / MP4 freeform ISRC），已规范化，见 [`normalize_isrc`]
+    pub isrc: Option<String>,
+    /// 唱片编号（来自 TXXX:CATALOGNUMBER / Vorbis CATALOGNUMBER / iTunes catalognumber）
+    pub catalog_number: Option<String>,
+    /// 条形码（UPC / EAN，来自 BARCODE / UPC / EAN/UPN 标签），见 [`normalize_barcode`]
+    pub barcode: Option<String>,
+    /// 演唱语言（来自 TLAN / Vorbis LANGUAGE / MP4 freeform LANGUAGE），已规范化，见 [`language::normalize`]
+    pub language: Option<String>,
+    /// 未映射到上述字段的其余标签（MusicBrainz ID、TXXX / WXXX 自定义帧、自定义 Vorbis comment 等），
+    /// 见 [`collect_extra_tags`]
+    pub extra_tags: HashMap<String, Vec<String>>,
+    /// 嵌入图片的索引（类型 / 大小 / 偏移，不含图片数据），见 [`pictures`]
+    pub pictures: Vec<EmbeddedPicture>,
+    /// 评分（0–100，来自 POPM / FMPS_RATING 等，见 [`file_stats`]）
+    pub rating: Option<u8>,
+    /// 其他播放器记录的播放次数
+    pub play_count: Option<u64>,
+}
+
+/// 备用语言标签所对应的字段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AltField {
+    Title,
+    Artist,
 }
 
 /// 探测音频文件，提取元数据。
@@ -77,10 +139,47 @@ pub fn probe_file(path: &PlatformPath) -> Result<AudioMeta, String> {
             if let Some(audio_params) = params.audio() {
                 meta.sample_rate = audio_params.sample_rate;
                 meta.channels = audio_params.channels.as_ref().map(|c| c.count() as u8);
+                meta.codec = codec_name(audio_params.codec);
+                meta.lossless = is_lossless_codec(audio_params.codec);
+                meta.duration_secs = match (track.num_frames, audio_params.sample_rate) {
+                    (Some(frames), Some(rate)) if rate > 0 => Some((frames + rate as u64 / 2) / rate as u64),
+                    _ => None,
+                };
             }
         }
     }
 
+    // OGG 逐页遍历：链式流的总时长，以及首个逻辑流不是音频时的采样率 / 声道
+    let ogg = if fi.short_name == "ogg" {
+        platform::open_file(path)
+            .ok()
+            .and_then(|mut file| ogg_chain::scan(&mut file).ok().flatten())
+    } else {
+        None
+    };
+    if let Some(chain) = ogg.as_ref().and_then(|layout| layout.chains.first()) {
+        meta.sample_rate = meta.sample_rate.or(Some(chain.sample_rate));
+        meta.channels = meta.channels.or(Some(chain.channels));
+    }
+    if let Some(layout) = &ogg {
+        meta.duration_secs = Some(layout.duration_secs().round() as u64).filter(|d| *d > 0);
+    }
+    // M4A 按音频轨道的 stts 计时，不受 mvhd 时间刻度与其他轨道影响
+    if fi.short_name == "isomp4" {
+        if let Ok(Some(info)) = mp4_tables::read_audio_info(path) {
+            meta.duration_secs = info.duration_ms.map(|ms| (ms + 500) / 1000);
+            meta.codec = Some(info.codec.name());
+            meta.lossless = info.is_lossless();
+            meta.bitrate_kbps = info.bitrate_kbps;
+        }
+    }
+    if meta.bitrate_kbps.is_none() {
+        meta.bitrate_kbps = match (platform::file_size(path), meta.duration_secs) {
+            (Ok(size), Some(secs)) if secs > 0 => Some((size * 8 / 1000 / secs) as u32),
+            _ => None,
+        };
+    }
+
     // 标签（标题、艺术家、专辑）
     // 消费所有旧版本，只留最新
     loop {
@@ -91,11 +190,28 @@ pub fn probe_file(path: &PlatformPath) -> Result<AudioMeta, String> {
         format.metadata().pop();
     }
 
-    if let Some(revision) = format.metadata().current() {
+    // 链式 OGG 按设置改用最后一段的注释；头部没有标签时再找文件末尾带 `3DI` 页脚的
+    // ID3v2.4 标签（symphonia 只探测流开头）
+    let metadata = format.metadata();
+    let replacement = match ogg.as_ref().and_then(|layout| layout.chosen_tags(ogg_chain::chain_tags())) {
+        Some(tags) => Some(tags),
+        None if metadata.current().is_none() => id3_appended::read_revision(path).ok().flatten(),
+        None => None,
+    };
+    let revision = replacement.as_ref().or(metadata.current());
+
+    if let Some(revision) = revision {
         for tag in &revision.media.tags {
             match &tag.std {
                 Some(StandardTag::TrackTitle(title)) => {
-                    meta.title = Some(title.to_string());
+                    // 多值标题（Vorbis 重复 TITLE、MP4 多值 atom）：首个为主标题，其余作为备用标题
+                    match &meta.title {
+                        None => meta.title = Some(title.to_string()),
+                        Some(main) if main.as_str() != title.as_str() => {
+                            push_localized(&mut meta.alt_titles, "und", title);
+                        }
+                        Some(_) => {}
+                    }
                 }
                 Some(StandardTag::Artist(artist)) => {
                     meta.artist = Some(artist.to_string());
@@ -103,36 +219,94 @@ pub fn probe_file(path: &PlatformPath) -> Result<AudioMeta, String> {
                 Some(StandardTag::Album(album)) => {
                     meta.album = Some(album.to_string());
                 }
+                Some(StandardTag::Genre(genre)) => {
+                    for name in split_genres(genre) {
+                        if !meta.genres.contains(&name) {
+                            meta.genres.push(name);
+                        }
+                    }
+                }
+                Some(StandardTag::Composer(composer)) => {
+                    for name in split_composers(composer) {
+                        if !meta.composers.contains(&name) {
+                            meta.composers.push(name);
+                        }
+                    }
+                }
+                // 0 是部分工具写入的「未知」占位
+                Some(StandardTag::TrackNumber(n)) => {
+                    meta.track_number = u32::try_from(*n).ok().filter(|n| *n > 0);
+                }
+                Some(StandardTag::DiscNumber(n)) => {
+                    meta.disc_number = u32::try_from(*n).ok().filter(|n| *n > 0);
+                }
+                Some(StandardTag::ReplayGainTrackGain(gain)) => {
+                    meta.replay_gain_db = parse_gain_db(gain);
+                }
+                Some(StandardTag::IdentIsrc(isrc)) => {
+                    if let Some(code) = normalize_isrc(isrc) {
+                        meta.isrc = Some(code);
+                    }
+                }
+                Some(StandardTag::IdentCatalogNumber(number)) => {
+                    let number = number.trim();
+                    if !number.is_empty() {
+                        meta.catalog_number = Some(number.to_string());
+                    }
+                }
+                Some(StandardTag::IdentBarcode(code) | StandardTag::IdentUpc(code) | StandardTag::IdentEanUpn(code)) => {
+                    if let Some(code) = normalize_barcode(code) {
+                        meta.barcode = Some(code);
+                    }
+                }
                 _ => {}
             }
 
-            // 年份兜底解析：StandardTag 未覆盖或解析失败时，
-            // 通过 raw tag key（不区分大小写）匹配常见 year/date 字段名。
-            // 覆盖 ID3 (TYER/TDRC/TDRL)、Vorbis (DATE/YEAR)、MP4 (©day) 等。
-            if meta.year.is_none() {
-                let key_lower = tag.raw.key.to_lowercase();
-                let is_year_key = matches!(
-                    key_lower.as_str(),
-                    "year"
-                        | "date"
-                        | "tdrc"
-                        | "tdrl"
-                        | "tory"
-                        | "tyer"
-                        | "release_date"
-                        | "releasedate"
-                        | "originaldate"
-                        | "©day"
-                );
-                if is_year_key {
-                    if let Some(y) = parse_year_from_value(&tag.raw.value) {
-                        meta.year = Some(y);
+            // 多语言字段：TXXX 取描述作为键，MP4 freeform 取 `----:mean:name` 中的 name
+            if tag.std.is_none() {
+                if let Some((field, lang)) = classify_alt_key(&alt_tag_key(&tag.raw)) {
+                    if let Some(text) = raw_value_text(&tag.raw.value) {
+                        let target = match field {
+                            AltField::Title => &mut meta.alt_titles,
+                            AltField::Artist => &mut meta.alt_artists,
+                        };
+                        push_localized(target, &lang, &text);
+                    }
+                }
+            }
+
+            if is_language_key(&alt_tag_key(&tag.raw)) && meta.language.is_none() {
+                meta.language = raw_value_text(&tag.raw.value).and_then(|v| language::normalize(&v));
+            }
+
+            // 日期通过 raw tag key（不区分大小写）识别；同类日期出现多次
+            // （如 TYER 与 TDRC 并存）时保留精度更高的一个
+            if let Some(kind) = date_key_kind(&tag.raw.key) {
+                if let Some(date) = parse_date_from_value(&tag.raw.value) {
+                    let slot = match kind {
+                        DateKind::Release => &mut meta.release_date,
+                        DateKind::Original => &mut meta.original_date,
+                    };
+                    if slot.as_ref().is_none_or(|d| d.len() < date.len()) {
+                        *slot = Some(date);
                     }
                 }
             }
         }
+        meta.year = meta
+            .release_date
+            .as_deref()
+            .or(meta.original_date.as_deref())
+            .and_then(|d| d[..4].parse().ok());
+        meta.extra_tags = collect_extra_tags(&revision.media.tags);
+        let stats = file_stats::from_tags(&revision.media.tags);
+        meta.rating = stats.rating;
+        meta.play_count = stats.play_count;
     }
 
+    // 图片只记位置，封面服务需要时再按偏移读取
+    meta.pictures = pictures::index_file(path);
+
     // 若标签中无标题，回退到文件名（不含扩展名）
     if meta.title.is_none() {
         meta.title = platform::path_file_stem(path);
@@ -148,6 +322,98 @@ pub fn probe_file(path: &PlatformPath) -> Result<AudioMeta, String> {
     Ok(meta)
 }
 
+/// 收集未被 [`AudioMeta`] 固定字段使用的标签，避免其他工具写入的信息被静默丢弃。
+///
+/// - 键：原始标签名；TXXX / WXXX 等带描述的自定义帧为 `TXXX:描述`，
+///   MP4 freeform atom 保持 `----:mean:name` 原样
+/// - 值：全部文本值（多值标签、同名重复帧依次追加）；二进制值（PRIV、GEOB 等）跳过
+///
+/// 标题 / 艺人 / 专辑 / 流派 / 作曲、音轨 / 碟号、ISRC / 唱片编号 / 条形码、日期、演唱语言、评分 / 播放次数和多语言标签已映射到专门字段，不在此重复。
+pub fn collect_extra_tags(tags: &[Tag]) -> HashMap<String, Vec<String>> {
+    let mut extra: HashMap<String, Vec<String>> = HashMap::new();
+    for tag in tags {
+        let mapped = matches!(
+            tag.std,
+            Some(
+                StandardTag::TrackTitle(_)
+                    | StandardTag::Artist(_)
+                    | StandardTag::Album(_)
+                    | StandardTag::Genre(_)
+                    | StandardTag::Composer(_)
+                    | StandardTag::TrackNumber(_)
+                    | StandardTag::DiscNumber(_)
+                    | StandardTag::ReplayGainTrackGain(_)
+                    | StandardTag::IdentIsrc(_)
+                    | StandardTag::IdentCatalogNumber(_)
+                    | StandardTag::IdentBarcode(_)
+                    | StandardTag::IdentUpc(_)
+                    | StandardTag::IdentEanUpn(_)
+            )
+        ) || date_key_kind(&tag.raw.key).is_some()
+            || is_language_key(&alt_tag_key(&tag.raw))
+            || file_stats::is_stats_tag(tag)
+            || (tag.std.is_none() && classify_alt_key(&alt_tag_key(&tag.raw)).is_some());
+        if mapped {
+            continue;
+        }
+        let values = raw_value_texts(&tag.raw.value);
+        if values.is_empty() {
+            continue;
+        }
+        extra.entry(extra_tag_key(&tag.raw)).or_default().extend(values);
+    }
+    extra
+}
+
+/// 解析 ReplayGain 增益值，如 `-6.54 dB`、`+1.2dB`；非有限值视为无效。
+fn parse_gain_db(raw: &str) -> Option<f32> {
+    let raw = raw.trim();
+    let number = raw
+        .get(raw.len().saturating_sub(2)..)
+        .filter(|unit| unit.eq_ignore_ascii_case("db"))
+        .map_or(raw, |_| &raw[..raw.len() - 2]);
+    number.trim().parse::<f32>().ok().filter(|g| g.is_finite())
+}
+
+/// 拆分流派标签：`;` / `\0` 分隔的多值依次拆开；ID3v1 风格的纯数字编号（如 `(17)`）无法可靠映射，忽略。
+fn split_genres(raw: &str) -> Vec<String> {
+    raw.split([';', '\0'])
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+        .filter(|g| !g.trim_matches(['(', ')']).chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 规范化 ISRC：去掉连字符与空白并转为大写（`us-rc1-76-07839` → `USRC17607839`）。
+///
+/// 不是 12 位字母数字的值视为无效，返回 `None`。
+pub fn normalize_isrc(raw: &str) -> Option<String> {
+    let code: String = raw
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    (code.len() == 12 && code.chars().all(|c| c.is_ascii_alphanumeric())).then_some(code)
+}
+
+/// 规范化条形码：去掉空白与连字符；只接受 8–14 位数字（EAN-8、UPC-A、EAN-13、GTIN-14）。
+pub fn normalize_barcode(raw: &str) -> Option<String> {
+    let code: String = raw.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+    ((8..=14).contains(&code.len()) && code.chars().all(|c| c.is_ascii_digit())).then_some(code)
+}
+
+/// 拆分作曲者标签：`;` / `\0` 分隔的多值依次拆开。
+///
+/// 不像艺人名那样按 `&` / `,` 拆分：作曲者常写作「姓, 名」或乐团名。
+fn split_composers(raw: &str) -> Vec<String> {
+    raw.split([';', '\0'])
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// 从音频文件中提取嵌入封面图片。
 ///
 /// 使用 symphonia 读取 FLAC/Vorbis comments 或 ID3v2 中的封面数据。
@@ -245,49 +511,371 @@ pub fn read_lyric_file(path: &PlatformPath) -> Option<String> {
 
 /// 检查文件是否为 symphonia 支持的音频格式。
 ///
-/// 通过扩展名快速过滤。
+/// 通过扩展名快速过滤，只认内置扩展名；扫描时应使用
+/// [`FolderManager::is_supported_file`](super::folder::FolderManager::is_supported_file)，它还会考虑别名与文件夹覆盖。
 pub fn is_supported_audio(path: &PlatformPath) -> bool {
-    if let Some(ext) = platform::path_extension(path) {
-        matches!(
-            ext.as_str(),
-            "mp3"
-                | "flac"
-                | "wav"
-                | "ogg"
-                | "oga"
-                | "opus"
-                | "m4a"
-                | "aac"
-                | "wma"
-                | "aiff"
-                | "aif"
-                | "caf"
-        )
-    } else {
-        false
+    platform::path_extension(path).is_some_and(|ext| is_supported_extension(&ext))
+}
+
+/// 编码是否无损（线性 PCM + 无损压缩格式）。
+pub fn is_lossless_codec(codec: AudioCodecId) -> bool {
+    (codec >= CODEC_ID_PCM_S32LE && codec < CODEC_ID_PCM_ALAW)
+        || (CODEC_ID_FLAC..=CODEC_ID_TRUEHD).contains(&codec)
+}
+
+/// 编码名称：已注册解码器的短名；未启用解码器的常见无损编码（如 ALAC）按 ID 给出名称。
+pub fn codec_name(codec: AudioCodecId) -> Option<String> {
+    if let Some(decoder) = symphonia::default::get_codecs().get_audio_decoder(codec) {
+        return Some(decoder.codec.info.short_name.to_string());
+    }
+    let name = match codec {
+        CODEC_ID_ALAC => "alac",
+        CODEC_ID_WAVPACK => "wavpack",
+        CODEC_ID_MONKEYS_AUDIO => "ape",
+        CODEC_ID_TTA => "tta",
+        CODEC_ID_TRUEHD => "truehd",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// 内置支持的扩展名（小写、不含点）。`.oga`、`.aif` 等变体作为默认别名见 [`super::extensions`]。
+pub fn is_supported_extension(ext: &str) -> bool {
+    matches!(
+        ext,
+        "mp3" | "flac" | "wav" | "ogg" | "opus" | "m4a" | "aac" | "wma" | "aiff" | "caf"
+    )
+}
+
+/// 重扫参数。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// 忽略文件指纹，重新读取每个文件的元数据（默认只读取新增 / 改动过的文件）
+    pub force_rescan: bool,
+    /// 取消令牌：取消后不再读取新文件，返回已完成的部分结果（见 [`RescanReport::cancelled`](super::source::RescanReport::cancelled)）
+    #[serde(skip)]
+    pub cancel: CancellationToken,
+}
+
+/// 批量读取元数据的调度参数。
+#[derive(Debug, Clone, Copy)]
+pub struct BatchReadOptions {
+    /// 每批文件数；每批结束后回报进度并检查取消
+    pub chunk_size: usize,
+    /// 批与批之间的停顿（毫秒），为前台 IO 让出带宽；0 表示不停顿
+    pub chunk_delay_ms: u64,
+    /// 每批内部的并行线程数上限
+    pub max_threads: usize,
+}
+
+impl Default for BatchReadOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 32,
+            chunk_delay_ms: 0,
+            max_threads: 4,
+        }
+    }
+}
+
+/// 批量读取进度。
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BatchProgress {
+    /// 已处理文件数（含失败）
+    pub done: usize,
+    /// 总文件数
+    pub total: usize,
+}
+
+/// 批量读取结果。取消时 `results` 只包含已处理的文件。
+#[derive(Debug, Default)]
+pub struct BatchReadOutcome {
+    pub results: Vec<(PlatformPath, Result<AudioMeta, String>)>,
+    /// 是否因取消而提前结束
+    pub cancelled: bool,
+}
+
+/// 分批并行读取元数据，每批结束后调用 `on_progress`。
+///
+/// 每处理一个文件前检查 `token`；取消后不再开始新文件，返回已完成的部分结果。
+/// 经由 `cache` 读取，未变化的文件直接取缓存结果。
+pub fn batch_read_metadata_with_progress<F>(
+    paths: &[PlatformPath],
+    cache: &MetaCache,
+    token: &CancellationToken,
+    options: BatchReadOptions,
+    mut on_progress: F,
+) -> BatchReadOutcome
+where
+    F: FnMut(BatchProgress),
+{
+    let _scope = perf::scope("scanner.batch_read_metadata");
+    let total = paths.len();
+    let mut outcome = BatchReadOutcome {
+        results: Vec::with_capacity(total),
+        cancelled: false,
+    };
+
+    for (i, batch) in paths.chunks(options.chunk_size.max(1)).enumerate() {
+        if token.is_cancelled() {
+            break;
+        }
+        if i > 0 && options.chunk_delay_ms > 0 {
+            std::thread::sleep(Duration::from_millis(options.chunk_delay_ms));
+        }
+
+        let num_threads = options.max_threads.clamp(1, batch.len());
+        let per_thread = batch.len().div_ceil(num_threads);
+        std::thread::scope(|s| {
+            let handles: Vec<_> = batch
+                .chunks(per_thread)
+                .map(|part| {
+                    s.spawn(move || {
+                        let mut part_results = Vec::with_capacity(part.len());
+                        for path in part {
+                            if token.is_cancelled() {
+                                break;
+                            }
+                            part_results.push((path.clone(), cache.read(path)));
+                        }
+                        part_results
+                    })
+                })
+                .collect();
+            for handle in handles {
+                match handle.join() {
+                    Ok(part_results) => outcome.results.extend(part_results),
+                    Err(_) => eprintln!("[scanner] batch_read_metadata 探测线程异常退出"),
+                }
+            }
+        });
+
+        on_progress(BatchProgress {
+            done: outcome.results.len(),
+            total,
+        });
     }
+
+    outcome.cancelled = token.is_cancelled() && outcome.results.len() < total;
+    outcome
 }
 
-/// 从 symphonia `RawValue` 提取合法的年份（1900..=2100）。
+/// 从 symphonia `RawValue` 解析发行日期，规范为 `YYYY`、`YYYY-MM` 或 `YYYY-MM-DD`。
 ///
-/// 支持三种形式：
-/// - 数字 `RawValue::UnsignedInt(2024)` / `RawValue::SignedInt(2024)` → 直接取值
-/// - 字符串 `"2024"`、`"2024-01-01"`、`"2024-05"` → 取前 4 位数字解析
-fn parse_year_from_value(value: &symphonia::core::meta::RawValue) -> Option<u32> {
-    use symphonia::core::meta::RawValue;
-    let raw_year: Option<u32> = match value {
-        RawValue::UnsignedInt(n) => Some(*n as u32),
-        RawValue::SignedInt(n) if *n > 0 => Some(*n as u32),
-        RawValue::String(s) => s
-            .chars()
-            .take_while(|c| c.is_ascii_digit())
-            .take(4)
-            .collect::<String>()
-            .parse::<u32>()
-            .ok(),
+/// - 数字 `RawValue::UnsignedInt(2024)` / `RawValue::SignedInt(2024)` → `"2024"`
+/// - 字符串 `"2024"`、`"2024-05"`、`"2024-05-01"`、`"2024-05-01T12:00:00"`、`"2024.05.01"`、`"2024/5/1"`
+///   → 保留到能识别的最高精度；月 / 日不合法时截断到上一级
+///
+/// 年份须在 1900..=2100 之间，否则视为无效。
+fn parse_date_from_value(value: &RawValue) -> Option<String> {
+    let text = match value {
+        RawValue::UnsignedInt(n) => n.to_string(),
+        RawValue::SignedInt(n) if *n > 0 => n.to_string(),
+        _ => raw_value_text(value)?,
+    };
+    let mut fields = text.split(['-', '.', '/', 'T', ' ']);
+    let head = fields.next()?;
+    let year: u32 = head
+        .get(..4)
+        .filter(|y| y.bytes().all(|b| b.is_ascii_digit()))?
+        .parse()
+        .ok()?;
+    if !(1900..=2100).contains(&year) {
+        return None;
+    }
+    // "20240501" 这类无分隔符的写法无法可靠区分，只取年份
+    if head.len() > 4 {
+        return Some(format!("{:04}", year));
+    }
+    let number = |part: Option<&str>| {
+        part.filter(|p| (1..=2).contains(&p.len()))
+            .and_then(|p| p.parse::<u32>().ok())
+    };
+    let Some(month) = number(fields.next()).filter(|m| (1..=12).contains(m)) else {
+        return Some(format!("{:04}", year));
+    };
+    let Some(day) = number(fields.next()).filter(|d| (1..=days_in_month(year, month)).contains(d)) else {
+        return Some(format!("{:04}-{:02}", year, month));
+    };
+    Some(format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 日期标签的类别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateKind {
+    /// 本版本的发行 / 录制日期
+    Release,
+    /// 首次发行日期
+    Original,
+}
+
+/// 按字段名（不区分大小写）识别日期标签。
+///
+/// 覆盖 ID3 (TYER/TDRC/TDRL、TDOR/TORY)、Vorbis (DATE/YEAR、ORIGINALDATE/ORIGINALYEAR)、MP4 (©day) 等。
+fn date_key_kind(key: &str) -> Option<DateKind> {
+    match key.to_lowercase().as_str() {
+        "year" | "date" | "tdrc" | "tdrl" | "tyer" | "release_date" | "releasedate" | "©day" => {
+            Some(DateKind::Release)
+        }
+        "tory" | "tdor" | "originaldate" | "originalyear" | "original_date" | "originalreleasedate" => {
+            Some(DateKind::Original)
+        }
         _ => None,
+    }
+}
+
+/// 演唱语言标签：ID3v2 `TLAN`、Vorbis `LANGUAGE`（TXXX 描述与 MP4 freeform 名称同样识别）。
+fn is_language_key(key: &str) -> bool {
+    key.eq_ignore_ascii_case("TLAN") || key.eq_ignore_ascii_case("LANGUAGE")
+}
+
+/// 自定义帧（TXXX / WXXX）的键附带描述，以区分同名帧。
+fn extra_tag_key(raw: &RawTag) -> String {
+    let desc = raw
+        .sub_fields
+        .as_deref()
+        .and_then(|fields| fields.iter().find(|f| f.field == "DESCRIPTION"))
+        .and_then(|f| raw_value_text(&f.value));
+    match desc {
+        Some(desc) if raw.key.eq_ignore_ascii_case("TXXX") || raw.key.eq_ignore_ascii_case("WXXX") => {
+            format!("{}:{}", raw.key.to_ascii_uppercase(), desc)
+        }
+        _ => raw.key.clone(),
+    }
+}
+
+/// 取用于多语言识别的标签键。
+fn alt_tag_key(raw: &RawTag) -> String {
+    if raw.key.eq_ignore_ascii_case("TXXX") {
+        if let Some(desc) = raw
+            .sub_fields
+            .as_deref()
+            .and_then(|fields| fields.iter().find(|f| f.field == "DESCRIPTION"))
+        {
+            return desc.value.to_string();
+        }
+    }
+    if raw.key.starts_with("----:") {
+        if let Some(name) = raw.key.splitn(3, ':').nth(2) {
+            return name.to_string();
+        }
+    }
+    raw.key.clone()
+}
+
+/// 识别携带备用语言标题 / 艺人名的标签键，返回字段与规范化后的语言标记。
+///
+/// 支持的写法（大小写不敏感）：
+/// - `TITLE:ja-Latn`、`ARTIST:en`、`TITLE_EN`、`Title (Romanized)`、`EN_TITLE`
+/// - 语言部分为 `romanized` / `romaji` 时记为 `und-Latn`，为 `original` 时记为 `original`
+///
+/// `TITLESORT`、`ALBUM ARTIST` 等非语言后缀不会被误识别。
+fn classify_alt_key(key: &str) -> Option<(AltField, String)> {
+    let key = key.trim();
+    let lower = key.to_lowercase();
+    for (name, field) in [("title", AltField::Title), ("artist", AltField::Artist)] {
+        if lower == name {
+            return None;
+        }
+        let is_sep = |c: char| matches!(c, ':' | '_' | ' ' | '-' | '(');
+        // 后缀形式：TITLE:ja / TITLE_EN / Title (Romanized)
+        if let Some(rest) = lower.strip_prefix(name) {
+            if rest.starts_with(is_sep) {
+                let lang = rest[1..].trim().trim_start_matches('(').trim_end_matches(')').trim();
+                if let Some(lang) = normalize_lang(lang) {
+                    return Some((field, lang));
+                }
+            }
+        }
+        // 前缀形式：EN_TITLE / Romanized Title
+        if let Some(rest) = lower.strip_suffix(name) {
+            if rest.ends_with(is_sep) {
+                if let Some(lang) = normalize_lang(rest[..rest.len() - 1].trim()) {
+                    return Some((field, lang));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 规范化语言标记：主标签小写、4 位文字标签首字母大写、2 位地区标签大写。
+///
+/// 不像语言标记的字符串（如 `sort`、`album`）返回 `None`。
+fn normalize_lang(lang: &str) -> Option<String> {
+    match lang {
+        "romanized" | "romanised" | "romaji" | "latin" | "transliterated" => {
+            return Some("und-Latn".to_string())
+        }
+        "original" | "orig" => return Some("original".to_string()),
+        _ => {}
+    }
+    let mut parts = lang.split(['-', '_']);
+    let primary = parts.next()?;
+    if !(2..=3).contains(&primary.len()) || !primary.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut out = primary.to_ascii_lowercase();
+    for sub in parts {
+        if !(2..=8).contains(&sub.len()) || !sub.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        out.push('-');
+        match sub.len() {
+            2 => out.push_str(&sub.to_ascii_uppercase()),
+            4 => {
+                out.push_str(&sub[..1].to_ascii_uppercase());
+                out.push_str(&sub[1..].to_ascii_lowercase());
+            }
+            _ => out.push_str(&sub.to_ascii_lowercase()),
+        }
+    }
+    Some(out)
+}
+
+/// 取标签值中的文本（多值时取第一个非空值）。
+fn raw_value_text(value: &RawValue) -> Option<String> {
+    let text = match value {
+        RawValue::String(s) => s.trim().to_string(),
+        RawValue::StringList(list) => list.iter().map(|s| s.trim()).find(|s| !s.is_empty())?.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// 取标签值中的全部文本；数字 / 布尔值转为字符串，二进制值与空串忽略。
+fn raw_value_texts(value: &RawValue) -> Vec<String> {
+    let texts = match value {
+        RawValue::String(s) => vec![s.trim().to_string()],
+        RawValue::StringList(list) => list.iter().map(|s| s.trim().to_string()).collect(),
+        RawValue::UnsignedInt(n) => vec![n.to_string()],
+        RawValue::SignedInt(n) => vec![n.to_string()],
+        RawValue::Float(n) => vec![n.to_string()],
+        RawValue::Boolean(b) => vec![b.to_string()],
+        _ => Vec::new(),
     };
-    raw_year.filter(|y| (1900..=2100).contains(y))
+    texts.into_iter().filter(|t| !t.is_empty()).collect()
+}
+
+/// 追加一条多语言文本；同一语言已存在时忽略。
+fn push_localized(list: &mut Vec<LocalizedText>, lang: &str, text: &str) {
+    if list.iter().any(|t| t.lang == lang) {
+        return;
+    }
+    list.push(LocalizedText {
+        lang: lang.to_string(),
+        text: text.to_string(),
+    });
 }
 
 #[cfg(test)]
@@ -296,6 +884,49 @@ mod tests {
 
     use crate::module::platform::PlatformPath;
 
+    #[test]
+    fn test_batch_read_cancelled_returns_partial() {
+        let paths: Vec<PlatformPath> = (0..10)
+            .map(|i| PlatformPath::from(format!("/nonexistent/chordial/{}.mp3", i).as_str()))
+            .collect();
+        let token = CancellationToken::new();
+        let options = BatchReadOptions {
+            chunk_size: 3,
+            chunk_delay_ms: 0,
+            max_threads: 1,
+        };
+        let mut progress = Vec::new();
+        let outcome = batch_read_metadata_with_progress(&paths, &MetaCache::default(), &token, options, |p| {
+            progress.push(p.done);
+            if p.done >= 6 {
+                token.cancel();
+            }
+        });
+        assert!(outcome.cancelled);
+        assert_eq!(outcome.results.len(), 6);
+        assert_eq!(progress, vec![3, 6]);
+        assert!(outcome.results.iter().all(|(_, r)| r.is_err()));
+    }
+
+    #[test]
+    fn test_codec_name_and_lossless() {
+        assert_eq!(codec_name(CODEC_ID_FLAC).as_deref(), Some("flac"));
+        // ALAC 解码器未启用时仍能识别名称与无损
+        assert_eq!(codec_name(CODEC_ID_ALAC).as_deref(), Some("alac"));
+        assert!(is_lossless_codec(CODEC_ID_ALAC));
+        assert!(is_lossless_codec(CODEC_ID_PCM_S16LE));
+        assert!(!is_lossless_codec(CODEC_ID_AAC));
+        assert!(!is_lossless_codec(CODEC_ID_MP3));
+    }
+
+    #[test]
+    fn test_normalize_release_identifiers() {
+        assert_eq!(normalize_isrc("us-rc1-76-07839").as_deref(), Some("USRC17607839"));
+        assert_eq!(normalize_isrc("USRC176078"), None);
+        assert_eq!(normalize_barcode("0 75678-16632 7").as_deref(), Some("075678166327"));
+        assert_eq!(normalize_barcode("none"), None);
+    }
+
     #[test]
     fn test_extension_filter() {
         assert!(is_supported_audio(&PlatformPath::from("song.mp3")));
@@ -305,4 +936,74 @@ mod tests {
         assert!(!is_supported_audio(&PlatformPath::from("lyrics.lrc")));
         assert!(!is_supported_audio(&PlatformPath::from("readme.txt")));
     }
+
+    #[test]
+    fn test_classify_alt_key() {
+        assert_eq!(classify_alt_key("TITLE:ja-latn"), Some((AltField::Title, "ja-Latn".into())));
+        assert_eq!(classify_alt_key("TITLE_EN"), Some((AltField::Title, "en".into())));
+        assert_eq!(classify_alt_key("Title (Romanized)"), Some((AltField::Title, "und-Latn".into())));
+        assert_eq!(classify_alt_key("romaji artist"), Some((AltField::Artist, "und-Latn".into())));
+        assert_eq!(classify_alt_key("ARTIST:zh-hans-cn"), Some((AltField::Artist, "zh-Hans-CN".into())));
+        assert_eq!(classify_alt_key("TITLE"), None);
+        assert_eq!(classify_alt_key("TITLE_SORT"), None);
+        assert_eq!(classify_alt_key("ALBUM ARTIST"), None);
+        assert_eq!(classify_alt_key("ARTISTS"), None);
+    }
+
+    #[test]
+    fn test_parse_date_precision() {
+        let date = |s: &str| parse_date_from_value(&RawValue::String(s.to_string().into()));
+        assert_eq!(date("2024").as_deref(), Some("2024"));
+        assert_eq!(date("2024-5").as_deref(), Some("2024-05"));
+        assert_eq!(date("2024-05-01T12:00:00").as_deref(), Some("2024-05-01"));
+        assert_eq!(date("1999.12.31").as_deref(), Some("1999-12-31"));
+        assert_eq!(date("2023-02-29").as_deref(), Some("2023-02"));
+        assert_eq!(date("20240501").as_deref(), Some("2024"));
+        assert_eq!(date("0000"), None);
+        assert_eq!(parse_date_from_value(&RawValue::UnsignedInt(1987)).as_deref(), Some("1987"));
+        assert_eq!(date_key_kind("TDOR"), Some(DateKind::Original));
+        assert_eq!(date_key_kind("DATE"), Some(DateKind::Release));
+    }
+
+    #[test]
+    fn test_parse_gain_db() {
+        assert_eq!(parse_gain_db("-6.54 dB"), Some(-6.54));
+        assert_eq!(parse_gain_db("+1.2dB"), Some(1.2));
+        assert_eq!(parse_gain_db("0.5"), Some(0.5));
+        assert_eq!(parse_gain_db("loud"), None);
+    }
+
+    #[test]
+    fn test_split_genres() {
+        assert_eq!(split_genres("Rock; Pop\0J-Pop"), vec!["Rock", "Pop", "J-Pop"]);
+        assert_eq!(split_composers("Bach, Johann Sebastian; Gounod"), vec!["Bach, Johann Sebastian", "Gounod"]);
+        assert_eq!(split_genres("(17)"), Vec::<String>::new());
+        assert_eq!(split_genres(" ; "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_collect_extra_tags_keeps_custom_frames() {
+        use symphonia::core::meta::RawTagSubField;
+        let txxx = |desc: &str, value: &str| {
+            Tag::new(RawTag::new_with_sub_fields(
+                "TXXX",
+                value.to_string(),
+                vec![RawTagSubField::new("DESCRIPTION", desc.to_string())].into_boxed_slice(),
+            ))
+        };
+        let tags = vec![
+            Tag::new_from_parts("TIT2", "Song".to_string(), Some(StandardTag::TrackTitle("Song".to_string().into()))),
+            Tag::new_from_parts("TDRC", "2024-05-01".to_string(), None),
+            txxx("MusicBrainz Album Id", "8c8b0b1e-0000-4000-8000-000000000000"),
+            txxx("DJ Cue", "intro"),
+            txxx("DJ Cue", "drop"),
+            txxx("Title (Romanized)", "Uta"),
+            Tag::new_from_parts("TLAN", "jpn".to_string(), None),
+            Tag::new_from_parts("PRIV", RawValue::Binary(std::sync::Arc::new(vec![1u8, 2].into_boxed_slice())), None),
+        ];
+        let extra = collect_extra_tags(&tags);
+        assert_eq!(extra.len(), 2);
+        assert_eq!(extra["TXXX:DJ Cue"], vec!["intro", "drop"]);
+        assert!(extra.contains_key("TXXX:MusicBrainz Album Id"));
+    }
 }
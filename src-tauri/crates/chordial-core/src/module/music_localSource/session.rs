@@ -0,0 +1,130 @@
+//! 临时播放 — 不添加来源、不入库，直接播放任意文件 / 文件夹。
+//!
+//! 对应「用 Chordial 打开」：操作系统把文件路径交给应用后，这里只读取元数据，
+//! 构造临时歌曲列表交给前端作为播放队列。整个过程不写音乐库、不写 mtime 缓存、
+//! 不计入损坏文件隔离，应用关闭后不留痕迹。
+//!
+//! 临时歌曲的 `source_ids` 指向本地来源、`entity_id` 为文件路径，
+//! 本地来源按路径即可直接提供音频 / 封面 / 歌词，无需额外注册来源。
+//! 库外的文件在这里逐个授权（[`LocalMusicSource::grant_session_path`]），否则本地来源会拒绝读取。
+//! 已在库中的文件直接复用库内歌曲，保留其 ID。
+
+use super::folder;
+use super::source::LocalMusicSource;
+use crate::module::music_library::models::Song;
+use crate::module::platform::{self, PlatformPath};
+use serde::Serialize;
+
+/// 单次临时播放最多读取的文件数，避免误拖入整个磁盘时长时间阻塞。
+pub const MAX_SESSION_FILES: usize = 2000;
+
+/// 临时播放队列。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionPlaylist {
+    /// 按打开顺序排列的歌曲；文件夹内按路径排序
+    pub songs: Vec<Song>,
+    /// 无法读取的文件及原因
+    pub skipped: Vec<SkippedFile>,
+    /// 超出 [`MAX_SESSION_FILES`] 被截断
+    pub truncated: bool,
+}
+
+/// 无法加入临时队列的文件。
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// 打开一组文件或文件夹，构造临时播放队列。
+///
+/// 文件夹递归展开为其中受支持的音频文件；不支持的文件记入 `skipped`。
+pub fn open_files(source: &LocalMusicSource, paths: &[PlatformPath]) -> SessionPlaylist {
+    let mut playlist = SessionPlaylist::default();
+    let files = expand_paths(paths, |p| source.folder_manager.is_supported_file(p), &mut playlist);
+
+    for path in files {
+        if let Some(song) = source
+            .find_song_id_by_path(&path)
+            .and_then(|id| source.library.get_song(&id))
+        {
+            playlist.songs.push(song);
+            continue;
+        }
+        match source.meta_cache.read(&path) {
+            Ok(meta) => {
+                source.grant_session_path(&path);
+                let mut song = source.build_song(&path, &meta);
+                if meta.title.is_none() {
+                    if let Some(stem) = platform::path_file_stem(&path) {
+                        song.title = stem;
+                    }
+                }
+                playlist.songs.push(song);
+            }
+            Err(reason) => playlist.skipped.push(SkippedFile {
+                path: platform::path_to_string(&path),
+                reason,
+            }),
+        }
+    }
+    playlist
+}
+
+/// 打开单个文件夹（[`open_files`] 的便捷形式）。
+pub fn play_folder(source: &LocalMusicSource, path: &PlatformPath) -> SessionPlaylist {
+    open_files(source, std::slice::from_ref(path))
+}
+
+/// 展开文件夹、过滤不支持的文件，并按上限截断。
+fn expand_paths<F>(paths: &[PlatformPath], is_audio: F, playlist: &mut SessionPlaylist) -> Vec<PlatformPath>
+where
+    F: Fn(&PlatformPath) -> bool,
+{
+    let mut files = Vec::new();
+    for path in paths {
+        if platform::is_dir(path) {
+            let mut found = folder::collect_audio_files(path, &is_audio);
+            found.sort();
+            files.extend(found);
+        } else if is_audio(path) && platform::is_file(path) {
+            files.push(path.clone());
+        } else {
+            playlist.skipped.push(SkippedFile {
+                path: platform::path_to_string(path),
+                reason: "不是受支持的音频文件".to_string(),
+            });
+        }
+        if files.len() > MAX_SESSION_FILES {
+            break;
+        }
+    }
+    if files.len() > MAX_SESSION_FILES {
+        files.truncate(MAX_SESSION_FILES);
+        playlist.truncated = true;
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_localSource::scanner;
+
+    #[test]
+    fn test_expand_skips_unsupported_and_sorts_folder() {
+        let dir = std::env::temp_dir().join(format!("chordial_session_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.flac", "a.mp3", "cover.jpg"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let note = dir.join("notes.txt");
+
+        let mut playlist = SessionPlaylist::default();
+        let files = expand_paths(&[dir.clone(), note], scanner::is_supported_audio, &mut playlist);
+        assert_eq!(files, vec![dir.join("a.mp3"), dir.join("b.flac")]);
+        assert_eq!(playlist.skipped.len(), 1);
+        assert!(!playlist.truncated);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -12,25 +12,75 @@
 //! - **跨平台路径**：通过 [`crate::module::platform::PlatformPath`] 适配桌面（`PathBuf`）
 //!   和 Android（`String` / content URI）。
 
+use super::file_stats::{self, FileStats};
+use super::fingerprint::{FileFingerprint, FingerprintMatch};
 use super::folder::FolderManager;
-use super::scanner::{self, AudioMeta};
-use crate::module::music_library::library::MusicLibrary;
-use crate::module::music_library::models::{Album, Artist, Lyric, Song};
+use super::meta_cache::MetaCache;
+use super::quarantine::Quarantine;
+use super::tag_writer;
+use crate::module::cancel::CancellationToken;
+use crate::module::events::{AppEvent, EventBus};
+use super::scanner::{self, AudioMeta, ScanOptions};
+use crate::module::music_library::{artists, books, language};
+use crate::module::music_library::edits::{ConflictResolution, FieldValues, MetadataField};
+use crate::module::music_library::history::Change;
+use crate::module::music_library::library::{MusicLibrary, UndoResult};
+use crate::module::music_library::models::{Album, Artist, LocalizedText, Lyric, Song};
 use crate::module::perf;
+use crate::module::music_source::manager::SourceManager;
 use crate::module::music_source::traits::MusicSource;
 use crate::module::music_source::types::{EntityType, SourceId, SourceType};
 use crate::module::platform::{self, PlatformPath};
+use crate::module::playback::ContentType;
+use crate::module::power::PowerMonitor;
 use crate::module::storage::persistent::PersistentStore;
 use parking_lot::Mutex;
 use parking_lot::RwLock;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
 use uuid::Uuid;
 
 /// 封面缓存容量上限（条目数）。
 /// 单条目平均 50-500KB，256 条 ≈ 12-128MB（最坏情况）。
 const COVER_CACHE_CAP: usize = 256;
 
+/// 批量索引时探测线程与入库线程之间的通道容量（条目数）。
+/// 入库跟不上时探测线程阻塞，在途的元数据不会无限堆积。
+const SCAN_CHANNEL_CAP: usize = 64;
+
+/// 带进度的批量索引每处理多少个文件回报一次进度。
+const SCAN_PROGRESS_STEP: usize = 64;
+
+/// 批量索引时每攒够多少首歌曲提交一次入库。
+/// 每次提交都要加载并写回整个库，过小会退化为逐首入库的 O(N²)；过大则占用内存。
+const SCAN_COMMIT_BATCH: usize = 500;
+
+/// 待入库的一首歌曲：路径、歌曲、歌词文本、文件内的统计。
+type PendingSong = (PlatformPath, Song, Option<String>, FileStats);
+
+/// 一次重扫的结果。
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct RescanReport {
+    pub files_found: usize,
+    /// 指纹一致、未重新读取的文件
+    pub unchanged: usize,
+    /// 改动过、重新读取的已索引文件
+    pub changed: usize,
+    /// 成功（重新）入库的文件
+    pub indexed: usize,
+    pub errors: Vec<String>,
+    /// 是否因取消而提前结束（其余字段只统计已处理的文件）
+    pub cancelled: bool,
+}
+
+/// 文件指纹在 mtime 存储中的键。
+const FINGERPRINTS_KEY: &str = "file_fingerprints";
+
+/// 旧版本只记录 (mtime, 大小, song_id) 时使用的键，加载时迁移。
+const LEGACY_MTIMES_KEY: &str = "file_mtimes";
+
 /// 本地音乐来源的名称常量。
 pub const LOCAL_SOURCE_NAME: &str = "local";
 
@@ -48,14 +98,26 @@ pub struct LocalMusicSource {
     pub file_index: RwLock<HashMap<PlatformPath, String>>,
     /// 反向索引：库内 Song ID → 规范路径
     pub id_to_path: RwLock<HashMap<String, PlatformPath>>,
-    /// 文件 mtime 缓存：规范路径字符串 → (modified_secs, file_size, song_id)
-    /// 启动时对比文件系统 mtime，跳过未变化文件的重扫描
-    pub file_mtimes: RwLock<HashMap<String, (u64, u64, String)>>,
+    /// 文件指纹缓存：规范路径字符串 → (mtime, 大小, 头部哈希, song_id)
+    /// 启动 / 重扫时对比文件系统，跳过未变化文件的重新探测（见 [`fingerprint`](super::fingerprint)）
+    pub file_mtimes: RwLock<HashMap<String, FileFingerprint>>,
     /// 独立的 mtime 持久化存储（与 library 分离，避免每次保存都序列化全部歌曲数据）
     mtime_store: PersistentStore,
+    /// 损坏文件隔离列表 — 反复探测失败的文件不再参与扫描
+    pub quarantine: Quarantine,
+    /// 事件总线 — 隔离文件、监听同步等变化由此通知其他子系统
+    pub events: Arc<EventBus>,
+    /// 电源策略 — 电池供电时减少探测线程
+    pub power: Arc<PowerMonitor>,
     /// 封面图内存缓存：entity_id（路径）→ 图片字节
     /// 避免每次 chordial://image 请求都触发 extract_cover_art（5-50ms/次）
     cover_cache: Mutex<HashMap<String, Arc<Vec<u8>>>>,
+    /// 元数据读取缓存 — 单曲详情、临时播放、批量读取反复读同一文件时不再重新解析
+    pub meta_cache: MetaCache,
+    /// 本次运行中额外授权访问的文件（规范路径）— 「用 Chordial 打开」的文件可能不在任何监听文件夹内
+    session_grants: RwLock<HashSet<PlatformPath>>,
+    /// 来源元信息 — 写文件前检查本地来源整体的只读开关（[`SourceEntry::read_only`](crate::module::music_source::manager::SourceEntry::read_only)）
+    source_manager: Arc<SourceManager>,
 }
 
 impl LocalMusicSource {
@@ -68,6 +130,10 @@ impl LocalMusicSource {
         folder_manager: Arc<FolderManager>,
         library: Arc<MusicLibrary>,
         mtime_store: PersistentStore,
+        quarantine: Quarantine,
+        events: Arc<EventBus>,
+        power: Arc<PowerMonitor>,
+        source_manager: Arc<SourceManager>,
     ) -> Self {
         Self {
             name: LOCAL_SOURCE_NAME.to_string(),
@@ -77,7 +143,13 @@ impl LocalMusicSource {
             id_to_path: RwLock::new(HashMap::new()),
             file_mtimes: RwLock::new(HashMap::new()),
             mtime_store,
+            quarantine,
+            events,
+            power,
             cover_cache: Mutex::new(HashMap::new()),
+            meta_cache: MetaCache::default(),
+            session_grants: RwLock::new(HashSet::new()),
+            source_manager,
         }
     }
 
@@ -89,14 +161,14 @@ impl LocalMusicSource {
     /// 写入 PersistentStore，并让 `Song.lyric_id` 指向该 `Lyric.id`。
     /// 若无歌词文件，`lyric_id` 置为 `None`，避免库中残留孤儿引用。
     ///
-    /// 返回 `true` 表示成功处理（新增或合并），`false` 表示跳过（非音频文件）。
+    /// 返回 `true` 表示成功处理（新增或合并），`false` 表示跳过（非音频文件 / 已隔离）。
     pub fn index_file(&self, path: &PlatformPath) -> Result<bool, String> {
         let _scope = perf::scope("source.index_file");
         let canonical = platform::canonicalize(path)
             .unwrap_or_else(|_| path.clone());
 
         // 跳过非音频文件
-        if !scanner::is_supported_audio(&canonical) {
+        if !self.folder_manager.is_supported_file(&canonical) {
             return Ok(false);
         }
 
@@ -105,14 +177,20 @@ impl LocalMusicSource {
             return Ok(true);
         }
 
+        // 已隔离的损坏文件，跳过
+        if self.quarantine.is_quarantined(&canonical) {
+            return Ok(false);
+        }
+
         // 探测元数据
-        let meta = scanner::probe_file(&canonical)?;
+        let meta = self.probe_tracked(&canonical)?;
 
         // 读取同目录歌词文件（.lrc 优先，.txt 兜底）
         let lyric_text = scanner::read_lyric_file(&canonical);
 
         // 构建 Song（lyric_id 占位 UUID 仅在确实有歌词时保留）
-        let mut song = self.build_song(&canonical, &meta);
+        let mut song = self.build_indexed_song(&canonical, &meta);
+        infer_language(&mut song, lyric_text.as_deref());
         if lyric_text.is_none() {
             song.lyric_id = None;
         }
@@ -120,6 +198,8 @@ impl LocalMusicSource {
         // 添加到音乐库（自动去重合并），获取实际存储的 ID
         let stored_id = self.library.add_song(&song)?;
 
+        self.import_file_stats(&stored_id, &meta);
+
         // 写入 Lyric 实体到 PersistentStore
         // —— 这一步是关键：前端 library_get_lyric_of_song(songId) 直接查 lyrics 表
         if let Some(text) = lyric_text {
@@ -158,6 +238,8 @@ impl LocalMusicSource {
     pub fn unindex_file(&self, path: &PlatformPath) -> Result<bool, String> {
         let canonical = platform::canonicalize(path)
             .unwrap_or_else(|_| path.clone());
+        // 文件被改写或删除：即使 mtime / 大小恰好未变，下次也重新探测
+        self.meta_cache.invalidate(&canonical);
 
         let song_id = {
             let index = self.file_index.read();
@@ -191,40 +273,66 @@ impl LocalMusicSource {
         self.index_file(path)
     }
 
-    /// 批量索引音频文件 — 一次性加载库 + 并行探测 + 单次批量合并写回。
+    /// 批量索引音频文件 — 并行探测 + 流式分批合并写回。
     ///
     /// 相比循环调用 [`index_file`](Self::index_file)，避免了每首歌曲都
     /// 反序列化整个库（songs/artists/albums）+ 重建索引 + 全量写回
     /// 带来的 O(N²) 开销。典型场景：1000 个文件
     /// - 旧路径（逐个 `index_file`）：~96ms × 1000 ≈ 96s
-    /// - 新路径（本方法）：~150ms 总计（单次加载 + 并行探测 + 单次写回）
+    /// - 新路径（本方法）：~150ms 总计（并行探测 + 每批一次加载 / 写回）
     ///
     /// # 流程
     /// 1. 规范化路径并过滤：跳过非音频文件、已在 file_index 中的文件
-    /// 2. 并行 `probe_file` + `read_lyric_file`（CPU/IO 并行）
-    /// 3. 构建 Song 列表
-    /// 4. 一次性调用 [`MusicLibrary::add_songs_batch`] 合并入库
-    /// 5. 串行写入 Lyric 实体、更新 file_index / id_to_path / mtime
+    /// 2. 探测线程并行 `probe_file` + `read_lyric_file`，结果经容量为
+    ///    [`SCAN_CHANNEL_CAP`] 的有界通道交给当前线程
+    /// 3. 当前线程收到结果即构建 Song 并丢弃 AudioMeta（扩展标签等只在探测期间存在）
+    /// 4. 每攒够 [`SCAN_COMMIT_BATCH`] 首调用一次 [`MusicLibrary::add_songs_batch`] 合并入库，
+    ///    随后写入 Lyric 实体、更新 file_index / id_to_path / mtime
+    ///
+    /// 大型曲库的内存占用因此与文件总数无关；中途出错时已提交的批次保留在库中。
     ///
     /// # 返回
     /// `(indexed, errors)` — 成功索引条目数 + 失败文件描述列表
     pub fn batch_index_files(&self, paths: &[PlatformPath]) -> Result<(usize, Vec<String>), String> {
+        self.index_files(paths, &CancellationToken::new(), 0, &mut |_| {})
+    }
+
+    /// [`batch_index_files`](Self::batch_index_files)，每处理 [`SCAN_PROGRESS_STEP`] 个文件
+    /// 以已处理数（含跳过的文件）调用一次 `on_progress`。
+    ///
+    /// `cancel` 取消后不再探测新文件，但前 `keep` 个路径总会处理完 —
+    /// 重扫时它们是已从库中移除、等待重新入库的改动文件。
+    fn index_files(
+        &self,
+        paths: &[PlatformPath],
+        cancel: &CancellationToken,
+        keep: usize,
+        on_progress: &mut dyn FnMut(usize),
+    ) -> Result<(usize, Vec<String>), String> {
         let _scope = perf::scope("source.batch_index_files");
 
         // 1. 规范化 + 过滤：跳过非音频文件、已索引文件
         // 预估待探测数量以减少扩容；上限为 paths.len()
         let mut needs_probe: Vec<PlatformPath> = Vec::with_capacity(paths.len());
+        // needs_probe 中来自前 keep 个路径、不受取消影响的条目数
+        let mut protected = 0;
         {
             let file_index = self.file_index.read();
-            for path in paths {
+            for (i, path) in paths.iter().enumerate() {
                 let canonical = platform::canonicalize(path)
                     .unwrap_or_else(|_| path.clone());
-                if !scanner::is_supported_audio(&canonical) {
+                if !self.folder_manager.is_supported_file(&canonical) {
                     continue;
                 }
                 if file_index.contains_key(&canonical) {
                     continue;
                 }
+                if self.quarantine.is_quarantined(&canonical) {
+                    continue;
+                }
+                if i < keep {
+                    protected += 1;
+                }
                 needs_probe.push(canonical);
             }
         }
@@ -232,89 +340,97 @@ impl LocalMusicSource {
         if needs_probe.is_empty() {
             return Ok((0, Vec::new()));
         }
+        let skipped = paths.len() - needs_probe.len();
 
         // 2. 并行 probe + read_lyric_file
-        // 线程数：取 CPU 核心数与文件数的较小值；至少 1
-        let probe_count = needs_probe.len();
-        let num_threads = std::thread::available_parallelism()
-            .map(|n| n.get())
-            .unwrap_or(4)
-            .min(probe_count)
-            .max(1);
-        let chunk_size = (probe_count + num_threads - 1) / num_threads;
-
-        // 每个探测结果携带 (path, Result<(meta, lyric_text), error_msg>)
-        let mut probe_results: Vec<(PlatformPath, Result<(AudioMeta, Option<String>), String>)> =
-            Vec::with_capacity(probe_count);
+        // 线程数：取 CPU 核心数（电池供电时按电源策略减少）与文件数的较小值；至少 1
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+        let num_threads = self.power.scan_threads(cores).min(needs_probe.len()).max(1);
+        // 各线程从共享游标取下一个文件，探测耗时不均时不会有线程提前闲置
+        let next = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::sync_channel::<(PlatformPath, Result<(AudioMeta, Option<String>), String>)>(
+            SCAN_CHANNEL_CAP,
+        );
 
-        std::thread::scope(|s| {
-            let mut handles = Vec::with_capacity(num_threads);
-            for chunk in needs_probe.chunks(chunk_size) {
-                // chunk.to_vec() 避免跨线程借用 needs_probe
-                let chunk: Vec<PlatformPath> = chunk.to_vec();
-                handles.push(s.spawn(move || {
-                    let mut chunk_results = Vec::with_capacity(chunk.len());
-                    for path in &chunk {
-                        // probe + read_lyric 在同一线程内串行，
-                        // 避免再次起线程的开销；线程间仍是并行的
-                        let result = scanner::probe_file(path).and_then(|meta| {
-                            // read_lyric_file 失败不影响 song 入库，返回 None 即可
-                            let lyric = scanner::read_lyric_file(path);
-                            Ok((meta, lyric))
-                        });
-                        chunk_results.push((path.clone(), result));
+        let mut indexed = 0;
+        let mut errors: Vec<String> = Vec::new();
+        let outcome = std::thread::scope(|s| {
+            for _ in 0..num_threads {
+                let tx = tx.clone();
+                let (next, needs_probe) = (&next, &needs_probe);
+                s.spawn(move || loop {
+                    // 游标单调递增：取到受保护区之外的序号时，受保护的文件都已被领取
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= protected && cancel.is_cancelled() {
+                        break;
                     }
-                    chunk_results
-                }));
-            }
-            for handle in handles {
-                match handle.join() {
-                    Ok(chunk_results) => probe_results.extend(chunk_results),
-                    Err(_) => eprintln!("[local_source] batch_index_files 探测线程异常退出"),
-                }
+                    let Some(path) = needs_probe.get(i) else {
+                        break;
+                    };
+                    // read_lyric_file 失败不影响 song 入库，返回 None 即可
+                    let result = scanner::probe_file(path).map(|meta| (meta, scanner::read_lyric_file(path)));
+                    // 接收端已放弃（入库出错）时停止探测
+                    if tx.send((path.clone(), result)).is_err() {
+                        break;
+                    }
+                });
             }
-        });
-
-        // 3. 构建 Song 列表，收集错误
-        // 容量上限 = 探测成功的条目数
-        let success_count = probe_results
-            .iter()
-            .filter(|r| r.1.is_ok())
-            .count();
-        let mut songs_and_lyrics: Vec<(PlatformPath, Song, Option<String>)> =
-            Vec::with_capacity(success_count);
-        let mut errors: Vec<String> = Vec::new();
-
-        for (path, result) in probe_results {
-            match result {
-                Ok((meta, lyric_text)) => {
-                    let mut song = self.build_song(&path, &meta);
-                    if lyric_text.is_none() {
-                        song.lyric_id = None;
+            // 只保留线程持有的发送端，全部探测完毕后通道自然关闭
+            drop(tx);
+
+            // 3. 边收边构建 Song，4. 分批入库
+            let mut pending: Vec<PendingSong> = Vec::with_capacity(SCAN_COMMIT_BATCH);
+            for (received, (path, result)) in rx.into_iter().enumerate() {
+                match result {
+                    Ok((meta, lyric_text)) => {
+                        self.quarantine.record_success(&path);
+                        let mut song = self.build_indexed_song(&path, &meta);
+                        infer_language(&mut song, lyric_text.as_deref());
+                        if lyric_text.is_none() {
+                            song.lyric_id = None;
+                        }
+                        let stats = FileStats {
+                            rating: meta.rating,
+                            play_count: meta.play_count,
+                        };
+                        pending.push((path, song, lyric_text, stats));
+                    }
+                    Err(e) => {
+                        self.note_failure(&path, &e);
+                        errors.push(format!("{}: {}", platform::path_to_string(&path), e));
                     }
-                    songs_and_lyrics.push((path, song, lyric_text));
                 }
-                Err(e) => {
-                    errors.push(format!("{}: {}", platform::path_to_string(&path), e));
+                if pending.len() >= SCAN_COMMIT_BATCH {
+                    indexed += self.commit_indexed_songs(std::mem::take(&mut pending))?;
+                }
+                if (received + 1) % SCAN_PROGRESS_STEP == 0 {
+                    on_progress(skipped + received + 1);
                 }
             }
-        }
+            indexed += self.commit_indexed_songs(pending)?;
+            Ok::<(), String>(())
+        });
 
-        if songs_and_lyrics.is_empty() {
-            return Ok((0, errors));
+        if let Err(e) = self.quarantine.save() {
+            eprintln!("[local_source] 保存隔离列表失败: {}", e);
         }
+        outcome?;
+        Ok((indexed, errors))
+    }
 
-        // 4. 批量合并入库（单次加载 + 单次写回，O(N+K) 总复杂度）
-        let songs: Vec<Song> = songs_and_lyrics
-            .iter()
-            .map(|(_, s, _)| s.clone())
-            .collect();
+    /// 把一批已构建的歌曲合并入库（单次加载 + 单次写回），再写入歌词、更新索引 / mtime。
+    ///
+    /// 返回提交的歌曲数。
+    fn commit_indexed_songs(&self, batch: Vec<PendingSong>) -> Result<usize, String> {
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        let songs: Vec<Song> = batch.iter().map(|(_, s, _, _)| s.clone()).collect();
         let stored_ids = self.library.add_songs_batch(&songs)?;
 
-        // 5. 串行写入 Lyric + 更新索引/mtime
         // 这部分都是 O(1) 操作或单次 fs 调用，不在热路径
-        for (i, (path, song, lyric_text)) in songs_and_lyrics.iter().enumerate() {
-            let stored_id = &stored_ids[i];
+        for ((path, song, lyric_text, stats), stored_id) in batch.iter().zip(&stored_ids) {
+            self.import_stats(stored_id, stats);
 
             // 写入 Lyric 实体（若有歌词）
             if let Some(text) = lyric_text {
@@ -342,8 +458,7 @@ impl LocalMusicSource {
                 .insert(stored_id.clone(), path.clone());
             self.update_file_mtime(path, stored_id);
         }
-
-        Ok((songs_and_lyrics.len(), errors))
+        Ok(batch.len())
     }
 
     /// 批量取消索引音频文件 — 单次库调用替代 N 次 `unindex_file`。
@@ -396,12 +511,87 @@ impl LocalMusicSource {
         Ok(removed)
     }
 
+    /// 重扫一批文件：新文件入库，已索引的文件按指纹判断是否改动，只重新读取改动过的文件。
+    ///
+    /// `options.force_rescan` 为 true 时所有已索引文件都重新读取。
+    /// 旧版本没有指纹的已索引文件按未改动处理，并补记指纹。
+    ///
+    /// `options.cancel` 取消后不再读取新文件，返回的报告只含已处理的部分并标记 `cancelled`；
+    /// 已移出库、等待重新读取的改动文件仍会处理完，不会因取消而从库中丢失。
+    pub fn rescan_files(&self, paths: &[PlatformPath], options: ScanOptions) -> Result<RescanReport, String> {
+        self.rescan_files_with_progress(paths, options, |_| {})
+    }
+
+    /// [`rescan_files`](Self::rescan_files)，读取文件期间以已处理的文件数（含未改动的文件）
+    /// 分批调用 `on_progress`，未取消时结束前以 `paths.len()` 再调用一次。
+    pub fn rescan_files_with_progress(
+        &self,
+        paths: &[PlatformPath],
+        options: ScanOptions,
+        mut on_progress: impl FnMut(usize),
+    ) -> Result<RescanReport, String> {
+        let _scope = perf::scope("source.rescan_files");
+        let mut report = RescanReport {
+            files_found: paths.len(),
+            ..Default::default()
+        };
+        let mut new_files: Vec<PlatformPath> = Vec::new();
+        let mut changed: Vec<PlatformPath> = Vec::new();
+        for path in paths {
+            if options.cancel.is_cancelled() {
+                break;
+            }
+            let canonical = platform::canonicalize(path).unwrap_or_else(|_| path.clone());
+            let Some(song_id) = self.file_index.read().get(&canonical).cloned() else {
+                new_files.push(canonical);
+                continue;
+            };
+            let known = self.file_mtimes.read().contains_key(&platform::path_to_string(&canonical));
+            let unchanged = match (options.force_rescan, known) {
+                (true, _) => false,
+                (false, true) => self.check_file_unchanged(&canonical).is_some(),
+                (false, false) => {
+                    self.update_file_mtime(&canonical, &song_id);
+                    true
+                }
+            };
+            if unchanged {
+                report.unchanged += 1;
+            } else {
+                self.meta_cache.invalidate(&canonical);
+                changed.push(canonical);
+            }
+        }
+
+        report.changed = changed.len();
+        self.batch_unindex_files(&changed)?;
+        // 改动文件排在前面，作为不受取消影响的部分
+        let keep = changed.len();
+        let mut to_index = changed;
+        to_index.extend(new_files);
+        let unchanged = report.unchanged;
+        on_progress(unchanged);
+        let (indexed, errors) =
+            self.index_files(&to_index, &options.cancel, keep, &mut |done| on_progress(unchanged + done))?;
+        report.indexed = indexed;
+        report.errors = errors;
+        report.cancelled = options.cancel.is_cancelled();
+        if let Err(e) = self.save_mtime_cache() {
+            eprintln!("[local_source] 保存文件指纹失败: {}", e);
+        }
+        if !report.cancelled {
+            on_progress(paths.len());
+        }
+        Ok(report)
+    }
+
     /// 从 AudioMeta 构建 Song 模型。
     ///
     /// 关键逻辑：
     /// - 将 `meta.artist` 按 `/`、`&`、`、`、`，`、` feat. `、` ft. `、` featuring ` 等
     ///   分隔符拆分为多个独立 artist，每个生成独立 UUID。
-    /// - 写入 `song.year = meta.year`，供后续 album 聚合使用。
+    /// - 写入 `song.year = meta.year` 及发行 / 原始发行日期，供后续 album 聚合使用。
+    /// - 写入标签中的多语言标题 / 艺人名（`alt_titles` / `alt_artist_names`）。
     pub fn build_song(&self, file_path: &PlatformPath, meta: &AudioMeta) -> Song {
         let entity_id = platform::path_to_string(file_path);
         let song_id = Uuid::new_v4().to_string();
@@ -413,6 +603,18 @@ impl LocalMusicSource {
         let album_id = album_title.as_ref().map(|_| Uuid::new_v4().to_string());
         let lyric_id = Some(Uuid::new_v4().to_string());
 
+        // 备用语言艺人名同样拆分；拆分后数量与主艺人名一致时才能按顺序对应
+        let mut alt_artist_names = Vec::new();
+        for alt in &meta.alt_artists {
+            let names = split_artist_names(Some(&alt.text));
+            if names.len() == artist_names.len() {
+                alt_artist_names.extend(names.into_iter().map(|text| LocalizedText {
+                    lang: alt.lang.clone(),
+                    text,
+                }));
+            }
+        }
+
         let source_id = SourceId {
             source_name: LOCAL_SOURCE_NAME.to_string(),
             source_type: SourceType::Local,
@@ -420,7 +622,7 @@ impl LocalMusicSource {
             entity_id: entity_id.clone(),
         };
 
-        Song {
+        let mut song = Song {
             id: song_id,
             title: meta.title.clone().unwrap_or_else(|| "未知歌曲".to_string()),
             artist_names,
@@ -431,7 +633,152 @@ impl LocalMusicSource {
             lyric_id,
             source_ids: vec![source_id],
             year: meta.year,
+            release_date: meta.release_date.clone(),
+            original_date: meta.original_date.clone(),
+            alt_titles: meta.alt_titles.clone(),
+            alt_artist_names,
+            genres: meta.genres.clone(),
+            composers: meta.composers.clone(),
+            track_number: meta.track_number,
+            disc_number: meta.disc_number,
+            replay_gain_db: meta.replay_gain_db,
+            isrc: meta.isrc.clone(),
+            catalog_number: meta.catalog_number.clone(),
+            barcode: meta.barcode.clone(),
+            language: meta.language.clone(),
+            language_inferred: false,
+            size_bytes: platform::file_size(file_path).ok(),
+            codec: meta.codec.clone(),
+            lossless: meta.lossless,
+            bitrate_kbps: meta.bitrate_kbps,
+            sample_rate: meta.sample_rate,
+            content_type: ContentType::Music,
+            content_hash: None,
+        };
+        song.content_type = books::classify(&song);
+        song
+    }
+
+    /// 构建入库用的 Song：先与该文件的用户编辑对账，再按生效值构建。
+    ///
+    /// 扫描 / 重扫入库时使用；对账中发现的冲突记入库内冲突列表。
+    pub fn build_indexed_song(&self, file_path: &PlatformPath, meta: &AudioMeta) -> Song {
+        let key = platform::path_to_string(file_path);
+        let file_values = field_values(meta);
+        let mut values = file_values.clone();
+        match self.library.apply_user_edits(&key, &mut values) {
+            Ok(0) => {}
+            Ok(n) => eprintln!("[local_source] '{}' 有 {} 个字段与用户编辑冲突", key, n),
+            Err(e) => eprintln!("[local_source] 应用用户编辑失败 '{}': {}", key, e),
+        }
+        if values == file_values {
+            return self.build_song(file_path, meta);
+        }
+        let take = |field| values.get(&field).cloned().flatten();
+        let year: Option<u32> = take(MetadataField::Year).and_then(|y| y.trim().parse().ok());
+        // 用户改过年份时，与之不符的完整发行日期不再可信
+        let release_date = meta
+            .release_date
+            .clone()
+            .filter(|d| year.is_some_and(|y| d.starts_with(&format!("{:04}", y))));
+        let edited = AudioMeta {
+            title: take(MetadataField::Title),
+            artist: take(MetadataField::Artist),
+            album: take(MetadataField::Album),
+            year,
+            release_date,
+            ..meta.clone()
+        };
+        self.build_song(file_path, &edited)
+    }
+
+    /// 把探测到的文件评分 / 播放次数导入播放统计。
+    pub fn import_file_stats(&self, song_id: &str, meta: &AudioMeta) {
+        self.import_stats(
+            song_id,
+            &FileStats {
+                rating: meta.rating,
+                play_count: meta.play_count,
+            },
+        );
+    }
+
+    fn import_stats(&self, song_id: &str, stats: &FileStats) {
+        if stats.rating.is_none() && stats.play_count.is_none() {
+            return;
+        }
+        if let Err(e) = self
+            .library
+            .import_file_stats(song_id, stats.rating, stats.play_count)
+        {
+            eprintln!("[local_source] 导入评分失败 '{}': {}", song_id, e);
+        }
+    }
+
+    /// 把库内的评分与播放次数写回歌曲的本地文件（POPM / FMPS，见 [`file_stats`]）。
+    pub fn write_stats_to_file(&self, song_id: &str) -> Result<(), String> {
+        let path = self
+            .id_to_path
+            .read()
+            .get(song_id)
+            .cloned()
+            .ok_or_else(|| format!("歌曲 {} 不在本地来源中", song_id))?;
+        self.ensure_writable(&path)?;
+        let stats = self.library.get_play_stats(song_id);
+        let result = file_stats::write_file_stats(
+            &path,
+            &FileStats {
+                rating: stats.rating,
+                play_count: Some(stats.play_count),
+            },
+        );
+        self.meta_cache.invalidate(&path);
+        result
+    }
+
+    /// 本地来源整体设为只读（见 [`SourceEntry::read_only`](crate::module::music_source::manager::SourceEntry::read_only)），
+    /// 或文件所在文件夹设为只读（见 [`FolderAccess`](super::folder::FolderAccess)）时拒绝写入。
+    fn ensure_writable(&self, path: &PlatformPath) -> Result<(), String> {
+        if self.source_manager.find_entry(&self.name).is_some_and(|e| e.read_only) {
+            return Err("本地来源为只读，需先在音乐源管理中允许写入文件".to_string());
+        }
+        if self.folder_manager.access_for(path).read_only {
+            return Err(format!("'{}' 所在的文件夹为只读", platform::path_to_string(path)));
         }
+        Ok(())
+    }
+
+    /// 授权本次运行访问某个文件，即使它不在任何监听文件夹内。
+    ///
+    /// 供临时播放（[`session`](super::session)）使用；授权不持久化，重启后失效。
+    pub fn grant_session_path(&self, path: &PlatformPath) {
+        let canonical = platform::canonicalize(path).unwrap_or_else(|_| path.clone());
+        self.session_grants.write().insert(canonical);
+    }
+
+    /// 把 entity_id（库内 Song ID 或文件路径）解析为允许访问的文件路径。
+    ///
+    /// 已索引的文件直接放行；其余路径规范化后必须位于某个监听文件夹内，
+    /// 或已由 [`grant_session_path`](Self::grant_session_path) 授权。
+    /// 这样移除文件夹后、或前端传入任意路径时，都无法借本地来源读取库外的文件。
+    fn resolve_accessible(&self, entity_id: &str) -> Result<PlatformPath, String> {
+        if let Some(p) = self.id_to_path.read().get(entity_id) {
+            return Ok(p.clone());
+        }
+        let path = PlatformPath::from(entity_id);
+        if self.file_index.read().contains_key(&path) {
+            return Ok(path);
+        }
+        let Ok(canonical) = platform::canonicalize(&path) else {
+            return Err(format!("文件不存在: {}", entity_id));
+        };
+        if self.session_grants.read().contains(&canonical)
+            || self.folder_manager.contains_path(&canonical)
+        {
+            return Ok(canonical);
+        }
+        eprintln!("[access] 拒绝访问监听文件夹之外的文件: {}", entity_id);
+        Err(format!("文件不在已添加的音乐文件夹内: {}", entity_id))
     }
 
     /// 按文件路径查找对应的库内 Song ID。
@@ -476,12 +823,21 @@ impl LocalMusicSource {
             t0
         );
 
-        // 从独立 mtime 存储加载
+        // 从独立 mtime 存储加载；旧版本只记录 (mtime, 大小, song_id)，迁移为没有头部哈希的指纹
         let _t1 = Instant::now();
-        let mut mtimes: HashMap<String, (u64, u64, String)> = self
-            .mtime_store
-            .get("file_mtimes")
-            .unwrap_or_default();
+        let mut mtimes: HashMap<String, FileFingerprint> = match self.mtime_store.get(FINGERPRINTS_KEY) {
+            Some(fingerprints) => fingerprints,
+            None => self
+                .mtime_store
+                .get::<HashMap<String, (u64, u64, String)>>(LEGACY_MTIMES_KEY)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(path, (mtime, size, song_id))| {
+                    let fingerprint = FileFingerprint { mtime, size, head_hash: None, song_id };
+                    (path, fingerprint)
+                })
+                .collect(),
+        };
         let t1 = _t1.elapsed();
         eprintln!(
             "[local_source] ⏱ 4b. 加载 mtime 缓存 ({} 条): {:?}",
@@ -602,65 +958,217 @@ impl LocalMusicSource {
         (restored, 0)
     }
 
-    /// 检查文件 mtime+size 是否与缓存一致，返回缓存的 song_id（若未变化）。
+    /// 检查文件指纹是否与缓存一致，返回缓存的 song_id（若未变化）。
     ///
+    /// 只有 mtime 变化而内容一致时同样视为未变化，并更新记录的 mtime。
     /// `path` 应为已规范化的路径。
     pub fn check_file_unchanged(&self, path: &PlatformPath) -> Option<String> {
         let path_str = platform::path_to_string(path);
-        let mtimes = self.file_mtimes.read();
-        let (cached_mtime, cached_size, song_id) = mtimes.get(&path_str)?;
-
-        let current_mtime = platform::file_modified_secs(
-            &PlatformPath::from(path_str.as_str()),
-        )
-        .ok()?;
-        let current_size = platform::file_size(
-            &PlatformPath::from(path_str.as_str()),
-        )
-        .ok()?;
-
-        if current_mtime == *cached_mtime && current_size == *cached_size {
-            Some(song_id.clone())
-        } else {
-            None
+        let cached = self.file_mtimes.read().get(&path_str).cloned()?;
+        match cached.compare(path) {
+            FingerprintMatch::Unchanged => Some(cached.song_id),
+            FingerprintMatch::Touched { mtime } => {
+                if let Some(entry) = self.file_mtimes.write().get_mut(&path_str) {
+                    entry.mtime = mtime;
+                }
+                Some(cached.song_id)
+            }
+            FingerprintMatch::Changed => None,
         }
     }
 
-    /// 更新文件的 mtime 缓存条目。
+    /// 更新文件的指纹缓存条目。
     ///
     /// `path` 应为已规范化的路径。
     pub fn update_file_mtime(&self, path: &PlatformPath, song_id: &str) {
-        let path_str = platform::path_to_string(path);
-        let platform_path = PlatformPath::from(path_str.as_str());
-        if let (Ok(mtime), Ok(size)) = (
-            platform::file_modified_secs(&platform_path),
-            platform::file_size(&platform_path),
-        ) {
-            self.file_mtimes.write().insert(
-                path_str,
-                (mtime, size, song_id.to_string()),
-            );
+        if let Some(fingerprint) = FileFingerprint::read(path, song_id) {
+            self.file_mtimes
+                .write()
+                .insert(platform::path_to_string(path), fingerprint);
         }
     }
 
-    /// 将 mtime 缓存持久化到独立存储（与 library 分离，避免每次保存都序列化全部歌曲）。
+    /// 将指纹缓存持久化到独立存储（与 library 分离，避免每次保存都序列化全部歌曲）。
     pub fn save_mtime_cache(&self) -> Result<(), String> {
         let mtimes = self.file_mtimes.read().clone();
-        self.mtime_store.set("file_mtimes", &mtimes)?;
+        self.mtime_store.set(FINGERPRINTS_KEY, &mtimes)?;
+        self.mtime_store.remove(LEGACY_MTIMES_KEY);
         self.mtime_store.save()
     }
 
+    /// 探测元数据，并把结果计入隔离列表（成功清除记录，失败累计次数）。
+    fn probe_tracked(&self, path: &PlatformPath) -> Result<AudioMeta, String> {
+        let result = self.meta_cache.read(path);
+        match &result {
+            Ok(_) => self.quarantine.record_success(path),
+            Err(e) => self.note_failure(path, e),
+        }
+        if let Err(e) = self.quarantine.save() {
+            eprintln!("[local_source] 保存隔离列表失败: {}", e);
+        }
+        result
+    }
+
+    /// 记录一次探测失败；文件因此进入隔离时打印一次日志并发布事件。
+    pub fn note_failure(&self, path: &PlatformPath, error: &str) {
+        if self.quarantine.record_failure(path, error) {
+            let path = platform::path_to_string(path);
+            eprintln!("[local_source] 文件多次探测失败，已隔离 '{}': {}", path, error);
+            self.events.publish(AppEvent::FileQuarantined { path });
+        }
+    }
+
+    /// 解除文件的隔离并立即重新索引。
+    ///
+    /// 返回 `true` 表示文件已成功入库；再次失败时返回错误，失败计数从 1 重新开始。
+    pub fn retry_quarantined(&self, path: &PlatformPath) -> Result<bool, String> {
+        let canonical = platform::canonicalize(path).unwrap_or_else(|_| path.clone());
+        self.quarantine.release(&canonical);
+        let indexed = self.index_file(&canonical)?;
+        if indexed {
+            self.library.save_if_dirty()?;
+            let _ = self.save_mtime_cache();
+        }
+        Ok(indexed)
+    }
+
+    /// 修改歌曲元数据并立即重新索引。
+    ///
+    /// 只修改库内数据，不写回文件标签；修改按文件记录，之后重扫时优先于文件标签
+    /// （见 [`edits`](crate::module::music_library::edits)）。
+    /// 返回重新索引后歌曲在库中的 ID（标题 / 艺人变化后可能与原 ID 不同）。
+    pub fn edit_song_metadata(
+        &self,
+        song_id: &str,
+        changes: &FieldValues,
+    ) -> Result<Option<String>, String> {
+        let ids = self.edit_songs_metadata(&[song_id.to_string()], changes)?;
+        Ok(ids.into_iter().next().flatten())
+    }
+
+    /// 批量修改多首歌曲的同一组字段，整批在变更历史中记为一条，可一次撤销。
+    ///
+    /// 任一歌曲不在本地来源中时不做任何修改。返回值与 `song_ids` 一一对应。
+    pub fn edit_songs_metadata(
+        &self,
+        song_ids: &[String],
+        changes: &FieldValues,
+    ) -> Result<Vec<Option<String>>, String> {
+        let paths = {
+            let id_to_path = self.id_to_path.read();
+            song_ids
+                .iter()
+                .map(|id| {
+                    id_to_path
+                        .get(id)
+                        .cloned()
+                        .ok_or_else(|| format!("歌曲 {} 不在本地来源中", id))
+                })
+                .collect::<Result<Vec<_>, String>>()?
+        };
+        let metas = paths
+            .iter()
+            .map(scanner::probe_file)
+            .collect::<Result<Vec<_>, String>>()?;
+        let keys: Vec<String> = paths.iter().map(platform::path_to_string).collect();
+
+        let summary = match song_ids.len() {
+            1 => format!("编辑元数据: {}", keys[0]),
+            n => format!("编辑 {} 首歌曲的元数据", n),
+        };
+        let snapshot = self.library.snapshot_files(&keys);
+        self.library
+            .record_change(summary, Change::MetadataEdit { files: snapshot })?;
+
+        for ((path, key), meta) in paths.iter().zip(&keys).zip(&metas) {
+            self.library.edit_metadata(key, changes, &field_values(meta))?;
+            self.reindex_file(path)?;
+        }
+        self.library.save_if_dirty()?;
+        Ok(paths.iter().map(|p| self.find_song_id_by_path(p)).collect())
+    }
+
+    /// 把元数据直接写进歌曲文件的标签（见 [`tag_writer`](super::tag_writer)），随后重新索引。
+    ///
+    /// 写入的字段此后与文件一致，库内对这些字段的用户编辑与冲突一并清除。
+    /// 改的是文件本身，不计入变更历史。返回重新索引后歌曲在库中的 ID。
+    pub fn write_song_metadata(
+        &self,
+        song_id: &str,
+        fields: &FieldValues,
+    ) -> Result<Option<String>, String> {
+        let path = self
+            .id_to_path
+            .read()
+            .get(song_id)
+            .cloned()
+            .ok_or_else(|| format!("歌曲 {} 不在本地来源中", song_id))?;
+        self.ensure_writable(&path)?;
+        let result = tag_writer::write_fields(&path, fields);
+        self.meta_cache.invalidate(&path);
+        result?;
+
+        // 变化与文件值相同，edit_metadata 会撤销这些字段的编辑
+        self.library
+            .edit_metadata(&platform::path_to_string(&path), fields, fields)?;
+        self.reindex_file(&path)?;
+        self.library.save_if_dirty()?;
+        Ok(self.find_song_id_by_path(&path))
+    }
+
+    /// 撤销最近一次库变更；元数据类变更恢复编辑记录后重新索引涉及的文件。
+    pub fn undo_last_change(&self) -> Result<Option<UndoResult>, String> {
+        let Some(result) = self.library.undo_last_change()? else {
+            return Ok(None);
+        };
+        for path in &result.reindex_paths {
+            let path = PlatformPath::from(path.as_str());
+            if platform::is_file(&path) {
+                self.reindex_file(&path)?;
+            }
+        }
+        self.library.save_if_dirty()?;
+        Ok(Some(result))
+    }
+
+    /// 处理一条元数据冲突；采用文件值时重新索引该文件。
+    ///
+    /// 返回冲突是否存在。
+    pub fn resolve_metadata_conflict(
+        &self,
+        path: &str,
+        field: MetadataField,
+        resolution: ConflictResolution,
+    ) -> Result<bool, String> {
+        let found = self
+            .library
+            .resolve_metadata_conflict(path, field, resolution)?;
+        if found && resolution == ConflictResolution::UseFile {
+            self.reindex_file(&PlatformPath::from(path))?;
+        }
+        self.library.save_if_dirty()?;
+        Ok(found)
+    }
+
     /// 从磁盘提取专辑封面字节（无缓存）。
     ///
     /// 提取顺序：
-    /// 1. 音频文件嵌入封面（FLAC / ID3v2）— symphonia 解析
+    /// 1. 音频文件嵌入封面 — 先按索引只读取选中的那张图片（FLAC / ID3v2 / MP4），
+    ///    无法索引的格式回退到 symphonia 完整解析；超过大小上限的嵌入图片忽略
     /// 2. 同目录同名图片（.jpg/.png/.webp/.bmp）
     /// 3. 目录下常见封面名（cover/folder/albumart/front）
     fn extract_album_picture(&self, path: &PlatformPath) -> Result<Vec<u8>, String> {
-        // 1. 若为音频文件，尝试提取嵌入封面（FLAC / ID3v2）
-        if platform::is_file(path) && super::scanner::is_supported_audio(path) {
-            if let Ok(cover_data) = super::scanner::extract_cover_art(path) {
-                return Ok(cover_data);
+        // 1. 若为音频文件，尝试提取嵌入封面
+        if platform::is_file(path) && self.folder_manager.is_supported_file(path) {
+            let embedded = super::pictures::index_file(path);
+            if embedded.is_empty() {
+                if let Ok(cover_data) = super::scanner::extract_cover_art(path) {
+                    return Ok(cover_data);
+                }
+            } else if let Some(cover) = super::pictures::pick_cover(&embedded) {
+                if let Ok(cover_data) = super::pictures::read_picture(path, cover) {
+                    return Ok(cover_data);
+                }
             }
         }
 
@@ -735,18 +1243,11 @@ impl MusicSource for LocalMusicSource {
 
     fn get_song(&self, id: &str) -> Result<Option<Song>, String> {
         // id 可能是库内 UUID 或文件路径
-        let path = if let Some(p) = self.id_to_path.read().get(id) {
-            p.clone()
-        } else {
-            let p = PlatformPath::from(id);
-            if platform::exists(&p) {
-                p
-            } else {
-                return Ok(None);
-            }
+        let Ok(path) = self.resolve_accessible(id) else {
+            return Ok(None);
         };
 
-        match scanner::probe_file(&path) {
+        match self.meta_cache.read(&path) {
             Ok(meta) => Ok(Some(self.build_song(&path, &meta))),
             Err(_) => Ok(None),
         }
@@ -767,22 +1268,22 @@ impl MusicSource for LocalMusicSource {
     }
 
     fn song_file_get(&self, entity_id: &str) -> Result<Vec<u8>, String> {
-        let path = PlatformPath::from(entity_id);
+        let path = self.resolve_accessible(entity_id)?;
         platform::read_bytes(&path)
             .map_err(|e| format!("读取音频文件失败 '{}': {}", entity_id, e))
     }
 
+    fn is_playable(&self, entity_id: &str) -> bool {
+        // 无法解析的 ID 交给 song_file_get / song_file_path 报告具体原因
+        self.resolve_accessible(entity_id)
+            .map_or(true, |path| !self.folder_manager.access_for(&path).metadata_only)
+    }
+
     fn song_file_path(&self, entity_id: &str) -> Option<String> {
-        let path = PlatformPath::from(entity_id);
-        if platform::is_file(&path) {
-            Some(entity_id.to_string())
-        } else {
-            // 尝试通过 id_to_path 查找
-            self.id_to_path
-                .read()
-                .get(entity_id)
-                .map(|p| platform::path_to_string(p))
-        }
+        self.resolve_accessible(entity_id)
+            .ok()
+            .filter(platform::is_file)
+            .map(|p| platform::path_to_string(&p))
     }
 
     fn album_picture_get(&self, entity_id: &str) -> Result<Vec<u8>, String> {
@@ -796,7 +1297,7 @@ impl MusicSource for LocalMusicSource {
             }
         }
 
-        let path = PlatformPath::from(entity_id);
+        let path = self.resolve_accessible(entity_id)?;
         let data = self.extract_album_picture(&path)?;
 
         // 2. 写入缓存（容量上限简单淘汰策略：超出 cap 时清空一半）
@@ -817,11 +1318,7 @@ impl MusicSource for LocalMusicSource {
     fn lyric_text_get(&self, song_id: &str) -> Result<String, String> {
         // 通过 song_id（或直接当作路径）定位音频文件，再读同目录 .lrc / .txt
         // 复用 scanner::read_lyric_file，与扫描时入库的逻辑保持一致
-        let audio_path = if let Some(p) = self.id_to_path.read().get(song_id) {
-            p.clone()
-        } else {
-            PlatformPath::from(song_id)
-        };
+        let audio_path = self.resolve_accessible(song_id)?;
 
         scanner::read_lyric_file(&audio_path)
             .ok_or_else(|| format!("未找到歌词文件: {}", platform::path_to_string(&audio_path)))
@@ -830,6 +1327,27 @@ impl MusicSource for LocalMusicSource {
 
 // ── 模块级辅助 ───────────────────────────────────────────────────────────────
 
+/// 文件标签中可由用户编辑的字段值（年份转为字符串）。
+/// 标签中没有演唱语言时按歌词推断，见 [`language::infer_from_lyrics`]。
+pub(crate) fn infer_language(song: &mut Song, lyric_text: Option<&str>) {
+    if song.language.is_some() {
+        return;
+    }
+    if let Some(lang) = lyric_text.and_then(language::infer_from_lyrics) {
+        song.language = Some(lang);
+        song.language_inferred = true;
+    }
+}
+
+fn field_values(meta: &AudioMeta) -> FieldValues {
+    FieldValues::from([
+        (MetadataField::Title, meta.title.clone()),
+        (MetadataField::Artist, meta.artist.clone()),
+        (MetadataField::Album, meta.album.clone()),
+        (MetadataField::Year, meta.year.map(|y| y.to_string())),
+    ])
+}
+
 /// 将单个 artist 标签字符串按常见分隔符拆分为多个独立 artist 名称。
 ///
 /// 支持的分隔符（不区分大小写）：
@@ -845,10 +1363,10 @@ impl MusicSource for LocalMusicSource {
 pub fn split_artist_names(raw: Option<&str>) -> Vec<String> {
     let raw = match raw {
         Some(s) => s.trim(),
-        None => return vec!["未知艺术家".to_string()],
+        None => return vec![artists::UNKNOWN_NAME.to_string()],
     };
     if raw.is_empty() {
-        return vec!["未知艺术家".to_string()];
+        return vec![artists::UNKNOWN_NAME.to_string()];
     }
 
     // 策略：先按 ASCII 分隔符 (`/`、`&`、`,`、`;`) 和全角分隔符拆分，
@@ -879,7 +1397,7 @@ pub fn split_artist_names(raw: Option<&str>) -> Vec<String> {
     }
 
     if final_parts.is_empty() {
-        vec!["未知艺术家".to_string()]
+        vec![artists::UNKNOWN_NAME.to_string()]
     } else {
         final_parts
     }
@@ -1039,4 +1557,100 @@ mod tests {
             vec!["A", "B", "C", "D"]
         );
     }
+
+    #[test]
+    fn test_write_back_respects_source_read_only() {
+        use super::*;
+        use crate::module::config::store::ConfigStore;
+        use crate::module::fixtures::{self, FixtureFormat, FixtureSpec};
+
+        let dir = std::env::temp_dir().join(format!("chordial_local_read_only_{}", std::process::id()));
+        let spec = FixtureSpec::default();
+        let audio = fixtures::write(&dir.join("music"), "song", FixtureFormat::Flac, &spec).unwrap();
+        let store = |name: &str| PersistentStore::new(dir.join(name));
+        let manager = Arc::new(SourceManager::new(dir.join("sources.json")));
+        let source = LocalMusicSource::new(
+            Arc::new(FolderManager::new(store("folders.json"))),
+            Arc::new(MusicLibrary::new(dir.join("library.json"))),
+            store("mtimes.json"),
+            Quarantine::new(store("quarantine.json")),
+            Arc::new(EventBus::new()),
+            Arc::new(PowerMonitor::new(Arc::new(ConfigStore::new(dir.join("config.json"))))),
+            manager.clone(),
+        );
+        let path = platform::canonicalize(&PlatformPath::from(audio.as_path())).unwrap();
+        assert!(source.index_file(&path).unwrap());
+        let song_id = source.find_song_id_by_path(&path).unwrap();
+        let retitle = FieldValues::from([(MetadataField::Title, Some("Renamed".to_string()))]);
+
+        // 本地来源的条目默认可写；设为只读后标签与评分都不写入文件
+        manager.add_entry(LOCAL_SOURCE_NAME, SourceType::Local).unwrap();
+        assert!(!manager.find_entry(LOCAL_SOURCE_NAME).unwrap().read_only);
+        manager.set_read_only(LOCAL_SOURCE_NAME, true).unwrap();
+        assert!(source.write_song_metadata(&song_id, &retitle).unwrap_err().contains("只读"));
+        assert!(source.write_stats_to_file(&song_id).unwrap_err().contains("只读"));
+        assert_eq!(scanner::probe_file(&path).unwrap().title, Some(spec.title.clone()));
+
+        manager.set_read_only(LOCAL_SOURCE_NAME, false).unwrap();
+        source.write_song_metadata(&song_id, &retitle).unwrap();
+        assert_eq!(scanner::probe_file(&path).unwrap().title.as_deref(), Some("Renamed"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_cancelled_rescan_keeps_changed_files_and_reports_partial_work() {
+        use super::*;
+        use crate::module::config::store::ConfigStore;
+        use crate::module::fixtures::{self, FixtureFormat, FixtureSpec};
+
+        let dir = std::env::temp_dir().join(format!("chordial_local_cancel_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let music = dir.join("music");
+        let write = |stem: &str, title: &str| {
+            let spec = FixtureSpec { title: title.to_string(), ..FixtureSpec::default() };
+            let path = fixtures::write(&music, stem, FixtureFormat::Flac, &spec).unwrap();
+            platform::canonicalize(&PlatformPath::from(path.as_path())).unwrap()
+        };
+        let store = |name: &str| PersistentStore::new(dir.join(name));
+        let library = Arc::new(MusicLibrary::new(dir.join("library.json")));
+        let source = LocalMusicSource::new(
+            Arc::new(FolderManager::new(store("folders.json"))),
+            library.clone(),
+            store("mtimes.json"),
+            Quarantine::new(store("quarantine.json")),
+            Arc::new(EventBus::new()),
+            Arc::new(PowerMonitor::new(Arc::new(ConfigStore::new(dir.join("config.json"))))),
+            Arc::new(SourceManager::new(dir.join("sources.json"))),
+        );
+        let indexed: Vec<PlatformPath> = ["a", "b", "c"].iter().map(|stem| write(stem, stem)).collect();
+        let report = source.rescan_files(&indexed, ScanOptions::default()).unwrap();
+        assert_eq!((report.indexed, report.cancelled), (3, false));
+
+        // a 改动（标题变长，文件大小随之变化），d / e 为新文件；读取开始前取消
+        write("a", "a (changed)");
+        let mut paths = indexed.clone();
+        paths.push(write("d", "d"));
+        paths.push(write("e", "e"));
+        let options = ScanOptions::default();
+        let cancel = options.cancel.clone();
+        let mut progress = Vec::new();
+        let report = source
+            .rescan_files_with_progress(&paths, options, |done| {
+                cancel.cancel();
+                progress.push(done);
+            })
+            .unwrap();
+
+        assert!(report.cancelled);
+        assert_eq!((report.files_found, report.unchanged, report.changed), (5, 2, 1));
+        // 只有已移出库的改动文件被处理；新文件未读取，也没有「全部完成」的进度
+        assert_eq!(report.indexed, 1);
+        assert!(!progress.contains(&paths.len()));
+        assert_eq!(library.song_count(), 3);
+        let a = source.find_song_id_by_path(&indexed[0]).unwrap();
+        assert_eq!(library.get_song(&a).unwrap().title, "a (changed)");
+        assert!(source.find_song_id_by_path(&paths[3]).is_none());
+        assert!(source.find_song_id_by_path(&paths[4]).is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,91 @@
+//! FLAC 标签写回：替换 `VORBIS_COMMENT` 元数据块。
+//!
+//! 注释块放在 STREAMINFO 之后，`PADDING` 块统一放到最后并按需伸缩，
+//! 其余块（封面、SEEKTABLE、CUESHEET 等）原样保留。
+
+use super::{io_err, replace_head, MetadataWriter, VorbisComment, PADDING_BYTES};
+use crate::module::music_library::edits::FieldValues;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const BLOCK_PADDING: u8 = 1;
+const BLOCK_VORBIS_COMMENT: u8 = 4;
+
+pub struct FlacTagWriter;
+
+impl MetadataWriter for FlacTagWriter {
+    fn name(&self) -> &'static str {
+        "FLAC"
+    }
+
+    fn accepts(&self, head: &[u8], _extension: &str) -> bool {
+        head.starts_with(b"fLaC")
+    }
+
+    fn write(&self, path: &Path, fields: &FieldValues) -> Result<(), String> {
+        let file = File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+        rewrite_comment(path, file, |comment| {
+            comment.apply_fields(fields);
+            Ok(())
+        })
+    }
+}
+
+/// 读出注释块（没有时新建）交给 `update` 修改，再写回文件。
+pub(crate) fn rewrite_comment(
+    path: &Path,
+    mut file: File,
+    update: impl FnOnce(&mut VorbisComment) -> Result<(), String>,
+) -> Result<(), String> {
+    file.seek(SeekFrom::Start(4)).map_err(io_err)?;
+    let mut blocks: Vec<(u8, Vec<u8>)> = Vec::new();
+    loop {
+        let mut header = [0u8; 4];
+        file.read_exact(&mut header).map_err(io_err)?;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        let mut body = vec![0u8; len];
+        file.read_exact(&mut body).map_err(io_err)?;
+        blocks.push((header[0] & 0x7f, body));
+        if header[0] & 0x80 != 0 {
+            break;
+        }
+    }
+    let audio_offset = file.stream_position().map_err(io_err)?;
+
+    let mut comment = match blocks.iter().position(|(kind, _)| *kind == BLOCK_VORBIS_COMMENT) {
+        Some(i) => VorbisComment::parse(&blocks.remove(i).1)?.0,
+        None => VorbisComment::new(),
+    };
+    update(&mut comment)?;
+    blocks.insert(1.min(blocks.len()), (BLOCK_VORBIS_COMMENT, comment.to_bytes()));
+    let had_padding = blocks
+        .iter()
+        .position(|(kind, _)| *kind == BLOCK_PADDING)
+        .map(|i| blocks.remove(i))
+        .is_some();
+
+    let used: usize = 4 + blocks.iter().map(|(_, b)| 4 + b.len()).sum::<usize>();
+    let old_len = audio_offset as usize;
+    // 放得下就保持总长度不变，原地覆盖
+    let padding_len = if had_padding && used + 4 <= old_len {
+        old_len - used - 4
+    } else {
+        PADDING_BYTES
+    };
+    blocks.push((BLOCK_PADDING, vec![0u8; padding_len]));
+
+    let mut head = Vec::with_capacity(used + 4 + padding_len);
+    head.extend_from_slice(b"fLaC");
+    let last = blocks.len() - 1;
+    for (i, (kind, body)) in blocks.iter().enumerate() {
+        if body.len() >= 1 << 24 {
+            return Err("FLAC 元数据块过大".to_string());
+        }
+        let flag = if i == last { 0x80 } else { 0 };
+        head.push(kind | flag);
+        head.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+        head.extend_from_slice(body);
+    }
+    replace_head(path, file, audio_offset, &head)
+}
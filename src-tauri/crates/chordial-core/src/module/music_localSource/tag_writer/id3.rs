@@ -0,0 +1,177 @@
+//! ID3v2 标签写回（MP3）：替换 `TIT2` / `TPE1` / `TALB` / 年份文本帧。
+//!
+//! 保留原标签的版本：v2.4 写 UTF-8；v2.3 没有 UTF-8，纯 ASCII 写 ISO-8859-1，
+//! 否则写带 BOM 的 UTF-16。没有标签的文件新建 v2.4 标签。
+//! 非同步化或带页脚的标签不做改动，直接报错。
+
+use super::{field_text, io_err, replace_head, MetadataWriter, PADDING_BYTES};
+use crate::module::music_library::edits::{FieldValues, MetadataField};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// ID3 帧：`(帧 ID, 帧标志, 帧数据)`。
+pub(crate) type Frame = ([u8; 4], [u8; 2], Vec<u8>);
+
+pub struct Id3TagWriter;
+
+impl MetadataWriter for Id3TagWriter {
+    fn name(&self) -> &'static str {
+        "ID3v2"
+    }
+
+    fn accepts(&self, head: &[u8], extension: &str) -> bool {
+        head.starts_with(b"ID3") || extension == "mp3"
+    }
+
+    fn write(&self, path: &Path, fields: &FieldValues) -> Result<(), String> {
+        let file = File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+        rewrite_frames(path, file, 4, |major, frames| {
+            for (field, value) in fields {
+                let ids: &[&[u8; 4]] = match field {
+                    MetadataField::Title => &[b"TIT2"],
+                    MetadataField::Artist => &[b"TPE1"],
+                    MetadataField::Album => &[b"TALB"],
+                    MetadataField::Year => &[b"TDRC", b"TYER"],
+                };
+                frames.retain(|(id, _, _)| !ids.contains(&id));
+                if let Some(text) = field_text(value) {
+                    let id = match (field, major) {
+                        (MetadataField::Year, 3) => *b"TYER",
+                        _ => *ids[0],
+                    };
+                    frames.push((id, [0, 0], text_frame(major, text)));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// 文本帧数据：编码字节 + 文本。
+fn text_frame(major: u8, text: &str) -> Vec<u8> {
+    if major == 4 {
+        let mut data = vec![3];
+        data.extend_from_slice(text.as_bytes());
+        return data;
+    }
+    if text.is_ascii() {
+        let mut data = vec![0];
+        data.extend_from_slice(text.as_bytes());
+        return data;
+    }
+    let mut data = vec![1, 0xff, 0xfe];
+    data.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    data
+}
+
+/// 读出全部帧交给 `update` 修改，再写回文件。
+///
+/// 没有标签时新建版本为 `new_major` 的标签；`update` 收到的是实际写入的版本号。
+pub(crate) fn rewrite_frames(
+    path: &Path,
+    mut file: File,
+    new_major: u8,
+    update: impl FnOnce(u8, &mut Vec<Frame>) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut header = [0u8; 10];
+    let has_tag = file.read_exact(&mut header).is_ok() && &header[..3] == b"ID3";
+
+    let (major, old_len, mut frames) = if has_tag {
+        let major = header[3];
+        let flags = header[5];
+        if !(3..=4).contains(&major) {
+            return Err("只支持写回 ID3v2.3 / v2.4 标签".to_string());
+        }
+        if flags & 0xd0 != 0 {
+            return Err("暂不支持写回非同步化 / 带页脚的 ID3 标签".to_string());
+        }
+        let tag_size = syncsafe(&header[6..10]) as usize;
+        let mut body = vec![0u8; tag_size];
+        file.read_exact(&mut body).map_err(io_err)?;
+        let skip = if flags & 0x40 != 0 {
+            extended_header_len(&body, major).ok_or("ID3 扩展头格式错误")?
+        } else {
+            0
+        };
+        (major, 10 + tag_size as u64, split_frames(&body[skip..], major))
+    } else {
+        (new_major, 0, Vec::new())
+    };
+
+    update(major, &mut frames)?;
+
+    let mut body = Vec::new();
+    for (id, flags, data) in &frames {
+        body.extend_from_slice(id);
+        let size = data.len() as u32;
+        if major == 4 {
+            body.extend_from_slice(&encode_syncsafe(size));
+        } else {
+            body.extend_from_slice(&size.to_be_bytes());
+        }
+        body.extend_from_slice(flags);
+        body.extend_from_slice(data);
+    }
+    let tag_size = if has_tag && body.len() as u64 + 10 <= old_len {
+        (old_len - 10) as usize
+    } else {
+        body.len() + PADDING_BYTES
+    };
+    body.resize(tag_size, 0);
+
+    let mut head = Vec::with_capacity(10 + tag_size);
+    head.extend_from_slice(&[b'I', b'D', b'3', major, 0, 0]);
+    head.extend_from_slice(&encode_syncsafe(tag_size as u32));
+    head.extend_from_slice(&body);
+    replace_head(path, file, old_len, &head)
+}
+
+/// 拆分 ID3 帧；遇到填充或格式错误时停止。
+fn split_frames(body: &[u8], major: u8) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    let mut pos = 0;
+    while pos + 10 <= body.len() && body[pos] != 0 {
+        let head = &body[pos..pos + 10];
+        let size = if major == 4 {
+            syncsafe(&head[4..8])
+        } else {
+            u32::from_be_bytes([head[4], head[5], head[6], head[7]]) as u64
+        } as usize;
+        let Some(data) = body.get(pos + 10..pos + 10 + size) else {
+            break;
+        };
+        frames.push((
+            [head[0], head[1], head[2], head[3]],
+            [head[8], head[9]],
+            data.to_vec(),
+        ));
+        pos += 10 + size;
+    }
+    frames
+}
+
+fn extended_header_len(body: &[u8], major: u8) -> Option<usize> {
+    let raw = body.get(..4)?;
+    let len = if major == 4 {
+        syncsafe(raw) as usize
+    } else {
+        u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize + 4
+    };
+    (len <= body.len()).then_some(len)
+}
+
+fn syncsafe(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0u64, |acc, &b| (acc << 7) | (b & 0x7f) as u64)
+}
+
+fn encode_syncsafe(value: u32) -> [u8; 4] {
+    [
+        (value >> 21 & 0x7f) as u8,
+        (value >> 14 & 0x7f) as u8,
+        (value >> 7 & 0x7f) as u8,
+        (value & 0x7f) as u8,
+    ]
+}
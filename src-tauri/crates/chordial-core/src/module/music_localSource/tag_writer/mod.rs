@@ -0,0 +1,290 @@
+//! 标签写回 — 把标题 / 艺人 / 专辑 / 年份直接写进音频文件的标签。
+//!
+//! 读取由 [`scanner::probe_file`](super::scanner::probe_file) 借 symphonia 完成，symphonia
+//! 不能写，写回按格式各自实现 [`MetadataWriter`]：
+//!
+//! | 格式 | 实现 | 标签 | 年份字段 |
+//! |------|------|------|----------|
+//! | MP3 | [`Id3TagWriter`](id3::Id3TagWriter) | ID3v2 `TIT2` / `TPE1` / `TALB`（新建为 v2.4） | `TDRC`（v2.3 为 `TYER`） |
+//! | FLAC | [`FlacTagWriter`](flac::FlacTagWriter) | `VORBIS_COMMENT` 块 | `DATE` |
+//! | OGG | [`OggTagWriter`](ogg::OggTagWriter) | Vorbis / Opus / Ogg FLAC 的注释头包 | `DATE` |
+//! | M4A | [`Mp4TagWriter`](mp4::Mp4TagWriter) | `moov/udta/meta/ilst` 的 `©nam` / `©ART` / `©alb` | `©day` |
+//!
+//! 只改动涉及的字段，其余标签（封面、评分、歌词等）原样保留；字段值为 `None` 时删除该标签。
+//! 新内容能放进原有空间时原地覆盖，否则写到同目录临时文件后替换原文件。
+//! [`file_stats`](super::file_stats) 的评分写回复用这里的 ID3 / FLAC 重写逻辑。
+
+pub mod flac;
+pub mod id3;
+pub mod mp4;
+pub mod ogg;
+
+use crate::module::music_library::edits::{FieldValues, MetadataField};
+use crate::module::platform::{self, PlatformPath};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// 新建 / 重写标签时预留的填充字节数，之后再改标签可原地写入。
+pub(crate) const PADDING_BYTES: usize = 2048;
+
+/// 按格式写入标签的实现。
+pub trait MetadataWriter: Send + Sync {
+    /// 格式名，用于错误信息。
+    fn name(&self) -> &'static str;
+
+    /// 是否处理该文件；`head` 为文件开头最多 12 字节，`extension` 为小写扩展名。
+    fn accepts(&self, head: &[u8], extension: &str) -> bool;
+
+    /// 写入字段：出现在 `fields` 中的字段被替换（`None` 为删除），其余不动。
+    fn write(&self, path: &Path, fields: &FieldValues) -> Result<(), String>;
+}
+
+static WRITERS: [&dyn MetadataWriter; 4] = [
+    &flac::FlacTagWriter,
+    &ogg::OggTagWriter,
+    &mp4::Mp4TagWriter,
+    &id3::Id3TagWriter,
+];
+
+/// 找出能写入该文件的实现。
+pub fn writer_for(path: &PlatformPath) -> Result<&'static dyn MetadataWriter, String> {
+    let path = PathBuf::from(platform::path_to_string(path));
+    let mut file = File::open(&path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let mut head = [0u8; 12];
+    let n = read_up_to(&mut file, &mut head).map_err(io_err)?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    WRITERS
+        .iter()
+        .copied()
+        .find(|w| w.accepts(&head[..n], &extension))
+        .ok_or_else(|| "暂不支持向该格式写入标签".to_string())
+}
+
+/// 把字段写入文件标签。
+pub fn write_fields(path: &PlatformPath, fields: &FieldValues) -> Result<(), String> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+    let writer = writer_for(path)?;
+    writer
+        .write(&PathBuf::from(platform::path_to_string(path)), fields)
+        .map_err(|e| format!("写入 {} 标签失败: {}", writer.name(), e))
+}
+
+/// 去掉首尾空白，空串视为删除。
+fn field_text(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+pub(crate) fn io_err(e: io::Error) -> String {
+    format!("读写文件失败: {}", e)
+}
+
+/// 用 `head` 替换文件开头的 `old_len` 字节，见 [`replace_range`]。
+pub(crate) fn replace_head(path: &Path, src: File, old_len: u64, head: &[u8]) -> Result<(), String> {
+    replace_range(path, src, 0, old_len, head)
+}
+
+/// 用 `data` 替换文件中从 `start` 起的 `old_len` 字节。
+///
+/// 长度相同时原地覆盖；否则写到同目录临时文件，再替换原文件。
+pub(crate) fn replace_range(
+    path: &Path,
+    mut src: File,
+    start: u64,
+    old_len: u64,
+    data: &[u8],
+) -> Result<(), String> {
+    if data.len() as u64 == old_len {
+        drop(src);
+        let mut out = OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map_err(|e| format!("打开文件失败: {}", e))?;
+        out.seek(SeekFrom::Start(start)).map_err(io_err)?;
+        out.write_all(data).map_err(io_err)?;
+        return out.flush().map_err(io_err);
+    }
+
+    // 闭包持有 `src`，写完即关闭，替换时原文件不再被占用
+    rewrite_via_temp(path, move |out| {
+        src.seek(SeekFrom::Start(0))?;
+        io::copy(&mut (&mut src).take(start), out)?;
+        out.write_all(data)?;
+        src.seek(SeekFrom::Start(start + old_len))?;
+        io::copy(&mut src, out)?;
+        Ok(())
+    })
+}
+
+/// 由 `write` 写出同目录临时文件，成功后替换原文件；失败时删除临时文件。
+pub(crate) fn rewrite_via_temp(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> Result<(), String> {
+    let tmp = path.with_extension("chordial-tmp");
+    let result = File::create(&tmp).and_then(|mut out| {
+        write(&mut out)?;
+        out.sync_all()
+    });
+    if let Err(e) = result.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("重写文件失败: {}", e));
+    }
+    Ok(())
+}
+
+// ── Vorbis comment ────────────────────────────────
+
+/// Vorbis comment 结构（FLAC 的 `VORBIS_COMMENT` 块、Ogg 的注释头包共用）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VorbisComment {
+    pub vendor: Vec<u8>,
+    /// `KEY=value` 原文
+    pub comments: Vec<Vec<u8>>,
+}
+
+impl VorbisComment {
+    pub fn new() -> Self {
+        Self {
+            vendor: b"Chordial".to_vec(),
+            comments: Vec::new(),
+        }
+    }
+
+    /// 解析注释，返回注释本身与其占用的字节数（之后可能还有帧标志位等数据）。
+    pub fn parse(block: &[u8]) -> Result<(Self, usize), String> {
+        let invalid = || "Vorbis comment 格式错误".to_string();
+        let read_u32 = |pos: usize| -> Option<usize> {
+            block
+                .get(pos..pos + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        };
+        let vendor_len = read_u32(0).ok_or_else(invalid)?;
+        let vendor = block.get(4..4 + vendor_len).ok_or_else(invalid)?.to_vec();
+        let mut pos = 4 + vendor_len;
+        let count = read_u32(pos).ok_or_else(invalid)?;
+        pos += 4;
+
+        let mut comments = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            let len = read_u32(pos).ok_or_else(invalid)?;
+            comments.push(block.get(pos + 4..pos + 4 + len).ok_or_else(invalid)?.to_vec());
+            pos += 4 + len;
+        }
+        Ok((Self { vendor, comments }, pos))
+    }
+
+    /// 删除键名（不区分大小写）满足条件的注释。
+    pub fn remove_where(&mut self, mut matches: impl FnMut(&[u8]) -> bool) {
+        self.comments.retain(|entry| {
+            let key = entry.split(|&b| b == b'=').next().unwrap_or_default();
+            !matches(key)
+        });
+    }
+
+    pub fn push(&mut self, key: &str, value: &str) {
+        self.comments.push(format!("{}={}", key, value).into_bytes());
+    }
+
+    /// 替换标题等字段：删除同名（及别名）注释后追加新值。
+    pub fn apply_fields(&mut self, fields: &FieldValues) {
+        for (field, value) in fields {
+            let keys = vorbis_keys(*field);
+            self.remove_where(|key| keys.iter().any(|k| key.eq_ignore_ascii_case(k.as_bytes())));
+            if let Some(text) = field_text(value) {
+                self.push(keys[0], text);
+            }
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(self.vendor.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.vendor);
+        out.extend_from_slice(&(self.comments.len() as u32).to_le_bytes());
+        for comment in &self.comments {
+            out.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            out.extend_from_slice(comment);
+        }
+        out
+    }
+}
+
+/// 字段对应的注释键名，第一个为写入时使用的键，其余为写入时一并删除的别名。
+fn vorbis_keys(field: MetadataField) -> &'static [&'static str] {
+    match field {
+        MetadataField::Title => &["TITLE"],
+        MetadataField::Artist => &["ARTIST"],
+        MetadataField::Album => &["ALBUM"],
+        MetadataField::Year => &["DATE", "YEAR"],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::analysis::decode;
+    use crate::module::fixtures::{self, FixtureFormat, FixtureSpec};
+    use crate::module::music_localSource::scanner;
+
+    #[test]
+    fn test_write_fields_round_trip() {
+        let dir = std::env::temp_dir().join(format!("chordial-tag-writer-{}", std::process::id()));
+        let spec = FixtureSpec::default();
+        let changes = FieldValues::from([
+            (MetadataField::Title, Some("新标题 — Ünïcode".to_string())),
+            (MetadataField::Artist, Some("Another Artist".to_string())),
+            (MetadataField::Album, None),
+            (MetadataField::Year, Some("1999".to_string())),
+        ]);
+
+        for format in [FixtureFormat::Mp3, FixtureFormat::Flac, FixtureFormat::Ogg, FixtureFormat::M4a] {
+            let path = fixtures::write(&dir, "tagged", format, &spec).unwrap();
+            let before = scanner::probe_file(&path).unwrap();
+            write_fields(&path, &changes).unwrap();
+
+            let meta = scanner::probe_file(&path).unwrap();
+            let ext = format.extension();
+            assert_eq!(meta.title.as_deref(), Some("新标题 — Ünïcode"), "{}", ext);
+            assert_eq!(meta.artist.as_deref(), Some("Another Artist"), "{}", ext);
+            assert_eq!(meta.album, None, "{}", ext);
+            assert_eq!(meta.year, Some(1999), "{}", ext);
+            // 音频与封面不受影响
+            assert_eq!(meta.duration_secs, before.duration_secs, "{}", ext);
+            assert_eq!(meta.pictures.len(), before.pictures.len(), "{}", ext);
+            let mut frames = 0;
+            decode::decode_file(&path, |block| frames += block.samples.len() / block.channels).unwrap();
+            assert!(frames as u64 >= spec.frames(), "{}", ext);
+
+            // 第二次写入放得进填充区（OGG 总是重写文件）
+            let len = std::fs::metadata(&path).unwrap().len();
+            let retitle = FieldValues::from([(MetadataField::Title, Some("Short".to_string()))]);
+            write_fields(&path, &retitle).unwrap();
+            assert_eq!(scanner::probe_file(&path).unwrap().title.as_deref(), Some("Short"), "{}", ext);
+            if format != FixtureFormat::Ogg {
+                assert_eq!(std::fs::metadata(&path).unwrap().len(), len, "{}", ext);
+            }
+        }
+
+        let wav = fixtures::write(&dir, "tagged", FixtureFormat::Wav, &spec).unwrap();
+        assert!(write_fields(&wav, &changes).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
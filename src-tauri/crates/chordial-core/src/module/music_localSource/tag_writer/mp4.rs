@@ -0,0 +1,306 @@
+//! M4A 标签写回：改写 `moov/udta/meta/ilst` 中的 iTunes 标签。
+//!
+//! | 字段 | atom |
+//! |------|------|
+//! | 标题 | `©nam` |
+//! | 艺人 | `©ART` |
+//! | 专辑 | `©alb` |
+//! | 年份 | `©day` |
+//!
+//! 缺少的 `udta` / `meta` / `ilst` 会补上。`moov` 长度变化时优先用紧随其后的 `free`
+//! 吸收，原地覆盖；放不下则重写文件，并在 `moov` 后留出填充。`moov` 位于 `mdat`
+//! 之前（快速启动布局）时，重写后音频数据整体后移，各轨道的 `stco` / `co64` 块偏移同步平移。
+
+use super::{field_text, io_err, replace_range, MetadataWriter, PADDING_BYTES};
+use crate::module::music_library::edits::{FieldValues, MetadataField};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// `moov` 的读取上限，超出视为文件损坏。
+const MAX_MOOV_BYTES: u64 = 64 << 20;
+/// `ilst` 数据类型：UTF-8 文本
+const DATA_UTF8: u32 = 1;
+
+pub struct Mp4TagWriter;
+
+impl MetadataWriter for Mp4TagWriter {
+    fn name(&self) -> &'static str {
+        "MP4"
+    }
+
+    fn accepts(&self, head: &[u8], _extension: &str) -> bool {
+        head.get(4..8) == Some(b"ftyp")
+    }
+
+    fn write(&self, path: &Path, fields: &FieldValues) -> Result<(), String> {
+        let mut file = File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+        let atoms = top_level_atoms(&mut file)?;
+        let index = atoms
+            .iter()
+            .position(|a| &a.name == b"moov")
+            .ok_or("找不到 moov")?;
+        let moov = &atoms[index];
+        if moov.header_len != 8 || moov.len > MAX_MOOV_BYTES {
+            return Err("moov 过大或格式错误".to_string());
+        }
+        let mut data = vec![0u8; moov.len as usize];
+        file.seek(SeekFrom::Start(moov.start)).map_err(io_err)?;
+        file.read_exact(&mut data).map_err(io_err)?;
+
+        let mut body = data.split_off(8);
+        edit_child(&mut body, 0, b"udta", Vec::new, |udta| {
+            edit_child(udta, 0, b"meta", new_meta, |meta| {
+                // ISO 的 meta 是 full box；QuickTime 写法没有版本 / 标志，hdlr 紧跟在头后
+                let skip = if meta.get(4..8) == Some(b"hdlr") { 0 } else { 4 };
+                edit_child(meta, skip, b"ilst", Vec::new, |ilst| apply_fields(ilst, fields))
+            })
+        })?;
+        let new_moov = atom(b"moov", &body);
+
+        // 紧随其后的 free 能吸收长度变化时原地覆盖，音频数据不动
+        let free_len = atoms
+            .get(index + 1)
+            .filter(|a| &a.name == b"free" || &a.name == b"skip")
+            .map_or(0, |a| a.len);
+        let old_len = moov.len + free_len;
+        let room = old_len as i64 - new_moov.len() as i64;
+        if room == 0 || room >= 8 {
+            let mut region = new_moov;
+            if room > 0 {
+                region.extend(free_atom(room as usize));
+            }
+            return replace_range(path, file, moov.start, old_len, &region);
+        }
+
+        let mut region = new_moov;
+        region.extend(free_atom(PADDING_BYTES));
+        let shift = region.len() as i64 - old_len as i64;
+        if atoms[index + 1..].iter().any(|a| &a.name == b"mdat") {
+            shift_chunk_offsets(&mut region[8..], moov.start, shift)?;
+        }
+        replace_range(path, file, moov.start, old_len, &region)
+    }
+}
+
+struct TopAtom {
+    name: [u8; 4],
+    start: u64,
+    len: u64,
+    header_len: u64,
+}
+
+fn top_level_atoms(file: &mut File) -> Result<Vec<TopAtom>, String> {
+    let file_len = file.metadata().map_err(io_err)?.len();
+    let mut atoms = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= file_len {
+        let mut header = [0u8; 8];
+        file.seek(SeekFrom::Start(pos)).map_err(io_err)?;
+        file.read_exact(&mut header).map_err(io_err)?;
+        let (len, header_len) = match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+            0 => (file_len - pos, 8),
+            1 => {
+                let mut large = [0u8; 8];
+                file.read_exact(&mut large).map_err(io_err)?;
+                (u64::from_be_bytes(large), 16)
+            }
+            n => (n as u64, 8),
+        };
+        if len < header_len {
+            return Err("atom 长度错误".to_string());
+        }
+        atoms.push(TopAtom {
+            name: [header[4], header[5], header[6], header[7]],
+            start: pos,
+            len,
+            header_len,
+        });
+        pos += len;
+    }
+    Ok(atoms)
+}
+
+/// 修改 `container[skip..]` 中第一个名为 `name` 的子 atom；没有时以 `init()` 为内容新建并追加。
+fn edit_child(
+    container: &mut Vec<u8>,
+    skip: usize,
+    name: &[u8; 4],
+    init: impl FnOnce() -> Vec<u8>,
+    edit: impl FnOnce(&mut Vec<u8>) -> Result<(), String>,
+) -> Result<(), String> {
+    let found = children(container, skip)?
+        .into_iter()
+        .find(|(_, _, child)| child == name);
+    let (range, mut body) = match found {
+        Some((pos, size, _)) => (pos..pos + size, container[pos + 8..pos + size].to_vec()),
+        None => (container.len()..container.len(), init()),
+    };
+    edit(&mut body)?;
+    container.splice(range, atom(name, &body));
+    Ok(())
+}
+
+/// 列出子 atom：`(偏移, 长度, 名称)`。
+fn children(container: &[u8], skip: usize) -> Result<Vec<(usize, usize, [u8; 4])>, String> {
+    let mut out = Vec::new();
+    let mut pos = skip;
+    while pos + 8 <= container.len() {
+        let size = u32::from_be_bytes([
+            container[pos],
+            container[pos + 1],
+            container[pos + 2],
+            container[pos + 3],
+        ]) as usize;
+        if size < 8 || pos + size > container.len() {
+            return Err("atom 长度错误".to_string());
+        }
+        let name = [container[pos + 4], container[pos + 5], container[pos + 6], container[pos + 7]];
+        out.push((pos, size, name));
+        pos += size;
+    }
+    Ok(out)
+}
+
+fn apply_fields(ilst: &mut Vec<u8>, fields: &FieldValues) -> Result<(), String> {
+    for (field, value) in fields {
+        let name = match field {
+            MetadataField::Title => b"\xa9nam",
+            MetadataField::Artist => b"\xa9ART",
+            MetadataField::Album => b"\xa9alb",
+            MetadataField::Year => b"\xa9day",
+        };
+        for (pos, size, _) in children(ilst, 0)?.into_iter().rev().filter(|(_, _, n)| n == name) {
+            ilst.drain(pos..pos + size);
+        }
+        if let Some(text) = field_text(value) {
+            let mut data = DATA_UTF8.to_be_bytes().to_vec(); // 版本 0 + 类型
+            data.extend_from_slice(&[0; 4]); // 语言 / 国家：默认
+            data.extend_from_slice(text.as_bytes());
+            ilst.extend(atom(name, &atom(b"data", &data)));
+        }
+    }
+    Ok(())
+}
+
+/// 新建的 `meta`：版本 / 标志 + `hdlr`（mdir）。
+fn new_meta() -> Vec<u8> {
+    let mut hdlr = vec![0; 8]; // 版本 / 标志 + pre_defined
+    hdlr.extend_from_slice(b"mdir");
+    hdlr.extend_from_slice(b"appl");
+    hdlr.extend_from_slice(&[0; 9]); // 保留字段 + 空名称
+    let mut meta = vec![0; 4];
+    meta.extend(atom(b"hdlr", &hdlr));
+    meta
+}
+
+/// 平移 `moov` 内各轨道中不小于 `from` 的块偏移。
+fn shift_chunk_offsets(container: &mut [u8], from: u64, shift: i64) -> Result<(), String> {
+    for (pos, size, name) in children(container, 0)? {
+        let body = &mut container[pos + 8..pos + size];
+        match &name {
+            b"trak" | b"mdia" | b"minf" | b"stbl" => shift_chunk_offsets(body, from, shift)?,
+            b"stco" | b"co64" => {
+                let width = if &name == b"stco" { 4 } else { 8 };
+                let count = body
+                    .get(4..8)
+                    .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+                    .ok_or("块偏移表格式错误")?;
+                let entries = body
+                    .get_mut(8..8 + count * width)
+                    .ok_or("块偏移表格式错误")?;
+                for entry in entries.chunks_exact_mut(width) {
+                    let offset = entry.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+                    if offset < from {
+                        continue;
+                    }
+                    let shifted = offset as i64 + shift;
+                    if width == 4 {
+                        let shifted = u32::try_from(shifted).map_err(|_| "块偏移超出 stco 范围")?;
+                        entry.copy_from_slice(&shifted.to_be_bytes());
+                    } else {
+                        entry.copy_from_slice(&(shifted as u64).to_be_bytes());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn free_atom(len: usize) -> Vec<u8> {
+    atom(b"free", &vec![0; len - 8])
+}
+
+fn atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 8);
+    out.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(body);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::analysis::decode;
+    use crate::module::fixtures::{self, FixtureFormat, FixtureSpec};
+    use crate::module::music_localSource::scanner;
+
+    /// 读出唯一一条轨道 `stco` 的第一个块偏移。
+    fn chunk_offset(file: &[u8]) -> u64 {
+        let pos = file.windows(4).position(|w| w == b"stco").unwrap();
+        u32::from_be_bytes(file[pos + 12..pos + 16].try_into().unwrap()) as u64
+    }
+
+    fn layout(path: &Path) -> Vec<([u8; 4], u64)> {
+        let mut file = File::open(path).unwrap();
+        top_level_atoms(&mut file).unwrap().into_iter().map(|a| (a.name, a.start)).collect()
+    }
+
+    #[test]
+    fn test_grow_moov_before_mdat_shifts_chunk_offsets() {
+        let dir = std::env::temp_dir().join(format!("chordial-mp4-writer-{}", std::process::id()));
+        let spec = FixtureSpec::default();
+        let path = fixtures::write(&dir, "faststart", FixtureFormat::M4a, &spec).unwrap();
+
+        // 夹具是 ftyp + mdat + moov，改成快速启动布局 ftyp + moov + mdat，后面没有 free
+        let bytes = std::fs::read(&path).unwrap();
+        let atoms = layout(&path);
+        assert_eq!(atoms.iter().map(|a| &a.0).collect::<Vec<_>>(), [b"ftyp", b"mdat", b"moov"]);
+        let (ftyp, mdat, mut moov) = (
+            bytes[..atoms[1].1 as usize].to_vec(),
+            bytes[atoms[1].1 as usize..atoms[2].1 as usize].to_vec(),
+            bytes[atoms[2].1 as usize..].to_vec(),
+        );
+        let offset = (ftyp.len() + moov.len() + 8) as u32;
+        let stco = moov.windows(4).position(|w| w == b"stco").unwrap();
+        moov[stco + 12..stco + 16].copy_from_slice(&offset.to_be_bytes());
+        std::fs::write(&path, [ftyp, moov, mdat].concat()).unwrap();
+        let before = scanner::probe_file(&path).unwrap();
+        let old_mdat = layout(&path)[2].1;
+
+        let title = "很长的标题".repeat(200);
+        let fields = FieldValues::from([(MetadataField::Title, Some(title.clone()))]);
+        Mp4TagWriter.write(&path, &fields).unwrap();
+
+        let atoms = layout(&path);
+        assert_eq!(atoms.iter().map(|a| &a.0).collect::<Vec<_>>(), [b"ftyp", b"moov", b"free", b"mdat"]);
+        let growth = atoms[3].1 - old_mdat;
+        assert!(growth as usize > title.len());
+        let offset = chunk_offset(&std::fs::read(&path).unwrap());
+        assert_eq!(offset, old_mdat + 8 + growth);
+        assert_eq!(offset, atoms[3].1 + 8);
+
+        // 标题写入，音频数据仍能完整解码
+        let meta = scanner::probe_file(&path).unwrap();
+        assert_eq!(meta.title.as_deref(), Some(title.as_str()));
+        assert_eq!(meta.duration_secs, before.duration_secs);
+        let mut frames = 0;
+        decode::decode_file(&path, |block| frames += block.samples.len() / block.channels).unwrap();
+        assert!(frames as u64 >= spec.frames());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
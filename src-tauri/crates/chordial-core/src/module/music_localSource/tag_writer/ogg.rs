@@ -0,0 +1,286 @@
+//! OGG 标签写回：替换 Vorbis / Opus / Ogg FLAC 的注释头包。
+//!
+//! | 编码 | 识别头包 | 注释头包 |
+//! |------|----------|----------|
+//! | Vorbis | `\x01vorbis` | 第 2 个包：`\x03vorbis` + 注释 + 帧标志位 |
+//! | Opus | `OpusHead` | 第 2 个包：`OpusTags` + 注释 |
+//! | FLAC | `\x7fFLAC`（含其后的头包数） | 类型为 4 的元数据块 |
+//!
+//! 头包总在页边界结束，注释变长后只需重新分页头包；之后的音频页原样复制，
+//! 页序号整体平移并重算校验。只处理单个逻辑流：链式或复用的文件（见
+//! [`ogg_chain`](super::super::ogg_chain)）出现其他流的页时放弃写入。
+
+use super::{io_err, rewrite_via_temp, MetadataWriter, VorbisComment};
+use crate::module::music_library::edits::FieldValues;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_BOS: u8 = 0x02;
+/// 没有包在本页结束时的 granule
+const GRANULE_NONE: u64 = u64::MAX;
+
+pub struct OggTagWriter;
+
+impl MetadataWriter for OggTagWriter {
+    fn name(&self) -> &'static str {
+        "OGG"
+    }
+
+    fn accepts(&self, head: &[u8], _extension: &str) -> bool {
+        head.starts_with(b"OggS")
+    }
+
+    fn write(&self, path: &Path, fields: &FieldValues) -> Result<(), String> {
+        let file = File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+        let mut reader = BufReader::new(file);
+        let (serial, header_pages, mut packets) = read_headers(&mut reader)?;
+        update_comment(&mut packets, |comment| comment.apply_fields(fields))?;
+
+        let pages = paginate(serial, &packets);
+        // 新旧头页数不同，之后每页的序号都要平移
+        let shift = pages.len() as i64 - header_pages as i64;
+        rewrite_via_temp(path, move |out| {
+            let mut out = BufWriter::new(out);
+            for page in &pages {
+                out.write_all(&page.to_bytes())?;
+            }
+            while let Some(mut page) = Page::read(&mut reader)? {
+                if page.serial != serial {
+                    return Err(io::Error::other("暂不支持链式或多路复用的 OGG 文件"));
+                }
+                page.sequence = (page.sequence as i64 + shift) as u32;
+                out.write_all(&page.to_bytes())?;
+            }
+            out.flush()
+        })
+    }
+}
+
+/// 一个 Ogg 页。
+struct Page {
+    flags: u8,
+    granule: u64,
+    serial: u32,
+    sequence: u32,
+    lacing: Vec<u8>,
+    body: Vec<u8>,
+}
+
+impl Page {
+    /// 读取下一页；文件结束时返回 `None`。
+    fn read<R: Read>(r: &mut R) -> io::Result<Option<Self>> {
+        let mut header = [0u8; 27];
+        match r.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        if &header[..4] != b"OggS" {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "OGG 页头格式错误"));
+        }
+        let mut lacing = vec![0u8; header[26] as usize];
+        r.read_exact(&mut lacing)?;
+        let mut body = vec![0u8; lacing.iter().map(|&l| l as usize).sum()];
+        r.read_exact(&mut body)?;
+        let u32_at = |pos: usize| u32::from_le_bytes([header[pos], header[pos + 1], header[pos + 2], header[pos + 3]]);
+        Ok(Some(Self {
+            flags: header[5],
+            granule: u64::from_le_bytes(header[6..14].try_into().unwrap_or_default()),
+            serial: u32_at(14),
+            sequence: u32_at(18),
+            lacing,
+            body,
+        }))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(27 + self.lacing.len() + self.body.len());
+        out.extend_from_slice(b"OggS");
+        out.push(0);
+        out.push(self.flags);
+        out.extend_from_slice(&self.granule.to_le_bytes());
+        out.extend_from_slice(&self.serial.to_le_bytes());
+        out.extend_from_slice(&self.sequence.to_le_bytes());
+        out.extend_from_slice(&[0; 4]); // CRC，最后回填
+        out.push(self.lacing.len() as u8);
+        out.extend_from_slice(&self.lacing);
+        out.extend_from_slice(&self.body);
+        let crc = crc32(&out);
+        out[22..26].copy_from_slice(&crc.to_le_bytes());
+        out
+    }
+}
+
+/// 读出全部头包，返回 `(流序列号, 头包占用的页数, 头包)`。
+fn read_headers<R: Read>(r: &mut R) -> Result<(u32, usize, Vec<Vec<u8>>), String> {
+    let mut packets: Vec<Vec<u8>> = Vec::new();
+    let mut current = Vec::new();
+    let mut serial = None;
+    let mut pages = 0;
+    let mut needed = 1;
+    while packets.len() < needed {
+        let page = Page::read(r)
+            .map_err(io_err)?
+            .ok_or("OGG 文件在头包结束前截断")?;
+        if *serial.get_or_insert(page.serial) != page.serial {
+            return Err("暂不支持链式或多路复用的 OGG 文件".to_string());
+        }
+        pages += 1;
+        let mut pos = 0;
+        for &len in &page.lacing {
+            current.extend_from_slice(&page.body[pos..pos + len as usize]);
+            pos += len as usize;
+            if len < 255 {
+                packets.push(std::mem::take(&mut current));
+                if packets.len() == 1 {
+                    needed = header_count(&packets[0])?;
+                }
+            }
+        }
+        if packets.len() > needed || (packets.len() == needed && !current.is_empty()) {
+            return Err("OGG 头包与音频包位于同一页，无法安全改写".to_string());
+        }
+    }
+    Ok((serial.unwrap_or_default(), pages, packets))
+}
+
+/// 按识别头包判断编码，返回头包总数（含识别头包）。
+fn header_count(ident: &[u8]) -> Result<usize, String> {
+    if ident.starts_with(b"\x01vorbis") {
+        Ok(3)
+    } else if ident.starts_with(b"OpusHead") {
+        Ok(2)
+    } else if ident.starts_with(b"\x7fFLAC") {
+        match ident.get(7..9).map(|b| u16::from_be_bytes([b[0], b[1]])) {
+            Some(0) | None => Err("Ogg FLAC 未声明头包数，无法改写".to_string()),
+            Some(count) => Ok(1 + count as usize),
+        }
+    } else {
+        Err("暂不支持该 OGG 编码".to_string())
+    }
+}
+
+/// 找到注释头包交给 `update` 修改；Ogg FLAC 没有注释块时新建一个。
+fn update_comment(
+    packets: &mut Vec<Vec<u8>>,
+    update: impl FnOnce(&mut VorbisComment),
+) -> Result<(), String> {
+    let prefix: &[u8] = if packets[0].starts_with(b"\x01vorbis") {
+        b"\x03vorbis"
+    } else if packets[0].starts_with(b"OpusHead") {
+        b"OpusTags"
+    } else {
+        return update_flac_comment(packets, update);
+    };
+    let packet = &packets[1];
+    if !packet.starts_with(prefix) {
+        return Err("OGG 注释头包格式错误".to_string());
+    }
+    let (mut comment, len) = VorbisComment::parse(&packet[prefix.len()..])?;
+    // Vorbis 的帧标志位、Opus 注释后的二进制数据原样保留
+    let trailing = packet[prefix.len() + len..].to_vec();
+    update(&mut comment);
+    let mut rebuilt = prefix.to_vec();
+    rebuilt.extend(comment.to_bytes());
+    rebuilt.extend(trailing);
+    packets[1] = rebuilt;
+    Ok(())
+}
+
+fn update_flac_comment(
+    packets: &mut Vec<Vec<u8>>,
+    update: impl FnOnce(&mut VorbisComment),
+) -> Result<(), String> {
+    let existing = packets
+        .iter()
+        .skip(1)
+        .position(|p| p.first().is_some_and(|b| b & 0x7f == 4))
+        .map(|i| i + 1);
+    let (index, flags, mut comment) = match existing {
+        Some(i) => {
+            let packet = &packets[i];
+            let body = packet.get(4..).ok_or("Ogg FLAC 元数据块格式错误")?;
+            (i, packet[0] & 0x80, VorbisComment::parse(body)?.0)
+        }
+        None => {
+            // 识别头包中的头包数加一，新块放在识别头包之后
+            let count = u16::from_be_bytes([packets[0][7], packets[0][8]]) + 1;
+            packets[0][7..9].copy_from_slice(&count.to_be_bytes());
+            packets.insert(1, Vec::new());
+            let last = if packets.len() == 2 { 0x80 } else { 0 };
+            (1, last, VorbisComment::new())
+        }
+    };
+    update(&mut comment);
+    let body = comment.to_bytes();
+    if body.len() >= 1 << 24 {
+        return Err("FLAC 元数据块过大".to_string());
+    }
+    let mut packet = vec![4 | flags];
+    packet.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    packet.extend(body);
+    packets[index] = packet;
+    Ok(())
+}
+
+/// 把头包重新分页：识别头包单独一页（BOS），其余头包连续排布，每页最多 255 个段。
+fn paginate(serial: u32, packets: &[Vec<u8>]) -> Vec<Page> {
+    let mut pages = Vec::new();
+    let mut page = Page {
+        flags: FLAG_BOS,
+        granule: GRANULE_NONE,
+        serial,
+        sequence: 0,
+        lacing: Vec::new(),
+        body: Vec::new(),
+    };
+    for (n, packet) in packets.iter().enumerate() {
+        let mut segments: Vec<&[u8]> = packet.chunks(255).collect();
+        if packet.len().is_multiple_of(255) {
+            segments.push(&[]);
+        }
+        let last = segments.len() - 1;
+        for (i, segment) in segments.into_iter().enumerate() {
+            if page.lacing.len() == 255 || (n == 1 && i == 0) {
+                let sequence = page.sequence + 1;
+                let flags = if i > 0 { FLAG_CONTINUED } else { 0 };
+                pages.push(std::mem::replace(
+                    &mut page,
+                    Page {
+                        flags,
+                        granule: GRANULE_NONE,
+                        serial,
+                        sequence,
+                        lacing: Vec::new(),
+                        body: Vec::new(),
+                    },
+                ));
+            }
+            page.lacing.push(segment.len() as u8);
+            page.body.extend_from_slice(segment);
+            if i == last {
+                page.granule = 0;
+            }
+        }
+    }
+    pages.push(page);
+    pages
+}
+
+/// Ogg 页校验：多项式 0x04C11DB7，初值 0，不反转。
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for &byte in data {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
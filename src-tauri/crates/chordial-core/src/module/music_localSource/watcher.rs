@@ -7,18 +7,30 @@
 //! # 设计要点
 //!
 //! - **单 watcher 多目录**：使用一个 notify watcher 监听所有文件夹，避免重复扫描。
-//! - **事件去重**：使用简单的延时去重（同一文件 500ms 内的重复事件合并）。
-//! - **穿透同步**：文件变化 → watcher 事件 → LocalMusicSource → MusicLibrary。
+//! - **跟随文件夹列表**：每轮循环与 [`FolderManager`] 的当前列表对齐，运行中添加的文件夹开始监听，
+//!   移除的文件夹停止监听，无需重启监听线程。
+//! - **事件去重**：使用简单的延时去重（同一文件 500ms 内的重复事件合并）；没有新事件时
+//!   等待超时后照样处理，最后一批变化不必等下一个事件。
+//! - **跳过未改动的文件**：修改事件先比较文件指纹（见 [`fingerprint`](super::fingerprint)），
+//!   只碰了 mtime 的文件不重新探测。
+//! - **穿透同步**：文件变化 → watcher 事件 → LocalMusicSource → MusicLibrary，
+//!   每批处理完发布 [`AppEvent::LibraryDiff`]，前端只刷新受影响的条目。
 
-use notify::event::{CreateKind, ModifyKind, RemoveKind};
-use notify::{Event, EventKind, RecursiveMode, Watcher};
-use std::collections::HashMap;
+use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use super::folder::FolderManager;
 use super::source::LocalMusicSource;
+use crate::module::events::AppEvent;
+use crate::module::platform;
+
+/// 同一文件的事件合并窗口，同时也是无事件时检查文件夹列表的间隔。
+const DEDUP_WINDOW: Duration = Duration::from_millis(500);
 
 /// 文件事件去重记录。
 struct PendingEvent {
@@ -38,102 +50,173 @@ enum SimpleEventKind {
 /// 启动文件系统监听器（阻塞式，应在独立线程中运行）。
 ///
 /// # 参数
-/// - `source`: 本地音乐来源的共享引用
-/// - `folders`: 需要监听的文件夹路径列表
+/// - `source`: 本地音乐来源的共享引用；监听的文件夹取自其 `folder_manager`
 ///
 /// # 行为
 /// 此函数会阻塞当前线程，持续监听文件夹变化并同步到音乐库。
 /// 建议在 `std::thread::spawn` 中调用。
 ///
 /// 对于每个文件事件：
-/// - **Create** → `source.index_file(path)`
-/// - **Modify** → `source.reindex_file(path)`
-/// - **Remove** → `source.unindex_file(path)`
+/// - **Create** / 改名后的新路径 → `source.index_file(path)`
+/// - **Modify** → 指纹变化时 `source.reindex_file(path)`
+/// - **Remove** / 改名前的旧路径 → `source.unindex_file(path)`
 ///
 /// # 事件去重
 /// 同一文件在 500ms 内的重复事件（如编辑器保存触发的 Remove+Create）会被合并，
 /// 只执行最终状态对应的操作。
-pub fn start_watcher(source: Arc<LocalMusicSource>, folders: Vec<PathBuf>) -> Result<(), String> {
+pub fn start_watcher(source: Arc<LocalMusicSource>) -> Result<(), String> {
     let (tx, rx) = mpsc::channel::<Result<Event, notify::Error>>();
 
     let mut watcher = notify::recommended_watcher(tx)
         .map_err(|e| format!("创建文件监听器失败: {}", e))?;
 
-    // 监听所有文件夹（递归）
-    for folder in &folders {
-        if folder.exists() {
-            watcher
-                .watch(folder, RecursiveMode::Recursive)
-                .map_err(|e| format!("监听文件夹失败 '{}': {}", folder.display(), e))?;
-        }
-    }
+    // 当前已监听的文件夹（递归）
+    let mut watched: HashSet<PathBuf> = HashSet::new();
+    sync_watched_folders(&mut watcher, &source.folder_manager, &mut watched);
 
     // 事件去重缓冲：file_path → PendingEvent
     let mut pending: HashMap<PathBuf, PendingEvent> = HashMap::new();
-    let dedup_window = Duration::from_millis(500);
 
     // 事件处理循环
     loop {
-        // 收集本批次事件（非阻塞获取，至少等一个）
-        match rx.recv() {
+        // 至少等一个事件；超时后照常处理已就绪的缓冲并检查文件夹列表
+        match rx.recv_timeout(DEDUP_WINDOW) {
             Ok(Ok(event)) => {
-                handle_raw_event(&event, &mut pending);
+                handle_raw_event(&event, &source.folder_manager, &mut pending);
             }
             Ok(Err(e)) => {
                 eprintln!("[local_watcher] 监听错误: {}", e);
             }
-            Err(mpsc::RecvError) => {
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
                 // 通道关闭，退出
                 break;
             }
         }
 
+        // 非阻塞排空积压事件
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                Ok(ev) => handle_raw_event(&ev, &source.folder_manager, &mut pending),
+                Err(e) => eprintln!("[local_watcher] 监听错误: {}", e),
+            }
+        }
+
+        sync_watched_folders(&mut watcher, &source.folder_manager, &mut watched);
+
         // 处理所有就绪的已去重事件
         let now = Instant::now();
         let ready: Vec<(PathBuf, SimpleEventKind)> = pending
             .iter()
-            .filter(|(_, e)| now.duration_since(e.received_at) >= dedup_window)
+            .filter(|(_, e)| now.duration_since(e.received_at) >= DEDUP_WINDOW)
             .map(|(p, e)| (p.clone(), e.kind.clone()))
             .collect();
+        if ready.is_empty() {
+            continue;
+        }
 
+        let before = source.library.snapshot();
         for (path, kind) in ready {
             pending.remove(&path);
-
-            let result = match kind {
-                SimpleEventKind::Create => source.index_file(&path),
-                SimpleEventKind::Modify => source.reindex_file(&path),
-                SimpleEventKind::Remove => source.unindex_file(&path),
-            };
-
-            if let Err(e) = result {
+            if let Err(e) = apply_event(&source, &path, kind) {
                 eprintln!("[local_watcher] 同步失败 '{}': {}", path.display(), e);
             }
         }
+        if let Err(e) = source.library.save_if_dirty() {
+            eprintln!("[local_watcher] 保存音乐库失败: {}", e);
+        }
+        if let Err(e) = source.save_mtime_cache() {
+            eprintln!("[local_watcher] 保存文件指纹失败: {}", e);
+        }
+        let diff = source.library.diff_since(&before);
+        if !diff.is_empty() {
+            source.events.publish(AppEvent::LibraryDiff(diff));
+        }
+    }
 
-        // 非阻塞排空积压事件
-        while let Ok(event) = rx.try_recv() {
-            match event {
-                Ok(ev) => handle_raw_event(&ev, &mut pending),
-                Err(e) => eprintln!("[local_watcher] 监听错误: {}", e),
+    Ok(())
+}
+
+/// 执行一个去重后的文件事件，并同步文件指纹缓存。
+fn apply_event(source: &LocalMusicSource, path: &PathBuf, kind: SimpleEventKind) -> Result<(), String> {
+    let canonical = platform::canonicalize(path).unwrap_or_else(|_| path.clone());
+    let path_key = platform::path_to_string(&canonical);
+    let indexed = match kind {
+        SimpleEventKind::Create => source.index_file(&canonical)?,
+        SimpleEventKind::Modify => {
+            // 内容未变（只改了 mtime）的文件不重新探测
+            if source.file_index.read().contains_key(&canonical)
+                && source.check_file_unchanged(&canonical).is_some()
+            {
+                return Ok(());
             }
+            source.reindex_file(&canonical)?
+        }
+        SimpleEventKind::Remove => {
+            source.unindex_file(&canonical)?;
+            source.file_mtimes.write().remove(&path_key);
+            return Ok(());
+        }
+    };
+    let song_id = source.file_index.read().get(&canonical).cloned();
+    match song_id {
+        Some(song_id) if indexed => source.update_file_mtime(&canonical, &song_id),
+        _ => {
+            source.file_mtimes.write().remove(&path_key);
         }
     }
-
     Ok(())
 }
 
+/// 让 watcher 监听的目录与文件夹管理器的当前列表一致。
+///
+/// 新增的文件夹开始递归监听，已移除的文件夹停止监听；暂时无法监听的文件夹（如未挂载的移动硬盘）
+/// 不记入 `watched`，下一轮再试。
+fn sync_watched_folders(watcher: &mut RecommendedWatcher, folders: &FolderManager, watched: &mut HashSet<PathBuf>) {
+    let current: HashSet<PathBuf> = folders.get_folders().into_iter().collect();
+
+    watched.retain(|folder| {
+        if current.contains(folder) {
+            return true;
+        }
+        if let Err(e) = watcher.unwatch(folder) {
+            eprintln!("[local_watcher] 取消监听 '{}' 失败: {}", folder.display(), e);
+        }
+        false
+    });
+
+    for folder in current {
+        if watched.contains(&folder) || !folder.exists() {
+            continue;
+        }
+        match watcher.watch(&folder, RecursiveMode::Recursive) {
+            Ok(()) => {
+                watched.insert(folder);
+            }
+            Err(e) => eprintln!("[local_watcher] 监听文件夹失败 '{}': {}", folder.display(), e),
+        }
+    }
+}
+
 /// 处理原始 notify 事件，提取文件路径并加入去重缓冲。
-fn handle_raw_event(event: &Event, pending: &mut HashMap<PathBuf, PendingEvent>) {
-    let kind = match event.kind {
-        EventKind::Create(CreateKind::File) => SimpleEventKind::Create,
-        EventKind::Modify(ModifyKind::Data(_)) => SimpleEventKind::Modify,
-        EventKind::Remove(RemoveKind::File) => SimpleEventKind::Remove,
+fn handle_raw_event(event: &Event, folders: &FolderManager, pending: &mut HashMap<PathBuf, PendingEvent>) {
+    let kinds: Vec<(&PathBuf, SimpleEventKind)> = match event.kind {
+        EventKind::Create(CreateKind::File) => with_kind(&event.paths, SimpleEventKind::Create),
+        EventKind::Modify(ModifyKind::Data(_)) => with_kind(&event.paths, SimpleEventKind::Modify),
+        EventKind::Remove(RemoveKind::File) => with_kind(&event.paths, SimpleEventKind::Remove),
+        // 改名 / 移动：旧路径按删除处理，新路径按新增处理
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => with_kind(&event.paths, SimpleEventKind::Remove),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => with_kind(&event.paths, SimpleEventKind::Create),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => vec![
+            (&event.paths[0], SimpleEventKind::Remove),
+            (&event.paths[1], SimpleEventKind::Create),
+        ],
         _ => return, // 忽略文件夹事件、访问事件、元数据事件等
     };
 
-    for path in &event.paths {
+    for (path, kind) in kinds {
         // 忽略非音频文件
-        if !super::scanner::is_supported_audio(path) {
+        if !folders.is_supported_file(path) {
             continue;
         }
 
@@ -146,7 +229,97 @@ fn handle_raw_event(event: &Event, pending: &mut HashMap<PathBuf, PendingEvent>)
             })
             .or_insert(PendingEvent {
                 received_at: now,
-                kind: kind.clone(),
+                kind,
             });
     }
 }
+
+fn with_kind(paths: &[PathBuf], kind: SimpleEventKind) -> Vec<(&PathBuf, SimpleEventKind)> {
+    paths.iter().map(|path| (path, kind.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::config::store::ConfigStore;
+    use crate::module::events::EventBus;
+    use crate::module::fixtures::{self, FixtureFormat, FixtureSpec};
+    use crate::module::music_library::library::MusicLibrary;
+    use crate::module::music_localSource::quarantine::Quarantine;
+    use crate::module::music_source::manager::SourceManager;
+    use crate::module::power::PowerMonitor;
+    use crate::module::storage::persistent::PersistentStore;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("chordial_watcher_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("music")).unwrap();
+        dir
+    }
+
+    fn local_source(dir: &std::path::Path) -> LocalMusicSource {
+        let store = |name: &str| PersistentStore::new(dir.join(name));
+        LocalMusicSource::new(
+            Arc::new(FolderManager::new(store("folders.json"))),
+            Arc::new(MusicLibrary::new(dir.join("library.json"))),
+            store("mtimes.json"),
+            Quarantine::new(store("quarantine.json")),
+            Arc::new(EventBus::new()),
+            Arc::new(PowerMonitor::new(Arc::new(ConfigStore::new(dir.join("config.json"))))),
+            Arc::new(SourceManager::new(dir.join("sources.json"))),
+        )
+    }
+
+    #[test]
+    fn test_apply_event_create_rename_and_touch() {
+        let dir = temp_dir("apply");
+        let source = local_source(&dir);
+        let spec = FixtureSpec::default();
+        let path = fixtures::write(&dir.join("music"), "song", FixtureFormat::Flac, &spec).unwrap();
+        let path = platform::canonicalize(&path).unwrap();
+
+        // 新建：入库并记下指纹
+        apply_event(&source, &path, SimpleEventKind::Create).unwrap();
+        let song_id = source.find_song_id_by_path(&path).unwrap();
+        assert!(source.file_mtimes.read().contains_key(&platform::path_to_string(&path)));
+
+        // 只改 mtime：内容未变，不重新探测（歌曲 ID 不变）
+        let later = std::time::SystemTime::now() + Duration::from_secs(3600);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        apply_event(&source, &path, SimpleEventKind::Modify).unwrap();
+        assert_eq!(source.find_song_id_by_path(&path).as_deref(), Some(song_id.as_str()));
+
+        // 改名：旧路径按删除、新路径按新增处理
+        let renamed = path.with_file_name("renamed.flac");
+        std::fs::rename(&path, &renamed).unwrap();
+        apply_event(&source, &path, SimpleEventKind::Remove).unwrap();
+        apply_event(&source, &renamed, SimpleEventKind::Create).unwrap();
+        assert!(source.find_song_id_by_path(&path).is_none());
+        assert!(!source.file_mtimes.read().contains_key(&platform::path_to_string(&path)));
+        let renamed_id = source.find_song_id_by_path(&renamed).unwrap();
+        assert_eq!(source.library.get_song(&renamed_id).unwrap().title, spec.title);
+        assert_eq!(source.library.song_count(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_sync_watched_folders_follows_folder_manager() {
+        let dir = temp_dir("sync");
+        let folders = FolderManager::new(PersistentStore::new(dir.join("folders.json")));
+        let music = platform::canonicalize(&dir.join("music")).unwrap();
+        folders.add_folder(&music).unwrap();
+        let (tx, _rx) = mpsc::channel::<Result<Event, notify::Error>>();
+        let mut watcher = notify::recommended_watcher(tx).unwrap();
+        let mut watched = HashSet::new();
+
+        sync_watched_folders(&mut watcher, &folders, &mut watched);
+        assert_eq!(watched, HashSet::from([music.clone()]));
+
+        // 从文件夹管理器移除后停止监听：再次取消监听会因为未在监听而失败
+        assert!(folders.remove_folder(&music));
+        sync_watched_folders(&mut watcher, &folders, &mut watched);
+        assert!(watched.is_empty());
+        assert!(watcher.unwatch(&music).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,110 @@
+//! 访问审计 — 记录经 [`resource`](super::resource) 发起的每次资源读写。
+//!
+//! 日志只在内存中保留最近 [`MAX_RECORDS`] 条，重启即清空。流式播放会对同一首歌发起大量 Range 请求，
+//! 因此与上一条完全相同（操作、来源 ID、结果）的访问只累加 `count` 并刷新时间，不另起一条。
+
+use super::types::SourceId;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// 内存中最多保留的记录数。
+pub const MAX_RECORDS: usize = 500;
+
+/// 一条访问记录。
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessRecord {
+    /// 最近一次发生时间（Unix 秒）
+    pub at: u64,
+    /// 操作名，如 `song_file_get`、`lyric_text_put`
+    pub operation: String,
+    pub source_id: SourceId,
+    pub allowed: bool,
+    /// 拒绝或失败的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 连续重复的次数
+    pub count: u32,
+}
+
+/// 资源访问日志。
+#[derive(Default)]
+pub struct AccessLog {
+    records: Mutex<VecDeque<AccessRecord>>,
+}
+
+impl AccessLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次访问。`result` 为 `Err` 时记为未放行。
+    pub fn record<T>(&self, operation: &str, source_id: &SourceId, result: &Result<T, String>) {
+        let error = result.as_ref().err().cloned();
+        let allowed = error.is_none();
+        let at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut records = self.records.lock();
+        if let Some(last) = records.back_mut() {
+            if last.operation == operation
+                && last.source_id == *source_id
+                && last.allowed == allowed
+                && last.error == error
+            {
+                last.at = at;
+                last.count = last.count.saturating_add(1);
+                return;
+            }
+        }
+        records.push_back(AccessRecord {
+            at,
+            operation: operation.to_string(),
+            source_id: source_id.clone(),
+            allowed,
+            error,
+            count: 1,
+        });
+        while records.len() > MAX_RECORDS {
+            records.pop_front();
+        }
+    }
+
+    /// 最近的 `limit` 条记录，新的在前。
+    pub fn recent(&self, limit: usize) -> Vec<AccessRecord> {
+        self.records.lock().iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.records.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_source::types::{EntityType, SourceType};
+
+    #[test]
+    fn test_access_log_collapses_repeats() {
+        let log = AccessLog::new();
+        let sid = SourceId::new("local", SourceType::Local, EntityType::Song, "/music/a.flac");
+        for _ in 0..3 {
+            log.record("song_file_path", &sid, &Ok::<(), String>(()));
+        }
+        log.record("song_file_path", &sid, &Err::<(), String>("denied".into()));
+
+        let recent = log.recent(10);
+        assert_eq!(recent.len(), 2);
+        assert!(!recent[0].allowed);
+        assert_eq!(recent[1].count, 3);
+
+        for i in 0..MAX_RECORDS + 10 {
+            let sid = SourceId::new("local", SourceType::Local, EntityType::Song, format!("/music/{}.flac", i));
+            log.record("song_file_get", &sid, &Ok::<(), String>(()));
+        }
+        assert_eq!(log.recent(usize::MAX).len(), MAX_RECORDS);
+    }
+}
@@ -7,6 +7,7 @@
 //!
 //! 使用 [`PersistentStore`] 将 `Vec<SourceEntry>` 保存在键 `"source_registry_entries"` 下。
 
+use super::stream::StreamQuality;
 use super::types::SourceType;
 use crate::module::storage::persistent::PersistentStore;
 use parking_lot::RwLock;
@@ -15,9 +16,27 @@ use std::path::PathBuf;
 
 /// 已注册来源的持久化条目（仅保存元信息，不保存实现）。
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SourceEntry {
     pub name: String,
     pub source_type: SourceType,
+    /// 只读：为 `true` 时不向该来源写回标签 / 歌词 / 封面。新的网络来源默认只读，需用户显式开启写回；
+    /// 本地来源默认可写（各文件夹另有只读开关），设为只读后标签编辑与评分写回都不再改动文件
+    #[serde(default = "default_read_only")]
+    pub read_only: bool,
+    /// 仅元数据：索引并展示其中的歌曲，但不提供音频（如只作归档的共享盘），播放、预加载与音频分析都跳过
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub metadata_only: bool,
+    /// 流式播放 / 下载时请求的转码质量；`None` 取原始文件（仅对支持转码的来源生效）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcode: Option<StreamQuality>,
+    /// 计费网络下改用的转码质量；`None` 时沿用 `transcode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metered_transcode: Option<StreamQuality>,
+}
+
+fn default_read_only() -> bool {
+    true
 }
 
 /// 来源管理器。
@@ -93,12 +112,61 @@ impl SourceManager {
         }
         entries.push(SourceEntry {
             name: name.to_string(),
+            read_only: source_type != SourceType::Local,
             source_type,
+            metadata_only: false,
+            transcode: None,
+            metered_transcode: None,
         });
         drop(entries);
         self.save()
     }
 
+    /// 设置来源的只读开关，并持久化到磁盘。
+    pub fn set_read_only(&self, name: &str, read_only: bool) -> Result<(), String> {
+        let mut entries = self.entries.write();
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.name == name)
+            .ok_or_else(|| format!("来源 '{}' 不存在", name))?;
+        entry.read_only = read_only;
+        drop(entries);
+        self.save()
+    }
+
+    /// 设置来源是否仅元数据，并持久化到磁盘。
+    pub fn set_metadata_only(&self, name: &str, metadata_only: bool) -> Result<(), String> {
+        let mut entries = self.entries.write();
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.name == name)
+            .ok_or_else(|| format!("来源 '{}' 不存在", name))?;
+        entry.metadata_only = metadata_only;
+        drop(entries);
+        self.save()
+    }
+
+    /// 设置来源的转码质量（平时 / 计费网络），并持久化到磁盘。
+    pub fn set_transcode(
+        &self,
+        name: &str,
+        transcode: Option<StreamQuality>,
+        metered_transcode: Option<StreamQuality>,
+    ) -> Result<(), String> {
+        for quality in transcode.iter().chain(metered_transcode.iter()) {
+            quality.validate()?;
+        }
+        let mut entries = self.entries.write();
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.name == name)
+            .ok_or_else(|| format!("来源 '{}' 不存在", name))?;
+        entry.transcode = transcode;
+        entry.metered_transcode = metered_transcode;
+        drop(entries);
+        self.save()
+    }
+
     /// 从内存中移除来源条目，并持久化到磁盘。
     ///
     /// 返回 `true` 表示条目存在并被移除。
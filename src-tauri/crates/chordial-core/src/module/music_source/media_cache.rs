@@ -0,0 +1,196 @@
+//! 媒体缓存 — 远程歌曲音频的磁盘缓存。
+//!
+//! 远程来源（如 P2P 节点）没有本地文件路径，每次播放都要整文件拉取。缓存把拉取到的
+//! 音频落到 `data_dir/media_cache/` 下，之后 [`resource::get_song_file_path`](super::resource::get_song_file_path)
+//! 直接返回缓存文件路径，`chordial://audio` 就能按 Range 流式读取。
+//!
+//! 文件名取 `source_name` + `entity_id` 的 FNV-1a 哈希，保留原扩展名以便推断 MIME。
+//! 总大小超过上限时按修改时间淘汰最旧的文件；命中时刷新修改时间，近似 LRU。
+
+use super::types::SourceId;
+use crate::module::safe_mode;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// 默认容量上限：1 GiB。
+pub const DEFAULT_MAX_BYTES: u64 = 1 << 30;
+
+/// 远程音频的磁盘缓存。
+pub struct MediaCache {
+    dir: PathBuf,
+    max_bytes: AtomicU64,
+    /// 只读（安全模式）：命中照常，不写入、不淘汰、不删除
+    read_only: AtomicBool,
+}
+
+impl MediaCache {
+    /// 创建缓存（目录不存在时创建）。
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Result<Self, String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("创建媒体缓存目录失败: {}", e))?;
+        Ok(Self {
+            dir,
+            max_bytes: AtomicU64::new(max_bytes),
+            read_only: AtomicBool::new(false),
+        })
+    }
+
+    /// 缓存文件的路径（不检查是否存在）。
+    fn file_path(&self, source_id: &SourceId) -> PathBuf {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let key = [source_id.source_name.as_bytes(), b"\0", source_id.entity_id.as_bytes()];
+        for byte in key.iter().flat_map(|part| part.iter()) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        let ext = Path::new(&source_id.entity_id)
+            .extension()
+            .and_then(|e| e.to_str())
+            .filter(|e| e.len() <= 8 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+            .map(|e| format!(".{}", e.to_ascii_lowercase()))
+            .unwrap_or_default();
+        self.dir.join(format!("{:016x}{}", hash, ext))
+    }
+
+    /// 是否已缓存。
+    pub fn contains(&self, source_id: &SourceId) -> bool {
+        self.file_path(source_id).is_file()
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Relaxed);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    /// 返回已缓存文件的路径，并刷新其修改时间（只读时不刷新）。
+    pub fn cached_path(&self, source_id: &SourceId) -> Option<String> {
+        let path = self.file_path(source_id);
+        if self.is_read_only() {
+            return path.is_file().then(|| path.to_string_lossy().into_owned());
+        }
+        let file = fs::OpenOptions::new().append(true).open(&path).ok()?;
+        let _ = file.set_modified(SystemTime::now());
+        Some(path.to_string_lossy().into_owned())
+    }
+
+    /// 写入缓存，返回缓存文件路径。写入后按容量上限淘汰旧文件（不会淘汰刚写入的文件）。
+    pub fn store(&self, source_id: &SourceId, data: &[u8]) -> Result<String, String> {
+        if self.is_read_only() {
+            return Err(safe_mode::READ_ONLY_ERROR.to_string());
+        }
+        let path = self.file_path(source_id);
+        let tmp = path.with_extension("part");
+        fs::write(&tmp, data).map_err(|e| format!("写入媒体缓存失败: {}", e))?;
+        fs::rename(&tmp, &path).map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            format!("写入媒体缓存失败: {}", e)
+        })?;
+        self.evict(&path);
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    /// 调整容量上限，超出新上限的旧文件立即淘汰。
+    pub fn set_max_bytes(&self, max_bytes: u64) {
+        self.max_bytes.store(max_bytes, Ordering::Relaxed);
+        self.evict(Path::new(""));
+    }
+
+    /// 当前容量上限。
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 缓存当前占用的字节数。
+    pub fn size(&self) -> u64 {
+        self.entries().iter().map(|(_, len, _)| len).sum()
+    }
+
+    /// 清空缓存。
+    pub fn clear(&self) -> Result<(), String> {
+        if self.is_read_only() {
+            return Err(safe_mode::READ_ONLY_ERROR.to_string());
+        }
+        for (path, _, _) in self.entries() {
+            fs::remove_file(&path).map_err(|e| format!("清理媒体缓存失败: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// 列出缓存文件：(路径, 大小, 修改时间)，忽略未写完的临时文件。
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        let Ok(dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        dir.flatten()
+            .filter(|e| e.path().extension().is_none_or(|ext| ext != "part"))
+            .filter_map(|e| {
+                let meta = e.metadata().ok()?;
+                meta.is_file().then(|| {
+                    let mtime = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    (e.path(), meta.len(), mtime)
+                })
+            })
+            .collect()
+    }
+
+    fn evict(&self, keep: &Path) {
+        if self.is_read_only() {
+            return;
+        }
+        let mut entries = self.entries();
+        let max_bytes = self.max_bytes.load(Ordering::Relaxed);
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        if total <= max_bytes {
+            return;
+        }
+        entries.sort_by_key(|(_, _, mtime)| *mtime);
+        for (path, len, _) in entries {
+            if total <= max_bytes {
+                break;
+            }
+            if path == keep {
+                continue;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_source::types::{EntityType, SourceType};
+
+    #[test]
+    fn test_store_and_evict_oldest() {
+        let dir = std::env::temp_dir().join(format!("chordial-media-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = MediaCache::new(dir.clone(), 10).unwrap();
+        let id = |name: &str| SourceId::new("peer", SourceType::Web("p2p".into()), EntityType::Song, name);
+
+        let first = cache.store(&id("/music/a.flac"), b"123456").unwrap();
+        assert!(first.ends_with(".flac"));
+        assert_eq!(cache.cached_path(&id("/music/a.flac")), Some(first));
+        assert!(cache.cached_path(&id("/music/b.flac")).is_none());
+
+        let old = SystemTime::now() - std::time::Duration::from_secs(60);
+        fs::OpenOptions::new()
+            .append(true)
+            .open(cache.file_path(&id("/music/a.flac")))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        cache.store(&id("/music/b.flac"), b"abcdef").unwrap();
+        assert!(!cache.contains(&id("/music/a.flac")));
+        assert!(cache.contains(&id("/music/b.flac")));
+        assert_eq!(cache.size(), 6);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
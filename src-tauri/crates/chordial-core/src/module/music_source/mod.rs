@@ -7,7 +7,11 @@
 //! MusicSource (trait)                  ← 来源实现必须遵循的接口
 //! SourceManager                        ← 元信息持久化 + 内存挂载
 //! SourceRegistrar                      ← 注册/注销/查找 + MusicLibrary 联动清理
-//! resource                             ← 资源获取调度（song_file / album_picture / lyric_text）
+//! resource                             ← 资源获取调度（song_file / album_picture / lyric_text）+ 写回（put_*）
+//! MediaCache                           ← 远程音频的磁盘缓存（供 resource / 预加载使用）
+//! PcmCache                             ← 常播短曲目的解码 PCM 副本（chordial://audio 优先返回）
+//! AccessLog (audit)                    ← resource 读写的访问审计（内存环形日志）
+//! StreamQuality (stream)               ← 按来源 / 计费网络选择的转码流质量
 //! ```
 //!
 //! # 使用示例
@@ -30,8 +34,12 @@
 //! let audio = resource::get_song_file(&registrar, &source_id)?;
 //! ```
 
+pub mod audit;
 pub mod manager;
+pub mod media_cache;
+pub mod pcm_cache;
 pub mod registrar;
 pub mod resource;
+pub mod stream;
 pub mod traits;
 pub mod types;
@@ -0,0 +1,174 @@
+//! 解码 PCM 缓存 — 常播短曲目预先解码成 24 bit WAV 存在磁盘上。
+//!
+//! 浏览器播放 WAV 不需要再解码压缩格式，开播更快，A-B 循环来回拖动时也不必重新定位帧。
+//! `chordial://audio` 请求的文件在这里有副本时直接返回副本（见 [`media`](crate::media)）。
+//!
+//! - 以源文件路径 + mtime 为键，文件修改后旧副本不再命中，随 LRU 淘汰。
+//! - 落盘、容量上限与淘汰复用 [`MediaCache`]；上限可随设置调整。
+//! - 哪些歌曲值得缓存（播放次数、时长）由调用方按
+//!   [`PcmCacheSettings`](crate::module::playback::PcmCacheSettings) 判断。
+
+use super::media_cache::MediaCache;
+use super::types::{EntityType, SourceId, SourceType};
+use crate::module::analysis::decode;
+use crate::module::platform::{self, PlatformPath};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+/// 默认容量上限：512 MiB。
+pub const DEFAULT_MAX_BYTES: u64 = 512 << 20;
+
+/// 输出位深。
+const BITS_PER_SAMPLE: u16 = 24;
+
+/// 解码 PCM 的磁盘缓存。
+pub struct PcmCache {
+    files: MediaCache,
+    /// 正在后台解码的源文件，避免同一首重复解码
+    pending: Mutex<HashSet<String>>,
+}
+
+impl PcmCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Result<Self, String> {
+        Ok(Self {
+            files: MediaCache::new(dir, max_bytes)?,
+            pending: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// 源文件当前版本在缓存中的键；源文件不存在时返回 `None`。
+    fn key(path: &str) -> Option<SourceId> {
+        let mtime = platform::file_modified_secs(&PlatformPath::from(path)).ok()?;
+        Some(SourceId::new(
+            "pcm",
+            SourceType::Local,
+            EntityType::Song,
+            format!("{}@{}.wav", path, mtime),
+        ))
+    }
+
+    /// 源文件的 PCM 副本路径（源文件修改过则不命中），命中时刷新其 LRU 时间。
+    pub fn cached_path(&self, path: &str) -> Option<String> {
+        self.files.cached_path(&Self::key(path)?)
+    }
+
+    /// 整首解码并写入缓存，返回副本路径。
+    pub fn store(&self, path: &str) -> Result<String, String> {
+        let key = Self::key(path).ok_or_else(|| format!("文件不存在: {}", path))?;
+        let wav = encode_wav(&PlatformPath::from(path))?;
+        self.files.store(&key, &wav)
+    }
+
+    /// 在后台线程解码并缓存；缓存只读、已有副本或正在解码时直接返回。
+    pub fn store_in_background(self: &Arc<Self>, path: String) {
+        if self.files.is_read_only() || self.cached_path(&path).is_some() || !self.pending.lock().insert(path.clone()) {
+            return;
+        }
+        let cache = self.clone();
+        let spawned = thread::Builder::new()
+            .name("pcm-cache".into())
+            .spawn(move || {
+                if let Err(e) = cache.store(&path) {
+                    eprintln!("[pcm_cache] 缓存 {} 失败: {}", path, e);
+                }
+                cache.pending.lock().remove(&path);
+            });
+        if let Err(e) = spawned {
+            eprintln!("[pcm_cache] 启动解码线程失败: {}", e);
+        }
+    }
+
+    pub fn set_max_bytes(&self, max_bytes: u64) {
+        self.files.set_max_bytes(max_bytes);
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        self.files.set_read_only(read_only);
+    }
+
+    pub fn size(&self) -> u64 {
+        self.files.size()
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        self.files.clear()
+    }
+}
+
+/// 整首解码为 24 bit PCM WAV（保留原声道数与采样率）。
+fn encode_wav(path: &PlatformPath) -> Result<Vec<u8>, String> {
+    let bytes_per_sample = usize::from(BITS_PER_SAMPLE / 8);
+    let mut data = Vec::new();
+    let mut format: Option<(u16, u32)> = None;
+    decode::decode_file(path, |block| {
+        format.get_or_insert((block.channels as u16, block.sample_rate));
+        data.reserve(block.samples.len() * bytes_per_sample);
+        for &sample in block.samples {
+            let value = (sample.clamp(-1.0, 1.0) * 8_388_607.0).round() as i32;
+            data.extend_from_slice(&value.to_le_bytes()[..bytes_per_sample]);
+        }
+    })?;
+    let (channels, sample_rate) = format
+        .filter(|_| !data.is_empty())
+        .ok_or("没有解码出音频数据")?;
+    let block_align = channels * BITS_PER_SAMPLE / 8;
+    let data_len = u32::try_from(data.len()).map_err(|_| "音频过长，超出 WAV 大小上限".to_string())?;
+
+    let mut wav = Vec::with_capacity(44 + data.len());
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    wav.extend_from_slice(&data);
+    Ok(wav)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::playback::flac::FlacWriter;
+
+    #[test]
+    fn test_store_wav_and_invalidate_on_modify() {
+        let dir = std::env::temp_dir().join(format!("chordial-pcm-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("song.flac");
+        let mut writer = FlacWriter::create(&source, 8000).unwrap();
+        writer.write(&[0.5; 8000 * 2]).unwrap();
+        writer.finish().unwrap();
+        let path = source.to_string_lossy().into_owned();
+
+        let cache = PcmCache::new(dir.join("pcm"), DEFAULT_MAX_BYTES).unwrap();
+        assert!(cache.cached_path(&path).is_none());
+        let cached = cache.store(&path).unwrap();
+        assert!(cached.ends_with(".wav"));
+        assert_eq!(cache.cached_path(&path), Some(cached.clone()));
+
+        let wav = std::fs::read(&cached).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        // 1 秒立体声，每样本 3 字节
+        assert_eq!(wav.len(), 44 + 8000 * 2 * 3);
+
+        // 源文件修改后不再命中旧副本
+        std::fs::File::options()
+            .append(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert!(cache.cached_path(&path).is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -11,10 +11,15 @@
 //! 通过 [`SourceCleanup`] 通知音乐库移除所有引用该来源的实体。
 //! 若某实体的 `source_ids` 被全部清空，该实体本身也会被删除。
 
+use super::audit::AccessLog;
 use super::manager::{SourceEntry, SourceManager};
+use super::media_cache::MediaCache;
+use super::pcm_cache::PcmCache;
+use super::stream::{self, StreamQuality};
 use super::traits::MusicSource;
 use parking_lot::RwLock;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 /// 来源注销时的清理回调接口。
@@ -36,6 +41,14 @@ pub struct SourceRegistrar {
     sources: RwLock<HashMap<String, Arc<dyn MusicSource>>>,
     /// 清理回调（指向 MusicLibrary）
     cleanup: Arc<dyn SourceCleanup>,
+    /// 远程音频的磁盘缓存（可选）
+    media_cache: RwLock<Option<Arc<MediaCache>>>,
+    /// 常播短曲目的解码 PCM 缓存（可选）
+    pcm_cache: RwLock<Option<Arc<PcmCache>>>,
+    /// 资源访问审计日志
+    access_log: AccessLog,
+    /// 当前是否处于计费网络（前端告知，不持久化）
+    metered: AtomicBool,
 }
 
 impl SourceRegistrar {
@@ -50,6 +63,10 @@ impl SourceRegistrar {
             manager,
             sources: RwLock::new(HashMap::new()),
             cleanup,
+            media_cache: RwLock::new(None),
+            pcm_cache: RwLock::new(None),
+            access_log: AccessLog::new(),
+            metered: AtomicBool::new(false),
         }
     }
 
@@ -65,6 +82,59 @@ impl SourceRegistrar {
         self.manager.find_entry(name)
     }
 
+    /// 设置来源的只读开关（见 [`SourceEntry::read_only`]）。
+    pub fn set_read_only(&self, name: &str, read_only: bool) -> Result<(), String> {
+        self.manager.set_read_only(name, read_only)
+    }
+
+    /// 来源是否允许写回：实现支持写回且条目未设为只读。
+    pub fn is_writable(&self, name: &str) -> bool {
+        let supported = self.get(name).is_some_and(|s| s.supports_write_back());
+        supported && self.find_entry(name).is_some_and(|e| !e.read_only)
+    }
+
+    /// 设置来源是否仅元数据（见 [`SourceEntry::metadata_only`]）。
+    pub fn set_metadata_only(&self, name: &str, metadata_only: bool) -> Result<(), String> {
+        self.manager.set_metadata_only(name, metadata_only)
+    }
+
+    /// 该来源中的歌曲能否播放：条目未设为仅元数据，且来源实现允许播放这一条（见
+    /// [`MusicSource::is_playable`]，本地来源按所在文件夹判断）。
+    pub fn is_playable(&self, source_name: &str, entity_id: &str) -> bool {
+        if self.find_entry(source_name).is_some_and(|e| e.metadata_only) {
+            return false;
+        }
+        self.get(source_name).is_some_and(|s| s.is_playable(entity_id))
+    }
+
+    /// 设置来源的转码质量（见 [`SourceEntry::transcode`]）。
+    pub fn set_transcode(
+        &self,
+        name: &str,
+        transcode: Option<StreamQuality>,
+        metered_transcode: Option<StreamQuality>,
+    ) -> Result<(), String> {
+        self.manager.set_transcode(name, transcode, metered_transcode)
+    }
+
+    /// 更新网络是否计费。
+    pub fn set_metered(&self, metered: bool) {
+        self.metered.store(metered, Ordering::Relaxed);
+    }
+
+    pub fn is_metered(&self) -> bool {
+        self.metered.load(Ordering::Relaxed)
+    }
+
+    /// 按当前网络状态，向该来源请求音频时应使用的转码质量；来源不支持转码时为 `None`。
+    pub fn stream_quality(&self, name: &str) -> Option<StreamQuality> {
+        if !self.get(name)?.supports_transcode() {
+            return None;
+        }
+        let entry = self.find_entry(name)?;
+        stream::effective_quality(entry.transcode, entry.metered_transcode, self.is_metered())
+    }
+
     // ── 注册 / 注销 ───────────────────────────────────
 
     /// 注册一个来源实现。
@@ -164,4 +234,33 @@ impl SourceRegistrar {
     pub fn manager(&self) -> &Arc<SourceManager> {
         &self.manager
     }
+
+    // ── 媒体缓存 ─────────────────────────────────────
+
+    /// 挂载远程音频的磁盘缓存。
+    pub fn set_media_cache(&self, cache: Arc<MediaCache>) {
+        *self.media_cache.write() = Some(cache);
+    }
+
+    /// 返回已挂载的媒体缓存。
+    pub fn media_cache(&self) -> Option<Arc<MediaCache>> {
+        self.media_cache.read().clone()
+    }
+
+    /// 挂载解码 PCM 缓存。
+    pub fn set_pcm_cache(&self, cache: Arc<PcmCache>) {
+        *self.pcm_cache.write() = Some(cache);
+    }
+
+    /// 返回已挂载的解码 PCM 缓存。
+    pub fn pcm_cache(&self) -> Option<Arc<PcmCache>> {
+        self.pcm_cache.read().clone()
+    }
+
+    // ── 访问审计 ─────────────────────────────────────
+
+    /// 返回资源访问日志。
+    pub fn access_log(&self) -> &AccessLog {
+        &self.access_log
+    }
 }
@@ -11,16 +11,27 @@
 //!
 //! 每个函数接收 [`SourceId`](super::types::SourceId) 作为参数，
 //! 从中提取 `source_name` 以查找来源实现，提取 `entity_id` 传给 trait 方法。
+//!
+//! `put_*` 函数是反方向的写回：仅当来源实现支持写回且条目未设为只读时才调用来源，
+//! 否则直接返回错误，不会触达来源后端。
+//!
+//! 所有读写的结果都记入注册器的 [`AccessLog`](super::audit::AccessLog)。
 
 use super::registrar::SourceRegistrar;
-use super::types::SourceId;
+use super::traits::MusicSource;
+use super::types::{EntityType, SourceId};
+use crate::module::music_library::edits::FieldValues;
 use crate::module::perf;
+use serde::Serialize;
+use std::sync::Arc;
 
 /// 获取歌曲的音频文件。
 ///
 /// # 链路
 /// 1. 用 `source_id.source_name` 查找来源实现
-/// 2. 调用来源的 [`song_file_get`](super::traits::MusicSource::song_file_get)
+/// 2. 来源设置了转码质量（见 [`SourceRegistrar::stream_quality`]）时调用
+///    [`song_file_get_transcoded`](super::traits::MusicSource::song_file_get_transcoded)，
+///    否则调用 [`song_file_get`](super::traits::MusicSource::song_file_get)
 /// 3. 返回音频字节数据
 pub fn get_song_file(
     registrar: &SourceRegistrar,
@@ -30,20 +41,105 @@ pub fn get_song_file(
     let source = registrar
         .get(&source_id.source_name)
         .ok_or_else(|| format!("来源 '{}' 未注册", source_id.source_name))?;
+    if !registrar.is_playable(&source_id.source_name, &source_id.entity_id) {
+        let result = Err(format!("来源 '{}' 仅提供元数据，不能播放", source_id.source_name));
+        registrar.access_log().record("song_file_get", source_id, &result);
+        return result;
+    }
+
+    let (operation, result) = match registrar.stream_quality(&source_id.source_name) {
+        Some(quality) => (
+            "song_file_get_transcoded",
+            source.song_file_get_transcoded(&source_id.entity_id, &quality),
+        ),
+        None => ("song_file_get", source.song_file_get(&source_id.entity_id)),
+    };
+    registrar.access_log().record(operation, source_id, &result);
+    result
+}
 
-    source.song_file_get(&source_id.entity_id)
+/// 该歌曲音频在媒体缓存中的键：按当前转码质量区分，不转码时即 `source_id` 本身。
+pub fn stream_cache_key(registrar: &SourceRegistrar, source_id: &SourceId) -> SourceId {
+    match registrar.stream_quality(&source_id.source_name) {
+        Some(quality) => quality.cache_key(source_id),
+        None => source_id.clone(),
+    }
 }
 
 /// 获取歌曲文件的本地路径（用于自定义协议流式传输）。
 ///
-/// 仅本地来源支持；网络来源返回 `None`。
+/// 本地来源返回文件本身；网络来源在媒体缓存中有副本时返回缓存文件，否则返回 `None`。
+/// 仅元数据的来源 / 文件夹（见 [`SourceRegistrar::is_playable`]）同样返回 `None`。
 pub fn get_song_file_path(
     registrar: &SourceRegistrar,
     source_id: &SourceId,
 ) -> Option<String> {
     let source = registrar
         .get(&source_id.source_name)?;
-    source.song_file_path(&source_id.entity_id)
+    if !registrar.is_playable(&source_id.source_name, &source_id.entity_id) {
+        let result: Result<String, String> = Err("仅提供元数据，不能播放".to_string());
+        registrar.access_log().record("song_file_path", source_id, &result);
+        return None;
+    }
+    let path = source
+        .song_file_path(&source_id.entity_id)
+        .or_else(|| registrar.media_cache()?.cached_path(&stream_cache_key(registrar, source_id)));
+    let result = path.ok_or_else(|| "没有可直接访问的文件".to_string());
+    registrar.access_log().record("song_file_path", source_id, &result);
+    result.ok()
+}
+
+/// 在一组来源 ID 中查找第一个可直接访问的歌曲文件路径。
+///
+/// 只考虑 `EntityType::Song` 的来源 ID，按顺序尝试，返回首个命中的本地路径。
+pub fn find_song_file_path(
+    registrar: &SourceRegistrar,
+    source_ids: &[SourceId],
+) -> Option<String> {
+    source_ids
+        .iter()
+        .filter(|sid| sid.entity_type == EntityType::Song)
+        .find_map(|sid| get_song_file_path(registrar, sid))
+}
+
+/// 获取歌曲音频的本地路径；网络来源没有缓存副本时先整文件拉取（按当前转码质量）写入媒体缓存。
+///
+/// 返回路径，以及文件来自媒体缓存时的缓存键（本地文件为 `None`）。
+/// 供需要完整读取文件的后端功能（音频分析等）使用，播放仍走 [`get_song_file_path`]。
+/// 仅元数据的来源不提供音频，这里同样跳过。
+pub fn fetch_song_file_path(
+    registrar: &SourceRegistrar,
+    source_ids: &[SourceId],
+) -> Result<(String, Option<SourceId>), String> {
+    let songs: Vec<&SourceId> = source_ids
+        .iter()
+        .filter(|sid| sid.entity_type == EntityType::Song)
+        .filter(|sid| registrar.is_playable(&sid.source_name, &sid.entity_id))
+        .collect();
+    if songs.is_empty() && source_ids.iter().any(|sid| sid.entity_type == EntityType::Song) {
+        return Err("歌曲所在的来源仅提供元数据，不能读取音频".to_string());
+    }
+    let local = songs.iter().find_map(|sid| {
+        registrar.get(&sid.source_name)?.song_file_path(&sid.entity_id)
+    });
+    if let Some(path) = local {
+        return Ok((path, None));
+    }
+    let cache = registrar
+        .media_cache()
+        .ok_or("媒体缓存未启用，无法读取远程歌曲")?;
+    let mut last_error = "没有可用的来源".to_string();
+    for sid in songs {
+        let cache_key = stream_cache_key(registrar, sid);
+        if let Some(path) = cache.cached_path(&cache_key) {
+            return Ok((path, Some(cache_key)));
+        }
+        match get_song_file(registrar, sid).and_then(|data| cache.store(&cache_key, &data)) {
+            Ok(path) => return Ok((path, Some(cache_key))),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
 }
 
 /// 获取专辑的封面图片。
@@ -61,7 +157,9 @@ pub fn get_album_picture(
         .get(&source_id.source_name)
         .ok_or_else(|| format!("来源 '{}' 未注册", source_id.source_name))?;
 
-    source.album_picture_get(&source_id.entity_id)
+    let result = source.album_picture_get(&source_id.entity_id);
+    registrar.access_log().record("album_picture_get", source_id, &result);
+    result
 }
 
 /// 获取歌曲的歌词文本。
@@ -79,5 +177,185 @@ pub fn get_lyric_text(
         .get(&source_id.source_name)
         .ok_or_else(|| format!("来源 '{}' 未注册", source_id.source_name))?;
 
-    source.lyric_text_get(&source_id.entity_id)
+    let result = source.lyric_text_get(&source_id.entity_id);
+    registrar.access_log().record("lyric_text_get", source_id, &result);
+    result
+}
+
+// ── 写回 ─────────────────────────────────────────────
+
+/// 取出允许写回的来源实现。
+fn writable_source(
+    registrar: &SourceRegistrar,
+    source_name: &str,
+) -> Result<Arc<dyn MusicSource>, String> {
+    let source = registrar
+        .get(source_name)
+        .ok_or_else(|| format!("来源 '{}' 未注册", source_name))?;
+    if !source.supports_write_back() {
+        return Err(format!("来源 '{}' 不支持写回", source_name));
+    }
+    if !registrar.is_writable(source_name) {
+        return Err(format!("来源 '{}' 为只读", source_name));
+    }
+    Ok(source)
+}
+
+/// 把标签修改写回来源中的歌曲。
+pub fn put_song_tags(
+    registrar: &SourceRegistrar,
+    source_id: &SourceId,
+    changes: &FieldValues,
+) -> Result<(), String> {
+    let _scope = perf::scope("resource.put_song_tags");
+    let result = writable_source(registrar, &source_id.source_name)
+        .and_then(|s| s.song_tags_put(&source_id.entity_id, changes));
+    registrar.access_log().record("song_tags_put", source_id, &result);
+    result
+}
+
+/// 把歌词文本写回来源。
+pub fn put_lyric_text(
+    registrar: &SourceRegistrar,
+    source_id: &SourceId,
+    text: &str,
+) -> Result<(), String> {
+    let _scope = perf::scope("resource.put_lyric_text");
+    let result = writable_source(registrar, &source_id.source_name)
+        .and_then(|s| s.lyric_text_put(&source_id.entity_id, text));
+    registrar.access_log().record("lyric_text_put", source_id, &result);
+    result
+}
+
+/// 把封面图片写回来源。
+pub fn put_album_picture(
+    registrar: &SourceRegistrar,
+    source_id: &SourceId,
+    data: &[u8],
+) -> Result<(), String> {
+    let _scope = perf::scope("resource.put_album_picture");
+    let result = writable_source(registrar, &source_id.source_name)
+        .and_then(|s| s.album_picture_put(&source_id.entity_id, data));
+    registrar.access_log().record("album_picture_put", source_id, &result);
+    result
+}
+
+/// 一次同步写回的结果。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WriteBackReport {
+    /// 写回成功的来源名称
+    pub written: Vec<String>,
+    /// 写回失败的来源及错误
+    pub failed: Vec<(String, String)>,
+}
+
+/// 把本地的标签编辑同步到歌曲的所有可写回来源；只读或不支持写回的来源静默跳过。
+pub fn sync_song_tags(
+    registrar: &SourceRegistrar,
+    source_ids: &[SourceId],
+    changes: &FieldValues,
+) -> WriteBackReport {
+    let mut report = WriteBackReport::default();
+    for sid in source_ids.iter().filter(|s| s.entity_type == EntityType::Song) {
+        if !registrar.is_writable(&sid.source_name) {
+            continue;
+        }
+        match put_song_tags(registrar, sid, changes) {
+            Ok(()) => report.written.push(sid.source_name.clone()),
+            Err(e) => report.failed.push((sid.source_name.clone(), e)),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_library::models::{Album, Artist, Lyric, Song};
+    use crate::module::music_source::manager::SourceManager;
+    use crate::module::music_source::registrar::SourceCleanup;
+    use crate::module::music_source::types::SourceType;
+    use parking_lot::Mutex;
+
+    struct NoCleanup;
+    impl SourceCleanup for NoCleanup {
+        fn remove_source_from_all_entities(&self, _source_name: &str) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    /// 只记录写回歌词的来源。
+    #[derive(Default)]
+    struct Writable {
+        lyrics: Mutex<Vec<String>>,
+    }
+    impl MusicSource for Writable {
+        fn name(&self) -> &str {
+            "backend"
+        }
+        fn source_type(&self) -> SourceType {
+            SourceType::Web("backend".into())
+        }
+        fn search_songs(&self, _query: &str) -> Result<Vec<Song>, String> {
+            Ok(Vec::new())
+        }
+        fn get_song(&self, _id: &str) -> Result<Option<Song>, String> {
+            Ok(None)
+        }
+        fn get_artist(&self, _id: &str) -> Result<Option<Artist>, String> {
+            Ok(None)
+        }
+        fn get_album(&self, _id: &str) -> Result<Option<Album>, String> {
+            Ok(None)
+        }
+        fn get_lyric(&self, _song_id: &str) -> Result<Option<Lyric>, String> {
+            Ok(None)
+        }
+        fn song_file_get(&self, _entity_id: &str) -> Result<Vec<u8>, String> {
+            Err("n/a".into())
+        }
+        fn album_picture_get(&self, _entity_id: &str) -> Result<Vec<u8>, String> {
+            Err("n/a".into())
+        }
+        fn lyric_text_get(&self, _song_id: &str) -> Result<String, String> {
+            Err("n/a".into())
+        }
+        fn supports_write_back(&self) -> bool {
+            true
+        }
+        fn lyric_text_put(&self, _song_id: &str, text: &str) -> Result<(), String> {
+            self.lyrics.lock().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_back_respects_read_only() {
+        let path = std::env::temp_dir().join(format!("chordial_sources_{}.json", std::process::id()));
+        let registrar = SourceRegistrar::new(Arc::new(SourceManager::new(path.clone())), Arc::new(NoCleanup));
+        let source = Arc::new(Writable::default());
+        registrar.register(source.clone()).unwrap();
+        let sid = SourceId {
+            source_name: "backend".into(),
+            source_type: SourceType::Web("backend".into()),
+            entity_type: EntityType::Lyric,
+            entity_id: "1".into(),
+        };
+
+        // 新来源默认只读
+        assert!(put_lyric_text(&registrar, &sid, "a").is_err());
+        registrar.set_read_only("backend", false).unwrap();
+        put_lyric_text(&registrar, &sid, "b").unwrap();
+        assert_eq!(*source.lyrics.lock(), vec!["b".to_string()]);
+        // 未实现的写回方法仍报错
+        assert!(put_album_picture(&registrar, &sid, &[1, 2]).is_err());
+
+        // 仅元数据的来源不提供音频
+        let song = SourceId { entity_type: EntityType::Song, ..sid };
+        assert_eq!(get_song_file(&registrar, &song).unwrap_err(), "n/a");
+        registrar.set_metadata_only("backend", true).unwrap();
+        assert!(get_song_file(&registrar, &song).unwrap_err().contains("仅提供元数据"));
+        assert!(fetch_song_file_path(&registrar, std::slice::from_ref(&song)).is_err());
+        let _ = std::fs::remove_file(path);
+    }
 }
@@ -0,0 +1,106 @@
+//! 转码流 — 慢速 / 计费网络下向远程来源请求转码后的音频，而不是原始无损文件。
+//!
+//! 每个来源条目可以设置两档质量（见 [`SourceEntry`](super::manager::SourceEntry)）：
+//! - `transcode`：平时使用；`None` 表示取原始文件
+//! - `metered_transcode`：处于计费网络时覆盖前者；`None` 表示沿用平时的设置
+//!
+//! 是否处于计费网络由前端根据系统网络状态告知注册器（运行时状态，不持久化）。
+//! 只有声明 [`supports_transcode`](super::traits::MusicSource::supports_transcode) 的来源才会收到转码请求，
+//! 其余来源始终返回原始文件。转码结果在媒体缓存中与原始文件分开存放，见 [`StreamQuality::cache_key`]。
+
+use super::types::SourceId;
+use serde::{Deserialize, Serialize};
+
+/// 转码流的编码格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum StreamCodec {
+    Opus,
+    Mp3,
+    Aac,
+}
+
+impl StreamCodec {
+    /// 文件扩展名（用于缓存文件命名与 MIME 推断）。
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Opus => "opus",
+            Self::Mp3 => "mp3",
+            Self::Aac => "m4a",
+        }
+    }
+}
+
+/// 转码质量。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct StreamQuality {
+    pub codec: StreamCodec,
+    /// 目标码率（kbit/s）
+    pub bitrate_kbps: u32,
+}
+
+/// 允许的码率范围（kbit/s）。
+pub const MIN_BITRATE_KBPS: u32 = 32;
+pub const MAX_BITRATE_KBPS: u32 = 320;
+
+impl StreamQuality {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_BITRATE_KBPS..=MAX_BITRATE_KBPS).contains(&self.bitrate_kbps) {
+            return Err(format!(
+                "码率 {}kbps 超出范围（{} ~ {}kbps）",
+                self.bitrate_kbps, MIN_BITRATE_KBPS, MAX_BITRATE_KBPS
+            ));
+        }
+        Ok(())
+    }
+
+    /// 转码结果在媒体缓存中使用的键：`entity_id` 追加质量后缀和对应扩展名，
+    /// 与原始文件、其他质量的缓存互不覆盖，也能按扩展名推断出正确的 MIME。
+    pub fn cache_key(&self, source_id: &SourceId) -> SourceId {
+        SourceId {
+            entity_id: format!(
+                "{}@{}k.{}",
+                source_id.entity_id,
+                self.bitrate_kbps,
+                self.codec.extension()
+            ),
+            ..source_id.clone()
+        }
+    }
+}
+
+/// 按网络状态选出生效的转码质量。
+pub fn effective_quality(
+    transcode: Option<StreamQuality>,
+    metered_transcode: Option<StreamQuality>,
+    metered: bool,
+) -> Option<StreamQuality> {
+    if metered {
+        metered_transcode.or(transcode)
+    } else {
+        transcode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::music_source::types::{EntityType, SourceType};
+
+    #[test]
+    fn test_quality_selection_and_cache_key() {
+        let opus = StreamQuality { codec: StreamCodec::Opus, bitrate_kbps: 128 };
+        let mp3 = StreamQuality { codec: StreamCodec::Mp3, bitrate_kbps: 96 };
+        assert_eq!(effective_quality(None, Some(opus), false), None);
+        assert_eq!(effective_quality(None, Some(opus), true), Some(opus));
+        assert_eq!(effective_quality(Some(mp3), None, true), Some(mp3));
+
+        let sid = SourceId::new("peer", SourceType::Web("p2p".into()), EntityType::Song, "a/b.flac");
+        let key = opus.cache_key(&sid);
+        assert_eq!(key.entity_id, "a/b.flac@128k.opus");
+        assert_eq!(key.source_name, "peer");
+        assert!(StreamQuality { codec: StreamCodec::Aac, bitrate_kbps: 8 }.validate().is_err());
+    }
+}
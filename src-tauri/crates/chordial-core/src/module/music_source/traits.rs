@@ -1,4 +1,6 @@
+use super::stream::StreamQuality;
 use super::types::SourceType;
+use crate::module::music_library::edits::FieldValues;
 use crate::module::music_library::models::{Album, Artist, Lyric, Song};
 
 /// 音乐来源插件必须实现的接口。
@@ -46,6 +48,14 @@ pub trait MusicSource: Send + Sync {
         None
     }
 
+    /// 该歌曲能否播放；默认允许。来源内部还有更细的访问设置时覆盖（如本地来源的仅元数据文件夹）。
+    ///
+    /// 来源条目整体设为仅元数据时由 [`SourceRegistrar::is_playable`](super::registrar::SourceRegistrar::is_playable)
+    /// 直接拒绝，不会调用此方法。
+    fn is_playable(&self, _entity_id: &str) -> bool {
+        true
+    }
+
     /// 获取专辑的封面图片数据。
     ///
     /// `entity_id` 为来源内部的专辑 ID。返回图片字节（JPEG/PNG 等）。
@@ -57,4 +67,41 @@ pub trait MusicSource: Send + Sync {
     /// 与 [`get_lyric`](Self::get_lyric) 不同，此方法直接从来源拉取原始文本，
     /// 而不是返回库内已存储的 [`Lyric`] 结构体。
     fn lyric_text_get(&self, song_id: &str) -> Result<String, String>;
+
+    // ── 转码流（可选）────────────────────────────────
+
+    /// 来源能否按请求的质量返回转码后的音频（如自建服务端的转码接口）。默认不支持。
+    fn supports_transcode(&self) -> bool {
+        false
+    }
+
+    /// 获取转码后的音频数据。仅在 [`supports_transcode`](Self::supports_transcode) 为 `true` 时调用。
+    fn song_file_get_transcoded(&self, _entity_id: &str, _quality: &StreamQuality) -> Result<Vec<u8>, String> {
+        Err(format!("来源 '{}' 不支持转码", self.name()))
+    }
+
+    // ── 写回（可选）──────────────────────────────────
+
+    /// 来源是否支持写回。默认不支持；支持的来源（如自建后端）覆盖此方法与下列 `*_put` 方法。
+    ///
+    /// 写回还受来源条目的 [`read_only`](super::manager::SourceEntry::read_only) 开关约束，
+    /// 调用方应经由 [`resource`](super::resource) 中的 `put_*` 函数发起。
+    fn supports_write_back(&self) -> bool {
+        false
+    }
+
+    /// 把编辑后的标签字段写回来源中的歌曲。`changes` 中值为 `None` 的字段表示清空。
+    fn song_tags_put(&self, _entity_id: &str, _changes: &FieldValues) -> Result<(), String> {
+        Err(format!("来源 '{}' 不支持写回标签", self.name()))
+    }
+
+    /// 写回歌曲的歌词文本。
+    fn lyric_text_put(&self, _song_id: &str, _text: &str) -> Result<(), String> {
+        Err(format!("来源 '{}' 不支持写回歌词", self.name()))
+    }
+
+    /// 写回专辑封面图片。
+    fn album_picture_put(&self, _entity_id: &str, _data: &[u8]) -> Result<(), String> {
+        Err(format!("来源 '{}' 不支持写回封面", self.name()))
+    }
 }
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 
 /// 实体类型 — 标记一个来源引用指向哪种音乐实体。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub enum EntityType {
     Song,
     Artist,
@@ -11,6 +12,7 @@ pub enum EntityType {
 
 /// 来源类型 — 区分本地来源与不同的网络来源。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub enum SourceType {
     /// 本地来源
     Local,
@@ -33,6 +35,7 @@ pub enum SourceType {
 /// }
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 pub struct SourceId {
     /// 已注册的来源名称，如 `"my_local"`, `"netease"`, `"spotify"`
     pub source_name: String,
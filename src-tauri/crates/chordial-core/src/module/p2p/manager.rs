@@ -10,6 +10,7 @@
 //! 所有公共方法均为同步方法，内部通过自有的 tokio 运行时驱动异步任务。
 
 use crate::module::config::store::ConfigStore;
+use crate::module::events::{AppEvent, EventBus};
 use crate::module::music_library::library::MusicLibrary;
 use crate::module::music_library::models::Song;
 use crate::module::music_source::registrar::SourceRegistrar;
@@ -50,8 +51,9 @@ const MATCH_CODE_HISTORY_LEN: usize = 10;
 const MATCH_CODE_ROTATE_INTERVAL: Duration = Duration::from_secs(60);
 const AUTO_CONNECT_SCAN_INTERVAL: Duration = Duration::from_secs(5);
 
-/// P2P 事件 — 通过外部注入的事件通道送出，由 Tauri 层转发为前端事件。
+/// P2P 事件 — 以 [`AppEvent::P2p`] 发布到事件总线，由 Tauri 层转发为前端事件。
 #[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum P2pEvent {
     /// 收到入站握手请求，等待本机用户确认
@@ -132,6 +134,7 @@ pub struct P2pManager {
     library: Arc<MusicLibrary>,
     registrar: Arc<SourceRegistrar>,
     config: Arc<ConfigStore>,
+    events: Arc<EventBus>,
     server_name: String,
     instance_id: String,
 
@@ -147,8 +150,6 @@ struct Inner {
     permission: Permission,
     peers: HashMap<String, PeerEntry>,
     pending_requests: HashMap<String, PendingRequest>,
-    /// 接收方为外部事件订阅者；manager 内部 clone 后发出事件
-    event_tx: Option<mpsc::UnboundedSender<P2pEvent>>,
     /// 关闭整个监听 accept 循环
     server_shutdown: Option<oneshot::Sender<()>>,
     /// 可信设备列表（持久化到 ConfigStore）
@@ -169,6 +170,7 @@ impl P2pManager {
         library: Arc<MusicLibrary>,
         registrar: Arc<SourceRegistrar>,
         config: Arc<ConfigStore>,
+        events: Arc<EventBus>,
     ) -> Arc<Self> {
         let runtime = Builder::new_multi_thread()
             .worker_threads(2)
@@ -209,7 +211,6 @@ impl P2pManager {
             permission: Permission::ReadOnly,
             peers: HashMap::new(),
             pending_requests: HashMap::new(),
-            event_tx: None,
             server_shutdown: None,
             trusted_devices,
             match_code_history: vec![initial_code],
@@ -222,6 +223,7 @@ impl P2pManager {
             library,
             registrar,
             config,
+            events,
             server_name,
             instance_id,
             inner: RwLock::new(inner),
@@ -230,15 +232,8 @@ impl P2pManager {
         })
     }
 
-    /// 注入事件通道 — Tauri 层用此通道消费事件并转发为前端事件。
-    pub fn set_event_channel(self: &Arc<Self>, tx: mpsc::UnboundedSender<P2pEvent>) {
-        self.inner.write().event_tx = Some(tx);
-    }
-
     fn emit(&self, evt: P2pEvent) {
-        if let Some(tx) = self.inner.read().event_tx.as_ref() {
-            let _ = tx.send(evt);
-        }
+        self.events.publish(AppEvent::P2p(evt));
     }
 
     /// 启动共享服务。
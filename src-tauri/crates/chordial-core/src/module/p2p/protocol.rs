@@ -27,6 +27,7 @@ pub const BROADCAST_PORT: u16 = 58009;
 
 /// 共享权限。
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "snake_case")]
 pub enum Permission {
     /// 仅可查询 / 流式播放
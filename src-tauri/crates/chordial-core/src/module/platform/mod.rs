@@ -149,6 +149,20 @@ pub fn path_join(base: &PlatformPath, child: &str) -> PlatformPath {
     }
 }
 
+/// `path` 是否位于 `root` 之内（按路径分段比较，`/music2` 不算在 `/music` 内）。
+///
+/// 两者都应是规范路径；不做任何文件系统访问。
+#[cfg(not(target_os = "android"))]
+pub fn path_starts_with(path: &PlatformPath, root: &PlatformPath) -> bool {
+    path.starts_with(root)
+}
+
+#[cfg(target_os = "android")]
+pub fn path_starts_with(path: &PlatformPath, root: &PlatformPath) -> bool {
+    let root = root.trim_end_matches('/');
+    path == root || path.strip_prefix(root).is_some_and(|rest| rest.starts_with('/'))
+}
+
 // ══════════════════════════════════════════════════════════════════════════════
 // Note: 所有平台 I/O 函数（read_bytes, open_file, exists, is_dir, read_dir,
 // file_size, mime_from_path）由 desktop.rs / android.rs 定义，
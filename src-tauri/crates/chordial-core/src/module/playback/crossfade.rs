@@ -0,0 +1,268 @@
+//! 切歌交叉淡化 — 根据前后两首的时长和剩余时间决定实际的过渡时长。
+//!
+//! 固定 6 秒的过渡套在 8 秒的过场或跳到结尾前几秒再切歌时，两首会叠成一团：
+//! 上一首还没淡完下一首已经结束，或者过渡从已经播完的位置开始。这里按
+//! [`ShortTrackThresholds`] 把过渡缩短到歌曲时长的一部分，并不超过当前歌曲的剩余时间；
+//! 缩短到 `min_crossfade_ms` 以下时干脆直接切歌。
+//!
+//! 实时播放由前端混音器执行：在当前歌曲剩余 `duration_ms` 时开始下一首，见
+//! [`PlaybackManager::crossfade_plan`](super::PlaybackManager::crossfade_plan)。
+//! 离线渲染（[`render`](super::render)）对每个过渡套用同一规则。
+//!
+//! 播放列表设为 [`PlaybackTransition::Gapless`] 时不套用上述规则，返回 [`CrossfadePlan::gapless`]：
+//! 前端混音器把预加载好的下一首排在当前解码流结束的那一个采样上开始，中间不插静音也不淡化。
+//!
+//! 过渡期间两首的增益按 [`CrossfadeCurve`] 变化：[`curve_gains`] 给出某一进度处淡出 / 淡入两方的增益，
+//! 前端混音器用 [`curve_table`] 的采样表调用 `setValueCurveAtTime`，离线渲染逐帧调用 [`curve_gains`]。
+//! 自定义曲线由控制点定义，保存前经 [`validate_curve_points`] 校验。
+
+use super::fade;
+use super::settings::{
+    CrossfadeCurve, CrossfadeSettings, CurvePoint, PlaybackTransition, ShortTrackThresholds, VolumeCurve,
+};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+/// 允许设置的最长过渡时长（毫秒）。
+pub const MAX_CROSSFADE_MS: u32 = 12_000;
+
+/// 自定义曲线最多的控制点数。
+pub const MAX_CURVE_POINTS: usize = 32;
+
+/// 曲线采样表的默认点数与上限。
+pub const DEFAULT_CURVE_SAMPLES: usize = 256;
+pub const MAX_CURVE_SAMPLES: usize = 4096;
+
+/// 过渡时长被缩短的原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossfadeLimit {
+    /// 当前歌曲太短
+    CurrentTrack,
+    /// 下一首太短
+    NextTrack,
+    /// 当前歌曲剩余时间不够（如拖到结尾附近）
+    RemainingTime,
+}
+
+/// 一次切歌的过渡方案；`duration_ms` 为 0 表示直接切歌。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossfadePlan {
+    /// 切歌方式；无缝衔接时其余字段均为 0 / `None`
+    #[serde(default)]
+    pub transition: PlaybackTransition,
+    /// 实际过渡时长（毫秒）
+    pub duration_ms: u32,
+    /// 设置的过渡时长；关闭交叉淡化时为 0
+    pub requested_ms: u32,
+    /// 被缩短时的主要原因（最后生效的限制）
+    pub limited_by: Option<CrossfadeLimit>,
+    /// 增益曲线；自定义曲线的控制点在播放设置中
+    #[serde(default)]
+    pub curve: CrossfadeCurve,
+}
+
+impl ShortTrackThresholds {
+    /// 把 `requested_ms` 缩短到适合这两首歌的时长；未知的时长传 `None` 即不参与限制。
+    ///
+    /// 只有被缩短的过渡才会因不足 `min_crossfade_ms` 而取消，用户本来就设得很短的过渡保持原样。
+    pub fn fit(
+        &self,
+        requested_ms: u32,
+        current_ms: Option<u64>,
+        remaining_ms: Option<u64>,
+        next_ms: Option<u64>,
+    ) -> CrossfadePlan {
+        let ratio = self.min_track_ratio.max(1.0) as f64;
+        let mut duration = requested_ms as u64;
+        let mut limited_by = None;
+        let limits = [
+            (current_ms.map(|ms| (ms as f64 / ratio) as u64), CrossfadeLimit::CurrentTrack),
+            (next_ms.map(|ms| (ms as f64 / ratio) as u64), CrossfadeLimit::NextTrack),
+            (remaining_ms, CrossfadeLimit::RemainingTime),
+        ];
+        for (cap, limit) in limits {
+            if let Some(cap) = cap.filter(|&cap| cap < duration) {
+                duration = cap;
+                limited_by = Some(limit);
+            }
+        }
+        if limited_by.is_some() && duration < self.min_crossfade_ms as u64 {
+            duration = 0;
+        }
+        CrossfadePlan {
+            transition: PlaybackTransition::Crossfade,
+            duration_ms: duration as u32,
+            requested_ms,
+            limited_by,
+            curve: CrossfadeCurve::default(),
+        }
+    }
+}
+
+impl CrossfadePlan {
+    /// 无缝衔接的方案：下一首在当前歌曲解码流结束处紧接开始。
+    pub fn gapless() -> Self {
+        Self {
+            transition: PlaybackTransition::Gapless,
+            duration_ms: 0,
+            requested_ms: 0,
+            limited_by: None,
+            curve: CrossfadeCurve::default(),
+        }
+    }
+}
+
+/// 按设置给出从当前歌曲切到下一首的过渡方案。
+///
+/// `current_ms` / `next_ms` 为两首的时长，`remaining_ms` 为当前歌曲还剩多久；未知时传 `None`。
+pub fn plan(
+    settings: &CrossfadeSettings,
+    current_ms: Option<u64>,
+    remaining_ms: Option<u64>,
+    next_ms: Option<u64>,
+) -> CrossfadePlan {
+    if !settings.enabled || settings.duration_ms == 0 {
+        return CrossfadePlan {
+            transition: PlaybackTransition::Crossfade,
+            duration_ms: 0,
+            requested_ms: 0,
+            limited_by: None,
+            curve: settings.curve,
+        };
+    }
+    CrossfadePlan {
+        curve: settings.curve,
+        ..settings
+            .short_tracks
+            .fit(settings.duration_ms, current_ms, remaining_ms, next_ms)
+    }
+}
+
+/// 校验自定义曲线的控制点：2 ~ [`MAX_CURVE_POINTS`] 个，进度严格递增，进度与增益都在 0 ~ 1 之间，
+/// 且从 (0, 0) 开始、到 (1, 1) 结束，淡入淡出的起止处不会跳变。
+pub fn validate_curve_points(points: &[CurvePoint]) -> Result<(), String> {
+    if !(2..=MAX_CURVE_POINTS).contains(&points.len()) {
+        return Err(format!("自定义曲线需要 2 ~ {} 个控制点", MAX_CURVE_POINTS));
+    }
+    for p in points {
+        if !(0.0..=1.0).contains(&p.t) || !(0.0..=1.0).contains(&p.gain) {
+            return Err(format!("控制点 ({}, {}) 超出范围（0 ~ 1）", p.t, p.gain));
+        }
+    }
+    if points.windows(2).any(|w| w[1].t <= w[0].t) {
+        return Err("控制点的进度必须严格递增".to_string());
+    }
+    let (first, last) = (points[0], points[points.len() - 1]);
+    if (first.t, first.gain) != (0.0, 0.0) || (last.t, last.gain) != (1.0, 1.0) {
+        return Err("自定义曲线必须从 (0, 0) 开始、到 (1, 1) 结束".to_string());
+    }
+    Ok(())
+}
+
+/// 淡入一方在进度 `t`（0.0 ~ 1.0）处的增益。自定义曲线的控制点无效时按等功率处理。
+fn fade_in_gain(curve: CrossfadeCurve, points: &[CurvePoint], t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    match curve {
+        CrossfadeCurve::Linear => VolumeCurve::Linear.gain(t),
+        CrossfadeCurve::Logarithmic => VolumeCurve::Logarithmic.gain(t),
+        CrossfadeCurve::SCurve => fade::gain(t),
+        CrossfadeCurve::Custom if validate_curve_points(points).is_ok() => interpolate(points, t),
+        CrossfadeCurve::EqualPower | CrossfadeCurve::Custom => (t * FRAC_PI_2).sin(),
+    }
+}
+
+/// 控制点间线性插值；`points` 已通过校验。
+fn interpolate(points: &[CurvePoint], t: f32) -> f32 {
+    let i = points.partition_point(|p| p.t < t).clamp(1, points.len() - 1);
+    let (a, b) = (points[i - 1], points[i]);
+    a.gain + (b.gain - a.gain) * (t - a.t) / (b.t - a.t)
+}
+
+/// 进度 `t` 处（淡出一方增益，淡入一方增益）；淡出按淡入曲线倒序，即 `fade_in(1 - t)`。
+pub fn curve_gains(curve: CrossfadeCurve, points: &[CurvePoint], t: f32) -> (f32, f32) {
+    (fade_in_gain(curve, points, 1.0 - t), fade_in_gain(curve, points, t))
+}
+
+/// 淡入曲线的等距采样表（首尾为进度 0 与 1），点数限制在 2 ~ [`MAX_CURVE_SAMPLES`]；
+/// 前端混音器对淡入一方直接使用，对淡出一方倒序使用。
+pub fn curve_table(curve: CrossfadeCurve, points: &[CurvePoint], samples: usize) -> Vec<f32> {
+    let samples = samples.clamp(2, MAX_CURVE_SAMPLES);
+    (0..samples)
+        .map(|i| fade_in_gain(curve, points, i as f32 / (samples - 1) as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_tracks_shorten_or_skip_crossfade() {
+        let settings = CrossfadeSettings {
+            enabled: true,
+            ..CrossfadeSettings::default()
+        };
+        let full = plan(&settings, Some(240_000), Some(60_000), Some(200_000));
+        assert_eq!((full.duration_ms, full.limited_by), (6000, None));
+
+        // 8 秒的过场：过渡缩短到一半时长
+        let interlude = plan(&settings, Some(240_000), None, Some(8000));
+        assert_eq!((interlude.duration_ms, interlude.limited_by), (4000, Some(CrossfadeLimit::NextTrack)));
+
+        // 剩余时间限制优先生效；缩短到 1 秒以下直接切歌
+        let late = plan(&settings, Some(240_000), Some(2500), Some(200_000));
+        assert_eq!((late.duration_ms, late.limited_by), (2500, Some(CrossfadeLimit::RemainingTime)));
+        let jingle = plan(&settings, Some(1500), None, Some(200_000));
+        assert_eq!((jingle.duration_ms, jingle.limited_by), (0, Some(CrossfadeLimit::CurrentTrack)));
+
+        // 设得很短的过渡不受最小值影响；关闭时不过渡
+        assert_eq!(ShortTrackThresholds::default().fit(500, Some(60_000), None, None).duration_ms, 500);
+        assert_eq!(plan(&CrossfadeSettings::default(), None, None, None).duration_ms, 0);
+    }
+
+    #[test]
+    fn test_gapless_plan_and_legacy_records() {
+        let mut settings = crate::module::playback::PlaybackSettings::default();
+        settings.playlist_transitions.insert("live".into(), PlaybackTransition::Gapless);
+        assert_eq!(settings.playlist_transition("live"), PlaybackTransition::Gapless);
+        assert_eq!(settings.playlist_transition("other"), PlaybackTransition::Crossfade);
+        assert_eq!(CrossfadePlan::gapless().duration_ms, 0);
+
+        // 旧的过渡日志没有 transition 字段，按交叉淡化读取
+        let legacy: CrossfadePlan =
+            serde_json::from_str(r#"{"duration_ms":6000,"requested_ms":6000,"limited_by":null}"#).unwrap();
+        assert_eq!(legacy.transition, PlaybackTransition::Crossfade);
+        assert_eq!(legacy.curve, CrossfadeCurve::EqualPower);
+    }
+
+    #[test]
+    fn test_curves_and_custom_points() {
+        // 等功率：全程功率之和为 1；线性在中点下陷一半
+        for i in 0..=10 {
+            let (out, inn) = curve_gains(CrossfadeCurve::EqualPower, &[], i as f32 / 10.0);
+            assert!((out * out + inn * inn - 1.0).abs() < 1e-5);
+        }
+        let (out, inn) = curve_gains(CrossfadeCurve::Linear, &[], 0.5);
+        assert!((out * out + inn * inn - 0.5).abs() < 1e-5);
+
+        let point = |t, gain| CurvePoint { t, gain };
+        let points = vec![point(0.0, 0.0), point(0.25, 0.75), point(1.0, 1.0)];
+        validate_curve_points(&points).unwrap();
+        let table = curve_table(CrossfadeCurve::Custom, &points, 5);
+        assert_eq!(table.len(), 5);
+        assert!((table[1] - 0.75).abs() < 1e-6);
+        assert!((table[2] - (0.75 + 0.25 / 3.0)).abs() < 1e-6);
+        assert_eq!((table[0], table[4]), (0.0, 1.0));
+
+        assert!(validate_curve_points(&[point(0.0, 0.0)]).is_err());
+        assert!(validate_curve_points(&[point(0.0, 0.0), point(0.5, 0.5), point(0.5, 0.6), point(1.0, 1.0)]).is_err());
+        assert!(validate_curve_points(&[point(0.0, 0.2), point(1.0, 1.0)]).is_err());
+        assert!(validate_curve_points(&[point(0.0, 0.0), point(1.0, 1.5)]).is_err());
+        // 无效的自定义曲线按等功率处理
+        assert_eq!(
+            curve_gains(CrossfadeCurve::Custom, &[], 0.3),
+            curve_gains(CrossfadeCurve::EqualPower, &[], 0.3)
+        );
+    }
+}
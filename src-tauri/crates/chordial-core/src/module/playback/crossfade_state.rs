@@ -0,0 +1,176 @@
+//! 进行中的交叉淡化 — 查询过渡进度，或让用户中途取消开始得太早的过渡。
+//!
+//! 过渡由前端混音器执行：开始时调用 [`CrossfadeTracker::begin`] 登记前后两首与过渡时长，
+//! 结束时照常上报 [`TransitionReport`](super::TransitionReport)（同时清除登记）。
+//! 取消时由 [`CrossfadeTracker::cancel`] 清除登记并返回取消方案，调用方发布
+//! [`AppEvent::CrossfadeCancelled`](crate::module::events::AppEvent::CrossfadeCancelled)，
+//! 前端混音器按 [`CrossfadeKeep`] 停掉另一首并把保留的一首恢复到满音量。
+//!
+//! 进度按登记以来的真实时间推算；超过过渡时长仍未上报结束的视为已结束。
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 取消过渡时保留哪一首。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum CrossfadeKeep {
+    /// 继续播放当前（淡出中的）歌曲，停掉下一首
+    Current,
+    /// 立即切到下一首，停掉当前歌曲
+    Next,
+}
+
+/// 交叉淡化状态快照。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrossfadeStatus {
+    pub active: bool,
+    /// 淡出中的歌曲
+    pub from_song_id: Option<String>,
+    /// 淡入中的歌曲
+    pub to_song_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_title: Option<String>,
+    /// 过渡进度（0 ~ 100）
+    pub progress_pct: f32,
+    pub elapsed_ms: u64,
+    pub duration_ms: u32,
+}
+
+impl CrossfadeStatus {
+    fn idle() -> Self {
+        Self {
+            active: false,
+            from_song_id: None,
+            to_song_id: None,
+            from_title: None,
+            to_title: None,
+            progress_pct: 0.0,
+            elapsed_ms: 0,
+            duration_ms: 0,
+        }
+    }
+}
+
+/// 一次取消：前端按 `keep` 停掉另一首。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrossfadeCancel {
+    pub from_song_id: String,
+    pub to_song_id: String,
+    pub keep: CrossfadeKeep,
+}
+
+struct ActiveCrossfade {
+    from_song_id: String,
+    to_song_id: String,
+    duration_ms: u32,
+    started: Instant,
+}
+
+impl ActiveCrossfade {
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed_ms() >= self.duration_ms as u64
+    }
+}
+
+/// 进行中的交叉淡化登记（同一时间最多一个）。
+#[derive(Default)]
+pub struct CrossfadeTracker {
+    active: Mutex<Option<ActiveCrossfade>>,
+}
+
+impl CrossfadeTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一次开始的过渡，替换之前的登记。时长为 0（直接切歌 / 无缝衔接）时不登记。
+    pub fn begin(&self, from_song_id: &str, to_song_id: &str, duration_ms: u32) {
+        let mut active = self.active.lock();
+        *active = (duration_ms > 0).then(|| ActiveCrossfade {
+            from_song_id: from_song_id.to_string(),
+            to_song_id: to_song_id.to_string(),
+            duration_ms,
+            started: Instant::now(),
+        });
+    }
+
+    /// 过渡结束（上报过渡记录）时清除登记；`to_song_id` 不是当前登记的过渡时忽略。
+    pub fn finish(&self, to_song_id: &str) {
+        let mut active = self.active.lock();
+        if active.as_ref().is_some_and(|a| a.to_song_id == to_song_id) {
+            *active = None;
+        }
+    }
+
+    /// 当前状态；没有进行中的过渡时 `active` 为 false，标题由调用方补上。
+    pub fn status(&self) -> CrossfadeStatus {
+        let active = self.active.lock();
+        match active.as_ref().filter(|a| !a.finished()) {
+            Some(a) => {
+                let elapsed_ms = a.elapsed_ms();
+                CrossfadeStatus {
+                    active: true,
+                    from_song_id: Some(a.from_song_id.clone()),
+                    to_song_id: Some(a.to_song_id.clone()),
+                    progress_pct: (elapsed_ms as f32 / a.duration_ms as f32 * 100.0).min(100.0),
+                    elapsed_ms,
+                    duration_ms: a.duration_ms,
+                    ..CrossfadeStatus::idle()
+                }
+            }
+            None => CrossfadeStatus::idle(),
+        }
+    }
+
+    /// 取消进行中的过渡并清除登记；没有进行中的过渡时返回错误。
+    pub fn cancel(&self, keep: CrossfadeKeep) -> Result<CrossfadeCancel, String> {
+        let mut active = self.active.lock();
+        match active.take().filter(|a| !a.finished()) {
+            Some(a) => Ok(CrossfadeCancel {
+                from_song_id: a.from_song_id,
+                to_song_id: a.to_song_id,
+                keep,
+            }),
+            None => Err("没有进行中的交叉淡化".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_begin_cancel_and_finish() {
+        let tracker = CrossfadeTracker::new();
+        assert!(!tracker.status().active);
+        assert!(tracker.cancel(CrossfadeKeep::Current).is_err());
+
+        tracker.begin("a", "b", 60_000);
+        let status = tracker.status();
+        assert!(status.active);
+        assert_eq!(status.to_song_id.as_deref(), Some("b"));
+        assert!(status.progress_pct < 50.0);
+        let cancel = tracker.cancel(CrossfadeKeep::Next).unwrap();
+        assert_eq!((cancel.from_song_id.as_str(), cancel.keep), ("a", CrossfadeKeep::Next));
+        assert!(!tracker.status().active);
+
+        // 其他过渡的结束上报不清除当前登记；时长为 0 不登记
+        tracker.begin("b", "c", 60_000);
+        tracker.finish("b");
+        assert!(tracker.status().active);
+        tracker.finish("c");
+        assert!(!tracker.status().active);
+        tracker.begin("c", "d", 0);
+        assert!(!tracker.status().active);
+    }
+}
@@ -0,0 +1,211 @@
+//! 播放质量指示 — 汇总当前歌曲的编码、解码速度、缓冲、重采样与 DSP 链，供播放器的调试浮层使用。
+//!
+//! 实时播放由前端 `<audio>` 元素解码，缓冲水位、卡顿次数和输出采样率只有前端知道：
+//! 前端查询时随请求附上 [`PlayerReport`]，后端与文件技术信息和当前播放设置合并成 [`DecodeStats`]。
+//! 解码速度由后端用同一套解码器试解开头 [`BENCH_SECS`] 秒测得，表示这个文件在本机上的解码余量；
+//! 结果按文件路径缓存。
+
+use super::gain::{GainSource, TrackGain};
+use super::settings::{StretchParams, VolumeCurve};
+use crate::module::analysis::decode;
+use crate::module::analysis::TechnicalInfo;
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 测量解码速度时解码的音频时长（秒）。
+pub const BENCH_SECS: u64 = 10;
+
+/// 缓冲水位按此时长（毫秒）计满；离结尾更近时以剩余时长计。
+pub const BUFFER_TARGET_MS: f64 = 30_000.0;
+
+/// 前端播放器上报的实时状态。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PlayerReport {
+    /// 当前播放位置（毫秒）
+    pub position_ms: f64,
+    /// 当前位置之后已缓冲的时长（毫秒）
+    pub buffered_ms: f64,
+    /// 歌曲时长（毫秒），未知时为 0
+    pub duration_ms: f64,
+    /// 本曲开始播放以来的卡顿（缓冲耗尽）次数
+    pub underruns: u32,
+    /// 输出设备采样率（`AudioContext.sampleRate`），未知时省略
+    pub output_sample_rate: Option<u32>,
+}
+
+/// 缓冲状态。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BufferStatus {
+    pub buffered_ms: f64,
+    /// 水位（0.0 ~ 1.0）
+    pub fill: f32,
+    pub underruns: u32,
+}
+
+/// 重采样状态；任一采样率未知时 `active` 为 `None`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ResamplerStatus {
+    pub active: Option<bool>,
+    pub from_hz: Option<u32>,
+    pub to_hz: Option<u32>,
+}
+
+/// DSP 链中的一级处理，按信号经过的顺序排列。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum DspStage {
+    /// 单曲增益（覆盖值 / ReplayGain）
+    Gain { gain_db: f32, source: GainSource },
+    /// 变速
+    TimeStretch { rate: f64, params: StretchParams },
+    /// 静音跳过
+    SilenceSkip { min_silence_ms: u64 },
+    /// 卡拉 OK 人声压低
+    Karaoke { vocal_gain_db: f32 },
+    /// 切歌交叉淡化
+    Crossfade { duration_ms: u32 },
+    /// 均衡器（当前输出设备的配置）
+    Equalizer { bands: usize, preamp_db: f32 },
+    /// 声道平衡
+    Balance { balance: f32 },
+    /// 交叉馈送
+    Crossfeed { level: f32 },
+    /// 音量曲线
+    Volume { volume: f32, curve: VolumeCurve, gain: f32 },
+    /// 输出限幅器
+    Limiter { threshold_db: f32 },
+    /// 输出延迟补偿（只影响歌词 / 可视化的时间轴）
+    LatencyCompensation { latency_ms: u32 },
+}
+
+/// 当前歌曲的解码与播放质量。
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodeStats {
+    pub song_id: String,
+    pub codec: String,
+    pub container: String,
+    pub lossless: bool,
+    pub sample_rate: Option<u32>,
+    pub bit_depth: Option<u32>,
+    pub channels: Option<u32>,
+    pub bitrate_kbps: Option<u32>,
+    /// 解码速度（实时的倍数），测量失败时为 `None`
+    pub decode_speed: Option<f32>,
+    /// 前端未上报时为 `None`
+    pub buffer: Option<BufferStatus>,
+    pub resampler: ResamplerStatus,
+    pub dsp_chain: Vec<DspStage>,
+}
+
+impl DecodeStats {
+    pub fn new(song_id: &str, info: &TechnicalInfo, decode_speed: Option<f32>) -> Self {
+        Self {
+            song_id: song_id.to_string(),
+            codec: info.codec.clone(),
+            container: info.container.clone(),
+            lossless: info.lossless,
+            sample_rate: info.sample_rate,
+            bit_depth: info.bit_depth,
+            channels: info.channels,
+            bitrate_kbps: info.bitrate_kbps,
+            decode_speed,
+            buffer: None,
+            resampler: resampler(info.sample_rate, None),
+            dsp_chain: Vec::new(),
+        }
+    }
+
+    /// 合并前端上报的缓冲与输出采样率。
+    pub fn with_report(mut self, report: &PlayerReport) -> Self {
+        self.buffer = Some(buffer_status(report));
+        self.resampler = resampler(self.sample_rate, report.output_sample_rate);
+        self
+    }
+
+    pub fn with_dsp_chain(mut self, chain: Vec<DspStage>) -> Self {
+        self.dsp_chain = chain;
+        self
+    }
+}
+
+fn buffer_status(report: &PlayerReport) -> BufferStatus {
+    let remaining = if report.duration_ms > 0.0 {
+        (report.duration_ms - report.position_ms).max(0.0)
+    } else {
+        BUFFER_TARGET_MS
+    };
+    let target = remaining.min(BUFFER_TARGET_MS);
+    let fill = if target <= 0.0 {
+        1.0
+    } else {
+        (report.buffered_ms.max(0.0) / target).min(1.0) as f32
+    };
+    BufferStatus {
+        buffered_ms: report.buffered_ms.max(0.0),
+        fill,
+        underruns: report.underruns,
+    }
+}
+
+fn resampler(from_hz: Option<u32>, to_hz: Option<u32>) -> ResamplerStatus {
+    ResamplerStatus {
+        active: from_hz.zip(to_hz).map(|(from, to)| from != to),
+        from_hz,
+        to_hz,
+    }
+}
+
+/// 单曲增益不为 0 时对应的 DSP 级。
+pub fn gain_stage(gain: &TrackGain) -> Option<DspStage> {
+    (gain.source != GainSource::None && gain.gain_db != 0.0).then_some(DspStage::Gain {
+        gain_db: gain.gain_db,
+        source: gain.source,
+    })
+}
+
+/// 解码文件开头 [`BENCH_SECS`] 秒，返回解码速度（实时的倍数）。
+pub fn measure_decode_speed(path: &PlatformPath) -> Result<f32, String> {
+    let started = Instant::now();
+    let mut frames = 0u64;
+    let mut rate = 0u32;
+    decode::decode_while(path, |block| {
+        rate = block.sample_rate;
+        frames += (block.samples.len() / block.channels.max(1)) as u64;
+        frames < BENCH_SECS * rate as u64
+    })?;
+    if frames == 0 || rate == 0 {
+        return Err("没有解码出音频数据".to_string());
+    }
+    let elapsed = started.elapsed().as_secs_f64().max(1e-6);
+    Ok((frames as f64 / rate as f64 / elapsed) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_fill_and_resampler() {
+        let report = PlayerReport {
+            position_ms: 170_000.0,
+            buffered_ms: 5_000.0,
+            duration_ms: 180_000.0,
+            underruns: 2,
+            output_sample_rate: Some(48_000),
+        };
+        // 离结尾 10 秒，缓冲 5 秒即半满
+        let buffer = buffer_status(&report);
+        assert_eq!((buffer.fill, buffer.underruns), (0.5, 2));
+        let early = buffer_status(&PlayerReport {
+            position_ms: 0.0,
+            ..report.clone()
+        });
+        assert!((early.fill - 1.0 / 6.0).abs() < 1e-6);
+
+        assert_eq!(resampler(Some(44_100), Some(48_000)).active, Some(true));
+        assert_eq!(resampler(Some(48_000), Some(48_000)).active, Some(false));
+        assert_eq!(resampler(Some(44_100), None).active, None);
+    }
+}
@@ -0,0 +1,84 @@
+//! 抖动 — 浮点样本降到 16 bit 时加入微量噪声，把量化失真变成平稳的底噪。
+//!
+//! 直接四舍五入时，淡入淡出尾部等极低电平的信号会被量化成与信号相关的失真（听感为「沙沙」的颗粒声）；
+//! 加入 ±1 LSB 的三角分布（TPDF）噪声后失真与信号解相关。噪声整形在此基础上把量化误差
+//! 反馈到下一个样本（一阶高通），底噪更多地落在人耳不敏感的高频。
+
+use super::settings::DitherMode;
+
+/// 逐样本量化器，按声道保存噪声整形的误差状态。
+pub struct Ditherer {
+    mode: DitherMode,
+    /// 每个声道上一个样本的量化误差
+    error: Vec<f32>,
+    /// xorshift32 状态
+    rng: u32,
+}
+
+impl Ditherer {
+    pub fn new(mode: DitherMode, channels: usize) -> Self {
+        Self {
+            mode,
+            error: vec![0.0; channels.max(1)],
+            rng: 0x9E37_79B9,
+        }
+    }
+
+    /// 把 [-1, 1] 的浮点样本量化为 i16（超出部分削波）。
+    pub fn quantize(&mut self, sample: f32, channel: usize) -> i16 {
+        let scaled = sample.clamp(-1.0, 1.0) * i16::MAX as f32;
+        let quantized = match self.mode {
+            DitherMode::Off => scaled.round(),
+            DitherMode::Tpdf => (scaled + self.tpdf()).round(),
+            DitherMode::NoiseShaped => {
+                let noise = self.tpdf();
+                let slot = channel % self.error.len();
+                let error = &mut self.error[slot];
+                let target = scaled - *error;
+                let q = (target + noise).round();
+                *error = q - target;
+                q
+            }
+        };
+        quantized.clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }
+
+    /// ±1 LSB 的三角分布噪声。
+    fn tpdf(&mut self) -> f32 {
+        next_uniform(&mut self.rng) - next_uniform(&mut self.rng)
+    }
+}
+
+/// [0, 1) 均匀分布。
+fn next_uniform(state: &mut u32) -> f32 {
+    let mut x = *state;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    *state = x;
+    (x >> 8) as f32 / (1u32 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dither_decorrelates_low_level_signal() {
+        // 0.3 LSB 的直流：不抖动时全部量化为 0，抖动后平均值保留信号
+        let level = 0.3 / i16::MAX as f32;
+        let n = 20_000;
+        let mut plain = Ditherer::new(DitherMode::Off, 1);
+        assert!((0..n).all(|_| plain.quantize(level, 0) == 0));
+
+        for mode in [DitherMode::Tpdf, DitherMode::NoiseShaped] {
+            let mut d = Ditherer::new(mode, 1);
+            let sum: i64 = (0..n).map(|_| d.quantize(level, 0) as i64).sum();
+            let mean = sum as f64 / n as f64;
+            assert!((mean - 0.3).abs() < 0.05, "{:?} mean {}", mode, mean);
+        }
+
+        let mut clipped = Ditherer::new(DitherMode::Off, 2);
+        assert_eq!(clipped.quantize(2.0, 1), i16::MAX);
+    }
+}
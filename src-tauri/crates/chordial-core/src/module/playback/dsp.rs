@@ -0,0 +1,180 @@
+//! 按输出设备保存的 DSP 配置 — 均衡器、声道平衡、交叉馈送、限幅器。
+//!
+//! 不同音箱 / 耳机需要不同的均衡，配置以输出设备 ID（浏览器 `MediaDeviceInfo.deviceId`，
+//! 系统默认设备为 [`DEFAULT_DEVICE`]）为键存放在播放设置中。前端切换输出设备时上报新设备，
+//! 后端返回该设备的配置，前端据此重建滤波器链；从未配置过的设备使用平直的默认配置。
+
+use serde::{Deserialize, Serialize};
+
+/// 系统默认输出设备的 ID。
+pub const DEFAULT_DEVICE: &str = "default";
+
+/// 均衡器最多的频段数。
+pub const MAX_EQ_BANDS: usize = 31;
+
+/// 单个频段 / 前级增益的范围（±dB）。
+pub const MAX_EQ_GAIN_DB: f32 = 24.0;
+
+/// 均衡器滤波器类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EqFilter {
+    /// 峰值（钟形）
+    #[default]
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+/// 均衡器的一个频段。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EqBand {
+    #[serde(default)]
+    pub filter: EqFilter,
+    pub freq_hz: f32,
+    pub gain_db: f32,
+    /// 品质因数
+    #[serde(default = "default_q")]
+    pub q: f32,
+}
+
+fn default_q() -> f32 {
+    std::f32::consts::FRAC_1_SQRT_2
+}
+
+/// 参数均衡器。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EqualizerSettings {
+    pub enabled: bool,
+    /// 前级增益（dB），用于给提升的频段留出余量
+    pub preamp_db: f32,
+    pub bands: Vec<EqBand>,
+}
+
+/// 交叉馈送 — 耳机聆听时把少量对侧声道混入，减轻硬分离的疲劳感。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrossfeedSettings {
+    pub enabled: bool,
+    /// 混入量（0.0 ~ 1.0）
+    pub level: f32,
+}
+
+impl Default for CrossfeedSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: 0.3,
+        }
+    }
+}
+
+/// 输出限幅器，防止均衡提升后削波。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimiterSettings {
+    pub enabled: bool,
+    /// 门限（dBFS，≤ 0）
+    pub threshold_db: f32,
+}
+
+impl Default for LimiterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -1.0,
+        }
+    }
+}
+
+/// 一台输出设备的 DSP 配置。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DspProfile {
+    pub equalizer: EqualizerSettings,
+    /// 声道平衡（-1.0 全左 ~ 1.0 全右）
+    pub balance: f32,
+    pub crossfeed: CrossfeedSettings,
+    pub limiter: LimiterSettings,
+}
+
+impl DspProfile {
+    /// 检查各参数是否在允许范围内。
+    pub fn validate(&self) -> Result<(), String> {
+        let eq = &self.equalizer;
+        if eq.bands.len() > MAX_EQ_BANDS {
+            return Err(format!("均衡器频段过多（最多 {} 段）", MAX_EQ_BANDS));
+        }
+        check_gain("前级增益", eq.preamp_db)?;
+        for band in &eq.bands {
+            if !(20.0..=20_000.0).contains(&band.freq_hz) {
+                return Err(format!("频段频率 {}Hz 超出范围（20 ~ 20000Hz）", band.freq_hz));
+            }
+            check_gain("频段增益", band.gain_db)?;
+            if !(0.1..=20.0).contains(&band.q) {
+                return Err(format!("频段 Q 值 {} 超出范围（0.1 ~ 20）", band.q));
+            }
+        }
+        if !(-1.0..=1.0).contains(&self.balance) {
+            return Err(format!("声道平衡 {} 超出范围（-1 ~ 1）", self.balance));
+        }
+        if !(0.0..=1.0).contains(&self.crossfeed.level) {
+            return Err(format!("交叉馈送量 {} 超出范围（0 ~ 1）", self.crossfeed.level));
+        }
+        if !(-24.0..=0.0).contains(&self.limiter.threshold_db) {
+            return Err(format!("限幅门限 {}dB 超出范围（-24 ~ 0dB）", self.limiter.threshold_db));
+        }
+        Ok(())
+    }
+
+    /// 均衡器是否真正改变声音（启用且有非零增益）。
+    pub fn equalizer_active(&self) -> bool {
+        let eq = &self.equalizer;
+        eq.enabled && (eq.preamp_db != 0.0 || eq.bands.iter().any(|b| b.gain_db != 0.0))
+    }
+}
+
+fn check_gain(what: &str, gain_db: f32) -> Result<(), String> {
+    if (-MAX_EQ_GAIN_DB..=MAX_EQ_GAIN_DB).contains(&gain_db) {
+        Ok(())
+    } else {
+        Err(format!("{} {}dB 超出范围（±{}dB）", what, gain_db, MAX_EQ_GAIN_DB))
+    }
+}
+
+/// 规范化设备 ID：空串视为系统默认设备。
+pub fn device_key(device_id: &str) -> String {
+    match device_id.trim() {
+        "" => DEFAULT_DEVICE.to_string(),
+        id => id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_profile_ranges() {
+        let mut profile = DspProfile::default();
+        assert!(profile.validate().is_ok());
+        assert!(!profile.equalizer_active());
+
+        profile.equalizer = EqualizerSettings {
+            enabled: true,
+            preamp_db: -3.0,
+            bands: vec![EqBand { filter: EqFilter::LowShelf, freq_hz: 80.0, gain_db: 4.0, q: default_q() }],
+        };
+        assert!(profile.validate().is_ok());
+        assert!(profile.equalizer_active());
+
+        profile.equalizer.bands[0].freq_hz = 10.0;
+        assert!(profile.validate().is_err());
+        profile.equalizer.bands[0].freq_hz = 80.0;
+        profile.balance = 1.5;
+        assert!(profile.validate().is_err());
+
+        assert_eq!(device_key("  "), DEFAULT_DEVICE);
+    }
+}
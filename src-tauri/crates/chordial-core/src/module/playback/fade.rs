@@ -0,0 +1,111 @@
+//! 淡入淡出 — 开始播放、停止、拖动进度时的短时音量包络。
+//!
+//! 播放从任意采样点开始或戛然而止时，波形在该点的突变会被听成「啪」的爆音。
+//! 在这些时刻用几百毫秒的包络把音量从 0 拉起或压到 0 即可消除。
+//!
+//! 实时播放由前端混音器执行：调用 [`PlaybackManager::fade_plan`](super::PlaybackManager::fade_plan)
+//! 取得本次操作的淡入 / 淡出时长，按 [`gain`] 的升余弦曲线设置增益
+//! （Web Audio 可用 `setValueCurveAtTime`）。离线渲染直接对样本调用 [`apply_fade_in`] / [`apply_fade_out`]。
+
+use super::settings::FadeSettings;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// 单段淡入 / 淡出的最长时长（毫秒）。
+pub const MAX_FADE_MS: u32 = 1000;
+
+/// 触发淡入淡出的操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FadeAction {
+    /// 开始 / 继续播放：淡入
+    Play,
+    /// 停止 / 暂停：淡出后再停
+    Stop,
+    /// 拖动进度：旧位置淡出，跳转后淡入
+    Seek,
+}
+
+/// 一次操作的淡入淡出方案；时长为 0 表示不做该段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FadePlan {
+    pub action: FadeAction,
+    /// 操作生效前的淡出时长（毫秒）
+    pub fade_out_ms: u32,
+    /// 操作生效后的淡入时长（毫秒）
+    pub fade_in_ms: u32,
+}
+
+/// 根据设置给出某个操作的淡入淡出方案。
+pub fn plan(settings: &FadeSettings, action: FadeAction) -> FadePlan {
+    let (fade_out_ms, fade_in_ms) = if !settings.enabled {
+        (0, 0)
+    } else {
+        match action {
+            FadeAction::Play => (0, settings.play_ms),
+            FadeAction::Stop => (settings.stop_ms, 0),
+            FadeAction::Seek => (settings.seek_ms, settings.seek_ms),
+        }
+    };
+    FadePlan {
+        action,
+        fade_out_ms,
+        fade_in_ms,
+    }
+}
+
+/// 淡入曲线：进度 `t`（0.0 ~ 1.0）处的增益。升余弦两端斜率为 0，起止处都不会产生折角。
+pub fn gain(t: f32) -> f32 {
+    0.5 - 0.5 * (t.clamp(0.0, 1.0) * PI).cos()
+}
+
+/// 对交织样本的开头 `frames` 帧做淡入。
+pub fn apply_fade_in(samples: &mut [f32], channels: usize, frames: usize) {
+    let frames = frames.min(samples.len() / channels);
+    for f in 0..frames {
+        let g = gain(f as f32 / frames as f32);
+        for s in &mut samples[f * channels..(f + 1) * channels] {
+            *s *= g;
+        }
+    }
+}
+
+/// 对交织样本的末尾 `frames` 帧做淡出，最后一帧归零。
+pub fn apply_fade_out(samples: &mut [f32], channels: usize, frames: usize) {
+    let total = samples.len() / channels;
+    let frames = frames.min(total);
+    let offset = total - frames;
+    for f in 0..frames {
+        let g = gain((frames - 1 - f) as f32 / frames as f32);
+        let start = (offset + f) * channels;
+        for s in &mut samples[start..start + channels] {
+            *s *= g;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fades_start_and_end_at_silence() {
+        let mut samples = vec![1.0f32; 300];
+        apply_fade_in(&mut samples, 2, 50);
+        apply_fade_out(&mut samples, 2, 50);
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[299], 0.0);
+        assert_eq!(samples[150], 1.0);
+        // 淡入段单调上升
+        assert!(samples[..100].chunks(2).zip(samples[2..100].chunks(2)).all(|(a, b)| a[0] <= b[0]));
+
+        let off = FadeSettings {
+            enabled: false,
+            ..FadeSettings::default()
+        };
+        let seek = plan(&off, FadeAction::Seek);
+        assert_eq!((seek.fade_out_ms, seek.fade_in_ms), (0, 0));
+        let stop = plan(&FadeSettings::default(), FadeAction::Stop);
+        assert!(stop.fade_out_ms > 0 && stop.fade_in_ms == 0);
+    }
+}
@@ -0,0 +1,236 @@
+//! 最小 FLAC 写入器 — 16 bit 立体声，VERBATIM 子帧。
+//!
+//! 不做预测 / 残差编码，文件体积与 WAV 相当，但带完整的 STREAMINFO 和逐帧 CRC，
+//! 任何 FLAC 解码器都能播放、可按帧定位。项目没有引入编码器依赖，
+//! 这里只实现离线渲染所需的最小子集。
+
+use super::dither::Ditherer;
+use super::settings::DitherMode;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// 每帧的采样帧数。
+pub(crate) const BLOCK_SIZE: usize = 4096;
+const CHANNELS: usize = 2;
+const BITS_PER_SAMPLE: u32 = 16;
+
+/// 流式 FLAC 写入器：样本写满一块即输出一帧，[`finish`](Self::finish) 时回填总样本数。
+///
+/// 默认写文件；[`new`](Self::new) 可写入任意可定位的输出（如内存中的 `Cursor<Vec<u8>>`）。
+pub struct FlacWriter<W: Write + Seek = BufWriter<File>> {
+    out: W,
+    sample_rate: u32,
+    /// 尚未凑满一帧的交织样本
+    pending: Vec<i16>,
+    ditherer: Ditherer,
+    frame_number: u64,
+    total_frames: u64,
+}
+
+impl FlacWriter {
+    pub fn create(path: &Path, sample_rate: u32) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("创建输出文件失败: {}", e))?;
+        Self::new(BufWriter::new(file), sample_rate)
+    }
+}
+
+impl<W: Write + Seek> FlacWriter<W> {
+    pub fn new(out: W, sample_rate: u32) -> Result<Self, String> {
+        let mut writer = Self {
+            out,
+            sample_rate,
+            pending: Vec::with_capacity(BLOCK_SIZE * CHANNELS),
+            ditherer: Ditherer::new(DitherMode::Off, CHANNELS),
+            frame_number: 0,
+            total_frames: 0,
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    /// 设置量化到 16 bit 时的抖动方式（默认不抖动）。
+    pub fn with_dither(mut self, mode: DitherMode) -> Self {
+        self.ditherer = Ditherer::new(mode, CHANNELS);
+        self
+    }
+
+    /// 写入交织的立体声样本（范围 [-1, 1]，超出部分削波）。
+    pub fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        for &s in samples {
+            let channel = self.pending.len() % CHANNELS;
+            let sample = self.ditherer.quantize(s, channel);
+            self.pending.push(sample);
+            if self.pending.len() == BLOCK_SIZE * CHANNELS {
+                self.flush_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 写出剩余样本并回填 STREAMINFO 中的总样本数，返回写入的采样帧数。
+    pub fn finish(mut self) -> Result<u64, String> {
+        self.finalize()?;
+        Ok(self.total_frames)
+    }
+
+    /// 同 [`finish`](Self::finish)，但交回底层输出。
+    pub fn into_inner(mut self) -> Result<W, String> {
+        self.finalize()?;
+        Ok(self.out)
+    }
+
+    fn finalize(&mut self) -> Result<(), String> {
+        if !self.pending.is_empty() {
+            self.flush_frame()?;
+        }
+        self.write_streaminfo_at_start()?;
+        self.out.flush().map_err(io_err)
+    }
+
+    fn write_header(&mut self) -> Result<(), String> {
+        self.out.write_all(b"fLaC").map_err(io_err)?;
+        self.write_streaminfo()
+    }
+
+    fn write_streaminfo_at_start(&mut self) -> Result<(), String> {
+        self.out.seek(SeekFrom::Start(4)).map_err(io_err)?;
+        self.write_streaminfo()?;
+        self.out.seek(SeekFrom::End(0)).map_err(io_err)?;
+        Ok(())
+    }
+
+    fn write_streaminfo(&mut self) -> Result<(), String> {
+        let block = streaminfo_block(self.sample_rate, self.total_frames, true);
+        self.out.write_all(&block).map_err(io_err)
+    }
+
+    fn flush_frame(&mut self) -> Result<(), String> {
+        let frame = encode_frame(&self.pending, self.frame_number);
+        self.out.write_all(&frame).map_err(io_err)?;
+        self.total_frames += (self.pending.len() / CHANNELS) as u64;
+        self.pending.clear();
+        self.frame_number += 1;
+        Ok(())
+    }
+}
+
+/// STREAMINFO 元数据块（含 4 字节块头）。帧大小与 MD5 填 0 表示未知。
+pub(crate) fn streaminfo_block(sample_rate: u32, total_frames: u64, last: bool) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.put(last as u64, 1); // last-metadata-block
+    bits.put(0, 7); // STREAMINFO
+    bits.put(34, 24);
+    bits.put(BLOCK_SIZE as u64, 16);
+    bits.put(BLOCK_SIZE as u64, 16);
+    bits.put(0, 24);
+    bits.put(0, 24);
+    bits.put(sample_rate as u64, 20);
+    bits.put((CHANNELS - 1) as u64, 3);
+    bits.put((BITS_PER_SAMPLE - 1) as u64, 5);
+    bits.put(total_frames, 36);
+    bits.put(0, 64);
+    bits.put(0, 64);
+    bits.bytes
+}
+
+/// 把一块交织的 16 bit 立体声样本（最多 [`BLOCK_SIZE`] 帧）编码为一个 VERBATIM 帧。
+pub(crate) fn encode_frame(pending: &[i16], frame_number: u64) -> Vec<u8> {
+    let frames = pending.len() / CHANNELS;
+    let mut bits = BitWriter::default();
+    bits.put(0b11_1111_1111_1110, 14); // sync
+    bits.put(0, 1);
+    bits.put(0, 1); // 固定块大小
+    bits.put(0b0111, 4); // 块大小在帧头末尾以 16 bit 给出
+    bits.put(0, 4); // 采样率取自 STREAMINFO
+    bits.put(0b0001, 4); // 双声道，独立编码
+    bits.put(0b100, 3); // 16 bit
+    bits.put(0, 1);
+    bits.put_utf8(frame_number);
+    bits.put((frames - 1) as u64, 16);
+    let crc8 = crc8(&bits.bytes);
+    bits.put(crc8 as u64, 8);
+
+    for ch in 0..CHANNELS {
+        bits.put(0b0000_0010, 8); // VERBATIM，无 wasted bits
+        for frame in 0..frames {
+            bits.put(pending[frame * CHANNELS + ch] as u16 as u64, 16);
+        }
+    }
+    let crc16 = crc16(&bits.bytes);
+    bits.put(crc16 as u64, 16);
+    bits.bytes
+}
+
+fn io_err(e: std::io::Error) -> String {
+    format!("写入输出文件失败: {}", e)
+}
+
+/// 按位写入（大端），调用方保证在字节边界结束。
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    nbits: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            self.acc = (self.acc << 1) | ((value >> i) & 1);
+            self.nbits += 1;
+            if self.nbits == 8 {
+                self.bytes.push(self.acc as u8);
+                self.acc = 0;
+                self.nbits = 0;
+            }
+        }
+    }
+
+    /// FLAC 帧号使用的「UTF-8」变长编码（最多 36 bit）。
+    fn put_utf8(&mut self, value: u64) {
+        if value < 0x80 {
+            self.put(value, 8);
+            return;
+        }
+        let mut len = 2;
+        while len < 7 && value >= 1u64 << (5 * len + 1) {
+            len += 1;
+        }
+        let lead = (0xFFu64 << (8 - len)) & 0xFF;
+        self.put(lead | (value >> (6 * (len - 1))), 8);
+        for i in (0..len - 1).rev() {
+            self.put(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+        }
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
@@ -0,0 +1,124 @@
+//! 音轨增益 — 决定每首歌播放时实际应用的增益。
+//!
+//! 优先级：用户为单曲设置的覆盖值 > 文件中的 ReplayGain 音轨增益 > 0 dB。
+//! 覆盖值按歌曲 ID 保存在库的 `track_gain` 键下，随音乐库一起落盘与快照。
+//!
+//! 前端在开播前查询 [`TrackGain`] 并按 `linear` 设置增益；预览接口对队列中接下来的几首
+//! 逐一给出结果与来源，便于排查「这首为什么特别小声」。
+
+use crate::module::music_library::models::Song;
+use crate::module::storage::persistent::PersistentStore;
+use serde::Serialize;
+
+pub const OVERRIDE_KEY: &str = "track_gain";
+
+/// 覆盖值的允许范围（dB）。
+pub const MIN_TRACK_GAIN_DB: f32 = -24.0;
+pub const MAX_TRACK_GAIN_DB: f32 = 24.0;
+
+/// 预览默认查看的队列项数。
+pub const PREVIEW_DEFAULT_COUNT: usize = 5;
+
+/// 增益的来源。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GainSource {
+    /// 用户覆盖值
+    Override,
+    /// 文件的 ReplayGain 标签
+    ReplayGain,
+    /// 两者都没有，不做调整
+    None,
+}
+
+/// 一首歌实际应用的增益。
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackGain {
+    pub song_id: String,
+    pub title: String,
+    /// 最终增益（dB）
+    pub gain_db: f32,
+    /// 换算后的线性倍数，前端直接乘到音量上
+    pub linear: f32,
+    pub source: GainSource,
+    /// 文件中的 ReplayGain 值（被覆盖时仍列出，方便对比）
+    pub replay_gain_db: Option<f32>,
+    pub override_db: Option<f32>,
+}
+
+/// 按优先级得出歌曲的增益。
+pub fn resolve(song: &Song, override_db: Option<f32>) -> TrackGain {
+    let (gain_db, source) = match (override_db, song.replay_gain_db) {
+        (Some(db), _) => (db, GainSource::Override),
+        (None, Some(db)) => (db, GainSource::ReplayGain),
+        (None, None) => (0.0, GainSource::None),
+    };
+    TrackGain {
+        song_id: song.id.clone(),
+        title: song.title.clone(),
+        gain_db,
+        linear: db_to_linear(gain_db),
+        source,
+        replay_gain_db: song.replay_gain_db,
+        override_db,
+    }
+}
+
+pub fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+pub fn get_override(store: &PersistentStore, song_id: &str) -> Option<f32> {
+    store.get_entry::<f32>(OVERRIDE_KEY, song_id)
+}
+
+/// 设置（`Some`）或清除（`None`）单曲覆盖值。
+pub fn set_override(store: &PersistentStore, song_id: &str, gain_db: Option<f32>) -> Result<(), String> {
+    match gain_db {
+        Some(db) => {
+            if !(MIN_TRACK_GAIN_DB..=MAX_TRACK_GAIN_DB).contains(&db) {
+                return Err(format!(
+                    "增益 {} dB 超出范围（{} ~ {} dB）",
+                    db, MIN_TRACK_GAIN_DB, MAX_TRACK_GAIN_DB
+                ));
+            }
+            store.set_subkey(OVERRIDE_KEY, song_id, &db)
+        }
+        None => {
+            store.remove_entry(OVERRIDE_KEY, song_id);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_takes_precedence_over_replay_gain() {
+        let mut song: Song = serde_json::from_value(serde_json::json!({
+            "id": "s",
+            "title": "t",
+            "artist_names": [],
+            "album_title": null,
+            "duration": 200,
+            "artist_ids": [],
+            "album_id": null,
+            "lyric_id": null,
+            "source_ids": [],
+        }))
+        .unwrap();
+        assert_eq!(resolve(&song, None).source, GainSource::None);
+        assert_eq!(resolve(&song, None).linear, 1.0);
+
+        song.replay_gain_db = Some(-8.0);
+        let rg = resolve(&song, None);
+        assert_eq!((rg.source, rg.gain_db), (GainSource::ReplayGain, -8.0));
+
+        let overridden = resolve(&song, Some(2.0));
+        assert_eq!(overridden.source, GainSource::Override);
+        assert_eq!(overridden.replay_gain_db, Some(-8.0));
+        assert!((db_to_linear(-6.0) - 0.501).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,553 @@
+//! 正在播放的队列 — 由后端持有，前端只负责出声。
+//!
+//! [`queue`](super::queue) 负责「播放专辑」等场景下一次排好的队列；排好后交给 [`LiveQueue`]，
+//! 之后的追加 / 插到下一首 / 移除 / 切歌 / 随机 / 循环都在这里完成，前端按返回的快照刷新界面。
+//!
+//! - 随机：只打乱当前歌曲之后的部分，关闭时恢复打乱前的顺序（期间追加的歌曲按追加顺序排在后面）；
+//!   有声书不参与随机，按原顺序排在打乱后的歌曲之后
+//! - 上一首：队列同时记录实际播放过的队列项（最多 [`HISTORY_LIMIT`] 项），[`previous`](LiveQueue::previous)
+//!   先按播放历史回退（随机 / 跳转后也是真正听过的那首），历史为空时才退回队列中的前一项
+//! - 循环：[`RepeatMode::One`] 切歌时留在当前歌曲，[`RepeatMode::All`] 播到队尾回到队首；
+//!   [`RepeatMode::Off`] 时队尾之后交给 [`EndOfQueueBehavior`](super::EndOfQueueBehavior) 处理
+//! - 临近结尾：前端上报剩余时长，[`announce_once`](LiveQueue::announce_once) 保证每首歌只通知一次
+//!   开始过渡，由 [`AppContext::queue_report_position`](crate::AppContext::queue_report_position)
+//!   给出下一首与交叉淡化方案
+
+use super::crossfade::CrossfadePlan;
+use super::queue::shuffle_songs;
+use crate::module::music_library::models::Song;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// 距离过渡开始还有这么久时通知前端（毫秒），留出排好下一首的时间。
+pub const TRANSITION_NOTICE_MS: u64 = 1_000;
+
+/// 播放历史最多保留的队列项数。
+pub const HISTORY_LIMIT: usize = 100;
+
+/// 循环模式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepeatMode {
+    /// 不循环（默认）
+    #[default]
+    Off,
+    /// 整个队列循环
+    All,
+    /// 单曲循环
+    One,
+}
+
+/// 队列快照。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueueSnapshot {
+    pub song_ids: Vec<String>,
+    /// 正在播放的歌曲在队列中的位置；队列为空时为 `None`
+    pub current_index: Option<usize>,
+    pub repeat: RepeatMode,
+    pub shuffle: bool,
+    /// 队列来自的播放列表（决定交叉淡化 / 无缝衔接）
+    pub playlist_id: Option<String>,
+    /// 播放历史是否非空（「上一首」可以回到历史中的歌曲）
+    pub has_history: bool,
+}
+
+/// 带歌曲详情的队列（返回给前端）。
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueView {
+    pub songs: Vec<Song>,
+    pub current_index: Option<usize>,
+    pub repeat: RepeatMode,
+    pub shuffle: bool,
+    pub playlist_id: Option<String>,
+    pub has_history: bool,
+}
+
+/// 一次切歌的结果：`song` 为 `None` 表示队列播完、应停止播放。
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStep {
+    pub song: Option<Song>,
+    pub queue: QueueView,
+}
+
+/// 当前歌曲临近结尾时给出的过渡：`starts_in_ms` 后开始下一首，按 `plan` 淡化或无缝衔接。
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueTransition {
+    pub next: Song,
+    pub plan: CrossfadePlan,
+    pub starts_in_ms: u64,
+}
+
+/// 队列中的一项。同一首歌可以出现多次，随机 / 移除时按 `key` 区分各项。
+#[derive(Debug, Clone)]
+struct Entry {
+    key: u64,
+    song_id: String,
+}
+
+#[derive(Default)]
+struct QueueInner {
+    entries: Vec<Entry>,
+    current: Option<usize>,
+    repeat: RepeatMode,
+    /// 打乱前的顺序；未开启随机时为 `None`
+    original: Option<Vec<Entry>>,
+    /// 切走的队列项（栈，末尾为上一首）；换队列后仍保留，回退时插回队列
+    history: Vec<Entry>,
+    playlist_id: Option<String>,
+    /// 当前歌曲是否已通知过开始过渡
+    announced: bool,
+    /// 最近一次上报的当前歌曲剩余时长（毫秒），用于重排预加载
+    remaining_ms: u64,
+    /// 下一个 [`Entry::key`]
+    next_key: u64,
+}
+
+impl QueueInner {
+    fn make_entries(&mut self, song_ids: &[String]) -> Vec<Entry> {
+        song_ids
+            .iter()
+            .map(|id| {
+                self.next_key += 1;
+                Entry {
+                    key: self.next_key,
+                    song_id: id.clone(),
+                }
+            })
+            .collect()
+    }
+
+    fn song_id(&self, index: usize) -> Option<String> {
+        self.entries.get(index).map(|e| e.song_id.clone())
+    }
+
+    fn current_key(&self) -> Option<u64> {
+        self.current_entry().map(|e| e.key)
+    }
+
+    fn current_entry(&self) -> Option<&Entry> {
+        self.current.and_then(|i| self.entries.get(i))
+    }
+
+    fn set_current(&mut self, index: Option<usize>) {
+        self.current = index;
+        self.announced = false;
+    }
+
+    /// 把切走的一项压入播放历史，超出上限时丢弃最早的。
+    fn push_history(&mut self, entry: Option<Entry>) {
+        let Some(entry) = entry else {
+            return;
+        };
+        self.history.push(entry);
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+    }
+
+    /// 切到 `index`（用户切歌 / 播完）：切走的当前项记入播放历史，留在同一项时不记。
+    fn step_to(&mut self, index: usize) {
+        let key = self.entries.get(index).map(|e| e.key);
+        let left = self.current_entry().filter(|e| Some(e.key) != key).cloned();
+        self.push_history(left);
+        self.set_current(Some(index));
+    }
+
+    /// 把 `entries` 插到当前歌曲之后；随机开启时同样插到打乱前顺序中当前歌曲之后。
+    fn insert_after_current(&mut self, entries: Vec<Entry>) {
+        let at = self.after_current().min(self.entries.len());
+        self.entries.splice(at..at, entries.iter().cloned());
+        let current_key = self.current_key();
+        if let Some(original) = self.original.as_mut() {
+            let at = current_key
+                .and_then(|key| original.iter().position(|e| e.key == key))
+                .map_or(0, |i| i + 1);
+            original.splice(at..at, entries);
+        }
+    }
+
+    /// 当前歌曲之后的插入位置。
+    fn after_current(&self) -> usize {
+        self.current.map_or(0, |i| i + 1)
+    }
+
+    /// 按循环模式，当前歌曲之后的下一首位置；`One` 时为当前歌曲。
+    fn next_index(&self) -> Option<usize> {
+        let len = self.entries.len();
+        let current = self.current?;
+        match self.repeat {
+            RepeatMode::One => Some(current),
+            RepeatMode::All if current + 1 >= len => Some(0),
+            _ => (current + 1 < len).then_some(current + 1),
+        }
+    }
+
+    /// 把队列的后半段（当前歌曲之后）打乱；`is_book` 判定的有声书按原顺序移到打乱后的歌曲之后。
+    fn shuffle_upcoming(&mut self, is_book: impl Fn(&str) -> bool) {
+        let start = self.after_current().min(self.entries.len());
+        let (mut music, books): (Vec<Entry>, Vec<Entry>) =
+            self.entries.drain(start..).partition(|e| !is_book(&e.song_id));
+        shuffle_songs(&mut music);
+        self.entries.extend(music);
+        self.entries.extend(books);
+    }
+
+    fn snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            song_ids: self.entries.iter().map(|e| e.song_id.clone()).collect(),
+            current_index: self.current,
+            repeat: self.repeat,
+            shuffle: self.original.is_some(),
+            playlist_id: self.playlist_id.clone(),
+            has_history: !self.history.is_empty(),
+        }
+    }
+}
+
+/// 正在播放的队列。
+#[derive(Default)]
+pub struct LiveQueue {
+    inner: Mutex<QueueInner>,
+}
+
+impl LiveQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> QueueSnapshot {
+        self.inner.lock().snapshot()
+    }
+
+    /// 当前歌曲。
+    pub fn current_id(&self) -> Option<String> {
+        let inner = self.inner.lock();
+        inner.current.and_then(|i| inner.song_id(i))
+    }
+
+    /// 替换整个队列，从 `start_index` 开始；随机开启时起始歌曲之后的部分立即打乱（`is_book` 见 [`set_shuffle`](Self::set_shuffle)）。
+    pub fn replace(
+        &self,
+        song_ids: Vec<String>,
+        start_index: usize,
+        playlist_id: Option<String>,
+        is_book: impl Fn(&str) -> bool,
+    ) -> QueueSnapshot {
+        let mut inner = self.inner.lock();
+        let current = (!song_ids.is_empty()).then(|| start_index.min(song_ids.len() - 1));
+        let played = inner.current_entry().cloned();
+        inner.push_history(played);
+        let entries = inner.make_entries(&song_ids);
+        let shuffle = inner.original.is_some();
+        inner.original = shuffle.then(|| entries.clone());
+        inner.entries = entries;
+        inner.playlist_id = playlist_id;
+        inner.set_current(current);
+        if shuffle {
+            inner.shuffle_upcoming(is_book);
+        }
+        inner.snapshot()
+    }
+
+    /// 追加到队尾；队列原本为空时从第一首开始。
+    pub fn add(&self, song_ids: &[String]) -> QueueSnapshot {
+        let mut inner = self.inner.lock();
+        let entries = inner.make_entries(song_ids);
+        inner.entries.extend_from_slice(&entries);
+        if let Some(original) = inner.original.as_mut() {
+            original.extend(entries);
+        }
+        if inner.current.is_none() && !inner.entries.is_empty() {
+            inner.set_current(Some(0));
+        }
+        inner.snapshot()
+    }
+
+    /// 插到当前歌曲之后（保持 `song_ids` 内部的顺序）。
+    pub fn insert_next(&self, song_ids: &[String]) -> QueueSnapshot {
+        let mut inner = self.inner.lock();
+        let entries = inner.make_entries(song_ids);
+        inner.insert_after_current(entries);
+        if inner.current.is_none() && !inner.entries.is_empty() {
+            inner.set_current(Some(0));
+        }
+        inner.snapshot()
+    }
+
+    /// 移除第 `index` 首；不能移除正在播放的歌曲。
+    pub fn remove(&self, index: usize) -> Result<QueueSnapshot, String> {
+        let mut inner = self.inner.lock();
+        if index >= inner.entries.len() {
+            return Err(format!("队列中没有第 {} 首", index));
+        }
+        if inner.current == Some(index) {
+            return Err("不能移除正在播放的歌曲".to_string());
+        }
+        let removed = inner.entries.remove(index);
+        if let Some(original) = inner.original.as_mut() {
+            original.retain(|e| e.key != removed.key);
+        }
+        if let Some(current) = inner.current.filter(|&c| c > index) {
+            inner.current = Some(current - 1);
+        }
+        Ok(inner.snapshot())
+    }
+
+    /// 只保留 `keep` 返回 true 的歌曲（如去掉已从曲库删除的歌曲）；当前歌曲被去掉时改播其后一首。
+    pub fn retain(&self, keep: impl Fn(&str) -> bool) {
+        let mut inner = self.inner.lock();
+        inner.history.retain(|e| keep(&e.song_id));
+        let current = inner.current;
+        let mut new_current = None;
+        let mut kept = Vec::with_capacity(inner.entries.len());
+        for (i, entry) in inner.entries.iter().enumerate() {
+            if current.is_some_and(|c| i >= c) && new_current.is_none() && keep(&entry.song_id) {
+                new_current = Some(kept.len());
+            }
+            if keep(&entry.song_id) {
+                kept.push(entry.clone());
+            }
+        }
+        if kept.len() == inner.entries.len() {
+            return;
+        }
+        if let Some(original) = inner.original.as_mut() {
+            original.retain(|e| keep(&e.song_id));
+        }
+        let current_removed = current.is_some_and(|c| !keep(&inner.entries[c].song_id));
+        let new_current = current.and(new_current.or_else(|| kept.len().checked_sub(1)));
+        inner.entries = kept;
+        match current_removed {
+            true => inner.set_current(new_current),
+            false => inner.current = new_current,
+        }
+    }
+
+    /// 切到下一首并返回它；不循环且已在队尾时返回 `None`，位置不变。
+    pub fn advance(&self) -> Option<String> {
+        let mut inner = self.inner.lock();
+        let next = inner.next_index()?;
+        inner.step_to(next);
+        inner.song_id(next)
+    }
+
+    /// 按循环模式预览下一首，不切歌。
+    pub fn peek_next(&self) -> Option<String> {
+        let inner = self.inner.lock();
+        inner.next_index().and_then(|i| inner.song_id(i))
+    }
+
+    /// 回到上一首：先弹出播放历史，回到实际播放过的上一项（已不在队列中时插回当前歌曲之后）；
+    /// 历史为空时退回队列中的前一项，已在队首时整个队列循环则回到队尾，否则留在第一首。
+    pub fn previous(&self) -> Option<String> {
+        let mut inner = self.inner.lock();
+        let current = inner.current?;
+        if let Some(entry) = inner.history.pop() {
+            let index = match inner.entries.iter().position(|e| e.key == entry.key) {
+                Some(index) => index,
+                None => {
+                    inner.insert_after_current(vec![entry]);
+                    current + 1
+                }
+            };
+            inner.set_current(Some(index));
+            return inner.song_id(index);
+        }
+        let prev = match (current, inner.repeat) {
+            (0, RepeatMode::All) => inner.entries.len() - 1,
+            (0, _) => 0,
+            (i, _) => i - 1,
+        };
+        inner.set_current(Some(prev));
+        inner.song_id(prev)
+    }
+
+    /// 跳到第 `index` 首。
+    pub fn jump(&self, index: usize) -> Result<String, String> {
+        let mut inner = self.inner.lock();
+        let id = inner
+            .song_id(index)
+            .ok_or_else(|| format!("队列中没有第 {} 首", index))?;
+        inner.step_to(index);
+        Ok(id)
+    }
+
+    pub fn set_repeat(&self, mode: RepeatMode) -> QueueSnapshot {
+        let mut inner = self.inner.lock();
+        inner.repeat = mode;
+        inner.snapshot()
+    }
+
+    /// 开启随机时打乱当前歌曲之后的部分（`is_book` 为 true 的有声书不参与，排在最后）；
+    /// 关闭时恢复原顺序，当前歌曲不变。
+    pub fn set_shuffle(&self, enabled: bool, is_book: impl Fn(&str) -> bool) -> QueueSnapshot {
+        let mut inner = self.inner.lock();
+        match (enabled, inner.original.is_some()) {
+            (true, false) => {
+                inner.original = Some(inner.entries.clone());
+                inner.shuffle_upcoming(is_book);
+            }
+            (false, true) => {
+                let current_key = inner.current_key();
+                let original = inner.original.take().unwrap_or_default();
+                inner.current = current_key.and_then(|key| original.iter().position(|e| e.key == key));
+                inner.entries = original;
+            }
+            _ => {}
+        }
+        inner.snapshot()
+    }
+
+    /// 当前歌曲之后依次要播放的歌曲（交给预加载器），整个队列循环时接上队首的部分。
+    pub fn upcoming(&self) -> Vec<String> {
+        let inner = self.inner.lock();
+        let Some(current) = inner.current else {
+            return Vec::new();
+        };
+        let mut ids: Vec<String> = inner.entries[current + 1..].iter().map(|e| e.song_id.clone()).collect();
+        if inner.repeat == RepeatMode::All {
+            ids.extend(inner.entries[..current].iter().map(|e| e.song_id.clone()));
+        }
+        ids
+    }
+
+    /// 记录当前歌曲的剩余时长，重排预加载时使用。
+    pub fn set_remaining_ms(&self, remaining_ms: u64) {
+        self.inner.lock().remaining_ms = remaining_ms;
+    }
+
+    pub fn remaining_ms(&self) -> u64 {
+        self.inner.lock().remaining_ms
+    }
+
+    /// 当前歌曲第一次调用时返回 true，之后（直到切歌）返回 false。
+    pub fn announce_once(&self) -> bool {
+        let mut inner = self.inner.lock();
+        !std::mem::replace(&mut inner.announced, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn no_books(_: &str) -> bool {
+        false
+    }
+
+    #[test]
+    fn test_queue_edits_repeat_and_shuffle() {
+        let queue = LiveQueue::new();
+        queue.replace(ids(&["a", "b", "c"]), 1, None, no_books);
+        queue.insert_next(&ids(&["x"]));
+        queue.add(&ids(&["z"]));
+        assert_eq!(queue.snapshot().song_ids, ids(&["a", "b", "x", "c", "z"]));
+        assert!(queue.remove(1).is_err());
+        let snapshot = queue.remove(0).unwrap();
+        assert_eq!(snapshot.current_index, Some(0));
+        assert_eq!(queue.current_id().as_deref(), Some("b"));
+
+        // 不循环：队尾之后没有下一首；整个循环：回到队首；单曲循环：留在原地
+        assert_eq!(queue.jump(3).unwrap(), "z");
+        assert_eq!(queue.advance(), None);
+        queue.set_repeat(RepeatMode::All);
+        assert_eq!(queue.upcoming(), ids(&["b", "x", "c"]));
+        assert_eq!(queue.advance().as_deref(), Some("b"));
+        queue.set_repeat(RepeatMode::One);
+        assert_eq!(queue.advance().as_deref(), Some("b"));
+
+        // 每首只通知一次，切歌后重置
+        assert!(queue.announce_once());
+        assert!(!queue.announce_once());
+        queue.set_repeat(RepeatMode::Off);
+        queue.advance();
+        assert!(queue.announce_once());
+
+        // 随机只动当前歌曲之后的部分，关闭后恢复
+        let many: Vec<String> = (0..30).map(|i| i.to_string()).collect();
+        queue.replace(many.clone(), 5, Some("p".into()), no_books);
+        let shuffled = queue.set_shuffle(true, no_books);
+        assert_eq!(shuffled.song_ids[..6], many[..6]);
+        queue.add(&ids(&["tail"]));
+        let restored = queue.set_shuffle(false, no_books);
+        assert_eq!(restored.song_ids[..30], many[..]);
+        assert_eq!(restored.current_index, Some(5));
+        assert_eq!(restored.playlist_id.as_deref(), Some("p"));
+
+        // 删掉当前歌曲时改播其后一首
+        queue.retain(|id| id != "5" && id != "0");
+        assert_eq!(queue.current_id().as_deref(), Some("6"));
+
+        // 同一首歌出现多次：按队列项而不是歌曲 ID 区分
+        queue.replace(ids(&["d", "e", "d", "f", "d"]), 2, None, no_books);
+        queue.set_shuffle(true, no_books);
+        let restored = queue.set_shuffle(false, no_books);
+        assert_eq!(restored.song_ids, ids(&["d", "e", "d", "f", "d"]));
+        assert_eq!(restored.current_index, Some(2));
+        queue.set_shuffle(true, no_books);
+        let shuffled = queue.snapshot().song_ids;
+        let last_d = shuffled.iter().rposition(|id| id == "d").unwrap();
+        assert!(last_d > 2);
+        queue.remove(last_d).unwrap();
+        let restored = queue.set_shuffle(false, no_books);
+        assert_eq!(restored.song_ids.iter().filter(|id| *id == "d").count(), 2);
+        assert_eq!(restored.current_index, Some(2));
+        assert_eq!(restored.song_ids[..3], ids(&["d", "e", "d"])[..]);
+    }
+
+    #[test]
+    fn test_shuffle_keeps_audiobooks_in_order_after_music() {
+        let queue = LiveQueue::new();
+        let mut names: Vec<String> = (0..20).map(|i| format!("m{}", i)).collect();
+        names.splice(3..3, ids(&["book1", "book2"]));
+        names.push("book3".into());
+        queue.replace(names.clone(), 0, None, no_books);
+        let is_book = |id: &str| id.starts_with("book");
+        let shuffled = queue.set_shuffle(true, is_book).song_ids;
+        assert_eq!(shuffled[0], "m0");
+        assert_eq!(shuffled[shuffled.len() - 3..], ids(&["book1", "book2", "book3"])[..]);
+        let mut music = shuffled[1..shuffled.len() - 3].to_vec();
+        music.sort();
+        let mut expected: Vec<String> = names[1..].iter().filter(|id| !is_book(id)).cloned().collect();
+        expected.sort();
+        assert_eq!(music, expected);
+        assert_eq!(queue.set_shuffle(false, is_book).song_ids, names);
+    }
+
+    #[test]
+    fn test_previous_walks_play_history_before_queue_order() {
+        let queue = LiveQueue::new();
+        queue.replace(ids(&["a", "b", "c", "d"]), 0, None, no_books);
+        assert!(!queue.snapshot().has_history);
+        // 播放顺序：a → d（跳转）→ a（循环回队首）；单曲循环留在原地不记历史
+        queue.jump(3).unwrap();
+        queue.set_repeat(RepeatMode::All);
+        assert_eq!(queue.advance().as_deref(), Some("a"));
+        queue.set_repeat(RepeatMode::One);
+        queue.advance();
+        assert!(queue.snapshot().has_history);
+        assert_eq!(queue.previous().as_deref(), Some("d"));
+        assert_eq!(queue.previous().as_deref(), Some("a"));
+        // 历史用完后退回队列中的前一项
+        queue.set_repeat(RepeatMode::Off);
+        assert_eq!(queue.previous().as_deref(), Some("a"));
+        assert_eq!(queue.current_id().as_deref(), Some("a"));
+
+        // 换队列后，上一首回到旧队列中的歌曲，插回当前歌曲之后
+        queue.jump(2).unwrap();
+        queue.replace(ids(&["x", "y"]), 0, None, no_books);
+        assert_eq!(queue.previous().as_deref(), Some("c"));
+        assert_eq!(queue.snapshot().song_ids, ids(&["x", "c", "y"]));
+        assert_eq!(queue.snapshot().current_index, Some(1));
+
+        // 已从曲库删除的歌曲不会从历史中回来
+        queue.jump(2).unwrap();
+        queue.retain(|id| id != "c");
+        assert_eq!(queue.previous().as_deref(), Some("a"));
+        assert!(!queue.snapshot().has_history);
+    }
+}
@@ -0,0 +1,719 @@
+//! 播放设置管理器。
+
+use super::crossfade::{self, CrossfadePlan, MAX_CROSSFADE_MS};
+use super::decode_stats::{self, DspStage};
+use super::dsp::{self, DspProfile};
+use super::fade::{self, FadeAction, FadePlan, MAX_FADE_MS};
+use super::gain::TrackGain;
+use super::null_output;
+use super::settings::{
+    ActivateAction, ActivateSurface, ContentType, CrossfadeCurve, CrossfadeSettings, DitherMode, EndOfQueueBehavior, FadeSettings, KaraokeSettings, PcmCacheSettings, PlaybackSettings, PlaybackTransition, PreloadSettings, StretchParams,
+    TimeStretchQuality, VolumeCurve, MAX_PLAYBACK_RATE, MIN_PLAYBACK_RATE,
+};
+use super::silence::{self, SilenceMap, SilenceSkipSettings};
+use crate::module::config::store::ConfigStore;
+use crate::module::platform::PlatformPath;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// ConfigStore 中存放播放设置的键。
+const PLAYBACK_CONFIG_KEY: &str = "playback";
+
+/// 卡拉 OK 人声区间增益的下限（dB）。
+pub const MIN_KARAOKE_GAIN_DB: f32 = -40.0;
+
+/// 允许校准的最大输出延迟（毫秒）。
+pub const MAX_OUTPUT_LATENCY_MS: u32 = 1000;
+
+/// 预加载提前量上限（秒）。
+pub const MAX_PRELOAD_LEAD_SECS: u32 = 600;
+
+/// 快进 / 快退步长上限（秒）。
+pub const MAX_SKIP_SECS: u32 = 600;
+
+/// 判定「原速」的容差 — 浮点速度与 1.0 相差小于此值即视为原速。
+const UNITY_SPEED_EPSILON: f64 = 1e-3;
+
+/// 某内容类型的播放速度方案。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PlaybackRate {
+    pub content_type: ContentType,
+    pub rate: f64,
+    /// 变速时是否保持音高（语音类内容始终保持）
+    pub preserve_pitch: bool,
+    /// 变速器参数；原速时为 `None`
+    pub stretch: Option<StretchParams>,
+}
+
+/// 音量滑块位置与实际增益。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct VolumeGain {
+    /// 滑块位置（0.0 ~ 1.0）
+    pub volume: f32,
+    pub curve: VolumeCurve,
+    /// 按曲线换算后的线性增益，前端直接设到 GainNode 上
+    pub gain: f32,
+}
+
+/// 经延迟补偿后的播放位置。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AudioPosition {
+    /// 播放器上报的原始位置（毫秒）
+    pub raw_ms: f64,
+    /// 总延迟（输出设备 + 变速器，毫秒）
+    pub latency_ms: u32,
+    /// 用户此刻实际听到的位置（毫秒），歌词 / 可视化应以此为准
+    pub position_ms: f64,
+}
+
+/// 快进 / 快退方向。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipDirection {
+    Forward,
+    Backward,
+}
+
+/// 一次快进 / 快退的跳转方案 — 前端按 `target_ms` 执行 seek，并套用 `fade`（与拖动进度相同）。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SkipPlan {
+    pub direction: SkipDirection,
+    pub step_secs: u32,
+    /// 跳转目标（毫秒），已限制在 0 ~ 时长之间
+    pub target_ms: f64,
+    /// 快进越过了结尾，前端应直接切到下一首
+    pub reached_end: bool,
+    pub fade: FadePlan,
+}
+
+/// 一次拖动进度 / 点击歌词的跳转方案 — 前端暂停输出、按 `fade` 淡出后执行 seek，
+/// 等到解码器真正到位（`seeked`）后再以实际位置更新进度并淡入。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SeekPlan {
+    /// 跳转目标（毫秒）
+    pub target_ms: f64,
+    pub fade: FadePlan,
+}
+
+/// 播放设置管理器。
+///
+/// 内存中持有一份设置快照，修改时整体写回 ConfigStore（由其防抖落盘）。
+pub struct PlaybackManager {
+    config: Arc<ConfigStore>,
+    settings: RwLock<PlaybackSettings>,
+    /// 静音分析结果缓存（文件路径 → 跳过表），静音跳过设置变化时清空
+    silence_maps: Mutex<HashMap<String, Arc<SilenceMap>>>,
+    /// 解码速度测量结果（文件路径 → 实时倍数）
+    decode_speeds: Mutex<HashMap<String, f32>>,
+}
+
+impl PlaybackManager {
+    /// 创建管理器，从 ConfigStore 加载已保存的设置（缺失时使用默认值）。
+    pub fn new(config: Arc<ConfigStore>) -> Self {
+        let settings = config
+            .get::<PlaybackSettings>(PLAYBACK_CONFIG_KEY)
+            .unwrap_or_default();
+        Self {
+            config,
+            settings: RwLock::new(settings),
+            silence_maps: Mutex::new(HashMap::new()),
+            decode_speeds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 当前设置快照。
+    pub fn settings(&self) -> PlaybackSettings {
+        self.settings.read().clone()
+    }
+
+    /// 在写锁内修改设置并写回 ConfigStore。
+    fn update<F: FnOnce(&mut PlaybackSettings)>(&self, f: F) -> Result<PlaybackSettings, String> {
+        let mut settings = self.settings.write();
+        f(&mut settings);
+        self.config.set(PLAYBACK_CONFIG_KEY, &*settings)?;
+        Ok(settings.clone())
+    }
+
+    // ── 变速 ─────────────────────────────────────────
+
+    /// 设置变速质量档位。
+    pub fn set_time_stretch_quality(
+        &self,
+        quality: TimeStretchQuality,
+    ) -> Result<PlaybackSettings, String> {
+        self.update(|s| s.time_stretch_quality = quality)
+    }
+
+    /// 给定播放速度下应采用的变速器参数。
+    ///
+    /// 原速（`speed == 1.0`）时返回 `None`，表示完全旁路变速器以节省 CPU。
+    pub fn stretch_params_for(&self, speed: f64) -> Option<StretchParams> {
+        if is_unity_speed(speed) {
+            return None;
+        }
+        Some(self.settings.read().time_stretch_quality.params())
+    }
+
+    // ── 播放速度 ─────────────────────────────────────
+
+    /// 查询某内容类型当前的播放速度方案。
+    pub fn playback_rate(&self, content_type: ContentType) -> PlaybackRate {
+        let rate = self.settings.read().playback_rate(content_type);
+        self.rate_plan(content_type, rate)
+    }
+
+    /// 设置某内容类型的播放速度并记忆。
+    ///
+    /// 速度需在 [`MIN_PLAYBACK_RATE`] ~ [`MAX_PLAYBACK_RATE`] 之间。
+    /// 只影响当前曲目的播放方式，不参与任何节拍同步。
+    pub fn set_playback_rate(
+        &self,
+        content_type: ContentType,
+        rate: f64,
+    ) -> Result<PlaybackRate, String> {
+        if !(MIN_PLAYBACK_RATE..=MAX_PLAYBACK_RATE).contains(&rate) {
+            return Err(format!(
+                "播放速度 {} 超出范围（{} ~ {}）",
+                rate, MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE
+            ));
+        }
+        self.update(|s| {
+            if is_unity_speed(rate) {
+                s.playback_rates.remove(&content_type);
+            } else {
+                s.playback_rates.insert(content_type, rate);
+            }
+        })?;
+        Ok(self.rate_plan(content_type, rate))
+    }
+
+    // ── 静音跳过 ─────────────────────────────────────
+
+    /// 更新静音跳过设置；阈值变化后旧的分析结果全部作废。
+    pub fn set_silence_skip(
+        &self,
+        silence_skip: SilenceSkipSettings,
+    ) -> Result<PlaybackSettings, String> {
+        let settings = self.update(|s| s.silence_skip = silence_skip)?;
+        self.silence_maps.lock().clear();
+        Ok(settings)
+    }
+
+    /// 获取文件的静音跳过表（首次调用会完整解码，之后命中缓存）。
+    ///
+    /// 未启用静音跳过时返回 `None`。
+    pub fn silence_map(&self, path: &str) -> Result<Option<Arc<SilenceMap>>, String> {
+        let settings = self.settings.read().silence_skip;
+        if !settings.enabled {
+            return Ok(None);
+        }
+        if let Some(hit) = self.silence_maps.lock().get(path) {
+            return Ok(Some(hit.clone()));
+        }
+        let map = Arc::new(silence::analyze_file(&PlatformPath::from(path), &settings)?);
+        self.silence_maps.lock().insert(path.to_string(), map.clone());
+        Ok(Some(map))
+    }
+
+    // ── 卡拉 OK ─────────────────────────────────────
+
+    /// 更新卡拉 OK 辅助设置。增益需在 [`MIN_KARAOKE_GAIN_DB`] ~ 0 dB 之间。
+    pub fn set_karaoke(&self, karaoke: KaraokeSettings) -> Result<PlaybackSettings, String> {
+        if !(MIN_KARAOKE_GAIN_DB..=0.0).contains(&karaoke.vocal_gain_db) {
+            return Err(format!(
+                "人声增益 {}dB 超出范围（{} ~ 0dB）",
+                karaoke.vocal_gain_db, MIN_KARAOKE_GAIN_DB
+            ));
+        }
+        self.update(|s| s.karaoke = karaoke)
+    }
+
+    // ── 预加载 ───────────────────────────────────────
+
+    /// 更新远程歌曲预加载设置。提前量需在 [`MAX_PRELOAD_LEAD_SECS`] 秒以内。
+    pub fn set_preload(&self, preload: PreloadSettings) -> Result<PlaybackSettings, String> {
+        if preload.lead_secs > MAX_PRELOAD_LEAD_SECS {
+            return Err(format!(
+                "预加载提前量 {}s 超出范围（0 ~ {}s）",
+                preload.lead_secs, MAX_PRELOAD_LEAD_SECS
+            ));
+        }
+        self.update(|s| s.preload = preload)
+    }
+
+    /// 更新解码 PCM 缓存设置。磁盘上限不能为 0（要关闭请把 `enabled` 设为 false）。
+    pub fn set_pcm_cache(&self, pcm_cache: PcmCacheSettings) -> Result<PlaybackSettings, String> {
+        if pcm_cache.max_bytes == 0 {
+            return Err("PCM 缓存上限不能为 0".to_string());
+        }
+        self.update(|s| s.pcm_cache = pcm_cache)
+    }
+
+    // ── 音量 / 抖动 ───────────────────────────────────
+
+    /// 当前音量及其增益。
+    pub fn volume_gain(&self) -> VolumeGain {
+        let settings = self.settings.read();
+        VolumeGain {
+            volume: settings.volume,
+            curve: settings.volume_curve,
+            gain: settings.volume_curve.gain(settings.volume),
+        }
+    }
+
+    /// 设置音量滑块位置（0.0 ~ 1.0），返回按当前曲线换算的增益。
+    pub fn set_volume(&self, volume: f32) -> Result<VolumeGain, String> {
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(format!("音量 {} 超出范围（0 ~ 1）", volume));
+        }
+        self.update(|s| s.volume = volume)?;
+        Ok(self.volume_gain())
+    }
+
+    /// 切换音量曲线；滑块位置不变，增益按新曲线重新换算。
+    pub fn set_volume_curve(&self, curve: VolumeCurve) -> Result<VolumeGain, String> {
+        self.update(|s| s.volume_curve = curve)?;
+        Ok(self.volume_gain())
+    }
+
+    /// 设置降低位深时的抖动方式。
+    pub fn set_dither(&self, dither: DitherMode) -> Result<PlaybackSettings, String> {
+        self.update(|s| s.dither = dither)
+    }
+
+    /// 设置队列播完后的行为。
+    pub fn set_end_of_queue(&self, behavior: EndOfQueueBehavior) -> Result<PlaybackSettings, String> {
+        self.update(|s| s.end_of_queue = behavior)
+    }
+
+    /// 设置在某列表中激活歌曲时的处理。
+    pub fn set_activate_action(
+        &self,
+        surface: ActivateSurface,
+        action: ActivateAction,
+    ) -> Result<PlaybackSettings, String> {
+        self.update(|s| {
+            s.activate_actions.insert(surface, action);
+        })
+    }
+
+    /// 设置系统休眠唤醒后是否保持暂停。
+    pub fn set_pause_on_suspend(&self, enabled: bool) -> Result<PlaybackSettings, String> {
+        self.update(|s| s.pause_on_suspend = enabled)
+    }
+
+    /// 设置是否使用空输出；环境变量强制空输出时设置仍会保存，但不影响生效的输出方式。
+    pub fn set_null_output(&self, enabled: bool) -> Result<PlaybackSettings, String> {
+        self.update(|s| s.null_output = enabled)
+    }
+
+    /// 当前生效的输出方式（设置与环境变量合并）。
+    pub fn output_mode(&self) -> null_output::OutputMode {
+        null_output::from_env(&self.settings.read())
+    }
+
+    // ── 淡入淡出 ─────────────────────────────────────
+
+    /// 更新淡入淡出设置。各段时长需在 [`MAX_FADE_MS`] 毫秒以内。
+    pub fn set_fades(&self, fades: FadeSettings) -> Result<PlaybackSettings, String> {
+        let longest = fades.play_ms.max(fades.stop_ms).max(fades.seek_ms);
+        if longest > MAX_FADE_MS {
+            return Err(format!(
+                "淡入淡出时长 {}ms 超出范围（0 ~ {}ms）",
+                longest, MAX_FADE_MS
+            ));
+        }
+        self.update(|s| s.fades = fades)
+    }
+
+    /// 某个操作应采用的淡入淡出方案。
+    pub fn fade_plan(&self, action: FadeAction) -> FadePlan {
+        fade::plan(&self.settings.read().fades, action)
+    }
+
+    // ── 交叉淡化 ─────────────────────────────────────
+
+    /// 更新交叉淡化设置。过渡时长需在 [`MAX_CROSSFADE_MS`] 毫秒以内，短曲目倍数需在 1 ~ 10 之间，
+    /// 自定义曲线的控制点需通过 [`crossfade::validate_curve_points`]。
+    pub fn set_crossfade(&self, crossfade: CrossfadeSettings) -> Result<PlaybackSettings, String> {
+        let longest = crossfade.duration_ms.max(crossfade.short_tracks.min_crossfade_ms);
+        if longest > MAX_CROSSFADE_MS {
+            return Err(format!(
+                "交叉淡化时长 {}ms 超出范围（0 ~ {}ms）",
+                longest, MAX_CROSSFADE_MS
+            ));
+        }
+        let ratio = crossfade.short_tracks.min_track_ratio;
+        if !(1.0..=10.0).contains(&ratio) {
+            return Err(format!("短曲目倍数 {} 超出范围（1 ~ 10）", ratio));
+        }
+        if crossfade.curve == CrossfadeCurve::Custom || !crossfade.custom_curve.is_empty() {
+            crossfade::validate_curve_points(&crossfade.custom_curve)?;
+        }
+        self.update(|s| s.crossfade = crossfade)
+    }
+
+    /// 设置某播放列表的切歌方式；设回交叉淡化即删除该列表的记录。
+    pub fn set_playlist_transition(
+        &self,
+        playlist_id: &str,
+        transition: PlaybackTransition,
+    ) -> Result<PlaybackSettings, String> {
+        self.update(|s| match transition {
+            PlaybackTransition::Crossfade => {
+                s.playlist_transitions.remove(playlist_id);
+            }
+            PlaybackTransition::Gapless => {
+                s.playlist_transitions.insert(playlist_id.to_string(), transition);
+            }
+        })
+    }
+
+    /// 从当前歌曲切到下一首的过渡方案；时长未知时传 `None`。
+    ///
+    /// 正在播放某个播放列表时传入 `playlist_id`，该列表设为无缝衔接时返回 [`CrossfadePlan::gapless`]。
+    pub fn crossfade_plan(
+        &self,
+        playlist_id: Option<&str>,
+        current_ms: Option<u64>,
+        remaining_ms: Option<u64>,
+        next_ms: Option<u64>,
+    ) -> CrossfadePlan {
+        let settings = self.settings.read();
+        if playlist_id.is_some_and(|id| settings.playlist_transition(id) == PlaybackTransition::Gapless) {
+            return CrossfadePlan::gapless();
+        }
+        crossfade::plan(&settings.crossfade, current_ms, remaining_ms, next_ms)
+    }
+
+    /// 当前交叉淡化曲线的淡入采样表（`samples` 个点），见 [`crossfade::curve_table`]。
+    pub fn crossfade_curve_table(&self, samples: usize) -> Vec<f32> {
+        let settings = self.settings.read();
+        crossfade::curve_table(settings.crossfade.curve, &settings.crossfade.custom_curve, samples)
+    }
+
+    // ── 播放质量 ─────────────────────────────────────
+
+    /// 文件的解码速度（实时的倍数）；首次调用试解开头一段，之后命中缓存。
+    pub fn decode_speed(&self, path: &str) -> Result<f32, String> {
+        if let Some(&speed) = self.decode_speeds.lock().get(path) {
+            return Ok(speed);
+        }
+        let speed = decode_stats::measure_decode_speed(&PlatformPath::from(path))?;
+        self.decode_speeds.lock().insert(path.to_string(), speed);
+        Ok(speed)
+    }
+
+    /// 按当前设置，某内容类型的歌曲实际经过的 DSP 链（不生效的处理不列出）。
+    pub fn dsp_chain(&self, content_type: ContentType, gain: Option<&TrackGain>) -> Vec<DspStage> {
+        let rate = self.playback_rate(content_type);
+        let volume = self.volume_gain();
+        let settings = self.settings.read();
+        let mut chain: Vec<DspStage> = gain.and_then(decode_stats::gain_stage).into_iter().collect();
+        if let Some(params) = rate.stretch {
+            chain.push(DspStage::TimeStretch { rate: rate.rate, params });
+        }
+        if settings.silence_skip.enabled {
+            chain.push(DspStage::SilenceSkip {
+                min_silence_ms: settings.silence_skip.min_silence_ms,
+            });
+        }
+        if settings.karaoke.enabled {
+            chain.push(DspStage::Karaoke {
+                vocal_gain_db: settings.karaoke.vocal_gain_db,
+            });
+        }
+        if settings.crossfade.enabled && settings.crossfade.duration_ms > 0 {
+            chain.push(DspStage::Crossfade {
+                duration_ms: settings.crossfade.duration_ms,
+            });
+        }
+        let profile = settings.dsp_profile(&settings.output_device);
+        if profile.equalizer_active() {
+            chain.push(DspStage::Equalizer {
+                bands: profile.equalizer.bands.len(),
+                preamp_db: profile.equalizer.preamp_db,
+            });
+        }
+        if profile.balance != 0.0 {
+            chain.push(DspStage::Balance { balance: profile.balance });
+        }
+        if profile.crossfeed.enabled && profile.crossfeed.level > 0.0 {
+            chain.push(DspStage::Crossfeed {
+                level: profile.crossfeed.level,
+            });
+        }
+        chain.push(DspStage::Volume {
+            volume: volume.volume,
+            curve: settings.volume_curve,
+            gain: volume.gain,
+        });
+        if profile.limiter.enabled {
+            chain.push(DspStage::Limiter {
+                threshold_db: profile.limiter.threshold_db,
+            });
+        }
+        if settings.output_latency_ms > 0 {
+            chain.push(DspStage::LatencyCompensation {
+                latency_ms: settings.output_latency_ms,
+            });
+        }
+        chain
+    }
+
+    // ── 输出设备 DSP ─────────────────────────────────
+
+    /// 某输出设备的 DSP 配置；`device_id` 缺省时取当前输出设备。
+    pub fn dsp_profile(&self, device_id: Option<&str>) -> DspProfile {
+        let settings = self.settings.read();
+        let device = device_id.map_or_else(|| settings.output_device.clone(), dsp::device_key);
+        settings.dsp_profile(&device)
+    }
+
+    /// 保存某输出设备的 DSP 配置。
+    pub fn set_dsp_profile(&self, device_id: &str, profile: DspProfile) -> Result<PlaybackSettings, String> {
+        profile.validate()?;
+        let device = dsp::device_key(device_id);
+        self.update(|s| {
+            s.dsp_profiles.insert(device, profile);
+        })
+    }
+
+    /// 记录当前输出设备，返回前端应立即套用的 DSP 配置。
+    pub fn set_output_device(&self, device_id: &str) -> Result<DspProfile, String> {
+        let device = dsp::device_key(device_id);
+        let settings = self.update(|s| s.output_device = device.clone())?;
+        Ok(settings.dsp_profile(&device))
+    }
+
+    /// 把一台设备的 DSP 配置复制到另一台（覆盖目标设备原有配置）。
+    pub fn copy_dsp_profile(&self, from: &str, to: &str) -> Result<PlaybackSettings, String> {
+        let (from, to) = (dsp::device_key(from), dsp::device_key(to));
+        if from == to {
+            return Err("源设备与目标设备相同".to_string());
+        }
+        self.update(|s| {
+            let profile = s.dsp_profile(&from);
+            s.dsp_profiles.insert(to, profile);
+        })
+    }
+
+    // ── 快进 / 快退 ───────────────────────────────────
+
+    /// 设置某内容类型的快进 / 快退步长（1 ~ [`MAX_SKIP_SECS`] 秒）。
+    pub fn set_skip_step(&self, content_type: ContentType, secs: u32) -> Result<PlaybackSettings, String> {
+        check_skip_secs(secs)?;
+        self.update(|s| {
+            s.skip_steps.insert(content_type, secs);
+        })
+    }
+
+    /// 从 `position_ms` 快进 / 快退的方案。
+    ///
+    /// `secs` 缺省时使用该内容类型设置的步长；`duration_ms` 未知时快进不设上限。
+    pub fn skip_plan(
+        &self,
+        content_type: ContentType,
+        direction: SkipDirection,
+        position_ms: f64,
+        duration_ms: Option<f64>,
+        secs: Option<u32>,
+    ) -> Result<SkipPlan, String> {
+        let step_secs = match secs {
+            Some(secs) => check_skip_secs(secs)?,
+            None => self.settings.read().skip_step(content_type),
+        };
+        let (target_ms, reached_end) = skip_target(position_ms, duration_ms, step_secs, direction);
+        Ok(SkipPlan {
+            direction,
+            step_secs,
+            target_ms,
+            reached_end,
+            fade: self.fade_plan(FadeAction::Seek),
+        })
+    }
+
+    /// 跳转到 `target_ms` 的方案；目标为负、不是有限数或超过已知时长时报错。
+    pub fn seek_plan(&self, target_ms: f64, duration_ms: Option<f64>) -> Result<SeekPlan, String> {
+        check_seek_target(target_ms, duration_ms)?;
+        Ok(SeekPlan {
+            target_ms,
+            fade: self.fade_plan(FadeAction::Seek),
+        })
+    }
+
+    // ── 延迟补偿 ─────────────────────────────────────
+
+    /// 设置输出设备延迟（用户校准滑块）。
+    pub fn set_output_latency(&self, latency_ms: u32) -> Result<PlaybackSettings, String> {
+        if latency_ms > MAX_OUTPUT_LATENCY_MS {
+            return Err(format!(
+                "输出延迟 {}ms 超出范围（0 ~ {}ms）",
+                latency_ms, MAX_OUTPUT_LATENCY_MS
+            ));
+        }
+        self.update(|s| s.output_latency_ms = latency_ms)
+    }
+
+    /// 将播放器上报的位置换算为用户实际听到的位置。
+    ///
+    /// 延迟是真实时间，折算到媒体时间需乘以播放速度；
+    /// 变速器启用时其窗口延迟也计入。
+    pub fn compensate_position(&self, raw_ms: f64, speed: f64) -> AudioPosition {
+        let output_latency = self.settings.read().output_latency_ms;
+        let stretch_latency = self.stretch_params_for(speed).map_or(0, |p| p.latency_ms);
+        let latency_ms = output_latency + stretch_latency;
+        AudioPosition {
+            raw_ms,
+            latency_ms,
+            position_ms: (raw_ms - latency_ms as f64 * speed).max(0.0),
+        }
+    }
+
+    fn rate_plan(&self, content_type: ContentType, rate: f64) -> PlaybackRate {
+        PlaybackRate {
+            content_type,
+            rate,
+            preserve_pitch: content_type.is_spoken() || !is_unity_speed(rate),
+            stretch: self.stretch_params_for(rate),
+        }
+    }
+}
+
+fn check_skip_secs(secs: u32) -> Result<u32, String> {
+    if !(1..=MAX_SKIP_SECS).contains(&secs) {
+        return Err(format!("跳转步长 {} 秒超出范围（1 ~ {} 秒）", secs, MAX_SKIP_SECS));
+    }
+    Ok(secs)
+}
+
+/// 跳转目标位置及是否越过结尾。
+fn skip_target(position_ms: f64, duration_ms: Option<f64>, step_secs: u32, direction: SkipDirection) -> (f64, bool) {
+    let step_ms = f64::from(step_secs) * 1000.0;
+    match direction {
+        SkipDirection::Backward => ((position_ms - step_ms).max(0.0), false),
+        SkipDirection::Forward => {
+            let target = position_ms.max(0.0) + step_ms;
+            match duration_ms {
+                Some(duration) if target >= duration => (duration, true),
+                _ => (target, false),
+            }
+        }
+    }
+}
+
+/// 检查跳转目标是否落在 0 ~ 时长之间（时长未知时只检查下限）。
+fn check_seek_target(target_ms: f64, duration_ms: Option<f64>) -> Result<(), String> {
+    if !target_ms.is_finite() || target_ms < 0.0 {
+        return Err(format!("跳转位置 {}ms 无效", target_ms));
+    }
+    match duration_ms.filter(|d| d.is_finite()) {
+        Some(duration) if target_ms > duration => {
+            Err(format!("跳转位置 {:.0}ms 超出歌曲时长（{:.0}ms）", target_ms, duration))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 速度是否等于原速。
+pub fn is_unity_speed(speed: f64) -> bool {
+    (speed - 1.0).abs() < UNITY_SPEED_EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unity_speed() {
+        assert!(is_unity_speed(1.0));
+        assert!(is_unity_speed(1.0000001));
+        assert!(!is_unity_speed(0.75));
+        assert!(!is_unity_speed(1.5));
+    }
+
+    #[test]
+    fn test_volume_curve_gain() {
+        assert_eq!(VolumeCurve::Linear.gain(0.5), 0.5);
+        assert_eq!(VolumeCurve::Logarithmic.gain(0.0), 0.0);
+        assert!((VolumeCurve::Logarithmic.gain(1.0) - 1.0).abs() < 1e-6);
+        // 滑块中点约为 -25 dB，而线性曲线的中点只有 -6 dB
+        let mid_db = 20.0 * VolumeCurve::Logarithmic.gain(0.5).log10();
+        assert!((mid_db + 25.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_skip_target_clamps_to_track() {
+        assert_eq!(skip_target(5_000.0, Some(60_000.0), 10, SkipDirection::Backward), (0.0, false));
+        assert_eq!(skip_target(5_000.0, Some(60_000.0), 10, SkipDirection::Forward), (15_000.0, false));
+        assert_eq!(skip_target(55_000.0, Some(60_000.0), 10, SkipDirection::Forward), (60_000.0, true));
+        assert_eq!(skip_target(55_000.0, None, 30, SkipDirection::Forward), (85_000.0, false));
+        assert_eq!(PlaybackSettings::default().skip_step(ContentType::Podcast), 30);
+        assert!(check_skip_secs(0).is_err());
+    }
+
+    #[test]
+    fn test_seek_target_must_be_within_track() {
+        assert!(check_seek_target(0.0, Some(60_000.0)).is_ok());
+        assert!(check_seek_target(60_000.0, Some(60_000.0)).is_ok());
+        assert!(check_seek_target(60_001.0, Some(60_000.0)).is_err());
+        assert!(check_seek_target(-1.0, None).is_err());
+        assert!(check_seek_target(f64::NAN, None).is_err());
+        assert!(check_seek_target(3_600_000.0, None).is_ok());
+    }
+
+    #[test]
+    fn test_quality_params_latency_order() {
+        let low = TimeStretchQuality::LowLatency.params().latency_ms;
+        let mid = TimeStretchQuality::Balanced.params().latency_ms;
+        let high = TimeStretchQuality::HighQuality.params().latency_ms;
+        assert!(low < mid && mid < high);
+    }
+
+    fn manager(name: &str) -> (Arc<ConfigStore>, PlaybackManager) {
+        let path = std::env::temp_dir().join(format!("chordial_playback_{}_{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = Arc::new(ConfigStore::new(path));
+        (config.clone(), PlaybackManager::new(config))
+    }
+
+    #[test]
+    fn test_set_playback_rate_validates_and_persists() {
+        let (config, manager) = manager("rate");
+        assert!(manager.set_playback_rate(ContentType::Audiobook, 0.5).is_err());
+        assert!(manager.set_playback_rate(ContentType::Audiobook, 3.5).is_err());
+        let plan = manager.set_playback_rate(ContentType::Audiobook, 1.5).unwrap();
+        assert_eq!(plan.rate, 1.5);
+        assert!(plan.preserve_pitch);
+
+        // 写入 ConfigStore 的 "playback" 键，同一存储上新建的管理器读回
+        let saved: PlaybackSettings = config.get(PLAYBACK_CONFIG_KEY).unwrap();
+        assert_eq!(PLAYBACK_CONFIG_KEY, "playback");
+        assert_eq!(saved.playback_rate(ContentType::Audiobook), 1.5);
+        let reloaded = PlaybackManager::new(config);
+        assert_eq!(reloaded.playback_rate(ContentType::Audiobook).rate, 1.5);
+        assert_eq!(reloaded.playback_rate(ContentType::Music).rate, 1.0);
+    }
+
+    #[test]
+    fn test_compensate_position_scales_latency_by_speed() {
+        let (_, manager) = manager("latency");
+        manager.set_output_latency(100).unwrap();
+
+        // 原速：旁路变速器，只扣输出延迟
+        let unity = manager.compensate_position(1_000.0, 1.0);
+        assert_eq!((unity.latency_ms, unity.position_ms), (100, 900.0));
+
+        // 2 倍速：变速器延迟计入，真实时间折算为两倍的媒体时间
+        let stretch = manager.stretch_params_for(2.0).unwrap().latency_ms;
+        let fast = manager.compensate_position(10_000.0, 2.0);
+        assert_eq!(fast.latency_ms, 100 + stretch);
+        assert_eq!(fast.position_ms, 10_000.0 - (100 + stretch) as f64 * 2.0);
+
+        // 刚开始播放、上报位置小于延迟时不出现负数
+        assert_eq!(manager.compensate_position(50.0, 1.0).position_ms, 0.0);
+        assert_eq!(manager.compensate_position(150.0, 2.0).position_ms, 0.0);
+    }
+}
@@ -0,0 +1,73 @@
+//! 播放设置模块 — 播放行为相关的用户偏好（持久化到 ConfigStore）。
+//!
+//! 实际解码 / 出声由前端完成，本模块只负责：
+//! - 保存用户选择的播放参数（变速质量档位、按内容类型记忆的播放速度等）
+//! - 根据当前参数给出前端可直接采用的处理方案（例如是否旁路变速器、静音跳过区间、延迟补偿后的播放位置）
+//!
+//! # 模块布局
+//!
+//! | 文件 | 职责 |
+//! |------|------|
+//! | [`settings`] | 设置数据结构 + 变速质量档位 + 卡拉 OK 辅助 + 预加载 + 解码 PCM 缓存 + 音量曲线 + 快进 / 快退步长 |
+//! | [`manager`] | `PlaybackManager` — 读写设置并落盘 |
+//! | [`silence`] | 静音检测 / 静音跳过表 |
+//! | [`render`] | 离线混音渲染（交叉淡化 → FLAC + CUE） |
+//! | [`prefetch`] | 解码预取 — 每首歌一个解码线程 + 按时长定容量的无锁环形缓冲 |
+//! | [`flac`] | 渲染输出用的最小 FLAC 写入器 |
+//! | [`preview`] | 搜索结果悬停试听 — 高潮段定位 + 电平归一化片段 |
+//! | [`dither`] | 降位深抖动（TPDF / 噪声整形） |
+//! | [`fade`] | 播放 / 停止 / 拖动时的淡入淡出包络 |
+//! | [`crossfade`] | 切歌交叉淡化时长 — 短曲目 / 过场自动缩短或取消；按播放列表选择无缝衔接 |
+//! | [`crossfade_state`] | 进行中的交叉淡化 — 进度查询 / 中途取消 |
+//! | [`transitions`] | 切歌过渡日志（磁盘 JSON Lines），用于回看并调整自动混音 |
+//! | [`decode_stats`] | 播放质量指示 — 编码 / 解码速度 / 缓冲 / 重采样 / DSP 链 |
+//! | [`dsp`] | 按输出设备保存的 DSP 配置（均衡器 / 声道平衡 / 交叉馈送 / 限幅器） |
+//! | [`preload`] | 队列预加载 — 远程歌曲开播前下载到媒体缓存 |
+//! | [`queue`] | 播放专辑 / 艺人时在后端排好的播放队列 + 激活歌曲时的入队方式 + 队列播完后的续播 |
+//! | [`live_queue`] | 正在播放的队列 — 追加 / 插到下一首 / 移除 / 随机 / 循环，临近结尾时通知过渡 |
+//! | [`gain`] | 单曲增益 — 用户覆盖值 / ReplayGain 的取舍与预览 |
+//! | [`now_playing`] | 正在播放页的聚合数据（歌曲 / 专辑 / 艺人 / 歌词 / 分析 / 封面配色） |
+//! | [`power`] | 系统休眠检测 — 唤醒后通知前端重建音频输出 |
+//! | [`null_output`] | 空输出 — 无物理设备时按实时速度消耗样本（CI / 无头服务器 / 驱动异常） |
+
+pub mod crossfade;
+pub mod crossfade_state;
+pub mod decode_stats;
+pub mod dither;
+pub mod dsp;
+pub mod fade;
+pub mod flac;
+pub mod gain;
+pub mod live_queue;
+pub mod manager;
+pub mod now_playing;
+pub mod null_output;
+pub mod power;
+pub mod prefetch;
+pub mod preload;
+pub mod preview;
+pub mod queue;
+pub mod render;
+pub mod settings;
+pub mod silence;
+pub mod transitions;
+
+pub use crossfade::{CrossfadeLimit, CrossfadePlan};
+pub use crossfade_state::{CrossfadeCancel, CrossfadeKeep, CrossfadeStatus, CrossfadeTracker};
+pub use decode_stats::{DecodeStats, DspStage, PlayerReport};
+pub use dsp::{CrossfeedSettings, DspProfile, EqBand, EqFilter, EqualizerSettings, LimiterSettings};
+pub use fade::{FadeAction, FadePlan};
+pub use gain::{GainSource, TrackGain};
+pub use live_queue::{LiveQueue, QueueSnapshot, QueueStep, QueueTransition, QueueView, RepeatMode};
+pub use manager::{AudioPosition, PlaybackManager, PlaybackRate, SeekPlan, SkipDirection, SkipPlan, VolumeGain};
+pub use now_playing::{LyricsInfo, NowPlayingBundle};
+pub use null_output::{NullOutput, OutputMode};
+pub use preload::{PreloadState, PreloadStatus, Preloader};
+pub use queue::{ActivateContext, EndOfQueuePlan, PlayQueue, TrackActivation};
+pub use settings::{
+    ActivateAction, ActivateSurface, ContentType, CrossfadeCurve, CrossfadeSettings, CurvePoint, DitherMode, EndOfQueueBehavior, FadeSettings, KaraokeSettings, PcmCacheSettings, PlaybackSettings, PlaybackTransition, PreloadSettings, ShortTrackThresholds, StretchAlgorithm,
+    StretchParams, TimeStretchQuality, VolumeCurve, PLAYBACK_RATE_PRESETS,
+};
+pub use render::{render_mix, RenderOptions, RenderSummary, RenderTrack};
+pub use silence::{SilenceMap, SilenceSegment, SilenceSkipAggressiveness, SilenceSkipSettings};
+pub use transitions::{TransitionLog, TransitionRecord, TransitionReport};
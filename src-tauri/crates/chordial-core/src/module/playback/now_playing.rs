@@ -0,0 +1,84 @@
+//! 正在播放页的聚合数据 — 切歌时一次取齐歌曲、专辑、艺人简介、歌词、分析结果与封面配色。
+//!
+//! 各部分互不依赖，由 [`AppContext::now_playing_bundle`](crate::AppContext::now_playing_bundle)
+//! 在多个线程上同时计算；任一部分取不到（没有歌词、没有本地文件、没有封面等）只留空，不影响其余部分。
+
+use super::gain::TrackGain;
+use crate::module::analysis::{TechnicalInfo, TrackSegments};
+use crate::module::music_library::models::{Album, Artist, Song};
+use serde::Serialize;
+
+/// 封面配色取几种颜色。
+pub const PALETTE_SIZE: usize = 5;
+
+/// 歌词概况。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LyricsInfo {
+    /// 歌词全文；没有歌词时为 `None`
+    pub text: Option<String>,
+    /// 是否为带时间轴的 LRC 歌词
+    pub synced: bool,
+}
+
+impl LyricsInfo {
+    pub fn from_text(text: String) -> Self {
+        let text = Some(text).filter(|t| !t.trim().is_empty());
+        Self {
+            synced: text.as_deref().is_some_and(is_synced),
+            text,
+        }
+    }
+}
+
+/// 以 `[mm:ss.xx]` 时间标签开头的行视为同步歌词（与前端的判断一致）。
+fn is_synced(text: &str) -> bool {
+    text.lines().any(|line| {
+        let Some(rest) = line.trim_start().strip_prefix('[') else {
+            return false;
+        };
+        let Some((minutes, rest)) = rest.split_once(':') else {
+            return false;
+        };
+        let Some((seconds, rest)) = rest.split_once('.') else {
+            return false;
+        };
+        let fraction: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        (1..=2).contains(&minutes.len())
+            && minutes.chars().all(|c| c.is_ascii_digit())
+            && seconds.len() == 2
+            && seconds.chars().all(|c| c.is_ascii_digit())
+            && (2..=3).contains(&fraction.len())
+            && rest[fraction.len()..].starts_with(']')
+    })
+}
+
+/// 正在播放页所需的全部数据。
+#[derive(Debug, Clone, Serialize)]
+pub struct NowPlayingBundle {
+    /// 已本地化的歌曲
+    pub song: Song,
+    pub album: Option<Album>,
+    /// 演出艺人（含简介），按歌曲的艺人顺序
+    pub artists: Vec<Artist>,
+    pub lyrics: LyricsInfo,
+    /// 编码 / 采样率等技术信息；没有可直接访问的文件时为 `None`
+    pub technical: Option<TechnicalInfo>,
+    /// 前奏 / 尾奏边界；同上
+    pub segments: Option<TrackSegments>,
+    /// 开播时应用的增益
+    pub gain: Option<TrackGain>,
+    /// 封面主要颜色（`#rrggbb`，占比从高到低）；没有封面时为空
+    pub palette: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_synced_lyrics() {
+        assert!(LyricsInfo::from_text("[ti:Song]\n[00:12.34]第一句".into()).synced);
+        assert!(!LyricsInfo::from_text("第一句\n[副歌]".into()).synced);
+        assert!(LyricsInfo::from_text("  \n".into()).text.is_none());
+    }
+}
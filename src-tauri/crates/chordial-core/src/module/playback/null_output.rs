@@ -0,0 +1,112 @@
+//! 空输出 — 不连接任何物理设备、按实时速度消耗样本的音频输出。
+//!
+//! 用于 CI、HTTP 服务模式下的无头服务器，以及音频驱动异常、一开输出就出错的机器。
+//! 通过播放设置 `null_output` 或环境变量 `CHORDIAL_AUDIO_OUTPUT=null` 启用（环境变量优先，不写入设置）。
+//!
+//! 出声由前端完成：启用后前端把音频元素接到输出为 `{ type: 'none' }` 的 AudioContext 上（WebView 不支持时
+//! 仍用默认设备），播放进度、结束事件、交叉淡化照常按实时推进，并以 [`DEVICE_ID`] 上报输出设备，DSP 配置单独保存。
+//! 后端的 [`NullOutput`] 是同样语义的样本汇，供预取 / 渲染等消费方在测试中模拟实时播放节奏。
+
+use super::settings::PlaybackSettings;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// 强制使用空输出的环境变量，取值为 `null` 时生效。
+pub const ENV_VAR: &str = "CHORDIAL_AUDIO_OUTPUT";
+
+/// 空输出上报的输出设备 ID。
+pub const DEVICE_ID: &str = "null";
+
+/// 当前生效的输出方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OutputMode {
+    /// 是否使用空输出
+    pub null_output: bool,
+    /// 是否由环境变量强制（此时设置中的开关不起作用）
+    pub forced_by_env: bool,
+}
+
+/// 环境变量取值是否要求空输出（不区分大小写）。
+fn env_requests_null(value: Option<&str>) -> bool {
+    value.is_some_and(|v| v.trim().eq_ignore_ascii_case(DEVICE_ID))
+}
+
+/// 按设置与环境变量得出当前输出方式。
+pub fn resolve(settings: &PlaybackSettings, env_value: Option<&str>) -> OutputMode {
+    let forced_by_env = env_requests_null(env_value);
+    OutputMode {
+        null_output: forced_by_env || settings.null_output,
+        forced_by_env,
+    }
+}
+
+/// 按当前进程的环境变量得出输出方式。
+pub fn from_env(settings: &PlaybackSettings) -> OutputMode {
+    resolve(settings, std::env::var(ENV_VAR).ok().as_deref())
+}
+
+/// 按实时速度消耗交织样本的输出：写入超前于墙上时钟时阻塞到追平为止。
+pub struct NullOutput {
+    sample_rate: u32,
+    channels: usize,
+    started: Option<Instant>,
+    frames: u64,
+}
+
+impl NullOutput {
+    pub fn new(sample_rate: u32, channels: usize) -> Result<Self, String> {
+        if sample_rate == 0 || channels == 0 {
+            return Err("采样率与声道数必须大于 0".to_string());
+        }
+        Ok(Self {
+            sample_rate,
+            channels,
+            started: None,
+            frames: 0,
+        })
+    }
+
+    /// 写入交织样本；计时从第一次写入开始。
+    pub fn write(&mut self, samples: &[f32]) {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        self.frames += (samples.len() / self.channels) as u64;
+        let due = Duration::from_micros(self.frames * 1_000_000 / self.sample_rate as u64);
+        if let Some(ahead) = due.checked_sub(started.elapsed()) {
+            std::thread::sleep(ahead);
+        }
+    }
+
+    /// 已「播放」的时长（毫秒）。
+    pub fn position_ms(&self) -> u64 {
+        self.frames * 1000 / self.sample_rate as u64
+    }
+
+    /// 从头开始计时（如拖动进度后），已播放时长清零。
+    pub fn reset(&mut self) {
+        self.started = None;
+        self.frames = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consumes_at_real_time_pace() {
+        let mut output = NullOutput::new(8000, 2).unwrap();
+        let started = Instant::now();
+        for _ in 0..4 {
+            output.write(&[0.0; 400]);
+        }
+        // 4 × 200 帧 @ 8 kHz = 100 ms
+        assert_eq!(output.position_ms(), 100);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        let settings = PlaybackSettings::default();
+        assert!(!resolve(&settings, None).null_output);
+        assert_eq!(resolve(&settings, Some(" NULL ")), OutputMode { null_output: true, forced_by_env: true });
+        let enabled = PlaybackSettings { null_output: true, ..Default::default() };
+        assert_eq!(resolve(&enabled, Some("default")), OutputMode { null_output: true, forced_by_env: false });
+    }
+}
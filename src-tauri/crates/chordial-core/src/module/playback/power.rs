@@ -0,0 +1,66 @@
+//! 系统休眠检测 — 笔记本合盖 / 待机唤醒后，前端的音频输出流常已失效，界面却仍显示「播放中」。
+//!
+//! 不依赖各平台的电源通知 API，而是用墙上时钟检测：后台线程每 [`POLL_INTERVAL`] 醒来一次，
+//! 若两次之间墙上时钟走过的时间比预期多出 [`SUSPEND_THRESHOLD`] 以上，说明期间进程被挂起，
+//! 随即发布 [`AppEvent::SystemResumed`]。
+//!
+//! 前端收到事件后重建音频元素、回到休眠前的位置，并按
+//! [`PlaybackSettings::pause_on_suspend`](super::PlaybackSettings::pause_on_suspend) 决定保持暂停还是继续播放。
+//! 因为休眠前没有可靠的通知，「休眠前暂停」以唤醒后立即停在原位置实现，效果相同。
+
+use super::PlaybackManager;
+use crate::module::events::{AppEvent, EventBus};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// 检测间隔。
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 墙上时钟超出预期多少才视为发生过休眠（避开高负载下的调度延迟与小幅校时）。
+pub const SUSPEND_THRESHOLD: Duration = Duration::from_secs(15);
+
+/// 一次检测间隔内墙上时钟实际走过 `elapsed`；判定为休眠时返回休眠时长（秒）。
+pub fn suspended_for(expected: Duration, elapsed: Duration) -> Option<u64> {
+    let gap = elapsed.checked_sub(expected)?;
+    (gap >= SUSPEND_THRESHOLD).then_some(gap.as_secs())
+}
+
+/// 启动休眠检测线程。线程只持有弱引用，事件总线释放后自动退出。
+pub fn spawn_suspend_watcher(playback: Arc<PlaybackManager>, events: &Arc<EventBus>) {
+    let events: Weak<EventBus> = Arc::downgrade(events);
+    let spawned = thread::Builder::new()
+        .name("suspend-watcher".into())
+        .spawn(move || loop {
+            let before = SystemTime::now();
+            thread::sleep(POLL_INTERVAL);
+            let Some(events) = events.upgrade() else {
+                break;
+            };
+            // 时钟被往回调时 duration_since 失败，不当作休眠
+            let Ok(elapsed) = SystemTime::now().duration_since(before) else {
+                continue;
+            };
+            if let Some(slept_secs) = suspended_for(POLL_INTERVAL, elapsed) {
+                events.publish(AppEvent::SystemResumed {
+                    slept_secs,
+                    pause: playback.settings().pause_on_suspend,
+                });
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("[playback] 启动休眠检测线程失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspended_for_ignores_scheduling_jitter() {
+        assert_eq!(suspended_for(POLL_INTERVAL, Duration::from_secs(3)), None);
+        assert_eq!(suspended_for(POLL_INTERVAL, Duration::from_millis(1500)), None);
+        assert_eq!(suspended_for(POLL_INTERVAL, Duration::from_secs(2 + 600)), Some(600));
+    }
+}
@@ -0,0 +1,256 @@
+//! 解码预取 — 每首歌一个解码线程，经无锁单生产者 / 单消费者环形缓冲交出 PCM。
+//!
+//! 高采样率文件（如 192 kHz FLAC）解码较重，交叉淡化时两首同时解码容易跟不上。
+//! 把解码放到独立线程后，消费方（渲染循环等）只从环形缓冲取样本，解码节奏与处理节奏互不阻塞，
+//! 相邻两首的解码也能分到不同的核心上。
+//!
+//! ```text
+//! 解码线程 ── decode_while ──► FrameRing（按时长定容量） ──► PrefetchedTrack::read / into_pcm
+//! ```
+//!
+//! - 环形缓冲容量按时长计（[`DEFAULT_BUFFER_MS`]），采样率在解码出第一块后才确定；
+//!   缓冲写满时解码线程让出 CPU 等待，不会无限占用内存。
+//! - 样本统一为交织立体声：单声道复制到两个声道，多声道只取前两个。
+//! - 消费方提前丢弃 [`PrefetchedTrack`] 时解码线程在下一个 packet 处停止。
+//! - [`PrefetchedTrack::spawn_at`] 从指定位置开始解码，经 [`decode_from`](decode::decode_from) 预解码对齐到目标帧。
+
+use crate::module::analysis::decode::{self, PcmBlock};
+use crate::module::platform::PlatformPath;
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 默认预取时长（毫秒）。
+pub const DEFAULT_BUFFER_MS: u32 = 10_000;
+
+/// 输出声道数（交织立体声）。
+const CHANNELS: usize = 2;
+
+/// 缓冲满 / 空时的等待间隔。
+const WAIT: Duration = Duration::from_millis(1);
+
+/// 按时长计算的环形缓冲容量（样本数，至少一帧）。
+pub fn ring_capacity(sample_rate: u32, buffer_ms: u32) -> usize {
+    (sample_rate as usize * buffer_ms as usize / 1000).max(1) * CHANNELS
+}
+
+/// 无锁 SPSC 环形缓冲。`head` 只由消费者推进，`tail` 只由生产者推进，两者单调递增、按容量取模定位。
+struct FrameRing {
+    buf: Box<[UnsafeCell<f32>]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    /// 生产者已写完（正常结束或出错）
+    finished: AtomicBool,
+    /// 消费者已放弃，生产者应停止
+    abandoned: AtomicBool,
+    /// 解码中途出错时的错误信息；只在 `finished` 之后读取
+    error: Mutex<Option<String>>,
+}
+
+// SAFETY: 每个槽位同一时刻只被一方访问——生产者只写 [tail, head + 容量) 区间，
+// 消费者只读 [head, tail) 区间，区间边界经 Release / Acquire 发布。
+unsafe impl Sync for FrameRing {}
+
+impl FrameRing {
+    fn new(capacity: usize) -> Self {
+        Self {
+            buf: (0..capacity).map(|_| UnsafeCell::new(0.0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            finished: AtomicBool::new(false),
+            abandoned: AtomicBool::new(false),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// 写入尽可能多的样本，返回写入数。只能由生产者调用。
+    fn push(&self, samples: &[f32]) -> usize {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        let free = self.buf.len() - tail.wrapping_sub(head);
+        let n = free.min(samples.len());
+        for (i, &sample) in samples[..n].iter().enumerate() {
+            let slot = &self.buf[tail.wrapping_add(i) % self.buf.len()];
+            // SAFETY: 该槽位在 [tail, head + 容量) 内，消费者不会读取
+            unsafe { *slot.get() = sample };
+        }
+        self.tail.store(tail.wrapping_add(n), Ordering::Release);
+        n
+    }
+
+    /// 取出最多 `max` 个样本追加到 `out`，返回取出数。只能由消费者调用。
+    fn pop(&self, out: &mut Vec<f32>, max: usize) -> usize {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        let n = tail.wrapping_sub(head).min(max);
+        out.reserve(n);
+        for i in 0..n {
+            let slot = &self.buf[head.wrapping_add(i) % self.buf.len()];
+            // SAFETY: 该槽位在 [head, tail) 内，生产者不会改写
+            out.push(unsafe { *slot.get() });
+        }
+        self.head.store(head.wrapping_add(n), Ordering::Release);
+        n
+    }
+
+    /// 阻塞写入全部样本；消费者放弃时返回 `false`。
+    fn push_all(&self, mut samples: &[f32]) -> bool {
+        while !samples.is_empty() {
+            if self.abandoned.load(Ordering::Relaxed) {
+                return false;
+            }
+            let n = self.push(samples);
+            samples = &samples[n..];
+            if n == 0 {
+                thread::sleep(WAIT);
+            }
+        }
+        true
+    }
+}
+
+/// 正在后台解码的一首歌。
+pub struct PrefetchedTrack {
+    ring: Arc<FrameRing>,
+    sample_rate: u32,
+}
+
+impl PrefetchedTrack {
+    /// 启动解码线程，等到解码出第一块（确定采样率）后返回。
+    ///
+    /// 文件无法打开或没有解码出任何音频时返回错误。
+    pub fn spawn(path: &PlatformPath, buffer_ms: u32) -> Result<Self, String> {
+        Self::spawn_at(path, 0, buffer_ms)
+    }
+
+    /// 同 [`spawn`](Self::spawn)，但从 `start_ms` 处开始解码（跳转后重新预取时使用）。
+    pub fn spawn_at(path: &PlatformPath, start_ms: u64, buffer_ms: u32) -> Result<Self, String> {
+        let (ready_tx, ready_rx) = mpsc::sync_channel::<Result<(u32, Arc<FrameRing>), String>>(1);
+        let path = path.clone();
+        thread::Builder::new()
+            .name("playback-prefetch".into())
+            .spawn(move || decode_worker(&path, start_ms, buffer_ms, ready_tx))
+            .map_err(|e| format!("启动解码线程失败: {}", e))?;
+        let (sample_rate, ring) = ready_rx
+            .recv()
+            .map_err(|_| "解码线程意外退出".to_string())??;
+        Ok(Self { ring, sample_rate })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// 取出最多 `max` 个样本追加到 `out`；缓冲为空时等待解码线程。
+    ///
+    /// 返回 `Ok(0)` 表示已到结尾；解码中途出错时返回错误。
+    pub fn read(&self, out: &mut Vec<f32>, max: usize) -> Result<usize, String> {
+        loop {
+            // 先看结束标记再取样本：标记之后不会再有新样本写入
+            let finished = self.ring.finished.load(Ordering::Acquire);
+            let n = self.ring.pop(out, max);
+            if n > 0 || max == 0 {
+                return Ok(n);
+            }
+            if finished {
+                return match self.ring.error.lock().unwrap().take() {
+                    Some(e) => Err(e),
+                    None => Ok(0),
+                };
+            }
+            thread::sleep(WAIT);
+        }
+    }
+
+    /// 读完整首，返回交织立体声样本与采样率。
+    pub fn into_pcm(self) -> Result<(Vec<f32>, u32), String> {
+        let mut pcm = Vec::new();
+        let chunk = self.ring.buf.len();
+        while self.read(&mut pcm, chunk)? > 0 {}
+        Ok((pcm, self.sample_rate))
+    }
+}
+
+impl Drop for PrefetchedTrack {
+    fn drop(&mut self) {
+        self.ring.abandoned.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 把一块 PCM 转为交织立体声追加到 `out`。
+pub(crate) fn push_stereo(block: &PcmBlock<'_>, out: &mut Vec<f32>) {
+    for frame in block.samples.chunks_exact(block.channels) {
+        let left = frame[0];
+        out.push(left);
+        out.push(frame.get(1).copied().unwrap_or(left));
+    }
+}
+
+fn decode_worker(
+    path: &PlatformPath,
+    start_ms: u64,
+    buffer_ms: u32,
+    ready: mpsc::SyncSender<Result<(u32, Arc<FrameRing>), String>>,
+) {
+    let mut ring: Option<Arc<FrameRing>> = None;
+    let mut stereo = Vec::new();
+    let result = decode::decode_from(path, start_ms, |block| {
+        if block.sample_rate == 0 || block.samples.is_empty() {
+            return true;
+        }
+        let ring = match &ring {
+            Some(ring) => ring,
+            None => {
+                let created = Arc::new(FrameRing::new(ring_capacity(block.sample_rate, buffer_ms)));
+                if ready.send(Ok((block.sample_rate, created.clone()))).is_err() {
+                    return false;
+                }
+                ring.insert(created)
+            }
+        };
+        stereo.clear();
+        push_stereo(&block, &mut stereo);
+        ring.push_all(&stereo)
+    });
+
+    match ring {
+        Some(ring) => {
+            if let Err(e) = result {
+                *ring.error.lock().unwrap() = Some(e);
+            }
+            ring.finished.store(true, Ordering::Release);
+        }
+        None => {
+            let _ = ready.send(Err(result.err().unwrap_or_else(|| "没有解码出音频数据".to_string())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_wraps_across_threads() {
+        let ring = Arc::new(FrameRing::new(ring_capacity(1000, 3)));
+        assert_eq!(ring.buf.len(), 6);
+
+        let producer = {
+            let ring = ring.clone();
+            thread::spawn(move || {
+                let samples: Vec<f32> = (0..1000).map(|n| n as f32).collect();
+                for chunk in samples.chunks(7) {
+                    assert!(ring.push_all(chunk));
+                }
+                ring.finished.store(true, Ordering::Release);
+            })
+        };
+        let track = PrefetchedTrack { ring, sample_rate: 1000 };
+        let mut out = Vec::new();
+        while track.read(&mut out, 4).unwrap() > 0 {}
+        producer.join().unwrap();
+        assert_eq!(out, (0..1000).map(|n| n as f32).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,270 @@
+//! 队列预加载 — 在远程歌曲即将播放前把音频下载到媒体缓存。
+//!
+//! 前端在播放队列变化（以及跳转 / 切歌）时调用 [`Preloader::set_queue`]，传入接下来要播放的
+//! 歌曲 ID 和当前歌曲的剩余时长。预加载线程据此推算每首歌的开播时间，在开播前
+//! [`PreloadSettings::lead_secs`](super::settings::PreloadSettings) 秒开始下载，
+//! 写入 [`MediaCache`](crate::module::music_source::media_cache::MediaCache)；
+//! 之后 `chordial://audio` 直接从缓存文件按 Range 读取。
+//!
+//! - 只处理没有本地路径的来源（P2P 节点等），本地歌曲直接跳过
+//! - 队列变化时取消旧队列：尚未开始的下载不再进行；进行中的下载无法中途打断
+//!   （来源接口一次返回整个文件），完成后若歌曲已不在队列中则丢弃结果
+//! - 带宽限制按平均速率计：每首下载完成后休眠到「字节数 ÷ 限速」的时长再开始下一首
+
+use super::manager::PlaybackManager;
+use crate::module::cancel::CancellationToken;
+use crate::module::music_library::library::MusicLibrary;
+use crate::module::music_source::registrar::SourceRegistrar;
+use crate::module::music_source::resource;
+use crate::module::music_source::types::{EntityType, SourceId};
+use parking_lot::{Condvar, Mutex};
+use serde::Serialize;
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 没有到期任务时的最长等待间隔（设置变化无通知，靠定期复查生效）。
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 时长未知的歌曲按此估算（秒）。
+const UNKNOWN_DURATION_SECS: u64 = 0;
+
+/// 单首歌曲的预加载状态。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreloadState {
+    /// 等待进入提前量窗口
+    Pending,
+    Downloading,
+    Cached,
+    Failed,
+}
+
+/// 预加载状态快照（供前端展示）。
+#[derive(Debug, Clone, Serialize)]
+pub struct PreloadStatus {
+    pub song_id: String,
+    pub state: PreloadState,
+    /// 预计距开播的毫秒数
+    pub starts_in_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 队列中的一首远程歌曲。
+#[derive(Debug, Clone)]
+struct PlannedTrack {
+    song_id: String,
+    source_id: SourceId,
+    /// 相对 `set_at` 的开播时间（毫秒）
+    start_in_ms: u64,
+    state: PreloadState,
+    error: Option<String>,
+}
+
+struct QueueState {
+    set_at: Instant,
+    tracks: Vec<PlannedTrack>,
+    /// 当前队列的取消令牌，换队列时取消
+    token: CancellationToken,
+}
+
+/// 一次下载任务。
+struct Job {
+    song_id: String,
+    source_id: SourceId,
+    token: CancellationToken,
+    max_kbps: u32,
+}
+
+/// 远程歌曲预加载器。
+pub struct Preloader {
+    library: Arc<MusicLibrary>,
+    registrar: Arc<SourceRegistrar>,
+    playback: Arc<PlaybackManager>,
+    state: Mutex<QueueState>,
+    cond: Condvar,
+}
+
+impl Preloader {
+    /// 创建预加载器；需调用 [`start`](Self::start) 启动后台线程才会真正下载。
+    pub fn new(
+        library: Arc<MusicLibrary>,
+        registrar: Arc<SourceRegistrar>,
+        playback: Arc<PlaybackManager>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            library,
+            registrar,
+            playback,
+            state: Mutex::new(QueueState {
+                set_at: Instant::now(),
+                tracks: Vec::new(),
+                token: CancellationToken::new(),
+            }),
+            cond: Condvar::new(),
+        })
+    }
+
+    /// 启动后台线程。线程只持有弱引用，预加载器释放后自动退出。
+    pub fn start(self: &Arc<Self>) {
+        let weak: Weak<Self> = Arc::downgrade(self);
+        let spawned = thread::Builder::new()
+            .name("playback-preload".into())
+            .spawn(move || {
+                while let Some(preloader) = weak.upgrade() {
+                    if let Some(job) = preloader.next_job() {
+                        preloader.run(job);
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            eprintln!("[preload] 启动预加载线程失败: {}", e);
+        }
+    }
+
+    /// 更新接下来的播放队列。
+    ///
+    /// `song_ids` 按播放顺序排列（不含当前歌曲）；`current_remaining_ms` 为当前歌曲的剩余时长。
+    /// 旧队列中尚未开始的下载被取消，已缓存的歌曲保持缓存状态。
+    pub fn set_queue(&self, song_ids: &[String], current_remaining_ms: u64) {
+        let cache = self.registrar.media_cache();
+        let mut start_in_ms = current_remaining_ms;
+        let mut tracks = Vec::new();
+        for song_id in song_ids {
+            let Some(song) = self.library.get_song(song_id) else {
+                continue;
+            };
+            let starts_at = start_in_ms;
+            start_in_ms += song.duration.unwrap_or(UNKNOWN_DURATION_SECS) * 1000;
+            let Some(source_id) = self.remote_source(&song.source_ids) else {
+                continue;
+            };
+            let cache_key = resource::stream_cache_key(&self.registrar, &source_id);
+            let cached = cache.as_ref().is_some_and(|c| c.contains(&cache_key));
+            tracks.push(PlannedTrack {
+                song_id: song_id.clone(),
+                source_id,
+                start_in_ms: starts_at,
+                state: if cached { PreloadState::Cached } else { PreloadState::Pending },
+                error: None,
+            });
+        }
+
+        let mut state = self.state.lock();
+        // 进行中的下载仍标记为下载中，避免线程空闲时重复发起
+        for track in &mut tracks {
+            let downloading = state.tracks.iter().any(|t| {
+                t.song_id == track.song_id && t.state == PreloadState::Downloading
+            });
+            if downloading {
+                track.state = PreloadState::Downloading;
+            }
+        }
+        state.token.cancel();
+        state.token = CancellationToken::new();
+        state.set_at = Instant::now();
+        state.tracks = tracks;
+        self.cond.notify_all();
+    }
+
+    /// 当前队列的预加载状态。
+    pub fn status(&self) -> Vec<PreloadStatus> {
+        let state = self.state.lock();
+        let elapsed = state.set_at.elapsed().as_millis() as u64;
+        state
+            .tracks
+            .iter()
+            .map(|t| PreloadStatus {
+                song_id: t.song_id.clone(),
+                state: t.state.clone(),
+                starts_in_ms: t.start_in_ms.saturating_sub(elapsed),
+                error: t.error.clone(),
+            })
+            .collect()
+    }
+
+    /// 选出歌曲的远程来源 ID；任一来源能提供本地路径时返回 `None`（无需预加载）。
+    fn remote_source(&self, source_ids: &[SourceId]) -> Option<SourceId> {
+        let mut remote = None;
+        for sid in source_ids.iter().filter(|s| s.entity_type == EntityType::Song) {
+            let Some(source) = self.registrar.get(&sid.source_name) else {
+                continue;
+            };
+            if source.song_file_path(&sid.entity_id).is_some() {
+                return None;
+            }
+            remote.get_or_insert_with(|| sid.clone());
+        }
+        remote
+    }
+
+    /// 等待下一首进入提前量窗口的歌曲，最多等 [`IDLE_POLL_INTERVAL`]。
+    fn next_job(&self) -> Option<Job> {
+        let settings = self.playback.settings().preload;
+        let mut state = self.state.lock();
+        let mut wait = IDLE_POLL_INTERVAL;
+        if settings.enabled && self.registrar.media_cache().is_some() {
+            let elapsed = state.set_at.elapsed().as_millis() as u64;
+            let lead_ms = u64::from(settings.lead_secs) * 1000;
+            let token = state.token.clone();
+            if let Some(track) = state
+                .tracks
+                .iter_mut()
+                .find(|t| t.state == PreloadState::Pending)
+            {
+                let due_in = track.start_in_ms.saturating_sub(lead_ms).saturating_sub(elapsed);
+                if due_in == 0 {
+                    track.state = PreloadState::Downloading;
+                    return Some(Job {
+                        song_id: track.song_id.clone(),
+                        source_id: track.source_id.clone(),
+                        token,
+                        max_kbps: settings.max_kbps,
+                    });
+                }
+                wait = wait.min(Duration::from_millis(due_in));
+            }
+        }
+        self.cond.wait_for(&mut state, wait);
+        None
+    }
+
+    /// 执行下载并写入缓存。
+    fn run(&self, job: Job) {
+        let Some(cache) = self.registrar.media_cache() else {
+            return;
+        };
+        let started = Instant::now();
+        let result = resource::get_song_file(&self.registrar, &job.source_id);
+        let bytes = result.as_ref().map_or(0, |data| data.len() as u64);
+
+        let mut state = self.state.lock();
+        // 队列已换且不再包含这首歌时丢弃下载结果
+        let still_queued = state.tracks.iter().any(|t| t.song_id == job.song_id);
+        if !job.token.is_cancelled() || still_queued {
+            let cache_key = resource::stream_cache_key(&self.registrar, &job.source_id);
+            let outcome = result.and_then(|data| cache.store(&cache_key, &data));
+            if let Some(track) = state.tracks.iter_mut().find(|t| t.song_id == job.song_id) {
+                match outcome {
+                    Ok(_) => {
+                        track.state = PreloadState::Cached;
+                        track.error = None;
+                    }
+                    Err(e) => {
+                        track.state = PreloadState::Failed;
+                        track.error = Some(e);
+                    }
+                }
+            }
+        }
+        drop(state);
+
+        if job.max_kbps > 0 {
+            let budget = Duration::from_secs_f64(bytes as f64 * 8.0 / (f64::from(job.max_kbps) * 1000.0));
+            if let Some(rest) = budget.checked_sub(started.elapsed()) {
+                thread::sleep(rest);
+            }
+        }
+    }
+}
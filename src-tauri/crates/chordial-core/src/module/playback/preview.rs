@@ -0,0 +1,155 @@
+//! 试听片段 — 搜索结果悬停时播放的一小段音频，不改动播放状态。
+//!
+//! 未指定起点时取整首歌中平均电平最高的一段，通常落在副歌上。
+//! 片段按自身电平归一化到 [`TARGET_DBFS`]，提升量受 [`MAX_BOOST_DB`] 和峰值上限 [`PEAK_CEILING_DBFS`]
+//! 限制，不会削波；首尾各做 [`EDGE_FADE_MS`] 的淡入淡出，悬停切换时不会爆音。
+//!
+//! 输出为 16 bit 立体声 FLAC（见 [`flac`](super::flac)）。项目不含有损编码器，
+//! 10 秒片段约 1.7 MB，调用方应缓存结果。
+
+use super::fade;
+use super::flac::FlacWriter;
+use super::gain::db_to_linear;
+use super::prefetch::push_stereo;
+use crate::module::analysis::decode;
+use crate::module::platform::PlatformPath;
+use crate::module::storage::entry::Ttl;
+use std::io::Cursor;
+
+/// 默认片段时长（秒）。
+pub const DEFAULT_DURATION_SECS: f64 = 10.0;
+
+/// 片段时长上限（秒）。
+pub const MAX_DURATION_SECS: f64 = 30.0;
+
+/// 片段在 Blob 缓存中的有效期。
+pub const CACHE_TTL: Ttl = Ttl::DurationSecs(7 * 24 * 3600);
+
+/// 归一化的目标平均电平（dBFS）。
+const TARGET_DBFS: f32 = -16.0;
+
+/// 归一化的最大提升量（dB），避免把很安静的段落噪声放大。
+const MAX_BOOST_DB: f32 = 12.0;
+
+/// 归一化后的峰值上限（dBFS）。
+const PEAK_CEILING_DBFS: f32 = -1.0;
+
+/// 首尾淡入淡出时长（毫秒）。
+const EDGE_FADE_MS: u64 = 150;
+
+/// 寻找高潮段时的电平采样间隔（毫秒）。
+const HOP_MS: u64 = 500;
+
+/// 片段的缓存键；`start_ms` 为 `None` 表示自动选取的高潮段。
+pub fn cache_key(song_id: &str, start_ms: Option<u64>, duration_ms: u64) -> String {
+    match start_ms {
+        Some(start) => format!("track_preview:{}:{}:{}", song_id, start, duration_ms),
+        None => format!("track_preview:{}:auto:{}", song_id, duration_ms),
+    }
+}
+
+/// 整首歌中平均电平最高、长 `duration_ms` 的一段的起点（毫秒）。
+///
+/// 需要解码整首歌；歌曲比片段还短时返回 0。
+pub fn highlight_start_ms(path: &PlatformPath, duration_ms: u64) -> Result<u64, String> {
+    let mut hops: Vec<f32> = Vec::new();
+    let (mut sum, mut frames, mut hop_frames) = (0f64, 0u64, 0u64);
+    decode::decode_file(path, |block| {
+        if block.sample_rate == 0 || block.channels == 0 {
+            return;
+        }
+        hop_frames = block.sample_rate as u64 * HOP_MS / 1000;
+        for frame in block.samples.chunks_exact(block.channels) {
+            sum += frame.iter().map(|s| (s * s) as f64).sum::<f64>() / block.channels as f64;
+            frames += 1;
+            if frames == hop_frames {
+                hops.push((sum / frames as f64) as f32);
+                sum = 0.0;
+                frames = 0;
+            }
+        }
+    })?;
+
+    let window = (duration_ms / HOP_MS).max(1) as usize;
+    if hops.len() <= window {
+        return Ok(0);
+    }
+    let mut energy: f32 = hops[..window].iter().sum();
+    let (mut best, mut best_energy) = (0, energy);
+    for start in 1..=hops.len() - window {
+        energy += hops[start + window - 1] - hops[start - 1];
+        if energy > best_energy {
+            best = start;
+            best_energy = energy;
+        }
+    }
+    Ok(best as u64 * HOP_MS)
+}
+
+/// 从 `start_ms` 起解码 `duration_ms`，归一化并加首尾淡化后编码为 FLAC。
+pub fn render(path: &PlatformPath, start_ms: u64, duration_ms: u64) -> Result<Vec<u8>, String> {
+    let mut pcm: Vec<f32> = Vec::new();
+    let mut sample_rate = 0u32;
+    decode::decode_from(path, start_ms, |block| {
+        if block.sample_rate == 0 || block.samples.is_empty() {
+            return true;
+        }
+        sample_rate = block.sample_rate;
+        push_stereo(&block, &mut pcm);
+        let wanted = (sample_rate as u64 * duration_ms / 1000) as usize * 2;
+        if pcm.len() >= wanted {
+            pcm.truncate(wanted);
+            return false;
+        }
+        true
+    })?;
+    if pcm.is_empty() {
+        return Err("起点超出歌曲时长".to_string());
+    }
+
+    normalize(&mut pcm);
+    let fade_frames = (sample_rate as u64 * EDGE_FADE_MS / 1000) as usize;
+    fade::apply_fade_in(&mut pcm, 2, fade_frames);
+    fade::apply_fade_out(&mut pcm, 2, fade_frames);
+
+    let mut writer = FlacWriter::new(Cursor::new(Vec::new()), sample_rate)?;
+    writer.write(&pcm)?;
+    Ok(writer.into_inner()?.into_inner())
+}
+
+/// 把片段的平均电平拉到 [`TARGET_DBFS`]，提升量与峰值受限；静音片段不处理。
+fn normalize(pcm: &mut [f32]) {
+    let mean_square = pcm.iter().map(|s| (s * s) as f64).sum::<f64>() / pcm.len() as f64;
+    let peak = pcm.iter().fold(0f32, |m, s| m.max(s.abs()));
+    if mean_square <= 0.0 || peak <= 0.0 {
+        return;
+    }
+    let rms_db = 10.0 * mean_square.log10() as f32;
+    let peak_db = 20.0 * peak.log10();
+    let gain_db = (TARGET_DBFS - rms_db).min(MAX_BOOST_DB).min(PEAK_CEILING_DBFS - peak_db);
+    let linear = db_to_linear(gain_db);
+    for s in pcm.iter_mut() {
+        *s *= linear;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_stays_below_peak_ceiling() {
+        // 平均电平很低但带一个尖峰：提升量由峰值上限决定
+        let mut pcm = vec![0.01f32; 1000];
+        pcm[10] = 0.5;
+        normalize(&mut pcm);
+        let peak = pcm.iter().fold(0f32, |m, s| m.max(s.abs()));
+        assert!((20.0 * peak.log10() - PEAK_CEILING_DBFS).abs() < 0.01);
+
+        // 过响的片段被压低到目标电平
+        let mut loud = vec![0.9f32, -0.9];
+        normalize(&mut loud);
+        let rms_db = 20.0 * loud[0].abs().log10();
+        assert!((rms_db - TARGET_DBFS).abs() < 0.01);
+    }
+}
@@ -0,0 +1,330 @@
+//! 播放队列构建 — 「播放专辑」「播放艺人」时由后端一次排好整个队列。
+//!
+//! 专辑内按碟号 → 音轨号排序，缺少编号的歌曲保持专辑内原有顺序排在最后；
+//! 艺人按专辑发行时间（[`get_albums_by_artist`](crate::module::music_library::library::MusicLibrary::get_albums_by_artist)
+//! 的顺序）依次展开各专辑，不属于任何专辑的歌曲按标题排在末尾。
+//!
+//! 随机播放时若指定了起始歌曲，它固定在队首，其余歌曲打乱，前端从第 0 首开始播即可。
+//! 已隐藏的歌曲（见 [`hidden`](crate::module::music_library::hidden)）不进入专辑 / 艺人队列，也不会被自动续播选中。
+//!
+//! 激活（双击 / 回车）一首歌时由 [`activate_track`] 统一决定替换队列、追加还是插到下一首，
+//! 按歌曲所在的列表类型分别取 [`ActivateAction`] 设置，各界面行为一致。
+//!
+//! 队列最后一首播完后由 [`end_of_queue`] 按 [`EndOfQueueBehavior`] 决定下一步。自动续播没有推荐服务可依赖，
+//! 只按与队列共有的艺人（权重 2）和流派（权重 1）给库中其余音乐打分（有声书 / 播客不参与）；一首都挑不出来时退回停止，
+//! 避免前端为空队列继续预加载。
+
+use super::settings::{ActivateAction, ActivateSurface, EndOfQueueBehavior};
+use crate::module::music_library::library::MusicLibrary;
+use crate::module::music_library::models::Song;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 自动续播一次追加的歌曲数。
+pub const AUTOPLAY_BATCH: usize = 20;
+
+/// 构建好的播放队列。
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayQueue {
+    pub songs: Vec<Song>,
+    /// 从队列中的第几首开始播放
+    pub start_index: usize,
+}
+
+impl PlayQueue {
+    /// 起始歌曲之后的歌曲 ID（交给预加载器）。
+    pub fn upcoming_ids(&self) -> Vec<String> {
+        self.songs
+            .iter()
+            .skip(self.start_index + 1)
+            .map(|s| s.id.clone())
+            .collect()
+    }
+}
+
+/// 队列播完后的处理结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct EndOfQueuePlan {
+    /// 实际采取的行为（自动续播找不到歌曲时为 `Stop`）
+    pub behavior: EndOfQueueBehavior,
+    /// 自动续播时追加到队尾的歌曲，其余行为为空
+    pub songs: Vec<Song>,
+}
+
+/// 被激活的歌曲所在的列表。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActivateContext {
+    Album { album_id: String },
+    Artist { artist_id: String },
+    Playlist { playlist_id: String },
+    /// 搜索结果，按显示顺序
+    Search { song_ids: Vec<String> },
+    /// 曲库列表等前端自带的列表，按显示顺序
+    Library { song_ids: Vec<String> },
+}
+
+impl ActivateContext {
+    pub fn surface(&self) -> ActivateSurface {
+        match self {
+            Self::Album { .. } => ActivateSurface::Album,
+            Self::Artist { .. } => ActivateSurface::Artist,
+            Self::Playlist { .. } => ActivateSurface::Playlist,
+            Self::Search { .. } => ActivateSurface::Search,
+            Self::Library { .. } => ActivateSurface::Library,
+        }
+    }
+}
+
+/// [`activate_track`] 的结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackActivation {
+    pub action: ActivateAction,
+    /// `Replace` 时为替换后的整个队列；`Append` / `InsertNext` 时只含被激活的歌曲，由前端放入现有队列
+    pub queue: PlayQueue,
+}
+
+/// 激活 `track_id`：`Replace` 时按列表排好新队列并从该歌曲开始，其余只返回这首歌。
+pub fn activate_track(
+    library: &MusicLibrary,
+    track_id: &str,
+    context: &ActivateContext,
+    action: ActivateAction,
+) -> Result<TrackActivation, String> {
+    if action != ActivateAction::Replace {
+        let song = library
+            .get_song(track_id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", track_id))?;
+        return Ok(TrackActivation {
+            action,
+            queue: PlayQueue {
+                songs: vec![song],
+                start_index: 0,
+            },
+        });
+    }
+    let queue = match context {
+        ActivateContext::Album { album_id } => album_queue(library, album_id, Some(track_id), false)?,
+        ActivateContext::Artist { artist_id } => start_at(artist_queue(library, artist_id)?.songs, track_id)?,
+        ActivateContext::Playlist { playlist_id } => start_at(library.get_playlist_songs(playlist_id)?, track_id)?,
+        ActivateContext::Search { song_ids } | ActivateContext::Library { song_ids } => {
+            start_at(library.get_songs_by_ids(song_ids), track_id)?
+        }
+    };
+    Ok(TrackActivation { action, queue })
+}
+
+/// 以列表为队列、从 `track_id` 开始播放；列表中有重复时取第一次出现的位置。
+fn start_at(songs: Vec<Song>, track_id: &str) -> Result<PlayQueue, String> {
+    let start_index = songs
+        .iter()
+        .position(|s| s.id == track_id)
+        .ok_or_else(|| format!("歌曲 '{}' 不在该列表中", track_id))?;
+    Ok(PlayQueue { songs, start_index })
+}
+
+/// 专辑的播放队列。`start_track_id` 不在专辑中时报错。
+pub fn album_queue(
+    library: &MusicLibrary,
+    album_id: &str,
+    start_track_id: Option<&str>,
+    shuffle: bool,
+) -> Result<PlayQueue, String> {
+    if library.get_album(album_id).is_none() {
+        return Err(format!("专辑 '{}' 不存在", album_id));
+    }
+    let mut songs = library.without_hidden(library.get_songs_in_album(album_id));
+    sort_by_track(&mut songs);
+
+    let start_index = match start_track_id {
+        Some(id) => songs
+            .iter()
+            .position(|s| s.id == id)
+            .ok_or_else(|| format!("歌曲 '{}' 不在该专辑中", id))?,
+        None => 0,
+    };
+    if !shuffle {
+        return Ok(PlayQueue { songs, start_index });
+    }
+
+    let first = start_track_id.map(|_| songs.remove(start_index));
+    shuffle_songs(&mut songs);
+    if let Some(first) = first {
+        songs.insert(0, first);
+    }
+    Ok(PlayQueue {
+        songs,
+        start_index: 0,
+    })
+}
+
+/// 艺人全部歌曲的播放队列：按专辑时间顺序，专辑内按音轨顺序。
+pub fn artist_queue(library: &MusicLibrary, artist_id: &str) -> Result<PlayQueue, String> {
+    if library.get_artist(artist_id).is_none() {
+        return Err(format!("艺术家 '{}' 不存在", artist_id));
+    }
+    let artist_songs = library.without_hidden(library.get_songs_by_artist(artist_id));
+    let wanted: HashSet<&str> = artist_songs.iter().map(|s| s.id.as_str()).collect();
+
+    let mut songs = Vec::with_capacity(artist_songs.len());
+    let mut seen = HashSet::new();
+    for album in library.get_albums_by_artist(artist_id) {
+        let mut tracks = library.get_songs_in_album(&album.id);
+        sort_by_track(&mut tracks);
+        // 合辑里其他艺人的歌曲不算
+        tracks.retain(|s| wanted.contains(s.id.as_str()) && seen.insert(s.id.clone()));
+        songs.extend(tracks);
+    }
+
+    let mut rest: Vec<Song> = artist_songs
+        .into_iter()
+        .filter(|s| !seen.contains(&s.id))
+        .collect();
+    rest.sort_by_cached_key(|s| s.title.to_lowercase());
+    songs.extend(rest);
+
+    Ok(PlayQueue {
+        songs,
+        start_index: 0,
+    })
+}
+
+/// 决定队列播完后的行为。`queue_ids` 为刚播完的队列中的歌曲 ID。
+pub fn end_of_queue(
+    library: &MusicLibrary,
+    behavior: EndOfQueueBehavior,
+    queue_ids: &[String],
+) -> EndOfQueuePlan {
+    let songs = match behavior {
+        EndOfQueueBehavior::Autoplay => {
+            let queue: Vec<Song> = queue_ids.iter().filter_map(|id| library.get_song(id)).collect();
+            let hidden = library.hidden_set();
+            let candidates = library.get_all_songs().into_values().filter(|s| !hidden.hides_song(s));
+            similar_songs(&queue, candidates, AUTOPLAY_BATCH)
+        }
+        _ => Vec::new(),
+    };
+    let behavior = match behavior {
+        EndOfQueueBehavior::Autoplay if songs.is_empty() => EndOfQueueBehavior::Stop,
+        other => other,
+    };
+    EndOfQueuePlan { behavior, songs }
+}
+
+/// 从 `candidates` 中挑出与 `queue` 最相似、且不在队列中的至多 `limit` 首歌。
+/// 得分为 0 的歌曲不入选；同分按标题排序，保证结果稳定。
+fn similar_songs(queue: &[Song], candidates: impl Iterator<Item = Song>, limit: usize) -> Vec<Song> {
+    let queued: HashSet<&str> = queue.iter().map(|s| s.id.as_str()).collect();
+    let artists: HashSet<&str> = queue.iter().flat_map(|s| s.artist_ids.iter().map(String::as_str)).collect();
+    let genres: HashSet<String> = queue
+        .iter()
+        .flat_map(|s| s.genres.iter().map(|g| g.to_lowercase()))
+        .collect();
+
+    let mut scored: Vec<(usize, Song)> = candidates
+        .filter(|s| s.content_type.is_music() && !queued.contains(s.id.as_str()))
+        .filter_map(|s| {
+            let score = 2 * s.artist_ids.iter().filter(|a| artists.contains(a.as_str())).count()
+                + s.genres.iter().filter(|g| genres.contains(&g.to_lowercase())).count();
+            (score > 0).then_some((score, s))
+        })
+        .collect();
+    scored.sort_by(|(a, x), (b, y)| b.cmp(a).then_with(|| x.title.cmp(&y.title)));
+    scored.into_iter().take(limit).map(|(_, s)| s).collect()
+}
+
+/// 按碟号、音轨号排序；稳定排序，没有编号的保持原顺序排在最后。
+fn sort_by_track(songs: &mut [Song]) {
+    songs.sort_by_key(|s| {
+        (
+            s.track_number.is_none(),
+            s.disc_number.unwrap_or(1),
+            s.track_number,
+        )
+    });
+}
+
+/// Fisher–Yates 洗牌。种子取自 UUID v4（系统随机源），避免为此单独引入随机数依赖。
+pub(super) fn shuffle_songs<T>(songs: &mut [T]) {
+    let mut state = uuid::Uuid::new_v4().as_u64_pair().0 | 1;
+    for i in (1..songs.len()).rev() {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = (state % (i as u64 + 1)) as usize;
+        songs.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(id: &str, disc: Option<u32>, track: Option<u32>) -> Song {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": id,
+            "artist_names": [],
+            "album_title": null,
+            "duration": null,
+            "artist_ids": [],
+            "album_id": null,
+            "lyric_id": null,
+            "source_ids": [],
+            "disc_number": disc,
+            "track_number": track,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_activate_context_surface_and_start() {
+        let context: ActivateContext =
+            serde_json::from_value(serde_json::json!({ "kind": "search", "song_ids": ["a", "b"] })).unwrap();
+        assert_eq!(context.surface(), ActivateSurface::Search);
+
+        let queue = start_at(vec![song("a", None, None), song("b", None, None)], "b").unwrap();
+        assert_eq!(queue.start_index, 1);
+        assert_eq!(queue.upcoming_ids(), Vec::<String>::new());
+        assert!(start_at(vec![song("a", None, None)], "x").is_err());
+    }
+
+    #[test]
+    fn test_sort_by_disc_then_track() {
+        let mut songs = vec![
+            song("untagged", None, None),
+            song("d2t1", Some(2), Some(1)),
+            song("d1t2", Some(1), Some(2)),
+            song("t1", None, Some(1)),
+        ];
+        sort_by_track(&mut songs);
+        let order: Vec<&str> = songs.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(order, ["t1", "d1t2", "d2t1", "untagged"]);
+
+        let mut many: Vec<Song> = (0..50).map(|i| song(&i.to_string(), None, Some(i))).collect();
+        shuffle_songs(&mut many);
+        let ids: HashSet<String> = many.iter().map(|s| s.id.clone()).collect();
+        assert_eq!(ids.len(), 50);
+    }
+
+    #[test]
+    fn test_similar_songs_prefers_shared_artist() {
+        let with = |id: &str, artists: &[&str], genres: &[&str]| {
+            let mut s = song(id, None, None);
+            s.artist_ids = artists.iter().map(|a| a.to_string()).collect();
+            s.genres = genres.iter().map(|g| g.to_string()).collect();
+            s
+        };
+        let queue = vec![with("q", &["a1"], &["Rock"])];
+        let candidates = vec![
+            with("q", &["a1"], &["Rock"]),
+            with("genre", &["a2"], &["rock"]),
+            with("artist", &["a1"], &[]),
+            with("unrelated", &["a3"], &["Jazz"]),
+        ];
+        let picked = similar_songs(&queue, candidates.into_iter(), 10);
+        let ids: Vec<&str> = picked.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["artist", "genre"]);
+    }
+}
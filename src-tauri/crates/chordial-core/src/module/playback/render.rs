@@ -0,0 +1,357 @@
+//! 离线混音渲染 — 把一组歌曲按交叉淡化首尾相接，写成单个 FLAC 文件 + CUE 分轨表。
+//!
+//! 不经过音频输出设备，解码完就写，速度只受解码与磁盘限制。
+//! 逐首流式处理：内存中只保留当前歌曲和上一首尾部的淡出段。
+//! 处理当前歌曲时下一首已在另一线程上解码（见 [`prefetch`](super::prefetch)）。
+//!
+//! 局限：
+//! - 输出固定为 16 bit 立体声 FLAC（量化时按 [`RenderOptions::dither`] 抖动）；项目不含 MP3 编码器，暂不支持 MP3 输出。
+//! - 采样率不同的歌曲用线性插值重采样到第一首歌曲的采样率。
+//! - 上一首带明显尾奏时，过渡延长到覆盖整段尾奏（最多为设定时长的 [`MAX_OUTRO_STRETCH`] 倍），
+//!   下一首压着尾奏淡入；结尾静音在淡化前去掉。
+//! - 短曲目 / 过场按 [`RenderOptions::short_tracks`] 缩短过渡，太短时直接拼接，规则与实时播放相同。
+//! - 开头 / 结尾可按 [`RenderOptions`] 淡入淡出，与实时播放的启停淡化一致。
+//! - 尚无节拍分析，不做 BPM 对齐。
+
+use super::crossfade;
+use super::fade;
+use super::flac::FlacWriter;
+use super::prefetch::{PrefetchedTrack, DEFAULT_BUFFER_MS};
+use super::settings::{CrossfadeCurve, CurvePoint, DitherMode, ShortTrackThresholds};
+use crate::module::analysis::segments::SegmentDetector;
+use crate::module::cancel::CancellationToken;
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// CUE 时间单位：每秒 75 帧。
+const CUE_FRAMES_PER_SEC: u64 = 75;
+
+/// 按尾奏延长过渡时，最多延长到 `crossfade_ms` 的倍数。
+const MAX_OUTRO_STRETCH: u64 = 3;
+
+/// 待渲染的一首歌。
+#[derive(Debug, Clone)]
+pub struct RenderTrack {
+    pub path: PlatformPath,
+    pub title: String,
+    pub performer: String,
+}
+
+/// 渲染参数。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    /// 相邻歌曲的交叉淡化时长（毫秒），0 表示直接拼接
+    pub crossfade_ms: u32,
+    /// 量化到 16 bit 时的抖动方式
+    pub dither: DitherMode,
+    /// 整段混音开头的淡入时长（毫秒）
+    pub fade_in_ms: u32,
+    /// 整段混音结尾的淡出时长（毫秒）
+    pub fade_out_ms: u32,
+    /// 短曲目的过渡缩短规则
+    pub short_tracks: ShortTrackThresholds,
+    /// 交叉淡化的增益曲线
+    pub curve: CrossfadeCurve,
+    /// `curve` 为自定义时的控制点
+    pub custom_curve: Vec<CurvePoint>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            crossfade_ms: 6000,
+            dither: DitherMode::default(),
+            fade_in_ms: 0,
+            fade_out_ms: 0,
+            short_tracks: ShortTrackThresholds::default(),
+            curve: CrossfadeCurve::default(),
+            custom_curve: Vec::new(),
+        }
+    }
+}
+
+/// CUE 中的一条分轨。
+#[derive(Debug, Clone, Serialize)]
+pub struct CueTrack {
+    pub title: String,
+    pub performer: String,
+    /// 在输出文件中的起点（毫秒），即开始淡入的位置
+    pub start_ms: u64,
+}
+
+/// 渲染结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderSummary {
+    pub output_path: String,
+    pub cue_path: String,
+    pub duration_ms: u64,
+    pub sample_rate: u32,
+    pub tracks: Vec<CueTrack>,
+    /// 解码失败被跳过的文件
+    pub skipped: Vec<String>,
+    /// 中途被取消（输出文件只包含已渲染部分）
+    pub cancelled: bool,
+}
+
+/// 渲染混音到 `output`（FLAC），同目录写入同名 `.cue`。
+///
+/// 每渲染完一首调用一次 `on_progress(已完成, 总数)`；`token` 取消后在下一首开始前停止。
+pub fn render_mix(
+    queue: &[RenderTrack],
+    output: &Path,
+    options: RenderOptions,
+    token: &CancellationToken,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<RenderSummary, String> {
+    if queue.is_empty() {
+        return Err("播放队列为空".to_string());
+    }
+
+    let mut writer: Option<FlacWriter> = None;
+    let mut sample_rate = 0u32;
+    // 上一首留待与下一首交叉淡化的尾部
+    let mut tail: Vec<f32> = Vec::new();
+    let mut written_frames = 0u64;
+    let mut tracks = Vec::new();
+    let mut skipped = Vec::new();
+    let mut cancelled = false;
+    let mut upcoming: Option<Result<PrefetchedTrack, String>> = None;
+
+    for (i, track) in queue.iter().enumerate() {
+        if token.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+        let current = upcoming
+            .take()
+            .unwrap_or_else(|| PrefetchedTrack::spawn(&track.path, DEFAULT_BUFFER_MS));
+        upcoming = queue
+            .get(i + 1)
+            .map(|next| PrefetchedTrack::spawn(&next.path, DEFAULT_BUFFER_MS));
+        let (mut pcm, rate) = match current.and_then(PrefetchedTrack::into_pcm) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                eprintln!("[render] 跳过 {}: {}", track.title, e);
+                skipped.push(track.title.clone());
+                on_progress(i + 1, queue.len());
+                continue;
+            }
+        };
+        let out = match writer {
+            Some(ref mut out) => {
+                if rate != sample_rate {
+                    pcm = resample_linear(&pcm, rate, sample_rate);
+                }
+                out
+            }
+            None => {
+                // 输出采样率取自第一首可解码的歌曲
+                sample_rate = rate;
+                let fade_in = options.fade_in_ms as usize * rate as usize / 1000;
+                fade::apply_fade_in(&mut pcm, 2, fade_in);
+                writer.insert(FlacWriter::create(output, sample_rate)?.with_dither(options.dither))
+            }
+        };
+
+        let next_ms = frames_to_ms(pcm.len() / 2, sample_rate);
+        let fade_ms = options
+            .short_tracks
+            .fit(frames_to_ms(tail.len() / 2, sample_rate) as u32, None, None, Some(next_ms))
+            .duration_ms;
+        let fade_frames = (fade_ms as usize * sample_rate as usize / 1000).min(tail.len() / 2);
+        // 淡入段从上一首尾部的淡出起点开始
+        let start_frame = written_frames + (tail.len() / 2 - fade_frames) as u64;
+        tracks.push(CueTrack {
+            title: track.title.clone(),
+            performer: track.performer.clone(),
+            start_ms: start_frame * 1000 / sample_rate as u64,
+        });
+
+        crossfade_into(&mut tail, &mut pcm, fade_frames, &options);
+        out.write(&tail)?;
+        written_frames += (tail.len() / 2) as u64;
+
+        // 保留本首尾部供下一首淡化；最后一首整首写出
+        // 最后一首留出结尾淡出段，收尾时统一处理
+        let keep = if i + 1 < queue.len() && options.crossfade_ms > 0 {
+            let track_ms = frames_to_ms(pcm.len() / 2, sample_rate);
+            let transition_ms =
+                trim_for_transition(&mut pcm, sample_rate, options.crossfade_ms as u64);
+            let fitted = options.short_tracks.fit(transition_ms as u32, Some(track_ms), None, None);
+            (fitted.duration_ms as usize * sample_rate as usize / 1000).min(pcm.len() / 2) * 2
+        } else if i + 1 == queue.len() {
+            (options.fade_out_ms as usize * sample_rate as usize / 1000).min(pcm.len() / 2) * 2
+        } else {
+            0
+        };
+        let split = pcm.len() - keep;
+        out.write(&pcm[..split])?;
+        written_frames += (split / 2) as u64;
+        tail = pcm.split_off(split);
+        on_progress(i + 1, queue.len());
+    }
+
+    let Some(mut out) = writer else {
+        return Err("队列中没有可解码的歌曲".to_string());
+    };
+    let fade_out = options.fade_out_ms as usize * sample_rate as usize / 1000;
+    fade::apply_fade_out(&mut tail, 2, fade_out);
+    out.write(&tail)?;
+    written_frames += (tail.len() / 2) as u64;
+    out.finish()?;
+
+    let cue_path = output.with_extension("cue");
+    std::fs::write(&cue_path, build_cue(output, &tracks))
+        .map_err(|e| format!("写入 CUE 文件失败: {}", e))?;
+
+    Ok(RenderSummary {
+        output_path: output.to_string_lossy().into_owned(),
+        cue_path: cue_path.to_string_lossy().into_owned(),
+        duration_ms: written_frames * 1000 / sample_rate as u64,
+        sample_rate,
+        tracks,
+        skipped,
+        cancelled,
+    })
+}
+
+/// 把 `tail` 的末尾 `fade_frames` 帧与 `next` 的开头按 `options` 的曲线交叉淡化。
+///
+/// 混合结果留在 `tail` 中，`next` 去掉已被混入的开头部分。
+fn crossfade_into(tail: &mut [f32], next: &mut Vec<f32>, fade_frames: usize, options: &RenderOptions) {
+    if fade_frames == 0 {
+        return;
+    }
+    let offset = tail.len() - fade_frames * 2;
+    for f in 0..fade_frames {
+        let t = (f as f32 + 0.5) / fade_frames as f32;
+        let (gain_out, gain_in) = crossfade::curve_gains(options.curve, &options.custom_curve, t);
+        for ch in 0..2 {
+            let idx = offset + f * 2 + ch;
+            tail[idx] = tail[idx] * gain_out + next[f * 2 + ch] * gain_in;
+        }
+    }
+    next.drain(..fade_frames * 2);
+}
+
+fn frames_to_ms(frames: usize, sample_rate: u32) -> u64 {
+    frames as u64 * 1000 / sample_rate as u64
+}
+
+/// 去掉结尾静音，并按尾奏长度确定与下一首的过渡时长（毫秒）。
+fn trim_for_transition(pcm: &mut Vec<f32>, sample_rate: u32, crossfade_ms: u64) -> u64 {
+    let mut detector = SegmentDetector::new(sample_rate);
+    detector.push(pcm, 2);
+    let segments = detector.finish();
+    if segments.audio_end_ms > 0 {
+        let end = (segments.audio_end_ms * sample_rate as u64 / 1000) as usize * 2;
+        pcm.truncate(end.min(pcm.len()));
+    }
+    crossfade_ms.max(segments.outro_ms().min(crossfade_ms * MAX_OUTRO_STRETCH))
+}
+
+/// 交织立体声的线性插值重采样。
+fn resample_linear(pcm: &[f32], from: u32, to: u32) -> Vec<f32> {
+    let in_frames = pcm.len() / 2;
+    let out_frames = (in_frames as u64 * to as u64 / from as u64) as usize;
+    let step = from as f64 / to as f64;
+    let mut out = Vec::with_capacity(out_frames * 2);
+    for i in 0..out_frames {
+        let pos = i as f64 * step;
+        let idx = pos as usize;
+        let frac = (pos - idx as f64) as f32;
+        let next = (idx + 1).min(in_frames - 1);
+        for ch in 0..2 {
+            let a = pcm[idx * 2 + ch];
+            let b = pcm[next * 2 + ch];
+            out.push(a + (b - a) * frac);
+        }
+    }
+    out
+}
+
+/// 生成 CUE 分轨表（`INDEX 01 mm:ss:ff`，ff 为 1/75 秒）。
+fn build_cue(output: &Path, tracks: &[CueTrack]) -> String {
+    let file_name = output
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut cue = format!("FILE \"{}\" WAVE\n", escape_cue(&file_name));
+    for (i, track) in tracks.iter().enumerate() {
+        let frames = track.start_ms * CUE_FRAMES_PER_SEC / 1000;
+        let _ = write!(
+            cue,
+            "  TRACK {:02} AUDIO\n    TITLE \"{}\"\n    PERFORMER \"{}\"\n    INDEX 01 {:02}:{:02}:{:02}\n",
+            i + 1,
+            escape_cue(&track.title),
+            escape_cue(&track.performer),
+            frames / CUE_FRAMES_PER_SEC / 60,
+            frames / CUE_FRAMES_PER_SEC % 60,
+            frames % CUE_FRAMES_PER_SEC,
+        );
+    }
+    cue
+}
+
+fn escape_cue(s: &str) -> String {
+    s.replace('"', "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_crossfade_and_cue() {
+        let dir = std::env::temp_dir().join(format!("chordial_render_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rate = 8000u32;
+        let mut queue = Vec::new();
+        for (i, freq) in [440.0f32, 660.0].iter().enumerate() {
+            let input = dir.join(format!("in{}.flac", i));
+            let mut w = FlacWriter::create(&input, rate).unwrap();
+            let samples: Vec<f32> = (0..rate as usize * 2)
+                .flat_map(|n| {
+                    let s = (n as f32 * freq * std::f32::consts::TAU / rate as f32).sin() * 0.5;
+                    [s, s]
+                })
+                .collect();
+            w.write(&samples).unwrap();
+            assert_eq!(w.finish().unwrap(), rate as u64 * 2);
+            queue.push(RenderTrack {
+                path: input,
+                title: format!("T{}", i),
+                performer: "A".into(),
+            });
+        }
+
+        let output = dir.join("mix.flac");
+        let options = RenderOptions {
+            crossfade_ms: 500,
+            ..RenderOptions::default()
+        };
+        let summary = render_mix(
+            &queue,
+            &output,
+            options,
+            &CancellationToken::new(),
+            |_, _| {},
+        )
+        .unwrap();
+        // 两首各 2 秒，重叠 0.5 秒
+        assert_eq!(summary.duration_ms, 3500);
+        assert_eq!(summary.tracks[1].start_ms, 1500);
+
+        let (decoded, decoded_rate) = PrefetchedTrack::spawn(&output, DEFAULT_BUFFER_MS)
+            .and_then(PrefetchedTrack::into_pcm)
+            .unwrap();
+        assert_eq!(decoded_rate, rate);
+        assert_eq!(decoded.len() / 2, rate as usize * 7 / 2);
+        let cue = std::fs::read_to_string(dir.join("mix.cue")).unwrap();
+        assert!(cue.contains("TRACK 02 AUDIO") && cue.contains("INDEX 01 00:01:37"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,471 @@
+//! 播放设置数据结构。
+
+use super::dsp::{self, DspProfile};
+use super::silence::SilenceSkipSettings;
+use crate::module::music_source::pcm_cache;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 用户可选的播放速度预设。
+pub const PLAYBACK_RATE_PRESETS: [f64; 8] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0];
+
+/// 允许的最低播放速度。
+pub const MIN_PLAYBACK_RATE: f64 = 0.75;
+
+/// 允许的最高播放速度。
+pub const MAX_PLAYBACK_RATE: f64 = 3.0;
+
+/// 内容类型 — 播放速度按类型分别记忆。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    #[default]
+    Music,
+    Podcast,
+    Audiobook,
+}
+
+impl ContentType {
+    pub fn is_music(&self) -> bool {
+        *self == Self::Music
+    }
+
+    /// 是否为语音类内容（播客 / 有声书）。
+    pub fn is_spoken(self) -> bool {
+        matches!(self, Self::Podcast | Self::Audiobook)
+    }
+
+    /// 快进 / 快退的默认步长（秒）：音乐 10 秒，播客 / 有声书 30 秒。
+    pub fn default_skip_secs(self) -> u32 {
+        if self.is_spoken() { 30 } else { 10 }
+    }
+}
+
+/// 变速（time-stretch）质量档位 — 在延迟与音质之间取舍。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeStretchQuality {
+    /// 低延迟：短窗口 WSOLA，拖动 / 调速响应最快，高倍速下可能有颤音
+    LowLatency,
+    /// 均衡（默认）
+    #[default]
+    Balanced,
+    /// 高音质：相位声码器，延迟与 CPU 占用最高
+    HighQuality,
+}
+
+/// 变速算法。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StretchAlgorithm {
+    Wsola,
+    PhaseVocoder,
+}
+
+/// 某一档位对应的变速器参数。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StretchParams {
+    pub algorithm: StretchAlgorithm,
+    /// 分析窗口长度（毫秒）
+    pub window_ms: u32,
+    /// 相邻窗口重叠比例（0.0 ~ 1.0）
+    pub overlap: f32,
+    /// 变速器引入的额外延迟（毫秒）
+    pub latency_ms: u32,
+}
+
+impl TimeStretchQuality {
+    /// 档位对应的变速器参数。
+    pub fn params(self) -> StretchParams {
+        match self {
+            Self::LowLatency => StretchParams {
+                algorithm: StretchAlgorithm::Wsola,
+                window_ms: 20,
+                overlap: 0.25,
+                latency_ms: 20,
+            },
+            Self::Balanced => StretchParams {
+                algorithm: StretchAlgorithm::Wsola,
+                window_ms: 40,
+                overlap: 0.5,
+                latency_ms: 40,
+            },
+            Self::HighQuality => StretchParams {
+                algorithm: StretchAlgorithm::PhaseVocoder,
+                window_ms: 93,
+                overlap: 0.75,
+                latency_ms: 93,
+            },
+        }
+    }
+}
+
+/// 卡拉 OK 辅助设置 — 在人声区间内压低原唱音量。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KaraokeSettings {
+    /// 是否启用（默认关闭）
+    pub enabled: bool,
+    /// 人声区间内的音量增益（dB，负值表示压低）
+    pub vocal_gain_db: f32,
+}
+
+impl Default for KaraokeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            vocal_gain_db: -12.0,
+        }
+    }
+}
+
+/// 降低位深（如渲染为 16 bit）时的抖动方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DitherMode {
+    /// 直接四舍五入
+    Off,
+    /// 三角分布噪声（默认）
+    #[default]
+    Tpdf,
+    /// TPDF + 一阶噪声整形，底噪推向高频
+    NoiseShaped,
+}
+
+/// 队列最后一首播完后的行为。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EndOfQueueBehavior {
+    /// 停止播放（默认）
+    #[default]
+    Stop,
+    /// 从队首重新播放
+    RepeatQueue,
+    /// 追加与队列相似的歌曲继续播放（同艺人 / 同流派）
+    Autoplay,
+}
+
+/// 激活歌曲（双击 / 回车）时所在的列表类型，各自可设不同的 [`ActivateAction`]。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivateSurface {
+    Album,
+    Artist,
+    Playlist,
+    Search,
+    /// 曲库列表及其他前端自带的歌曲列表
+    Library,
+}
+
+/// 激活歌曲时对播放队列的处理。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivateAction {
+    /// 用歌曲所在的列表替换队列，从该歌曲开始播放（默认）
+    #[default]
+    Replace,
+    /// 追加到队尾，不打断当前播放
+    Append,
+    /// 插到当前歌曲之后，不打断当前播放
+    InsertNext,
+}
+
+/// 切歌方式 — 按播放列表选择。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackTransition {
+    /// 按 [`CrossfadeSettings`] 交叉淡化（关闭时直接切歌）（默认）
+    #[default]
+    Crossfade,
+    /// 无缝衔接：当前歌曲解码流的最后一个采样之后紧接下一首的第一个采样，
+    /// 不淡化、不去首尾静音。适合连续录制的现场 / 古典专辑
+    Gapless,
+}
+
+/// 音量滑块到增益的映射曲线。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeCurve {
+    /// 增益与滑块位置成正比（旧行为）
+    #[default]
+    Linear,
+    /// 按分贝均匀分布：滑块每格对应相同的响度变化，覆盖 [`VOLUME_CURVE_RANGE_DB`] 的动态范围
+    Logarithmic,
+}
+
+/// 对数曲线覆盖的动态范围（dB）：滑块最左端之上一格对应约 -50 dB。
+pub const VOLUME_CURVE_RANGE_DB: f32 = 50.0;
+
+impl VolumeCurve {
+    /// 滑块位置（0.0 ~ 1.0）对应的线性增益。滑块为 0 时始终静音。
+    pub fn gain(self, volume: f32) -> f32 {
+        let volume = volume.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => volume,
+            Self::Logarithmic if volume <= 0.0 => 0.0,
+            Self::Logarithmic => 10f32.powf((volume - 1.0) * VOLUME_CURVE_RANGE_DB / 20.0),
+        }
+    }
+}
+
+/// 播放 / 停止 / 拖动进度时的淡入淡出设置（毫秒）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FadeSettings {
+    /// 是否启用（默认开启）
+    pub enabled: bool,
+    /// 开始播放时的淡入时长
+    pub play_ms: u32,
+    /// 停止 / 暂停前的淡出时长
+    pub stop_ms: u32,
+    /// 拖动进度时淡出、淡入各自的时长
+    pub seek_ms: u32,
+}
+
+impl Default for FadeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            play_ms: 200,
+            stop_ms: 300,
+            seek_ms: 60,
+        }
+    }
+}
+
+/// 短曲目的过渡规则：歌曲太短或剩余时间不够时缩短交叉淡化，缩到太短就直接切歌。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortTrackThresholds {
+    /// 歌曲时长至少为过渡时长的多少倍才做完整过渡，更短的歌曲过渡缩短到 `时长 / 倍数`
+    pub min_track_ratio: f32,
+    /// 缩短后不足此时长（毫秒）则不做过渡
+    pub min_crossfade_ms: u32,
+}
+
+impl Default for ShortTrackThresholds {
+    fn default() -> Self {
+        Self {
+            min_track_ratio: 2.0,
+            min_crossfade_ms: 1000,
+        }
+    }
+}
+
+/// 交叉淡化的增益曲线；淡出段按淡入曲线倒序，见 [`crossfade::curve_gains`](super::crossfade::curve_gains)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossfadeCurve {
+    /// 增益随时间线性变化；过渡中点两首都只有一半增益，响度下陷约 3 dB
+    Linear,
+    /// 按分贝均匀变化（同 [`VolumeCurve::Logarithmic`]），淡入段前半几乎听不到
+    Logarithmic,
+    /// 升余弦，两端平缓（同启停淡入淡出）；中点同样下陷
+    SCurve,
+    /// 等功率（正弦 / 余弦）：任意时刻两首的功率之和不变，过渡全程响度平稳（默认）
+    #[default]
+    EqualPower,
+    /// 用户定义的控制点（[`CrossfadeSettings::custom_curve`]），点间线性插值
+    Custom,
+}
+
+/// 自定义淡入曲线的控制点。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    /// 过渡进度（0.0 ~ 1.0）
+    pub t: f32,
+    /// 该处淡入一方的增益（0.0 ~ 1.0）
+    pub gain: f32,
+}
+
+/// 切歌时的交叉淡化设置。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrossfadeSettings {
+    /// 实时播放是否交叉淡化（默认关闭）
+    pub enabled: bool,
+    /// 过渡时长（毫秒），同时是离线渲染的默认值
+    pub duration_ms: u32,
+    /// 短曲目 / 过场的缩短规则
+    pub short_tracks: ShortTrackThresholds,
+    /// 增益曲线
+    pub curve: CrossfadeCurve,
+    /// `curve` 为 [`CrossfadeCurve::Custom`] 时的控制点，校验规则见
+    /// [`crossfade::validate_curve_points`](super::crossfade::validate_curve_points)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom_curve: Vec<CurvePoint>,
+}
+
+impl Default for CrossfadeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            duration_ms: 6000,
+            short_tracks: ShortTrackThresholds::default(),
+            curve: CrossfadeCurve::default(),
+            custom_curve: Vec::new(),
+        }
+    }
+}
+
+/// 远程歌曲预加载设置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreloadSettings {
+    /// 是否预加载队列中的远程歌曲（默认开启）
+    pub enabled: bool,
+    /// 提前多少秒开始下载
+    pub lead_secs: u32,
+    /// 预加载占用的平均带宽上限（kbit/s），0 表示不限
+    pub max_kbps: u32,
+}
+
+impl Default for PreloadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lead_secs: 60,
+            max_kbps: 0,
+        }
+    }
+}
+
+/// 解码 PCM 缓存设置：播放次数够多的短曲目预先解码落盘。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PcmCacheSettings {
+    /// 是否启用（默认关闭；WAV 副本体积约为无损压缩的两倍）
+    pub enabled: bool,
+    /// 磁盘占用上限（字节），超出时淘汰最久未播放的副本
+    pub max_bytes: u64,
+    /// 播放次数达到该值才缓存
+    pub min_play_count: u64,
+    /// 只缓存不长于该时长（秒）的歌曲
+    pub max_track_secs: u64,
+}
+
+impl Default for PcmCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: pcm_cache::DEFAULT_MAX_BYTES,
+            min_play_count: 5,
+            max_track_secs: 600,
+        }
+    }
+}
+
+impl PcmCacheSettings {
+    /// 按播放次数与时长判断歌曲是否值得缓存；时长未知的不缓存。
+    pub fn should_cache(&self, play_count: u64, duration_secs: Option<u64>) -> bool {
+        self.enabled
+            && play_count >= self.min_play_count
+            && duration_secs.is_some_and(|d| d <= self.max_track_secs)
+    }
+}
+
+/// 播放设置（整体以一个 JSON 对象存放在 ConfigStore 的 `playback` 键下）。
+///
+/// 所有字段带 `serde(default)`，旧配置缺字段时自动补默认值。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackSettings {
+    /// 变速质量档位
+    pub time_stretch_quality: TimeStretchQuality,
+    /// 各内容类型上次使用的播放速度（缺失即原速）
+    pub playback_rates: HashMap<ContentType, f64>,
+    /// 静音跳过（播客模式）
+    pub silence_skip: SilenceSkipSettings,
+    /// 输出设备延迟（毫秒，用户校准值；蓝牙耳机通常 150 ~ 300）
+    pub output_latency_ms: u32,
+    /// 卡拉 OK 辅助
+    pub karaoke: KaraokeSettings,
+    /// 远程歌曲预加载
+    pub preload: PreloadSettings,
+    /// 常播短曲目的解码 PCM 缓存
+    pub pcm_cache: PcmCacheSettings,
+    /// 音量滑块位置（0.0 ~ 1.0）
+    pub volume: f32,
+    /// 音量曲线
+    pub volume_curve: VolumeCurve,
+    /// 降低位深时的抖动方式
+    pub dither: DitherMode,
+    /// 淡入淡出
+    pub fades: FadeSettings,
+    /// 切歌交叉淡化
+    pub crossfade: CrossfadeSettings,
+    /// 各内容类型的快进 / 快退步长（秒，缺失即 [`ContentType::default_skip_secs`]）
+    pub skip_steps: HashMap<ContentType, u32>,
+    /// 队列播完后的行为
+    pub end_of_queue: EndOfQueueBehavior,
+    /// 各列表激活歌曲时的处理（缺失即 [`ActivateAction::Replace`]）
+    pub activate_actions: HashMap<ActivateSurface, ActivateAction>,
+    /// 各播放列表的切歌方式（缺失即 [`PlaybackTransition::Crossfade`]）
+    pub playlist_transitions: HashMap<String, PlaybackTransition>,
+    /// 系统休眠唤醒后保持暂停（关闭时唤醒后从原位置继续播放）
+    pub pause_on_suspend: bool,
+    /// 不连接物理设备，按实时速度空转（见 [`null_output`](super::null_output)）
+    pub null_output: bool,
+    /// 当前输出设备 ID
+    pub output_device: String,
+    /// 各输出设备的 DSP 配置（缺失即平直的默认配置）
+    pub dsp_profiles: HashMap<String, DspProfile>,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self {
+            time_stretch_quality: TimeStretchQuality::default(),
+            playback_rates: HashMap::new(),
+            silence_skip: SilenceSkipSettings::default(),
+            output_latency_ms: 0,
+            karaoke: KaraokeSettings::default(),
+            preload: PreloadSettings::default(),
+            pcm_cache: PcmCacheSettings::default(),
+            volume: 1.0,
+            volume_curve: VolumeCurve::default(),
+            dither: DitherMode::default(),
+            fades: FadeSettings::default(),
+            crossfade: CrossfadeSettings::default(),
+            skip_steps: HashMap::new(),
+            end_of_queue: EndOfQueueBehavior::default(),
+            activate_actions: HashMap::new(),
+            playlist_transitions: HashMap::new(),
+            pause_on_suspend: true,
+            null_output: false,
+            output_device: dsp::DEFAULT_DEVICE.to_string(),
+            dsp_profiles: HashMap::new(),
+        }
+    }
+}
+
+impl PlaybackSettings {
+    /// 某内容类型的播放速度。
+    pub fn playback_rate(&self, content_type: ContentType) -> f64 {
+        self.playback_rates.get(&content_type).copied().unwrap_or(1.0)
+    }
+
+    /// 在某列表中激活歌曲时的处理。
+    pub fn activate_action(&self, surface: ActivateSurface) -> ActivateAction {
+        self.activate_actions.get(&surface).copied().unwrap_or_default()
+    }
+
+    /// 某播放列表的切歌方式。
+    pub fn playlist_transition(&self, playlist_id: &str) -> PlaybackTransition {
+        self.playlist_transitions.get(playlist_id).copied().unwrap_or_default()
+    }
+
+    /// 某输出设备的 DSP 配置。
+    pub fn dsp_profile(&self, device_id: &str) -> DspProfile {
+        self.dsp_profiles.get(device_id).cloned().unwrap_or_default()
+    }
+
+    /// 某内容类型的快进 / 快退步长（秒）。
+    pub fn skip_step(&self, content_type: ContentType) -> u32 {
+        self.skip_steps
+            .get(&content_type)
+            .copied()
+            .unwrap_or_else(|| content_type.default_skip_secs())
+    }
+}
@@ -0,0 +1,196 @@
+//! 静音跳过 — 检测语音类内容中的长静音段，供播放时压缩。
+//!
+//! 分析流程：
+//! 1. 完整解码，按 10ms 窗口计算混合单声道的 RMS 电平（dBFS）
+//! 2. 连续低于阈值且超过 `min_silence_ms` 的窗口构成一个静音段
+//! 3. 每个静音段两端各保留 `keep_ms / 2`，中间部分即为可跳过区间
+//!
+//! 前端播放到可跳过区间起点时直接 seek 到终点，并用 `saved_ms` 展示节省的时间。
+
+use crate::module::analysis::decode::{decode_file, PcmBlock};
+use crate::module::platform::PlatformPath;
+use serde::{Deserialize, Serialize};
+
+/// 电平分析窗口长度（毫秒）。
+const WINDOW_MS: u64 = 10;
+
+/// 电平下限，防止全零窗口产生 `-inf`。
+const FLOOR_DB: f32 = -120.0;
+
+/// 静音跳过力度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SilenceSkipAggressiveness {
+    /// 保守：只压缩几乎无声的片段，保留较长停顿
+    Gentle,
+    #[default]
+    Normal,
+    /// 激进：底噪较大的停顿也会被压缩，只保留极短间隔
+    Aggressive,
+}
+
+impl SilenceSkipAggressiveness {
+    /// 静音判定阈值（dBFS）。
+    pub fn threshold_db(self) -> f32 {
+        match self {
+            Self::Gentle => -50.0,
+            Self::Normal => -45.0,
+            Self::Aggressive => -38.0,
+        }
+    }
+
+    /// 压缩后每个静音段保留的时长（毫秒）。
+    pub fn keep_ms(self) -> u64 {
+        match self {
+            Self::Gentle => 300,
+            Self::Normal => 200,
+            Self::Aggressive => 100,
+        }
+    }
+}
+
+/// 静音跳过设置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SilenceSkipSettings {
+    /// 是否启用（默认关闭）
+    pub enabled: bool,
+    /// 超过该时长（毫秒）的静音才会被压缩
+    pub min_silence_ms: u64,
+    /// 压缩力度
+    pub aggressiveness: SilenceSkipAggressiveness,
+}
+
+impl Default for SilenceSkipSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_silence_ms: 700,
+            aggressiveness: SilenceSkipAggressiveness::Normal,
+        }
+    }
+}
+
+/// 一个可跳过区间（毫秒，左闭右开）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SilenceSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// 单个文件的静音分析结果。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SilenceMap {
+    /// 文件总时长（毫秒）
+    pub duration_ms: u64,
+    /// 可跳过区间，按时间升序
+    pub segments: Vec<SilenceSegment>,
+    /// 全部跳过后节省的总时长（毫秒）
+    pub saved_ms: u64,
+}
+
+/// 解码文件并生成静音跳过表。
+pub fn analyze_file(path: &PlatformPath, settings: &SilenceSkipSettings) -> Result<SilenceMap, String> {
+    let mut levels: Vec<f32> = Vec::new();
+    let mut acc_sq = 0.0f64;
+    let mut acc_frames = 0usize;
+    let mut window_frames = 0usize;
+
+    decode_file(path, |block: PcmBlock<'_>| {
+        if window_frames == 0 {
+            window_frames = (block.sample_rate as u64 * WINDOW_MS / 1000).max(1) as usize;
+        }
+        for frame in block.samples.chunks(block.channels) {
+            let mono = frame.iter().sum::<f32>() / block.channels as f32;
+            acc_sq += (mono as f64) * (mono as f64);
+            acc_frames += 1;
+            if acc_frames == window_frames {
+                levels.push(rms_db(acc_sq, acc_frames));
+                acc_sq = 0.0;
+                acc_frames = 0;
+            }
+        }
+    })?;
+    if acc_frames > 0 {
+        levels.push(rms_db(acc_sq, acc_frames));
+    }
+
+    Ok(find_silences(&levels, settings))
+}
+
+fn rms_db(sum_sq: f64, frames: usize) -> f32 {
+    let rms = (sum_sq / frames as f64).sqrt() as f32;
+    if rms > 0.0 {
+        (20.0 * rms.log10()).max(FLOOR_DB)
+    } else {
+        FLOOR_DB
+    }
+}
+
+/// 由逐窗口电平序列（每个元素对应 [`WINDOW_MS`]）计算可跳过区间。
+pub fn find_silences(levels_db: &[f32], settings: &SilenceSkipSettings) -> SilenceMap {
+    let threshold = settings.aggressiveness.threshold_db();
+    let keep_ms = settings.aggressiveness.keep_ms();
+    let min_ms = settings.min_silence_ms.max(keep_ms);
+
+    let mut map = SilenceMap {
+        duration_ms: levels_db.len() as u64 * WINDOW_MS,
+        ..Default::default()
+    };
+
+    let mut run_start: Option<usize> = None;
+    // 末尾追加一个非静音哨兵，保证结尾处的静音段也能闭合
+    for (i, level) in levels_db.iter().copied().chain([0.0]).enumerate() {
+        match (level < threshold, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                let start_ms = start as u64 * WINDOW_MS;
+                let end_ms = i as u64 * WINDOW_MS;
+                if end_ms - start_ms >= min_ms {
+                    let segment = SilenceSegment {
+                        start_ms: start_ms + keep_ms / 2,
+                        end_ms: end_ms - keep_ms / 2,
+                    };
+                    map.saved_ms += segment.end_ms - segment.start_ms;
+                    map.segments.push(segment);
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_gap_compressed_short_pause_kept() {
+        let settings = SilenceSkipSettings {
+            enabled: true,
+            min_silence_ms: 500,
+            aggressiveness: SilenceSkipAggressiveness::Normal,
+        };
+        // 1s 语音 + 1s 静音 + 0.3s 语音 + 0.3s 短停顿 + 1s 语音
+        let mut levels = vec![-20.0; 100];
+        levels.extend(vec![-80.0; 100]);
+        levels.extend(vec![-20.0; 30]);
+        levels.extend(vec![-80.0; 30]);
+        levels.extend(vec![-20.0; 100]);
+
+        let map = find_silences(&levels, &settings);
+        assert_eq!(map.segments, vec![SilenceSegment { start_ms: 1100, end_ms: 1900 }]);
+        assert_eq!(map.saved_ms, 800);
+    }
+
+    #[test]
+    fn test_trailing_silence_is_closed() {
+        let mut levels = vec![-20.0; 50];
+        levels.extend(vec![-90.0; 200]);
+        let map = find_silences(&levels, &SilenceSkipSettings::default());
+        assert_eq!(map.segments.len(), 1);
+        assert_eq!(map.segments[0].end_ms, 2500 - 100);
+    }
+}
@@ -0,0 +1,176 @@
+//! 切歌过渡日志 — 把每次交叉淡化 / 自动混音的过渡记到磁盘，供用户回看并调整设置。
+//!
+//! 过渡由前端混音器执行，结束（或被用户跳过打断）时上报一条 [`TransitionReport`]：
+//! 前后两首、BPM、为对齐节拍做的变速、实际使用的混音点与过渡方案。
+//! 记录以 JSON Lines 追加写入 `transitions.jsonl`，只保留最近 [`MAX_RECORDS`] 条；
+//! 文件行数超过两倍上限时整体重写一次，其余时候只追加，不必每次重写整个文件。
+
+use super::crossfade::CrossfadePlan;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// 保留的记录数。
+pub const MAX_RECORDS: usize = 1000;
+
+/// 前端上报的一次过渡。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitionReport {
+    pub from_song_id: String,
+    pub to_song_id: String,
+    /// 两首的 BPM（未分析时为 `None`）
+    #[serde(default)]
+    pub from_bpm: Option<f32>,
+    #[serde(default)]
+    pub to_bpm: Option<f32>,
+    /// 为对齐节拍给下一首设置的播放速度（未变速为 `None`）
+    #[serde(default)]
+    pub speed: Option<f64>,
+    /// 过渡开始时上一首的播放位置（毫秒）
+    pub mix_out_ms: f64,
+    /// 下一首从哪里开始播放（毫秒，通常为 0 或跳过前奏后的位置）
+    #[serde(default)]
+    pub mix_in_ms: f64,
+    /// 实际采用的过渡方案
+    pub plan: CrossfadePlan,
+    /// 用户在过渡期间跳过了歌曲
+    #[serde(default)]
+    pub skipped: bool,
+}
+
+/// 日志中的一条记录。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitionRecord {
+    /// 记录时间（Unix 秒）
+    pub at: u64,
+    /// 记录时的歌曲标题；歌曲之后被移出音乐库也能看出是哪两首
+    #[serde(default)]
+    pub from_title: Option<String>,
+    #[serde(default)]
+    pub to_title: Option<String>,
+    #[serde(flatten)]
+    pub report: TransitionReport,
+}
+
+struct LogFile {
+    records: VecDeque<TransitionRecord>,
+    /// 文件当前的行数（含已淘汰、尚未压缩掉的旧记录）
+    lines: usize,
+}
+
+/// 磁盘上的过渡日志。
+pub struct TransitionLog {
+    path: PathBuf,
+    inner: Mutex<LogFile>,
+}
+
+impl TransitionLog {
+    /// 打开日志文件（不存在时为空日志）；无法解析的行跳过。
+    pub fn new(path: PathBuf) -> Self {
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let lines = text.lines().count();
+        let mut records: VecDeque<TransitionRecord> =
+            text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+        while records.len() > MAX_RECORDS {
+            records.pop_front();
+        }
+        Self {
+            path,
+            inner: Mutex::new(LogFile { records, lines }),
+        }
+    }
+
+    /// 追加一条记录。
+    pub fn record(&self, record: TransitionRecord) -> Result<(), String> {
+        let mut inner = self.inner.lock();
+        inner.records.push_back(record.clone());
+        if inner.records.len() > MAX_RECORDS {
+            inner.records.pop_front();
+        }
+        if inner.lines + 1 > MAX_RECORDS * 2 {
+            self.rewrite(&mut inner)
+        } else {
+            let line = serde_json::to_string(&record).map_err(|e| format!("序列化过渡记录失败: {}", e))?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(|e| format!("打开过渡日志失败: {}", e))?;
+            writeln!(file, "{}", line).map_err(|e| format!("写入过渡日志失败: {}", e))?;
+            inner.lines += 1;
+            Ok(())
+        }
+    }
+
+    /// 只保留内存中的记录重写整个文件。
+    fn rewrite(&self, inner: &mut LogFile) -> Result<(), String> {
+        let mut text = String::new();
+        for record in &inner.records {
+            text.push_str(&serde_json::to_string(record).map_err(|e| format!("序列化过渡记录失败: {}", e))?);
+            text.push('\n');
+        }
+        fs::write(&self.path, text).map_err(|e| format!("写入过渡日志失败: {}", e))?;
+        inner.lines = inner.records.len();
+        Ok(())
+    }
+
+    /// 最近的 `limit` 条记录，新的在前。
+    pub fn recent(&self, limit: usize) -> Vec<TransitionRecord> {
+        self.inner.lock().records.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        let mut inner = self.inner.lock();
+        inner.records.clear();
+        self.rewrite(&mut inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::playback::settings::PlaybackTransition;
+
+    #[test]
+    fn test_log_survives_reopen_and_compacts() {
+        let path = std::env::temp_dir().join(format!("chordial-transitions-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let record = |n: usize| TransitionRecord {
+            at: n as u64,
+            from_title: Some("A".into()),
+            to_title: None,
+            report: TransitionReport {
+                from_song_id: format!("s{}", n),
+                to_song_id: format!("s{}", n + 1),
+                from_bpm: Some(120.0),
+                to_bpm: Some(124.0),
+                speed: Some(120.0 / 124.0),
+                mix_out_ms: 180_000.0,
+                mix_in_ms: 0.0,
+                plan: CrossfadePlan {
+                    transition: PlaybackTransition::Crossfade,
+                    duration_ms: 6000,
+                    requested_ms: 6000,
+                    limited_by: None,
+                    curve: Default::default(),
+                },
+                skipped: n.is_multiple_of(2),
+            },
+        };
+
+        let log = TransitionLog::new(path.clone());
+        for n in 0..MAX_RECORDS * 2 + 5 {
+            log.record(record(n)).unwrap();
+        }
+        let reopened = TransitionLog::new(path.clone());
+        let recent = reopened.recent(2);
+        assert_eq!(recent, vec![record(MAX_RECORDS * 2 + 4), record(MAX_RECORDS * 2 + 3)]);
+        assert_eq!(reopened.inner.lock().records.len(), MAX_RECORDS);
+        // 压缩过一次，文件不会无限增长
+        assert!(fs::read_to_string(&path).unwrap().lines().count() < MAX_RECORDS * 2);
+        let _ = fs::remove_file(path);
+    }
+}
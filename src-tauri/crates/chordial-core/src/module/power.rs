@@ -0,0 +1,238 @@
+//! 电源策略 — 笔记本使用电池时降低后台扫描 / 分析的并行度，避免整库扫描耗尽电量。
+//!
+//! [`PowerMonitor`] 按 [`PowerPolicy`] 和当前供电状态给出限流参数：
+//! - 本地扫描的探测线程数（[`scan_threads`](PowerMonitor::scan_threads)）
+//! - 批量分析可同时占用的解码名额（[`batch_analysis_limit`](PowerMonitor::batch_analysis_limit)），
+//!   为 0 时批量分析暂停，接上电源后自动继续
+//!
+//! 交互式请求（单曲技术信息等）不受限制。
+//!
+//! 供电状态按平台读取：Linux 读 `/sys/class/power_supply`，macOS 解析 `pmset -g batt`，
+//! Windows 调用 `GetSystemPowerStatus`；无法判断（台式机、Android 等）时按接通电源处理。
+//! 读取结果缓存 [`REFRESH_INTERVAL`]，调度器频繁查询也不会反复访问系统。
+
+use crate::module::config::store::ConfigStore;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const POLICY_CONFIG_KEY: &str = "power_policy";
+
+/// 供电状态缓存时长。
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 低功耗时扫描探测线程数上限。
+pub const LOW_POWER_SCAN_THREADS: usize = 2;
+
+/// 电源策略。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerPolicy {
+    /// 使用电池时自动降低并行度（默认）
+    #[default]
+    Auto,
+    /// 使用电池时降低并行度，并暂停批量分析
+    PauseOnBattery,
+    /// 始终低功耗，不论供电状态
+    LowPower,
+    /// 始终全速，忽略供电状态
+    Performance,
+}
+
+impl PowerPolicy {
+    /// 给定供电状态下的限流方案：`(低功耗, 暂停批量分析)`。
+    pub fn throttle(self, on_battery: Option<bool>) -> (bool, bool) {
+        let on_battery = on_battery.unwrap_or(false);
+        match self {
+            PowerPolicy::Auto => (on_battery, false),
+            PowerPolicy::PauseOnBattery => (on_battery, on_battery),
+            PowerPolicy::LowPower => (true, false),
+            PowerPolicy::Performance => (false, false),
+        }
+    }
+}
+
+/// 当前电源状态与生效的限流参数。
+#[derive(Debug, Clone, Serialize)]
+pub struct PowerStatus {
+    pub policy: PowerPolicy,
+    /// 是否使用电池；无法判断时为 `None`
+    pub on_battery: Option<bool>,
+    pub low_power: bool,
+    pub analysis_paused: bool,
+    /// 本地扫描的探测线程数
+    pub scan_threads: usize,
+}
+
+/// 电源策略与供电状态监测。
+pub struct PowerMonitor {
+    config: Arc<ConfigStore>,
+    policy: RwLock<PowerPolicy>,
+    /// 上次读取供电状态的时间与结果
+    cached: Mutex<Option<(Instant, Option<bool>)>>,
+}
+
+impl PowerMonitor {
+    /// 创建监测器，从 ConfigStore 读取已保存的策略。
+    pub fn new(config: Arc<ConfigStore>) -> Self {
+        let policy = config.get::<PowerPolicy>(POLICY_CONFIG_KEY).unwrap_or_default();
+        Self {
+            config,
+            policy: RwLock::new(policy),
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn policy(&self) -> PowerPolicy {
+        *self.policy.read()
+    }
+
+    /// 更新策略并保存。
+    pub fn set_policy(&self, policy: PowerPolicy) -> Result<PowerStatus, String> {
+        self.config.set(POLICY_CONFIG_KEY, &policy)?;
+        *self.policy.write() = policy;
+        Ok(self.status())
+    }
+
+    /// 是否使用电池（缓存 [`REFRESH_INTERVAL`]）。
+    pub fn on_battery(&self) -> Option<bool> {
+        let mut cached = self.cached.lock();
+        match *cached {
+            Some((at, value)) if at.elapsed() < REFRESH_INTERVAL => value,
+            _ => {
+                let value = detect_on_battery();
+                *cached = Some((Instant::now(), value));
+                value
+            }
+        }
+    }
+
+    /// 本地扫描的探测线程数：`default` 为全速时的线程数。
+    pub fn scan_threads(&self, default: usize) -> usize {
+        let (low_power, _) = self.policy().throttle(self.on_battery());
+        if low_power {
+            default.clamp(1, LOW_POWER_SCAN_THREADS)
+        } else {
+            default.max(1)
+        }
+    }
+
+    /// 批量分析可占用的解码名额：`default` 为全速时的名额数，返回 0 表示暂停。
+    pub fn batch_analysis_limit(&self, default: usize) -> usize {
+        match self.policy().throttle(self.on_battery()) {
+            (_, true) => 0,
+            (true, false) => 1,
+            (false, false) => default,
+        }
+    }
+
+    pub fn status(&self) -> PowerStatus {
+        let policy = self.policy();
+        let on_battery = self.on_battery();
+        let (low_power, analysis_paused) = policy.throttle(on_battery);
+        let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+        PowerStatus {
+            policy,
+            on_battery,
+            low_power,
+            analysis_paused,
+            scan_threads: self.scan_threads(cores),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_on_battery() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let (mut mains, mut battery, mut discharging) = (false, false, false);
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let read = |name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        match read("type").as_str() {
+            "Mains" | "USB" => {
+                if read("online") == "1" {
+                    return Some(false);
+                }
+                mains = true;
+            }
+            // scope=Device 是鼠标、耳机等外设的电池
+            "Battery" if read("scope") != "Device" => {
+                battery = true;
+                discharging |= read("status") == "Discharging";
+            }
+            _ => {}
+        }
+    }
+    battery.then_some(discharging || mains)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    if text.contains("'Battery Power'") {
+        Some(true)
+    } else if text.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn detect_on_battery() -> Option<bool> {
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    let mut status = SystemPowerStatus::default();
+    // SAFETY: 传入指向有效、可写且布局与 SYSTEM_POWER_STATUS 一致的结构体指针
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    // battery_flag 128 = 没有电池
+    if status.battery_flag == 128 {
+        return None;
+    }
+    match status.ac_line_status {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect_on_battery() -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_throttle() {
+        assert_eq!(PowerPolicy::Auto.throttle(Some(true)), (true, false));
+        assert_eq!(PowerPolicy::Auto.throttle(None), (false, false));
+        assert_eq!(PowerPolicy::PauseOnBattery.throttle(Some(true)), (true, true));
+        assert_eq!(PowerPolicy::PauseOnBattery.throttle(Some(false)), (false, false));
+        assert_eq!(PowerPolicy::LowPower.throttle(Some(false)), (true, false));
+        assert_eq!(PowerPolicy::Performance.throttle(Some(true)), (false, false));
+    }
+}
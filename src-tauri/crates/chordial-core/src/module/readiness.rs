@@ -0,0 +1,194 @@
+//! 启动就绪状态 — 记录各子系统是否完成启动预热，并在就绪时发布事件。
+//!
+//! [`AppContext::new`](crate::AppContext::new) 只做快速的加载（配置、库文件、索引恢复），
+//! 耗时的启动扫描由 [`AppContext::warm_up`](crate::AppContext::warm_up) 在后台完成。
+//! 在此之前库里可能缺少新加入的文件，列表 / 搜索类命令先调用 [`Readiness::wait`]：
+//! 等待子系统就绪，超时仍未就绪时返回以 [`NOT_READY`] 开头的错误，而不是返回不完整的结果。
+//!
+//! 每个子系统就绪（或失败）时发布 [`AppEvent::SubsystemReady`]，全部结束后发布一次
+//! [`AppEvent::AppReady`]。事件可能早于前端订阅发出，前端应再用 [`Readiness::status`] 补查。
+
+use crate::module::events::{AppEvent, EventBus};
+use parking_lot::{Condvar, Mutex};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 未就绪错误的前缀，格式为 `not_ready:<子系统>: <说明>`，前端按前缀识别。
+pub const NOT_READY: &str = "not_ready";
+
+/// 命令等待子系统就绪的默认时长。
+pub const DEFAULT_WAIT: Duration = Duration::from_secs(15);
+
+/// 需要启动预热的子系统。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    /// 配置与播放设置
+    Settings,
+    /// 来源：本地文件夹完成增量扫描并开始监听
+    Sources,
+    /// 音乐库：已包含扫描结果，搜索索引已建立
+    Library,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 3] = [Subsystem::Settings, Subsystem::Sources, Subsystem::Library];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Subsystem::Settings => "settings",
+            Subsystem::Sources => "sources",
+            Subsystem::Library => "library",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Subsystem::Settings => "设置",
+            Subsystem::Sources => "音乐来源",
+            Subsystem::Library => "音乐库",
+        }
+    }
+}
+
+/// 子系统的启动状态。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum SubsystemState {
+    Pending,
+    Ready,
+    /// 预热失败；子系统按已加载的数据继续工作
+    Failed { error: String },
+}
+
+/// [`Readiness::status`] 的结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessStatus {
+    /// 所有子系统都已结束预热（含失败）
+    pub ready: bool,
+    pub subsystems: BTreeMap<Subsystem, SubsystemState>,
+}
+
+/// 各子系统的就绪状态。
+pub struct Readiness {
+    states: Mutex<BTreeMap<Subsystem, SubsystemState>>,
+    changed: Condvar,
+    events: Arc<EventBus>,
+}
+
+impl Readiness {
+    /// 所有子系统初始为 `Pending`。
+    pub fn new(events: Arc<EventBus>) -> Self {
+        Self {
+            states: Mutex::new(Subsystem::ALL.iter().map(|&s| (s, SubsystemState::Pending)).collect()),
+            changed: Condvar::new(),
+            events,
+        }
+    }
+
+    pub fn mark_ready(&self, subsystem: Subsystem) {
+        self.finish(subsystem, SubsystemState::Ready);
+    }
+
+    pub fn mark_failed(&self, subsystem: Subsystem, error: String) {
+        eprintln!("[readiness] {} 预热失败: {}", subsystem.label(), error);
+        self.finish(subsystem, SubsystemState::Failed { error });
+    }
+
+    fn finish(&self, subsystem: Subsystem, state: SubsystemState) {
+        let error = match &state {
+            SubsystemState::Failed { error } => Some(error.clone()),
+            _ => None,
+        };
+        let all_done = {
+            let mut states = self.states.lock();
+            if states.get(&subsystem) != Some(&SubsystemState::Pending) {
+                return;
+            }
+            states.insert(subsystem, state);
+            states.values().all(|s| *s != SubsystemState::Pending)
+        };
+        self.changed.notify_all();
+        self.events.publish(AppEvent::SubsystemReady { subsystem, error });
+        if all_done {
+            self.events.publish(AppEvent::AppReady);
+        }
+    }
+
+    pub fn is_ready(&self, subsystem: Subsystem) -> bool {
+        self.states.lock().get(&subsystem) != Some(&SubsystemState::Pending)
+    }
+
+    pub fn status(&self) -> ReadinessStatus {
+        let subsystems = self.states.lock().clone();
+        ReadinessStatus {
+            ready: subsystems.values().all(|s| *s != SubsystemState::Pending),
+            subsystems,
+        }
+    }
+
+    /// 立即检查子系统是否就绪，未就绪时返回 [`NOT_READY`] 错误。
+    ///
+    /// 预热失败的子系统视为就绪：其数据不完整，但不会再变好，调用方不应一直等下去。
+    pub fn require(&self, subsystem: Subsystem) -> Result<(), String> {
+        if self.is_ready(subsystem) {
+            Ok(())
+        } else {
+            Err(not_ready(subsystem))
+        }
+    }
+
+    /// 阻塞等待子系统就绪，最多 `timeout`；超时返回 [`NOT_READY`] 错误。
+    ///
+    /// 只能在后台线程（异步命令、RPC 工作线程）中调用，不要阻塞 UI 线程。
+    pub fn wait(&self, subsystem: Subsystem, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        let mut states = self.states.lock();
+        while states.get(&subsystem) == Some(&SubsystemState::Pending) {
+            if self.changed.wait_until(&mut states, deadline).timed_out() {
+                return Err(not_ready(subsystem));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn not_ready(subsystem: Subsystem) -> String {
+    format!("{}:{}: {}仍在加载，请稍后重试", NOT_READY, subsystem.as_str(), subsystem.label())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_unblocks_and_app_ready_fires_once() {
+        let events = Arc::new(EventBus::new());
+        let mut rx = events.subscribe();
+        let readiness = Arc::new(Readiness::new(events));
+
+        let err = readiness.wait(Subsystem::Library, Duration::from_millis(10)).unwrap_err();
+        assert!(err.starts_with("not_ready:library"));
+
+        let waiter = {
+            let readiness = readiness.clone();
+            std::thread::spawn(move || readiness.wait(Subsystem::Library, Duration::from_secs(5)))
+        };
+        readiness.mark_ready(Subsystem::Settings);
+        readiness.mark_failed(Subsystem::Sources, "扫描失败".into());
+        readiness.mark_ready(Subsystem::Library);
+        readiness.mark_ready(Subsystem::Library); // 重复标记不再发布事件
+        assert!(waiter.join().unwrap().is_ok());
+        assert!(readiness.status().ready);
+
+        let mut received = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 4);
+        assert!(matches!(received[3], AppEvent::AppReady));
+    }
+}
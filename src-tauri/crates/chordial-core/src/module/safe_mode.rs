@@ -0,0 +1,63 @@
+//! 安全模式 — 启动即崩溃（缓存损坏、音频驱动异常等）时的恢复入口。
+//!
+//! 通过环境变量 `CHORDIAL_SAFE_MODE=1` 或命令行参数 `--safe-mode` 启用。安全模式下：
+//!
+//! | 部分 | 行为 |
+//! |------|------|
+//! | 音频引擎 | 前端不创建音频输出，播放请求直接报错 |
+//! | 文件监听 / 启动扫描 | 不启动；音乐库按磁盘上已保存的内容提供 |
+//! | 后台任务 | 预加载、内容哈希、PCM 预解码、休眠检测均不启动 |
+//! | 缓存 | 媒体缓存 / PCM 缓存 / Blob 缓存只读：可读取已有文件，不写入、不淘汰、不删除 |
+//!
+//! 用户可以在安全模式下修改设置或清理有问题的数据，然后正常重启。
+
+use serde::Serialize;
+
+/// 启用安全模式的环境变量。
+pub const ENV_VAR: &str = "CHORDIAL_SAFE_MODE";
+
+/// 启用安全模式的命令行参数。
+pub const CLI_FLAG: &str = "--safe-mode";
+
+/// 安全模式下拒绝写缓存时的错误信息。
+pub const READ_ONLY_ERROR: &str = "安全模式下缓存只读";
+
+/// 前端查询用的启动模式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StartupMode {
+    pub safe_mode: bool,
+}
+
+/// 环境变量取值是否表示开启（`1` / `true` / `yes` / `on`，不区分大小写）。
+fn env_enabled(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+/// 按环境变量与命令行参数（不含程序名）判断是否以安全模式启动。
+pub fn requested<I, S>(env_value: Option<&str>, args: I) -> bool
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    env_value.is_some_and(env_enabled) || args.into_iter().any(|arg| arg.as_ref() == CLI_FLAG)
+}
+
+/// 当前进程是否以安全模式启动。
+pub fn from_env() -> bool {
+    requested(std::env::var(ENV_VAR).ok().as_deref(), std::env::args().skip(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested_by_env_or_flag() {
+        let none: [&str; 0] = [];
+        assert!(requested(Some("1"), none));
+        assert!(requested(Some(" TRUE "), none));
+        assert!(!requested(Some("0"), none));
+        assert!(!requested(None, ["song.flac"]));
+        assert!(requested(None, ["song.flac", "--safe-mode"]));
+    }
+}
@@ -8,6 +8,7 @@
 //!   └── MemoryBackend        ← 内存 HashMap
 //!
 //! PersistentStore            ← 通用持久化存储（文件后端 + 内存缓存，手动落盘）
+//! Snapshots                  ← 数据文件的每日快照 + 启动时损坏回滚
 //! ```
 //!
 //! # 三层职责划分
@@ -33,3 +34,4 @@ pub mod entry;
 pub mod file;
 pub mod memory;
 pub mod persistent;
+pub mod snapshot;
@@ -4,6 +4,7 @@ use crate::module::perf;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use parking_lot::Mutex;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::hash::{Hash, Hasher};
@@ -24,9 +25,22 @@ use std::path::PathBuf;
 /// 适用于音乐资源（音频、图片、歌词文本）的本地持久化缓存。
 /// Blob 文件保存在 JSON 文件同级的 `blobs/` 目录下。
 ///
+/// # 分段落盘
+///
+/// 每个顶层 key 视为一个分段，单独记录脏标记，并缓存上次落盘时的序列化结果。
+/// [`save()`](Self::save) 只重新序列化被修改过的分段，其余分段直接复用缓存文本
+/// 拼接成完整文件——例如音乐库只改了 `songs` 时不会重新序列化 `albums` / `artists`。
+///
 /// # 并发
 ///
-/// 内部使用 `parking_lot::RwLock` 保护缓存和脏标记，允许多读单写。
+/// 内部使用 `parking_lot::RwLock` 保护缓存和脏标记，允许多读单写；
+/// 落盘过程由独立互斥锁串行化，避免两个线程同时写同一文件。
+///
+/// # 条目变更记录
+///
+/// 对 [`track_entry_changes`](Self::track_entry_changes) 登记过的分段，按条目记录自上次
+/// [`take_entry_changes`](Self::take_entry_changes) 以来被写入 / 删除的 ID，供外部索引增量同步。
+/// 整段替换（`set` / `remove` / `reload` 等）记为 [`EntryChanges::All`]。
 ///
 /// # 示例
 ///
@@ -52,6 +66,14 @@ pub struct PersistentStore {
     cache: RwLock<HashMap<String, Value>>,
     /// 是否有未落盘的修改
     dirty: RwLock<bool>,
+    /// 自上次落盘后被修改过的顶层 key
+    dirty_keys: Mutex<HashSet<String>>,
+    /// 各顶层 key 上次落盘时的 JSON 文本
+    fragments: Mutex<HashMap<String, String>>,
+    /// 串行化落盘
+    write_lock: Mutex<()>,
+    /// 登记分段的条目变更：顶层 key → 变更
+    entry_changes: Mutex<HashMap<String, EntryChanges>>,
     /// Blob 文件存储目录
     blob_dir: PathBuf,
     /// 内存中的 Blob key 集合（避免每次扫描目录）
@@ -87,6 +109,10 @@ impl PersistentStore {
             backend,
             cache: RwLock::new(cache),
             dirty: RwLock::new(false),
+            dirty_keys: Mutex::new(HashSet::new()),
+            fragments: Mutex::new(HashMap::new()),
+            write_lock: Mutex::new(()),
+            entry_changes: Mutex::new(HashMap::new()),
             blob_dir,
             blob_keys_cache: RwLock::new(blob_keys_cache),
         }
@@ -186,7 +212,8 @@ impl PersistentStore {
             .and_then(|v| v.as_object_mut())
             .map_or(false, |obj| obj.remove(id).is_some());
         if removed {
-            *self.dirty.write() = true;
+            self.mark_dirty(key);
+            self.log_entry(key, id);
         }
         removed
     }
@@ -207,13 +234,26 @@ impl PersistentStore {
         if let Some(obj) = entry.as_object_mut() {
             obj.insert(id.to_string(), json);
             drop(guard);
-            *self.dirty.write() = true;
+            self.mark_dirty(key);
+            self.log_entry(key, id);
             Ok(())
         } else {
             Err(format!("键 '{}' 的值不是 JSON Object", key))
         }
     }
 
+    /// 计算 HashMap 值中每条记录的内容指纹（xxh3-64，基于条目的 JSON 文本），不做反序列化。
+    ///
+    /// 用于比较一批写操作前后哪些条目被增删改，见 `music_library::diff`。
+    pub fn entry_fingerprints(&self, key: &str) -> HashMap<String, u64> {
+        let _scope = perf::scope("persistent.entry_fingerprints");
+        let guard = self.cache.read();
+        let Some(obj) = guard.get(key).and_then(|v| v.as_object()) else {
+            return HashMap::new();
+        };
+        obj.iter().map(|(id, v)| (id.clone(), entry_fingerprint(v))).collect()
+    }
+
     /// 获取 HashMap 值的条目数量，不做反序列化。
     ///
     /// 仅检查 JSON Object 的键数量，开销 O(1)。
@@ -405,14 +445,16 @@ impl PersistentStore {
         let json = serde_json::to_value(value)
             .map_err(|e| format!("序列化失败: {}", e))?;
         self.cache.write().insert(key.to_string(), json);
-        *self.dirty.write() = true;
+        self.mark_dirty(key);
+        self.log_all(key);
         Ok(())
     }
 
     /// 写入原始 JSON 值，仅修改内存缓存。
     pub fn set_raw(&self, key: &str, value: Value) {
         self.cache.write().insert(key.to_string(), value);
-        *self.dirty.write() = true;
+        self.mark_dirty(key);
+        self.log_all(key);
     }
 
     // ── 删除 / 检查 ──────────────────────────────────
@@ -421,7 +463,8 @@ impl PersistentStore {
     pub fn remove(&self, key: &str) -> bool {
         let existed = self.cache.write().remove(key).is_some();
         if existed {
-            *self.dirty.write() = true;
+            self.mark_dirty(key);
+            self.log_all(key);
         }
         existed
     }
@@ -439,25 +482,99 @@ impl PersistentStore {
     /// 清空所有数据（仅修改内存缓存）。
     pub fn clear(&self) {
         self.cache.write().clear();
+        self.fragments.lock().clear();
+        *self.dirty.write() = true;
+        self.log_all_keys();
+    }
+
+    /// 标记某个顶层 key 已修改。
+    fn mark_dirty(&self, key: &str) {
+        self.dirty_keys.lock().insert(key.to_string());
         *self.dirty.write() = true;
     }
 
+    /// 自上次落盘后被修改过的顶层 key。
+    pub fn dirty_keys(&self) -> Vec<String> {
+        self.dirty_keys.lock().iter().cloned().collect()
+    }
+
+    // ── 条目变更记录 ─────────────────────────────────
+
+    /// 开始按条目记录 `keys` 的变更；新登记的分段先记为 [`EntryChanges::All`]。
+    pub fn track_entry_changes(&self, keys: &[&str]) {
+        let mut changes = self.entry_changes.lock();
+        for key in keys {
+            changes.entry(key.to_string()).or_insert(EntryChanges::All);
+        }
+    }
+
+    /// 取出并清空 `key` 自上次调用以来的变更；未登记的分段返回 `None`。
+    pub fn take_entry_changes(&self, key: &str) -> Option<EntryChanges> {
+        self.entry_changes
+            .lock()
+            .get_mut(key)
+            .map(|changes| std::mem::replace(changes, EntryChanges::Ids(HashSet::new())))
+    }
+
+    fn log_entry(&self, key: &str, id: &str) {
+        if let Some(EntryChanges::Ids(ids)) = self.entry_changes.lock().get_mut(key) {
+            ids.insert(id.to_string());
+        }
+    }
+
+    fn log_all(&self, key: &str) {
+        if let Some(changes) = self.entry_changes.lock().get_mut(key) {
+            *changes = EntryChanges::All;
+        }
+    }
+
+    fn log_all_keys(&self) {
+        for changes in self.entry_changes.lock().values_mut() {
+            *changes = EntryChanges::All;
+        }
+    }
+
     // ── 持久化 ───────────────────────────────────────
 
     /// 立即将内存中所有数据写入磁盘。
     ///
     /// 写入成功后清除脏标记。
     ///
-    /// 优化：在读锁内序列化为字符串（~10ms for 10k entries），释放锁后写盘，
-    /// 避免 `cache.read().clone()` 导致的全量 HashMap clone（O(n) 内存+时间）。
+    /// 优化：在读锁内序列化为字符串，释放锁后写盘，
+    /// 避免 `cache.read().clone()` 导致的全量 HashMap clone（O(n) 内存+时间）；
+    /// 未修改的分段复用上次的序列化文本，只有脏分段重新序列化。
     pub fn save(&self) -> Result<(), String> {
         let _scope = perf::scope("persistent.save");
+        let _write = self.write_lock.lock();
         let content = {
             let guard = self.cache.read();
-            serde_json::to_string(&*guard).map_err(|e| format!("序列化失败: {}", e))?
+            let mut dirty_keys = self.dirty_keys.lock();
+            let mut fragments = self.fragments.lock();
+            fragments.retain(|key, _| guard.contains_key(key));
+            let mut content = String::from("{");
+            for (i, (key, value)) in guard.iter().enumerate() {
+                if dirty_keys.contains(key) || !fragments.contains_key(key) {
+                    let fragment =
+                        serde_json::to_string(value).map_err(|e| format!("序列化失败: {}", e))?;
+                    fragments.insert(key.clone(), fragment);
+                }
+                if i > 0 {
+                    content.push(',');
+                }
+                content.push_str(&serde_json::to_string(key).map_err(|e| format!("序列化失败: {}", e))?);
+                content.push(':');
+                content.push_str(&fragments[key]);
+            }
+            content.push('}');
+            dirty_keys.clear();
+            *self.dirty.write() = false;
+            content
         };
-        self.backend.write_str(&content)?;
-        *self.dirty.write() = false;
+        if let Err(e) = self.backend.write_str(&content) {
+            // 分段缓存仍与内存一致，只需恢复脏标记让下次重试
+            *self.dirty.write() = true;
+            return Err(e);
+        }
         Ok(())
     }
 
@@ -479,7 +596,10 @@ impl PersistentStore {
     pub fn reload(&self) {
         if let Ok(data) = self.backend.read() {
             *self.cache.write() = data;
+            self.fragments.lock().clear();
+            self.dirty_keys.lock().clear();
             *self.dirty.write() = false;
+            self.log_all_keys();
         }
     }
 
@@ -567,3 +687,65 @@ impl PersistentStore {
         &self.blob_dir
     }
 }
+
+/// 登记分段自上次取出以来的变更，见 [`PersistentStore::take_entry_changes`]。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryChanges {
+    /// 整段被替换，需要与全部条目重新比对
+    All,
+    /// 被写入或删除的条目 ID
+    Ids(HashSet<String>),
+}
+
+/// 条目的内容指纹（xxh3-64，基于条目的 JSON 文本）。
+pub fn entry_fingerprint(value: &Value) -> u64 {
+    xxhash_rust::xxh3::xxh3_64(&serde_json::to_vec(value).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_reserializes_only_dirty_sections() {
+        let dir = std::env::temp_dir().join(format!("chordial_persistent_{}", std::process::id()));
+        let path = dir.join("store.json");
+        let store = PersistentStore::new(path.clone());
+        store.set("albums", &serde_json::json!({ "a": { "title": "A" } })).unwrap();
+        store.set("songs", &serde_json::json!({})).unwrap();
+        store.save().unwrap();
+        assert!(store.dirty_keys().is_empty());
+
+        store.set_subkey("songs", "s1", &serde_json::json!({ "title": "S" })).unwrap();
+        assert_eq!(store.dirty_keys(), vec!["songs".to_string()]);
+        store.save().unwrap();
+
+        let reloaded = PersistentStore::new(path);
+        assert_eq!(reloaded.get_raw("albums"), store.get_raw("albums"));
+        assert_eq!(reloaded.get_raw("songs"), store.get_raw("songs"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_entry_changes_record_ids_until_section_replaced() {
+        let dir = std::env::temp_dir().join(format!("chordial_persistent_changes_{}", std::process::id()));
+        let store = PersistentStore::new(dir.join("store.json"));
+        store.set("songs", &serde_json::json!({ "s0": {} })).unwrap();
+        assert_eq!(store.take_entry_changes("songs"), None);
+
+        store.track_entry_changes(&["songs"]);
+        assert_eq!(store.take_entry_changes("songs"), Some(EntryChanges::All));
+
+        store.set_subkey("songs", "s1", &serde_json::json!({ "title": "S" })).unwrap();
+        store.remove_entry("songs", "s0");
+        store.remove_entry("songs", "missing");
+        let ids = HashSet::from(["s0".to_string(), "s1".to_string()]);
+        assert_eq!(store.take_entry_changes("songs"), Some(EntryChanges::Ids(ids)));
+        assert_eq!(store.take_entry_changes("songs"), Some(EntryChanges::Ids(HashSet::new())));
+
+        store.set_subkey("songs", "s2", &serde_json::json!({})).unwrap();
+        store.set("songs", &serde_json::json!({})).unwrap();
+        assert_eq!(store.take_entry_changes("songs"), Some(EntryChanges::All));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
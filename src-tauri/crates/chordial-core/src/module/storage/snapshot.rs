@@ -0,0 +1,261 @@
+//! 每日快照与损坏自愈 — 防止一次写坏的落盘清空整个音乐库（播放统计、进度、编辑等）。
+//!
+//! 快照保存在数据文件同级的 `snapshots/` 目录，文件名为 `{文件名}-{YYYY-MM-DD}.json`（UTC 日期），
+//! 每天最多一份，只保留最近 [`DEFAULT_RETAIN`] 份（可配置）。拍快照前先校验当前文件能解析，
+//! 坏文件不会挤掉好快照。
+//!
+//! 启动时在加载数据文件之前调用 [`Snapshots::heal`]：文件无法解析（或被截断为空、而快照存在）时，
+//! 把坏文件改名为 `*.corrupt-{时间戳}` 留档，用最新一份可解析的快照替换，并返回 [`RecoveryReport`]。
+
+use serde::de::IgnoredAny;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// 默认保留的快照份数。
+pub const DEFAULT_RETAIN: usize = 7;
+
+/// 快照保留份数的配置键。
+pub const RETAIN_CONFIG_KEY: &str = "library_snapshot_retain";
+
+/// 一份快照。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotInfo {
+    pub file_name: String,
+    /// 快照日期（UTC，`YYYY-MM-DD`）
+    pub date: String,
+    pub size: u64,
+}
+
+/// 启动时发现数据文件损坏后的恢复结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryReport {
+    /// 解析失败的原因
+    pub error: String,
+    /// 损坏文件的留档路径
+    pub corrupt_backup: Option<String>,
+    /// 损坏文件大小（字节）
+    pub corrupt_bytes: u64,
+    /// 回滚到的快照；`None` 表示没有可用快照，从空库开始
+    pub restored_from: Option<SnapshotInfo>,
+    /// 恢复后各顶层键的条目数（如 `songs`、`play_stats`），快照日期之后的修改均已丢失
+    pub restored_entries: BTreeMap<String, usize>,
+}
+
+/// 某个数据文件的快照管理。
+pub struct Snapshots {
+    file: PathBuf,
+    dir: PathBuf,
+    retain: AtomicUsize,
+    /// 已确认有快照的日期（距 Unix 纪元的天数），避免每次落盘都访问快照目录
+    last_day: AtomicU64,
+}
+
+impl Snapshots {
+    pub fn new(file: PathBuf) -> Self {
+        let dir = file
+            .parent()
+            .map(|p| p.join("snapshots"))
+            .unwrap_or_else(|| PathBuf::from("snapshots"));
+        Self {
+            file,
+            dir,
+            retain: AtomicUsize::new(DEFAULT_RETAIN),
+            last_day: AtomicU64::new(0),
+        }
+    }
+
+    /// 设置保留份数（至少 1 份），并立即清理多余的快照。
+    pub fn set_retain(&self, retain: usize) -> Result<(), String> {
+        self.retain.store(retain.max(1), Ordering::Relaxed);
+        self.prune()
+    }
+
+    /// 已有快照，新的在前。
+    pub fn list(&self) -> Vec<SnapshotInfo> {
+        let prefix = self.prefix();
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut snapshots: Vec<SnapshotInfo> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                let date = file_name.strip_prefix(&prefix)?.strip_suffix(".json")?.to_string();
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                Some(SnapshotInfo { file_name, date, size })
+            })
+            .collect();
+        snapshots.sort_by(|a, b| b.date.cmp(&a.date));
+        snapshots
+    }
+
+    /// 当天还没有快照时拍一份。当前文件不存在时跳过，无法解析时报错。
+    pub fn take_daily(&self) -> Result<Option<SnapshotInfo>, String> {
+        let today = days_since_epoch();
+        if self.last_day.load(Ordering::Relaxed) == today || !self.file.exists() {
+            return Ok(None);
+        }
+        let date = format_day(today);
+        let target = self.dir.join(format!("{}{}.json", self.prefix(), date));
+        if target.exists() {
+            self.last_day.store(today, Ordering::Relaxed);
+            return Ok(None);
+        }
+
+        let content = fs::read(&self.file).map_err(|e| format!("读取数据文件失败: {}", e))?;
+        serde_json::from_slice::<HashMap<String, IgnoredAny>>(&content)
+            .map_err(|e| format!("数据文件无法解析，跳过快照: {}", e))?;
+        fs::create_dir_all(&self.dir).map_err(|e| format!("创建快照目录失败: {}", e))?;
+        let tmp = target.with_extension("tmp");
+        fs::write(&tmp, &content)
+            .and_then(|_| fs::rename(&tmp, &target))
+            .map_err(|e| format!("写入快照失败: {}", e))?;
+
+        self.last_day.store(today, Ordering::Relaxed);
+        self.prune()?;
+        Ok(Some(SnapshotInfo {
+            file_name: target.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            date,
+            size: content.len() as u64,
+        }))
+    }
+
+    /// 检查数据文件；损坏时回滚到最新的可用快照。文件完好或不存在时返回 `None`。
+    pub fn heal(&self) -> Option<RecoveryReport> {
+        let content = match fs::read(&self.file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => return Some(self.restore(format!("读取数据文件失败: {}", e), 0)),
+        };
+        let error = if content.iter().all(u8::is_ascii_whitespace) {
+            // 空文件可能是全新安装，也可能是被截断的写入；有快照时按后者处理
+            if self.list().is_empty() {
+                return None;
+            }
+            "数据文件为空".to_string()
+        } else {
+            match serde_json::from_slice::<HashMap<String, IgnoredAny>>(&content) {
+                Ok(_) => return None,
+                Err(e) => format!("解析 JSON 失败: {}", e),
+            }
+        };
+        Some(self.restore(error, content.len() as u64))
+    }
+
+    /// 把坏文件改名留档，再用最新一份可解析的快照替换它。
+    fn restore(&self, error: String, corrupt_bytes: u64) -> RecoveryReport {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut backup = self.file.clone().into_os_string();
+        backup.push(format!(".corrupt-{}", stamp));
+        let backup = PathBuf::from(backup);
+        let corrupt_backup = fs::rename(&self.file, &backup)
+            .ok()
+            .map(|_| backup.to_string_lossy().into_owned());
+
+        let mut report = RecoveryReport {
+            error,
+            corrupt_backup,
+            corrupt_bytes,
+            restored_from: None,
+            restored_entries: BTreeMap::new(),
+        };
+        for snapshot in self.list() {
+            let path = self.dir.join(&snapshot.file_name);
+            let Ok(data) = read_store(&path) else {
+                continue;
+            };
+            if fs::copy(&path, &self.file).is_err() {
+                continue;
+            }
+            report.restored_entries = data
+                .iter()
+                .map(|(key, value)| (key.clone(), value.as_object().map_or(1, |o| o.len())))
+                .collect();
+            report.restored_from = Some(snapshot);
+            break;
+        }
+        report
+    }
+
+    /// 删除超出保留份数的旧快照。
+    fn prune(&self) -> Result<(), String> {
+        let retain = self.retain.load(Ordering::Relaxed);
+        for old in self.list().into_iter().skip(retain) {
+            fs::remove_file(self.dir.join(&old.file_name))
+                .map_err(|e| format!("删除旧快照失败: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn prefix(&self) -> String {
+        let stem = self
+            .file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("{}-", stem)
+    }
+}
+
+fn read_store(path: &Path) -> Result<HashMap<String, Value>, String> {
+    let content = fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&content).map_err(|e| e.to_string())
+}
+
+fn days_since_epoch() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0)
+}
+
+/// 天数 → `YYYY-MM-DD`（公历，Howard Hinnant 的 civil_from_days 算法）。
+fn format_day(days: u64) -> String {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_then_heal_corrupt_file() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(20_513), "2026-03-01");
+
+        let dir = std::env::temp_dir().join(format!("chordial_snapshot_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("music_library.json");
+        fs::write(&file, r#"{"songs":{"a":{},"b":{}},"play_stats":{"a":{}}}"#).unwrap();
+
+        let snapshots = Snapshots::new(file.clone());
+        assert!(snapshots.heal().is_none());
+        assert!(snapshots.take_daily().unwrap().is_some());
+        assert!(snapshots.take_daily().unwrap().is_none());
+
+        fs::write(&file, r#"{"songs":{"a":{"#).unwrap();
+        let report = snapshots.heal().expect("corrupt file should be healed");
+        assert!(report.restored_from.is_some());
+        assert_eq!(report.restored_entries["songs"], 2);
+        assert!(report.corrupt_backup.is_some());
+        assert!(read_store(&file).is_ok());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! 启动 axum HTTP 服务器，对外暴露完整的音乐系统 API。
 //! 监听地址可通过环境变量 `CHORDIAL_BIND` 覆盖，默认 `127.0.0.1:7878`。
+//! 设置 `CHORDIAL_SAFE_MODE=1` 或带 `--safe-mode` 参数启动时进入安全模式（见 `chordial_core::module::safe_mode`）。
 
 use chordial_core::AppContext;
 use chordial_server::routes;
@@ -18,7 +19,10 @@ async fn main() {
             .expect("初始化 Chordial server 层上下文失败"),
     );
 
-    let state = AppState { ctx };
+    // 后台启动扫描；列表 / 搜索请求在扫描结束前等待或返回 503
+    ctx.warm_up();
+
+    let state = AppState { ctx: ctx.clone() };
 
     let app = routes::build(state);
 
@@ -29,6 +33,12 @@ async fn main() {
     println!("[chordial-server] 监听 {}", bind_addr);
 
     axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
         .await
         .expect("服务器运行错误");
+
+    // 退出前落盘写回窗口内的修改
+    ctx.shutdown();
 }
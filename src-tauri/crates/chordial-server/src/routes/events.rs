@@ -0,0 +1,104 @@
+//! 事件流 — 通过 WebSocket 把 core 事件总线推给 HTTP 模式的客户端。
+//!
+//! | 方法 | 路径 | 说明 |
+//! |------|------|------|
+//! | GET | `/events?topics=scan,analysis` | 升级为 WebSocket；省略 `topics` 时订阅全部主题 |
+//!
+//! 无界面部署没有 Tauri 事件桥，靠这里观察扫描、分析等长时间操作。每条事件一个文本帧：
+//! `{ "topic": "analysis", "event": { "type": "analysis_progress", ... } }`，`event` 与 Tauri 事件的载荷相同。
+//! 连接期间客户端可发送 `{ "subscribe": [..], "unsubscribe": [..] }` 调整订阅。
+//! 客户端读得太慢时丢弃最旧的事件，并发送 `{ "lagged": n }` 提示丢了多少条。
+
+use crate::state::AppState;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use chordial_core::module::events::EventTopic;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use tokio::sync::broadcast::error::RecvError;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/events", get(events))
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    topics: Option<String>,
+}
+
+/// 连接期间的订阅调整消息。
+#[derive(Debug, Deserialize)]
+struct SubscriptionChange {
+    #[serde(default)]
+    subscribe: Vec<EventTopic>,
+    #[serde(default)]
+    unsubscribe: Vec<EventTopic>,
+}
+
+async fn events(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let topics = match query.topics.as_deref() {
+        Some(list) => parse_topics(list).map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+        None => EventTopic::ALL.into_iter().collect(),
+    };
+    Ok(ws.on_upgrade(move |socket| stream(socket, state, topics)))
+}
+
+/// 解析逗号分隔的主题列表。
+fn parse_topics(list: &str) -> Result<HashSet<EventTopic>, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| EventTopic::parse(name).ok_or_else(|| format!("未知的事件主题: {}", name)))
+        .collect()
+}
+
+async fn stream(mut socket: WebSocket, state: AppState, mut topics: HashSet<EventTopic>) {
+    let mut rx = state.ctx.events.subscribe();
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                let frame = match event {
+                    Ok(event) => {
+                        let topic = event.topic();
+                        if !topics.contains(&topic) {
+                            continue;
+                        }
+                        json!({ "topic": topic, "event": event })
+                    }
+                    Err(RecvError::Lagged(skipped)) => json!({ "lagged": skipped }),
+                    Err(RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(frame.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<SubscriptionChange>(&text) {
+                    Ok(change) => {
+                        topics.extend(change.subscribe);
+                        for topic in &change.unsubscribe {
+                            topics.remove(topic);
+                        }
+                    }
+                    Err(e) => {
+                        let error = json!({ "error": format!("无法解析订阅消息: {}", e) });
+                        if socket.send(Message::Text(error.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -39,6 +39,7 @@ use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::{get, post};
 use axum::{Json, Router};
+use chordial_core::module::readiness::Subsystem;
 use serde::Deserialize;
 
 pub fn router() -> Router<AppState> {
@@ -90,7 +91,7 @@ pub fn router() -> Router<AppState> {
 // ── 持久化 ──────────────────────────────────────────
 
 async fn library_save(State(state): State<AppState>) -> Result<StatusCode, String> {
-    state.ctx.library.save()?;
+    state.ctx.library.flush()?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -108,14 +109,18 @@ async fn song_count(State(state): State<AppState>) -> Json<usize> {
 
 async fn get_song(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
     match state.ctx.library.get_song(&id) {
-        Some(song) => Ok(Json(serde_json::to_value(&song).unwrap())),
+        Some(song) => Ok(Json(serde_json::to_value(state.ctx.library.localize_song(song)).unwrap())),
         None => Err((StatusCode::NOT_FOUND, format!("歌曲 '{}' 不存在", id))),
     }
 }
 
-async fn get_all_songs(State(state): State<AppState>) -> Json<serde_json::Value> {
-    let songs: Vec<_> = state.ctx.library.get_all_songs().into_values().collect();
-    Json(serde_json::to_value(&songs).unwrap())
+async fn get_all_songs(State(state): State<AppState>) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    state.wait_ready(Subsystem::Library).await?;
+    let songs = state
+        .ctx
+        .library
+        .localize_songs(state.ctx.library.get_all_songs().into_values().collect());
+    Ok(Json(serde_json::to_value(&songs).unwrap()))
 }
 
 #[derive(Debug, Deserialize)]
@@ -126,9 +131,10 @@ struct SearchQuery {
 async fn search_songs(
     State(state): State<AppState>,
     Query(query): Query<SearchQuery>,
-) -> Json<serde_json::Value> {
-    let songs = state.ctx.library.search_songs(&query.q);
-    Json(serde_json::to_value(&songs).unwrap())
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    state.wait_ready(Subsystem::Library).await?;
+    let songs = state.ctx.library.localize_songs(state.ctx.library.search_songs(&query.q));
+    Ok(Json(serde_json::to_value(&songs).unwrap()))
 }
 
 // ── Artist ──────────────────────────────────────────
@@ -144,17 +150,19 @@ async fn get_artist(State(state): State<AppState>, Path(id): Path<String>) -> im
     }
 }
 
-async fn get_all_artists(State(state): State<AppState>) -> Json<serde_json::Value> {
+async fn get_all_artists(State(state): State<AppState>) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    state.wait_ready(Subsystem::Library).await?;
     let artists: Vec<_> = state.ctx.library.get_all_artists().into_values().collect();
-    Json(serde_json::to_value(&artists).unwrap())
+    Ok(Json(serde_json::to_value(&artists).unwrap()))
 }
 
 async fn search_artists(
     State(state): State<AppState>,
     Query(query): Query<SearchQuery>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    state.wait_ready(Subsystem::Library).await?;
     let artists = state.ctx.library.search_artists(&query.q);
-    Json(serde_json::to_value(&artists).unwrap())
+    Ok(Json(serde_json::to_value(&artists).unwrap()))
 }
 
 // ── Album ───────────────────────────────────────────
@@ -170,17 +178,19 @@ async fn get_album(State(state): State<AppState>, Path(id): Path<String>) -> imp
     }
 }
 
-async fn get_all_albums(State(state): State<AppState>) -> Json<serde_json::Value> {
+async fn get_all_albums(State(state): State<AppState>) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    state.wait_ready(Subsystem::Library).await?;
     let albums: Vec<_> = state.ctx.library.get_all_albums().into_values().collect();
-    Json(serde_json::to_value(&albums).unwrap())
+    Ok(Json(serde_json::to_value(&albums).unwrap()))
 }
 
 async fn search_albums(
     State(state): State<AppState>,
     Query(query): Query<SearchQuery>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    state.wait_ready(Subsystem::Library).await?;
     let albums = state.ctx.library.search_albums(&query.q);
-    Json(serde_json::to_value(&albums).unwrap())
+    Ok(Json(serde_json::to_value(&albums).unwrap()))
 }
 
 // ── Lyric ───────────────────────────────────────────
@@ -251,7 +261,7 @@ async fn songs_by_artist(
     State(state): State<AppState>,
     Path(artist_id): Path<String>,
 ) -> Json<serde_json::Value> {
-    let songs = state.ctx.library.get_songs_by_artist(&artist_id);
+    let songs = state.ctx.library.localize_songs(state.ctx.library.get_songs_by_artist(&artist_id));
     Json(serde_json::to_value(&songs).unwrap())
 }
 
@@ -267,6 +277,6 @@ async fn songs_in_album(
     State(state): State<AppState>,
     Path(album_id): Path<String>,
 ) -> Json<serde_json::Value> {
-    let songs = state.ctx.library.get_songs_in_album(&album_id);
+    let songs = state.ctx.library.localize_songs(state.ctx.library.get_songs_in_album(&album_id));
     Json(serde_json::to_value(&songs).unwrap())
 }
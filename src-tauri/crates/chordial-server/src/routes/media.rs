@@ -2,17 +2,18 @@
 //!
 //! | 方法 | 路径 | 对应功能 |
 //! |------|------|---------|
-//! | GET/HEAD | `/audio/:sn_b64/:eid_b64` | 音频流（支持 Range/206） |
+//! | GET/HEAD | `/audio/:sn_b64/:eid_b64` | 音频流（支持 Range/206；不带 Range 的大文件从磁盘流式发送） |
 //! | GET | `/image/:sn_b64/:eid_b64` | 封面图片 |
 //! | GET | `/lyric/:sn_b64/:eid_b64` | 歌词文本 |
 
 use crate::state::AppState;
 use axum::body::Body;
 use axum::extract::{Path, State};
-use axum::http::{HeaderMap, Method, StatusCode};
+use axum::http::{header, HeaderMap, Method, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::routing::get;
 use axum::Router;
+use tokio_util::io::ReaderStream;
 
 pub fn router() -> Router<AppState> {
     Router::new()
@@ -50,6 +51,9 @@ fn to_core_request(method: &Method, headers: &HeaderMap) -> http::Request<Vec<u8
 }
 
 /// `GET/HEAD /audio/{sn}/{eid}` — 音频流（支持 Range）。
+///
+/// core 每次最多返回 [`MAX_RANGE_BYTES`](chordial_core::media::MAX_RANGE_BYTES)；
+/// 不带 Range 的 GET 请求超过该大小的磁盘文件时（下载、转发到其他播放器），在这里按块流式发送整个文件。
 async fn audio(
     State(state): State<AppState>,
     Path((sn, eid)): Path<(String, String)>,
@@ -57,11 +61,33 @@ async fn audio(
     headers: HeaderMap,
 ) -> impl IntoResponse {
     let path = format!("/audio/{}/{}", sn, eid);
+    if method == Method::GET && !headers.contains_key(header::RANGE) {
+        if let Some(file_path) = chordial_core::media::audio_file_path(&state.ctx.registrar, &path) {
+            if let Some(response) = stream_file(&file_path).await {
+                return response;
+            }
+        }
+    }
     let req = to_core_request(&method, &headers);
     let resp = chordial_core::media::handle(&state.ctx.registrar, &path, &req);
     convert_response(resp)
 }
 
+/// 以 `200` 流式发送整个文件；文件不超过单次响应上限或无法打开时返回 `None`，交给 core 处理。
+async fn stream_file(file_path: &str) -> Option<Response> {
+    let file = tokio::fs::File::open(file_path).await.ok()?;
+    let size = file.metadata().await.ok()?.len();
+    if size <= chordial_core::media::MAX_RANGE_BYTES {
+        return None;
+    }
+    Response::builder()
+        .header(header::CONTENT_TYPE, chordial_core::module::platform::mime_from_path(file_path))
+        .header(header::CONTENT_LENGTH, size)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .ok()
+}
+
 /// `GET /image/{sn}/{eid}` — 封面图片。
 async fn image(
     State(state): State<AppState>,
@@ -2,6 +2,7 @@
 
 pub mod cache;
 pub mod config;
+pub mod events;
 pub mod library;
 pub mod media;
 pub mod rpc;
@@ -27,6 +28,7 @@ pub fn build(state: AppState) -> Router {
         .merge(sources::router())
         .merge(media::router())
         .merge(rpc::router())
+        .merge(events::router())
         .layer(cors)
         .with_state(state)
 }
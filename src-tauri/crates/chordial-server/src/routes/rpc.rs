@@ -11,10 +11,29 @@ use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::post;
 use axum::{Json, Router};
+use chordial_core::module::analysis::content_hash;
+use chordial_core::module::artwork::{self, ExportOptions};
+use chordial_core::module::lyrics::LyricsQuery;
+use chordial_core::module::metadata::EnrichTarget;
+use chordial_core::module::music_library::batch::BatchReport;
+use chordial_core::module::music_library::edits::{ConflictResolution, FieldValues, MetadataField};
+use chordial_core::module::music_library::grouping::{GroupBy, GROUP_BY_CONFIG_KEY};
+use chordial_core::module::music_library::localize::DISPLAY_LANGUAGE_CONFIG_KEY;
+use chordial_core::module::music_library::quality::QualityFilter;
+use chordial_core::module::music_library::smart_playlists::SmartRules;
+use chordial_core::module::music_library::{relations, songs};
+use chordial_core::module::music_library::stats::WRITE_BACK_CONFIG_KEY;
 use chordial_core::module::music_localSource;
+use chordial_core::module::music_localSource::ogg_chain::{self, ChainTags};
+use chordial_core::module::music_localSource::scanner::{self, BatchReadOptions};
+use chordial_core::module::music_localSource::session;
 use chordial_core::module::music_source::resource;
 use chordial_core::module::music_source::types::{EntityType, SourceId};
 use chordial_core::module::platform::{self, PlatformPath};
+use chordial_core::module::readiness::Subsystem;
+use chordial_core::module::playback::crossfade::DEFAULT_CURVE_SAMPLES;
+use chordial_core::module::playback::render::{self, RenderOptions, RenderTrack};
+use chordial_core::module::playback::{ContentType, FadeAction, PlayerReport, SkipDirection, PLAYBACK_RATE_PRESETS};
 use chordial_core::module::storage::entry::Ttl;
 use serde::Deserialize;
 use serde_json::{json, Value};
@@ -31,10 +50,30 @@ pub fn router() -> Router<AppState> {
     Router::new().route("/rpc", post(handle_rpc))
 }
 
+/// 需要音乐库完成启动预热的命令（列表 / 搜索），与 Tauri 命令层的 `wait_library` 一致。
+const LIBRARY_READ_COMMANDS: &[&str] = &[
+    "library_get_all_songs",
+    "library_get_songs_page",
+    "playlist_get_songs",
+    "import_playlist",
+    "playlist_preview_smart",
+    "playlist_materialize",
+    "library_search_songs",
+    "library_get_all_artists",
+    "library_search_artists",
+    "library_get_all_albums",
+    "library_search_albums",
+    "library_search",
+    "search_library",
+];
+
 async fn handle_rpc(
     State(state): State<AppState>,
     Json(req): Json<RpcRequest>,
 ) -> impl IntoResponse {
+    if LIBRARY_READ_COMMANDS.contains(&req.name.as_str()) {
+        state.wait_ready(Subsystem::Library).await?;
+    }
     match dispatch(&state, &req.name, &req.args) {
         Ok(value) => Ok(Json(value)),
         Err(msg) => Err((StatusCode::BAD_REQUEST, msg)),
@@ -219,90 +258,197 @@ fn dispatch(state: &AppState, name: &str, args: &Value) -> Result<Value, String>
             let source = &state.ctx.local_source;
             let folder_path = PlatformPath::from(path);
             source.folder_manager.add_folder(&folder_path)?;
-            let files = music_localSource::folder::collect_audio_files(&folder_path);
-            let mut indexed = 0u64;
-            let mut errors = Vec::new();
-            for file in &files {
-                match source.index_file(file) {
-                    Ok(true) => indexed += 1,
-                    Ok(false) => {}
-                    Err(e) => errors.push(format!("{}: {}", platform::path_to_string(file), e)),
+            let files = source.folder_manager.collect_audio_files(&folder_path);
+            let ((indexed, errors), diff) = state.ctx.apply_library_change(|| {
+                let mut indexed = 0u64;
+                let mut errors = Vec::new();
+                for file in &files {
+                    match source.index_file(file) {
+                        Ok(true) => indexed += 1,
+                        Ok(false) => {}
+                        Err(e) => errors.push(format!("{}: {}", platform::path_to_string(file), e)),
+                    }
                 }
-            }
-            source.library.save()?;
-            Ok(json!({ "added": true, "path": path, "files_found": files.len(), "indexed": indexed, "errors": errors }))
+                Ok((indexed, errors))
+            })?;
+            Ok(json!({ "added": true, "path": path, "files_found": files.len(), "indexed": indexed, "errors": errors, "diff": diff }))
         }
         "local_remove_folder" => {
             let path = args["path"].as_str().ok_or("缺少 path")?;
             let source = &state.ctx.local_source;
             let folder_path = PlatformPath::from(path);
-            let files = music_localSource::folder::collect_audio_files(&folder_path);
+            let files = source.folder_manager.collect_audio_files(&folder_path);
             let entity_ids: HashSet<String> = files.iter()
                 .map(|f| platform::path_to_string(&platform::canonicalize(f).unwrap_or_else(|_| f.clone())))
                 .collect();
-            if !entity_ids.is_empty() {
-                source.library.remove_specific_song_source_ids(
-                    music_localSource::source::LOCAL_SOURCE_NAME, &entity_ids,
-                )?;
-            }
-            for file in &files { let _ = source.unindex_file(file); }
-            let removed = source.folder_manager.remove_folder(&folder_path);
-            source.library.save()?;
-            Ok(json!({ "removed": removed, "path": path, "cleaned_files": entity_ids.len() }))
+            let (removed, diff) = state.ctx.apply_library_change(|| {
+                if !entity_ids.is_empty() {
+                    source.library.remove_specific_song_source_ids(
+                        music_localSource::source::LOCAL_SOURCE_NAME, &entity_ids,
+                    )?;
+                }
+                for file in &files { let _ = source.unindex_file(file); }
+                Ok(source.folder_manager.remove_folder(&folder_path))
+            })?;
+            Ok(json!({ "removed": removed, "path": path, "cleaned_files": entity_ids.len(), "diff": diff }))
         }
         "local_get_folders" => Ok(json!(state.ctx.local_source.folder_manager.get_folders()
             .iter().map(|p| platform::path_to_string(p)).collect::<Vec<_>>())),
         "local_rescan" => {
+            use chordial_core::module::music_localSource::scanner::ScanOptions;
+            let options: ScanOptions = match args.get("options").filter(|v| !v.is_null()) {
+                Some(v) => serde_json::from_value(v.clone()).map_err(|e| format!("无效的 options: {}", e))?,
+                None => ScanOptions::default(),
+            };
             let source = &state.ctx.local_source;
             let folders = source.folder_manager.get_folders();
-            let mut total = 0u64;
-            for folder in &folders {
-                for file in &music_localSource::folder::collect_audio_files(folder) {
-                    match source.index_file(file) {
-                        Ok(true) => total += 1,
-                        _ => {}
-                    }
-                }
-            }
-            source.library.save()?;
-            Ok(json!({ "indexed": total, "folders_scanned": folders.len() }))
+            let files: Vec<PlatformPath> = folders
+                .iter()
+                .flat_map(|folder| source.folder_manager.collect_audio_files(folder))
+                .collect();
+            let (report, diff) = state.ctx.apply_library_change(|| source.rescan_files(&files, options))?;
+            Ok(json!({
+                "indexed": report.indexed,
+                "files_found": report.files_found,
+                "unchanged": report.unchanged,
+                "changed": report.changed,
+                "folders_scanned": folders.len(),
+                "errors": report.errors,
+                "diff": diff,
+            }))
+        }
+        "start_scan" => {
+            use chordial_core::module::music_localSource::scanner::ScanOptions;
+            let options: ScanOptions = match args.get("options").filter(|v| !v.is_null()) {
+                Some(v) => serde_json::from_value(v.clone()).map_err(|e| format!("无效的 options: {}", e))?,
+                None => ScanOptions::default(),
+            };
+            serde_json::to_value(state.ctx.start_scan(options)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "cancel_scan" => Ok(json!(state.ctx.cancel_scan())),
+        "get_scan_status" => serde_json::to_value(state.ctx.scans.status()).map_err(|e| format!("序列化失败: {}", e)),
+        "get_quarantined_files" => Ok(json!(state.ctx.local_source.quarantine.list())),
+        "local_retry_quarantined" => {
+            let path = args["path"].as_str().ok_or("缺少 path")?;
+            let indexed = state.ctx.local_source.retry_quarantined(&PlatformPath::from(path))?;
+            Ok(json!({ "path": path, "indexed": indexed }))
+        }
+        "local_read_metadata" => {
+            let paths: Vec<PlatformPath> = args["paths"].as_array().ok_or("缺少 paths")?
+                .iter().filter_map(|v| v.as_str()).map(PlatformPath::from).collect();
+            let task_id = args["task_id"].as_str().ok_or("缺少 task_id")?;
+            let defaults = BatchReadOptions::default();
+            let options = BatchReadOptions {
+                chunk_size: args.get("chunk_size").and_then(|v| v.as_u64()).map(|n| n as usize).unwrap_or(defaults.chunk_size),
+                chunk_delay_ms: args.get("chunk_delay_ms").and_then(|v| v.as_u64()).unwrap_or(defaults.chunk_delay_ms),
+                max_threads: state.ctx.power.scan_threads(defaults.max_threads),
+            };
+            let token = state.ctx.tasks.register(task_id);
+            let outcome = scanner::batch_read_metadata_with_progress(&paths, &state.ctx.local_source.meta_cache, &token, options, |_| {});
+            state.ctx.tasks.finish(task_id, &token);
+            let results: Vec<Value> = outcome.results.into_iter().map(|(path, result)| match result {
+                Ok(meta) => json!({ "path": platform::path_to_string(&path), "meta": meta }),
+                Err(e) => json!({ "path": platform::path_to_string(&path), "error": e }),
+            }).collect();
+            Ok(json!({ "results": results, "cancelled": outcome.cancelled }))
+        }
+        "session_open_files" => {
+            let paths: Vec<PlatformPath> = args["paths"].as_array().ok_or("缺少 paths")?
+                .iter().filter_map(|v| v.as_str()).map(PlatformPath::from).collect();
+            serde_json::to_value(session::open_files(&state.ctx.local_source, &paths)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "session_play_folder" => {
+            let path = args["path"].as_str().ok_or("缺少 path")?;
+            serde_json::to_value(session::play_folder(&state.ctx.local_source, &PlatformPath::from(path))).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "cancel_task" => {
+            let task_id = args["task_id"].as_str().ok_or("缺少 task_id")?;
+            Ok(json!(state.ctx.tasks.cancel(task_id)))
         }
 
         // Library persistence
-        "library_save" => { state.ctx.library.save()?; Ok(Value::Null) }
+        "library_save" => { state.ctx.library.flush()?; Ok(Value::Null) }
         "library_cleanup_empty_entities" => { state.ctx.library.cleanup_empty_entities()?; state.ctx.library.save()?; Ok(Value::Null) }
 
         // Library Song
-        "library_song_count" => Ok(json!(state.ctx.library.song_count())),
+        "library_song_count" => {
+            let filter = parse_quality_filter(args)?;
+            Ok(json!(state.ctx.library.song_count_filtered(filter.as_ref())))
+        }
+        "library_get_songs_page" => {
+            let offset = args["offset"].as_u64().unwrap_or(0) as usize;
+            let limit = args["limit"].as_u64().unwrap_or(50) as usize;
+            let filter = parse_quality_filter(args)?;
+            let page = state.ctx.library.get_songs_page_filtered(offset, limit, filter.as_ref());
+            serde_json::to_value(state.ctx.library.localize_songs(page)).map_err(|e| format!("序列化失败: {}", e))
+        }
         "library_get_song" => {
             let id = args["id"].as_str().ok_or("缺少 id")?;
             let song = state.ctx.library.get_song(id).ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
-            serde_json::to_value(&song).map_err(|e| format!("序列化失败: {}", e))
+            serde_json::to_value(state.ctx.library.localize_song(song)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_get_songs_by_ids" => {
+            let ids = parse_ids(args, "ids")?;
+            let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(songs::MAX_IDS_PER_PAGE, |n| n as usize);
+            let mut page = state.ctx.library.get_songs_page_by_ids(&ids, offset, limit);
+            page.songs = state.ctx.library.localize_songs(page.songs);
+            serde_json::to_value(page).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_get_all_songs" => {
-            let songs: Vec<_> = state.ctx.library.get_all_songs().into_values().collect();
+            let songs = state.ctx.library.without_hidden(state.ctx.library.get_all_songs().into_values().collect());
+            let songs = state.ctx.library.localize_songs(songs);
             serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_search_songs" => {
             let q = args["q"].as_str().ok_or("缺少 q")?;
-            serde_json::to_value(&state.ctx.library.search_songs(q)).map_err(|e| format!("序列化失败: {}", e))
+            serde_json::to_value(state.ctx.library.localize_songs(state.ctx.library.search_songs(q))).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_set_display_language" => {
+            let language = args.get("language").and_then(|v| v.as_str()).map(str::trim).filter(|l| !l.is_empty()).map(str::to_string);
+            match &language {
+                Some(lang) => state.ctx.config.set(DISPLAY_LANGUAGE_CONFIG_KEY, lang)?,
+                None => { state.ctx.config.remove(DISPLAY_LANGUAGE_CONFIG_KEY); }
+            }
+            state.ctx.library.set_display_language(language);
+            Ok(Value::Null)
         }
+        "library_get_display_language" => Ok(json!(state.ctx.library.display_language())),
+        "library_set_group_by" => {
+            let group_by: GroupBy = serde_json::from_value(args["group_by"].clone())
+                .map_err(|e| format!("group_by 无效: {}", e))?;
+            state.ctx.config.set(GROUP_BY_CONFIG_KEY, &group_by)?;
+            state.ctx.library.set_group_by(group_by);
+            Ok(Value::Null)
+        }
+        "library_get_group_by" => Ok(json!(state.ctx.library.group_by())),
 
         // Library Artist
-        "library_artist_count" => Ok(json!(state.ctx.library.artist_count())),
+        "library_artist_count" => Ok(json!(state.ctx.library.artist_count_grouped(parse_group_by(args)?))),
         "library_get_artist" => {
             let id = args["id"].as_str().ok_or("缺少 id")?;
             let artist = state.ctx.library.get_artist(id).ok_or_else(|| format!("艺术家 '{}' 不存在", id))?;
             serde_json::to_value(&artist).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_get_all_artists" => {
-            let artists: Vec<_> = state.ctx.library.get_all_artists().into_values().collect();
+            let artists: Vec<_> = state.ctx.library.get_all_artists_grouped(parse_group_by(args)?).into_values().collect();
             serde_json::to_value(&artists).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_search_artists" => {
             let q = args["q"].as_str().ok_or("缺少 q")?;
             serde_json::to_value(&state.ctx.library.search_artists(q)).map_err(|e| format!("序列化失败: {}", e))
         }
+        "set_artist_bio" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let bio = args["bio"].as_str().map(str::to_string);
+            serde_json::to_value(state.ctx.library.set_artist_bio(id, bio)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_artist_genres" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let genres: Vec<String> = serde_json::from_value(args["genres"].clone())
+                .map_err(|e| format!("解析 genres 失败: {}", e))?;
+            serde_json::to_value(state.ctx.library.set_artist_genres(id, genres)?).map_err(|e| format!("序列化失败: {}", e))
+        }
 
         // Library Album
         "library_album_count" => Ok(json!(state.ctx.library.album_count())),
@@ -312,7 +458,7 @@ fn dispatch(state: &AppState, name: &str, args: &Value) -> Result<Value, String>
             serde_json::to_value(&album).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_get_all_albums" => {
-            let albums: Vec<_> = state.ctx.library.get_all_albums().into_values().collect();
+            let albums = state.ctx.library.without_hidden_albums(state.ctx.library.get_all_albums().into_values().collect());
             serde_json::to_value(&albums).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_search_albums" => {
@@ -329,7 +475,31 @@ fn dispatch(state: &AppState, name: &str, args: &Value) -> Result<Value, String>
             };
             let source_name = args.get("source_name").and_then(|v| v.as_str());
             let limit_per_type = args.get("limit_per_type").and_then(|v| v.as_u64()).map(|n| n as usize);
-            let results = state.ctx.library.search(query, entity_type, source_name, limit_per_type);
+            let filter = parse_quality_filter(args)?;
+            let mut results = state.ctx.library.search(query, entity_type, source_name, limit_per_type);
+            let songs = state.ctx.library.filter_songs(results.songs, filter.as_ref());
+            results.songs = state.ctx.library.localize_songs(songs);
+            serde_json::to_value(&results).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "search_library" => {
+            use chordial_core::module::music_library::search::{SearchOptions, DEFAULT_RANKED_LIMIT};
+            let query = args["query"].as_str().ok_or("缺少 query")?;
+            let types = match args.get("types").and_then(|v| v.as_array()) {
+                Some(types) => types
+                    .iter()
+                    .map(|t| t.as_str().ok_or("types 须为字符串数组".to_string()).and_then(parse_entity_type))
+                    .collect::<Result<_, _>>()?,
+                None => Vec::new(),
+            };
+            let options = SearchOptions {
+                types,
+                source_name: args.get("source_name").and_then(|v| v.as_str()).map(str::to_string),
+                lyrics: args.get("lyrics").and_then(|v| v.as_bool()).unwrap_or(false),
+                fuzzy: args.get("fuzzy").and_then(|v| v.as_bool()).unwrap_or(true),
+            };
+            let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(DEFAULT_RANKED_LIMIT, |n| n as usize);
+            let filter = parse_quality_filter(args)?;
+            let results = state.ctx.library.search_library(query, &options, filter.as_ref(), limit);
             serde_json::to_value(&results).map_err(|e| format!("序列化失败: {}", e))
         }
 
@@ -366,25 +536,1115 @@ fn dispatch(state: &AppState, name: &str, args: &Value) -> Result<Value, String>
         }
         "library_get_songs_by_artist" => {
             let id = args["artist_id"].as_str().ok_or("缺少 artist_id")?;
-            serde_json::to_value(&state.ctx.library.get_songs_by_artist(id)).map_err(|e| format!("序列化失败: {}", e))
+            let filter = parse_quality_filter(args)?;
+            let songs = state.ctx.library.filter_songs(state.ctx.library.get_songs_by_artist(id), filter.as_ref());
+            serde_json::to_value(state.ctx.library.localize_songs(songs)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_get_lossless_songs" => {
+            serde_json::to_value(state.ctx.library.localize_songs(state.ctx.library.get_lossless_songs())).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_get_song_languages" => {
+            serde_json::to_value(state.ctx.library.get_song_languages()).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_get_albums_by_artist" => {
             let id = args["artist_id"].as_str().ok_or("缺少 artist_id")?;
             serde_json::to_value(&state.ctx.library.get_albums_by_artist(id)).map_err(|e| format!("序列化失败: {}", e))
         }
+        "library_get_related_artists" => {
+            let id = args["artist_id"].as_str().ok_or("缺少 artist_id")?;
+            let limit = args["limit"].as_u64().map(|n| n as usize).unwrap_or(relations::RELATED_ARTISTS_LIMIT);
+            serde_json::to_value(state.ctx.library.get_related_artists(id, limit)).map_err(|e| format!("序列化失败: {}", e))
+        }
         "library_get_songs_in_album" => {
             let id = args["album_id"].as_str().ok_or("缺少 album_id")?;
-            serde_json::to_value(&state.ctx.library.get_songs_in_album(id)).map_err(|e| format!("序列化失败: {}", e))
+            let filter = parse_quality_filter(args)?;
+            let songs = state.ctx.library.filter_songs(state.ctx.library.get_songs_in_album(id), filter.as_ref());
+            serde_json::to_value(state.ctx.library.localize_songs(songs)).map_err(|e| format!("序列化失败: {}", e))
         }
         "library_get_source_ids_of_song" => {
             let id = args["song_id"].as_str().ok_or("缺少 song_id")?;
             serde_json::to_value(&state.ctx.library.get_source_ids_of_song(id)).map_err(|e| format!("序列化失败: {}", e))
         }
 
+        // Playback
+        "playback_get_settings" => {
+            serde_json::to_value(state.ctx.playback.settings()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_time_stretch_quality" => {
+            let quality = serde_json::from_value(args.get("quality").cloned().ok_or("缺少 quality")?)
+                .map_err(|e| format!("无效的 quality: {}", e))?;
+            let settings = state.ctx.playback.set_time_stretch_quality(quality)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_stretch_params" => {
+            let speed = args["speed"].as_f64().ok_or("缺少 speed")?;
+            serde_json::to_value(state.ctx.playback.stretch_params_for(speed)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_rate_presets" => Ok(json!(PLAYBACK_RATE_PRESETS)),
+        "playback_get_rate" => {
+            let content_type = parse_content_type(args)?;
+            serde_json::to_value(state.ctx.playback.playback_rate(content_type)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_playback_rate" => {
+            let content_type = parse_content_type(args)?;
+            let rate = args["rate"].as_f64().ok_or("缺少 rate")?;
+            let plan = state.ctx.playback.set_playback_rate(content_type, rate)?;
+            serde_json::to_value(plan).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_silence_skip" => {
+            let silence_skip = serde_json::from_value(args.get("silence_skip").cloned().ok_or("缺少 silence_skip")?)
+                .map_err(|e| format!("无效的 silence_skip: {}", e))?;
+            let settings = state.ctx.playback.set_silence_skip(silence_skip)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_karaoke" => {
+            let karaoke = serde_json::from_value(args.get("karaoke").cloned().ok_or("缺少 karaoke")?)
+                .map_err(|e| format!("无效的 karaoke: {}", e))?;
+            let settings = state.ctx.playback.set_karaoke(karaoke)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_preload" => {
+            let preload = serde_json::from_value(args.get("preload").cloned().ok_or("缺少 preload")?)
+                .map_err(|e| format!("无效的 preload: {}", e))?;
+            let settings = state.ctx.playback.set_preload(preload)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_audio_volume" => {
+            let volume = args["volume"].as_f64().ok_or("缺少 volume")? as f32;
+            let gain = state.ctx.playback.set_volume(volume)?;
+            serde_json::to_value(gain).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "get_audio_volume" => {
+            serde_json::to_value(state.ctx.playback.volume_gain()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_volume_curve" => {
+            let curve = serde_json::from_value(args.get("curve").cloned().ok_or("缺少 curve")?)
+                .map_err(|e| format!("无效的 curve: {}", e))?;
+            let gain = state.ctx.playback.set_volume_curve(curve)?;
+            serde_json::to_value(gain).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_dither" => {
+            let dither = serde_json::from_value(args.get("dither").cloned().ok_or("缺少 dither")?)
+                .map_err(|e| format!("无效的 dither: {}", e))?;
+            let settings = state.ctx.playback.set_dither(dither)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_fades" => {
+            let fades = serde_json::from_value(args.get("fades").cloned().ok_or("缺少 fades")?)
+                .map_err(|e| format!("无效的 fades: {}", e))?;
+            let settings = state.ctx.playback.set_fades(fades)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_fade_plan" => {
+            let action = serde_json::from_value(args.get("action").cloned().ok_or("缺少 action")?)
+                .map_err(|e| format!("无效的 action: {}", e))?;
+            serde_json::to_value(state.ctx.playback.fade_plan(action)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_crossfade" => {
+            let crossfade = serde_json::from_value(args.get("crossfade").cloned().ok_or("缺少 crossfade")?)
+                .map_err(|e| format!("无效的 crossfade: {}", e))?;
+            let settings = state.ctx.playback.set_crossfade(crossfade)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_playlist_transition" => {
+            let playlist_id = args["playlist_id"].as_str().ok_or("缺少 playlist_id")?;
+            let transition = serde_json::from_value(args.get("transition").cloned().ok_or("缺少 transition")?)
+                .map_err(|e| format!("无效的 transition: {}", e))?;
+            let settings = state.ctx.playback.set_playlist_transition(playlist_id, transition)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_crossfade_plan" => {
+            let ms = |key: &str| args.get(key).and_then(|v| v.as_u64());
+            let playlist_id = args.get("playlist_id").and_then(|v| v.as_str());
+            let plan = state.ctx.playback.crossfade_plan(playlist_id, ms("current_ms"), ms("remaining_ms"), ms("next_ms"));
+            serde_json::to_value(plan).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_crossfade_curve" => {
+            let samples = args.get("samples").and_then(|v| v.as_u64()).map_or(DEFAULT_CURVE_SAMPLES, |n| n as usize);
+            serde_json::to_value(state.ctx.playback.crossfade_curve_table(samples)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_crossfade_started" => {
+            let from = args["from_song_id"].as_str().ok_or("缺少 from_song_id")?;
+            let to = args["to_song_id"].as_str().ok_or("缺少 to_song_id")?;
+            let duration_ms = args["duration_ms"].as_u64().ok_or("缺少 duration_ms")?;
+            state.ctx.crossfade.begin(from, to, duration_ms.min(u32::MAX as u64) as u32);
+            Ok(Value::Null)
+        }
+        "get_crossfade_status" => serde_json::to_value(state.ctx.crossfade_status()).map_err(|e| format!("序列化失败: {}", e)),
+        "cancel_crossfade" => {
+            let keep = serde_json::from_value(args.get("keep").cloned().ok_or("缺少 keep")?)
+                .map_err(|e| format!("无效的 keep: {}", e))?;
+            serde_json::to_value(state.ctx.cancel_crossfade(keep)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "record_transition" => {
+            let report = serde_json::from_value(args.get("transition").cloned().ok_or("缺少 transition")?)
+                .map_err(|e| format!("无效的 transition: {}", e))?;
+            state.ctx.record_transition(report)?;
+            Ok(Value::Null)
+        }
+        "get_transition_history" => {
+            use chordial_core::module::playback::transitions::MAX_RECORDS;
+            let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(MAX_RECORDS, |n| n as usize);
+            serde_json::to_value(state.ctx.transitions.recent(limit)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "clear_transition_history" => {
+            state.ctx.transitions.clear()?;
+            Ok(Value::Null)
+        }
+        "playback_set_skip_step" => {
+            let content_type = parse_content_type(args)?;
+            let secs = args["secs"].as_u64().ok_or("缺少 secs")? as u32;
+            serde_json::to_value(state.ctx.playback.set_skip_step(content_type, secs)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "skip_forward" | "skip_backward" => {
+            let content_type = parse_content_type(args)?;
+            let position_ms = args["position_ms"].as_f64().ok_or("缺少 position_ms")?;
+            let secs = args.get("secs").and_then(|v| v.as_u64()).map(|n| n as u32);
+            let (direction, duration_ms) = if name == "skip_forward" {
+                (SkipDirection::Forward, args.get("duration_ms").and_then(|v| v.as_f64()))
+            } else {
+                (SkipDirection::Backward, None)
+            };
+            let plan = state.ctx.playback.skip_plan(content_type, direction, position_ms, duration_ms, secs)?;
+            serde_json::to_value(plan).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "seek_audio" => {
+            let target_ms = args["target_ms"].as_f64().ok_or("缺少 target_ms")?;
+            let duration_ms = args.get("duration_ms").and_then(|v| v.as_f64());
+            let plan = state.ctx.playback.seek_plan(target_ms, duration_ms)?;
+            serde_json::to_value(plan).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_get_silence_map" => {
+            let id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let song = state.ctx.library.get_song(id).ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+            let path = resource::find_song_file_path(&state.ctx.registrar, &song.source_ids)
+                .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", id))?;
+            let map = state.ctx.playback.silence_map(&path)?;
+            serde_json::to_value(map.as_deref()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_output_latency" => {
+            let latency_ms = args["latency_ms"].as_u64().ok_or("缺少 latency_ms")?;
+            let latency_ms = u32::try_from(latency_ms).map_err(|_| "latency_ms 过大".to_string())?;
+            let settings = state.ctx.playback.set_output_latency(latency_ms)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_get_dsp_profile" => {
+            let profile = state.ctx.playback.dsp_profile(args["device_id"].as_str());
+            serde_json::to_value(profile).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_dsp_profile" => {
+            let device_id = args["device_id"].as_str().ok_or("缺少 device_id")?;
+            let profile = serde_json::from_value(args.get("profile").cloned().ok_or("缺少 profile")?)
+                .map_err(|e| format!("无效的 profile: {}", e))?;
+            let settings = state.ctx.playback.set_dsp_profile(device_id, profile)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_output_device" => {
+            let device_id = args["device_id"].as_str().ok_or("缺少 device_id")?;
+            let profile = state.ctx.playback.set_output_device(device_id)?;
+            serde_json::to_value(profile).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_copy_dsp_profile" => {
+            let from = args["from_device"].as_str().ok_or("缺少 from_device")?;
+            let to = args["to_device"].as_str().ok_or("缺少 to_device")?;
+            let settings = state.ctx.playback.copy_dsp_profile(from, to)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "get_audio_position" => {
+            let raw = args["raw_position_ms"].as_f64().ok_or("缺少 raw_position_ms")?;
+            let speed = args.get("speed").and_then(|v| v.as_f64()).unwrap_or(1.0);
+            serde_json::to_value(state.ctx.playback.compensate_position(raw, speed)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "render_mix" => {
+            let track_ids: Vec<&str> = args["track_ids"].as_array().ok_or("缺少 track_ids")?
+                .iter().filter_map(|v| v.as_str()).collect();
+            let output_path = args["output_path"].as_str().ok_or("缺少 output_path")?;
+            let task_id = args["task_id"].as_str().ok_or("缺少 task_id")?;
+            let queue: Vec<RenderTrack> = track_ids.iter()
+                .filter_map(|id| state.ctx.library.get_song(id))
+                .filter_map(|song| {
+                    let path = resource::find_song_file_path(&state.ctx.registrar, &song.source_ids)?;
+                    Some(RenderTrack { path: PlatformPath::from(path), title: song.title, performer: song.artist_names.join(", ") })
+                })
+                .collect();
+            let settings = state.ctx.playback.settings();
+            let mut options = RenderOptions {
+                crossfade_ms: settings.crossfade.duration_ms,
+                dither: settings.dither,
+                short_tracks: settings.crossfade.short_tracks,
+                fade_in_ms: state.ctx.playback.fade_plan(FadeAction::Play).fade_in_ms,
+                fade_out_ms: state.ctx.playback.fade_plan(FadeAction::Stop).fade_out_ms,
+                curve: settings.crossfade.curve,
+                custom_curve: settings.crossfade.custom_curve.clone(),
+            };
+            if let Some(ms) = args.get("crossfade_ms").and_then(|v| v.as_u64()) {
+                options.crossfade_ms = u32::try_from(ms).map_err(|_| "crossfade_ms 过大".to_string())?;
+            }
+            let token = state.ctx.tasks.register(task_id);
+            let result = render::render_mix(&queue, std::path::Path::new(output_path), options, &token, |_, _| {});
+            state.ctx.tasks.finish(task_id, &token);
+            serde_json::to_value(result?).map_err(|e| format!("序列化失败: {}", e))
+        }
+
+        // Analysis
+        "get_track_technical_info" => {
+            let id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let song = state.ctx.library.get_song(id).ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+            let path = resource::find_song_file_path(&state.ctx.registrar, &song.source_ids)
+                .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", id))?;
+            serde_json::to_value(&*state.ctx.analysis.technical_info(&path)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "analyze_track_transcode" => {
+            let id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let song = state.ctx.library.get_song(id).ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+            let path = resource::find_song_file_path(&state.ctx.registrar, &song.source_ids)
+                .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", id))?;
+            serde_json::to_value(state.ctx.analysis.transcode_verdict(id, &path)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "get_track_segments" => {
+            let id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let input = state.ctx.analysis_input(id)?;
+            serde_json::to_value(state.ctx.analysis.track_segments(id, &input)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "get_vocal_segments" => {
+            let id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let input = state.ctx.analysis_input(id)?;
+            serde_json::to_value(state.ctx.analysis.vocal_map(id, &input)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "analyze_library_transcodes" => {
+            let songs: Vec<(String, String)> = state.ctx.library.get_all_songs().into_values()
+                .filter_map(|song| resource::find_song_file_path(&state.ctx.registrar, &song.source_ids).map(|path| (song.id, path)))
+                .collect();
+            let task_id = args.get("task_id").and_then(|v| v.as_str()).unwrap_or("transcode_scan");
+            let job = state.ctx.analysis.spawn_transcode_scan(task_id, songs, state.ctx.tasks.clone(), state.ctx.events.clone())?;
+            serde_json::to_value(job).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_get_suspected_transcodes" => {
+            let min_confidence = args.get("min_confidence").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32;
+            let items: Vec<Value> = state.ctx.analysis.suspected_transcodes(min_confidence).into_iter()
+                .filter_map(|record| {
+                    let song = state.ctx.library.get_song(&record.song_id)?;
+                    Some(json!({ "song": state.ctx.library.localize_song(song), "verdict": record.verdict }))
+                })
+                .collect();
+            Ok(json!(items))
+        }
+        "get_track_energy" => {
+            let id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let input = state.ctx.analysis_input(id)?;
+            serde_json::to_value(state.ctx.analysis.energy_profile(id, &input)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "analyze_library_energy" => {
+            let songs: Vec<(String, String)> = state.ctx.library.get_all_songs().into_values()
+                .filter_map(|song| resource::find_song_file_path(&state.ctx.registrar, &song.source_ids).map(|path| (song.id, path)))
+                .collect();
+            let task_id = args.get("task_id").and_then(|v| v.as_str()).unwrap_or("energy_scan");
+            let job = state.ctx.analysis.spawn_energy_scan(task_id, songs, state.ctx.tasks.clone(), state.ctx.events.clone())?;
+            serde_json::to_value(job).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_get_songs_by_energy" => {
+            let bound = |key: &str, default: u8| args.get(key).and_then(|v| v.as_u64()).map_or(default, |v| v.min(100) as u8);
+            let descending = args.get("descending").and_then(|v| v.as_bool()).unwrap_or(false);
+            let items: Vec<Value> = state.ctx.analysis.songs_by_energy(bound("min_energy", 0), bound("max_energy", 100), descending).into_iter()
+                .filter_map(|(song_id, profile)| {
+                    let song = state.ctx.library.get_song(&song_id)?;
+                    Some(json!({ "song": state.ctx.library.localize_song(song), "energy": profile }))
+                })
+                .collect();
+            Ok(json!(items))
+        }
+
+        // Lyrics providers
+        "lyrics_get_providers" => serde_json::to_value(state.ctx.lyrics.statuses()).map_err(|e| format!("序列化失败: {}", e)),
+        "lyrics_set_provider_enabled" => {
+            let name = args["name"].as_str().ok_or("缺少 name")?;
+            let enabled = args["enabled"].as_bool().ok_or("缺少 enabled")?;
+            state.ctx.lyrics.set_enabled(name, enabled)?;
+            Ok(Value::Null)
+        }
+        "lyrics_search" => {
+            let id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let song = state.ctx.library.get_song(id).ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+            let path = resource::find_song_file_path(&state.ctx.registrar, &song.source_ids);
+            serde_json::to_value(state.ctx.lyrics.search(&LyricsQuery::for_song(&song, path))).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "lyrics_fetch" => {
+            let provider = args["provider"].as_str().ok_or("缺少 provider")?;
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            Ok(json!(state.ctx.lyrics.fetch(provider, id)?))
+        }
+
+        // Metadata providers
+        "metadata_get_providers" => serde_json::to_value(state.ctx.metadata.providers()).map_err(|e| format!("序列化失败: {}", e)),
+        "metadata_enrich" => {
+            let target = parse_enrich_target(args)?;
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+            serde_json::to_value(state.ctx.metadata.enrich(target, id, overwrite)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "metadata_get_provenance" => {
+            let target = parse_enrich_target(args)?;
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            serde_json::to_value(state.ctx.metadata.provenance(target, id)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "metadata_revert_field" => {
+            let target = parse_enrich_target(args)?;
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let field = args["field"].as_str().ok_or("缺少 field")?;
+            serde_json::to_value(state.ctx.metadata.revert(target, id, field)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "metadata_enrich_all" => {
+            let target = parse_enrich_target(args)?;
+            let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+            Ok(json!(state.ctx.metadata.enrich_all(target, overwrite)))
+        }
+        "library_edit_song_metadata" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let changes: FieldValues = serde_json::from_value(args["changes"].clone())
+                .map_err(|e| format!("解析 changes 失败: {}", e))?;
+            let stored_id = state.ctx.local_source.edit_song_metadata(song_id, &changes)?;
+            state.ctx.sync_song_edits(std::slice::from_ref(&stored_id), &changes);
+            Ok(json!(stored_id))
+        }
+        "library_edit_songs_metadata" => {
+            let song_ids: Vec<String> = serde_json::from_value(args["song_ids"].clone())
+                .map_err(|e| format!("解析 song_ids 失败: {}", e))?;
+            let changes: FieldValues = serde_json::from_value(args["changes"].clone())
+                .map_err(|e| format!("解析 changes 失败: {}", e))?;
+            let stored_ids = state.ctx.local_source.edit_songs_metadata(&song_ids, &changes)?;
+            state.ctx.sync_song_edits(&stored_ids, &changes);
+            Ok(json!(stored_ids))
+        }
+        "write_track_metadata" => {
+            let track_id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let fields: FieldValues = serde_json::from_value(args["fields"].clone())
+                .map_err(|e| format!("解析 fields 失败: {}", e))?;
+            let stored_id = state.ctx.local_source.write_song_metadata(track_id, &fields)?;
+            state.ctx.sync_song_edits(std::slice::from_ref(&stored_id), &fields);
+            Ok(json!(stored_id))
+        }
+        "library_remove_songs" => {
+            let song_ids: Vec<String> = serde_json::from_value(args["song_ids"].clone())
+                .map_err(|e| format!("解析 song_ids 失败: {}", e))?;
+            let removed = state.ctx.library.remove_songs(&song_ids)?;
+            if removed > 0 {
+                state.ctx.library.save()?;
+            }
+            Ok(json!(removed))
+        }
+        "library_hide_tracks" => {
+            let song_ids: Vec<String> = serde_json::from_value(args["song_ids"].clone())
+                .map_err(|e| format!("解析 song_ids 失败: {}", e))?;
+            let hidden = state.ctx.library.hide_songs(&song_ids)?;
+            if hidden > 0 {
+                state.ctx.library.save()?;
+            }
+            Ok(json!(hidden))
+        }
+        "library_hide_albums" => {
+            let album_ids: Vec<String> = serde_json::from_value(args["album_ids"].clone())
+                .map_err(|e| format!("解析 album_ids 失败: {}", e))?;
+            let hidden = state.ctx.library.hide_albums(&album_ids)?;
+            if hidden > 0 {
+                state.ctx.library.save()?;
+            }
+            Ok(json!(hidden))
+        }
+        "library_get_hidden" => serde_json::to_value(state.ctx.library.hidden_items())
+            .map_err(|e| format!("序列化失败: {}", e)),
+        "library_restore_hidden" => {
+            let song_ids: Vec<String> = serde_json::from_value(args.get("song_ids").cloned().unwrap_or(Value::Null))
+                .unwrap_or_default();
+            let album_ids: Vec<String> = serde_json::from_value(args.get("album_ids").cloned().unwrap_or(Value::Null))
+                .unwrap_or_default();
+            let restored = state.ctx.library.restore_hidden(&song_ids, &album_ids)?;
+            if restored > 0 {
+                state.ctx.library.save()?;
+            }
+            Ok(json!(restored))
+        }
+        "get_change_history" => serde_json::to_value(state.ctx.library.change_history())
+            .map_err(|e| format!("序列化失败: {}", e)),
+        "undo_last_change" => serde_json::to_value(state.ctx.local_source.undo_last_change()?)
+            .map_err(|e| format!("序列化失败: {}", e)),
+        "get_metadata_conflicts" => serde_json::to_value(state.ctx.library.get_metadata_conflicts())
+            .map_err(|e| format!("序列化失败: {}", e)),
+        "resolve_metadata_conflict" => {
+            let path = args["path"].as_str().ok_or("缺少 path")?;
+            let field: MetadataField = serde_json::from_value(args["field"].clone())
+                .map_err(|e| format!("解析 field 失败: {}", e))?;
+            let resolution: ConflictResolution = serde_json::from_value(args["resolution"].clone())
+                .map_err(|e| format!("解析 resolution 失败: {}", e))?;
+            Ok(json!(state.ctx.local_source.resolve_metadata_conflict(path, field, resolution)?))
+        }
+        "stats_get" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            Ok(json!(state.ctx.library.get_play_stats(song_id)))
+        }
+        "stats_set_rating" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let rating = match args["rating"].as_u64() {
+                Some(r) => Some(u8::try_from(r).map_err(|_| "评分需在 0–100 之间")?),
+                None => None,
+            };
+            let stats = state.ctx.library.set_rating(song_id, rating)?;
+            state.ctx.library.save()?;
+            let write_back = state.ctx.config.get::<bool>(WRITE_BACK_CONFIG_KEY).unwrap_or(false);
+            let write_error = if write_back { state.ctx.local_source.write_stats_to_file(song_id).err() } else { None };
+            Ok(json!({ "stats": stats, "written_to_file": write_back && write_error.is_none(), "write_error": write_error }))
+        }
+        "stats_record_play" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let stats = state.ctx.record_play(song_id)?;
+            Ok(json!(stats))
+        }
+        "stats_set_write_back" => {
+            let enabled = args["enabled"].as_bool().ok_or("缺少 enabled")?;
+            state.ctx.config.set(WRITE_BACK_CONFIG_KEY, &enabled)?;
+            Ok(Value::Null)
+        }
+        "stats_get_write_back" => Ok(json!(state.ctx.config.get::<bool>(WRITE_BACK_CONFIG_KEY).unwrap_or(false))),
+
+        // ── 队列预加载 / 媒体缓存 ──
+        "preload_set_queue" => {
+            let song_ids: Vec<String> = serde_json::from_value(args.get("song_ids").cloned().ok_or("缺少 song_ids")?)
+                .map_err(|e| format!("无效的 song_ids: {}", e))?;
+            let remaining = args["current_remaining_ms"].as_u64().ok_or("缺少 current_remaining_ms")?;
+            state.ctx.preload.set_queue(&song_ids, remaining);
+            Ok(Value::Null)
+        }
+        "preload_get_status" => {
+            serde_json::to_value(state.ctx.preload.status()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_set_pcm_cache" => {
+            let pcm_cache = serde_json::from_value(args.get("pcm_cache").cloned().ok_or("缺少 pcm_cache")?)
+                .map_err(|e| format!("无效的 pcm_cache: {}", e))?;
+            let settings = state.ctx.set_pcm_cache(pcm_cache)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "pcm_cache_get_size" => Ok(json!(state.ctx.registrar.pcm_cache().map_or(0, |cache| cache.size()))),
+        "pcm_cache_clear" => {
+            if let Some(cache) = state.ctx.registrar.pcm_cache() {
+                cache.clear()?;
+            }
+            Ok(Value::Null)
+        }
+        "media_cache_get_size" => Ok(json!(state.ctx.registrar.media_cache().map_or(0, |cache| cache.size()))),
+        "media_cache_clear" => {
+            if let Some(cache) = state.ctx.registrar.media_cache() {
+                cache.clear()?;
+            }
+            Ok(Value::Null)
+        }
+        "cache_get_location" => serde_json::to_value(state.ctx.cache_location()).map_err(|e| format!("序列化失败: {}", e)),
+        "cache_relocate" => {
+            let report = state.ctx.relocate_cache(args["dir"].as_str())?;
+            serde_json::to_value(report).map_err(|e| format!("序列化失败: {}", e))
+        }
+
+        // Source write-back
+        "source_get_entries" => serde_json::to_value(state.ctx.registrar.get_entries()).map_err(|e| format!("序列化失败: {}", e)),
+        "source_set_read_only" => {
+            let name = args["name"].as_str().ok_or("缺少 name")?;
+            let read_only = args["read_only"].as_bool().ok_or("缺少 read_only")?;
+            state.ctx.registrar.set_read_only(name, read_only)?;
+            Ok(Value::Null)
+        }
+        "source_set_metadata_only" => {
+            let name = args["name"].as_str().ok_or("缺少 name")?;
+            let metadata_only = args["metadata_only"].as_bool().ok_or("缺少 metadata_only")?;
+            state.ctx.registrar.set_metadata_only(name, metadata_only)?;
+            Ok(Value::Null)
+        }
+        "source_put_lyric_text" => {
+            let sid: SourceId = serde_json::from_value(args["source_id"].clone())
+                .map_err(|e| format!("解析 SourceId: {}", e))?;
+            let text = args["text"].as_str().ok_or("缺少 text")?;
+            resource::put_lyric_text(&state.ctx.registrar, &sid, text)?;
+            Ok(Value::Null)
+        }
+        "source_put_album_picture" => {
+            let sid: SourceId = serde_json::from_value(args["source_id"].clone())
+                .map_err(|e| format!("解析 SourceId: {}", e))?;
+            let data_b64 = args["data"].as_str().ok_or("缺少 data")?;
+            use base64::Engine;
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(data_b64)
+                .map_err(|e| format!("base64 解码失败: {}", e))?;
+            resource::put_album_picture(&state.ctx.registrar, &sid, &data)?;
+            Ok(Value::Null)
+        }
+
+        // Access audit
+        "source_get_access_log" => {
+            use chordial_core::module::music_source::audit::MAX_RECORDS;
+            let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(MAX_RECORDS, |n| n as usize);
+            serde_json::to_value(state.ctx.registrar.access_log().recent(limit)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "source_clear_access_log" => {
+            state.ctx.registrar.access_log().clear();
+            Ok(Value::Null)
+        }
+
+        // Transcoded streams
+        "source_set_transcode" => {
+            let name = args["name"].as_str().ok_or("缺少 name")?;
+            let transcode = serde_json::from_value(args.get("transcode").cloned().unwrap_or(Value::Null))
+                .map_err(|e| format!("解析 transcode 失败: {}", e))?;
+            let metered_transcode = serde_json::from_value(args.get("metered_transcode").cloned().unwrap_or(Value::Null))
+                .map_err(|e| format!("解析 metered_transcode 失败: {}", e))?;
+            state.ctx.registrar.set_transcode(name, transcode, metered_transcode)?;
+            Ok(Value::Null)
+        }
+        "source_set_metered_network" => {
+            let metered = args["metered"].as_bool().ok_or("缺少 metered")?;
+            state.ctx.registrar.set_metered(metered);
+            Ok(Value::Null)
+        }
+
+        // Album / artist playback
+        "play_album" | "play_artist" => {
+            use chordial_core::module::playback::queue;
+            let mut play_queue = if name == "play_album" {
+                let album_id = args["album_id"].as_str().ok_or("缺少 album_id")?;
+                let start_track_id = args["start_track_id"].as_str();
+                let shuffle = args["shuffle"].as_bool().unwrap_or(false);
+                queue::album_queue(&state.ctx.library, album_id, start_track_id, shuffle)?
+            } else {
+                let artist_id = args["artist_id"].as_str().ok_or("缺少 artist_id")?;
+                queue::artist_queue(&state.ctx.library, artist_id)?
+            };
+            let song_ids = play_queue.songs.iter().map(|s| s.id.clone()).collect();
+            state.ctx.queue_replace(song_ids, play_queue.start_index, None);
+            play_queue.songs = state.ctx.library.localize_songs(play_queue.songs);
+            serde_json::to_value(play_queue).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "activate_track" => {
+            use chordial_core::module::playback::{queue, ActivateAction, ActivateContext};
+            let track_id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let context: ActivateContext = serde_json::from_value(args.get("context").cloned().ok_or("缺少 context")?)
+                .map_err(|e| format!("无效的 context: {}", e))?;
+            let action = state.ctx.playback.settings().activate_action(context.surface());
+            let mut activation = queue::activate_track(&state.ctx.library, track_id, &context, action)?;
+            if action == ActivateAction::Replace {
+                let playlist_id = match &context {
+                    ActivateContext::Playlist { playlist_id } => Some(playlist_id.clone()),
+                    _ => None,
+                };
+                let song_ids = activation.queue.songs.iter().map(|s| s.id.clone()).collect();
+                state.ctx.queue_replace(song_ids, activation.queue.start_index, playlist_id);
+            } else {
+                state.ctx.queue_add(&[track_id.to_string()], action == ActivateAction::InsertNext)?;
+            }
+            activation.queue.songs = state.ctx.library.localize_songs(activation.queue.songs);
+            serde_json::to_value(activation).map_err(|e| format!("序列化失败: {}", e))
+        }
+        // Play queue
+        "queue_get" => serde_json::to_value(state.ctx.queue_view()).map_err(|e| format!("序列化失败: {}", e)),
+        "queue_set" => {
+            let song_ids = parse_ids(args, "song_ids")?;
+            let start_index = args["start_index"].as_u64().unwrap_or(0) as usize;
+            let playlist_id = args["playlist_id"].as_str().map(String::from);
+            let step = state.ctx.queue_replace(song_ids, start_index, playlist_id);
+            serde_json::to_value(step).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "queue_add" => {
+            let song_ids = parse_ids(args, "song_ids")?;
+            let view = state.ctx.queue_add(&song_ids, args["next"].as_bool().unwrap_or(false))?;
+            serde_json::to_value(view).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "queue_remove" => {
+            let index = args["index"].as_u64().ok_or("缺少 index")? as usize;
+            serde_json::to_value(state.ctx.queue_remove(index)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "queue_next" => serde_json::to_value(state.ctx.queue_next()).map_err(|e| format!("序列化失败: {}", e)),
+        "queue_previous" => serde_json::to_value(state.ctx.queue_previous()).map_err(|e| format!("序列化失败: {}", e)),
+        "queue_jump" => {
+            let index = args["index"].as_u64().ok_or("缺少 index")? as usize;
+            serde_json::to_value(state.ctx.queue_jump(index)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "queue_set_repeat_mode" => {
+            let mode = serde_json::from_value(args.get("mode").cloned().ok_or("缺少 mode")?)
+                .map_err(|e| format!("无效的 mode: {}", e))?;
+            serde_json::to_value(state.ctx.queue_set_repeat_mode(mode)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "queue_set_shuffle" => {
+            let enabled = args["enabled"].as_bool().ok_or("缺少 enabled")?;
+            serde_json::to_value(state.ctx.queue_set_shuffle(enabled)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "queue_report_position" => {
+            let remaining_ms = args["remaining_ms"].as_u64().ok_or("缺少 remaining_ms")?;
+            serde_json::to_value(state.ctx.queue_report_position(remaining_ms)).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_activate_action" => {
+            let surface = serde_json::from_value(args.get("surface").cloned().ok_or("缺少 surface")?)
+                .map_err(|e| format!("无效的 surface: {}", e))?;
+            let action = serde_json::from_value(args.get("action").cloned().ok_or("缺少 action")?)
+                .map_err(|e| format!("无效的 action: {}", e))?;
+            let settings = state.ctx.playback.set_activate_action(surface, action)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_endofqueue_behavior" => {
+            let behavior = serde_json::from_value(args.get("behavior").cloned().ok_or("缺少 behavior")?)
+                .map_err(|e| format!("无效的 behavior: {}", e))?;
+            let settings = state.ctx.playback.set_end_of_queue(behavior)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_pause_on_suspend" => {
+            let enabled = args["enabled"].as_bool().ok_or("缺少 enabled")?;
+            let settings = state.ctx.playback.set_pause_on_suspend(enabled)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "set_null_output" => {
+            let enabled = args["enabled"].as_bool().ok_or("缺少 enabled")?;
+            let settings = state.ctx.playback.set_null_output(enabled)?;
+            serde_json::to_value(&settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_get_output_mode" => {
+            serde_json::to_value(state.ctx.playback.output_mode()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_end_of_queue" => {
+            use chordial_core::module::playback::{queue, EndOfQueueBehavior};
+            let song_ids: Vec<String> = serde_json::from_value(args.get("song_ids").cloned().ok_or("缺少 song_ids")?)
+                .map_err(|e| format!("无效的 song_ids: {}", e))?;
+            let behavior = state.ctx.playback.settings().end_of_queue;
+            let mut plan = queue::end_of_queue(&state.ctx.library, behavior, &song_ids);
+            let next: Vec<String> = match plan.behavior {
+                EndOfQueueBehavior::Stop => Vec::new(),
+                EndOfQueueBehavior::RepeatQueue => song_ids,
+                EndOfQueueBehavior::Autoplay => plan.songs.iter().map(|s| s.id.clone()).collect(),
+            };
+            state.ctx.preload.set_queue(&next, 0);
+            plan.songs = state.ctx.library.localize_songs(plan.songs);
+            serde_json::to_value(plan).map_err(|e| format!("序列化失败: {}", e))
+        }
+
+        // Batch operations
+        "queue_add_many" => {
+            let track_ids = parse_ids(args, "track_ids")?;
+            let mut songs = Vec::with_capacity(track_ids.len());
+            let report = BatchReport::run(&track_ids, |id| {
+                let song = state.ctx.library.get_song(id).ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+                songs.push(song);
+                Ok(())
+            });
+            let ids: Vec<String> = songs.iter().map(|s| s.id.clone()).collect();
+            state.ctx.queue_add(&ids, false)?;
+            Ok(json!({ "songs": state.ctx.library.localize_songs(songs), "report": report }))
+        }
+        "add_tracks_to_playlist" => {
+            let playlist_id = args["playlist_id"].as_str().ok_or("缺少 playlist_id")?;
+            let track_ids = parse_ids(args, "track_ids")?;
+            let (playlist, report) = state.ctx.library.add_tracks_to_playlist(playlist_id, &track_ids)?;
+            state.ctx.library.save()?;
+            Ok(json!({ "playlist": playlist, "report": report }))
+        }
+        "batch_set_rating" => {
+            let song_ids = parse_ids(args, "song_ids")?;
+            let rating = match args["rating"].as_u64() {
+                Some(r) => Some(u8::try_from(r).map_err(|_| "评分需在 0–100 之间")?),
+                None => None,
+            };
+            let report = state.ctx.library.set_ratings(&song_ids, rating)?;
+            state.ctx.library.save()?;
+            let write_back = state.ctx.config.get::<bool>(WRITE_BACK_CONFIG_KEY).unwrap_or(false);
+            let write_errors: Vec<(String, String)> = if write_back {
+                report.succeeded.iter()
+                    .filter_map(|id| state.ctx.local_source.write_stats_to_file(id).err().map(|e| (id.clone(), e)))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            Ok(json!({ "report": report, "write_errors": write_errors }))
+        }
+        "batch_analyze" => {
+            let track_ids = parse_ids(args, "track_ids")?;
+            let mut songs = Vec::with_capacity(track_ids.len());
+            let report = BatchReport::run(&track_ids, |id| {
+                let song = state.ctx.library.get_song(id).ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+                let path = resource::find_song_file_path(&state.ctx.registrar, &song.source_ids)
+                    .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", id))?;
+                songs.push((song.id, path));
+                Ok(())
+            });
+            let job = if songs.is_empty() {
+                None
+            } else {
+                let task_id = args.get("task_id").and_then(|v| v.as_str()).unwrap_or("batch_analyze");
+                Some(state.ctx.analysis.spawn_transcode_scan(task_id, songs, state.ctx.tasks.clone(), state.ctx.events.clone())?)
+            };
+            Ok(json!({ "job": job, "report": report }))
+        }
+
+        // Scan extensions
+        "local_get_extensions" => {
+            serde_json::to_value(state.ctx.local_source.folder_manager.extension_settings())
+                .map_err(|e| format!("序列化失败: {}", e))
+        }
+        "local_set_extension_alias" => {
+            let ext = args["ext"].as_str().ok_or("缺少 ext")?;
+            let target = args["target"].as_str();
+            let settings = state.ctx.local_source.folder_manager.set_extension_alias(ext, target)?;
+            serde_json::to_value(settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "local_set_folder_extensions" => {
+            let entry = serde_json::from_value(args.clone()).map_err(|e| format!("无效的文件夹扩展名设置: {}", e))?;
+            let settings = state.ctx.local_source.folder_manager.set_folder_extensions(entry)?;
+            serde_json::to_value(settings).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "local_get_folder_access" => {
+            serde_json::to_value(state.ctx.local_source.folder_manager.folder_access())
+                .map_err(|e| format!("序列化失败: {}", e))
+        }
+        "local_set_folder_access" => {
+            use chordial_core::module::music_localSource::folder::FolderAccess;
+            let folder = args["folder"].as_str().ok_or("缺少 folder")?;
+            let access = FolderAccess {
+                read_only: args["read_only"].as_bool().ok_or("缺少 read_only")?,
+                metadata_only: args["metadata_only"].as_bool().ok_or("缺少 metadata_only")?,
+            };
+            let manager = &state.ctx.local_source.folder_manager;
+            manager.set_folder_access(&PlatformPath::from(folder), access)?;
+            serde_json::to_value(manager.folder_access()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "local_set_ogg_chain_tags" => {
+            let choice: ChainTags = serde_json::from_value(args["choice"].clone())
+                .map_err(|e| format!("无效的 choice: {}", e))?;
+            state.ctx.config.set(ogg_chain::CHAIN_TAGS_CONFIG_KEY, &choice)?;
+            ogg_chain::set_chain_tags(choice);
+            Ok(Value::Null)
+        }
+        "local_get_ogg_chain_tags" => {
+            serde_json::to_value(ogg_chain::chain_tags()).map_err(|e| format!("序列化失败: {}", e))
+        }
+
+        // Audiobooks
+        "library_get_books" => {
+            let books: Vec<_> = state.ctx.library.get_books()?.into_iter()
+                .map(|mut book| { book.song = state.ctx.library.localize_song(book.song); book })
+                .collect();
+            state.ctx.library.save_if_dirty()?;
+            serde_json::to_value(books).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "book_get_chapters" | "book_save_progress" => {
+            use chordial_core::module::music_library::books;
+            use chordial_core::module::music_localSource::chapters;
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let song = state.ctx.library.get_song(song_id).ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+            let song_chapters = match resource::find_song_file_path(&state.ctx.registrar, &song.source_ids) {
+                Some(path) => chapters::read_chapters(&PlatformPath::from(path.as_str()))?,
+                None => Vec::new(),
+            };
+            if name == "book_get_chapters" {
+                return serde_json::to_value(song_chapters).map_err(|e| format!("序列化失败: {}", e));
+            }
+            let position_ms = args["position_ms"].as_u64().ok_or("缺少 position_ms")?;
+            let chapter = books::chapter_at(&song_chapters, position_ms);
+            let progress = state.ctx.library.set_book_progress(song_id, position_ms, chapter)?;
+            state.ctx.library.save()?;
+            serde_json::to_value(progress).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "book_get_progress" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            Ok(json!(state.ctx.library.get_book_progress(song_id)))
+        }
+        "book_clear_progress" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            state.ctx.library.clear_book_progress(song_id);
+            state.ctx.library.save()?;
+            Ok(Value::Null)
+        }
+
+        // Playlists
+        "playlist_get_tree" => {
+            serde_json::to_value(state.ctx.library.playlist_tree()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "create_playlist" | "playlist_create_folder" => {
+            let name_arg = args["name"].as_str().ok_or("缺少 name")?;
+            let result = if name == "create_playlist" {
+                let playlist = state.ctx.library.create_playlist(name_arg, args["folder_id"].as_str())?;
+                serde_json::to_value(playlist)
+            } else {
+                let folder = state.ctx.library.create_playlist_folder(name_arg, args["parent_id"].as_str())?;
+                serde_json::to_value(folder)
+            };
+            state.ctx.library.save()?;
+            result.map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playlist_rename" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let new_name = args["name"].as_str().ok_or("缺少 name")?;
+            state.ctx.library.rename_playlist(id, new_name)?;
+            state.ctx.library.save()?;
+            Ok(Value::Null)
+        }
+        "remove_playlist" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            state.ctx.library.remove_playlist(id)?;
+            state.ctx.library.save()?;
+            Ok(Value::Null)
+        }
+        "playlist_move" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let index = args["index"].as_u64().map(|i| i as usize);
+            state.ctx.library.move_playlist(id, args["folder_id"].as_str(), index)?;
+            state.ctx.library.save()?;
+            Ok(Value::Null)
+        }
+        "playlist_reorder" => {
+            let ids = parse_ids(args, "ids")?;
+            state.ctx.library.reorder_playlists(args["folder_id"].as_str(), &ids)?;
+            state.ctx.library.save()?;
+            Ok(Value::Null)
+        }
+        "playlist_get_songs" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let songs = state.ctx.library.localize_songs(state.ctx.library.get_playlist_songs(id)?);
+            serde_json::to_value(songs).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "reorder_playlist" => {
+            let playlist_id = args["playlist_id"].as_str().ok_or("缺少 playlist_id")?;
+            let track_ids = parse_ids(args, "track_ids")?;
+            let playlist = state.ctx.library.set_playlist_songs(playlist_id, track_ids)?;
+            state.ctx.library.save()?;
+            serde_json::to_value(playlist).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playlist_create_smart" | "playlist_set_rules" => {
+            let rules = parse_smart_rules(args)?;
+            let playlist = if name == "playlist_create_smart" {
+                let name_arg = args["name"].as_str().ok_or("缺少 name")?;
+                state.ctx.library.create_smart_playlist(name_arg, args["folder_id"].as_str(), rules)?
+            } else {
+                let id = args["id"].as_str().ok_or("缺少 id")?;
+                state.ctx.library.set_smart_playlist_rules(id, rules)?
+            };
+            state.ctx.library.save()?;
+            serde_json::to_value(playlist).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playlist_preview_smart" => {
+            let mut preview = state.ctx.library.preview_smart_playlist(&parse_smart_rules(args)?)?;
+            preview.songs = state.ctx.library.localize_songs(preview.songs);
+            serde_json::to_value(preview).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playlist_materialize" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let playlist = state.ctx.library.materialize_smart_playlist(id, args["name"].as_str())?;
+            state.ctx.library.save()?;
+            serde_json::to_value(playlist).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "export_playlist" => {
+            let id = args["id"].as_str().ok_or("缺少 id")?;
+            let path = args["path"].as_str().ok_or("缺少 path")?;
+            let exported = state.ctx.library.export_playlist(id)?;
+            chordial_core::module::music_library::playlist_export::write_file(&exported, std::path::Path::new(path))?;
+            Ok(Value::Null)
+        }
+        "import_playlist" => {
+            use chordial_core::module::music_library::playlist_export;
+            let path = args["path"].as_str().ok_or("缺少 path")?;
+            let exported = playlist_export::read_file(std::path::Path::new(path))?;
+            let result = state.ctx.library.import_playlist_with_matching(&exported, args["folder_id"].as_str())?;
+            state.ctx.library.save()?;
+            serde_json::to_value(result).map_err(|e| format!("序列化失败: {}", e))
+        }
+
+        // Library snapshots
+        "library_get_recovery_report" => Ok(json!(state.ctx.library.recovery_report())),
+        "library_list_snapshots" => Ok(json!(state.ctx.library.list_snapshots())),
+        "library_set_snapshot_retain" => {
+            use chordial_core::module::storage::snapshot;
+            let retain = args["retain"].as_u64().ok_or("缺少 retain")? as usize;
+            state.ctx.library.set_snapshot_retain(retain)?;
+            state.ctx.config.set(snapshot::RETAIN_CONFIG_KEY, &retain.max(1))?;
+            Ok(json!(state.ctx.library.list_snapshots()))
+        }
+
+        // Track gain
+        "set_track_gain_db" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let gain_db = args["gain_db"].as_f64().map(|g| g as f32);
+            let applied = state.ctx.library.set_track_gain_db(song_id, gain_db)?;
+            state.ctx.library.save()?;
+            serde_json::to_value(applied).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_track_gain" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            serde_json::to_value(state.ctx.library.track_gain(song_id)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "playback_gain_preview" => {
+            use chordial_core::module::playback::gain;
+            let song_ids = parse_ids(args, "song_ids")?;
+            let count = args["count"].as_u64().map_or(gain::PREVIEW_DEFAULT_COUNT, |c| c as usize);
+            Ok(json!(state.ctx.library.preview_gains(&song_ids, count)))
+        }
+
+        // Power policy
+        "set_power_policy" => {
+            let policy = serde_json::from_value(args.get("policy").cloned().ok_or("缺少 policy")?)
+                .map_err(|e| format!("无效的 policy: {}", e))?;
+            serde_json::to_value(state.ctx.power.set_policy(policy)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "power_get_status" => serde_json::to_value(state.ctx.power.status()).map_err(|e| format!("序列化失败: {}", e)),
+        "app_get_diagnostics" => Ok(json!({
+            "power": state.ctx.power.status(),
+            "library": {
+                "songs": state.ctx.library.song_count(),
+                "snapshots": state.ctx.library.list_snapshots().len(),
+                "recovered_at_startup": state.ctx.library.recovery_report().is_some(),
+            },
+            "local": {
+                "folder_count": state.ctx.local_source.folder_manager.count(),
+                "indexed_files": state.ctx.local_source.file_index.read().len(),
+                "quarantined_files": state.ctx.local_source.quarantine.list().len(),
+            },
+        })),
+
+        // Totals
+        "library_get_album_totals" => {
+            let album_id = args["album_id"].as_str().ok_or("缺少 album_id")?;
+            serde_json::to_value(state.ctx.library.album_totals(album_id))
+                .map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_get_artist_totals" => {
+            let artist_id = args["artist_id"].as_str().ok_or("缺少 artist_id")?;
+            serde_json::to_value(state.ctx.library.artist_totals(artist_id))
+                .map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_rebuild_totals" => {
+            state.ctx.library.rebuild_totals()?;
+            Ok(Value::Null)
+        }
+
+        // Track versions
+        "get_track_versions" => {
+            let id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let compare_audio = args["compare_audio"].as_bool().unwrap_or(true);
+            let versions = state.ctx.library.track_versions(id, |song| {
+                if !compare_audio {
+                    return None;
+                }
+                let path = resource::find_song_file_path(&state.ctx.registrar, &song.source_ids)?;
+                let input = chordial_core::module::analysis::AudioInput::Local(path);
+                state.ctx.analysis.fingerprint(&song.id, &input).ok()
+            })?;
+            serde_json::to_value(versions).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "link_track_versions" | "unlink_track_versions" => {
+            let id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            let other_id = args["other_id"].as_str().ok_or("缺少 other_id")?;
+            state
+                .ctx
+                .library
+                .set_track_version_link(id, other_id, name == "link_track_versions")?;
+            state.ctx.library.save()?;
+            Ok(Value::Null)
+        }
+
+        // Album art
+        "export_album_art" => {
+            let album_id = args["album_id"].as_str().ok_or("缺少 album_id")?;
+            let path = args["path"].as_str().ok_or("缺少 path")?;
+            let options: ExportOptions =
+                serde_json::from_value(args.clone()).map_err(|e| format!("无效的导出选项: {}", e))?;
+            let export = artwork::export_album_art(
+                &state.ctx.registrar,
+                &state.ctx.library,
+                album_id,
+                std::path::Path::new(path),
+                options,
+            )?;
+            serde_json::to_value(export).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "get_album_thumbnail" => {
+            let album_id = args["album_id"].as_str().ok_or("缺少 album_id")?;
+            let size = args.get("size").and_then(|v| v.as_u64()).map(|n| n as u32);
+            let data = state.ctx.album_thumbnail(album_id, size)?;
+            use base64::Engine;
+            Ok(json!(base64::engine::general_purpose::STANDARD.encode(&data)))
+        }
+        "get_track_preview" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let start_secs = args.get("start_secs").and_then(|v| v.as_f64());
+            let duration_secs = args.get("duration_secs").and_then(|v| v.as_f64());
+            let data = state.ctx.track_preview(song_id, start_secs, duration_secs)?;
+            use base64::Engine;
+            Ok(json!(base64::engine::general_purpose::STANDARD.encode(&data)))
+        }
+        "prewarm_covers" => {
+            let album_ids: Vec<String> = serde_json::from_value(args["album_ids"].clone())
+                .map_err(|e| format!("解析 album_ids 失败: {}", e))?;
+            let size = args.get("size").and_then(|v| v.as_u64()).map(|n| n as u32);
+            Ok(json!(state.ctx.prewarm_covers(album_ids, size)?))
+        }
+
+        // Readiness
+        "app_get_readiness" => {
+            serde_json::to_value(state.ctx.readiness.status()).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "app_get_startup_mode" => Ok(json!({ "safe_mode": state.ctx.safe_mode })),
+
+        // Content hash
+        "library_hash_songs" => {
+            let task_id = args.get("task_id").and_then(|v| v.as_str()).unwrap_or("content_hash_scan");
+            let song_ids = match args.get("song_ids") {
+                Some(v) if !v.is_null() => Some(parse_ids(args, "song_ids")?),
+                _ => None,
+            };
+            let job = state.ctx.spawn_content_hash_scan(task_id, song_ids)?;
+            serde_json::to_value(job).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_verify_content_hash" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            serde_json::to_value(state.ctx.verify_content_hash(song_id)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "library_find_exact_duplicates" => {
+            let groups: Vec<_> = state.ctx.library.exact_duplicates().into_iter()
+                .map(|group| state.ctx.library.localize_songs(group))
+                .collect();
+            serde_json::to_value(groups).map_err(|e| format!("序列化失败: {}", e))
+        }
+        "content_hash_set_enabled" => {
+            let enabled = args["enabled"].as_bool().ok_or("缺少 enabled")?;
+            state.ctx.config.set(content_hash::ENABLED_CONFIG_KEY, &enabled)?;
+            state.ctx.analysis.set_content_hash_keys(enabled);
+            Ok(Value::Null)
+        }
+        "content_hash_get_enabled" => {
+            Ok(json!(state.ctx.config.get::<bool>(content_hash::ENABLED_CONFIG_KEY).unwrap_or(false)))
+        }
+
+        // Decode stats
+        "get_decode_stats" => {
+            let song_id = args["song_id"].as_str().ok_or("缺少 song_id")?;
+            let report: Option<PlayerReport> = match args.get("report") {
+                Some(v) if !v.is_null() => {
+                    Some(serde_json::from_value(v.clone()).map_err(|e| format!("无效的 report: {}", e))?)
+                }
+                _ => None,
+            };
+            serde_json::to_value(state.ctx.decode_stats(song_id, report.as_ref())?).map_err(|e| format!("序列化失败: {}", e))
+        }
+
+        // Now playing
+        "get_now_playing_bundle" => {
+            let track_id = args["track_id"].as_str().ok_or("缺少 track_id")?;
+            serde_json::to_value(state.ctx.now_playing_bundle(track_id)?).map_err(|e| format!("序列化失败: {}", e))
+        }
+
         _ => Err(format!("未知命令: {}", name)),
     }
 }
 
+fn parse_ids(args: &Value, key: &str) -> Result<Vec<String>, String> {
+    let value = args.get(key).cloned().ok_or_else(|| format!("缺少 {}", key))?;
+    serde_json::from_value(value).map_err(|e| format!("无效的 {}: {}", key, e))
+}
+
+fn parse_smart_rules(args: &Value) -> Result<SmartRules, String> {
+    let value = args.get("rules").cloned().ok_or("缺少 rules")?;
+    serde_json::from_value(value).map_err(|e| format!("无效的 rules: {}", e))
+}
+
 fn parse_ttl(args: &Value) -> Result<Ttl, String> {
     match args.get("ttl") {
         Some(Value::String(s)) => match s.as_str() {
@@ -405,6 +1665,21 @@ fn parse_ttl(args: &Value) -> Result<Ttl, String> {
 }
 
 /// 将字符串解析为 `EntityType`（大小写不敏感）。
+/// 可选的 `group_by` 参数；缺省时由音乐库按设置分组。
+fn parse_group_by(args: &Value) -> Result<Option<GroupBy>, String> {
+    match args.get("group_by") {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => serde_json::from_value(v.clone()).map(Some).map_err(|e| format!("group_by 无效: {}", e)),
+    }
+}
+
+fn parse_quality_filter(args: &Value) -> Result<Option<QualityFilter>, String> {
+    match args.get("filter") {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => serde_json::from_value(v.clone()).map(Some).map_err(|e| format!("filter 无效: {}", e)),
+    }
+}
+
 fn parse_entity_type(s: &str) -> Result<EntityType, String> {
     match s.to_lowercase().as_str() {
         "song" => Ok(EntityType::Song),
@@ -414,3 +1689,17 @@ fn parse_entity_type(s: &str) -> Result<EntityType, String> {
         other => Err(format!("未知实体类型 '{}'（支持: song/artist/album/lyric）", other)),
     }
 }
+
+/// 从 `args.content_type` 解析内容类型（缺省为 `music`）。
+fn parse_content_type(args: &Value) -> Result<ContentType, String> {
+    match args.get("content_type") {
+        Some(v) => serde_json::from_value(v.clone()).map_err(|e| format!("无效的 content_type: {}", e)),
+        None => Ok(ContentType::Music),
+    }
+}
+
+/// 从 `args.target` 解析补全目标（`album` / `artist`）。
+fn parse_enrich_target(args: &Value) -> Result<EnrichTarget, String> {
+    serde_json::from_value(args.get("target").cloned().ok_or("缺少 target")?)
+        .map_err(|e| format!("无效的 target: {}", e))
+}
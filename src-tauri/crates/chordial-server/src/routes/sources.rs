@@ -71,7 +71,7 @@ async fn local_add_folder(
     let folder_path = PlatformPath::from(body.path.as_str());
     source.folder_manager.add_folder(&folder_path)?;
 
-    let files = music_localSource::folder::collect_audio_files(&folder_path);
+    let files = source.folder_manager.collect_audio_files(&folder_path);
     let mut indexed = 0u64;
     let mut errors = Vec::new();
 
@@ -101,7 +101,7 @@ async fn local_remove_folder(
     let source = &state.ctx.local_source;
     let folder_path = PlatformPath::from(body.path.as_str());
 
-    let files = music_localSource::folder::collect_audio_files(&folder_path);
+    let files = source.folder_manager.collect_audio_files(&folder_path);
     let entity_ids: HashSet<String> = files
         .iter()
         .map(|f| {
@@ -136,7 +136,7 @@ async fn local_rescan(State(state): State<AppState>) -> Result<Json<serde_json::
     let mut total = 0u64;
 
     for folder in &folders {
-        let files = music_localSource::folder::collect_audio_files(folder);
+        let files = source.folder_manager.collect_audio_files(folder);
         for file in &files {
             match source.index_file(file) {
                 Ok(true) => total += 1,
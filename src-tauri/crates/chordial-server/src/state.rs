@@ -1,5 +1,7 @@
 //! axum 共享状态 — 持有对 core [`AppContext`] 的引用。
 
+use axum::http::StatusCode;
+use chordial_core::module::readiness::{self, Subsystem};
 use chordial_core::AppContext;
 use std::sync::Arc;
 
@@ -7,3 +9,16 @@ use std::sync::Arc;
 pub struct AppState {
     pub ctx: Arc<AppContext>,
 }
+
+impl AppState {
+    /// 在阻塞线程池中等待子系统完成启动预热，不占用异步工作线程。
+    ///
+    /// 超时返回 `503` 与 `not_ready:<子系统>` 错误。
+    pub async fn wait_ready(&self, subsystem: Subsystem) -> Result<(), (StatusCode, String)> {
+        let ctx = self.ctx.clone();
+        tokio::task::spawn_blocking(move || ctx.readiness.wait(subsystem, readiness::DEFAULT_WAIT))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("等待启动预热失败: {}", e)))?
+            .map_err(|msg| (StatusCode::SERVICE_UNAVAILABLE, msg))
+    }
+}
@@ -6,22 +6,27 @@
 //! 这正是「库调用形式」的 front 层：前端 `invoke` → 本层 → core 同步函数调用，
 //! 全程进程内，无网络开销。
 
-use chordial_core::module::music_localSource;
+use chordial_core::module::events::AppEvent;
+use chordial_core::module::music_library::grouping::{GroupBy, GROUP_BY_CONFIG_KEY};
+use chordial_core::module::music_library::localize::DISPLAY_LANGUAGE_CONFIG_KEY;
+use chordial_core::module::music_library::quality::QualityFilter;
+use chordial_core::module::music_library::search::{RankedResults, SearchOptions, DEFAULT_RANKED_LIMIT};
+use chordial_core::module::music_library::{relations, songs};
+use chordial_core::module::music_localSource::quarantine::QuarantineEntry;
+use chordial_core::module::music_localSource::scan_job::ScanStatus;
+use chordial_core::module::music_localSource::scanner::{self, BatchReadOptions, ScanOptions};
 use chordial_core::module::music_source::resource;
 use chordial_core::module::music_source::types::{EntityType, SourceId};
 use chordial_core::module::platform::{self, PlatformPath};
+use chordial_core::module::readiness::{self, ReadinessStatus, Subsystem};
+use chordial_core::module::safe_mode::StartupMode;
 use chordial_core::module::storage::entry::Ttl;
 use chordial_core::AppContext;
 use serde::Deserialize;
 use serde_json::Value;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
-
-/// 库变更事件名 — 任何改变库内容的操作（添加/移除源、扫描、CRUD）都应 emit。
-///
-/// 前端通过 `listen("library-changed")` 订阅，触发专辑/艺人列表刷新。
-const LIBRARY_CHANGED_EVENT: &str = "library-changed";
+use tauri::State;
 
 // ══════════════════════════════════════════════════════════════════════════════
 // TTL 参数辅助类型
@@ -343,22 +348,16 @@ pub fn local_stats(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value,
 #[tauri::command]
 pub fn local_add_folder(
     ctx: State<'_, Arc<AppContext>>,
-    app: AppHandle,
     path: String,
 ) -> Result<serde_json::Value, String> {
     let source = &ctx.local_source;
     let folder_path = PlatformPath::from(path.as_str());
     source.folder_manager.add_folder(&folder_path)?;
 
-    // 扫描并索引文件夹中的音频文件（批量：单次加载库 + 并行探测 + 单次写回）
-    let files = music_localSource::folder::collect_audio_files(&folder_path);
-    let (indexed, errors) = source.batch_index_files(&files)?;
-
-    // 持久化音乐库
-    source.library.save()?;
-
-    // 通知前端：库内容已变更，触发专辑/艺人列表刷新
-    let _ = app.emit(LIBRARY_CHANGED_EVENT, ());
+    // 扫描并索引文件夹中的音频文件（批量：单次加载库 + 并行探测 + 单次写回），
+    // 持久化后发布增量变化：前端据此只刷新受影响的专辑 / 歌曲
+    let files = source.folder_manager.collect_audio_files(&folder_path);
+    let ((indexed, errors), diff) = ctx.apply_library_change(|| source.batch_index_files(&files))?;
 
     Ok(serde_json::json!({
         "added": true,
@@ -366,35 +365,31 @@ pub fn local_add_folder(
         "files_found": files.len(),
         "indexed": indexed,
         "errors": errors,
+        "diff": diff,
     }))
 }
 
 #[tauri::command]
 pub fn local_remove_folder(
     ctx: State<'_, Arc<AppContext>>,
-    app: AppHandle,
     path: String,
 ) -> Result<serde_json::Value, String> {
     let source = &ctx.local_source;
     let folder_path = PlatformPath::from(path.as_str());
 
-    // 1. 批量清理该文件夹下所有文件的库引用 + 本地索引（单次库调用）
-    let files = music_localSource::folder::collect_audio_files(&folder_path);
-    let cleaned = source.batch_unindex_files(&files)?;
-
-    // 2. 从文件夹管理器移除
-    let removed = source.folder_manager.remove_folder(&folder_path);
-
-    // 3. 持久化
-    source.library.save()?;
-
-    // 通知前端：库内容已变更，触发专辑/艺人列表刷新
-    let _ = app.emit(LIBRARY_CHANGED_EVENT, ());
+    let files = source.folder_manager.collect_audio_files(&folder_path);
+    let ((cleaned, removed), diff) = ctx.apply_library_change(|| {
+        // 1. 批量清理该文件夹下所有文件的库引用 + 本地索引（单次库调用）
+        let cleaned = source.batch_unindex_files(&files)?;
+        // 2. 从文件夹管理器移除
+        Ok((cleaned, source.folder_manager.remove_folder(&folder_path)))
+    })?;
 
     Ok(serde_json::json!({
         "removed": removed,
         "path": path,
         "cleaned_files": cleaned,
+        "diff": diff,
     }))
 }
 
@@ -409,10 +404,12 @@ pub fn local_get_folders(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<String>,
         .collect())
 }
 
+/// 重扫全部文件夹：新文件入库，已索引的文件只在指纹（mtime / 大小 / 头部哈希）变化时重新读取；
+/// `options.force_rescan` 为 true 时重新读取所有文件。
 #[tauri::command]
 pub fn local_rescan(
     ctx: State<'_, Arc<AppContext>>,
-    app: AppHandle,
+    options: Option<ScanOptions>,
 ) -> Result<serde_json::Value, String> {
     let source = &ctx.local_source;
     let folders = source.folder_manager.get_folders();
@@ -420,36 +417,173 @@ pub fn local_rescan(
     // 收集所有文件夹下的音频文件，一次性批量索引
     let mut all_files: Vec<PlatformPath> = Vec::new();
     for folder in &folders {
-        all_files.extend(music_localSource::folder::collect_audio_files(folder));
+        all_files.extend(source.folder_manager.collect_audio_files(folder));
     }
-    let files_found = all_files.len();
-    let (indexed, errors) = source.batch_index_files(&all_files)?;
-    for e in &errors {
+    let (report, diff) =
+        ctx.apply_library_change(|| source.rescan_files(&all_files, options.unwrap_or_default()))?;
+    for e in &report.errors {
         eprintln!("[local_rescan] {}", e);
     }
 
-    source.library.save()?;
+    Ok(serde_json::json!({
+        "indexed": report.indexed,
+        "files_found": report.files_found,
+        "unchanged": report.unchanged,
+        "changed": report.changed,
+        "folders_scanned": folders.len(),
+        "errors": report.errors,
+        "diff": diff,
+    }))
+}
+
+/// 在后台重扫全部文件夹，立即返回扫描状态；已有扫描在运行时返回其状态。
+///
+/// 进度以 `scan://progress` 事件推送（每个文件夹开始时 + 每处理一批文件），结束时推送 `scan://finished`。
+#[tauri::command]
+pub fn start_scan(
+    ctx: State<'_, Arc<AppContext>>,
+    options: Option<ScanOptions>,
+) -> Result<ScanStatus, String> {
+    ctx.start_scan(options.unwrap_or_default())
+}
+
+/// 取消后台扫描（正在读取的文件读完即停，已读取的部分保留在库中），返回是否有扫描在运行。
+#[tauri::command]
+pub fn cancel_scan(ctx: State<'_, Arc<AppContext>>) -> Result<bool, String> {
+    Ok(ctx.cancel_scan())
+}
+
+/// 后台扫描的当前状态（进行中的进度，或最近一次扫描的结果）。
+#[tauri::command]
+pub fn get_scan_status(ctx: State<'_, Arc<AppContext>>) -> Result<ScanStatus, String> {
+    Ok(ctx.scans.status())
+}
 
-    // 通知前端：库内容已变更，触发专辑/艺人列表刷新
-    let _ = app.emit(LIBRARY_CHANGED_EVENT, ());
+/// 列出因反复探测失败而被隔离的文件（扫描时跳过，不进入音乐库）。
+#[tauri::command]
+pub fn get_quarantined_files(
+    ctx: State<'_, Arc<AppContext>>,
+) -> Result<Vec<QuarantineEntry>, String> {
+    Ok(ctx.local_source.quarantine.list())
+}
 
+/// 解除单个文件的隔离并立即重新索引。
+#[tauri::command]
+pub fn local_retry_quarantined(
+    ctx: State<'_, Arc<AppContext>>,
+    path: String,
+) -> Result<serde_json::Value, String> {
+    let indexed = ctx
+        .local_source
+        .retry_quarantined(&PlatformPath::from(path.as_str()))?;
+    if indexed {
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
     Ok(serde_json::json!({
+        "path": path,
         "indexed": indexed,
-        "files_found": files_found,
-        "folders_scanned": folders.len(),
-        "errors": errors,
     }))
 }
 
+/// 批量读取所选文件的元数据（不入库），分批回报进度，可通过 `cancel_task` 取消。
+///
+/// 取消时返回已读取的部分结果，`cancelled` 为 `true`。
+/// 以 async 方式运行在后台线程，避免阻塞取消命令。
+#[tauri::command(async)]
+pub fn local_read_metadata(
+    ctx: State<'_, Arc<AppContext>>,
+    paths: Vec<String>,
+    task_id: String,
+    chunk_size: Option<usize>,
+    chunk_delay_ms: Option<u64>,
+) -> Result<serde_json::Value, String> {
+    let paths: Vec<PlatformPath> = paths.iter().map(|p| PlatformPath::from(p.as_str())).collect();
+    let defaults = BatchReadOptions::default();
+    let options = BatchReadOptions {
+        chunk_size: chunk_size.unwrap_or(defaults.chunk_size),
+        chunk_delay_ms: chunk_delay_ms.unwrap_or(defaults.chunk_delay_ms),
+        max_threads: ctx.power.scan_threads(defaults.max_threads),
+    };
+
+    let token = ctx.tasks.register(&task_id);
+    let outcome = scanner::batch_read_metadata_with_progress(&paths, &ctx.local_source.meta_cache, &token, options, |progress| {
+        ctx.events.publish(AppEvent::MetadataReadProgress {
+            task_id: task_id.clone(),
+            done: progress.done,
+            total: progress.total,
+        });
+    });
+    ctx.tasks.finish(&task_id, &token);
+
+    let results: Vec<serde_json::Value> = outcome
+        .results
+        .into_iter()
+        .map(|(path, result)| match result {
+            Ok(meta) => serde_json::json!({ "path": platform::path_to_string(&path), "meta": meta }),
+            Err(e) => serde_json::json!({ "path": platform::path_to_string(&path), "error": e }),
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "results": results,
+        "cancelled": outcome.cancelled,
+    }))
+}
+
+/// 取消进行中的长任务，返回任务是否存在。
+#[tauri::command]
+pub fn cancel_task(ctx: State<'_, Arc<AppContext>>, task_id: String) -> Result<bool, String> {
+    Ok(ctx.tasks.cancel(&task_id))
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 临时播放 / 启动请求命令 — 不入库直接打开文件 / 文件夹
+// ══════════════════════════════════════════════════════════════════════════════
+
+use crate::launch::{LaunchRequest, PendingLaunch};
+use chordial_core::module::music_localSource::session::{self, SessionPlaylist};
+
+/// 打开一组文件或文件夹，返回临时播放队列（只读元数据，不写入音乐库）。
+#[tauri::command(async)]
+pub fn session_open_files(
+    ctx: State<'_, Arc<AppContext>>,
+    paths: Vec<String>,
+) -> Result<SessionPlaylist, String> {
+    let paths: Vec<PlatformPath> = paths.iter().map(|p| PlatformPath::from(p.as_str())).collect();
+    Ok(session::open_files(&ctx.local_source, &paths))
+}
+
+/// 打开单个文件夹作为临时播放队列。
+#[tauri::command(async)]
+pub fn session_play_folder(
+    ctx: State<'_, Arc<AppContext>>,
+    path: String,
+) -> Result<SessionPlaylist, String> {
+    Ok(session::play_folder(&ctx.local_source, &PlatformPath::from(path.as_str())))
+}
+
+/// 取走启动时由操作系统传入的请求（文件关联 / `chordial://` 深链接）。
+///
+/// 只返回一次；应用运行中收到的请求改由 `launch-request` 事件推送。
+#[tauri::command]
+pub fn launch_take_requests(pending: State<'_, PendingLaunch>) -> Result<Vec<LaunchRequest>, String> {
+    Ok(pending.take())
+}
+
 // ══════════════════════════════════════════════════════════════════════════════
 // MusicLibrary 命令 — 音乐库 CRUD / 搜索 / 关系查询
 // ══════════════════════════════════════════════════════════════════════════════
 
+/// 列表 / 搜索类命令先等音乐库完成启动预热，避免返回缺少新文件的结果；
+/// 超时返回 `not_ready:library` 错误。
+fn wait_library(ctx: &AppContext) -> Result<(), String> {
+    ctx.readiness.wait(Subsystem::Library, readiness::DEFAULT_WAIT)
+}
+
 // ── 持久化 ──────────────────────────────────────────
 
 #[tauri::command]
 pub fn library_save(ctx: State<'_, Arc<AppContext>>) -> Result<(), String> {
-    ctx.library.save()
+    ctx.library.flush()
 }
 
 #[tauri::command]
@@ -461,8 +595,11 @@ pub fn library_cleanup_empty_entities(ctx: State<'_, Arc<AppContext>>) -> Result
 // ── Song ────────────────────────────────────────────
 
 #[tauri::command]
-pub fn library_song_count(ctx: State<'_, Arc<AppContext>>) -> Result<usize, String> {
-    Ok(ctx.library.song_count())
+pub fn library_song_count(
+    ctx: State<'_, Arc<AppContext>>,
+    filter: Option<QualityFilter>,
+) -> Result<usize, String> {
+    Ok(ctx.library.song_count_filtered(filter.as_ref()))
 }
 
 #[tauri::command]
@@ -471,36 +608,92 @@ pub fn library_get_song(ctx: State<'_, Arc<AppContext>>, id: String) -> Result<s
         .library
         .get_song(&id)
         .ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+    let song = ctx.library.localize_song(song);
     serde_json::to_value(&song).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_get_all_songs(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
-    let songs = ctx.library.get_all_songs();
+    wait_library(&ctx)?;
+    let hidden = ctx.library.hidden_set();
+    let songs: std::collections::HashMap<_, _> = ctx
+        .library
+        .get_all_songs()
+        .into_iter()
+        .filter(|(_, song)| !hidden.hides_song(song))
+        .map(|(id, song)| (id, ctx.library.localize_song(song)))
+        .collect();
     serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_get_songs_page(
     ctx: State<'_, Arc<AppContext>>,
     offset: usize,
     limit: usize,
+    filter: Option<QualityFilter>,
 ) -> Result<serde_json::Value, String> {
-    let songs = ctx.library.get_songs_page(offset, limit);
+    wait_library(&ctx)?;
+    let page = ctx.library.get_songs_page_filtered(offset, limit, filter.as_ref());
+    let songs = ctx.library.localize_songs(page);
     serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_search_songs(ctx: State<'_, Arc<AppContext>>, query: String) -> Result<serde_json::Value, String> {
-    let songs = ctx.library.search_songs(&query);
+    wait_library(&ctx)?;
+    let songs = ctx.library.localize_songs(ctx.library.search_songs(&query));
     serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 设置显示语言偏好（如 `ja`、`ja-Latn`、`en`；`null` 恢复按标签原文显示）。
+///
+/// 之后所有歌曲查询命令都会按该偏好返回标题 / 艺人名。
+#[tauri::command]
+pub fn library_set_display_language(
+    ctx: State<'_, Arc<AppContext>>,
+    language: Option<String>,
+) -> Result<(), String> {
+    // 空白值视为清除偏好，避免把 "" 写进配置
+    let language = language.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    match &language {
+        Some(lang) => ctx.config.set(DISPLAY_LANGUAGE_CONFIG_KEY, lang)?,
+        None => {
+            ctx.config.remove(DISPLAY_LANGUAGE_CONFIG_KEY);
+        }
+    }
+    ctx.library.set_display_language(language);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn library_get_display_language(ctx: State<'_, Arc<AppContext>>) -> Result<Option<String>, String> {
+    Ok(ctx.library.display_language())
+}
+
 // ── Artist ──────────────────────────────────────────
 
+/// 设置艺人列表的默认分组方式（`artist` / `composer`），持久化到配置。
+///
+/// 艺人列表类命令未传 `group_by` 时按此分组；作曲者条目的详情查询不受影响。
+#[tauri::command]
+pub fn library_set_group_by(ctx: State<'_, Arc<AppContext>>, group_by: GroupBy) -> Result<(), String> {
+    ctx.config.set(GROUP_BY_CONFIG_KEY, &group_by)?;
+    ctx.library.set_group_by(group_by);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn library_get_group_by(ctx: State<'_, Arc<AppContext>>) -> Result<GroupBy, String> {
+    Ok(ctx.library.group_by())
+}
+
 #[tauri::command]
-pub fn library_artist_count(ctx: State<'_, Arc<AppContext>>) -> Result<usize, String> {
-    Ok(ctx.library.artist_count())
+pub fn library_artist_count(
+    ctx: State<'_, Arc<AppContext>>,
+    group_by: Option<GroupBy>,
+) -> Result<usize, String> {
+    Ok(ctx.library.artist_count_grouped(group_by))
 }
 
 #[tauri::command]
@@ -512,28 +705,59 @@ pub fn library_get_artist(ctx: State<'_, Arc<AppContext>>, id: String) -> Result
     serde_json::to_value(&artist).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
-pub fn library_get_all_artists(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
-    let artists = ctx.library.get_all_artists();
+#[tauri::command(async)]
+pub fn library_get_all_artists(
+    ctx: State<'_, Arc<AppContext>>,
+    group_by: Option<GroupBy>,
+) -> Result<serde_json::Value, String> {
+    wait_library(&ctx)?;
+    let artists = ctx.library.get_all_artists_grouped(group_by);
     serde_json::to_value(&artists).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_get_artists_page(
     ctx: State<'_, Arc<AppContext>>,
     offset: usize,
     limit: usize,
+    group_by: Option<GroupBy>,
 ) -> Result<serde_json::Value, String> {
-    let artists = ctx.library.get_artists_page(offset, limit);
+    wait_library(&ctx)?;
+    let artists = ctx.library.get_artists_page_grouped(group_by, offset, limit);
     serde_json::to_value(&artists).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_search_artists(ctx: State<'_, Arc<AppContext>>, query: String) -> Result<serde_json::Value, String> {
+    wait_library(&ctx)?;
     let artists = ctx.library.search_artists(&query);
     serde_json::to_value(&artists).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 手动编辑艺人简介；传 `null` 或空串清除。
+#[tauri::command]
+pub fn set_artist_bio(
+    ctx: State<'_, Arc<AppContext>>,
+    id: String,
+    bio: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let artist = ctx.library.set_artist_bio(&id, bio)?;
+    ctx.events.publish(AppEvent::LibraryChanged);
+    serde_json::to_value(&artist).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 手动编辑艺人流派（整体替换）。
+#[tauri::command]
+pub fn set_artist_genres(
+    ctx: State<'_, Arc<AppContext>>,
+    id: String,
+    genres: Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let artist = ctx.library.set_artist_genres(&id, genres)?;
+    ctx.events.publish(AppEvent::LibraryChanged);
+    serde_json::to_value(&artist).map_err(|e| format!("序列化失败: {}", e))
+}
+
 // ── Album ───────────────────────────────────────────
 
 #[tauri::command]
@@ -550,9 +774,12 @@ pub fn library_get_album(ctx: State<'_, Arc<AppContext>>, id: String) -> Result<
     serde_json::to_value(&album).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_get_all_albums(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
-    let albums = ctx.library.get_all_albums();
+    wait_library(&ctx)?;
+    let hidden = ctx.library.hidden_set();
+    let mut albums = ctx.library.get_all_albums();
+    albums.retain(|id, _| !hidden.hides_album(id));
     serde_json::to_value(&albums).map_err(|e| format!("序列化失败: {}", e))
 }
 
@@ -570,16 +797,24 @@ pub fn library_get_albums_by_ids(
     serde_json::to_value(&albums).map_err(|e| format!("序列化失败: {}", e))
 }
 
-/// 批量按 ID 获取歌曲。
+/// 批量按 ID 分页获取歌曲，结果顺序与 `ids` 一致，重复 ID 只返回一次。
 ///
-/// 用于替代 N 次 `library_get_song` 的 N 次 IPC 开销。
+/// 用于替代 N 次 `library_get_song` 的 N 次 IPC 开销。每页最多
+/// [`songs::MAX_IDS_PER_PAGE`] 首，`next_offset` 非空时以它为 `offset` 继续请求。
 #[tauri::command]
 pub fn library_get_songs_by_ids(
     ctx: State<'_, Arc<AppContext>>,
     ids: Vec<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
 ) -> Result<serde_json::Value, String> {
-    let songs = ctx.library.get_songs_by_ids(&ids);
-    serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
+    let mut page = ctx.library.get_songs_page_by_ids(
+        &ids,
+        offset.unwrap_or(0),
+        limit.unwrap_or(songs::MAX_IDS_PER_PAGE),
+    );
+    page.songs = ctx.library.localize_songs(page.songs);
+    serde_json::to_value(&page).map_err(|e| format!("序列化失败: {}", e))
 }
 
 /// 批量按 ID 获取艺术家。
@@ -595,23 +830,26 @@ pub fn library_get_artists_by_ids(
     serde_json::to_value(&artists).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_get_albums_page(
     ctx: State<'_, Arc<AppContext>>,
     offset: usize,
     limit: usize,
 ) -> Result<serde_json::Value, String> {
+    wait_library(&ctx)?;
     let albums = ctx.library.get_albums_page(offset, limit);
     serde_json::to_value(&albums).map_err(|e| format!("序列化失败: {}", e))
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_get_home_stats(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
+    wait_library(&ctx)?;
     Ok(ctx.library.get_home_stats())
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_search_albums(ctx: State<'_, Arc<AppContext>>, query: String) -> Result<serde_json::Value, String> {
+    wait_library(&ctx)?;
     let albums = ctx.library.search_albums(&query);
     serde_json::to_value(&albums).map_err(|e| format!("序列化失败: {}", e))
 }
@@ -623,22 +861,57 @@ pub fn library_search_albums(ctx: State<'_, Arc<AppContext>>, query: String) ->
 /// - `entity_type`：可选，限定实体类型（"song" / "artist" / "album"）
 /// - `source_name`：可选，限定来源名称
 /// - `limit_per_type`：可选，每类实体最多返回多少条
-#[tauri::command]
+#[tauri::command(async)]
 pub fn library_search(
     ctx: State<'_, Arc<AppContext>>,
     query: String,
     entity_type: Option<String>,
     source_name: Option<String>,
     limit_per_type: Option<usize>,
+    filter: Option<QualityFilter>,
 ) -> Result<serde_json::Value, String> {
+    wait_library(&ctx)?;
     let et = entity_type
         .as_deref()
         .map(parse_entity_type)
         .transpose()?;
-    let results = ctx.library.search(&query, et, source_name.as_deref(), limit_per_type);
+    let mut results = ctx.library.search(&query, et, source_name.as_deref(), limit_per_type);
+    let songs = ctx.library.filter_songs(results.songs, filter.as_ref());
+    results.songs = ctx.library.localize_songs(songs);
     serde_json::to_value(&results).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 即时搜索 — 对歌曲 / 艺人 / 专辑按匹配程度打分排序，可选模糊匹配与歌词全文。
+///
+/// 参数：
+/// - `types`：可选，限定实体类型（"song" / "artist" / "album" / "lyric"），省略时搜前三类
+/// - `lyrics`：是否同时搜索歌词全文（默认否）
+/// - `fuzzy`：是否补充拼写相近的结果（默认是）
+/// - `limit`：每类最多返回多少条（默认 20）
+/// - `filter`：歌曲与歌词命中的音质 / 语言条件
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn search_library(
+    ctx: State<'_, Arc<AppContext>>,
+    query: String,
+    types: Option<Vec<String>>,
+    source_name: Option<String>,
+    lyrics: Option<bool>,
+    fuzzy: Option<bool>,
+    limit: Option<usize>,
+    filter: Option<QualityFilter>,
+) -> Result<RankedResults, String> {
+    wait_library(&ctx)?;
+    let options = SearchOptions {
+        types: types.unwrap_or_default().iter().map(|t| parse_entity_type(t)).collect::<Result<_, _>>()?,
+        source_name,
+        lyrics: lyrics.unwrap_or(false),
+        fuzzy: fuzzy.unwrap_or(true),
+    };
+    let limit = limit.unwrap_or(DEFAULT_RANKED_LIMIT);
+    Ok(ctx.library.search_library(&query, &options, filter.as_ref(), limit))
+}
+
 /// 将字符串解析为 `EntityType`（大小写不敏感）。
 fn parse_entity_type(s: &str) -> Result<EntityType, String> {
     match s.to_lowercase().as_str() {
@@ -711,11 +984,26 @@ pub fn library_get_lyric_of_song(
 pub fn library_get_songs_by_artist(
     ctx: State<'_, Arc<AppContext>>,
     artist_id: String,
+    filter: Option<QualityFilter>,
 ) -> Result<serde_json::Value, String> {
-    let songs = ctx.library.get_songs_by_artist(&artist_id);
+    let songs = ctx.library.filter_songs(ctx.library.get_songs_by_artist(&artist_id), filter.as_ref());
+    let songs = ctx.library.localize_songs(songs);
     serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 只看无损：编码为 FLAC / ALAC / WAV 等的歌曲。
+#[tauri::command]
+pub fn library_get_lossless_songs(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
+    let songs = ctx.library.localize_songs(ctx.library.get_lossless_songs());
+    serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 库中出现的演唱语言及歌曲数（按歌曲数降序），供语言筛选列出可选项。
+#[tauri::command]
+pub fn library_get_song_languages(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
+    serde_json::to_value(ctx.library.get_song_languages()).map_err(|e| format!("序列化失败: {}", e))
+}
+
 #[tauri::command]
 pub fn library_get_albums_by_artist(
     ctx: State<'_, Arc<AppContext>>,
@@ -725,12 +1013,26 @@ pub fn library_get_albums_by_artist(
     serde_json::to_value(&albums).map_err(|e| format!("序列化失败: {}", e))
 }
 
+/// 相关艺人：由库内的合作曲目与共同专辑计算，按关系强度降序；`limit` 默认 20。
+#[tauri::command]
+pub fn library_get_related_artists(
+    ctx: State<'_, Arc<AppContext>>,
+    artist_id: String,
+    limit: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let limit = limit.unwrap_or(relations::RELATED_ARTISTS_LIMIT);
+    let related = ctx.library.get_related_artists(&artist_id, limit);
+    serde_json::to_value(&related).map_err(|e| format!("序列化失败: {}", e))
+}
+
 #[tauri::command]
 pub fn library_get_songs_in_album(
     ctx: State<'_, Arc<AppContext>>,
     album_id: String,
+    filter: Option<QualityFilter>,
 ) -> Result<serde_json::Value, String> {
-    let songs = ctx.library.get_songs_in_album(&album_id);
+    let songs = ctx.library.filter_songs(ctx.library.get_songs_in_album(&album_id), filter.as_ref());
+    let songs = ctx.library.localize_songs(songs);
     serde_json::to_value(&songs).map_err(|e| format!("序列化失败: {}", e))
 }
 
@@ -854,3 +1156,2029 @@ pub fn p2p_remove_trusted(
 pub fn p2p_get_match_payload(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
     Ok(ctx.p2p.get_match_payload())
 }
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 播放设置命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::playback::crossfade::DEFAULT_CURVE_SAMPLES;
+use chordial_core::module::playback::render::{self, RenderOptions, RenderSummary, RenderTrack};
+use chordial_core::module::playback::transitions::{self, TransitionRecord, TransitionReport};
+use chordial_core::module::playback::{
+    AudioPosition, ContentType, CrossfadeCancel, CrossfadeKeep, CrossfadePlan, CrossfadeSettings, CrossfadeStatus, DitherMode, DspProfile, FadeAction, FadePlan, FadeSettings, KaraokeSettings, PcmCacheSettings, PlaybackRate, PlaybackTransition,
+    PlaybackSettings, PreloadSettings, SeekPlan, SilenceMap, SilenceSkipSettings, SkipDirection, SkipPlan, StretchParams, TimeStretchQuality, VolumeCurve, VolumeGain, PLAYBACK_RATE_PRESETS,
+};
+
+#[tauri::command]
+pub fn playback_get_settings(ctx: State<'_, Arc<AppContext>>) -> Result<PlaybackSettings, String> {
+    Ok(ctx.playback.settings())
+}
+
+/// 设置变速质量档位（`low_latency` / `balanced` / `high_quality`）。
+#[tauri::command]
+pub fn set_time_stretch_quality(
+    ctx: State<'_, Arc<AppContext>>,
+    quality: TimeStretchQuality,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_time_stretch_quality(quality)
+}
+
+/// 查询给定速度下的变速器参数；原速时返回 `null`（旁路变速器）。
+#[tauri::command]
+pub fn playback_stretch_params(
+    ctx: State<'_, Arc<AppContext>>,
+    speed: f64,
+) -> Result<Option<StretchParams>, String> {
+    Ok(ctx.playback.stretch_params_for(speed))
+}
+
+/// 可选的播放速度预设列表。
+#[tauri::command]
+pub fn playback_rate_presets() -> Result<Vec<f64>, String> {
+    Ok(PLAYBACK_RATE_PRESETS.to_vec())
+}
+
+/// 查询某内容类型（`music` / `podcast` / `audiobook`）记忆的播放速度。
+#[tauri::command]
+pub fn playback_get_rate(
+    ctx: State<'_, Arc<AppContext>>,
+    content_type: ContentType,
+) -> Result<PlaybackRate, String> {
+    Ok(ctx.playback.playback_rate(content_type))
+}
+
+/// 设置当前曲目的播放速度（保持音高），并按内容类型记忆。
+#[tauri::command]
+pub fn set_playback_rate(
+    ctx: State<'_, Arc<AppContext>>,
+    content_type: ContentType,
+    rate: f64,
+) -> Result<PlaybackRate, String> {
+    ctx.playback.set_playback_rate(content_type, rate)
+}
+
+/// 更新静音跳过设置（启用开关 / 最短静音时长 / 力度）。
+#[tauri::command]
+pub fn playback_set_silence_skip(
+    ctx: State<'_, Arc<AppContext>>,
+    silence_skip: SilenceSkipSettings,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_silence_skip(silence_skip)
+}
+
+/// 更新卡拉 OK 辅助设置（启用开关 / 人声区间增益）。人声区间通过 `get_vocal_segments` 获取。
+#[tauri::command]
+pub fn playback_set_karaoke(
+    ctx: State<'_, Arc<AppContext>>,
+    karaoke: KaraokeSettings,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_karaoke(karaoke)
+}
+
+/// 更新远程歌曲预加载设置（开关 / 提前量 / 带宽上限）。
+#[tauri::command]
+pub fn playback_set_preload(
+    ctx: State<'_, Arc<AppContext>>,
+    preload: PreloadSettings,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_preload(preload)
+}
+
+/// 设置音量滑块位置（0.0 ~ 1.0），返回按音量曲线换算后的增益。
+#[tauri::command]
+pub fn set_audio_volume(ctx: State<'_, Arc<AppContext>>, volume: f32) -> Result<VolumeGain, String> {
+    ctx.playback.set_volume(volume)
+}
+
+/// 获取当前音量及增益（启动时恢复音量用）。
+#[tauri::command]
+pub fn get_audio_volume(ctx: State<'_, Arc<AppContext>>) -> Result<VolumeGain, String> {
+    Ok(ctx.playback.volume_gain())
+}
+
+/// 设置音量曲线（`linear` / `logarithmic`）。
+#[tauri::command]
+pub fn playback_set_volume_curve(
+    ctx: State<'_, Arc<AppContext>>,
+    curve: VolumeCurve,
+) -> Result<VolumeGain, String> {
+    ctx.playback.set_volume_curve(curve)
+}
+
+/// 设置降低位深时的抖动方式（`off` / `tpdf` / `noise_shaped`）。
+#[tauri::command]
+pub fn playback_set_dither(
+    ctx: State<'_, Arc<AppContext>>,
+    dither: DitherMode,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_dither(dither)
+}
+
+/// 更新淡入淡出设置（开关 / 播放、停止、拖动各段时长）。
+#[tauri::command]
+pub fn playback_set_fades(
+    ctx: State<'_, Arc<AppContext>>,
+    fades: FadeSettings,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_fades(fades)
+}
+
+/// 查询某个操作（`play` / `stop` / `seek`）的淡入淡出时长，前端混音器据此设置增益包络。
+#[tauri::command]
+pub fn playback_fade_plan(ctx: State<'_, Arc<AppContext>>, action: FadeAction) -> Result<FadePlan, String> {
+    Ok(ctx.playback.fade_plan(action))
+}
+
+/// 更新交叉淡化设置（开关 / 过渡时长 / 短曲目缩短规则 / 增益曲线）。
+#[tauri::command]
+pub fn playback_set_crossfade(
+    ctx: State<'_, Arc<AppContext>>,
+    crossfade: CrossfadeSettings,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_crossfade(crossfade)
+}
+
+/// 设置某播放列表的切歌方式（交叉淡化 / 无缝衔接）。
+#[tauri::command]
+pub fn playback_set_playlist_transition(
+    ctx: State<'_, Arc<AppContext>>,
+    playlist_id: String,
+    transition: PlaybackTransition,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_playlist_transition(&playlist_id, transition)
+}
+
+/// 切到下一首时的过渡时长：前端在当前歌曲剩余 `duration_ms` 时开始播放下一首，为 0 时直接切歌。
+///
+/// 传入当前歌曲时长、剩余时长和下一首时长（毫秒），未知的可省略。
+/// 正在播放播放列表时传入 `playlist_id`；该列表设为无缝衔接时 `transition` 为 `gapless`，
+/// 前端应把下一首排在当前解码流结束的采样上开始。
+#[tauri::command]
+pub fn playback_crossfade_plan(
+    ctx: State<'_, Arc<AppContext>>,
+    playlist_id: Option<String>,
+    current_ms: Option<u64>,
+    remaining_ms: Option<u64>,
+    next_ms: Option<u64>,
+) -> Result<CrossfadePlan, String> {
+    Ok(ctx.playback.crossfade_plan(playlist_id.as_deref(), current_ms, remaining_ms, next_ms))
+}
+
+/// 当前交叉淡化曲线的淡入增益采样表（默认 256 点，首尾对应过渡的开始与结束）。
+///
+/// 前端混音器对淡入一方用 `setValueCurveAtTime` 套用此表，对淡出一方倒序套用。
+#[tauri::command]
+pub fn playback_crossfade_curve(ctx: State<'_, Arc<AppContext>>, samples: Option<usize>) -> Result<Vec<f32>, String> {
+    Ok(ctx.playback.crossfade_curve_table(samples.unwrap_or(DEFAULT_CURVE_SAMPLES)))
+}
+
+/// 前端开始一次交叉淡化时上报，供 [`get_crossfade_status`] / [`cancel_crossfade`] 使用。
+#[tauri::command]
+pub fn playback_crossfade_started(
+    ctx: State<'_, Arc<AppContext>>,
+    from_song_id: String,
+    to_song_id: String,
+    duration_ms: u32,
+) -> Result<(), String> {
+    ctx.crossfade.begin(&from_song_id, &to_song_id, duration_ms);
+    Ok(())
+}
+
+/// 进行中的交叉淡化：是否在过渡、进度百分比、前后两首。
+#[tauri::command]
+pub fn get_crossfade_status(ctx: State<'_, Arc<AppContext>>) -> Result<CrossfadeStatus, String> {
+    Ok(ctx.crossfade_status())
+}
+
+/// 取消进行中的交叉淡化，`keep` 为保留的一首（`current` / `next`）。
+///
+/// 前端混音器收到 `crossfade-cancelled` 事件后停掉另一首；没有进行中的过渡时返回错误。
+#[tauri::command]
+pub fn cancel_crossfade(ctx: State<'_, Arc<AppContext>>, keep: CrossfadeKeep) -> Result<CrossfadeCancel, String> {
+    ctx.cancel_crossfade(keep)
+}
+
+/// 记录一次切歌过渡（过渡结束或被跳过打断时由前端上报）。
+#[tauri::command]
+pub fn record_transition(ctx: State<'_, Arc<AppContext>>, transition: TransitionReport) -> Result<(), String> {
+    ctx.record_transition(transition)
+}
+
+/// 最近的切歌过渡记录，新的在前；`limit` 缺省时返回全部保留的记录。
+#[tauri::command]
+pub fn get_transition_history(
+    ctx: State<'_, Arc<AppContext>>,
+    limit: Option<usize>,
+) -> Result<Vec<TransitionRecord>, String> {
+    Ok(ctx.transitions.recent(limit.unwrap_or(transitions::MAX_RECORDS)))
+}
+
+/// 清空切歌过渡日志。
+#[tauri::command]
+pub fn clear_transition_history(ctx: State<'_, Arc<AppContext>>) -> Result<(), String> {
+    ctx.transitions.clear()
+}
+
+/// 设置某内容类型的快进 / 快退步长（秒）。
+#[tauri::command]
+pub fn playback_set_skip_step(
+    ctx: State<'_, Arc<AppContext>>,
+    content_type: ContentType,
+    secs: u32,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_skip_step(content_type, secs)
+}
+
+/// 快进：从 `position_ms` 向后跳 `secs` 秒（缺省按内容类型的步长，音乐 10 秒 / 播客 30 秒）。
+/// 返回跳转目标与淡入淡出方案，由前端执行 seek；越过结尾时 `reached_end` 为 `true`。
+#[tauri::command]
+pub fn skip_forward(
+    ctx: State<'_, Arc<AppContext>>,
+    content_type: Option<ContentType>,
+    position_ms: f64,
+    duration_ms: Option<f64>,
+    secs: Option<u32>,
+) -> Result<SkipPlan, String> {
+    let content_type = content_type.unwrap_or_default();
+    ctx.playback.skip_plan(content_type, SkipDirection::Forward, position_ms, duration_ms, secs)
+}
+
+/// 快退：从 `position_ms` 向前跳 `secs` 秒，最多回到开头。
+#[tauri::command]
+pub fn skip_backward(
+    ctx: State<'_, Arc<AppContext>>,
+    content_type: Option<ContentType>,
+    position_ms: f64,
+    secs: Option<u32>,
+) -> Result<SkipPlan, String> {
+    let content_type = content_type.unwrap_or_default();
+    ctx.playback.skip_plan(content_type, SkipDirection::Backward, position_ms, None, secs)
+}
+
+/// 跳转到 `target_ms`（拖动进度 / 点击歌词）：返回跳转方案，由前端执行 seek 并在到位后同步进度。
+/// 目标为负或超过 `duration_ms` 时返回错误。
+#[tauri::command]
+pub fn seek_audio(
+    ctx: State<'_, Arc<AppContext>>,
+    target_ms: f64,
+    duration_ms: Option<f64>,
+) -> Result<SeekPlan, String> {
+    ctx.playback.seek_plan(target_ms, duration_ms)
+}
+
+/// 获取歌曲的静音跳过表；未启用静音跳过时返回 `null`。
+#[tauri::command]
+pub fn playback_get_silence_map(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+) -> Result<Option<SilenceMap>, String> {
+    let song = ctx
+        .library
+        .get_song(&song_id)
+        .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+    let path = resource::find_song_file_path(&ctx.registrar, &song.source_ids)
+        .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", song_id))?;
+    Ok(ctx.playback.silence_map(&path)?.map(|m| (*m).clone()))
+}
+
+/// 设置输出设备延迟（毫秒），用于歌词 / 可视化同步补偿。
+#[tauri::command]
+pub fn playback_set_output_latency(
+    ctx: State<'_, Arc<AppContext>>,
+    latency_ms: u32,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_output_latency(latency_ms)
+}
+
+/// 获取某输出设备的 DSP 配置；`device_id` 缺省时取当前输出设备。
+#[tauri::command]
+pub fn playback_get_dsp_profile(
+    ctx: State<'_, Arc<AppContext>>,
+    device_id: Option<String>,
+) -> Result<DspProfile, String> {
+    Ok(ctx.playback.dsp_profile(device_id.as_deref()))
+}
+
+/// 保存某输出设备的 DSP 配置（均衡器 / 声道平衡 / 交叉馈送 / 限幅器）。
+#[tauri::command]
+pub fn playback_set_dsp_profile(
+    ctx: State<'_, Arc<AppContext>>,
+    device_id: String,
+    profile: DspProfile,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_dsp_profile(&device_id, profile)
+}
+
+/// 前端输出设备变化时调用，返回应立即套用的该设备 DSP 配置。
+#[tauri::command]
+pub fn playback_set_output_device(
+    ctx: State<'_, Arc<AppContext>>,
+    device_id: String,
+) -> Result<DspProfile, String> {
+    ctx.playback.set_output_device(&device_id)
+}
+
+/// 把一台设备的 DSP 配置复制到另一台。
+#[tauri::command]
+pub fn playback_copy_dsp_profile(
+    ctx: State<'_, Arc<AppContext>>,
+    from_device: String,
+    to_device: String,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.copy_dsp_profile(&from_device, &to_device)
+}
+
+/// 将播放器上报的位置换算为延迟补偿后的位置（歌词同步使用）。
+#[tauri::command]
+pub fn get_audio_position(
+    ctx: State<'_, Arc<AppContext>>,
+    raw_position_ms: f64,
+    speed: Option<f64>,
+) -> Result<AudioPosition, String> {
+    Ok(ctx
+        .playback
+        .compensate_position(raw_position_ms, speed.unwrap_or(1.0)))
+}
+
+/// 把一组歌曲离线渲染为带交叉淡化的 FLAC 混音，并在同目录生成 `.cue` 分轨表。
+///
+/// `crossfade_ms` 缺省为交叉淡化设置的时长，短曲目按设置缩短过渡；没有本地文件的歌曲被跳过。进度通过 `render-progress`
+/// 事件推送，以 `task_id` 调用 `cancel_task` 可中途停止（已渲染部分照常写出）。
+#[tauri::command(async)]
+pub fn render_mix(
+    ctx: State<'_, Arc<AppContext>>,
+    track_ids: Vec<String>,
+    output_path: String,
+    crossfade_ms: Option<u32>,
+    task_id: String,
+) -> Result<RenderSummary, String> {
+    let queue: Vec<RenderTrack> = track_ids
+        .iter()
+        .filter_map(|id| ctx.library.get_song(id))
+        .filter_map(|song| {
+            let path = resource::find_song_file_path(&ctx.registrar, &song.source_ids)?;
+            Some(RenderTrack {
+                path: PlatformPath::from(path),
+                title: song.title,
+                performer: song.artist_names.join(", "),
+            })
+        })
+        .collect();
+    let settings = ctx.playback.settings();
+    let options = RenderOptions {
+        crossfade_ms: crossfade_ms.unwrap_or(settings.crossfade.duration_ms),
+        dither: settings.dither,
+        short_tracks: settings.crossfade.short_tracks,
+        fade_in_ms: ctx.playback.fade_plan(FadeAction::Play).fade_in_ms,
+        fade_out_ms: ctx.playback.fade_plan(FadeAction::Stop).fade_out_ms,
+        curve: settings.crossfade.curve,
+        custom_curve: settings.crossfade.custom_curve.clone(),
+    };
+
+    let token = ctx.tasks.register(&task_id);
+    let result = render::render_mix(&queue, &PathBuf::from(&output_path), options, &token, |done, total| {
+        ctx.events.publish(AppEvent::RenderProgress {
+            task_id: task_id.clone(),
+            done,
+            total,
+        });
+    });
+    ctx.tasks.finish(&task_id, &token);
+    result
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 音频分析命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::analysis::{
+    AnalysisJob, AudioInput, EnergyProfile, TechnicalInfo, TrackSegments, TranscodeVerdict,
+    VocalMap,
+};
+
+/// 获取歌曲文件的技术信息（编码 / 位深 / 采样率 / 实际码率 / 编码器 / 是否无损 / 其余标签）。
+///
+/// 首次调用读取文件，之后命中缓存，文件修改后自动重新读取。
+#[tauri::command]
+pub fn get_track_technical_info(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+) -> Result<TechnicalInfo, String> {
+    let song = ctx
+        .library
+        .get_song(&track_id)
+        .ok_or_else(|| format!("歌曲 '{}' 不存在", track_id))?;
+    let path = resource::find_song_file_path(&ctx.registrar, &song.source_ids)
+        .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", track_id))?;
+    Ok((*ctx.analysis.technical_info(&path)?).clone())
+}
+
+/// 检测单首歌曲是否为有损转无损（「假无损」），结果持久化。
+#[tauri::command]
+pub fn analyze_track_transcode(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+) -> Result<TranscodeVerdict, String> {
+    let song = ctx
+        .library
+        .get_song(&track_id)
+        .ok_or_else(|| format!("歌曲 '{}' 不存在", track_id))?;
+    let path = resource::find_song_file_path(&ctx.registrar, &song.source_ids)
+        .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", track_id))?;
+    ctx.analysis.transcode_verdict(&track_id, &path)
+}
+
+/// 获取歌曲的前奏 / 尾奏边界（毫秒），供自动混音安排过渡。结果持久化，文件修改后重新分析。
+///
+/// 远程歌曲经媒体缓存拉取后分析，结果按来源键记录，缓存被淘汰后再次查询也不必重新分析。
+#[tauri::command(async)]
+pub fn get_track_segments(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+) -> Result<TrackSegments, String> {
+    let input = ctx.analysis_input(&track_id)?;
+    ctx.analysis.track_segments(&track_id, &input)
+}
+
+/// 获取歌曲的人声区间，供卡拉 OK 辅助和歌词视图的演唱提示使用。结果持久化；远程歌曲同样支持。
+#[tauri::command(async)]
+pub fn get_vocal_segments(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+) -> Result<VocalMap, String> {
+    let input = ctx.analysis_input(&track_id)?;
+    ctx.analysis.vocal_map(&track_id, &input)
+}
+
+/// 获取歌曲的能量 / 可舞性评分（0–100）及能量等级，供界面按等级着色。结果持久化；远程歌曲同样支持。
+#[tauri::command(async)]
+pub fn get_track_energy(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+) -> Result<EnergyProfile, String> {
+    let input = ctx.analysis_input(&track_id)?;
+    ctx.analysis.energy_profile(&track_id, &input)
+}
+
+/// 在后台对整个库做转码检测，立即返回任务句柄。已分析且文件未变化的歌曲直接复用结果。
+///
+/// 进度通过 `analysis-progress` 事件推送，结束时推送 `analysis-finished`；
+/// 以返回的 `task_id` 调用 `cancel_task` 可取消。已有批量任务在运行时返回该任务的句柄。
+#[tauri::command]
+pub fn analyze_library_transcodes(
+    ctx: State<'_, Arc<AppContext>>,
+    task_id: Option<String>,
+) -> Result<AnalysisJob, String> {
+    let songs: Vec<(String, String)> = ctx
+        .library
+        .get_all_songs()
+        .into_values()
+        .filter_map(|song| {
+            resource::find_song_file_path(&ctx.registrar, &song.source_ids)
+                .map(|path| (song.id, path))
+        })
+        .collect();
+    ctx.analysis.spawn_transcode_scan(
+        task_id.as_deref().unwrap_or("transcode_scan"),
+        songs,
+        ctx.tasks.clone(),
+        ctx.events.clone(),
+    )
+}
+
+/// 库筛选：列出疑似转码的歌曲（`{ song, verdict }`），按置信度降序。
+///
+/// `min_confidence` 缺省为 0.5；只包含已分析过的歌曲。
+#[tauri::command]
+pub fn library_get_suspected_transcodes(
+    ctx: State<'_, Arc<AppContext>>,
+    min_confidence: Option<f32>,
+) -> Result<Vec<Value>, String> {
+    let records = ctx
+        .analysis
+        .suspected_transcodes(min_confidence.unwrap_or(0.5));
+    Ok(records
+        .into_iter()
+        .filter_map(|record| {
+            let song = ctx.library.get_song(&record.song_id)?;
+            Some(serde_json::json!({
+                "song": ctx.library.localize_song(song),
+                "verdict": record.verdict,
+            }))
+        })
+        .collect())
+}
+
+/// 在后台为整个库计算能量评分，立即返回任务句柄（进度事件同 [`analyze_library_transcodes`]）。
+#[tauri::command]
+pub fn analyze_library_energy(
+    ctx: State<'_, Arc<AppContext>>,
+    task_id: Option<String>,
+) -> Result<AnalysisJob, String> {
+    let songs: Vec<(String, String)> = ctx
+        .library
+        .get_all_songs()
+        .into_values()
+        .filter_map(|song| {
+            resource::find_song_file_path(&ctx.registrar, &song.source_ids)
+                .map(|path| (song.id, path))
+        })
+        .collect();
+    ctx.analysis.spawn_energy_scan(
+        task_id.as_deref().unwrap_or("energy_scan"),
+        songs,
+        ctx.tasks.clone(),
+        ctx.events.clone(),
+    )
+}
+
+/// 库筛选：按能量区间列出歌曲（`{ song, energy }`），默认按能量升序（相同时按可舞性）。
+///
+/// 区间缺省为 0–100；只包含已分析过的歌曲。运动歌单可取 `min_energy = 70` 降序，
+/// 睡前歌单取 `max_energy = 30`。
+#[tauri::command]
+pub fn library_get_songs_by_energy(
+    ctx: State<'_, Arc<AppContext>>,
+    min_energy: Option<u8>,
+    max_energy: Option<u8>,
+    descending: Option<bool>,
+) -> Result<Vec<Value>, String> {
+    let records = ctx.analysis.songs_by_energy(
+        min_energy.unwrap_or(0),
+        max_energy.unwrap_or(100),
+        descending.unwrap_or(false),
+    );
+    Ok(records
+        .into_iter()
+        .filter_map(|(song_id, profile)| {
+            let song = ctx.library.get_song(&song_id)?;
+            Some(serde_json::json!({
+                "song": ctx.library.localize_song(song),
+                "energy": profile,
+            }))
+        })
+        .collect())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 歌词提供方命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::lyrics::{LyricsCandidate, LyricsProviderStatus, LyricsQuery};
+
+/// 列出所有歌词提供方及其状态（优先级 / 启用 / 限流 / 健康统计）。
+#[tauri::command]
+pub fn lyrics_get_providers(
+    ctx: State<'_, Arc<AppContext>>,
+) -> Result<Vec<LyricsProviderStatus>, String> {
+    Ok(ctx.lyrics.statuses())
+}
+
+#[tauri::command]
+pub fn lyrics_set_provider_enabled(
+    ctx: State<'_, Arc<AppContext>>,
+    name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    ctx.lyrics.set_enabled(&name, enabled)
+}
+
+/// 向所有启用的提供方搜索歌曲的候选歌词。
+#[tauri::command]
+pub fn lyrics_search(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+) -> Result<Vec<LyricsCandidate>, String> {
+    let song = ctx
+        .library
+        .get_song(&song_id)
+        .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+    let path = resource::find_song_file_path(&ctx.registrar, &song.source_ids);
+    Ok(ctx.lyrics.search(&LyricsQuery::for_song(&song, path)))
+}
+
+/// 从指定提供方获取候选歌词的全文。
+#[tauri::command]
+pub fn lyrics_fetch(
+    ctx: State<'_, Arc<AppContext>>,
+    provider: String,
+    id: String,
+) -> Result<String, String> {
+    ctx.lyrics.fetch(&provider, &id)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 元数据补全命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::metadata::{EnrichTarget, FieldProvenance, MetadataProviderInfo};
+
+#[tauri::command]
+pub fn metadata_get_providers(
+    ctx: State<'_, Arc<AppContext>>,
+) -> Result<Vec<MetadataProviderInfo>, String> {
+    Ok(ctx.metadata.providers())
+}
+
+/// 通过提供方链补全专辑 / 艺人字段，返回本次写入的字段及其来源。
+///
+/// `overwrite` 缺省为 `false`（只填充空字段）。
+#[tauri::command]
+pub fn metadata_enrich(
+    ctx: State<'_, Arc<AppContext>>,
+    target: EnrichTarget,
+    id: String,
+    overwrite: Option<bool>,
+) -> Result<Vec<FieldProvenance>, String> {
+    let applied = ctx
+        .metadata
+        .enrich(target, &id, overwrite.unwrap_or(false))?;
+    if !applied.is_empty() {
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(applied)
+}
+
+/// 查看实体各字段的写入来源记录（按写入顺序）。
+#[tauri::command]
+pub fn metadata_get_provenance(
+    ctx: State<'_, Arc<AppContext>>,
+    target: EnrichTarget,
+    id: String,
+) -> Result<Vec<FieldProvenance>, String> {
+    Ok(ctx.metadata.provenance(target, &id))
+}
+
+/// 撤销字段最近一次由提供方写入的值。
+#[tauri::command]
+pub fn metadata_revert_field(
+    ctx: State<'_, Arc<AppContext>>,
+    target: EnrichTarget,
+    id: String,
+    field: String,
+) -> Result<FieldProvenance, String> {
+    let reverted = ctx.metadata.revert(target, &id, &field)?;
+    ctx.events.publish(AppEvent::LibraryChanged);
+    Ok(reverted)
+}
+
+/// 对库中所有专辑 / 艺人执行补全，返回有字段被写入的实体数。
+#[tauri::command]
+pub fn metadata_enrich_all(
+    ctx: State<'_, Arc<AppContext>>,
+    target: EnrichTarget,
+    overwrite: Option<bool>,
+) -> Result<usize, String> {
+    let changed = ctx.metadata.enrich_all(target, overwrite.unwrap_or(false));
+    if changed > 0 {
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(changed)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 元数据编辑 / 重扫冲突命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::edits::{
+    ConflictResolution, FieldValues, MetadataConflict, MetadataField,
+};
+
+/// 修改本地歌曲的元数据（不写回文件），重扫时优先于文件标签；
+/// 歌曲同时存在于可写回的来源时一并同步过去。
+///
+/// `changes` 形如 `{ "title": "新标题", "year": null }`；返回重新索引后的歌曲 ID。
+#[tauri::command]
+pub fn library_edit_song_metadata(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+    changes: FieldValues,
+) -> Result<Option<String>, String> {
+    let stored_id = ctx.local_source.edit_song_metadata(&song_id, &changes)?;
+    ctx.sync_song_edits(std::slice::from_ref(&stored_id), &changes);
+    ctx.events.publish(AppEvent::LibraryChanged);
+    Ok(stored_id)
+}
+
+/// 批量修改多首歌曲的同一组字段；整批记为一条变更，可用 `undo_last_change` 一次撤销。
+#[tauri::command]
+pub fn library_edit_songs_metadata(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Vec<String>,
+    changes: FieldValues,
+) -> Result<Vec<Option<String>>, String> {
+    let stored_ids = ctx.local_source.edit_songs_metadata(&song_ids, &changes)?;
+    ctx.sync_song_edits(&stored_ids, &changes);
+    ctx.events.publish(AppEvent::LibraryChanged);
+    Ok(stored_ids)
+}
+
+/// 把元数据直接写进歌曲文件的标签（ID3v2 / Vorbis comment / MP4），随后更新库。
+///
+/// `fields` 的格式与 `library_edit_song_metadata` 的 `changes` 相同；
+/// 写入的字段不再保留库内编辑。返回重新索引后的歌曲 ID。
+#[tauri::command]
+pub fn write_track_metadata(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+    fields: FieldValues,
+) -> Result<Option<String>, String> {
+    let stored_id = ctx.local_source.write_song_metadata(&track_id, &fields)?;
+    ctx.sync_song_edits(std::slice::from_ref(&stored_id), &fields);
+    ctx.events.publish(AppEvent::LibraryChanged);
+    Ok(stored_id)
+}
+
+/// 列出重扫时文件标签与用户编辑冲突的字段。
+#[tauri::command]
+pub fn get_metadata_conflicts(
+    ctx: State<'_, Arc<AppContext>>,
+) -> Result<Vec<MetadataConflict>, String> {
+    Ok(ctx.library.get_metadata_conflicts())
+}
+
+/// 处理一条冲突：`keep_user` 保留用户值，`use_file` 采用文件值并重新索引。
+#[tauri::command]
+pub fn resolve_metadata_conflict(
+    ctx: State<'_, Arc<AppContext>>,
+    path: String,
+    field: MetadataField,
+    resolution: ConflictResolution,
+) -> Result<bool, String> {
+    let found = ctx
+        .local_source
+        .resolve_metadata_conflict(&path, field, resolution)?;
+    if found && resolution == ConflictResolution::UseFile {
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(found)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 播放统计命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::stats::{PlayStats, WRITE_BACK_CONFIG_KEY};
+
+#[tauri::command]
+pub fn stats_get(ctx: State<'_, Arc<AppContext>>, song_id: String) -> Result<PlayStats, String> {
+    Ok(ctx.library.get_play_stats(&song_id))
+}
+
+/// 设置评分（0–100，`null` 清除）；开启写回时同步写入本地文件的 POPM / FMPS 标签。
+///
+/// 写回失败不影响库内评分，错误信息放在 `write_error` 中返回。
+#[tauri::command]
+pub fn stats_set_rating(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+    rating: Option<u8>,
+) -> Result<serde_json::Value, String> {
+    let stats = ctx.library.set_rating(&song_id, rating)?;
+    ctx.library.save()?;
+    let write_back = ctx.config.get::<bool>(WRITE_BACK_CONFIG_KEY).unwrap_or(false);
+    let write_error = if write_back {
+        ctx.local_source.write_stats_to_file(&song_id).err()
+    } else {
+        None
+    };
+    Ok(serde_json::json!({
+        "stats": stats,
+        "written_to_file": write_back && write_error.is_none(),
+        "write_error": write_error,
+    }))
+}
+
+/// 记录一次播放（播放次数加一）；启用 PCM 缓存时常播的短曲目会在后台解码缓存。
+#[tauri::command]
+pub fn stats_record_play(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+) -> Result<PlayStats, String> {
+    ctx.record_play(&song_id)
+}
+
+/// 设置是否把评分写回音频文件。
+#[tauri::command]
+pub fn stats_set_write_back(ctx: State<'_, Arc<AppContext>>, enabled: bool) -> Result<(), String> {
+    ctx.config.set(WRITE_BACK_CONFIG_KEY, &enabled)
+}
+
+#[tauri::command]
+pub fn stats_get_write_back(ctx: State<'_, Arc<AppContext>>) -> Result<bool, String> {
+    Ok(ctx.config.get::<bool>(WRITE_BACK_CONFIG_KEY).unwrap_or(false))
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 队列预加载 / 媒体缓存命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::playback::PreloadStatus;
+
+/// 同步播放队列：`song_ids` 为接下来要播放的歌曲（不含当前歌曲），
+/// `current_remaining_ms` 为当前歌曲的剩余时长。队列、顺序或播放位置变化时都应重新调用。
+#[tauri::command]
+pub fn preload_set_queue(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Vec<String>,
+    current_remaining_ms: u64,
+) -> Result<(), String> {
+    ctx.preload.set_queue(&song_ids, current_remaining_ms);
+    Ok(())
+}
+
+/// 获取当前队列中远程歌曲的预加载状态。
+#[tauri::command]
+pub fn preload_get_status(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<PreloadStatus>, String> {
+    Ok(ctx.preload.status())
+}
+
+/// 获取媒体缓存占用的字节数。
+#[tauri::command]
+pub fn media_cache_get_size(ctx: State<'_, Arc<AppContext>>) -> Result<u64, String> {
+    Ok(ctx.registrar.media_cache().map_or(0, |cache| cache.size()))
+}
+
+/// 清空媒体缓存。
+#[tauri::command]
+pub fn media_cache_clear(ctx: State<'_, Arc<AppContext>>) -> Result<(), String> {
+    match ctx.registrar.media_cache() {
+        Some(cache) => cache.clear(),
+        None => Ok(()),
+    }
+}
+
+/// 更新解码 PCM 缓存设置（开关 / 磁盘上限 / 播放次数与时长门槛）；关闭时清空已有副本。
+#[tauri::command]
+pub fn playback_set_pcm_cache(
+    ctx: State<'_, Arc<AppContext>>,
+    pcm_cache: PcmCacheSettings,
+) -> Result<PlaybackSettings, String> {
+    ctx.set_pcm_cache(pcm_cache)
+}
+
+/// 获取解码 PCM 缓存占用的字节数。
+#[tauri::command]
+pub fn pcm_cache_get_size(ctx: State<'_, Arc<AppContext>>) -> Result<u64, String> {
+    Ok(ctx.registrar.pcm_cache().map_or(0, |cache| cache.size()))
+}
+
+/// 清空解码 PCM 缓存。
+#[tauri::command]
+pub fn pcm_cache_clear(ctx: State<'_, Arc<AppContext>>) -> Result<(), String> {
+    match ctx.registrar.pcm_cache() {
+        Some(cache) => cache.clear(),
+        None => Ok(()),
+    }
+}
+
+use chordial_core::module::cache::location::{CacheLocation, MigrationReport};
+
+/// 获取磁盘缓存（Blob / 媒体 / PCM）的根目录。
+#[tauri::command]
+pub fn cache_get_location(ctx: State<'_, Arc<AppContext>>) -> Result<CacheLocation, String> {
+    Ok(ctx.cache_location())
+}
+
+/// 把磁盘缓存搬到 `dir` 下并切换过去；`dir` 缺省时搬回数据目录。
+#[tauri::command]
+pub fn cache_relocate(ctx: State<'_, Arc<AppContext>>, dir: Option<String>) -> Result<MigrationReport, String> {
+    ctx.relocate_cache(dir.as_deref())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 变更历史命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::hidden::HiddenItems;
+use chordial_core::module::music_library::history::ChangeEntry;
+use chordial_core::module::music_library::library::UndoResult;
+
+/// 从库中移除歌曲（不删除文件），可撤销。返回实际移除的数量。
+#[tauri::command]
+pub fn library_remove_songs(ctx: State<'_, Arc<AppContext>>, song_ids: Vec<String>) -> Result<usize, String> {
+    let removed = ctx.library.remove_songs(&song_ids)?;
+    if removed > 0 {
+        ctx.library.save()?;
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(removed)
+}
+
+/// 隐藏歌曲：从列表、搜索、按艺人 / 专辑查询和队列中排除，不删除文件，可撤销。
+/// 返回新隐藏的数量。
+#[tauri::command]
+pub fn library_hide_tracks(ctx: State<'_, Arc<AppContext>>, song_ids: Vec<String>) -> Result<usize, String> {
+    let hidden = ctx.library.hide_songs(&song_ids)?;
+    if hidden > 0 {
+        ctx.library.save()?;
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(hidden)
+}
+
+/// 隐藏专辑及其全部曲目，可撤销。返回新隐藏的数量。
+#[tauri::command]
+pub fn library_hide_albums(ctx: State<'_, Arc<AppContext>>, album_ids: Vec<String>) -> Result<usize, String> {
+    let hidden = ctx.library.hide_albums(&album_ids)?;
+    if hidden > 0 {
+        ctx.library.save()?;
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(hidden)
+}
+
+/// 「已隐藏」列表（`{ songs, albums }`，每项附 `hidden_at`），按隐藏时间从新到旧。
+#[tauri::command]
+pub fn library_get_hidden(ctx: State<'_, Arc<AppContext>>) -> Result<HiddenItems, String> {
+    Ok(ctx.library.hidden_items())
+}
+
+/// 恢复已隐藏的歌曲 / 专辑。返回实际恢复的数量。
+#[tauri::command]
+pub fn library_restore_hidden(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Option<Vec<String>>,
+    album_ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let restored = ctx
+        .library
+        .restore_hidden(&song_ids.unwrap_or_default(), &album_ids.unwrap_or_default())?;
+    if restored > 0 {
+        ctx.library.save()?;
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(restored)
+}
+
+/// 列出可撤销的库变更，最新的在前。
+#[tauri::command]
+pub fn get_change_history(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<ChangeEntry>, String> {
+    Ok(ctx.library.change_history())
+}
+
+/// 撤销最近一次库变更；没有可撤销的记录时返回 `null`。
+#[tauri::command]
+pub fn undo_last_change(ctx: State<'_, Arc<AppContext>>) -> Result<Option<UndoResult>, String> {
+    let result = ctx.local_source.undo_last_change()?;
+    if result.is_some() {
+        ctx.events.publish(AppEvent::LibraryChanged);
+    }
+    Ok(result)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 来源写回命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_source::manager::SourceEntry;
+
+/// 列出已注册来源的条目（含只读开关）。
+#[tauri::command]
+pub fn source_get_entries(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<SourceEntry>, String> {
+    Ok(ctx.registrar.get_entries())
+}
+
+/// 设置来源是否只读；关闭只读后本地的标签编辑会同步写回该来源。
+#[tauri::command]
+pub fn source_set_read_only(
+    ctx: State<'_, Arc<AppContext>>,
+    name: String,
+    read_only: bool,
+) -> Result<(), String> {
+    ctx.registrar.set_read_only(&name, read_only)
+}
+
+/// 设置来源是否仅元数据：开启后其中的歌曲照常显示，但不能播放。
+#[tauri::command]
+pub fn source_set_metadata_only(
+    ctx: State<'_, Arc<AppContext>>,
+    name: String,
+    metadata_only: bool,
+) -> Result<(), String> {
+    ctx.registrar.set_metadata_only(&name, metadata_only)
+}
+
+/// 把歌词文本写回来源。
+#[tauri::command]
+pub fn source_put_lyric_text(
+    ctx: State<'_, Arc<AppContext>>,
+    source_id_json: String,
+    text: String,
+) -> Result<(), String> {
+    let source_id: SourceId = serde_json::from_str(&source_id_json)
+        .map_err(|e| format!("解析 SourceId 失败: {}", e))?;
+    resource::put_lyric_text(&ctx.registrar, &source_id, &text)
+}
+
+/// 把封面图片写回来源。
+#[tauri::command]
+pub fn source_put_album_picture(
+    ctx: State<'_, Arc<AppContext>>,
+    source_id_json: String,
+    data: Vec<u8>,
+) -> Result<(), String> {
+    let source_id: SourceId = serde_json::from_str(&source_id_json)
+        .map_err(|e| format!("解析 SourceId 失败: {}", e))?;
+    resource::put_album_picture(&ctx.registrar, &source_id, &data)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 资源访问审计命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_source::audit::{AccessRecord, MAX_RECORDS};
+
+/// 最近的资源访问记录（新的在前），默认返回全部保留的记录。
+#[tauri::command]
+pub fn source_get_access_log(
+    ctx: State<'_, Arc<AppContext>>,
+    limit: Option<usize>,
+) -> Result<Vec<AccessRecord>, String> {
+    Ok(ctx.registrar.access_log().recent(limit.unwrap_or(MAX_RECORDS)))
+}
+
+/// 清空资源访问记录。
+#[tauri::command]
+pub fn source_clear_access_log(ctx: State<'_, Arc<AppContext>>) -> Result<(), String> {
+    ctx.registrar.access_log().clear();
+    Ok(())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 播放专辑 / 艺人命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::playback::{
+    queue, ActivateAction, ActivateContext, ActivateSurface, EndOfQueueBehavior, EndOfQueuePlan, OutputMode,
+    PlayQueue, QueueStep, QueueTransition, QueueView, RepeatMode, TrackActivation,
+};
+
+/// 排好的队列成为正在播放的队列（同时交给预加载器：起始歌曲按整首时长计，其后的远程歌曲提前下载）。
+fn start_queue(ctx: &AppContext, mut play_queue: PlayQueue, playlist_id: Option<String>) -> PlayQueue {
+    let song_ids = play_queue.songs.iter().map(|s| s.id.clone()).collect();
+    ctx.queue_replace(song_ids, play_queue.start_index, playlist_id);
+    play_queue.songs = ctx.library.localize_songs(play_queue.songs);
+    play_queue
+}
+
+/// 播放专辑：按碟号 / 音轨号（或随机）排好队列并返回。
+/// 指定 `start_track_id` 时从该歌曲开始；随机播放时它固定在队首。
+#[tauri::command]
+pub fn play_album(
+    ctx: State<'_, Arc<AppContext>>,
+    album_id: String,
+    start_track_id: Option<String>,
+    shuffle: bool,
+) -> Result<PlayQueue, String> {
+    let play_queue = queue::album_queue(&ctx.library, &album_id, start_track_id.as_deref(), shuffle)?;
+    Ok(start_queue(&ctx, play_queue, None))
+}
+
+/// 播放艺人的全部歌曲：按专辑发行时间、专辑内音轨顺序排好队列并返回。
+#[tauri::command]
+pub fn play_artist(ctx: State<'_, Arc<AppContext>>, artist_id: String) -> Result<PlayQueue, String> {
+    let play_queue = queue::artist_queue(&ctx.library, &artist_id)?;
+    Ok(start_queue(&ctx, play_queue, None))
+}
+
+/// 激活（双击 / 回车）一首歌：按 `context` 所属界面的设置决定替换队列、追加还是插到下一首。
+/// 替换时返回的队列已成为正在播放的队列；追加 / 插入只返回这首歌，它已放进正在播放的队列。
+#[tauri::command]
+pub fn activate_track(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+    context: ActivateContext,
+) -> Result<TrackActivation, String> {
+    let action = ctx.playback.settings().activate_action(context.surface());
+    let mut activation = queue::activate_track(&ctx.library, &track_id, &context, action)?;
+    activation.queue = if action == ActivateAction::Replace {
+        let playlist_id = match &context {
+            ActivateContext::Playlist { playlist_id } => Some(playlist_id.clone()),
+            _ => None,
+        };
+        start_queue(&ctx, activation.queue, playlist_id)
+    } else {
+        ctx.queue_add(&[track_id], action == ActivateAction::InsertNext)?;
+        PlayQueue {
+            songs: ctx.library.localize_songs(activation.queue.songs),
+            ..activation.queue
+        }
+    };
+    Ok(activation)
+}
+
+/// 设置某一界面中激活歌曲时的行为。
+#[tauri::command]
+pub fn set_activate_action(
+    ctx: State<'_, Arc<AppContext>>,
+    surface: ActivateSurface,
+    action: ActivateAction,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_activate_action(surface, action)
+}
+
+/// 设置队列播完后的行为：停止 / 从头重播 / 自动续播相似歌曲。
+#[tauri::command]
+pub fn set_endofqueue_behavior(
+    ctx: State<'_, Arc<AppContext>>,
+    behavior: EndOfQueueBehavior,
+) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_end_of_queue(behavior)
+}
+
+/// 设置系统休眠唤醒后是否保持暂停（关闭时唤醒后从原位置继续播放）。
+#[tauri::command]
+pub fn set_pause_on_suspend(ctx: State<'_, Arc<AppContext>>, enabled: bool) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_pause_on_suspend(enabled)
+}
+
+/// 设置是否使用空输出（不连接物理设备，按实时速度空转）。
+#[tauri::command]
+pub fn set_null_output(ctx: State<'_, Arc<AppContext>>, enabled: bool) -> Result<PlaybackSettings, String> {
+    ctx.playback.set_null_output(enabled)
+}
+
+/// 当前生效的输出方式：设置开关与环境变量 `CHORDIAL_AUDIO_OUTPUT=null` 合并后的结果。
+/// 前端创建音频输出前查询，空输出时不连接物理设备。
+#[tauri::command]
+pub fn playback_get_output_mode(ctx: State<'_, Arc<AppContext>>) -> Result<OutputMode, String> {
+    Ok(ctx.playback.output_mode())
+}
+
+/// 队列最后一首播完时调用，`song_ids` 为刚播完的队列。
+/// 按设置返回下一步；同时重排预加载队列——停止时清空，避免预加载器停在一个不会再播的队列上。
+#[tauri::command]
+pub fn playback_end_of_queue(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Vec<String>,
+) -> Result<EndOfQueuePlan, String> {
+    let behavior = ctx.playback.settings().end_of_queue;
+    let mut plan = queue::end_of_queue(&ctx.library, behavior, &song_ids);
+    let next: Vec<String> = match plan.behavior {
+        EndOfQueueBehavior::Stop => Vec::new(),
+        EndOfQueueBehavior::RepeatQueue => song_ids,
+        EndOfQueueBehavior::Autoplay => plan.songs.iter().map(|s| s.id.clone()).collect(),
+    };
+    ctx.preload.set_queue(&next, 0);
+    plan.songs = ctx.library.localize_songs(plan.songs);
+    Ok(plan)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 播放队列命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+/// 获取正在播放的队列。
+#[tauri::command]
+pub fn queue_get(ctx: State<'_, Arc<AppContext>>) -> Result<QueueView, String> {
+    Ok(ctx.queue_view())
+}
+
+/// 用前端自带的列表替换队列，从第 `start_index` 首开始播放。
+#[tauri::command]
+pub fn queue_set(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Vec<String>,
+    start_index: Option<usize>,
+    playlist_id: Option<String>,
+) -> Result<QueueStep, String> {
+    Ok(ctx.queue_replace(song_ids, start_index.unwrap_or(0), playlist_id))
+}
+
+/// 加入队列：默认追加到队尾，`next` 为 true 时插到当前歌曲之后。
+#[tauri::command]
+pub fn queue_add(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Vec<String>,
+    next: Option<bool>,
+) -> Result<QueueView, String> {
+    ctx.queue_add(&song_ids, next.unwrap_or(false))
+}
+
+/// 从队列中移除第 `index` 首。
+#[tauri::command]
+pub fn queue_remove(ctx: State<'_, Arc<AppContext>>, index: usize) -> Result<QueueView, String> {
+    ctx.queue_remove(index)
+}
+
+/// 切到下一首；返回的歌曲为空表示队列播完、应停止播放。
+#[tauri::command]
+pub fn queue_next(ctx: State<'_, Arc<AppContext>>) -> Result<QueueStep, String> {
+    Ok(ctx.queue_next())
+}
+
+/// 回到上一首。
+#[tauri::command]
+pub fn queue_previous(ctx: State<'_, Arc<AppContext>>) -> Result<QueueStep, String> {
+    Ok(ctx.queue_previous())
+}
+
+/// 跳到队列中的第 `index` 首。
+#[tauri::command]
+pub fn queue_jump(ctx: State<'_, Arc<AppContext>>, index: usize) -> Result<QueueStep, String> {
+    ctx.queue_jump(index)
+}
+
+/// 设置循环模式：不循环 / 整个队列 / 单曲。
+#[tauri::command]
+pub fn queue_set_repeat_mode(ctx: State<'_, Arc<AppContext>>, mode: RepeatMode) -> Result<QueueView, String> {
+    Ok(ctx.queue_set_repeat_mode(mode))
+}
+
+/// 开关随机播放（只打乱当前歌曲之后的部分）。
+#[tauri::command]
+pub fn queue_set_shuffle(ctx: State<'_, Arc<AppContext>>, enabled: bool) -> Result<QueueView, String> {
+    Ok(ctx.queue_set_shuffle(enabled))
+}
+
+/// 上报当前歌曲的剩余时长；临近结尾时返回下一首与过渡方案（每首歌一次）。
+#[tauri::command]
+pub fn queue_report_position(
+    ctx: State<'_, Arc<AppContext>>,
+    remaining_ms: u64,
+) -> Result<Option<QueueTransition>, String> {
+    Ok(ctx.queue_report_position(remaining_ms))
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 转码流命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_source::stream::StreamQuality;
+
+/// 设置来源的转码质量：`transcode` 平时使用，`metered_transcode` 在计费网络下覆盖；`null` 表示取原始文件 / 沿用平时设置。
+#[tauri::command]
+pub fn source_set_transcode(
+    ctx: State<'_, Arc<AppContext>>,
+    name: String,
+    transcode: Option<StreamQuality>,
+    metered_transcode: Option<StreamQuality>,
+) -> Result<(), String> {
+    ctx.registrar.set_transcode(&name, transcode, metered_transcode)
+}
+
+/// 告知后端当前网络是否计费（前端根据系统网络状态在变化时调用）。
+#[tauri::command]
+pub fn source_set_metered_network(ctx: State<'_, Arc<AppContext>>, metered: bool) -> Result<(), String> {
+    ctx.registrar.set_metered(metered);
+    Ok(())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 批量操作命令（列表多选）
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::batch::BatchReport;
+
+/// 把多首歌曲加入播放队列：一次取回全部歌曲（已本地化），不存在的歌曲记入 `failed`。
+///
+/// 找到的歌曲已追加到正在播放的队列；返回的 `songs` 按请求顺序排列，供前端刷新界面。
+#[tauri::command]
+pub fn queue_add_many(
+    ctx: State<'_, Arc<AppContext>>,
+    track_ids: Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let mut songs = Vec::with_capacity(track_ids.len());
+    let report = BatchReport::run(&track_ids, |id| {
+        let song = ctx
+            .library
+            .get_song(id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+        songs.push(song);
+        Ok(())
+    });
+    let ids: Vec<String> = songs.iter().map(|s| s.id.clone()).collect();
+    ctx.queue_add(&ids, false)?;
+    Ok(serde_json::json!({
+        "songs": ctx.library.localize_songs(songs),
+        "report": report,
+    }))
+}
+
+/// 把多首歌曲加入歌单（不是播放队列）：歌单只落盘一次，不存在的歌曲记入 `failed`。
+///
+/// 歌单不存在或是智能歌单时整批拒绝。
+#[tauri::command]
+pub fn add_tracks_to_playlist(
+    ctx: State<'_, Arc<AppContext>>,
+    playlist_id: String,
+    track_ids: Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let (playlist, report) = ctx.library.add_tracks_to_playlist(&playlist_id, &track_ids)?;
+    ctx.library.save()?;
+    Ok(serde_json::json!({ "playlist": playlist, "report": report }))
+}
+
+/// 批量设置评分（0–100，`null` 清除）。评分越界时整批拒绝；库只落盘一次。
+///
+/// 开启写回时逐首写入文件标签，写回失败不影响库内评分，放在 `write_errors` 中返回。
+#[tauri::command]
+pub fn batch_set_rating(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Vec<String>,
+    rating: Option<u8>,
+) -> Result<serde_json::Value, String> {
+    let report = ctx.library.set_ratings(&song_ids, rating)?;
+    ctx.library.save()?;
+    let write_back = ctx.config.get::<bool>(WRITE_BACK_CONFIG_KEY).unwrap_or(false);
+    let write_errors: Vec<(String, String)> = if write_back {
+        report
+            .succeeded
+            .iter()
+            .filter_map(|id| {
+                ctx.local_source
+                    .write_stats_to_file(id)
+                    .err()
+                    .map(|e| (id.clone(), e))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Ok(serde_json::json!({
+        "report": report,
+        "write_errors": write_errors,
+    }))
+}
+
+/// 对选中的歌曲做转码检测（后台任务，进度事件同 [`analyze_library_transcodes`]）。
+///
+/// 不存在或没有本地文件的歌曲记入 `report.failed`，其余交给分析任务；已有批量任务在运行时返回该任务的句柄。
+#[tauri::command]
+pub fn batch_analyze(
+    ctx: State<'_, Arc<AppContext>>,
+    track_ids: Vec<String>,
+    task_id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let mut songs = Vec::with_capacity(track_ids.len());
+    let report = BatchReport::run(&track_ids, |id| {
+        let song = ctx
+            .library
+            .get_song(id)
+            .ok_or_else(|| format!("歌曲 '{}' 不存在", id))?;
+        let path = resource::find_song_file_path(&ctx.registrar, &song.source_ids)
+            .ok_or_else(|| format!("歌曲 '{}' 没有可访问的本地文件", id))?;
+        songs.push((song.id, path));
+        Ok(())
+    });
+    let job = if songs.is_empty() {
+        None
+    } else {
+        Some(ctx.analysis.spawn_transcode_scan(
+            task_id.as_deref().unwrap_or("batch_analyze"),
+            songs,
+            ctx.tasks.clone(),
+            ctx.events.clone(),
+        )?)
+    };
+    Ok(serde_json::json!({
+        "job": job,
+        "report": report,
+    }))
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 扫描扩展名命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_localSource::extensions::{ExtensionSettings, FolderExtensions};
+use std::collections::BTreeMap;
+
+/// 获取扫描用的扩展名设置（全局别名 + 按文件夹覆盖）。
+#[tauri::command]
+pub fn local_get_extensions(ctx: State<'_, Arc<AppContext>>) -> Result<ExtensionSettings, String> {
+    Ok(ctx.local_source.folder_manager.extension_settings())
+}
+
+/// 设置全局扩展名别名，如 `m4b` → `m4a`；`target` 为 `null` 时移除。新加入的扩展名在下次扫描时生效。
+#[tauri::command]
+pub fn local_set_extension_alias(
+    ctx: State<'_, Arc<AppContext>>,
+    ext: String,
+    target: Option<String>,
+) -> Result<ExtensionSettings, String> {
+    ctx.local_source
+        .folder_manager
+        .set_extension_alias(&ext, target.as_deref())
+}
+
+/// 替换某个监听文件夹的扩展名覆盖（额外别名 + 排除的扩展名）；两者都为空时移除覆盖。
+#[tauri::command]
+pub fn local_set_folder_extensions(
+    ctx: State<'_, Arc<AppContext>>,
+    folder: String,
+    aliases: BTreeMap<String, String>,
+    exclude: Vec<String>,
+) -> Result<ExtensionSettings, String> {
+    ctx.local_source.folder_manager.set_folder_extensions(FolderExtensions {
+        folder,
+        aliases,
+        exclude,
+    })
+}
+
+use chordial_core::module::music_localSource::folder::{FolderAccess, FolderAccessEntry};
+
+/// 列出各音乐文件夹的访问设置（只读 / 仅元数据）。
+#[tauri::command]
+pub fn local_get_folder_access(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<FolderAccessEntry>, String> {
+    Ok(ctx.local_source.folder_manager.folder_access())
+}
+
+/// 设置音乐文件夹的访问方式：只读时不向其中的文件写入标签 / 评分，仅元数据时其中的歌曲不能播放。
+#[tauri::command]
+pub fn local_set_folder_access(
+    ctx: State<'_, Arc<AppContext>>,
+    folder: String,
+    read_only: bool,
+    metadata_only: bool,
+) -> Result<Vec<FolderAccessEntry>, String> {
+    let manager = &ctx.local_source.folder_manager;
+    manager.set_folder_access(&PlatformPath::from(folder.as_str()), FolderAccess { read_only, metadata_only })?;
+    Ok(manager.folder_access())
+}
+
+use chordial_core::module::music_localSource::ogg_chain::{self, ChainTags};
+
+/// 链式 OGG（电台录音等多段首尾相接的文件）取第一段还是最后一段的标签；重新扫描后生效。
+#[tauri::command]
+pub fn local_set_ogg_chain_tags(ctx: State<'_, Arc<AppContext>>, choice: ChainTags) -> Result<(), String> {
+    ctx.config.set(ogg_chain::CHAIN_TAGS_CONFIG_KEY, &choice)?;
+    ogg_chain::set_chain_tags(choice);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn local_get_ogg_chain_tags() -> Result<ChainTags, String> {
+    Ok(ogg_chain::chain_tags())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 有声书命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::books::{self, Book, BookProgress, Chapter};
+use chordial_core::module::music_localSource::chapters;
+
+/// 读取歌曲的章节；没有本地文件或文件不含章节表时返回空列表。
+fn song_chapters(ctx: &AppContext, song_id: &str) -> Result<Vec<Chapter>, String> {
+    let song = ctx
+        .library
+        .get_song(song_id)
+        .ok_or_else(|| format!("歌曲 '{}' 不存在", song_id))?;
+    match resource::find_song_file_path(&ctx.registrar, &song.source_ids) {
+        Some(path) => chapters::read_chapters(&PlatformPath::from(path.as_str())),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 书籍分区：所有有声书（`.m4b`、有声书流派或超长文件）及其收听进度。
+#[tauri::command]
+pub fn library_get_books(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<Book>, String> {
+    let books = ctx.library.get_books()?;
+    ctx.library.save_if_dirty()?;
+    Ok(books
+        .into_iter()
+        .map(|mut book| {
+            book.song = ctx.library.localize_song(book.song);
+            book
+        })
+        .collect())
+}
+
+/// 获取有声书的章节列表（开始位置为毫秒）。
+#[tauri::command]
+pub fn book_get_chapters(ctx: State<'_, Arc<AppContext>>, song_id: String) -> Result<Vec<Chapter>, String> {
+    song_chapters(&ctx, &song_id)
+}
+
+/// 保存收听进度，后端据章节表记下所在章节。前端在暂停、切歌和定时（如每 15 秒）时调用。
+#[tauri::command]
+pub fn book_save_progress(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+    position_ms: u64,
+) -> Result<BookProgress, String> {
+    let chapter = books::chapter_at(&song_chapters(&ctx, &song_id)?, position_ms);
+    let progress = ctx.library.set_book_progress(&song_id, position_ms, chapter)?;
+    ctx.library.save()?;
+    Ok(progress)
+}
+
+/// 获取收听进度；没有记录时返回 `null`。
+#[tauri::command]
+pub fn book_get_progress(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+) -> Result<Option<BookProgress>, String> {
+    Ok(ctx.library.get_book_progress(&song_id))
+}
+
+/// 清除收听进度（听完或从头开始）。
+#[tauri::command]
+pub fn book_clear_progress(ctx: State<'_, Arc<AppContext>>, song_id: String) -> Result<(), String> {
+    ctx.library.clear_book_progress(&song_id);
+    ctx.library.save()
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 歌单命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::playlist_export::{self, PlaylistImport};
+use chordial_core::module::music_library::playlists::{Playlist, PlaylistFolder, PlaylistNode};
+use chordial_core::module::music_library::smart_playlists::{SmartPreview, SmartRules};
+
+/// 歌单树：文件夹可嵌套，同一层级按用户排列的顺序。
+#[tauri::command]
+pub fn playlist_get_tree(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<PlaylistNode>, String> {
+    Ok(ctx.library.playlist_tree())
+}
+
+/// 新建歌单；`folder_id` 为空时放在根层级末尾。
+#[tauri::command]
+pub fn create_playlist(
+    ctx: State<'_, Arc<AppContext>>,
+    name: String,
+    folder_id: Option<String>,
+) -> Result<Playlist, String> {
+    let playlist = ctx.library.create_playlist(&name, folder_id.as_deref())?;
+    ctx.library.save()?;
+    Ok(playlist)
+}
+
+/// 新建歌单文件夹；`parent_id` 为空时放在根层级末尾。
+#[tauri::command]
+pub fn playlist_create_folder(
+    ctx: State<'_, Arc<AppContext>>,
+    name: String,
+    parent_id: Option<String>,
+) -> Result<PlaylistFolder, String> {
+    let folder = ctx.library.create_playlist_folder(&name, parent_id.as_deref())?;
+    ctx.library.save()?;
+    Ok(folder)
+}
+
+/// 重命名歌单或文件夹。
+#[tauri::command]
+pub fn playlist_rename(ctx: State<'_, Arc<AppContext>>, id: String, name: String) -> Result<(), String> {
+    ctx.library.rename_playlist(&id, &name)?;
+    ctx.library.save()
+}
+
+/// 删除歌单；删除文件夹时其中的歌单与子文件夹移到文件夹原来的位置。
+#[tauri::command]
+pub fn remove_playlist(ctx: State<'_, Arc<AppContext>>, id: String) -> Result<(), String> {
+    ctx.library.remove_playlist(&id)?;
+    ctx.library.save()
+}
+
+/// 把歌单或文件夹移到 `folder_id`（为空表示根层级）的第 `index` 位，不传 `index` 时放到末尾。
+///
+/// 拖拽排序时传入目标位置即可，同一文件夹内的移动也走这里。
+#[tauri::command]
+pub fn playlist_move(
+    ctx: State<'_, Arc<AppContext>>,
+    id: String,
+    folder_id: Option<String>,
+    index: Option<usize>,
+) -> Result<(), String> {
+    ctx.library.move_playlist(&id, folder_id.as_deref(), index)?;
+    ctx.library.save()
+}
+
+/// 按 `ids` 的顺序重排某个文件夹（为空表示根层级）的全部子项。
+#[tauri::command]
+pub fn playlist_reorder(
+    ctx: State<'_, Arc<AppContext>>,
+    folder_id: Option<String>,
+    ids: Vec<String>,
+) -> Result<(), String> {
+    ctx.library.reorder_playlists(folder_id.as_deref(), &ids)?;
+    ctx.library.save()
+}
+
+/// 歌单中的歌曲，按歌单顺序。
+#[tauri::command]
+pub fn playlist_get_songs(ctx: State<'_, Arc<AppContext>>, id: String) -> Result<serde_json::Value, String> {
+    wait_library(&ctx)?;
+    let songs = ctx.library.localize_songs(ctx.library.get_playlist_songs(&id)?);
+    serde_json::to_value(songs).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 按 `track_ids` 的顺序重排歌单曲目；不在 `track_ids` 中的曲目从歌单移除。
+///
+/// 追加曲目见 [`add_tracks_to_playlist`]。
+#[tauri::command]
+pub fn reorder_playlist(
+    ctx: State<'_, Arc<AppContext>>,
+    playlist_id: String,
+    track_ids: Vec<String>,
+) -> Result<Playlist, String> {
+    let playlist = ctx.library.set_playlist_songs(&playlist_id, track_ids)?;
+    ctx.library.save()?;
+    Ok(playlist)
+}
+
+/// 新建智能歌单；曲目由 `rules` 对库求值得出，随库内容变化。
+#[tauri::command]
+pub fn playlist_create_smart(
+    ctx: State<'_, Arc<AppContext>>,
+    name: String,
+    folder_id: Option<String>,
+    rules: SmartRules,
+) -> Result<Playlist, String> {
+    let playlist = ctx.library.create_smart_playlist(&name, folder_id.as_deref(), rules)?;
+    ctx.library.save()?;
+    Ok(playlist)
+}
+
+/// 修改智能歌单的规则。
+#[tauri::command]
+pub fn playlist_set_rules(ctx: State<'_, Arc<AppContext>>, id: String, rules: SmartRules) -> Result<Playlist, String> {
+    let playlist = ctx.library.set_smart_playlist_rules(&id, rules)?;
+    ctx.library.save()?;
+    Ok(playlist)
+}
+
+/// 按尚未保存的规则试算：返回命中总数与前若干首歌曲，编辑规则时实时预览。
+#[tauri::command]
+pub fn playlist_preview_smart(ctx: State<'_, Arc<AppContext>>, rules: SmartRules) -> Result<SmartPreview, String> {
+    wait_library(&ctx)?;
+    let mut preview = ctx.library.preview_smart_playlist(&rules)?;
+    preview.songs = ctx.library.localize_songs(preview.songs);
+    Ok(preview)
+}
+
+/// 把智能歌单的当前曲目复制为普通歌单（放在智能歌单之后），此后不再随库变化。
+#[tauri::command]
+pub fn playlist_materialize(
+    ctx: State<'_, Arc<AppContext>>,
+    id: String,
+    name: Option<String>,
+) -> Result<Playlist, String> {
+    wait_library(&ctx)?;
+    let playlist = ctx.library.materialize_smart_playlist(&id, name.as_deref())?;
+    ctx.library.save()?;
+    Ok(playlist)
+}
+
+/// 导出歌单文件。`path` 以 `.m3u` / `.m3u8` 结尾时写出 M3U（只含有本地文件的曲目），供其他播放器使用；
+/// 否则写出 JSON，每首曲目附带标题 / 艺人 / 时长 / ISRC / 内容哈希等匹配线索，可在其他设备上导入。
+#[tauri::command]
+pub fn export_playlist(ctx: State<'_, Arc<AppContext>>, id: String, path: String) -> Result<(), String> {
+    let exported = ctx.library.export_playlist(&id)?;
+    playlist_export::write_file(&exported, std::path::Path::new(&path))
+}
+
+/// 导入歌单文件（JSON 或 M3U / M3U8）：按线索在本机库中匹配曲目并新建歌单，返回匹配结果与未找到的曲目。
+#[tauri::command]
+pub fn import_playlist(
+    ctx: State<'_, Arc<AppContext>>,
+    path: String,
+    folder_id: Option<String>,
+) -> Result<PlaylistImport, String> {
+    wait_library(&ctx)?;
+    let exported = playlist_export::read_file(std::path::Path::new(&path))?;
+    let result = ctx.library.import_playlist_with_matching(&exported, folder_id.as_deref())?;
+    ctx.library.save()?;
+    Ok(result)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 音乐库快照命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::storage::snapshot::{self, RecoveryReport, SnapshotInfo};
+
+/// 本次启动时库文件损坏并回滚的记录；库文件完好时返回 `null`。
+///
+/// 前端启动后查询一次，向用户说明回滚到了哪天的快照、损坏文件保存在哪里。
+#[tauri::command]
+pub fn library_get_recovery_report(ctx: State<'_, Arc<AppContext>>) -> Result<Option<RecoveryReport>, String> {
+    Ok(ctx.library.recovery_report().cloned())
+}
+
+/// 列出库文件的每日快照，新的在前。
+#[tauri::command]
+pub fn library_list_snapshots(ctx: State<'_, Arc<AppContext>>) -> Result<Vec<SnapshotInfo>, String> {
+    Ok(ctx.library.list_snapshots())
+}
+
+/// 设置快照保留份数（至少 1 份），多余的旧快照立即删除。
+#[tauri::command]
+pub fn library_set_snapshot_retain(ctx: State<'_, Arc<AppContext>>, retain: usize) -> Result<Vec<SnapshotInfo>, String> {
+    ctx.library.set_snapshot_retain(retain)?;
+    ctx.config.set(snapshot::RETAIN_CONFIG_KEY, &retain.max(1))?;
+    Ok(ctx.library.list_snapshots())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 单曲增益命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::playback::{gain, TrackGain};
+
+/// 设置单曲增益覆盖值（dB，优先于 ReplayGain）；`gain_db` 为 `null` 时清除覆盖。
+#[tauri::command]
+pub fn set_track_gain_db(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+    gain_db: Option<f32>,
+) -> Result<TrackGain, String> {
+    let applied = ctx.library.set_track_gain_db(&song_id, gain_db)?;
+    ctx.library.save()?;
+    Ok(applied)
+}
+
+/// 歌曲开播时应用的增益及其来源。
+#[tauri::command]
+pub fn playback_track_gain(ctx: State<'_, Arc<AppContext>>, song_id: String) -> Result<TrackGain, String> {
+    ctx.library.track_gain(&song_id)
+}
+
+/// 预览队列中接下来 `count` 首（默认 5 首）将应用的增益。`song_ids` 从下一首开始排列。
+#[tauri::command]
+pub fn playback_gain_preview(
+    ctx: State<'_, Arc<AppContext>>,
+    song_ids: Vec<String>,
+    count: Option<usize>,
+) -> Result<Vec<TrackGain>, String> {
+    Ok(ctx
+        .library
+        .preview_gains(&song_ids, count.unwrap_or(gain::PREVIEW_DEFAULT_COUNT)))
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 电源策略 / 诊断命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::power::{PowerPolicy, PowerStatus};
+
+/// 设置电源策略：auto（电池时降并行度）/ pause_on_battery（另暂停批量分析）/ low_power / performance。
+#[tauri::command]
+pub fn set_power_policy(ctx: State<'_, Arc<AppContext>>, policy: PowerPolicy) -> Result<PowerStatus, String> {
+    ctx.power.set_policy(policy)
+}
+
+/// 当前电源策略、供电状态与生效的限流参数。
+#[tauri::command]
+pub fn power_get_status(ctx: State<'_, Arc<AppContext>>) -> Result<PowerStatus, String> {
+    Ok(ctx.power.status())
+}
+
+/// 诊断信息：电源策略、音乐库与本地来源的概况，供设置页「诊断」一栏和问题反馈使用。
+#[tauri::command]
+pub fn app_get_diagnostics(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "power": ctx.power.status(),
+        "library": {
+            "songs": ctx.library.song_count(),
+            "snapshots": ctx.library.list_snapshots().len(),
+            "recovered_at_startup": ctx.library.recovery_report().is_some(),
+        },
+        "local": {
+            "folder_count": ctx.local_source.folder_manager.count(),
+            "indexed_files": ctx.local_source.file_index.read().len(),
+            "quarantined_files": ctx.local_source.quarantine.list().len(),
+        },
+    }))
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 专辑 / 艺人汇总命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::aggregates::Totals;
+
+/// 专辑的曲目数、总时长（秒）与总文件大小（字节）。
+#[tauri::command]
+pub fn library_get_album_totals(ctx: State<'_, Arc<AppContext>>, album_id: String) -> Result<Totals, String> {
+    Ok(ctx.library.album_totals(&album_id))
+}
+
+/// 艺人名下歌曲的曲目数、总时长（秒）与总文件大小（字节）。
+#[tauri::command]
+pub fn library_get_artist_totals(ctx: State<'_, Arc<AppContext>>, artist_id: String) -> Result<Totals, String> {
+    Ok(ctx.library.artist_totals(&artist_id))
+}
+
+/// 按当前歌曲全量重新生成汇总（汇总与列表对不上时的手动修复）。
+#[tauri::command]
+pub fn library_rebuild_totals(ctx: State<'_, Arc<AppContext>>) -> Result<(), String> {
+    ctx.library.rebuild_totals()
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 同曲多版本命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::music_library::versions::TrackVersions;
+
+/// 「其他版本」面板：同一首歌的现场版、混音、伴奏、MV 等版本。
+///
+/// `compare_audio`（默认开启）时为本地文件计算指纹相似度，标出同一录音的重制版 / 单曲版；
+/// 首次比较需要完整解码，结果持久化。
+#[tauri::command(async)]
+pub fn get_track_versions(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+    compare_audio: Option<bool>,
+) -> Result<TrackVersions, String> {
+    let compare_audio = compare_audio.unwrap_or(true);
+    ctx.library.track_versions(&track_id, |song| {
+        if !compare_audio {
+            return None;
+        }
+        let path = resource::find_song_file_path(&ctx.registrar, &song.source_ids)?;
+        ctx.analysis.fingerprint(&song.id, &AudioInput::Local(path)).ok()
+    })
+}
+
+/// 手动把两首歌关联为同曲的不同版本。
+#[tauri::command]
+pub fn link_track_versions(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+    other_id: String,
+) -> Result<(), String> {
+    ctx.library.set_track_version_link(&track_id, &other_id, true)?;
+    ctx.library.save()
+}
+
+/// 取消两首歌的版本关联；自动匹配也不再把它们列为同曲。
+#[tauri::command]
+pub fn unlink_track_versions(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+    other_id: String,
+) -> Result<(), String> {
+    ctx.library.set_track_version_link(&track_id, &other_id, false)?;
+    ctx.library.save()
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 封面导出命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::artwork::{self, ArtExport, ArtFormat, ExportOptions};
+
+/// 把专辑封面（嵌入 / 同名图片 / 文件夹图片 / 补全得到的封面）导出到 `path`。
+///
+/// `size` 为长边像素上限（只缩小）；`format` 省略时按扩展名判断，默认 JPEG。
+#[tauri::command(async)]
+pub fn export_album_art(
+    ctx: State<'_, Arc<AppContext>>,
+    album_id: String,
+    path: String,
+    size: Option<u32>,
+    format: Option<ArtFormat>,
+) -> Result<ArtExport, String> {
+    artwork::export_album_art(
+        &ctx.registrar,
+        &ctx.library,
+        &album_id,
+        std::path::Path::new(&path),
+        ExportOptions { size, format },
+    )
+}
+
+/// 专辑封面缩略图（JPEG，长边 `size` 像素，默认 300），已预热时直接读缓存。
+#[tauri::command(async)]
+pub fn get_album_thumbnail(
+    ctx: State<'_, Arc<AppContext>>,
+    album_id: String,
+    size: Option<u32>,
+) -> Result<Vec<u8>, String> {
+    ctx.album_thumbnail(&album_id, size)
+}
+
+/// 试听片段（FLAC 字节）：从 `start_secs` 起 `duration_secs` 秒（默认 10 秒），未指定起点时取高潮段。
+///
+/// 片段已按电平归一化，不经过播放器、不改动播放状态；结果会缓存。
+#[tauri::command(async)]
+pub fn get_track_preview(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+    start_secs: Option<f64>,
+    duration_secs: Option<f64>,
+) -> Result<Vec<u8>, String> {
+    ctx.track_preview(&song_id, start_secs, duration_secs)
+}
+
+/// 在后台为一批专辑预先生成缩略图（最多 4 个并发），立即返回需要生成的数量。
+///
+/// 专辑网格滚动时传入即将可见的专辑；新请求会取代尚未处理完的旧请求。
+#[tauri::command]
+pub fn prewarm_covers(
+    ctx: State<'_, Arc<AppContext>>,
+    album_ids: Vec<String>,
+    size: Option<u32>,
+) -> Result<usize, String> {
+    ctx.prewarm_covers(album_ids, size)
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 启动就绪命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+/// 各子系统的启动预热状态；前端订阅 `app://ready` 之前可能已错过事件，启动时先补查一次。
+#[tauri::command]
+pub fn app_get_readiness(ctx: State<'_, Arc<AppContext>>) -> Result<ReadinessStatus, String> {
+    Ok(ctx.readiness.status())
+}
+
+/// 启动模式；安全模式下前端不创建音频输出。
+#[tauri::command]
+pub fn app_get_startup_mode(ctx: State<'_, Arc<AppContext>>) -> Result<StartupMode, String> {
+    Ok(StartupMode { safe_mode: ctx.safe_mode })
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 内容哈希命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::analysis::content_hash::{self, HashStatus};
+
+/// 在后台计算音频内容哈希，立即返回任务句柄（进度事件同 [`analyze_library_transcodes`]）。
+///
+/// `song_ids` 省略时只处理缺少哈希或文件已修改的歌曲；指定时全部重新校验，
+/// 结束事件的 `summary.suspicious` 为疑似损坏的数量。
+#[tauri::command]
+pub fn library_hash_songs(
+    ctx: State<'_, Arc<AppContext>>,
+    task_id: Option<String>,
+    song_ids: Option<Vec<String>>,
+) -> Result<AnalysisJob, String> {
+    ctx.spawn_content_hash_scan(task_id.as_deref().unwrap_or("content_hash_scan"), song_ids)
+}
+
+/// 重新计算单曲的内容哈希并与上次记录比较（`corrupted` 表示文件未修改但音频数据变了）。
+#[tauri::command(async)]
+pub fn library_verify_content_hash(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+) -> Result<HashStatus, String> {
+    ctx.verify_content_hash(&song_id)
+}
+
+/// 音频内容完全相同的歌曲分组（只比较已计算过哈希的歌曲）。
+#[tauri::command(async)]
+pub fn library_find_exact_duplicates(ctx: State<'_, Arc<AppContext>>) -> Result<serde_json::Value, String> {
+    let groups: Vec<_> = ctx
+        .library
+        .exact_duplicates()
+        .into_iter()
+        .map(|group| ctx.library.localize_songs(group))
+        .collect();
+    serde_json::to_value(&groups).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 开启 / 关闭内容哈希：开启后启动时为新文件计算哈希，分析结果也以内容哈希为缓存键。
+#[tauri::command]
+pub fn content_hash_set_enabled(ctx: State<'_, Arc<AppContext>>, enabled: bool) -> Result<(), String> {
+    ctx.config.set(content_hash::ENABLED_CONFIG_KEY, &enabled)?;
+    ctx.analysis.set_content_hash_keys(enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn content_hash_get_enabled(ctx: State<'_, Arc<AppContext>>) -> Result<bool, String> {
+    Ok(ctx.config.get::<bool>(content_hash::ENABLED_CONFIG_KEY).unwrap_or(false))
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 播放质量命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::playback::{DecodeStats, PlayerReport};
+
+/// 当前歌曲的编码、解码速度、缓冲水位、卡顿次数、重采样状态和生效的 DSP 链，供播放器调试浮层显示。
+///
+/// `report` 为前端播放器此刻的缓冲 / 卡顿 / 输出采样率；首次查询某首歌会试解开头几秒测量解码速度。
+#[tauri::command(async)]
+pub fn get_decode_stats(
+    ctx: State<'_, Arc<AppContext>>,
+    song_id: String,
+    report: Option<PlayerReport>,
+) -> Result<DecodeStats, String> {
+    ctx.decode_stats(&song_id, report.as_ref())
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// 正在播放页命令
+// ══════════════════════════════════════════════════════════════════════════════
+
+use chordial_core::module::playback::NowPlayingBundle;
+
+/// 切歌时一次取齐正在播放页的数据：歌曲、专辑、艺人简介、歌词、技术信息、前奏 / 尾奏、增益、封面配色。
+///
+/// 各部分在后端并行计算，取不到的部分留空；替代前端逐项调用的多次 IPC。
+#[tauri::command(async)]
+pub fn get_now_playing_bundle(
+    ctx: State<'_, Arc<AppContext>>,
+    track_id: String,
+) -> Result<NowPlayingBundle, String> {
+    ctx.now_playing_bundle(&track_id)
+}
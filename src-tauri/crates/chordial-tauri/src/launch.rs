@@ -0,0 +1,165 @@
+//! 启动请求 — 文件关联与 `chordial://` 深链接。
+//!
+//! 操作系统在两种情况下把参数交给应用：
+//! - 双击已关联的音频文件 / 「用 Chordial 打开」→ 参数为文件路径
+//! - 点击 `chordial://track/<id>` 链接 → 参数为 URL（桌面端经命令行，移动端 / macOS 经 deep-link 插件）
+//!
+//! 首次启动时请求暂存在 [`PendingLaunch`]，前端就绪后调用 `launch_take_requests` 取走；
+//! 应用已在运行时，单实例插件把第二个进程的参数转发过来，直接以 `launch-request` 事件推给前端。
+//!
+//! 深链接统一由 deep-link 插件交付（启动时 `get_current`，运行中 `on_open_url`；
+//! 单实例插件启用 `deep-link` 特性后会把第二个进程收到的 URL 也交给它），
+//! 因此命令行参数里只处理文件路径，避免同一链接被处理两次。
+//!
+//! 注意：WebView 内部的媒体协议同样使用 `chordial://` scheme（`/audio/...` 等路径），
+//! 深链接只识别 `track` 主机名，两者互不干扰。
+
+use chordial_core::module::platform::{self, PlatformPath};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 深链接 scheme。
+const DEEP_LINK_SCHEME: &str = "chordial://";
+
+/// 一次启动请求。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LaunchRequest {
+    /// 打开文件 / 文件夹并作为临时队列播放
+    OpenFiles { paths: Vec<String> },
+    /// 播放库中的歌曲（`chordial://track/<id>`）
+    PlayTrack { track_id: String },
+}
+
+/// 尚未被前端取走的启动请求。
+#[derive(Default)]
+pub struct PendingLaunch(Mutex<Vec<LaunchRequest>>);
+
+impl PendingLaunch {
+    pub fn new(requests: Vec<LaunchRequest>) -> Self {
+        Self(Mutex::new(requests))
+    }
+
+    /// 取走全部请求（之后再取为空）。
+    pub fn take(&self) -> Vec<LaunchRequest> {
+        std::mem::take(&mut *self.0.lock())
+    }
+}
+
+/// 从命令行参数（不含程序名）中挑出存在的文件 / 文件夹，合并为一个打开请求。
+///
+/// `-` 开头的选项、深链接 URL 和不存在的路径被忽略。
+pub fn parse_file_args<I, S>(args: I) -> Option<LaunchRequest>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let paths: Vec<String> = args
+        .into_iter()
+        .map(|arg| arg.as_ref().to_string())
+        .filter(|arg| !arg.starts_with('-') && !arg.starts_with(DEEP_LINK_SCHEME))
+        .filter(|arg| platform::exists(&PlatformPath::from(arg.as_str())))
+        .collect();
+    (!paths.is_empty()).then_some(LaunchRequest::OpenFiles { paths })
+}
+
+/// 解析 `chordial://track/<id>`；其他形式返回 `None`。
+pub fn parse_deep_link(url: &str) -> Option<LaunchRequest> {
+    let rest = url.strip_prefix(DEEP_LINK_SCHEME)?;
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (kind, id) = rest.trim_end_matches('/').split_once('/')?;
+    match kind {
+        "track" if !id.is_empty() && !id.contains('/') => Some(LaunchRequest::PlayTrack {
+            track_id: percent_decode(id),
+        }),
+        _ => None,
+    }
+}
+
+/// 解码 URL 中的 `%XX` 转义；非法序列原样保留。
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 把运行期间收到的请求转交前端，并把主窗口带到前台。
+pub fn forward(app: &AppHandle, requests: Vec<LaunchRequest>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    for request in requests {
+        let _ = app.emit("launch-request", &request);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(id: &str) -> Option<LaunchRequest> {
+        Some(LaunchRequest::PlayTrack { track_id: id.to_string() })
+    }
+
+    #[test]
+    fn test_parse_deep_link_track() {
+        assert_eq!(parse_deep_link("chordial://track/abc"), play("abc"));
+        assert_eq!(parse_deep_link("chordial://track/abc?x#y"), play("abc"));
+        assert_eq!(parse_deep_link("chordial://track/abc/"), play("abc"));
+        assert_eq!(parse_deep_link("chordial://track/%E4%B8%AD"), play("中"));
+    }
+
+    #[test]
+    fn test_parse_deep_link_rejects_other_forms() {
+        assert_eq!(parse_deep_link("chordial://track/a/b"), None);
+        assert_eq!(parse_deep_link("chordial://track/"), None);
+        // 媒体协议的路径不是深链接
+        assert_eq!(parse_deep_link("chordial://audio/song.flac"), None);
+        assert_eq!(parse_deep_link("https://track/abc"), None);
+    }
+
+    #[test]
+    fn test_percent_decode_keeps_invalid_sequences() {
+        assert_eq!(percent_decode("%E4%B8%AD"), "中");
+        assert_eq!(percent_decode("a%20b"), "a b");
+        assert_eq!(percent_decode("abc%4"), "abc%4");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn test_parse_file_args_skips_options_and_urls() {
+        let dir = std::env::temp_dir().join(format!("chordial_launch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("song.flac");
+        std::fs::write(&file, b"").unwrap();
+        let file = file.to_string_lossy().into_owned();
+        let missing = dir.join("missing.flac").to_string_lossy().into_owned();
+
+        let request = parse_file_args([
+            "--minimized",
+            "chordial://track/abc",
+            missing.as_str(),
+            file.as_str(),
+        ]);
+        assert_eq!(request, Some(LaunchRequest::OpenFiles { paths: vec![file] }));
+        assert_eq!(parse_file_args(["-v", "chordial://track/abc"]), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
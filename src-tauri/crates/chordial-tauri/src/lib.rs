@@ -7,19 +7,86 @@
 //! `chordial://` 协议（[`media_protocol`]）同样委托给 core 的 `media::handle`。
 
 mod commands;
+mod launch;
 mod media_protocol;
+#[cfg(test)]
+mod test_harness;
 
-use chordial_core::module::p2p::P2pEvent;
+use chordial_core::module::events::AppEvent;
 use chordial_core::AppContext;
 use std::sync::Arc;
-use tauri::{Emitter, Manager};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tokio::sync::broadcast::error::RecvError;
+
+/// 订阅 core 事件总线，把前端关心的事件转发为 Tauri 事件。
+///
+/// 这是 core 与前端之间唯一的事件通道；前端事件名保持不变：
+/// - `library-changed`：库内容变化，触发专辑/艺人列表刷新
+/// - `library-diff`：重扫 / 增删文件夹 / 文件监听同步后的增量变化 `{ tracks_added, tracks_removed, tracks_updated, albums_changed, albums_removed }`
+/// - `metadata-read-progress`：批量元数据读取进度 `{ task_id, done, total }`
+/// - `scan://progress`：后台扫描进度 `{ folder, folder_index, folder_count, done, total }`
+/// - `scan://finished`：后台扫描结束 `{ report, cancelled, error }`
+/// - `analysis-progress`：批量分析进度 `{ task_id, done, total }`
+/// - `analysis-finished`：批量分析结束 `{ task_id, summary, cancelled }`
+/// - `crossfade-cancelled`：用户取消了交叉淡化 `{ from_song_id, to_song_id, keep }`，前端混音器停掉另一首
+/// - `file-quarantined`：文件被隔离 `{ path }`
+/// - `system-resumed`：系统从休眠中唤醒 `{ slept_secs, pause }`，前端据此重建音频输出
+/// - `app://ready/<subsystem>`：子系统完成启动预热 `{ subsystem, error }`（`settings` / `sources` / `library`）
+/// - `app://ready`：所有子系统结束启动预热
+/// - `p2p-event`：P2P 事件（负载为 `P2pEvent` 本身）
+fn spawn_event_bridge(app: AppHandle, ctx: &AppContext) {
+    let mut rx = ctx.events.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(n)) => {
+                    eprintln!("[event_bridge] 前端事件积压，丢弃 {} 条", n);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let _ = match &event {
+                AppEvent::LibraryChanged => app.emit("library-changed", ()),
+                AppEvent::LibraryDiff(diff) => app.emit("library-diff", diff),
+                AppEvent::MetadataReadProgress { .. } => app.emit("metadata-read-progress", &event),
+                AppEvent::ScanProgress(progress) => app.emit("scan://progress", progress),
+                AppEvent::ScanFinished { .. } => app.emit("scan://finished", &event),
+                AppEvent::AnalysisProgress { .. } => app.emit("analysis-progress", &event),
+                AppEvent::AnalysisFinished { .. } => app.emit("analysis-finished", &event),
+                AppEvent::RenderProgress { .. } => app.emit("render-progress", &event),
+                AppEvent::CrossfadeCancelled { .. } => app.emit("crossfade-cancelled", &event),
+                AppEvent::FileQuarantined { path } => {
+                    app.emit("file-quarantined", serde_json::json!({ "path": path }))
+                }
+                AppEvent::SystemResumed { .. } => app.emit("system-resumed", &event),
+                AppEvent::SubsystemReady { subsystem, .. } => {
+                    app.emit(&format!("app://ready/{}", subsystem.as_str()), &event)
+                }
+                AppEvent::AppReady => app.emit("app://ready", ()),
+                AppEvent::P2p(evt) => app.emit("p2p-event", evt),
+            };
+        }
+    });
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let builder = tauri::Builder::default()
+    let builder = tauri::Builder::default();
+
+    // 单实例必须最先注册：再次启动（双击文件 / 点击链接）时把参数转发给已运行的进程
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+        let requests = launch::parse_file_args(argv.iter().skip(1));
+        launch::forward(app, requests.into_iter().collect());
+    }));
+
+    let builder = builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_notification::init());
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init());
 
     #[cfg(not(any(target_os = "android", target_os = "ios")))]
     let builder = builder.plugin(tauri_plugin_window_state::Builder::default().build());
@@ -35,18 +102,40 @@ pub fn run() {
             // 媒体协议桥接：注入来源注册器
             media_protocol::init(ctx.registrar.clone());
 
-            // P2P 事件桥接：core 的 mpsc → Tauri 前端事件
-            let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel::<P2pEvent>();
-            ctx.p2p.set_event_channel(event_tx);
-            let app_handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                while let Some(evt) = event_rx.recv().await {
-                    let _ = app_handle.emit("p2p-event", &evt);
-                }
-            });
+            // 事件桥接：core 事件总线 → Tauri 前端事件
+            spawn_event_bridge(app.handle().clone(), &ctx);
+
+            // 启动预热（后台扫描本地文件夹），就绪事件经上面的桥接推给前端
+            ctx.warm_up();
 
             // 注入为 Tauri State，供各命令通过 State<'_, Arc<AppContext>> 提取
             app.manage(ctx);
+
+            // 启动请求：文件关联参数 + 启动时的深链接，暂存等前端取走
+            let mut pending: Vec<_> = launch::parse_file_args(std::env::args().skip(1))
+                .into_iter()
+                .collect();
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                pending.extend(urls.iter().filter_map(|url| launch::parse_deep_link(url.as_str())));
+            }
+            app.manage(launch::PendingLaunch::new(pending));
+
+            // 开发构建未经安装器注册 scheme，运行时临时注册
+            #[cfg(all(debug_assertions, any(windows, target_os = "linux")))]
+            if let Err(e) = app.deep_link().register_all() {
+                eprintln!("[launch] 注册 chordial:// 深链接失败: {}", e);
+            }
+
+            // 运行中收到的深链接直接推给前端
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                let requests = event
+                    .urls()
+                    .iter()
+                    .filter_map(|url| launch::parse_deep_link(url.as_str()))
+                    .collect();
+                launch::forward(&handle, requests);
+            });
             Ok(())
         })
         .register_asynchronous_uri_scheme_protocol("chordial", |_ctx, request, responder| {
@@ -106,6 +195,17 @@ pub fn run() {
             commands::local_remove_folder,
             commands::local_get_folders,
             commands::local_rescan,
+            commands::start_scan,
+            commands::cancel_scan,
+            commands::get_scan_status,
+            commands::get_quarantined_files,
+            commands::local_retry_quarantined,
+            commands::local_read_metadata,
+            commands::cancel_task,
+            // Session — 临时播放（不入库）+ 启动请求
+            commands::session_open_files,
+            commands::session_play_folder,
+            commands::launch_take_requests,
             // MusicLibrary — 持久化
             commands::library_save,
             commands::library_cleanup_empty_entities,
@@ -116,6 +216,10 @@ pub fn run() {
             commands::library_get_all_songs,
             commands::library_get_songs_page,
             commands::library_search_songs,
+            commands::library_set_display_language,
+            commands::library_get_display_language,
+            commands::library_set_group_by,
+            commands::library_get_group_by,
             // MusicLibrary — Artist CRUD + 搜索
             commands::library_artist_count,
             commands::library_get_artist,
@@ -123,6 +227,8 @@ pub fn run() {
             commands::library_get_all_artists,
             commands::library_get_artists_page,
             commands::library_search_artists,
+            commands::set_artist_bio,
+            commands::set_artist_genres,
             // MusicLibrary — Album CRUD + 搜索
             commands::library_album_count,
             commands::library_get_album,
@@ -132,6 +238,7 @@ pub fn run() {
             commands::library_search_albums,
             // MusicLibrary — 统一搜索（trigram 倒排索引）
             commands::library_search,
+            commands::search_library,
             // MusicLibrary — Home
             commands::library_get_home_stats,
             // MusicLibrary — Lyric CRUD + 搜索
@@ -144,7 +251,10 @@ pub fn run() {
             commands::library_get_album_of_song,
             commands::library_get_lyric_of_song,
             commands::library_get_songs_by_artist,
+            commands::library_get_lossless_songs,
+            commands::library_get_song_languages,
             commands::library_get_albums_by_artist,
+            commands::library_get_related_artists,
             commands::library_get_songs_in_album,
             commands::library_get_source_ids_of_song,
             // P2P 资源共享
@@ -161,7 +271,205 @@ pub fn run() {
             commands::p2p_add_trusted,
             commands::p2p_remove_trusted,
             commands::p2p_get_match_payload,
+            // Playback — 播放设置
+            commands::playback_get_settings,
+            commands::set_time_stretch_quality,
+            commands::playback_stretch_params,
+            commands::playback_rate_presets,
+            commands::playback_get_rate,
+            commands::set_playback_rate,
+            commands::playback_set_silence_skip,
+            commands::playback_get_silence_map,
+            commands::playback_set_karaoke,
+            commands::playback_set_preload,
+            commands::set_audio_volume,
+            commands::get_audio_volume,
+            commands::playback_set_volume_curve,
+            commands::playback_set_dither,
+            commands::playback_set_fades,
+            commands::playback_fade_plan,
+            commands::playback_set_crossfade,
+            commands::playback_crossfade_curve,
+            commands::playback_set_playlist_transition,
+            commands::playback_crossfade_plan,
+            commands::playback_crossfade_started,
+            commands::get_crossfade_status,
+            commands::cancel_crossfade,
+            commands::record_transition,
+            commands::get_transition_history,
+            commands::clear_transition_history,
+            commands::playback_set_skip_step,
+            commands::skip_forward,
+            commands::skip_backward,
+            commands::seek_audio,
+            commands::playback_set_output_latency,
+            commands::playback_get_dsp_profile,
+            commands::playback_set_dsp_profile,
+            commands::playback_set_output_device,
+            commands::playback_copy_dsp_profile,
+            commands::get_audio_position,
+            commands::render_mix,
+            // Analysis — 音频分析
+            commands::get_track_technical_info,
+            commands::analyze_track_transcode,
+            commands::get_track_segments,
+            commands::get_vocal_segments,
+            commands::analyze_library_transcodes,
+            commands::library_get_suspected_transcodes,
+            commands::get_track_energy,
+            commands::analyze_library_energy,
+            commands::library_get_songs_by_energy,
+            // Lyrics providers — 歌词提供方
+            commands::lyrics_get_providers,
+            commands::lyrics_set_provider_enabled,
+            commands::lyrics_search,
+            commands::lyrics_fetch,
+            // Metadata providers — 元数据补全
+            commands::metadata_get_providers,
+            commands::metadata_enrich,
+            commands::metadata_get_provenance,
+            commands::metadata_revert_field,
+            commands::metadata_enrich_all,
+            commands::library_edit_song_metadata,
+            commands::write_track_metadata,
+            commands::get_metadata_conflicts,
+            commands::resolve_metadata_conflict,
+            commands::stats_get,
+            commands::stats_set_rating,
+            commands::stats_record_play,
+            commands::stats_set_write_back,
+            commands::stats_get_write_back,
+            commands::library_edit_songs_metadata,
+            commands::library_remove_songs,
+            commands::library_hide_tracks,
+            commands::library_hide_albums,
+            commands::library_get_hidden,
+            commands::library_restore_hidden,
+            commands::get_change_history,
+            commands::undo_last_change,
+            // Preload — 队列预加载 / 媒体缓存
+            commands::preload_set_queue,
+            commands::preload_get_status,
+            commands::media_cache_get_size,
+            commands::media_cache_clear,
+            commands::playback_set_pcm_cache,
+            commands::pcm_cache_get_size,
+            commands::pcm_cache_clear,
+            commands::cache_get_location,
+            commands::cache_relocate,
+            // Source write-back — 来源写回
+            commands::source_get_entries,
+            commands::source_set_read_only,
+            commands::source_set_metadata_only,
+            commands::source_put_lyric_text,
+            commands::source_put_album_picture,
+            // Access audit — 资源访问审计
+            commands::source_get_access_log,
+            commands::source_clear_access_log,
+            // Album / artist playback — 播放专辑 / 艺人
+            commands::play_album,
+            commands::play_artist,
+            commands::activate_track,
+            commands::set_activate_action,
+            commands::set_endofqueue_behavior,
+            commands::playback_end_of_queue,
+            commands::queue_get,
+            commands::queue_set,
+            commands::queue_add,
+            commands::queue_remove,
+            commands::queue_next,
+            commands::queue_previous,
+            commands::queue_jump,
+            commands::queue_set_repeat_mode,
+            commands::queue_set_shuffle,
+            commands::queue_report_position,
+            commands::set_pause_on_suspend,
+            commands::set_null_output,
+            commands::playback_get_output_mode,
+            // Transcoded streams — 转码流
+            commands::source_set_transcode,
+            commands::source_set_metered_network,
+            // Batch operations — 多选批量操作
+            commands::queue_add_many,
+            commands::add_tracks_to_playlist,
+            commands::batch_set_rating,
+            commands::batch_analyze,
+            // Scan extensions — 扫描扩展名
+            commands::local_get_extensions,
+            commands::local_set_extension_alias,
+            commands::local_set_folder_extensions,
+            commands::local_get_folder_access,
+            commands::local_set_folder_access,
+            commands::local_set_ogg_chain_tags,
+            commands::local_get_ogg_chain_tags,
+            // Audiobooks — 有声书
+            commands::library_get_books,
+            commands::book_get_chapters,
+            commands::book_save_progress,
+            commands::book_get_progress,
+            commands::book_clear_progress,
+            commands::playlist_get_tree,
+            commands::create_playlist,
+            commands::playlist_create_folder,
+            commands::playlist_rename,
+            commands::remove_playlist,
+            commands::playlist_move,
+            commands::playlist_reorder,
+            commands::playlist_get_songs,
+            commands::reorder_playlist,
+            commands::export_playlist,
+            commands::import_playlist,
+            commands::playlist_create_smart,
+            commands::playlist_set_rules,
+            commands::playlist_preview_smart,
+            commands::playlist_materialize,
+            // Library snapshots — 音乐库快照
+            commands::library_get_recovery_report,
+            commands::library_list_snapshots,
+            commands::library_set_snapshot_retain,
+            // Track gain — 单曲增益
+            commands::set_track_gain_db,
+            commands::playback_track_gain,
+            commands::playback_gain_preview,
+            // Power policy — 电源策略
+            commands::set_power_policy,
+            commands::power_get_status,
+            commands::app_get_diagnostics,
+            // Totals — 专辑 / 艺人汇总
+            commands::library_get_album_totals,
+            commands::library_get_artist_totals,
+            commands::library_rebuild_totals,
+            // Track versions — 同曲多版本
+            commands::get_track_versions,
+            commands::link_track_versions,
+            commands::unlink_track_versions,
+            // Album art — 封面导出
+            commands::export_album_art,
+            commands::get_album_thumbnail,
+            commands::get_track_preview,
+            commands::prewarm_covers,
+            // Readiness — 启动就绪
+            commands::app_get_readiness,
+            commands::app_get_startup_mode,
+            // Content hash — 内容哈希
+            commands::library_hash_songs,
+            commands::library_verify_content_hash,
+            commands::library_find_exact_duplicates,
+            commands::content_hash_set_enabled,
+            commands::content_hash_get_enabled,
+            // Decode stats — 播放质量
+            commands::get_decode_stats,
+            // Now playing — 正在播放页
+            commands::get_now_playing_bundle,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // 退出前落盘写回窗口内的修改
+            if let tauri::RunEvent::Exit = event {
+                if let Some(ctx) = app.try_state::<Arc<AppContext>>() {
+                    ctx.shutdown();
+                }
+            }
+        });
 }
@@ -0,0 +1,346 @@
+//! 命令层集成测试 — 以 Tauri `MockRuntime` 构建应用，经 IPC 调用 [`commands`](crate::commands)。
+//!
+//! [`TestApp`] 在独立的临时数据目录上构建 [`AppContext`]（缓存、音乐库、配置都落在该目录，结束时删除），
+//! 注册一个内存中的假网络来源 [`FakeSource`]，并把启动预热标记为已完成，列表类命令不必等待扫描。
+//! 调用走完整的参数反序列化与返回值序列化，能发现命令签名与前端约定不一致的问题；
+//! 并发用例在多个线程上同时调用命令，锁顺序出错时以超时失败而不是卡住 CI。
+//!
+//! 测试进程设置 `CHORDIAL_AUDIO_OUTPUT=null`，输出方式固定为空输出（见 [`null_output`]），
+//! CI 与无声卡的机器上行为一致。
+
+use crate::commands;
+use chordial_core::module::music_library::models::{Album, Artist, Lyric, Song};
+use chordial_core::module::music_source::traits::MusicSource;
+use chordial_core::module::music_source::types::{EntityType, SourceId, SourceType};
+use chordial_core::module::playback::null_output;
+use chordial_core::module::readiness::Subsystem;
+use chordial_core::AppContext;
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Once};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{App, WebviewWindow, WebviewWindowBuilder};
+
+/// 假来源的名称。
+pub const FAKE_SOURCE: &str = "fake";
+
+/// 内存中的假网络来源：音频为预先放入的字节，其余资源一律不存在。
+#[derive(Default)]
+pub struct FakeSource {
+    files: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl FakeSource {
+    pub fn put_file(&self, entity_id: &str, data: Vec<u8>) {
+        self.files.write().insert(entity_id.to_string(), data);
+    }
+}
+
+impl MusicSource for FakeSource {
+    fn name(&self) -> &str {
+        FAKE_SOURCE
+    }
+
+    fn source_type(&self) -> SourceType {
+        SourceType::Web(FAKE_SOURCE.to_string())
+    }
+
+    fn search_songs(&self, _query: &str) -> Result<Vec<Song>, String> {
+        Ok(Vec::new())
+    }
+
+    fn get_song(&self, _id: &str) -> Result<Option<Song>, String> {
+        Ok(None)
+    }
+
+    fn get_artist(&self, _id: &str) -> Result<Option<Artist>, String> {
+        Ok(None)
+    }
+
+    fn get_album(&self, _id: &str) -> Result<Option<Album>, String> {
+        Ok(None)
+    }
+
+    fn get_lyric(&self, _song_id: &str) -> Result<Option<Lyric>, String> {
+        Ok(None)
+    }
+
+    fn song_file_get(&self, entity_id: &str) -> Result<Vec<u8>, String> {
+        self.files
+            .read()
+            .get(entity_id)
+            .cloned()
+            .ok_or_else(|| format!("文件 '{}' 不存在", entity_id))
+    }
+
+    fn album_picture_get(&self, entity_id: &str) -> Result<Vec<u8>, String> {
+        Err(format!("专辑 '{}' 没有封面", entity_id))
+    }
+
+    fn lyric_text_get(&self, song_id: &str) -> Result<String, String> {
+        Err(format!("歌曲 '{}' 没有歌词", song_id))
+    }
+}
+
+/// 测试用应用：MockRuntime 应用 + 主窗口 + 独立数据目录上的 [`AppContext`]。
+pub struct TestApp {
+    pub ctx: Arc<AppContext>,
+    pub source: Arc<FakeSource>,
+    client: Client,
+    dir: PathBuf,
+    _app: App<MockRuntime>,
+}
+
+/// 经主窗口 IPC 调用命令的句柄；可克隆到其他线程并发调用。
+#[derive(Clone)]
+pub struct Client {
+    webview: WebviewWindow<MockRuntime>,
+}
+
+impl Client {
+    /// 调用命令；`args` 的键与前端一致（camelCase）。
+    pub fn invoke<T: DeserializeOwned>(&self, cmd: &str, args: Value) -> Result<T, String> {
+        let request = InvokeRequest {
+            cmd: cmd.into(),
+            callback: CallbackFn(0),
+            error: CallbackFn(1),
+            url: "http://tauri.localhost".parse().unwrap(),
+            body: InvokeBody::Json(args),
+            headers: Default::default(),
+            invoke_key: INVOKE_KEY.to_string(),
+        };
+        match tauri::test::get_ipc_response(&self.webview, request) {
+            Ok(body) => body.deserialize::<T>().map_err(|e| format!("反序列化返回值失败: {}", e)),
+            Err(Value::String(e)) => Err(e),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "chordial_commands_{}_{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        static NULL_OUTPUT: Once = Once::new();
+        NULL_OUTPUT.call_once(|| std::env::set_var(null_output::ENV_VAR, null_output::DEVICE_ID));
+
+        let ctx = Arc::new(AppContext::new(dir.clone()).unwrap());
+        let source = Arc::new(FakeSource::default());
+        ctx.registrar.register(source.clone()).unwrap();
+        ctx.readiness.mark_ready(Subsystem::Sources);
+        ctx.readiness.mark_ready(Subsystem::Library);
+
+        let app = mock_builder()
+            .manage(ctx.clone())
+            .invoke_handler(tauri::generate_handler![
+                commands::config_get,
+                commands::config_set,
+                commands::get_song_file,
+                commands::library_get_song,
+                commands::library_get_all_songs,
+                commands::library_hide_tracks,
+                commands::library_get_hidden,
+                commands::library_restore_hidden,
+                commands::undo_last_change,
+                commands::get_scan_status,
+                commands::create_playlist,
+                commands::add_tracks_to_playlist,
+                commands::queue_get,
+                commands::set_null_output,
+                commands::playback_get_output_mode,
+            ])
+            .build(mock_context(noop_assets()))
+            .unwrap();
+        let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+        Self {
+            ctx,
+            source,
+            client: Client { webview },
+            dir,
+            _app: app,
+        }
+    }
+
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// 经 IPC 调用命令，见 [`Client::invoke`]。
+    pub fn invoke<T: DeserializeOwned>(&self, cmd: &str, args: Value) -> Result<T, String> {
+        self.client.invoke(cmd, args)
+    }
+
+    /// 向库中加入一首来自假来源的歌曲，返回库内 ID。
+    pub fn add_song(&self, title: &str, artist: &str, album: &str) -> String {
+        let song: Song = serde_json::from_value(json!({
+            "id": format!("fake-{}", title),
+            "title": title,
+            "artist_names": [artist],
+            "album_title": album,
+            "duration": 180,
+            "artist_ids": [],
+            "album_id": null,
+            "lyric_id": null,
+            "source_ids": [self.source_id(title)],
+        }))
+        .unwrap();
+        self.ctx.library.add_song(&song).unwrap()
+    }
+
+    /// 假来源中某首歌的来源 ID（实体 ID 即标题）。
+    pub fn source_id(&self, title: &str) -> SourceId {
+        SourceId::new(FAKE_SOURCE, SourceType::Web(FAKE_SOURCE.to_string()), EntityType::Song, title)
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        self.ctx.shutdown();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn visible_ids(client: &Client) -> Vec<String> {
+        let songs: HashMap<String, Value> = client.invoke("library_get_all_songs", json!({})).unwrap();
+        let mut ids: Vec<String> = songs.into_keys().collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_config_round_trips_through_ipc() {
+        let app = TestApp::new();
+        app.invoke::<()>("config_set", json!({ "key": "theme", "value": { "dark": true } }))
+            .unwrap();
+        let value: Value = app.invoke("config_get", json!({ "key": "theme" })).unwrap();
+        assert_eq!(value, json!({ "dark": true }));
+        assert!(app.invoke::<Value>("config_get", json!({ "key": "missing" })).is_err());
+        // 缺少参数时由 Tauri 拒绝，不会以默认值调用命令
+        assert!(app.invoke::<Value>("config_get", json!({})).is_err());
+    }
+
+    #[test]
+    fn test_fake_source_serves_song_files() {
+        let app = TestApp::new();
+        app.source.put_file("intro", vec![1, 2, 3]);
+        let source_id_json = serde_json::to_string(&app.source_id("intro")).unwrap();
+        let data: Vec<u8> = app
+            .invoke("get_song_file", json!({ "sourceIdJson": source_id_json }))
+            .unwrap();
+        assert_eq!(data, vec![1, 2, 3]);
+
+        let missing = serde_json::to_string(&app.source_id("outro")).unwrap();
+        assert!(app.invoke::<Vec<u8>>("get_song_file", json!({ "sourceIdJson": missing })).is_err());
+    }
+
+    #[test]
+    fn test_hide_restore_and_undo() {
+        let app = TestApp::new();
+        let a = app.add_song("a", "Artist", "Album");
+        let b = app.add_song("b", "Artist", "Album");
+        assert_eq!(visible_ids(&app.client()).len(), 2);
+
+        let hidden: usize = app.invoke("library_hide_tracks", json!({ "songIds": [a] })).unwrap();
+        assert_eq!(hidden, 1);
+        assert_eq!(visible_ids(&app.client()), vec![b.clone()]);
+        let items: Value = app.invoke("library_get_hidden", json!({})).unwrap();
+        assert_eq!(items["songs"][0]["id"], json!(a));
+        assert!(items["songs"][0]["hidden_at"].is_u64());
+
+        let restored: usize = app
+            .invoke("library_restore_hidden", json!({ "songIds": [a], "albumIds": null }))
+            .unwrap();
+        assert_eq!(restored, 1);
+        assert_eq!(visible_ids(&app.client()).len(), 2);
+
+        app.invoke::<usize>("library_hide_tracks", json!({ "songIds": [b] })).unwrap();
+        let undone: Option<Value> = app.invoke("undo_last_change", json!({})).unwrap();
+        assert!(undone.is_some());
+        assert_eq!(visible_ids(&app.client()).len(), 2);
+    }
+
+    #[test]
+    fn test_add_tracks_to_playlist_leaves_queue_alone() {
+        let app = TestApp::new();
+        let a = app.add_song("a", "Artist", "Album");
+        let b = app.add_song("b", "Artist", "Album");
+        let playlist: Value = app.invoke("create_playlist", json!({ "name": "Mix", "folderId": null })).unwrap();
+        let id = playlist["id"].as_str().unwrap();
+
+        let result: Value = app
+            .invoke("add_tracks_to_playlist", json!({ "playlistId": id, "trackIds": [a, "missing", b, a] }))
+            .unwrap();
+        assert_eq!(result["playlist"]["song_ids"], json!([a, b]));
+        assert_eq!(result["report"]["succeeded"], json!([a, b]));
+        assert_eq!(result["report"]["failed"][0][0], json!("missing"));
+
+        let queue: Value = app.invoke("queue_get", json!({})).unwrap();
+        assert_eq!(queue["songs"], json!([]));
+        assert!(app
+            .invoke::<Value>("add_tracks_to_playlist", json!({ "playlistId": "nope", "trackIds": [a] }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_output_mode_is_forced_to_null() {
+        let app = TestApp::new();
+        let mode: Value = app.invoke("playback_get_output_mode", json!({})).unwrap();
+        assert_eq!(mode, json!({ "null_output": true, "forced_by_env": true }));
+
+        // 设置中的开关照常保存，但环境变量强制时不影响生效的输出方式
+        let settings: Value = app.invoke("set_null_output", json!({ "enabled": true })).unwrap();
+        assert_eq!(settings["null_output"], json!(true));
+        let settings: Value = app.invoke("set_null_output", json!({ "enabled": false })).unwrap();
+        assert_eq!(settings["null_output"], json!(false));
+        assert!(!app.ctx.playback.settings().null_output);
+        let mode: Value = app.invoke("playback_get_output_mode", json!({})).unwrap();
+        assert_eq!(mode["null_output"], json!(true));
+    }
+
+    #[test]
+    fn test_concurrent_commands_do_not_deadlock() {
+        let app = TestApp::new();
+        let ids: Vec<String> = (0..20).map(|i| app.add_song(&format!("s{}", i), "Artist", "Album")).collect();
+
+        // 每个线程只操作自己的 5 首歌，结束时应全部恢复可见
+        let (done_tx, done_rx) = mpsc::channel();
+        for worker in 0..4 {
+            let (client, ids, done_tx) = (app.client(), ids.clone(), done_tx.clone());
+            std::thread::spawn(move || {
+                for round in 0..10 {
+                    let id = &ids[worker * 5 + round % 5];
+                    client.invoke::<usize>("library_hide_tracks", json!({ "songIds": [id] })).unwrap();
+                    let _ = visible_ids(&client);
+                    client.invoke::<Value>("get_scan_status", json!({})).unwrap();
+                    client.invoke::<usize>("library_restore_hidden", json!({ "songIds": [id] })).unwrap();
+                }
+                done_tx.send(()).unwrap();
+            });
+        }
+        for _ in 0..4 {
+            done_rx
+                .recv_timeout(Duration::from_secs(30))
+                .expect("命令并发调用超时，可能存在锁顺序问题");
+        }
+        assert_eq!(visible_ids(&app.client()).len(), ids.len());
+    }
+}
@@ -181,6 +181,14 @@ export function startAudioAnalyser(store, atoms) {
 	startLoop(store, atoms)
 }
 
+/**
+ * 输出设备采样率；AudioContext 尚未创建（从未打开过播放页）时为 null。
+ * @returns {number | null}
+ */
+export function getOutputSampleRate() {
+	return audioCtx?.sampleRate ?? null
+}
+
 /** 停止音频分析（PlayerView 卸载时调用） */
 export function stopAudioAnalyser() {
 	stopLoop()
@@ -4,7 +4,7 @@
  * 所有返回值通过 {@link Album|AlbumSummary} 类包装。
  */
 import { transport } from '@/api/transport';
-import { Album, AlbumSummary } from '@/class';
+import { Album, AlbumSummary, Song } from '@/class';
 
 /**
  * 获取专辑完整信息。
@@ -103,3 +103,34 @@ export async function getAlbumArtUrl(albumId) {
   }
   return '';
 }
+
+/**
+ * 播放专辑：后端按碟号 / 音轨号（或随机）排好队列并开始预加载。
+ * @param {string} albumId
+ * @param {{ startTrackId?: string, shuffle?: boolean }} [options] - 随机播放时起始歌曲固定在队首
+ * @returns {Promise<{ songs: Song[], startIndex: number }>}
+ */
+export async function playAlbum(albumId, { startTrackId = null, shuffle = false } = {}) {
+  const data = await transport.command('play_album', { albumId, startTrackId, shuffle });
+  return { songs: data.songs.map((d) => new Song(d)), startIndex: data.start_index };
+}
+
+/**
+ * 专辑汇总：曲目数、总时长（秒）、总文件大小（字节）。
+ * @param {string} albumId
+ * @returns {Promise<{ track_count: number, duration_secs: number, size_bytes: number }>}
+ */
+export async function getAlbumTotals(albumId) {
+  return transport.command('library_get_album_totals', { albumId });
+}
+
+/**
+ * 把专辑封面导出为图片文件（如各专辑文件夹的 folder.jpg）。
+ * @param {string} albumId
+ * @param {string} path 目标文件路径
+ * @param {{ size?: number, format?: 'jpeg' | 'png' }} [options] size 为长边像素上限（只缩小）；format 省略时按扩展名判断
+ * @returns {Promise<{ path: string, origin: object, format: string, width: number, height: number, bytes: number, reencoded: boolean }>}
+ */
+export async function exportAlbumArt(albumId, path, { size = null, format = null } = {}) {
+  return transport.command('export_album_art', { albumId, path, size, format });
+}
@@ -4,7 +4,7 @@
  * 所有返回值通过 {@link Artist|ArtistSummary} 类包装。
  */
 import { transport } from '@/api/transport';
-import { Artist, ArtistSummary } from '@/class';
+import { Artist, ArtistSummary, Song } from '@/class';
 
 /**
  * 获取歌手完整信息（自动填充 trackIds / albumIds）。
@@ -73,3 +73,22 @@ export async function getArtistImageUrl(_artistId) {
   // 歌手图片需通过其作品专辑封面或其他 SourceId 获取
   return '';
 }
+
+/**
+ * 播放歌手的全部歌曲：后端按专辑发行时间、专辑内音轨顺序排好队列。
+ * @param {string} artistId
+ * @returns {Promise<{ songs: Song[], startIndex: number }>}
+ */
+export async function playArtist(artistId) {
+  const data = await transport.command('play_artist', { artistId });
+  return { songs: data.songs.map((d) => new Song(d)), startIndex: data.start_index };
+}
+
+/**
+ * 歌手汇总：名下歌曲的曲目数、总时长（秒）、总文件大小（字节）。
+ * @param {string} artistId
+ * @returns {Promise<{ track_count: number, duration_secs: number, size_bytes: number }>}
+ */
+export async function getArtistTotals(artistId) {
+  return transport.command('library_get_artist_totals', { artistId });
+}
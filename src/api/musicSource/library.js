@@ -46,16 +46,27 @@ export async function getAllSongs() {
   return map;
 }
 
+/**
+ * 音质条件；各字段缺省表示不限制，条件之间为「且」。
+ * @typedef {object} QualityFilter
+ * @property {boolean} [lossless_only] - 只要无损
+ * @property {number} [min_bitrate] - 最低平均码率（kbps）
+ * @property {string[]} [formats] - 限定编码，如 ['flac', 'alac']
+ * @property {number} [min_sample_rate] - 最低采样率（Hz）
+ * @property {string[]} [languages] - 限定演唱语言，如 ['ja', 'zh']（'zh' 同时匹配 'zh-Hant'，'zxx' 为纯音乐）
+ */
+
 /**
  * 分页获取歌曲，减少 IPC 载荷。
  * @param {number} offset
  * @param {number} limit
+ * @param {QualityFilter|null} [filter=null] - 音质条件，由后端索引筛选
  * @returns {Promise<{songs: Song[], total: number}>}
  */
-export async function getSongsPage(offset = 0, limit = 50) {
+export async function getSongsPage(offset = 0, limit = 50, filter = null) {
   const [songsData, total] = await Promise.all([
-    transport.command('library_get_songs_page', { offset, limit }),
-    transport.command('library_song_count'),
+    transport.command('library_get_songs_page', { offset, limit, filter }),
+    transport.command('library_song_count', { filter }),
   ]);
   return {
     songs: Song.fromDataArray(songsData),
@@ -96,14 +107,18 @@ export async function getAllArtists() {
 
 /**
  * 分页获取艺术家。
+ *
+ * groupBy 为 'composer' 时返回由歌曲作曲者标签汇总的条目（ID 以 `composer:` 开头，可直接用于艺人详情）；
+ * 省略时按 {@link setGroupBy} 的设置分组。
  * @param {number} offset
  * @param {number} limit
+ * @param {'artist'|'composer'} [groupBy]
  * @returns {Promise<{artists: Artist[], total: number}>}
  */
-export async function getArtistsPage(offset = 0, limit = 50) {
+export async function getArtistsPage(offset = 0, limit = 50, groupBy) {
   const [artistsData, total] = await Promise.all([
-    transport.command('library_get_artists_page', { offset, limit }),
-    transport.command('library_artist_count'),
+    transport.command('library_get_artists_page', { offset, limit, groupBy }),
+    transport.command('library_artist_count', { groupBy }),
   ]);
   return {
     artists: Artist.fromDataArray(artistsData),
@@ -111,6 +126,19 @@ export async function getArtistsPage(offset = 0, limit = 50) {
   };
 }
 
+/**
+ * 设置艺人列表的默认分组方式（持久化）。
+ * @param {'artist'|'composer'} groupBy
+ */
+export async function setGroupBy(groupBy) {
+  return transport.command('library_set_group_by', { groupBy });
+}
+
+/** @returns {Promise<'artist'|'composer'>} */
+export async function getGroupBy() {
+  return transport.command('library_get_group_by');
+}
+
 /** @param {string} query @returns {Promise<Artist[]>} */
 export async function searchArtists(query) {
   const data = await transport.command('library_search_artists', { query });
@@ -207,13 +235,15 @@ export async function searchAlbums(query) {
  * @param {'song'|'artist'|'album'|null} [opts.entityType=null] - 限定实体类型，null 全搜
  * @param {string|null} [opts.sourceName=null] - 限定来源名称，null 不限制
  * @param {number|null} [opts.limitPerType=null] - 每类实体最多返回多少条
+ * @param {QualityFilter|null} [opts.filter=null] - 歌曲的音质条件（不影响艺术家 / 专辑）
  * @returns {Promise<{songs: Song[], artists: Artist[], albums: Album[]}>}
  */
-export async function search({ query, entityType = null, sourceName = null, limitPerType = null }) {
+export async function search({ query, entityType = null, sourceName = null, limitPerType = null, filter = null }) {
   const args = { query };
   if (entityType) args.entity_type = entityType;
   if (sourceName) args.source_name = sourceName;
   if (limitPerType != null) args.limit_per_type = limitPerType;
+  if (filter) args.filter = filter;
   const data = await transport.command('library_search', args);
   return {
     songs: Song.fromDataArray(data.songs || []),
@@ -222,6 +252,48 @@ export async function search({ query, entityType = null, sourceName = null, limi
   };
 }
 
+/**
+ * @typedef {'exact'|'prefix'|'word_prefix'|'substring'|'fuzzy'|'lyrics'} MatchKind
+ */
+
+/**
+ * 即时搜索 — 结果按匹配程度排序（完全相同 > 开头 > 词首 > 中间 > 拼写相近），供搜索框边输入边查询。
+ *
+ * @param {object} opts
+ * @param {string} opts.query - 搜索关键词
+ * @param {Array<'song'|'artist'|'album'|'lyric'>} [opts.types] - 限定实体类型，省略时搜歌曲 / 艺人 / 专辑
+ * @param {string|null} [opts.sourceName=null] - 限定来源名称
+ * @param {boolean} [opts.lyrics=false] - 同时搜索歌词全文
+ * @param {boolean} [opts.fuzzy=true] - 补充拼写相近的结果
+ * @param {number} [opts.limit=20] - 每类最多返回多少条
+ * @param {QualityFilter|null} [opts.filter=null] - 歌曲与歌词命中的音质 / 语言条件
+ * @returns {Promise<{
+ *   songs: Array<{ item: Song, score: number, matched: MatchKind }>,
+ *   artists: Array<{ item: Artist, score: number, matched: MatchKind }>,
+ *   albums: Array<{ item: Album, score: number, matched: MatchKind }>,
+ *   lyrics: Array<{ song: Song, line: string, score: number }>,
+ * }>}
+ */
+export async function searchLibrary({ query, types, sourceName = null, lyrics = false, fuzzy = true, limit, filter = null }) {
+  const args = { query, lyrics, fuzzy };
+  if (types?.length) args.types = types;
+  if (sourceName) args.source_name = sourceName;
+  if (limit != null) args.limit = limit;
+  if (filter) args.filter = filter;
+  const data = await transport.command('search_library', args);
+  const hits = (list, Model) => (list || []).map(({ score, matched, ...item }) => ({
+    item: new Model(item),
+    score,
+    matched,
+  }));
+  return {
+    songs: hits(data.songs, Song),
+    artists: hits(data.artists, Artist),
+    albums: hits(data.albums, Album),
+    lyrics: (data.lyrics || []).map(({ song, line, score }) => ({ song: new Song(song), line, score })),
+  };
+}
+
 // ══════════════════════════════════════════════════════════════════════════════
 // Lyric
 // ══════════════════════════════════════════════════════════════════════════════
@@ -275,9 +347,9 @@ export async function getLyricOfSong(songId) {
   return data ? new Lyric(data) : null;
 }
 
-/** @param {string} artistId @returns {Promise<Song[]>} */
-export async function getSongsByArtist(artistId) {
-  const data = await transport.command('library_get_songs_by_artist', { artistId });
+/** @param {string} artistId @param {QualityFilter|null} [filter] @returns {Promise<Song[]>} */
+export async function getSongsByArtist(artistId, filter = null) {
+  const data = await transport.command('library_get_songs_by_artist', { artistId, filter });
   return Song.fromDataArray(data);
 }
 
@@ -287,9 +359,25 @@ export async function getAlbumsByArtist(artistId) {
   return Album.fromDataArray(data);
 }
 
-/** @param {string} albumId @returns {Promise<Song[]>} */
-export async function getSongsInAlbum(albumId) {
-  const data = await transport.command('library_get_songs_in_album', { albumId });
+/**
+ * 相关艺人 — 由库内的合作曲目与共同专辑计算，按关系强度降序。
+ * @param {string} artistId
+ * @param {number} [limit] - 默认 20
+ * @returns {Promise<{ artist: Artist, weight: number, sharedTracks: number, sharedAlbums: number }[]>}
+ */
+export async function getRelatedArtists(artistId, limit) {
+  const data = await transport.command('library_get_related_artists', { artistId, limit });
+  return (data || []).map((r) => ({
+    artist: new Artist(r.artist),
+    weight: r.weight,
+    sharedTracks: r.shared_tracks,
+    sharedAlbums: r.shared_albums,
+  }));
+}
+
+/** @param {string} albumId @param {QualityFilter|null} [filter] @returns {Promise<Song[]>} */
+export async function getSongsInAlbum(albumId, filter = null) {
+  const data = await transport.command('library_get_songs_in_album', { albumId, filter });
   return Song.fromDataArray(data);
 }
 
@@ -300,6 +388,81 @@ export async function getSourceIdsOfSong(songId) {
   return (data || []).map((s) => new SourceId(s));
 }
 
+// ══════════════════════════════════════════════════════════════════════════════
+// Track versions
+// ══════════════════════════════════════════════════════════════════════════════
+
+/**
+ * 同一首歌的其他版本（现场 / 混音 / 伴奏 / MV 等）。
+ *
+ * 版本类型 kind：'original' | 'remaster' | 'edit' | 'live' | 'acoustic' | 'remix' | 'instrumental' | 'demo' | 'music_video'；
+ * link：'manual'（手动关联）| 'title'（标题匹配）；sameRecording 表示指纹判断为同一录音。
+ *
+ * @param {string} songId
+ * @param {{ compareAudio?: boolean }} [options] - compareAudio 为 false 时跳过指纹比较（首次比较需要解码）
+ * @returns {Promise<{ kind: string, versions: { song: Song, kind: string, link: string, similarity: number|null, sameRecording: boolean }[] }>}
+ */
+export async function getTrackVersions(songId, { compareAudio = true } = {}) {
+  const data = await transport.command('get_track_versions', { trackId: songId, compareAudio });
+  return {
+    kind: data.kind,
+    versions: data.versions.map((v) => ({
+      song: new Song(v.song),
+      kind: v.kind,
+      link: v.link,
+      similarity: v.similarity ?? null,
+      sameRecording: v.same_recording,
+    })),
+  };
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// Hidden
+// ══════════════════════════════════════════════════════════════════════════════
+
+/**
+ * 隐藏歌曲：从列表、搜索和队列中排除，不删除文件。可用 `undo_last_change` 撤销。
+ * @param {string[]} songIds
+ * @returns {Promise<number>} 新隐藏的数量
+ */
+export async function hideTracks(songIds) {
+  const hidden = await transport.command('library_hide_tracks', { songIds });
+  invalidateCache();
+  return hidden;
+}
+
+/**
+ * 隐藏专辑及其全部曲目，行为同 {@link hideTracks}。
+ * @param {string[]} albumIds
+ * @returns {Promise<number>}
+ */
+export async function hideAlbums(albumIds) {
+  const hidden = await transport.command('library_hide_albums', { albumIds });
+  invalidateCache();
+  return hidden;
+}
+
+/**
+ * 「已隐藏」列表，按隐藏时间从新到旧。
+ * @returns {Promise<{ songs: { item: Song, hiddenAt: number }[], albums: { item: Album, hiddenAt: number }[] }>}
+ */
+export async function getHiddenItems() {
+  const data = await transport.command('library_get_hidden');
+  const entries = (list, Model) => list.map(({ hidden_at, ...item }) => ({ item: new Model(item), hiddenAt: hidden_at }));
+  return { songs: entries(data.songs, Song), albums: entries(data.albums, Album) };
+}
+
+/**
+ * 恢复已隐藏的歌曲 / 专辑。
+ * @param {{ songIds?: string[], albumIds?: string[] }} items
+ * @returns {Promise<number>} 实际恢复的数量
+ */
+export async function restoreHidden({ songIds = [], albumIds = [] }) {
+  const restored = await transport.command('library_restore_hidden', { songIds, albumIds });
+  invalidateCache();
+  return restored;
+}
+
 // ══════════════════════════════════════════════════════════════════════════════
 // Memory cache — avoids re-fetching the entire library on every navigation
 // ══════════════════════════════════════════════════════════════════════════════
@@ -354,10 +517,12 @@ export async function getCached(force = false) {
   return _cache;
 }
 
-/** @deprecated use {@link rescanAll} from sources.js */
+/**
+ * @deprecated use {@link rescanAll} from sources.js（返回值中的 `diff` 列出增删改的歌曲 / 专辑）
+ */
 export async function refreshSource(_sourceId) {
   const { rescanAll } = await import('./sources.js');
-  const result = await rescanAll();
+  await rescanAll();
   return Song.fromDataArray([]);
 }
 
@@ -373,6 +538,8 @@ export const library = {
   getArtist,
   getAllArtists,
   getArtistsPage,
+  setGroupBy,
+  getGroupBy,
   searchArtists,
   albumCount,
   getAlbum,
@@ -390,8 +557,14 @@ export const library = {
   getLyricOfSong,
   getSongsByArtist,
   getAlbumsByArtist,
+  getRelatedArtists,
   getSongsInAlbum,
   getSourceIdsOfSong,
+  getTrackVersions,
+  hideTracks,
+  hideAlbums,
+  getHiddenItems,
+  restoreHidden,
   invalidateCache,
   // deprecated
   scanAll,
@@ -66,6 +66,30 @@ export async function getAlbumPicture(sourceId) {
 /** @deprecated 旧名称，使用 {@link getAlbumPicture} */
 export const getAlbumArt = getAlbumPicture;
 
+/**
+ * 获取专辑封面缩略图（JPEG），已预热的直接从缓存读取。
+ *
+ * @param {string} albumId
+ * @param {number} [size=300] - 长边像素
+ * @returns {Promise<ArrayBuffer>}
+ */
+export async function getAlbumThumbnail(albumId, size = 300) {
+  const result = await transport.command('get_album_thumbnail', { albumId, size });
+  return toArrayBuffer(result);
+}
+
+/**
+ * 在后台预先生成一批专辑的缩略图，专辑网格滚动时传入即将可见的专辑。
+ * 新请求会取代尚未处理完的旧请求。
+ *
+ * @param {string[]} albumIds
+ * @param {number} [size=300] - 长边像素，应与 {@link getAlbumThumbnail} 一致
+ * @returns {Promise<number>} 需要生成的数量（已缓存的不计入）
+ */
+export async function prewarmCovers(albumIds, size = 300) {
+  return transport.command('prewarm_covers', { albumIds, size });
+}
+
 /**
  * 获取歌曲的歌词文本。
  *
@@ -163,6 +187,9 @@ export async function getTrackInfo(trackId) {
   return getSong(trackId);
 }
 
+/** 后端 `library_get_songs_by_ids` 单页上限（与 `songs::MAX_IDS_PER_PAGE` 一致） */
+const SONGS_BY_IDS_PAGE = 500;
+
 /** @deprecated 使用 {@link getSongsByIds}（批量 IPC，单次往返） */
 export async function getTracksByIds(trackIds) {
   // 优化：批量命令替代 N 次 IPC 调用
@@ -173,16 +200,25 @@ export async function getTracksByIds(trackIds) {
 /**
  * 批量按 ID 获取歌曲（推荐使用，替代 N 次 getSong）。
  *
- * 优化：使用后端 `library_get_songs_by_ids` 单次 IPC 调用，
- * 替代 N 次 `library_get_song` 的 N×IPC 开销。
+ * 优化：使用后端 `library_get_songs_by_ids` 批量 IPC 调用，
+ * 替代 N 次 `library_get_song` 的 N×IPC 开销。后端每次最多返回
+ * {@link SONGS_BY_IDS_PAGE} 首，更长的列表分段请求。结果顺序与 trackIds 一致，
+ * 重复 ID 只返回一次，库中不存在的 ID 跳过。
  *
  * @param {string[]} trackIds
  * @returns {Promise<object[]>}
  */
 export async function getSongsByIds(trackIds) {
   if (!trackIds?.length) return [];
-  const list = await transport.command('library_get_songs_by_ids', { ids: trackIds });
-  return (list || []).map((d) => new Song(d));
+  const ids = [...new Set(trackIds)];
+  const songs = [];
+  for (let start = 0; start < ids.length; start += SONGS_BY_IDS_PAGE) {
+    const page = await transport.command('library_get_songs_by_ids', {
+      ids: ids.slice(start, start + SONGS_BY_IDS_PAGE),
+    });
+    songs.push(...(page?.songs || []).map((d) => new Song(d)));
+  }
+  return songs;
 }
 
 /** @deprecated 使用 {@link module:src/api/artist.getArtist} */
@@ -200,6 +236,8 @@ export default {
   getSongFile,
   getAlbumPicture,
   getAlbumArt,
+  getAlbumThumbnail,
+  prewarmCovers,
   getLyricText,
   getLyrics,
   parseSyncedLyrics,
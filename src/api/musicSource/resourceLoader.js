@@ -3,10 +3,14 @@
  *
  * 管理音乐资源（音频、封面）的访问。
  * 音频和图片通过 chordial:// 自定义协议流式传输，
- * 无需 Blob + createObjectURL。
+ * 无需 Blob + createObjectURL；只有专辑缩略图按字节返回，需转成 Blob URL。
  */
 
 import { buildAudioUrl, buildImageUrl } from './chordialUrl.js';
+import { getAlbumThumbnail } from './musicResource.js';
+
+/** 专辑缩略图的长边像素，与 prewarmCovers 的默认值一致以命中同一份缓存 */
+export const THUMBNAIL_SIZE = 300;
 
 /**
  * 获取专辑封面资源 URL。
@@ -19,6 +23,19 @@ export async function getAlbumArtResource(sourceId) {
   return { url, release: () => {} };
 }
 
+/**
+ * 获取专辑封面缩略图资源（后端缩放并缓存的 JPEG），`release` 时回收 Blob URL。
+ *
+ * @param {string} albumId
+ * @param {number} [size=THUMBNAIL_SIZE]
+ * @returns {Promise<{url: string, release: Function}>}
+ */
+export async function getAlbumThumbnailResource(albumId, size = THUMBNAIL_SIZE) {
+  const buffer = await getAlbumThumbnail(albumId, size);
+  const url = URL.createObjectURL(new Blob([buffer], { type: 'image/jpeg' }));
+  return { url, release: () => URL.revokeObjectURL(url) };
+}
+
 /**
  * 获取音乐文件资源 URL。
  *
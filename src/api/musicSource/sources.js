@@ -19,7 +19,7 @@ import { transport } from '@/api/transport';
  * 3. 启动文件系统监听
  *
  * @param {string} path - 文件夹绝对路径
- * @returns {Promise<{added: boolean, path: string, files_found: number, indexed: number, errors: string[]}>}
+ * @returns {Promise<{added: boolean, path: string, files_found: number, indexed: number, errors: string[], diff: import('@/bindings/LibraryDiff').LibraryDiff}>}
  */
 export async function addLocalFolder(path) {
   return transport.command('local_add_folder', { path });
@@ -34,7 +34,7 @@ export async function addLocalFolder(path) {
  * 3. 持久化
  *
  * @param {string} path - 文件夹绝对路径
- * @returns {Promise<{removed: boolean, path: string, cleaned_files: number}>}
+ * @returns {Promise<{removed: boolean, path: string, cleaned_files: number, diff: import('@/bindings/LibraryDiff').LibraryDiff}>}
  */
 export async function removeLocalFolder(path) {
   return transport.command('local_remove_folder', { path });
@@ -63,11 +63,57 @@ export async function getLocalStats() {
 }
 
 /**
- * 手动重新扫描所有文件夹（调试用）。
- * @returns {Promise<{indexed: number, folders_scanned: number}>}
+ * 手动重新扫描所有文件夹。
+ *
+ * 默认只重新读取新增和改动过的文件（按 mtime / 大小 / 头部哈希判断）；`forceRescan` 时重新读取全部文件。
+ * `diff` 为本次扫描增删改的歌曲 / 专辑 ID；有变化时后端同时 emit `"library-diff"`。
+ * @param {{ forceRescan?: boolean }} [options]
+ * @returns {Promise<{indexed: number, files_found: number, unchanged: number, changed: number, folders_scanned: number, diff: import('@/bindings/LibraryDiff').LibraryDiff}>}
+ */
+export async function rescanAll({ forceRescan = false } = {}) {
+  return transport.command('local_rescan', { options: { force_rescan: forceRescan } });
+}
+
+/**
+ * 扫描进度（对应 `scan://progress` 事件载荷）。
+ * @typedef {import('@/bindings/ScanProgress').ScanProgress} ScanProgress
+ */
+
+/**
+ * 后台扫描状态。
+ * @typedef {Object} ScanStatus
+ * @property {boolean} running
+ * @property {ScanProgress|null} progress - 进行中时为当前文件夹的进度
+ * @property {import('@/bindings/RescanReport').RescanReport|null} report - 已完成文件夹的累计结果
+ * @property {boolean} cancelled - 最近一次扫描是否被取消
+ * @property {string|null} error
+ */
+
+/**
+ * 在后台重新扫描所有文件夹，立即返回；已有扫描在运行时返回其状态。
+ *
+ * 进度通过 `"scan://progress"` 事件推送，结束时推送 `"scan://finished"`；每扫完一个文件夹另 emit `"library-diff"`。
+ * @param {{ forceRescan?: boolean }} [options]
+ * @returns {Promise<ScanStatus>}
  */
-export async function rescanAll() {
-  return transport.command('local_rescan');
+export async function startScan({ forceRescan = false } = {}) {
+  return transport.command('start_scan', { options: { force_rescan: forceRescan } });
+}
+
+/**
+ * 取消后台扫描（正在读取的文件读完即停，已读取的部分保留在库中）。
+ * @returns {Promise<boolean>} 是否有扫描在运行
+ */
+export async function cancelScan() {
+  return transport.command('cancel_scan');
+}
+
+/**
+ * 获取后台扫描状态。
+ * @returns {Promise<ScanStatus>}
+ */
+export async function getScanStatus() {
+  return transport.command('get_scan_status');
 }
 
 // ══════════════════════════════════════════════════════════════════════════════
@@ -97,3 +143,59 @@ export async function addWebDisk(_url, _username, _password) {
 export async function addWebDev(_apiBaseUrl, _name, _apiKey, _authToken) {
   throw new Error('WebDAV 来源后端尚未实现');
 }
+
+// ══════════════════════════════════════════════════════════════════════════════
+// Source Write-back
+// ══════════════════════════════════════════════════════════════════════════════
+
+/**
+ * 列出已注册来源的条目（含只读开关 `read_only` 与仅元数据开关 `metadata_only`）。
+ * @returns {Promise<Array<{name: string, source_type: any, read_only: boolean, metadata_only?: boolean}>>}
+ */
+export async function getSourceEntries() {
+  return transport.command('source_get_entries');
+}
+
+/**
+ * 设置来源是否只读。关闭只读且来源支持写回时，本地的标签编辑会同步写回该来源。
+ * @param {string} name - 来源名称
+ * @param {boolean} readOnly
+ */
+export async function setSourceReadOnly(name, readOnly) {
+  return transport.command('source_set_read_only', { name, readOnly });
+}
+
+/**
+ * 设置来源是否仅元数据：开启后其中的歌曲照常索引与显示，但不能播放。
+ * @param {string} name - 来源名称
+ * @param {boolean} metadataOnly
+ */
+export async function setSourceMetadataOnly(name, metadataOnly) {
+  return transport.command('source_set_metadata_only', { name, metadataOnly });
+}
+
+/**
+ * 告知后端当前网络是否计费（如移动数据），计费时改用各来源的 `metered_transcode`。
+ * @param {boolean} metered
+ */
+export async function setMeteredNetwork(metered) {
+  return transport.command('source_set_metered_network', { metered });
+}
+
+/**
+ * 各音乐文件夹的访问设置。
+ * @returns {Promise<Array<{ folder: string, read_only?: boolean, metadata_only?: boolean }>>}
+ */
+export async function getLocalFolderAccess() {
+  return transport.command('local_get_folder_access');
+}
+
+/**
+ * 设置音乐文件夹的访问方式：只读时不向其中的文件写入标签 / 评分，仅元数据时其中的歌曲照常显示但不能播放。
+ * @param {string} folder - 已添加的音乐文件夹
+ * @param {{ readOnly?: boolean, metadataOnly?: boolean }} access
+ * @returns {Promise<Array<{ folder: string, read_only?: boolean, metadata_only?: boolean }>>} 更新后的全部设置
+ */
+export async function setLocalFolderAccess(folder, { readOnly = false, metadataOnly = false } = {}) {
+  return transport.command('local_set_folder_access', { folder, readOnly, metadataOnly });
+}
@@ -0,0 +1,313 @@
+/**
+ * 播放相关 API — 后端 playback_* 命令
+ */
+import { transport } from '@/api/transport';
+import { Song, Album, Artist } from '@/class';
+
+/**
+ * 获取播放设置。
+ * @returns {Promise<object>}
+ */
+export async function getPlaybackSettings() {
+  return transport.command('playback_get_settings');
+}
+
+/**
+ * 队列播完后的行为。
+ * @enum {string}
+ */
+export const EndOfQueueBehavior = {
+  STOP: 'stop',                  // 停止播放
+  REPEAT_QUEUE: 'repeat_queue',  // 从队首重新播放
+  AUTOPLAY: 'autoplay',          // 自动续播相似歌曲
+};
+
+/**
+ * 设置队列播完后的行为。
+ * @param {string} behavior - {@link EndOfQueueBehavior}
+ * @returns {Promise<object>} 更新后的播放设置
+ */
+export async function setEndOfQueueBehavior(behavior) {
+  return transport.command('set_endofqueue_behavior', { behavior });
+}
+
+/**
+ * 激活（双击 / 回车）一首歌时的行为，可按界面分别设置。
+ * @enum {string}
+ */
+export const ActivateAction = {
+  REPLACE: 'replace',          // 以所在列表替换队列并从这首开始
+  APPEND: 'append',            // 追加到队尾
+  INSERT_NEXT: 'insert_next',  // 插到当前歌曲之后
+};
+
+/**
+ * 激活一首歌，由后端按设置决定如何处理队列。
+ * @param {string} trackId
+ * @param {{ kind: 'album', album_id: string } | { kind: 'artist', artist_id: string }
+ *   | { kind: 'playlist', playlist_id: string } | { kind: 'search' | 'library', song_ids: string[] }} context - 歌曲所在的列表
+ * @returns {Promise<{ action: string, songs: Song[], startIndex: number }>}
+ *   `replace` 时 `songs` 为新队列；其余只含这首歌
+ */
+export async function activateTrack(trackId, context) {
+  const data = await transport.command('activate_track', { trackId, context });
+  return {
+    action: data.action,
+    songs: data.queue.songs.map((d) => new Song(d)),
+    startIndex: data.queue.start_index,
+  };
+}
+
+/**
+ * 设置某一界面中激活歌曲时的行为。
+ * @param {'album'|'artist'|'playlist'|'search'|'library'} surface
+ * @param {string} action - {@link ActivateAction}
+ * @returns {Promise<object>} 更新后的播放设置
+ */
+export async function setActivateAction(surface, action) {
+  return transport.command('set_activate_action', { surface, action });
+}
+
+/**
+ * 循环模式。
+ * @enum {string}
+ */
+export const RepeatMode = {
+  OFF: 'off',  // 不循环
+  ALL: 'all',  // 整个队列循环
+  ONE: 'one',  // 单曲循环
+};
+
+/** @param {object} data 后端返回的队列 */
+function toQueue(data) {
+  return {
+    songs: data.songs.map((d) => new Song(d)),
+    currentIndex: data.current_index,
+    repeat: data.repeat,
+    shuffle: data.shuffle,
+    playlistId: data.playlist_id,
+    hasHistory: data.has_history,
+  };
+}
+
+/** @param {object} data 后端返回的切歌结果 */
+function toQueueStep(data) {
+  return { song: data.song ? new Song(data.song) : null, queue: toQueue(data.queue) };
+}
+
+/**
+ * 获取正在播放的队列（由后端维护）。
+ * @returns {Promise<{ songs: Song[], currentIndex: number|null, repeat: string, shuffle: boolean, playlistId: string|null, hasHistory: boolean }>}
+ */
+export async function getQueue() {
+  return toQueue(await transport.command('queue_get'));
+}
+
+/**
+ * 用一组歌曲替换队列并从 `startIndex` 开始播放。
+ * @param {string[]} songIds
+ * @param {number} [startIndex=0]
+ * @param {string} [playlistId] - 队列来自的播放列表（决定交叉淡化 / 无缝衔接）
+ * @returns {Promise<{ song: Song|null, queue: object }>}
+ */
+export async function setQueue(songIds, startIndex = 0, playlistId = null) {
+  return toQueueStep(await transport.command('queue_set', { songIds, startIndex, playlistId }));
+}
+
+/**
+ * 加入队列。
+ * @param {string[]} songIds
+ * @param {boolean} [next=false] - 插到当前歌曲之后，而不是追加到队尾
+ */
+export async function addToQueue(songIds, next = false) {
+  return toQueue(await transport.command('queue_add', { songIds, next }));
+}
+
+/**
+ * 从队列中移除一首（不能是正在播放的歌曲）。
+ * @param {number} index
+ */
+export async function removeFromQueue(index) {
+  return toQueue(await transport.command('queue_remove', { index }));
+}
+
+/**
+ * 切到下一首；`song` 为空表示队列播完、应停止播放。
+ * @returns {Promise<{ song: Song|null, queue: object }>}
+ */
+export async function queueNext() {
+  return toQueueStep(await transport.command('queue_next'));
+}
+
+/** 回到上一首。 */
+export async function queuePrevious() {
+  return toQueueStep(await transport.command('queue_previous'));
+}
+
+/**
+ * 跳到队列中的某一首。
+ * @param {number} index
+ */
+export async function queueJump(index) {
+  return toQueueStep(await transport.command('queue_jump', { index }));
+}
+
+/**
+ * 设置循环模式。
+ * @param {string} mode - {@link RepeatMode}
+ */
+export async function setRepeatMode(mode) {
+  return toQueue(await transport.command('queue_set_repeat_mode', { mode }));
+}
+
+/**
+ * 开关随机播放（只打乱当前歌曲之后的部分，有声书不参与、排在最后）。
+ * @param {boolean} enabled
+ */
+export async function setShuffle(enabled) {
+  return toQueue(await transport.command('queue_set_shuffle', { enabled }));
+}
+
+/**
+ * 定期上报当前歌曲的剩余时长；临近结尾时返回下一首与过渡方案（每首歌一次）。
+ * @param {number} remainingMs
+ * @returns {Promise<{ next: Song, plan: object, startsInMs: number } | null>}
+ *   `startsInMs` 后开始下一首，按 `plan` 交叉淡化或无缝衔接
+ */
+export async function reportQueuePosition(remainingMs) {
+  const data = await transport.command('queue_report_position', { remainingMs });
+  return data ? { next: new Song(data.next), plan: data.plan, startsInMs: data.starts_in_ms } : null;
+}
+
+/**
+ * 一次取回正在播放页所需的全部数据（后端并发组装）。
+ * @param {string} trackId
+ * @returns {Promise<{ song: Song, album: Album|null, artists: Artist[],
+ *   lyrics: { text: string|null, synced: boolean }, technical: object|null,
+ *   segments: object|null, gain: object|null, palette: string[] }>}
+ */
+export async function getNowPlayingBundle(trackId) {
+  const data = await transport.command('get_now_playing_bundle', { trackId });
+  return {
+    ...data,
+    song: new Song(data.song),
+    album: data.album ? new Album(data.album) : null,
+    artists: data.artists.map((d) => new Artist(d)),
+  };
+}
+
+/**
+ * 歌曲开播时应用的增益（单曲覆盖值优先于 ReplayGain）。
+ * @param {string} songId
+ * @returns {Promise<{ song_id: string, title: string, gain_db: number, linear: number, source: 'override'|'replay_gain'|'none',
+ *   replay_gain_db: number|null, override_db: number|null }>}
+ */
+export async function getTrackGain(songId) {
+  return transport.command('playback_track_gain', { songId });
+}
+
+/**
+ * 设置系统休眠唤醒后是否保持暂停。
+ * @param {boolean} enabled - 关闭时唤醒后从原位置继续播放
+ * @returns {Promise<object>} 更新后的播放设置
+ */
+export async function setPauseOnSuspend(enabled) {
+  return transport.command('set_pause_on_suspend', { enabled });
+}
+
+/**
+ * 设置是否使用空输出：不连接物理设备，播放照常按实时推进（无头服务器 / 音频驱动异常时使用）。
+ * @param {boolean} enabled
+ * @returns {Promise<object>} 更新后的播放设置
+ */
+export async function setNullOutput(enabled) {
+  return transport.command('set_null_output', { enabled });
+}
+
+/**
+ * 当前生效的输出方式（设置与环境变量 `CHORDIAL_AUDIO_OUTPUT=null` 合并）。
+ * @returns {Promise<{ null_output: boolean, forced_by_env: boolean }>}
+ */
+export async function getOutputMode() {
+  return transport.command('playback_get_output_mode');
+}
+
+/**
+ * 交叉淡化的增益曲线；默认 `equal_power`（等功率，过渡全程响度平稳）。
+ * @typedef {'linear' | 'logarithmic' | 's_curve' | 'equal_power' | 'custom'} CrossfadeCurve
+ */
+
+/**
+ * 切到下一首的过渡方案：在当前歌曲剩余 `duration_ms` 时开始下一首，为 0 时直接切歌。
+ * `transition` 为 `gapless` 时下一首应紧接在当前解码流的最后一个采样之后开始。
+ * @param {{ playlistId?: string, currentMs?: number, remainingMs?: number, nextMs?: number }} durations - 未知的可省略
+ * @returns {Promise<{ transition: 'crossfade' | 'gapless', duration_ms: number, requested_ms: number, limited_by: 'current_track' | 'next_track' | 'remaining_time' | null, curve: CrossfadeCurve }>}
+ */
+export async function getCrossfadePlan({ playlistId, currentMs, remainingMs, nextMs } = {}) {
+  return transport.command('playback_crossfade_plan', { playlistId, currentMs, remainingMs, nextMs });
+}
+
+/**
+ * 上报一次切歌过渡（过渡结束或被跳过打断时调用），写入磁盘日志。
+ * @param {{ from_song_id: string, to_song_id: string, from_bpm?: number, to_bpm?: number, speed?: number,
+ *   mix_out_ms: number, mix_in_ms?: number, plan: object, skipped?: boolean }} transition
+ *   - `plan` 为 {@link getCrossfadePlan} 的返回值；`speed` 为对齐节拍给下一首设置的速度
+ */
+export async function recordTransition(transition) {
+  return transport.command('record_transition', { transition });
+}
+
+/**
+ * 歌曲的解码与播放质量（编码 / 解码速度 / 缓冲 / 重采样 / DSP 链）。
+ * @param {string} songId
+ * @param {{ position_ms: number, buffered_ms: number, duration_ms: number, underruns: number, output_sample_rate?: number | null }} [report]
+ *   - 前端播放器此刻的状态，未播放时省略
+ * @returns {Promise<object>}
+ */
+export async function getDecodeStats(songId, report) {
+  return transport.command('get_decode_stats', { songId, report });
+}
+
+/**
+ * 更新解码 PCM 缓存设置：播放次数达到门槛的短曲目预先解码成 WAV，开播与 A-B 循环更快。
+ * @param {{ enabled: boolean, max_bytes: number, min_play_count: number, max_track_secs: number }} pcmCache
+ *   - 关闭时清空已有副本；`max_bytes` 不能为 0
+ * @returns {Promise<object>} 更新后的播放设置
+ */
+export async function setPcmCache(pcmCache) {
+  return transport.command('playback_set_pcm_cache', { pcmCache });
+}
+
+/**
+ * 解码 PCM 缓存占用的字节数。
+ * @returns {Promise<number>}
+ */
+export async function getPcmCacheSize() {
+  return transport.command('pcm_cache_get_size');
+}
+
+/**
+ * 清空解码 PCM 缓存。
+ */
+export async function clearPcmCache() {
+  return transport.command('pcm_cache_clear');
+}
+
+/**
+ * 上报当前输出设备，返回该设备应套用的 DSP 配置。
+ * @param {string} deviceId
+ * @returns {Promise<object>}
+ */
+export async function setOutputDevice(deviceId) {
+  return transport.command('playback_set_output_device', { deviceId });
+}
+
+/**
+ * 跳转方案（拖动进度 / 点击歌词）：目标为负或超过 `durationMs` 时抛出错误。
+ * @param {number} targetMs
+ * @param {number} [durationMs] - 已知时长；省略时只检查下限
+ * @returns {Promise<{ target_ms: number, fade: { action: string, fade_out_ms: number, fade_in_ms: number } }>}
+ */
+export async function seekAudio(targetMs, durationMs) {
+  return transport.command('seek_audio', { targetMs, durationMs });
+}
@@ -0,0 +1,30 @@
+/**
+ * 电源策略 API — 电池供电时降低后台扫描 / 分析的并行度
+ */
+import { transport } from '@/api/transport';
+
+/**
+ * 电源策略。
+ * @enum {string}
+ */
+export const PowerPolicy = {
+  AUTO: 'auto',                          // 使用电池时降低并行度（默认）
+  PAUSE_ON_BATTERY: 'pause_on_battery',  // 使用电池时降低并行度并暂停批量分析
+  LOW_POWER: 'low_power',                // 始终低功耗
+  PERFORMANCE: 'performance',            // 始终全速
+};
+
+/**
+ * 设置电源策略。
+ * @param {string} policy - {@link PowerPolicy}
+ * @returns {Promise<{ policy: string, on_battery: boolean|null, low_power: boolean,
+ *   analysis_paused: boolean, scan_threads: number }>} 更新后的状态
+ */
+export async function setPowerPolicy(policy) {
+  return transport.command('set_power_policy', { policy });
+}
+
+/** 当前电源策略、供电状态与生效的限流参数。 */
+export async function getPowerStatus() {
+  return transport.command('power_get_status');
+}
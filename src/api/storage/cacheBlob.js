@@ -77,3 +77,20 @@ export async function cacheClearBlobs() {
 export async function cacheClearExpiredBlobs() {
   return perf.measureAsync('cache.clearExpiredBlobs', transport.command('cache_clear_expired_blobs'));
 }
+
+/**
+ * 获取磁盘缓存（Blob / 远程音频 / 解码 PCM）的根目录。
+ * @returns {Promise<{dir: string, is_default: boolean}>}
+ */
+export async function getCacheLocation() {
+  return transport.command('cache_get_location');
+}
+
+/**
+ * 把磁盘缓存搬到另一个目录（如更大的硬盘）并立即切换过去。
+ * @param {string|null} [dir] - 新的缓存根目录（绝对路径）；缺省时搬回数据目录
+ * @returns {Promise<{from: string, to: string, files: number, bytes: number, failed: number}>}
+ */
+export async function relocateCache(dir = null) {
+  return transport.command('cache_relocate', { dir });
+}
@@ -64,6 +64,8 @@ export {
   cacheBlobKeys,
   cacheClearBlobs,
   cacheClearExpiredBlobs,
+  getCacheLocation,
+  relocateCache,
 } from './cacheBlob.js';
 
 // Blob Storage — 持久化二进制文件
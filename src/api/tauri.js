@@ -17,6 +17,9 @@ export {
   getFolders,
   getLocalStats,
   rescanAll,
+  startScan,
+  cancelScan,
+  getScanStatus,
 } from './musicSource/sources.js';
 
 // ── Music Library: CRUD + 搜索 + 关系查询 ──────────
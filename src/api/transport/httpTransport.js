@@ -74,4 +74,40 @@ export function streamUrl(type, sourceName, entityId) {
   return `${_baseUrl}/${type}/${sn}/${eid}`;
 }
 
-export default { command, streamUrl, setBaseUrl, getBaseUrl };
+/**
+ * 订阅服务器事件流（WebSocket `/events`）。
+ *
+ * 仅 HTTP 模式可用；Tauri 模式下事件由 Tauri 事件桥直接推送。
+ *
+ * @param {Array<'library'|'scan'|'analysis'|'playback'|'system'|'p2p'>} topics - 订阅的主题，空数组表示全部
+ * @param {(event: object, topic: string) => void} onEvent - 收到事件时回调，`event` 为带 `type` 的事件载荷
+ * @returns {{ subscribe: (topics: string[]) => void, unsubscribe: (topics: string[]) => void, close: () => void }}
+ */
+export function subscribeEvents(topics, onEvent) {
+  const query = topics.length ? `?topics=${encodeURIComponent(topics.join(','))}` : '';
+  const socket = new WebSocket(`${_baseUrl.replace(/^http/, 'ws')}/events${query}`);
+  socket.addEventListener('message', (message) => {
+    const frame = JSON.parse(message.data);
+    if (frame.event) {
+      onEvent(frame.event, frame.topic);
+    } else if (frame.lagged) {
+      console.warn(`[events] 处理过慢，丢失了 ${frame.lagged} 条事件`);
+    } else if (frame.error) {
+      console.warn(`[events] ${frame.error}`);
+    }
+  });
+  const send = (change) => {
+    if (socket.readyState === WebSocket.OPEN) {
+      socket.send(JSON.stringify(change));
+    } else {
+      socket.addEventListener('open', () => socket.send(JSON.stringify(change)), { once: true });
+    }
+  };
+  return {
+    subscribe: (more) => send({ subscribe: more }),
+    unsubscribe: (fewer) => send({ unsubscribe: fewer }),
+    close: () => socket.close(),
+  };
+}
+
+export default { command, streamUrl, setBaseUrl, getBaseUrl, subscribeEvents };
@@ -0,0 +1,51 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceId } from "./SourceId";
+
+/**
+ * 专辑。
+ */
+export type Album = { 
+/**
+ * 库内统一 ID（UUID）
+ */
+id: string, 
+/**
+ * 专辑标题
+ */
+title: string, 
+/**
+ * 所属艺术家 ID
+ */
+artist_id: string, 
+/**
+ * 封面图片 URL
+ */
+cover_url: string | null, 
+/**
+ * 包含的歌曲 ID 列表
+ */
+song_ids: Array<string>, 
+/**
+ * 来源引用
+ */
+source_ids: Array<SourceId>, 
+/**
+ * 专辑发行年份（从同名歌曲标签聚合得到）
+ */
+year?: number | null, 
+/**
+ * 发行日期，格式同 [`Song::release_date`]
+ */
+release_date?: string | null, 
+/**
+ * 原始发行日期，格式同 [`Song::original_date`]
+ */
+original_date?: string | null, 
+/**
+ * 唱片编号（从歌曲标签汇总，或由元数据提供方补全）
+ */
+catalog_number?: string | null, 
+/**
+ * 条形码（UPC / EAN），与外部服务对账时可精确定位发行版
+ */
+barcode?: string | null, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LibraryDiff } from "./LibraryDiff";
+import type { P2pEvent } from "./P2pEvent";
+import type { RescanReport } from "./RescanReport";
+import type { ScanProgress } from "./ScanProgress";
+import type { Subsystem } from "./Subsystem";
+import type { TranscodeScanSummary } from "./TranscodeScanSummary";
+
+/**
+ * 应用事件。
+ */
+export type AppEvent = { "type": "library_changed" } | { "type": "library_diff" } & LibraryDiff | { "type": "metadata_read_progress", task_id: string, done: number, total: number, } | { "type": "scan_progress" } & ScanProgress | { "type": "scan_finished", report: RescanReport, cancelled: boolean, error: string | null, } | { "type": "analysis_progress", task_id: string, done: number, total: number, } | { "type": "analysis_finished", task_id: string, summary: TranscodeScanSummary, cancelled: boolean, } | { "type": "render_progress", task_id: string, done: number, total: number, } | { "type": "file_quarantined", path: string, } | { "type": "system_resumed", slept_secs: number, pause: boolean, } | { "type": "subsystem_ready", subsystem: Subsystem, error: string | null, } | { "type": "app_ready" } | { "type": "p2p" } & P2pEvent;
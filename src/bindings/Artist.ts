@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceId } from "./SourceId";
+
+/**
+ * 艺术家。
+ */
+export type Artist = { 
+/**
+ * 库内统一 ID（UUID）
+ */
+id: string, 
+/**
+ * 艺术家名称
+ */
+name: string, 
+/**
+ * 简介
+ */
+bio: string | null, 
+/**
+ * 流派
+ */
+genres?: Array<string>, 
+/**
+ * 来源引用
+ */
+source_ids: Array<SourceId>, };
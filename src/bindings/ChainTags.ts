@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 链式流的标签取自哪一段。
+ */
+export type ChainTags = "first" | "last";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChangeKind } from "./ChangeKind";
+
+/**
+ * 变更记录的摘要（不含快照数据）。
+ */
+export type ChangeEntry = { id: number, at: number, kind: ChangeKind, summary: string, 
+/**
+ * 涉及的文件 / 歌曲数
+ */
+items: number, };
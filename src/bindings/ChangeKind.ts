@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 变更类型（供前端展示）。
+ */
+export type ChangeKind = "metadata_edit" | "remove_songs" | "hide";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 冲突的处理方式。
+ */
+export type ConflictResolution = "keep_user" | "use_file";
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 音频内容哈希及计算时的文件修改时间。
+ */
+export type ContentHash = { 
+/**
+ * 已编码音频数据包的 xxh3-128（32 位十六进制）
+ */
+xxh3: string, 
+/**
+ * 计算时文件的修改时间（Unix 秒）
+ */
+mtime: number, };
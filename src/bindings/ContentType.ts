@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 内容类型 — 播放速度按类型分别记忆。
+ */
+export type ContentType = "music" | "podcast" | "audiobook";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 可被补全的实体类型。
+ */
+export type EnrichTarget = "album" | "artist";
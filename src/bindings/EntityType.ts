@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 实体类型 — 标记一个来源引用指向哪种音乐实体。
+ */
+export type EntityType = "Song" | "Artist" | "Album" | "Lyric";
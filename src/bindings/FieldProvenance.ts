@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * 一次字段写入的来源记录。
+ */
+export type FieldProvenance = { field: string, 
+/**
+ * 写入的值
+ */
+value: JsonValue, 
+/**
+ * 写入前的值，撤销时恢复
+ */
+previous: JsonValue, 
+/**
+ * 给出该值的提供方
+ */
+provider: string, 
+/**
+ * 写入时间（Unix 秒）
+ */
+applied_at: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 艺人列表的分组方式。
+ */
+export type GroupBy = "artist" | "composer";
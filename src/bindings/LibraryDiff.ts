@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 两次快照之间的变化；各列表按 ID 排序。
+ */
+export type LibraryDiff = { tracks_added: Array<string>, tracks_removed: Array<string>, 
+/**
+ * 仍在库中但内容变了的歌曲（重扫时读到新标签、合并了新的来源等）
+ */
+tracks_updated: Array<string>, 
+/**
+ * 新增或内容变了的专辑（曲目列表、封面、年份等）
+ */
+albums_changed: Array<string>, albums_removed: Array<string>, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 带语言标记的文本（用于多语言标题 / 艺人名）。
+ */
+export type LocalizedText = { 
+/**
+ * 语言标记（BCP 47 风格，如 `ja`、`ja-Latn`、`en`；`und` 表示未知语言）
+ */
+lang: string, 
+/**
+ * 文本内容
+ */
+text: string, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceId } from "./SourceId";
+
+/**
+ * 歌词。
+ */
+export type Lyric = { 
+/**
+ * 库内统一 ID（UUID）
+ */
+id: string, 
+/**
+ * 关联的歌曲 ID
+ */
+song_id: string, 
+/**
+ * 歌词文本
+ */
+text: string, 
+/**
+ * 来源引用（歌词通常只有一个来源）
+ */
+source_id: SourceId, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MetadataField } from "./MetadataField";
+
+/**
+ * 重扫时发现的冲突：文件标签与用户编辑都改了同一字段。
+ */
+export type MetadataConflict = { 
+/**
+ * 文件路径
+ */
+path: string, field: MetadataField, 
+/**
+ * 当前生效的用户值
+ */
+user_value: string | null, 
+/**
+ * 重扫读到的文件值
+ */
+file_value: string | null, 
+/**
+ * 用户编辑时文件中的值
+ */
+previous_file_value: string | null, 
+/**
+ * 发现时间（Unix 秒）
+ */
+detected_at: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 可编辑的元数据字段。
+ */
+export type MetadataField = "title" | "artist" | "album" | "year";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 已注册提供方的概要。
+ */
+export type MetadataProviderInfo = { name: string, priority: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Permission } from "./Permission";
+
+/**
+ * P2P 事件 — 以 [`AppEvent::P2p`] 发布到事件总线，由 Tauri 层转发为前端事件。
+ */
+export type P2pEvent = { "kind": "match_requested", request_id: string, peer_addr: string, peer_name: string, } | { "kind": "peer_connected", peer_id: string, peer_name: string, addr: string, permission: Permission, } | { "kind": "peer_disconnected", peer_id: string, reason: string, } | { "kind": "match_result", request_id: string, accepted: boolean, reason: string | null, } | { "kind": "match_code_rotated", new_code: string, } | { "kind": "trusted_auto_connected", peer_name: string, addr: string, };
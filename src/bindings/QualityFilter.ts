@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 歌曲查询的音质与语言条件；各字段缺省表示不限制。
+ */
+export type QualityFilter = { 
+/**
+ * 只要无损编码
+ */
+lossless_only: boolean, 
+/**
+ * 最低平均码率（kbps）
+ */
+min_bitrate: number | null, 
+/**
+ * 限定编码（如 `["flac", "alac"]`，大小写不敏感）；空表示不限制
+ */
+formats: Array<string>, 
+/**
+ * 最低采样率（Hz）
+ */
+min_sample_rate: number | null, 
+/**
+ * 限定演唱语言（如 `["ja", "zh"]`，`zh` 同时匹配 `zh-Hant` 等变体；`zxx` 为纯音乐）；空表示不限制
+ */
+languages: Array<string>, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一次重扫的结果。
+ */
+export type RescanReport = { files_found: number, 
+/**
+ * 指纹一致、未重新读取的文件
+ */
+unchanged: number, 
+/**
+ * 改动过、重新读取的已索引文件
+ */
+changed: number, 
+/**
+ * 成功（重新）入库的文件
+ */
+indexed: number, errors: Array<string>, 
+/**
+ * 是否因取消而提前结束（其余字段只统计已处理的文件）
+ */
+cancelled: boolean, };
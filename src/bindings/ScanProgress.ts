@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 扫描进度。
+ */
+export type ScanProgress = { 
+/**
+ * 正在扫描的文件夹
+ */
+folder: string, 
+/**
+ * 当前文件夹的序号（从 0 开始）
+ */
+folder_index: number, folder_count: number, 
+/**
+ * 当前文件夹已处理的文件数（含未改动的文件）
+ */
+done: number, 
+/**
+ * 当前文件夹的音频文件数
+ */
+total: number, };
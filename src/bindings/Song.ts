@@ -0,0 +1,135 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContentHash } from "./ContentHash";
+import type { ContentType } from "./ContentType";
+import type { LocalizedText } from "./LocalizedText";
+import type { SourceId } from "./SourceId";
+
+/**
+ * 歌曲。
+ */
+export type Song = { 
+/**
+ * 库内统一 ID（UUID）
+ */
+id: string, 
+/**
+ * 歌曲标题
+ */
+title: string, 
+/**
+ * 艺人名称（无序），与 `artist_ids` 一一对应
+ */
+artist_names: Array<string>, 
+/**
+ * 专辑名称，与 `album_id` 对应
+ */
+album_title: string | null, 
+/**
+ * 时长（秒）
+ */
+duration: number | null, 
+/**
+ * 关联的艺术家 ID 列表
+ */
+artist_ids: Array<string>, 
+/**
+ * 关联的专辑 ID
+ */
+album_id: string | null, 
+/**
+ * 关联的歌词 ID
+ */
+lyric_id: string | null, 
+/**
+ * 来源引用 — 该歌曲在哪些来源中存在
+ */
+source_ids: Array<SourceId>, 
+/**
+ * 发行年份（来自音频标签 Year/Date）
+ */
+year?: number | null, 
+/**
+ * 发行日期（`YYYY` / `YYYY-MM` / `YYYY-MM-DD`，精度取决于标签；来自 TDRC、Vorbis DATE 等）
+ */
+release_date?: string | null, 
+/**
+ * 原始发行日期（再版 / 重制版的首发日期；来自 TDOR、Vorbis ORIGINALDATE 等），格式同上
+ */
+original_date?: string | null, 
+/**
+ * 其他语言的标题（如罗马音、译名）
+ */
+alt_titles?: Array<LocalizedText>, 
+/**
+ * 其他语言的艺人名；同一语言的条目按顺序与 `artist_names` 一一对应
+ */
+alt_artist_names?: Array<LocalizedText>, 
+/**
+ * 流派（来自音频标签 Genre，多值时依次排列）
+ */
+genres?: Array<string>, 
+/**
+ * 作曲者（来自音频标签 Composer，多值时依次排列）；古典模式下按此分组，见 [`grouping`](super::grouping)
+ */
+composers?: Array<string>, 
+/**
+ * 音轨号（来自 TRCK / TRACKNUMBER / trkn，`3/12` 只取 3）
+ */
+track_number?: number | null, 
+/**
+ * 碟号（来自 TPOS / DISCNUMBER / disk）
+ */
+disc_number?: number | null, 
+/**
+ * ReplayGain 音轨增益（dB，来自音频标签）；用户覆盖值见 [`gain`](crate::module::playback::gain)
+ */
+replay_gain_db?: number | null, 
+/**
+ * 国际标准录音代码（来自音频标签，已去掉连字符并转为大写）；导入歌单时用于跨设备匹配
+ */
+isrc?: string | null, 
+/**
+ * 唱片编号（来自音频标签 CATALOGNUMBER），同一发行版的歌曲相同，入库时汇总到专辑
+ */
+catalog_number?: string | null, 
+/**
+ * 发行版条形码（UPC / EAN，纯数字）
+ */
+barcode?: string | null, 
+/**
+ * 演唱语言（BCP 47 风格，如 `ja`、`zh-Hant`；`zxx` 表示纯音乐），来自音频标签，没有标签时从歌词推断，
+ * 见 [`language`](super::language)
+ */
+language?: string | null, 
+/**
+ * `language` 是否由歌词推断而来（而非标签）
+ */
+language_inferred?: boolean, 
+/**
+ * 文件大小（字节，本地文件入库时记录）
+ */
+size_bytes?: number | null, 
+/**
+ * 编码名称（如 `flac`、`aac`、`alac`，扫描时记录）
+ */
+codec?: string | null, 
+/**
+ * 是否为无损编码（FLAC / ALAC / WAV 等），供「只看无损」筛选
+ */
+lossless?: boolean, 
+/**
+ * 平均码率（kbps）
+ */
+bitrate_kbps?: number | null, 
+/**
+ * 采样率（Hz）
+ */
+sample_rate?: number | null, 
+/**
+ * 内容类型；有声书的识别规则见 [`books::classify`](super::books::classify)
+ */
+content_type?: ContentType, 
+/**
+ * 音频内容哈希（不含标签），开启后由哈希任务写入，见 [`content_hash`](crate::module::analysis::content_hash)
+ */
+content_hash?: ContentHash | null, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceType } from "./SourceType";
+import type { StreamQuality } from "./StreamQuality";
+
+/**
+ * 已注册来源的持久化条目（仅保存元信息，不保存实现）。
+ */
+export type SourceEntry = { name: string, source_type: SourceType, 
+/**
+ * 只读：为 `true` 时不向该来源写回标签 / 歌词 / 封面。新来源默认只读，需用户显式开启写回
+ */
+read_only: boolean, 
+/**
+ * 仅元数据：索引并展示其中的歌曲，但不提供音频（如只作归档的共享盘），播放、预加载与音频分析都跳过
+ */
+metadata_only?: boolean, 
+/**
+ * 流式播放 / 下载时请求的转码质量；`None` 取原始文件（仅对支持转码的来源生效）
+ */
+transcode?: StreamQuality | null, 
+/**
+ * 计费网络下改用的转码质量；`None` 时沿用 `transcode`
+ */
+metered_transcode?: StreamQuality | null, };
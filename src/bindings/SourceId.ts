@@ -0,0 +1,37 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EntityType } from "./EntityType";
+import type { SourceType } from "./SourceType";
+
+/**
+ * 来源标识 — 描述某个实体在特定来源中的身份。
+ *
+ * 一个实体（如歌曲）可以同时拥有多个 [`SourceId`]，表示它来自多个来源。
+ *
+ * # 示例
+ *
+ * ```ignore
+ * SourceId {
+ *     source_name: "my_local".into(),
+ *     source_type: SourceType::Local,
+ *     entity_type: EntityType::Song,
+ *     entity_id: "song_001".into(),
+ * }
+ * ```
+ */
+export type SourceId = { 
+/**
+ * 已注册的来源名称，如 `"my_local"`, `"netease"`, `"spotify"`
+ */
+source_name: string, 
+/**
+ * 来源类型
+ */
+source_type: SourceType, 
+/**
+ * 实体类型
+ */
+entity_type: EntityType, 
+/**
+ * 该来源内部的实体 ID
+ */
+entity_id: string, };
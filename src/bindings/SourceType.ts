@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 来源类型 — 区分本地来源与不同的网络来源。
+ */
+export type SourceType = "Local" | { "Web": string };
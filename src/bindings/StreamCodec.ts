@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 转码流的编码格式。
+ */
+export type StreamCodec = "opus" | "mp3" | "aac";
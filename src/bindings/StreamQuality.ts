@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StreamCodec } from "./StreamCodec";
+
+/**
+ * 转码质量。
+ */
+export type StreamQuality = { codec: StreamCodec, 
+/**
+ * 目标码率（kbit/s）
+ */
+bitrate_kbps: number, };
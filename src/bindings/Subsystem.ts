@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 需要启动预热的子系统。
+ */
+export type Subsystem = "settings" | "sources" | "library";
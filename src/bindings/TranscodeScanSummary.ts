@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 批量分析任务（转码检测、内容哈希）的统计结果。
+ */
+export type TranscodeScanSummary = { 
+/**
+ * 成功分析（含命中已有结果）的歌曲数
+ */
+analyzed: number, 
+/**
+ * 其中被标记为可疑的数量（转码检测：疑似转码；内容哈希：疑似损坏）
+ */
+suspicious: number, 
+/**
+ * 分析失败的歌曲数
+ */
+failed: number, };
@@ -6,7 +6,7 @@
 import { SourceId } from './SourceId.js';
 
 export class Album {
-  /** @param {Partial<Album>} data */
+  /** @param {Partial<import('@/bindings/Album').Album> | Partial<Album>} data 后端 JSON（见 bindings）或已转换的对象 */
   constructor(data = {}) {
     /** 库内统一 ID */
     this.id = data.id ?? '';
@@ -25,8 +25,17 @@ export class Album {
       (s) => (s instanceof SourceId ? s : new SourceId(s)),
     );
 
+    /** 发行日期（`YYYY` / `YYYY-MM` / `YYYY-MM-DD`） */
+    this.releaseDate = data.release_date ?? data.releaseDate ?? null;
+    /** 原始发行日期（重制版 / 再版的首发日期），格式同上 */
+    this.originalDate = data.original_date ?? data.originalDate ?? null;
+    /** 唱片编号 */
+    this.catalogNumber = data.catalog_number ?? data.catalogNumber ?? null;
+    /** 条形码（UPC / EAN） */
+    this.barcode = data.barcode ?? null;
+
     // ── 外部注入 / 计算 ──────────────────────────
-    /** 年份（外部注入，后端未提供该字段） */
+    /** 年份 */
     this._year = data.year ?? null;
   }
 
@@ -34,6 +43,11 @@ export class Album {
     return this._year;
   }
 
+  /** 「按年份」排序用的日期：优先原始发行日期，与后端 `Album::chronology_date` 一致 */
+  get chronologyDate() {
+    return this.originalDate ?? this.releaseDate ?? (this._year != null ? String(this._year) : null);
+  }
+
   getTrackCount() {
     return this.songIds.length;
   }
@@ -42,11 +56,20 @@ export class Album {
 
   /**
    * 获取封面资源（用于 useCoverImage composable）。
-   * 优先使用 coverUrl，其次通过第一个 SourceId 获取。
-   * @param {string} _size - 图片尺寸（本地来源忽略）
+   * 小尺寸优先使用后端生成的缩略图，其次 coverUrl，再通过第一个 SourceId 获取原图。
+   * @param {string} size - 图片尺寸（'large' 取原图）
    * @returns {Promise<{url: string, release: Function}|null>}
    */
-  async acquireCoverResource(_size = 'medium') {
+  async acquireCoverResource(size = 'medium') {
+    // 优先级0: 缩略图（没有封面或生成失败时按原图处理）
+    if (size !== 'large' && this.id) {
+      try {
+        const { getAlbumThumbnailResource } = await import('@/api/musicSource/resourceLoader.js');
+        return await getAlbumThumbnailResource(this.id);
+      } catch {
+        // 落到下面的原图
+      }
+    }
     // 优先级1: 已有 coverUrl
     if (this.coverUrl) {
       return { url: this.coverUrl, release: () => {} };
@@ -6,7 +6,7 @@
 import { SourceId } from './SourceId.js';
 
 export class Artist {
-  /** @param {Partial<Artist>} data */
+  /** @param {Partial<import('@/bindings/Artist').Artist> | Partial<Artist>} data 后端 JSON（见 bindings）或已转换的对象 */
   constructor(data = {}) {
     /** 库内统一 ID */
     this.id = data.id ?? '';
@@ -14,6 +14,8 @@ export class Artist {
     this.name = data.name ?? '未知艺术家';
     /** 简介 */
     this.bio = data.bio ?? null;
+    /** 流派 */
+    this.genres = data.genres ?? [];
     /** 来源引用列表 */
     this.sourceIds = (data.source_ids ?? data.sourceIds ?? []).map(
       (s) => (s instanceof SourceId ? s : new SourceId(s)),
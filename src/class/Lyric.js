@@ -6,7 +6,7 @@
 import { SourceId } from './SourceId.js';
 
 export class Lyric {
-  /** @param {Partial<Lyric>} data */
+  /** @param {Partial<import('@/bindings/Lyric').Lyric> | Partial<Lyric>} data 后端 JSON（见 bindings）或已转换的对象 */
   constructor(data = {}) {
     /** 库内统一 ID */
     this.id = data.id ?? '';
@@ -7,7 +7,7 @@
 import { SourceId } from './SourceId.js';
 
 export class Song {
-  /** @param {Partial<Song>} data 后端 JSON 或普通对象 */
+  /** @param {Partial<import('@/bindings/Song').Song> | Partial<Song>} data 后端 JSON（见 bindings）或普通对象 */
   constructor(data = {}) {
     /** 库内统一 ID */
     this.id = data.id ?? '';
@@ -25,6 +25,36 @@ export class Song {
     this.albumId = data.album_id ?? data.albumId ?? null;
     /** 关联的歌词 ID */
     this.lyricId = data.lyric_id ?? data.lyricId ?? null;
+    /** 作曲者名称列表 */
+    this.composers = data.composers ?? [];
+    /** 音轨号 */
+    this.trackNumber = data.track_number ?? data.trackNumber ?? null;
+    /** 碟号 */
+    this.discNumber = data.disc_number ?? data.discNumber ?? null;
+    /** ReplayGain 音轨增益（dB），文件未标注时为 null */
+    this.replayGainDb = data.replay_gain_db ?? data.replayGainDb ?? null;
+    /** ISRC（国际标准录音代码），标签中没有时为 null */
+    this.isrc = data.isrc ?? null;
+    /** 唱片编号 */
+    this.catalogNumber = data.catalog_number ?? data.catalogNumber ?? null;
+    /** 发行版条形码（UPC / EAN） */
+    this.barcode = data.barcode ?? null;
+    /** 演唱语言（如 'ja'、'zh-Hant'；'zxx' 为纯音乐），未知时为 null */
+    this.language = data.language ?? null;
+    /** 演唱语言是否由歌词推断（标签中没有） */
+    this.languageInferred = data.language_inferred ?? data.languageInferred ?? false;
+    /** 文件大小（字节），远程来源为 null */
+    this.sizeBytes = data.size_bytes ?? data.sizeBytes ?? null;
+    /** 编码名称（如 'flac'、'aac'、'alac'），未扫描到时为 null */
+    this.codec = data.codec ?? null;
+    /** 是否为无损编码 */
+    this.lossless = data.lossless ?? false;
+    /** 平均码率（kbps） */
+    this.bitrateKbps = data.bitrate_kbps ?? data.bitrateKbps ?? null;
+    /** 采样率（Hz） */
+    this.sampleRate = data.sample_rate ?? data.sampleRate ?? null;
+    /** 内容类型：'music' | 'podcast' | 'audiobook' */
+    this.contentType = data.content_type ?? data.contentType ?? 'music';
     /** 来源引用列表 */
     this.sourceIds = (data.source_ids ?? data.sourceIds ?? []).map(
       (s) => (s instanceof SourceId ? s : new SourceId(s)),
@@ -44,6 +74,11 @@ export class Song {
     return this.artist;
   }
 
+  /** 是否为有声书（不参与音乐的随机播放） */
+  get isAudiobook() {
+    return this.contentType === 'audiobook';
+  }
+
   /** 主要艺人，如果有 */
   get primaryArtist() {
     if (this.artistIds.length > 0) {
@@ -127,26 +162,41 @@ export class Song {
         }
       }
 
-      if (!lyricText) return result;
-
-      // 检测是否为同步歌词 (LRC 格式)
-      if (/^\[(\d{1,2}):(\d{2})\.(\d{2,3})\]/.test(lyricText)) {
-        const { parseSyncedLyrics, formatToLRC } = await import('@/api/musicSource/musicResource.js');
-        const syncedLines = parseSyncedLyrics(lyricText);
-        result.syncedLyrics = formatToLRC(syncedLines);
-        result.hasSyncedLyrics = true;
-      } else {
-        result.plainLyrics = lyricText;
-        result.hasPlainLyrics = true;
-      }
-
-      return result;
+      return await Song.lyricsInfoFromText(lyricText);
     } catch (error) {
       console.warn('获取歌词信息失败:', error);
       return result;
     }
   }
 
+  /**
+   * 由歌词原文生成歌词信息（同步 / 纯文本）
+   * @param {string} lyricText
+   * @returns {Promise<Object>} 与 getLyricsInfo 相同的结构
+   */
+  static async lyricsInfoFromText(lyricText) {
+    const result = {
+      plainLyrics: '',
+      syncedLyrics: '',
+      hasSyncedLyrics: false,
+      hasPlainLyrics: false
+    };
+    if (!lyricText) return result;
+
+    // 检测是否为同步歌词 (LRC 格式)
+    if (/^\[(\d{1,2}):(\d{2})\.(\d{2,3})\]/.test(lyricText)) {
+      const { parseSyncedLyrics, formatToLRC } = await import('@/api/musicSource/musicResource.js');
+      const syncedLines = parseSyncedLyrics(lyricText);
+      result.syncedLyrics = formatToLRC(syncedLines);
+      result.hasSyncedLyrics = true;
+    } else {
+      result.plainLyrics = lyricText;
+      result.hasPlainLyrics = true;
+    }
+
+    return result;
+  }
+
   // ── 工厂方法 ────────────────────────────────────
 
   /** 批量创建 */
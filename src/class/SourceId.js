@@ -4,7 +4,7 @@
  * 对应后端 `music_source::types::SourceId`。
  */
 export class SourceId {
-  /** @param {Partial<SourceId>} data */
+  /** @param {Partial<import('@/bindings/SourceId').SourceId> | Partial<SourceId>} data 后端 JSON（见 bindings）或已转换的对象 */
   constructor(data = {}) {
     /** 来源名称，如 "my_local", "netease", "spotify" */
     this.sourceName = data.source_name ?? data.sourceName ?? '';
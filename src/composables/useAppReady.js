@@ -0,0 +1,114 @@
+/**
+ * useAppReady — 启动预热的就绪状态。
+ *
+ * 后端启动后在后台扫描本地文件夹，各子系统（`settings` / `sources` / `library`）完成时
+ * emit `"app://ready/<subsystem>"`，全部结束后 emit `"app://ready"`。
+ * 预热完成前，列表 / 搜索命令会等待，超时返回以 `not_ready:` 开头的错误（见 `isNotReadyError`）；
+ * 音乐库就绪时后端会再发一次 `library-changed`，订阅了 `useLibraryEvents` 的视图自动重新拉取。
+ *
+ * 事件可能在前端订阅前就已发出，所以订阅后再用 `app_get_readiness` 补查一次；
+ * HTTP 模式没有事件推送，改为轮询直到就绪。
+ * 在 `main.js` 启动时调用 `initAppReady()` 一次。
+ *
+ * 以安全模式（`CHORDIAL_SAFE_MODE=1` / `--safe-mode`）启动时 `safeMode` 为 true，播放器不创建音频输出。
+ */
+
+import { ref, reactive } from 'vue';
+import { listen } from '@tauri-apps/api/event';
+import { transport } from '@/api/transport';
+import { platformIsTauri } from '@/composables/usePlatform.js';
+
+const SUBSYSTEMS = ['settings', 'sources', 'library'];
+const POLL_INTERVAL_MS = 1000;
+
+/** 所有子系统都已结束预热 */
+const appReady = ref(false);
+
+/** 后端以安全模式启动 */
+const safeMode = ref(false);
+
+/** 子系统 → `{ state: 'pending' | 'ready' | 'failed', error? }` */
+const subsystems = reactive(Object.fromEntries(SUBSYSTEMS.map((s) => [s, { state: 'pending' }])));
+
+let initPromise = null;
+
+function applyStatus(status) {
+  for (const [name, value] of Object.entries(status.subsystems ?? {})) {
+    subsystems[name] = value;
+  }
+  appReady.value = status.ready;
+}
+
+async function refresh() {
+  try {
+    applyStatus(await transport.command('app_get_readiness'));
+  } catch (e) {
+    console.warn('[readiness] 查询启动状态失败:', e);
+  }
+}
+
+/**
+ * 订阅就绪事件并补查当前状态。幂等：重复调用返回同一个 Promise。
+ *
+ * @returns {Promise<void>}
+ */
+export function initAppReady() {
+  if (initPromise) return initPromise;
+
+  initPromise = (async () => {
+    try {
+      safeMode.value = (await transport.command('app_get_startup_mode')).safe_mode;
+    } catch (e) {
+      console.warn('[readiness] 查询启动模式失败:', e);
+    }
+
+    if (platformIsTauri()) {
+      for (const name of SUBSYSTEMS) {
+        await listen(`app://ready/${name}`, (e) => {
+          const error = e.payload?.error;
+          subsystems[name] = error ? { state: 'failed', error } : { state: 'ready' };
+        });
+      }
+      await listen('app://ready', () => {
+        appReady.value = true;
+      });
+      await refresh();
+      return;
+    }
+
+    await refresh();
+    while (!appReady.value) {
+      await new Promise((resolve) => setTimeout(resolve, POLL_INTERVAL_MS));
+      await refresh();
+    }
+  })();
+
+  return initPromise;
+}
+
+/**
+ * 错误是否为「子系统仍在加载」（而不是真正的失败）。
+ *
+ * @param {unknown} err - 命令抛出的错误（Tauri 为字符串，HTTP 为 Error）
+ * @returns {boolean}
+ */
+export function isNotReadyError(err) {
+  const message = typeof err === 'string' ? err : err?.message;
+  return typeof message === 'string' && message.startsWith('not_ready:');
+}
+
+/**
+ * 后端是否以安全模式启动（`initAppReady` 查询之前为 false）。
+ *
+ * @returns {boolean}
+ */
+export function isSafeMode() {
+  return safeMode.value;
+}
+
+/**
+ * @returns {{ appReady: import('vue').Ref<boolean>, safeMode: import('vue').Ref<boolean>, subsystems: Record<string, { state: string, error?: string }> }}
+ */
+export function useAppReady() {
+  return { appReady, safeMode, subsystems };
+}
@@ -0,0 +1,75 @@
+/**
+ * useDecodeStats — 播放器调试浮层的质量指示。
+ *
+ * 实时解码由 `<audio>` 元素完成，缓冲水位、卡顿次数和输出采样率只有前端知道，
+ * 查询时随 `get_decode_stats` 一起上报；后端补上编码信息、解码速度和当前生效的 DSP 链。
+ * 浮层打开期间按 `intervalMs` 轮询，关闭时调用 `stop()`。
+ */
+
+import { ref } from 'vue';
+import { PlayerStore } from '@/stores/player.js';
+import { getOutputSampleRate } from '@/amll/useAudioAnalyser.js';
+import { getDecodeStats } from '@/api/playback.js';
+
+/** 当前位置之后已缓冲的时长（毫秒） */
+function bufferedAheadMs(audio) {
+  const t = audio.currentTime;
+  for (let i = 0; i < audio.buffered.length; i++) {
+    if (audio.buffered.start(i) <= t && t <= audio.buffered.end(i)) {
+      return (audio.buffered.end(i) - t) * 1000;
+    }
+  }
+  return 0;
+}
+
+/** 播放器此刻的状态；没有加载歌曲时返回 undefined */
+function playerReport() {
+  const audio = PlayerStore.getAudioElement();
+  if (!audio?.src) return undefined;
+  return {
+    position_ms: audio.currentTime * 1000,
+    buffered_ms: bufferedAheadMs(audio),
+    duration_ms: Number.isFinite(audio.duration) ? audio.duration * 1000 : 0,
+    underruns: PlayerStore.state.underruns,
+    output_sample_rate: getOutputSampleRate(),
+  };
+}
+
+/**
+ * @param {{ intervalMs?: number }} [options]
+ * @returns {{ stats: import('vue').Ref<object | null>, error: import('vue').Ref<string | null>, refresh: () => Promise<void>, start: () => void, stop: () => void }}
+ */
+export function useDecodeStats({ intervalMs = 1000 } = {}) {
+  const stats = ref(null);
+  const error = ref(null);
+  let timer = null;
+
+  async function refresh() {
+    const track = PlayerStore.state.currentTrack;
+    if (!track) {
+      stats.value = null;
+      return;
+    }
+    try {
+      stats.value = await getDecodeStats(track.id, playerReport());
+      error.value = null;
+    } catch (e) {
+      error.value = typeof e === 'string' ? e : e?.message ?? String(e);
+    }
+  }
+
+  function start() {
+    stop();
+    refresh();
+    timer = setInterval(refresh, intervalMs);
+  }
+
+  function stop() {
+    if (timer) {
+      clearInterval(timer);
+      timer = null;
+    }
+  }
+
+  return { stats, error, refresh, start, stop };
+}
@@ -0,0 +1,67 @@
+/**
+ * useLaunchRequests — 文件关联 / `chordial://` 深链接的启动请求处理。
+ *
+ * 后端（chordial-tauri `launch.rs`）产生两类请求：
+ * - `open_files`：双击音频文件或「用 Chordial 打开」→ 读取元数据构造临时队列并播放（不入库）
+ * - `play_track`：`chordial://track/<id>` → 播放库中对应歌曲
+ *
+ * 首次启动时的请求由 `launch_take_requests` 取走；应用运行中再次被唤起时，
+ * 单实例插件转发的请求以 `launch-request` 事件推送。
+ * 在 `main.js` 启动时调用 `initLaunchRequests()` 一次。
+ */
+
+import { listen } from '@tauri-apps/api/event';
+import { transport } from '@/api/transport';
+import { getSong } from '@/api/musicSource/library.js';
+import { Song } from '@/class';
+import { platformIsTauri } from '@/composables/usePlatform.js';
+import PlayerStore from '@/stores/player.js';
+
+let initPromise = null;
+
+async function handleRequest(request) {
+  try {
+    switch (request?.type) {
+      case 'open_files': {
+        const playlist = await transport.command('session_open_files', { paths: request.paths });
+        const tracks = (playlist?.songs ?? []).map((s) => new Song(s));
+        if (playlist?.skipped?.length) {
+          console.warn('[launch] 以下文件无法播放:', playlist.skipped);
+        }
+        if (tracks.length > 0) {
+          await PlayerStore.play(tracks[0], tracks);
+        }
+        break;
+      }
+      case 'play_track': {
+        const track = await getSong(request.track_id);
+        await PlayerStore.play(track);
+        break;
+      }
+      default:
+        console.warn('[launch] 未知的启动请求:', request);
+    }
+  } catch (e) {
+    console.error('[launch] 处理启动请求失败:', e);
+  }
+}
+
+/**
+ * 处理启动时的请求并订阅后续请求。幂等：重复调用返回同一个 Promise。
+ *
+ * @returns {Promise<void>}
+ */
+export function initLaunchRequests() {
+  if (initPromise) return initPromise;
+
+  initPromise = (async () => {
+    if (!platformIsTauri()) return;
+    await listen('launch-request', (e) => handleRequest(e.payload));
+    const pending = await transport.command('launch_take_requests');
+    for (const request of pending ?? []) {
+      await handleRequest(request);
+    }
+  })();
+
+  return initPromise;
+}
@@ -2,19 +2,24 @@
  * useLibraryEvents — 全局音乐库变更事件订阅。
  *
  * 设计要点：
- * - 单一事件源：后端 `commands.rs` 在 `local_add_folder` / `local_remove_folder` /
- *   `local_rescan` 等改变库内容的操作完成后 emit `"library-changed"`。
+ * - 两类事件：`local_add_folder` / `local_remove_folder` / `local_rescan` 完成后，以及文件监听
+ *   把音乐文件夹中的增删改同步进库后，后端 emit `"library-diff"`，载荷列出增删改的歌曲与专辑 ID
+ *   （没有变化时不发）；其余改变库内容的操作（CRUD、元数据补全等）仍 emit `"library-changed"`，需整体刷新。
  * - 全局唯一监听器：在 `main.js` 启动时调用 `initLibraryEvents()` 一次，
  *   避免每个组件各自 `listen` 导致的重复订阅与资源泄漏。
- * - 响应式版本号：每次事件递增 `libraryVersion.value`，组件通过 `watch`
- *   该 ref 实现自动刷新。
+ * - 响应式版本号：每次 `library-changed` 递增 `libraryVersion.value`，组件通过 `watch`
+ *   该 ref 实现自动刷新；`lastDiff` 保存最近一次增量变化，能按 ID 局部更新的组件改为 `watch` 它。
  * - 同时清除 `library.js` 中的内存缓存（`invalidateCache`），保证后续
  *   重新拉取的数据是最新版本。
+ * - HTTP 服务模式没有 Tauri 事件桥，改为通过 `/events` WebSocket 订阅 `library` 主题，
+ *   `library_changed` / `library_diff` 事件与上面两个 Tauri 事件一一对应。
  */
 
-import { ref } from 'vue';
+import { ref, shallowRef } from 'vue';
 import { listen } from '@tauri-apps/api/event';
 import { library } from '@/api/musicSource';
+import { getTransportMode } from '@/api/transport';
+import { subscribeEvents } from '@/api/transport/httpTransport.js';
 
 /** 库版本号 — 每收到一次 `library-changed` 事件自增 1 */
 const libraryVersion = ref(0);
@@ -22,11 +27,30 @@ const libraryVersion = ref(0);
 /** 详细事件载荷 — 描述本次变更的范围，便于组件按需决定是否刷新 */
 const lastChange = ref(null);
 
+/**
+ * 最近一次 `library-diff` 载荷（见 `bindings/LibraryDiff.ts`）；每次事件都替换为新对象。
+ * @type {import('vue').ShallowRef<import('@/bindings/LibraryDiff').LibraryDiff | null>}
+ */
+const lastDiff = shallowRef(null);
+
 let unlistenFn = null;
+let unlistenDiffFn = null;
 let initPromise = null;
 
+function onLibraryChanged(payload) {
+  libraryVersion.value += 1;
+  lastChange.value = payload ?? null;
+  // 失效前端缓存，确保下次查询重新拉取最新数据
+  library.invalidateCache();
+}
+
+function onLibraryDiff(diff) {
+  lastDiff.value = diff;
+  library.invalidateCache();
+}
+
 /**
- * 初始化全局 `library-changed` 监听器。应在应用启动时调用一次（如 `main.js`）。
+ * 初始化全局 `library-changed` / `library-diff` 监听器。应在应用启动时调用一次（如 `main.js`）。
  * 重复调用是幂等的：第二次起直接返回已有的 Promise。
  *
  * @returns {Promise<void>}
@@ -35,12 +59,16 @@ export function initLibraryEvents() {
   if (initPromise) return initPromise;
 
   initPromise = (async () => {
-    unlistenFn = await listen('library-changed', (e) => {
-      libraryVersion.value += 1;
-      lastChange.value = e.payload ?? null;
-      // 失效前端缓存，确保下次查询重新拉取最新数据
-      library.invalidateCache();
-    });
+    if (getTransportMode() === 'http') {
+      const subscription = subscribeEvents(['library'], ({ type, ...payload }) => {
+        if (type === 'library_changed') onLibraryChanged(null);
+        else if (type === 'library_diff') onLibraryDiff(payload);
+      });
+      unlistenFn = subscription.close;
+      return;
+    }
+    unlistenFn = await listen('library-changed', (e) => onLibraryChanged(e.payload));
+    unlistenDiffFn = await listen('library-diff', (e) => onLibraryDiff(e.payload));
   })();
 
   return initPromise;
@@ -52,12 +80,13 @@ export function initLibraryEvents() {
  * 使用示例：
  * ```js
  * import { useLibraryEvents } from '@/composables/useLibraryEvents';
- * const { libraryVersion } = useLibraryEvents();
+ * const { libraryVersion, lastDiff } = useLibraryEvents();
  * watch(libraryVersion, () => loadAlbums());
+ * watch(lastDiff, (diff) => patchAlbums(diff));
  * ```
  *
- * @returns {{ libraryVersion: import('vue').Ref<number>, lastChange: import('vue').Ref<any> }}
+ * @returns {{ libraryVersion: import('vue').Ref<number>, lastChange: import('vue').Ref<any>, lastDiff: import('vue').ShallowRef<any> }}
  */
 export function useLibraryEvents() {
-  return { libraryVersion, lastChange };
+  return { libraryVersion, lastChange, lastDiff };
 }
@@ -0,0 +1,35 @@
+/**
+ * useMeteredNetwork — 把当前网络是否计费告知后端。
+ *
+ * 后端只能从前端得知网络类型：计费时（移动数据或开启了省流量）远程来源改用各自的 `metered_transcode`。
+ * 依赖 Network Information API（`navigator.connection`）；WebView 不支持时不上报，后端按非计费处理。
+ * 在 `main.js` 启动时调用 `initMeteredNetwork()` 一次。
+ */
+
+import { setMeteredNetwork } from '@/api/musicSource/sources.js';
+
+let initialized = false;
+let lastReported = null;
+
+function report(connection) {
+  const metered = connection.type === 'cellular' || !!connection.saveData;
+  if (metered === lastReported) return;
+  lastReported = metered;
+  setMeteredNetwork(metered).catch((error) => {
+    lastReported = null;
+    console.warn('上报网络计费状态失败:', error);
+  });
+}
+
+/**
+ * 上报一次并在网络变化时重新上报。幂等。
+ */
+export function initMeteredNetwork() {
+  if (initialized) return;
+  initialized = true;
+
+  const connection = navigator.connection;
+  if (!connection) return;
+  report(connection);
+  connection.addEventListener?.('change', () => report(connection));
+}
@@ -1,7 +1,7 @@
 /**
  * P2P 事件桥接 composable。
  *
- * 监听 Tauri 'p2p-event' 事件（由 chordial-tauri lib.rs 从 core mpsc 转发），
+ * 监听 Tauri 'p2p-event' 事件（由 chordial-tauri lib.rs 从 core 事件总线转发），
  * 维护：
  * - pendingRequests：待用户确认的入站匹配请求队列（驱动 P2pMatchDialog）
  * - toasts：peer 连接/断开/出站匹配结果的可消失提示
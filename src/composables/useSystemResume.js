@@ -0,0 +1,32 @@
+/**
+ * useSystemResume — 系统休眠唤醒后的播放恢复。
+ *
+ * 后端检测到休眠（墙上时钟跳变）后 emit `"system-resumed"`，载荷 `{ slept_secs, pause }`。
+ * 这里重建音频输出并回到休眠前的位置；`pause` 来自播放设置「唤醒后保持暂停」。
+ * 在 `main.js` 启动时调用 `initSystemResume()` 一次。
+ */
+
+import { listen } from '@tauri-apps/api/event';
+import { platformIsTauri } from '@/composables/usePlatform.js';
+import PlayerStore from '@/stores/player.js';
+
+let initPromise = null;
+
+/**
+ * 订阅 `system-resumed` 事件。幂等：重复调用返回同一个 Promise。
+ *
+ * @returns {Promise<void>}
+ */
+export function initSystemResume() {
+  if (initPromise) return initPromise;
+
+  initPromise = (async () => {
+    if (!platformIsTauri()) return;
+    await listen('system-resumed', (e) => {
+      console.info(`[player] 系统休眠 ${e.payload?.slept_secs ?? '?'} 秒后唤醒，重建音频输出`);
+      PlayerStore.recoverFromSuspend({ pause: e.payload?.pause ?? true });
+    });
+  })();
+
+  return initPromise;
+}
@@ -4,6 +4,10 @@ import router from "./routers";
 import { initWindowState } from '@/api/window.js';
 import { AmllSettingsStore } from '@/stores/amllSettings.js';
 import { initLibraryEvents } from '@/composables/useLibraryEvents.js';
+import { initLaunchRequests } from '@/composables/useLaunchRequests.js';
+import { initSystemResume } from '@/composables/useSystemResume.js';
+import { initMeteredNetwork } from '@/composables/useMeteredNetwork.js';
+import { initAppReady } from '@/composables/useAppReady.js';
 
 import './style.css'
 import './app.css'
@@ -22,7 +26,19 @@ app.mount("#app");
 // 启动窗口状态持久化（自动恢复位置/尺寸/最大化）
 initWindowState();
 
-// 启动全局 music-library-changed 事件监听
-// 后端在 local_add_folder / local_remove_folder / local_rescan 后 emit 该事件，
-// 前端通过 useLibraryEvents() 订阅以触发专辑/艺人列表自动刷新
+// 启动全局 library-changed / library-diff 事件监听
+// 后端在 local_add_folder / local_remove_folder / local_rescan 后 emit library-diff（增量），
+// 其余库变更 emit library-changed；前端通过 useLibraryEvents() 订阅以触发专辑/艺人列表自动刷新
 initLibraryEvents();
+
+// 处理文件关联 / chordial:// 深链接：启动时的请求 + 运行中被再次唤起
+initLaunchRequests();
+
+// 系统休眠唤醒后重建音频输出并回到原位置
+initSystemResume();
+
+// 网络是否计费（移动数据 / 省流量）决定远程来源的转码质量
+initMeteredNetwork();
+
+// 启动预热就绪状态（后台扫描完成前列表命令会等待或返回 not_ready）
+initAppReady();
@@ -5,12 +5,20 @@
  * 功能：
  * - 管理播放器核心状态（当前歌曲、播放状态、进度等）
  * - 提供播放控制方法（播放、暂停、跳转等）
- * - 管理播放列表和播放模式
+ * - 管理播放列表和播放模式（队列由后端维护，这里按后端返回的快照刷新）
  * - 自动处理音频资源生命周期
  */
 
 import { reactive, readonly, computed, markRaw } from 'vue';
 import { perf } from '@/utils/performanceMonitor.js';
+import {
+  activateTrack, ActivateAction, getNowPlayingBundle, setOutputDevice, getOutputMode,
+  getQueue, setQueue, addToQueue, removeFromQueue, queueNext, queuePrevious, queueJump,
+  setRepeatMode, setShuffle, RepeatMode, reportQueuePosition, getCrossfadePlan, recordTransition, seekAudio,
+  getTrackGain,
+} from '@/api/playback.js';
+import { Song } from '@/class';
+import { isSafeMode } from '@/composables/useAppReady.js';
 
 // 播放模式枚举
 export const PlayMode = {
@@ -20,6 +28,19 @@ export const PlayMode = {
   LOOP_ONE: 'loop_one'   // 单曲循环
 };
 
+// 「上一首」时当前歌曲已播放超过该秒数则回到开头，而不是切回上一首
+const RESTART_THRESHOLD_SECS = 3;
+// 向后端上报剩余时长的间隔（毫秒）
+const POSITION_REPORT_INTERVAL = 1000;
+
+// 播放模式对应的后端循环模式；随机播放由后端打乱队列，打乱后的队列整体循环
+const REPEAT_FOR_MODE = {
+  [PlayMode.SEQUENCE]: RepeatMode.OFF,
+  [PlayMode.RANDOM]: RepeatMode.ALL,
+  [PlayMode.LOOP]: RepeatMode.ALL,
+  [PlayMode.LOOP_ONE]: RepeatMode.ONE,
+};
+
 // 创建音频元素
 const createAudioElement = () => {
   const audio = new Audio();
@@ -36,11 +57,13 @@ const state = reactive({
   duration: 0,               // 总时长 (秒)
   volume: 0.8,               // 音量 (0-1)
   muted: false,              // 是否静音
+  trackGain: 1,              // 当前歌曲的增益（线性倍数，来自单曲覆盖值或 ReplayGain）
 
   // 播放列表
   playlist: [],              // 当前播放列表
   currentIndex: -1,          // 当前歌曲在播放列表中的索引
   playMode: PlayMode.SEQUENCE, // 播放模式
+  hasHistory: false,         // 后端队列的播放历史是否非空（「上一首」先按历史回退）
 
   // 音频元素
   audioElement: null,        // HTMLAudioElement
@@ -48,6 +71,7 @@ const state = reactive({
   // 加载状态
   isLoading: false,          // 是否正在加载
   error: null,               // 错误信息
+  underruns: 0,              // 本曲播放中途缓冲耗尽的次数
 
   // 歌词显示
   showLyrics: false,         // 是否显示歌词页面
@@ -57,6 +81,9 @@ const state = reactive({
     hasSyncedLyrics: false,
     hasPlainLyrics: false
   },
+  nowPlaying: null,          // 正在播放页数据（专辑、艺人、技术信息、封面配色等）
+  dspProfile: null,          // 当前输出设备的 DSP 配置（均衡器 / 平衡 / 交叉馈送 / 限幅器）
+  nullOutput: false,         // 是否使用空输出（不连接物理设备，进度照常按实时推进）
 
   // ── PlayerView UI 状态（模态化，不再通过 router）──────────────
   ui: {
@@ -98,8 +125,7 @@ const getters = {
   // 是否可以播放上一首
   canPlayPrevious: computed(() => {
     if (state.playlist.length === 0) return false;
-    if (state.playMode === PlayMode.RANDOM) return true;
-    return state.currentIndex > 0;
+    return state.hasHistory || state.currentIndex > 0;
   }),
 
   // 是否可以播放下一首
@@ -146,13 +172,58 @@ function initAudioElement() {
   }
 
   state.audioElement = createAudioElement();
-  state.audioElement.volume = state.volume;
+  state.audioElement.volume = outputVolume();
   state.audioElement.muted = state.muted;
 
   // 绑定事件
   setupAudioEvents();
+  applyOutputMode(state.audioElement);
+}
+
+// 空输出使用的 AudioContext（输出到 `{ type: 'none' }`，不打开任何设备）
+let nullSinkContext = null;
+
+/**
+ * 按后端给出的输出方式连接音频元素，然后上报输出设备。
+ * 空输出时把元素接到不出声的 AudioContext 上：播放、进度、结束事件照常按实时推进。
+ * WebView 不支持 `setSinkId` 时仍使用默认设备。
+ * @param {HTMLAudioElement} audio
+ */
+async function applyOutputMode(audio) {
+  let mode = null;
+  try {
+    mode = await getOutputMode();
+  } catch (error) {
+    console.warn('查询输出方式失败:', error);
+  }
+  if (audio !== state.audioElement) return;
+  if (mode?.null_output) {
+    if (typeof AudioContext !== 'undefined' && 'setSinkId' in AudioContext.prototype) {
+      nullSinkContext ??= new AudioContext({ sinkId: { type: 'none' } });
+      nullSinkContext.createMediaElementSource(audio).connect(nullSinkContext.destination);
+      state.nullOutput = true;
+    } else {
+      console.warn('当前 WebView 不支持空输出，仍使用默认输出设备');
+    }
+  }
+  syncOutputDevice();
 }
 
+/**
+ * 向后端上报当前输出设备并取回其 DSP 配置。
+ * 插拔耳机等设备变化时重新上报，换到另一台设备的配置。
+ */
+async function syncOutputDevice() {
+  const deviceId = state.nullOutput ? 'null' : (state.audioElement?.sinkId || 'default');
+  try {
+    state.dspProfile = markRaw(await setOutputDevice(deviceId));
+  } catch (error) {
+    console.warn('同步输出设备失败:', error);
+  }
+}
+
+navigator.mediaDevices?.addEventListener?.('devicechange', syncOutputDevice);
+
 // 音频事件处理函数（用于正确移除监听）
 const audioEventHandlers = {
   timeupdate: null,
@@ -161,16 +232,73 @@ const audioEventHandlers = {
   waiting: null,
   ended: null,
   error: null,
-  volumechange: null
+  volumechange: null,
+  seeked: null
 };
 
+// 尚未到位的跳转目标（秒）。到位前 timeupdate 报的还是旧位置，不用它覆盖进度
+let pendingSeek = null;
+// 跳转的序号：连续拖动进度时只有最后一次跳转继续淡出 / 淡入
+let seekSeq = 0;
+// 跳转淡出 / 淡入期间音量由跳转控制，volumechange 不回写用户音量
+let seekFading = false;
+// 等待 `seeked` 的上限（毫秒），超时后照常淡入
+const SEEKED_TIMEOUT = 1000;
+
+/**
+ * 单曲增益实际可用的部分：音频元素音量不能超过 1，只能衰减，正增益按 0 dB 处理。
+ */
+function appliedGain() {
+  return Math.min(1, state.trackGain);
+}
+
+/**
+ * 音频元素应设的音量：用户音量叠加单曲增益。
+ */
+function outputVolume() {
+  return state.volume * appliedGain();
+}
+
+/**
+ * 取回歌曲开播时应用的增益，失败时不做调整。
+ * @returns {Promise<number>} 线性倍数
+ */
+async function loadTrackGain(songId) {
+  try {
+    return (await getTrackGain(songId)).linear;
+  } catch (error) {
+    console.warn('获取单曲增益失败:', error);
+    return 1;
+  }
+}
+
+/**
+ * 把音频元素的音量在 `ms` 毫秒内线性过渡到 `to`；`isCurrent` 返回 false 时中途停下。
+ * @returns {Promise<boolean>} 是否完整过渡
+ */
+function rampVolume(audio, to, ms, isCurrent) {
+  const from = audio.volume;
+  const startAt = performance.now();
+  return new Promise((resolve) => {
+    const step = (now) => {
+      if (!isCurrent()) return resolve(false);
+      const t = ms > 0 ? Math.min(1, (now - startAt) / ms) : 1;
+      audio.volume = from + (to - from) * t;
+      if (t < 1) requestAnimationFrame(step);
+      else resolve(true);
+    };
+    requestAnimationFrame(step);
+  });
+}
+
 // 设置音频事件监听
 function setupAudioEvents() {
   const audio = state.audioElement;
   if (!audio) return;
 
-  // 时间更新（节流处理，每 100ms 更新一次）
+  // 时间更新（节流处理，每 100ms 更新一次；每秒向后端上报一次剩余时长）
   let lastTimeUpdate = 0;
+  let lastPositionReport = 0;
   const TIME_UPDATE_THROTTLE = 100; // 100ms
 
   audioEventHandlers.timeupdate = () => {
@@ -178,12 +306,25 @@ function setupAudioEvents() {
     if (now - lastTimeUpdate < TIME_UPDATE_THROTTLE) return;
     lastTimeUpdate = now;
 
+    if (pendingSeek !== null) return;
     state.currentTime = audio.currentTime || 0;
+    if (now - lastPositionReport >= POSITION_REPORT_INTERVAL) {
+      lastPositionReport = now;
+      reportPosition();
+    }
   };
   audio.addEventListener('timeupdate', audioEventHandlers.timeupdate);
 
+  // 跳转到位：以解码器实际所在的位置为准（可能落在目标之前最近的帧）
+  audioEventHandlers.seeked = () => {
+    pendingSeek = null;
+    state.currentTime = audio.currentTime || 0;
+  };
+  audio.addEventListener('seeked', audioEventHandlers.seeked);
+
   // 加载完成
   audioEventHandlers.loadedmetadata = () => {
+    pendingSeek = null;
     state.duration = audio.duration || 0;
     state.isLoading = false;
   };
@@ -198,6 +339,10 @@ function setupAudioEvents() {
   // 等待加载
   audioEventHandlers.waiting = () => {
     state.isLoading = true;
+    // 开头加载和拖动进度引起的等待不算卡顿
+    if (audio.currentTime > 0 && !audio.seeking) {
+      state.underruns += 1;
+    }
   };
   audio.addEventListener('waiting', audioEventHandlers.waiting);
 
@@ -218,7 +363,10 @@ function setupAudioEvents() {
 
   // 音量变化
   audioEventHandlers.volumechange = () => {
-    state.volume = audio.volume;
+    if (seekFading) return;
+    // 元素音量含单曲增益，折算回用户音量
+    const gain = appliedGain();
+    if (gain > 0) state.volume = Math.min(1, audio.volume / gain);
     state.muted = audio.muted;
   };
   audio.addEventListener('volumechange', audioEventHandlers.volumechange);
@@ -251,37 +399,232 @@ function cleanupAudioEvents() {
   if (audioEventHandlers.volumechange) {
     audio.removeEventListener('volumechange', audioEventHandlers.volumechange);
   }
+  if (audioEventHandlers.seeked) {
+    audio.removeEventListener('seeked', audioEventHandlers.seeked);
+  }
+  pendingSeek = null;
 }
 
 // 处理歌曲播放结束
 function handleTrackEnded() {
-  switch (state.playMode) {
-    case PlayMode.LOOP_ONE:
-      // 单曲循环，重新播放
-      state.audioElement.currentTime = 0;
-      state.audioElement.play();
-      break;
-    case PlayMode.LOOP:
-    case PlayMode.RANDOM:
-    case PlayMode.SEQUENCE:
-    default:
-      actions.playNext();
-      break;
+  if (state.playMode === PlayMode.LOOP_ONE) {
+    // 单曲循环，重新播放
+    state.audioElement.currentTime = 0;
+    state.audioElement.play();
+    return;
+  }
+  // 顺序播放到队尾时由后端按「队列播完」设置处理（停止 / 从头重播 / 追加相似歌曲）
+  actions.playNext();
+}
+
+// 队列播完、没有下一首：停在当前歌曲开头
+function stopAtQueueEnd() {
+  state.isPlaying = false;
+  state.currentTime = 0;
+  if (state.audioElement) {
+    state.audioElement.pause();
+    state.audioElement.currentTime = 0;
+  }
+}
+
+// 以后端返回的队列快照刷新播放列表
+function applyQueue(queue) {
+  state.playlist = queue.songs.map(t => markRaw(t));
+  state.currentIndex = queue.currentIndex ?? -1;
+  state.hasHistory = Boolean(queue.hasHistory);
+}
+
+// 把播放模式同步到后端队列：循环模式，以及是否打乱当前歌曲之后的部分
+async function syncPlayMode() {
+  try {
+    await setRepeatMode(REPEAT_FOR_MODE[state.playMode]);
+  } catch (error) {
+    console.warn('同步循环模式失败:', error);
+  }
+  await updateQueue(() => setShuffle(state.playMode === PlayMode.RANDOM));
+}
+
+/**
+ * 把队列修改交给后端，并以返回的队列刷新播放列表。
+ * 后端拒绝时（如歌曲不在曲库中）调用 `fallback` 只改本地列表；没有 `fallback` 时保持原样。
+ * @param {() => Promise<object>} request - 返回队列快照
+ * @param {Function} [fallback]
+ */
+async function updateQueue(request, fallback = null) {
+  try {
+    applyQueue(await request());
+  } catch (error) {
+    console.warn('同步播放队列失败:', error);
+    fallback?.();
   }
 }
 
-// 获取歌曲在播放列表中的索引
+// 让后端队列的当前位置指向 `track`：不在队列中的先插到当前歌曲之后
+async function moveQueueTo(track) {
+  if (getTrackIndex(track) === -1) {
+    await updateQueue(() => addToQueue([track.id], true), () => {
+      state.playlist.splice(state.currentIndex + 1, 0, markRaw(track));
+    });
+  }
+  const index = getTrackIndex(track);
+  if (index !== -1 && index !== state.currentIndex) {
+    await updateQueue(async () => (await queueJump(index)).queue, () => {
+      state.currentIndex = index;
+    });
+  }
+}
+
+// 按后端切歌的结果播放；`song` 为空表示队列播完
+async function playStep(request) {
+  let step;
+  try {
+    step = await request();
+  } catch (error) {
+    console.warn('切歌失败:', error);
+    return;
+  }
+  applyQueue(step.queue);
+  if (step.song) {
+    await startTrack(step.song);
+  } else {
+    stopAtQueueEnd();
+  }
+}
+
+// 预先加载下一首的音频元素（只缓冲、不播放）
+let preloadAudio = null;
+
+/**
+ * 向后端上报当前歌曲的剩余时长（后端据此重排预加载）。
+ * 临近结尾时后端给出下一首，提前缓冲，切歌时可以直接开始播放。
+ */
+async function reportPosition() {
+  const audio = state.audioElement;
+  if (!audio || !state.currentTrack) return;
+  const duration = audio.duration || state.duration;
+  const remainingMs = Math.max(0, Math.round((duration - (audio.currentTime || 0)) * 1000));
+  try {
+    const transition = await reportQueuePosition(remainingMs);
+    const url = transition && await transition.next.getAudioBlobUrl();
+    if (url) {
+      preloadAudio ??= new Audio();
+      preloadAudio.preload = 'auto';
+      preloadAudio.src = url;
+    }
+  } catch (error) {
+    console.warn('上报播放进度失败:', error);
+  }
+}
+
+/**
+ * 把一次切歌记入后端的过渡日志。
+ * 播放器目前直接切歌、不做交叉淡化，方案取后端在剩余 0 毫秒时给出的结果（即直接切歌）；
+ * 两首歌、切出位置、是否在上一首播完前跳过仍照实记录，供调整自动混音设置时参考。
+ */
+async function logTransition(from, to, { mixOutMs, skipped, currentMs, nextMs }) {
+  try {
+    const plan = await getCrossfadePlan({ currentMs, remainingMs: 0, nextMs });
+    await recordTransition({
+      from_song_id: from.id,
+      to_song_id: to.id,
+      mix_out_ms: mixOutMs,
+      mix_in_ms: 0,
+      plan,
+      skipped,
+    });
+  } catch (error) {
+    console.warn('记录切歌过渡失败:', error);
+  }
+}
+
+// 获取歌曲在播放列表中的索引（正在播放的歌曲优先取当前位置）
 function getTrackIndex(track) {
   if (!track) return -1;
+  if (state.playlist[state.currentIndex]?.id === track.id) return state.currentIndex;
   return state.playlist.findIndex(t => t.id === track.id);
 }
 
+/**
+ * 加载并播放一首歌（不改动队列，队列位置由调用方先交给后端）。
+ * @param {Track} track - 要播放的歌曲
+ */
+async function startTrack(track) {
+  try {
+    state.isLoading = true;
+    state.error = null;
+
+    // 切走的歌曲播到了哪里，播放成功后记入过渡日志
+    const previous = state.currentTrack;
+    const previousAudio = state.audioElement;
+    const cut = previous && previous.id !== track.id && previousAudio?.src ? {
+      mixOutMs: Math.round((previousAudio.currentTime || 0) * 1000),
+      skipped: !previousAudio.ended,
+      currentMs: Math.round((previousAudio.duration || state.duration || 0) * 1000) || undefined,
+    } : null;
+
+    // 停止当前播放并释放旧的音频资源
+    if (state.audioElement) {
+      // chordial:// 协议下无需 revokeObjectURL；保留 blob: 兼容
+      const oldSrc = state.audioElement.src;
+      if (oldSrc && oldSrc.startsWith('blob:')) {
+        URL.revokeObjectURL(oldSrc);
+      }
+      state.audioElement.pause();
+      state.audioElement.currentTime = 0;
+    }
+
+    // 更新当前歌曲（markRaw 避免 Vue 对 Track 业务类实例创建深代理）
+    state.currentTrack = markRaw(track);
+    state.currentIndex = getTrackIndex(track);
+    state.currentTime = 0;
+    state.underruns = 0;
+    state.duration = track.duration || 0;
+
+    // 获取音频 URL 与单曲增益
+    const [audioUrl, gain] = await Promise.all([track.getAudioBlobUrl(), loadTrackGain(track.id)]);
+    if (!audioUrl) {
+      throw new Error('无法获取音频文件');
+    }
+
+    // 初始化音频元素
+    if (!state.audioElement) {
+      initAudioElement();
+    }
+
+    // 设置音频源，按新歌曲的增益调整音量
+    state.trackGain = gain;
+    state.audioElement.volume = outputVolume();
+    state.audioElement.src = audioUrl;
+
+    // 播放（先启动播放，歌词后台加载，不阻塞）
+    await state.audioElement.play();
+    state.isPlaying = true;
+
+    // 切歌后立即上报一次，后端按新歌曲重排预加载
+    reportPosition();
+    if (cut) {
+      const nextMs = Math.round((state.audioElement.duration || track.duration || 0) * 1000) || undefined;
+      logTransition(previous, track, { ...cut, nextMs });
+    }
+
+    // 后台加载正在播放页数据（含歌词），不阻塞播放启动
+    actions.loadNowPlaying(track);
+
+  } catch (error) {
+    console.error('播放失败:', error);
+    state.error = error.message || '播放失败';
+    state.isPlaying = false;
+  } finally {
+    state.isLoading = false;
+  }
+}
+
 // Actions
 const actions = {
   /**
    * 播放指定歌曲
    * @param {Track} track - 要播放的歌曲
-   * @param {Array} playlist - 可选的播放列表
+   * @param {Array} playlist - 可选的播放列表，提供时替换后端队列
    */
   async play(track, playlist = null) {
     return perf.measureAsync('PlayerStore.play', (async () => {
@@ -289,71 +632,38 @@ const actions = {
       console.warn('play: 无效的 track 参数');
       return;
     }
+    if (isSafeMode()) {
+      state.error = '安全模式下已停用音频播放';
+      return;
+    }
 
-    try {
-      state.isLoading = true;
-      state.error = null;
-
-      // 如果提供了播放列表，更新播放列表（markRaw 避免深代理 Track 实例）
-      if (playlist && Array.isArray(playlist)) {
+    if (playlist && Array.isArray(playlist)) {
+      // 以提供的播放列表替换队列（markRaw 避免深代理 Track 实例）
+      const ids = playlist.map(t => t.id);
+      const startIndex = ids.indexOf(track.id);
+      if (startIndex === -1) ids.unshift(track.id);
+      await updateQueue(async () => (await setQueue(ids, Math.max(startIndex, 0))).queue, () => {
         state.playlist = playlist.map(t => markRaw(t));
-      }
+      });
+    } else if (state.currentTrack?.id !== track.id) {
+      await moveQueueTo(track);
+    }
 
-      // 如果当前已经在播放这首歌，继续播放
-      if (state.currentTrack?.id === track.id) {
-        if (state.audioElement?.paused) {
+    // 如果当前已经在播放这首歌，继续播放
+    if (state.currentTrack?.id === track.id) {
+      if (state.audioElement?.paused) {
+        try {
           await state.audioElement.play();
           state.isPlaying = true;
+        } catch (error) {
+          console.error('播放失败:', error);
+          state.error = error.message || '播放失败';
         }
-        state.isLoading = false;
-        return;
       }
-
-      // 停止当前播放并释放旧的音频资源
-      if (state.audioElement) {
-        // chordial:// 协议下无需 revokeObjectURL；保留 blob: 兼容
-        const oldSrc = state.audioElement.src;
-        if (oldSrc && oldSrc.startsWith('blob:')) {
-          URL.revokeObjectURL(oldSrc);
-        }
-        state.audioElement.pause();
-        state.audioElement.currentTime = 0;
-      }
-
-      // 更新当前歌曲（markRaw 避免 Vue 对 Track 业务类实例创建深代理）
-      state.currentTrack = markRaw(track);
-      state.currentIndex = getTrackIndex(track);
-      state.currentTime = 0;
-      state.duration = track.duration || 0;
-
-      // 获取音频 URL
-      const audioUrl = await track.getAudioBlobUrl();
-      if (!audioUrl) {
-        throw new Error('无法获取音频文件');
-      }
-
-      // 初始化音频元素
-      if (!state.audioElement) {
-        initAudioElement();
-      }
-
-      // 设置音频源
-      state.audioElement.src = audioUrl;
-
-      // 播放（先启动播放，歌词后台加载，不阻塞）
-      await state.audioElement.play();
-      state.isPlaying = true;
-
-      // 后台加载歌词，不阻塞播放启动
-      actions.loadLyrics(track);
-
-    } catch (error) {
-      console.error('播放失败:', error);
-      state.error = error.message || '播放失败';
-      state.isPlaying = false;
-    } finally {
-      state.isLoading = false;
+      return;
     }
+
+    await startTrack(track);
     })());
   },
 
@@ -381,6 +691,43 @@ const actions = {
     }
   },
 
+  /**
+   * 系统休眠唤醒后恢复播放器：旧音频元素的输出流常已失效（`paused` 仍为 false 却不出声），
+   * 因此换一个新元素重新加载当前歌曲，回到休眠前的位置。
+   * @param {{ pause?: boolean }} options - `pause` 为 true 时停在原位置，否则原本在播放的继续播放
+   */
+  async recoverFromSuspend({ pause = true } = {}) {
+    const old = state.audioElement;
+    if (!state.currentTrack || !old?.src) return;
+
+    const src = old.src;
+    const position = state.currentTime;
+    const wasPlaying = state.isPlaying;
+    old.pause();
+    initAudioElement();
+    old.removeAttribute('src');
+    old.load();
+
+    const audio = state.audioElement;
+    audio.src = src;
+    try {
+      await new Promise((resolve, reject) => {
+        audio.addEventListener('loadedmetadata', resolve, { once: true });
+        audio.addEventListener('error', reject, { once: true });
+      });
+      audio.currentTime = Math.min(position, audio.duration || position);
+      state.currentTime = audio.currentTime;
+      if (wasPlaying && !pause) {
+        await audio.play();
+      } else {
+        state.isPlaying = false;
+      }
+    } catch (error) {
+      console.error('休眠唤醒后恢复播放失败:', error);
+      state.isPlaying = false;
+    }
+  },
+
   /**
    * 切换播放/暂停
    */
@@ -394,16 +741,49 @@ const actions = {
 
   /**
    * 跳转到指定时间
+   *
+   * 先由后端检查目标并给出跳转方案（`seek_audio`，超出时长时报错），播放中按方案淡出、
+   * 跳转、等音频到位（`seeked`）后再淡入。进度条立即显示目标位置，到位后以实际位置校正；
+   * 到位之前的 timeupdate 不会把进度拉回旧位置。
    * @param {number} time - 时间（秒）
    */
-  seek(time) {
+  async seek(time) {
+    const audio = state.audioElement;
+    if (!audio) return;
     perf.start('PlayerStore.seek');
-    if (state.audioElement) {
-      const clampedTime = Math.max(0, Math.min(time, state.duration));
-      state.audioElement.currentTime = clampedTime;
-      state.currentTime = clampedTime;
+    const seq = ++seekSeq;
+    const track = state.currentTrack;
+    const isCurrent = () => seq === seekSeq && state.audioElement === audio && state.currentTrack === track;
+    const clampedTime = Math.max(0, Math.min(time, state.duration || time));
+    pendingSeek = clampedTime;
+    state.currentTime = clampedTime;
+    try {
+      const durationMs = state.duration > 0 ? state.duration * 1000 : undefined;
+      const plan = await seekAudio(clampedTime * 1000, durationMs);
+      if (!isCurrent()) return;
+      const playing = !audio.paused;
+      seekFading = true;
+      if (playing && !await rampVolume(audio, 0, plan.fade.fade_out_ms, isCurrent)) return;
+      const seeked = new Promise((resolve) => {
+        audio.addEventListener('seeked', resolve, { once: true });
+        setTimeout(resolve, SEEKED_TIMEOUT);
+      });
+      audio.currentTime = plan.target_ms / 1000;
+      if (playing) {
+        await seeked;
+        if (isCurrent()) await rampVolume(audio, outputVolume(), plan.fade.fade_in_ms, isCurrent);
+      }
+    } catch (error) {
+      console.warn('跳转失败:', error);
+      pendingSeek = null;
+      state.currentTime = audio.currentTime || 0;
+    } finally {
+      if (seq === seekSeq) {
+        seekFading = false;
+        audio.volume = outputVolume();
+      }
+      perf.end('PlayerStore.seek');
     }
-    perf.end('PlayerStore.seek');
   },
 
   /**
@@ -417,69 +797,49 @@ const actions = {
 
   /**
    * 播放上一首
+   *
+   * 当前歌曲已播放超过 3 秒时回到开头；否则交给后端队列：先按播放历史回到实际播放过的上一首
+   * （随机模式下也是真正听过的那首），没有历史时退回队列中的前一首。
    */
-  playPrevious() {
+  async playPrevious() {
     perf.start('PlayerStore.playPrevious');
-    if (state.playlist.length === 0) { perf.end('PlayerStore.playPrevious'); return; }
-
-    let previousIndex;
-
-    if (state.playMode === PlayMode.RANDOM) {
-      // 随机模式，随机选择一首
-      previousIndex = Math.floor(Math.random() * state.playlist.length);
-    } else {
-      // 其他模式，播放上一首
-      previousIndex = state.currentIndex - 1;
-      if (previousIndex < 0) {
-        previousIndex = state.playlist.length - 1; // 循环到最后一首
-      }
+    if (state.currentTrack && state.currentTime > RESTART_THRESHOLD_SECS) {
+      actions.seek(0);
+      perf.end('PlayerStore.playPrevious');
+      return;
     }
 
-    const previousTrack = state.playlist[previousIndex];
-    if (previousTrack) {
-      actions.play(previousTrack);
-    }
+    if (state.playlist.length === 0) { perf.end('PlayerStore.playPrevious'); return; }
+    await playStep(queuePrevious);
     perf.end('PlayerStore.playPrevious');
   },
 
   /**
-   * 播放下一首
+   * 播放下一首（由后端队列决定，随机模式下队列已由后端打乱；顺序播放到队尾时按「队列播完」设置处理）
    */
-  playNext() {
+  async playNext() {
     perf.start('PlayerStore.playNext');
     if (state.playlist.length === 0) { perf.end('PlayerStore.playNext'); return; }
 
-    let nextIndex;
-
-    if (state.playMode === PlayMode.RANDOM) {
-      // 随机模式，随机选择一首
-      nextIndex = Math.floor(Math.random() * state.playlist.length);
+    if (state.playMode === PlayMode.LOOP_ONE) {
+      // 单曲循环下手动切歌仍然切到下一首
+      await playStep(() => queueJump((state.currentIndex + 1) % state.playlist.length));
     } else {
-      // 其他模式，播放下一首
-      nextIndex = state.currentIndex + 1;
-      if (nextIndex >= state.playlist.length) {
-        nextIndex = 0; // 循环到第一首
-      }
-    }
-
-    const nextTrack = state.playlist[nextIndex];
-    if (nextTrack) {
-      actions.play(nextTrack);
+      await playStep(queueNext);
     }
     perf.end('PlayerStore.playNext');
   },
 
   /**
-   * 随机播放
+   * 随机播放：切到随机模式（后端打乱队列）后播放下一首
    */
-  playRandom() {
+  async playRandom() {
     if (state.playlist.length === 0) return;
-
-    const randomIndex = Math.floor(Math.random() * state.playlist.length);
-    const randomTrack = state.playlist[randomIndex];
-    if (randomTrack) {
-      actions.play(randomTrack);
+    if (state.playMode !== PlayMode.RANDOM) {
+      state.playMode = PlayMode.RANDOM;
+      await syncPlayMode();
     }
+    await playStep(queueNext);
   },
 
   /**
@@ -490,7 +850,7 @@ const actions = {
     const clampedVolume = Math.max(0, Math.min(1, volume));
     state.volume = clampedVolume;
     if (state.audioElement) {
-      state.audioElement.volume = clampedVolume;
+      state.audioElement.volume = outputVolume();
     }
   },
 
@@ -511,6 +871,7 @@ const actions = {
   setPlayMode(mode) {
     if (Object.values(PlayMode).includes(mode)) {
       state.playMode = mode;
+      syncPlayMode();
     }
   },
 
@@ -522,61 +883,97 @@ const actions = {
     const currentIndex = modes.indexOf(state.playMode);
     const nextIndex = (currentIndex + 1) % modes.length;
     state.playMode = modes[nextIndex];
+    syncPlayMode();
   },
 
   /**
-   * 设置播放列表
+   * 设置播放列表（替换后端队列，正在播放的歌曲保持为当前位置）
    * @param {Array} playlist - 播放列表
    */
-  setPlaylist(playlist) {
+  async setPlaylist(playlist) {
     perf.start('PlayerStore.setPlaylist');
     // markRaw 每首 Track 实例，避免 Vue 对业务类（含方法）创建深代理
-    state.playlist = (playlist || []).map(t => markRaw(t));
-    // 更新当前歌曲索引
-    if (state.currentTrack) {
+    const tracks = (playlist || []).map(t => markRaw(t));
+    const ids = tracks.map(t => t.id);
+    const startIndex = Math.max(0, ids.indexOf(state.currentTrack?.id));
+    await updateQueue(async () => (await setQueue(ids, startIndex)).queue, () => {
+      state.playlist = tracks;
       state.currentIndex = getTrackIndex(state.currentTrack);
-    }
+    });
     perf.end('PlayerStore.setPlaylist');
   },
 
   /**
-   * 添加歌曲到播放列表
+   * 添加歌曲到播放列表（追加到后端队列队尾）
    * @param {Track} track - 歌曲
    */
-  addToPlaylist(track) {
+  async addToPlaylist(track) {
     perf.start('PlayerStore.addToPlaylist');
     if (!track) { perf.end('PlayerStore.addToPlaylist'); return; }
 
     // 检查是否已存在
     const exists = state.playlist.some(t => t.id === track.id);
     if (!exists) {
-      state.playlist.push(markRaw(track));
+      await updateQueue(() => addToQueue([track.id]), () => {
+        state.playlist.push(markRaw(track));
+      });
     }
     perf.end('PlayerStore.addToPlaylist');
   },
 
   /**
-   * 从播放列表移除歌曲
-   * @param {string} trackId - 歌曲 ID
+   * 批量添加歌曲到播放列表（多选），已存在的歌曲跳过
+   * @param {Track[]} tracks - 歌曲
    */
-  removeFromPlaylist(trackId) {
-    const index = state.playlist.findIndex(t => t.id === trackId);
-    if (index !== -1) {
-      state.playlist.splice(index, 1);
-
-      // 更新当前索引
-      if (state.currentTrack) {
-        state.currentIndex = getTrackIndex(state.currentTrack);
+  async addManyToPlaylist(tracks) {
+    const existing = new Set(state.playlist.map(t => t.id));
+    const added = [];
+    for (const track of tracks || []) {
+      if (track && !existing.has(track.id)) {
+        existing.add(track.id);
+        added.push(markRaw(track));
       }
     }
+    if (added.length === 0) return;
+    await updateQueue(() => addToQueue(added.map(t => t.id)), () => {
+      state.playlist.push(...added);
+    });
+  },
+
+  /**
+   * 激活（双击）一首歌：由后端按设置决定替换队列、追加还是插到下一首，并直接修改后端队列
+   * @param {Track} track - 歌曲
+   * @param {object} context - 歌曲所在的列表，见 api/playback.js 的 activateTrack
+   */
+  async activateTrack(track, context) {
+    const { action, songs, startIndex } = await activateTrack(track.id, context);
+    await updateQueue(getQueue);
+    // 替换队列时从这首开始；没有在播放的歌曲时直接开始播放
+    if (action === ActivateAction.REPLACE) {
+      await actions.play(songs[startIndex]);
+    } else if (!state.currentTrack) {
+      await actions.play(songs[0]);
+    }
+  },
+
+  /**
+   * 从播放列表移除歌曲（不能移除正在播放的那一项）
+   * @param {string} trackId - 歌曲 ID
+   * @param {number} [index] - 同一首歌在队列中出现多次时指定移除哪一项
+   */
+  async removeFromPlaylist(trackId, index = state.playlist.findIndex(t => t.id === trackId)) {
+    if (index === -1 || state.playlist[index]?.id !== trackId) return;
+    await updateQueue(() => removeFromQueue(index));
   },
 
   /**
    * 清空播放列表
    */
-  clearPlaylist() {
-    state.playlist = [];
-    state.currentIndex = -1;
+  async clearPlaylist() {
+    await updateQueue(async () => (await setQueue([])).queue, () => {
+      state.playlist = [];
+      state.currentIndex = -1;
+    });
     if (state.isPlaying) {
       actions.stop();
     }
@@ -592,16 +989,38 @@ const actions = {
     }
     state.isPlaying = false;
     state.currentTime = 0;
+    state.nowPlaying = null;
+  },
+
+  /**
+   * 加载正在播放页数据；后端组装失败时退回单独加载歌词
+   * @param {Track} track - 歌曲
+   */
+  async loadNowPlaying(track) {
+    let bundle;
+    try {
+      bundle = await getNowPlayingBundle(track.id);
+    } catch (error) {
+      console.warn('加载正在播放数据失败:', error);
+      state.nowPlaying = null;
+      return actions.loadLyrics(track);
+    }
+    // 加载期间已切歌则丢弃
+    if (state.currentTrack?.id !== track.id) return;
+    state.nowPlaying = markRaw(bundle);
+    const lyricsInfo = await Song.lyricsInfoFromText(bundle.lyrics.text);
+    return actions.loadLyrics(track, lyricsInfo);
   },
 
   /**
    * 加载歌词
    * @param {Track} track - 歌曲
+   * @param {Object} [preloaded] - 已取得的歌词信息，缺省时从歌曲读取
    */
-  async loadLyrics(track) {
+  async loadLyrics(track, preloaded = null) {
     return perf.measureAsync('PlayerStore.loadLyrics', (async () => {
     try {
-      const lyricsInfo = await track.getLyricsInfo();
+      const lyricsInfo = preloaded ?? await track.getLyricsInfo();
 
       // 比较是否有变化，避免不必要的更新
       const hasChanged =
@@ -789,6 +1208,7 @@ const actions = {
     state.currentTrack = null;
     state.isPlaying = false;
     state.playlist = [];
+    state.hasHistory = false;
     state.currentIndex = -1;
     state.ui.isPlayerViewOpen = false;
     state.ui.isImmersive = false;
@@ -797,6 +1217,9 @@ const actions = {
 
 // 初始化
 initAudioElement();
+// 后端队列在页面重新加载后仍然保留，取回来显示
+updateQueue(getQueue);
+syncPlayMode();
 
 // 导出 PlayerStore
 export const PlayerStore = {